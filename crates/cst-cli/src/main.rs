@@ -0,0 +1,1701 @@
+//! CSTEngine IFC Viewer CLI
+//!
+//! A command-line tool to convert IFC files to HTML viewers or other formats.
+//!
+//! # Usage
+//!
+//! ```bash
+//! # Convert IFC to an HTML viewer (building.html next to the input)
+//! cst_viewer convert building.ifc
+//!
+//! # The output extension picks the format: .html, .obj, .stl or .png
+//! cst_viewer convert building.ifc building.obj
+//!
+//! # Only walls and slabs, millimetre model scaled to metres
+//! cst_viewer convert building.ifc walls.stl --include-types IfcWall,IfcSlab --unit-scale 0.001
+//!
+//! # Coarser, smaller export: drop faces under 5 mm, at most 500k triangles
+//! cst_viewer gltf building.ifc preview.glb --tolerance 5 --max-triangles 500000
+//!
+//! # One storey without furniture
+//! cst_viewer gltf building.ifc level3.glb --storey "Level 3" --exclude-types IfcFurnishingElement
+//!
+//! # Keep parsed geometry in building.ifc.cstcache; later runs on the
+//! # unchanged file skip parsing and tessellation
+//! cst_viewer gltf building.ifc --cache
+//!
+//! # Convert every IFC under models/ to GLB in parallel, mirroring the tree
+//! cst_viewer convert ./models/ --out ./web/ --format glb
+//!
+//! # Render a PNG preview from a saved camera view
+//! cst_viewer convert building.ifc entrance.png --size 800x600 --view views.json Entrance
+//!
+//! # Or from a preset direction: top, front, left, iso-ne, ...
+//! cst_viewer convert building.ifc plan.png --standard-view top
+//!
+//! # Show summary statistics
+//! cst_viewer summary building.ifc
+//!
+//! # Machine-readable statistics for dashboards and CI
+//! cst_viewer summary building.ifc --json > building.json
+//!
+//! # Export binary mesh data for the web viewer
+//! cst_viewer web building.ifc web_viewer
+//!
+//! # Serve the web viewer on http://localhost:3000 (exports first for .ifc)
+//! cst_viewer serve building.ifc --port 3000
+//!
+//! # REST API: POST IFC to /api/models, then fetch manifests, chunks and
+//! # element properties (see cst_server::api for the routes)
+//! cst_viewer api --port 8080
+//! curl --data-binary @building.ifc 'http://localhost:8080/api/models?name=building'
+//!
+//! # Export to binary glTF (.glb; use a .gltf path for embedded JSON)
+//! cst_viewer gltf building.ifc building.glb --meshopt
+//!
+//! # Check geometry (exit code 4 when problems are found)
+//! cst_viewer validate building.ifc
+//!
+//! # List elements by type, storey and property
+//! cst_viewer query building.ifc --type IfcWall --storey "Level 2" --property FireRating=REI120
+//!
+//! # Area and volume of an element, the clearance between two, or the
+//! # distance from a point; --json for QA scripts
+//! cst_viewer measure building.ifc '#1234'
+//! cst_viewer measure building.ifc '#1234' 2O2Fr$t4X7Zf8NOew3FLOH --json
+//! cst_viewer measure building.ifc '#1234' --point 10,2.5,0
+//!
+//! # Floor plan per storey, cut 1.2 m above the floor (model in mm)
+//! cst_viewer plan building.ifc plans/ --cut-height 1200 --format dxf
+//! ```
+//!
+//! Progress and diagnostics go to stderr: `-q` shows only errors, `-v` adds
+//! timings and statistics, `-vv` parser progress, and `--log-format json`
+//! writes one JSON object per line for log collectors. On a terminal, long
+//! conversions show a progress bar with ETA (`--no-progress` turns it off).
+//!
+//! Exit codes: 0 on success, 1 when reading or exporting fails (for a
+//! directory, when any file fails), 2 for invalid arguments, 3 when the
+//! input file is missing and 4 when `validate` finds problems.
+
+use std::io::IsTerminal;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+use clap::{ArgAction, ArgGroup, Args, Parser, Subcommand, ValueEnum};
+use log::{debug, error, info, warn, LevelFilter};
+use rayon::prelude::*;
+use cst_ifc::ifc_options::IfcPipelineOptions;
+use cst_ifc::ifc_progress::{ProgressSink, ProgressStage};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+
+/// Reading or exporting failed
+const EXIT_FAILURE: i32 = 1;
+/// The input file does not exist (2 is taken by clap for usage errors)
+const EXIT_NO_INPUT: i32 = 3;
+/// `validate` found geometry problems
+const EXIT_INVALID: i32 = 4;
+
+#[derive(Parser)]
+#[command(name = "cst_viewer", version, about = "CSTEngine IFC Viewer CLI")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+    /// Only log errors
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Log more: -v for timings and statistics, -vv for parser progress
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    verbose: u8,
+    /// Format of log lines on stderr
+    #[arg(long, global = true, value_enum, default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+    /// Do not draw progress bars
+    #[arg(long, global = true)]
+    no_progress: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum LogFormat {
+    Text,
+    Json,
+}
+
+/// Writes `log` records from the CLI and the cst crates to stderr
+struct CliLogger {
+    format: LogFormat,
+}
+
+impl log::Log for CliLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        // Other crates, e.g. the GPU layers probing for adapters, only
+        // with -vv
+        let ours = metadata.target().starts_with("cst");
+        metadata.level() <= log::max_level() && (ours || log::max_level() == LevelFilter::Trace)
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        match self.format {
+            LogFormat::Text => {
+                let line = if record.level() <= log::Level::Warn {
+                    format!("{}: {}", record.level().as_str().to_ascii_lowercase(), record.args())
+                } else {
+                    record.args().to_string()
+                };
+                // Print above the progress bar instead of through it
+                match PROGRESS.get() {
+                    Some(bars) => bars.suspend(|| eprintln!("{}", line)),
+                    None => eprintln!("{}", line),
+                }
+            }
+            LogFormat::Json => {
+                let time = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0.0, |d| d.as_secs_f64());
+                let line = serde_json::json!({
+                    "time": time,
+                    "level": record.level().as_str(),
+                    "target": record.target(),
+                    "message": record.args().to_string(),
+                });
+                eprintln!("{}", line);
+            }
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+fn init_logging(cli: &Cli) {
+    let level = match (cli.quiet, cli.verbose) {
+        (true, _) => LevelFilter::Error,
+        (false, 0) => LevelFilter::Info,
+        (false, 1) => LevelFilter::Debug,
+        (false, _) => LevelFilter::Trace,
+    };
+    let logger = Box::leak(Box::new(CliLogger { format: cli.log_format }));
+    if log::set_logger(logger).is_ok() {
+        log::set_max_level(level);
+    }
+
+    // Bars only make sense for a person watching a text log
+    let show_progress = !cli.quiet
+        && !cli.no_progress
+        && cli.log_format == LogFormat::Text
+        && std::io::stderr().is_terminal();
+    if show_progress {
+        PROGRESS.get_or_init(MultiProgress::new);
+    }
+}
+
+/// Progress bars, when enabled by [`init_logging`]
+static PROGRESS: OnceLock<MultiProgress> = OnceLock::new();
+
+/// One progress bar with ETA per pipeline stage; a no-op when bars are
+/// disabled
+#[derive(Default)]
+struct CliProgress {
+    bar: Mutex<Option<ProgressBar>>,
+}
+
+impl ProgressSink for CliProgress {
+    fn start(&self, stage: ProgressStage, total: u64) {
+        let Some(bars) = PROGRESS.get() else {
+            return;
+        };
+        let (label, units) = match stage {
+            ProgressStage::Parse => ("Parsing", "{bytes}/{total_bytes}"),
+            ProgressStage::Resolve => ("Resolving", "{pos}/{len} products"),
+            ProgressStage::Tessellate => ("Tessellating", "{pos}/{len} meshes"),
+        };
+        let template = format!("{{msg:12}} [{{bar:40}}] {} ({{eta}} left)", units);
+        let style = ProgressStyle::with_template(&template)
+            .unwrap_or_else(|_| ProgressStyle::default_bar())
+            .progress_chars("=> ");
+        let bar = bars.add(ProgressBar::new(total).with_style(style).with_message(label));
+        if let Some(previous) = self.bar.lock().unwrap().replace(bar) {
+            previous.finish_and_clear();
+        }
+    }
+
+    fn advance(&self, _stage: ProgressStage, units: u64) {
+        if let Some(bar) = self.bar.lock().unwrap().as_ref() {
+            bar.inc(units);
+        }
+    }
+
+    fn finish(&self, _stage: ProgressStage) {
+        if let Some(bar) = self.bar.lock().unwrap().take() {
+            bar.finish_and_clear();
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Convert an IFC file, or every IFC file in a directory tree, to an
+    /// HTML viewer, OBJ, STL, GLB or PNG preview
+    Convert {
+        /// Path to the input IFC file or directory
+        input: PathBuf,
+        /// Output path (defaults to the input with the format's extension)
+        output: Option<PathBuf>,
+        /// Output directory; directory inputs keep their relative layout
+        #[arg(long, value_name = "DIR", conflicts_with = "output")]
+        out: Option<PathBuf>,
+        /// Output format; inferred from the output extension when omitted
+        #[arg(long, value_enum)]
+        format: Option<Format>,
+        /// PNG size as WIDTHxHEIGHT
+        #[arg(long, value_parser = parse_size, default_value = "512x512")]
+        size: (u32, u32),
+        /// PNG only: render from a named view in a views file
+        #[arg(long, num_args = 2, value_names = ["VIEWS", "NAME"])]
+        view: Option<Vec<String>>,
+        /// PNG only: preset direction framed on the model (top, bottom,
+        /// front, back, left, right, iso-ne, iso-nw, iso-se, iso-sw)
+        #[arg(long, value_parser = parse_standard_view, default_value = "iso-se", conflicts_with = "view")]
+        standard_view: cst_render::StandardView,
+        /// HTML only: outline boundaries and creases sharper than ANGLE
+        /// degrees (30 when given without a value)
+        #[arg(long, value_name = "ANGLE", num_args = 0..=1, default_missing_value = "30")]
+        edges: Option<f64>,
+        #[command(flatten)]
+        pipeline: PipelineArgs,
+    },
+    /// Print statistics about an IFC file
+    Summary {
+        /// Path to the input IFC file
+        input: PathBuf,
+        /// Print JSON (entity counts, storeys, representations, bounds, units)
+        #[arg(long)]
+        json: bool,
+    },
+    /// Export the web viewer page, binary mesh data and loader script
+    Web {
+        /// Path to the input IFC file
+        input: PathBuf,
+        /// Output directory
+        #[arg(default_value = "web_viewer")]
+        out_dir: PathBuf,
+        #[command(flatten)]
+        pipeline: PipelineArgs,
+    },
+    /// Serve a web export over HTTP, exporting an IFC input first
+    Serve {
+        /// Web export directory, or an IFC file to export
+        input: PathBuf,
+        /// Port to listen on
+        #[arg(long, default_value_t = 3000)]
+        port: u16,
+        /// Address to listen on; use 0.0.0.0 to allow other machines
+        #[arg(long, default_value = "127.0.0.1")]
+        bind: String,
+        /// Export directory for an IFC input (defaults to NAME_web next to it)
+        #[arg(long, value_name = "DIR")]
+        out: Option<PathBuf>,
+        #[command(flatten)]
+        pipeline: PipelineArgs,
+    },
+    /// Run the REST conversion API for web frontends
+    Api {
+        /// IFC files to load at startup
+        models: Vec<PathBuf>,
+        /// Port to listen on
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+        /// Address to listen on; use 0.0.0.0 to allow other machines
+        #[arg(long, default_value = "127.0.0.1")]
+        bind: String,
+        /// Largest accepted upload in MiB
+        #[arg(long, value_name = "MIB", default_value_t = 512)]
+        max_upload: u64,
+        #[command(flatten)]
+        pipeline: PipelineArgs,
+    },
+    /// Export to glTF (GLB unless the output ends in .gltf)
+    Gltf {
+        /// Path to the input IFC file
+        input: PathBuf,
+        /// Output path (defaults to the input with a .glb extension)
+        output: Option<PathBuf>,
+        /// Compress geometry with EXT_meshopt_compression
+        #[arg(long)]
+        meshopt: bool,
+        #[command(flatten)]
+        pipeline: PipelineArgs,
+    },
+    /// Report missing entities, bad faces, open shells and unsupported items
+    Validate {
+        /// Path to the input IFC file
+        input: PathBuf,
+        /// Largest allowed distance of a face vertex from the face plane
+        #[arg(long, default_value_t = 1e-3)]
+        planarity_tolerance: f64,
+    },
+    /// List element ids matching all given filters
+    #[command(group(ArgGroup::new("filter").required(true).multiple(true)))]
+    Query {
+        /// Path to the input IFC file
+        input: PathBuf,
+        /// IFC product type, e.g. IfcWall
+        #[arg(long = "type", group = "filter")]
+        ifc_type: Option<String>,
+        /// Name of the containing storey
+        #[arg(long, group = "filter")]
+        storey: Option<String>,
+        /// Property value as NAME=VALUE
+        #[arg(long, group = "filter", value_parser = parse_property)]
+        property: Option<(String, String)>,
+    },
+    /// Measure one element, the minimum distance between two, or the
+    /// distance from a point to an element
+    Measure {
+        /// Path to the input IFC file
+        input: PathBuf,
+        /// Element ids (#123) or GlobalIds
+        #[arg(required = true, num_args = 1..=2)]
+        elements: Vec<String>,
+        /// Distance from this point, as X,Y,Z in output units
+        #[arg(long, value_parser = parse_point)]
+        point: Option<cst_math::DVec3>,
+        /// Print JSON
+        #[arg(long)]
+        json: bool,
+        #[command(flatten)]
+        pipeline: PipelineArgs,
+    },
+    /// Export a 2D floor plan of every storey
+    Plan {
+        /// Path to the input IFC file
+        input: PathBuf,
+        /// Output directory (defaults to NAME_plans next to the input)
+        out_dir: Option<PathBuf>,
+        #[arg(long, value_enum, default_value_t = PlanFormat::Svg)]
+        format: PlanFormat,
+        /// Height of the cut above each storey, in model units (usually mm)
+        #[arg(long, default_value_t = 1200.0)]
+        cut_height: f64,
+        #[command(flatten)]
+        pipeline: PipelineArgs,
+    },
+    /// Export an element schedule as CSV
+    ExportCsv {
+        /// Path to the input IFC file
+        input: PathBuf,
+        /// Output file (defaults to stdout)
+        output: Option<PathBuf>,
+        /// Add a column per property and quantity, named Set.Property
+        #[arg(long)]
+        psets: bool,
+        /// Only elements of this IFC product type, e.g. IfcWall
+        #[arg(long = "type")]
+        ifc_type: Option<String>,
+        /// Only elements in this storey
+        #[arg(long)]
+        storey: Option<String>,
+    },
+    /// Dump entities from the data section as JSON, references kept as ids
+    Dump {
+        /// Path to the input IFC file
+        input: PathBuf,
+        /// Output file (defaults to stdout)
+        output: Option<PathBuf>,
+        /// Only these entity ids, e.g. #12,#40
+        #[arg(long = "id", value_name = "IDS", value_delimiter = ',', value_parser = parse_entity_id)]
+        ids: Vec<cst_core::StepId>,
+        /// Only these entity types, e.g. IfcWall,IfcSlab
+        #[arg(long = "type", value_name = "IFC_TYPES", value_delimiter = ',')]
+        types: Vec<String>,
+        /// Also dump everything the selected entities reference
+        #[arg(long)]
+        follow: bool,
+        /// Indent the JSON
+        #[arg(long)]
+        pretty: bool,
+    },
+    /// Sample a point cloud with normals and element ids, for comparison
+    /// against laser scans
+    Points {
+        /// Path to the input IFC file
+        input: PathBuf,
+        /// Output .ply or .xyz file (defaults to the input with .ply)
+        output: Option<PathBuf>,
+        /// Average point spacing, in model units (usually mm)
+        #[arg(long, default_value_t = 10.0)]
+        spacing: f64,
+        /// Seed of the sampling sequence
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        #[command(flatten)]
+        pipeline: PipelineArgs,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum PlanFormat {
+    Svg,
+    Dxf,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum Format {
+    Html,
+    Obj,
+    Stl,
+    Glb,
+    Png,
+}
+
+impl Format {
+    fn from_path(path: &Path) -> Option<Format> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "html" | "htm" => Some(Format::Html),
+            "obj" => Some(Format::Obj),
+            "stl" => Some(Format::Stl),
+            "glb" | "gltf" => Some(Format::Glb),
+            "png" => Some(Format::Png),
+            _ => None,
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Html => "html",
+            Format::Obj => "obj",
+            Format::Stl => "stl",
+            Format::Glb => "glb",
+            Format::Png => "png",
+        }
+    }
+}
+
+/// Flags that map onto [`IfcPipelineOptions`]
+#[derive(Args)]
+struct PipelineArgs {
+    /// Only convert these product types, e.g. IfcWall,IfcSlab
+    #[arg(long, value_name = "IFC_TYPES", value_delimiter = ',')]
+    include_types: Vec<String>,
+    /// Skip these product types, e.g. IfcFurnishingElement
+    #[arg(long, value_name = "IFC_TYPES", value_delimiter = ',')]
+    exclude_types: Vec<String>,
+    /// Only convert elements contained in the named storey
+    #[arg(long)]
+    storey: Option<String>,
+    /// Scale all coordinates, overriding the model's length unit
+    #[arg(long)]
+    unit_scale: Option<f64>,
+    /// Give every element its own mesh instead of drawing repeated
+    /// placements of a representation map as one instanced group; floor
+    /// plans never instance
+    #[arg(long)]
+    no_instancing: bool,
+    /// Draw unstyled elements in one gray instead of a color per type
+    #[arg(long)]
+    no_type_colors: bool,
+    /// Drop faces smaller than this, in model units (usually mm)
+    #[arg(long, default_value_t = 0.0)]
+    tolerance: f64,
+    /// Maximum number of triangles to export
+    #[arg(long)]
+    max_triangles: Option<usize>,
+    /// Reuse parsed geometry from NAME.ifc.cstcache, creating it if needed
+    #[arg(long)]
+    cache: bool,
+}
+
+impl PipelineArgs {
+    fn options(&self) -> IfcPipelineOptions {
+        IfcPipelineOptions {
+            type_filter: (!self.include_types.is_empty()).then(|| self.include_types.clone()),
+            exclude_types: self.exclude_types.clone(),
+            storey: self.storey.clone(),
+            unit_scale: self.unit_scale,
+            instancing: !self.no_instancing,
+            type_colors: if self.no_type_colors {
+                Vec::new()
+            } else {
+                IfcPipelineOptions::default().type_colors
+            },
+            tessellation_tolerance: self.tolerance,
+            triangle_budget: self.max_triangles,
+            cache: self.cache,
+            ..Default::default()
+        }
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    init_logging(&cli);
+
+    match cli.command {
+        Command::Convert { input, output, out, format, size, view, standard_view, edges, pipeline } => {
+            require_input(&input);
+            let format = format
+                .or_else(|| output.as_deref().and_then(Format::from_path))
+                .unwrap_or(Format::Html);
+            let options = pipeline.options();
+            let view = view.map(|v| load_view(Path::new(&v[0]), &v[1]));
+            let html = cst_render::HtmlExportOptions {
+                edge_overlay: edges.map(f64::to_radians),
+                ..Default::default()
+            };
+            if input.is_dir() {
+                let out_dir = out.unwrap_or_else(|| input.clone());
+                let preview = (standard_view, view.as_ref());
+                handle_batch_convert(&input, &out_dir, format, size, preview, &html, &options);
+                return;
+            }
+            let output = match (output, out) {
+                (Some(output), _) => output,
+                (None, Some(out_dir)) => {
+                    let name = input.file_name().map(Path::new).unwrap_or(&input);
+                    out_dir.join(name.with_extension(format.extension()))
+                }
+                (None, None) => input.with_extension(format.extension()),
+            };
+            match format {
+                Format::Html => handle_html_export(&input, &output, &html, &options),
+                Format::Obj => handle_obj_export(&input, &output, &options),
+                Format::Stl => handle_stl_export(&input, &output, &options),
+                Format::Glb => handle_gltf_export(&input, &output, false, &options),
+                Format::Png => {
+                    let preview = (standard_view, view.as_ref());
+                    handle_thumbnail(&input, &output, size, preview, &options)
+                }
+            }
+        }
+        Command::Summary { input, json } => {
+            require_input(&input);
+            if json {
+                handle_json_summary(&input);
+            } else {
+                handle_summary(&input);
+            }
+        }
+        Command::Web { input, out_dir, pipeline } => {
+            require_input(&input);
+            handle_web_export(&input, &out_dir, &pipeline.options());
+        }
+        Command::Serve { input, port, bind, out, pipeline } => {
+            require_input(&input);
+            let root = if input.is_dir() {
+                input
+            } else {
+                let out_dir = out.unwrap_or_else(|| {
+                    let stem = input.file_stem().unwrap_or_default().to_string_lossy();
+                    input.with_file_name(format!("{}_web", stem))
+                });
+                if export_is_stale(&input, &out_dir) {
+                    handle_web_export(&input, &out_dir, &pipeline.options());
+                } else {
+                    info!("Using existing export in {}", out_dir.display());
+                }
+                out_dir
+            };
+            handle_serve(&root, &bind, port);
+        }
+        Command::Api { models, port, bind, max_upload, pipeline } => {
+            models.iter().for_each(|path| require_input(path));
+            handle_api(&models, &bind, port, max_upload, pipeline.options());
+        }
+        Command::Gltf { input, output, meshopt, pipeline } => {
+            require_input(&input);
+            let output = output.unwrap_or_else(|| input.with_extension("glb"));
+            handle_gltf_export(&input, &output, meshopt, &pipeline.options());
+        }
+        Command::Validate { input, planarity_tolerance } => {
+            require_input(&input);
+            handle_validate(&input, planarity_tolerance);
+        }
+        Command::Query { input, ifc_type, storey, property } => {
+            require_input(&input);
+            handle_query(&input, ifc_type.as_deref(), storey.as_deref(), property.as_ref());
+        }
+        Command::Measure { input, elements, point, json, pipeline } => {
+            require_input(&input);
+            if point.is_some() && elements.len() > 1 {
+                error!("--point measures from a single element");
+                process::exit(2);
+            }
+            handle_measure(&input, &elements, point, json, &pipeline.options());
+        }
+        Command::Plan { input, out_dir, format, cut_height, pipeline } => {
+            require_input(&input);
+            let out_dir = out_dir.unwrap_or_else(|| {
+                let stem = input.file_stem().unwrap_or_default().to_string_lossy();
+                input.with_file_name(format!("{}_plans", stem))
+            });
+            handle_floor_plans(&input, &out_dir, format, cut_height, &pipeline.options());
+        }
+        Command::ExportCsv { input, output, psets, ifc_type, storey } => {
+            require_input(&input);
+            handle_csv_export(&input, output.as_deref(), psets, ifc_type.as_deref(), storey.as_deref());
+        }
+        Command::Dump { input, output, ids, types, follow, pretty } => {
+            require_input(&input);
+            let options = cst_ifc::ifc_json::JsonDumpOptions { ids, types, follow_references: follow };
+            handle_json_dump(&input, output.as_deref(), &options, pretty);
+        }
+        Command::Points { input, output, spacing, seed, pipeline } => {
+            require_input(&input);
+            let output = output.unwrap_or_else(|| input.with_extension("ply"));
+            handle_point_cloud(&input, &output, spacing, seed, &pipeline.options());
+        }
+    }
+}
+
+/// An entity id, with or without the leading `#`
+fn parse_entity_id(text: &str) -> Result<cst_core::StepId, String> {
+    text.trim_start_matches('#')
+        .parse()
+        .map(cst_core::StepId)
+        .map_err(|_| format!("invalid entity id: {}", text))
+}
+
+fn require_input(ifc_path: &Path) {
+    if !ifc_path.exists() {
+        error!("Input file does not exist: {}", ifc_path.display());
+        process::exit(EXIT_NO_INPUT);
+    }
+}
+
+fn handle_html_export(
+    ifc_path: &Path,
+    html_path: &Path,
+    html: &cst_render::HtmlExportOptions,
+    options: &IfcPipelineOptions,
+) {
+    info!("Reading IFC file: {}", ifc_path.display());
+
+    let scene = load_scene(ifc_path, options);
+    match scene.export_html_with_options(html_path, html) {
+        Ok(()) => {
+            info!("✓ Conversion successful!");
+            info!("Exported HTML viewer: {}", html_path.display());
+            info!("Open the HTML file in a web browser to view the 3D model.");
+        }
+        Err(e) => {
+            error!("Conversion failed: {}", e);
+            process::exit(EXIT_FAILURE);
+        }
+    }
+}
+
+fn handle_summary(ifc_path: &Path) {
+    let summary = cst_ifc::ifc_summary::summarize_ifc(ifc_path).unwrap_or_else(|e| {
+        error!("Failed to generate summary: {}", e);
+        process::exit(EXIT_FAILURE);
+    });
+    println!("{}", format_summary(&summary));
+}
+
+/// The statistics of `summary` as a few human-readable lines
+fn format_summary(summary: &cst_ifc::ifc_summary::IfcSummary) -> String {
+    let mut lines = vec![
+        format!("Schema:   {}", summary.schema.as_deref().unwrap_or("unknown")),
+        format!("Entities: {} of {} types", summary.entity_count, summary.entity_types.len()),
+        format!("Elements: {}", summary.element_count),
+    ];
+    if let Some(bounds) = &summary.bounds {
+        let size = bounds.max - bounds.min;
+        lines.push(format!("Size:     {:.3} x {:.3} x {:.3}", size.x, size.y, size.z));
+    }
+    if !summary.storeys.is_empty() {
+        lines.push("Storeys:".to_string());
+        for storey in &summary.storeys {
+            lines.push(format!("  {} ({} elements)", storey.name, storey.element_count));
+        }
+    }
+    if !summary.representations.is_empty() {
+        lines.push("Representations:".to_string());
+        for (kind, count) in &summary.representations {
+            lines.push(format!("  {}: {}", kind, count));
+        }
+    }
+    lines.join("\n")
+}
+
+fn handle_json_summary(ifc_path: &Path) {
+    match cst_ifc::ifc_summary::summarize_ifc(ifc_path) {
+        Ok(summary) => println!("{}", summary.to_json()),
+        Err(e) => {
+            error!("Failed to generate summary: {}", e);
+            process::exit(EXIT_FAILURE);
+        }
+    }
+}
+
+fn handle_web_export(ifc_path: &Path, out_dir: &Path, options: &IfcPipelineOptions) {
+    info!("Reading IFC file: {}", ifc_path.display());
+
+
+    // Create output directory
+    if !out_dir.exists() {
+        std::fs::create_dir_all(out_dir).unwrap_or_else(|e| {
+            error!("Failed to create directory: {}", e);
+            process::exit(EXIT_FAILURE);
+        });
+    }
+
+    // Build the scene with geometry instancing; the triangle budget is
+    // shared out between colours below rather than in file order
+    let model = load_model(ifc_path, options);
+    let scene_options = IfcPipelineOptions {
+        triangle_budget: None,
+        ..options.clone()
+    };
+    let mut scene = cst_render::model_scene(&model, &scene_options);
+    let meshes = std::mem::take(&mut scene.meshes);
+    // The tree refers to the meshes merged into batches below
+    scene.spatial_tree = None;
+    let mut total_tris = 0usize;
+    let max_tris = options.triangle_budget.unwrap_or(usize::MAX);
+
+    // Groups live in ordered maps so the scene, and every export
+    // built from it, comes out the same on every run
+    use std::collections::BTreeMap;
+    let color_key = |color: [f32; 3]| color.map(|c| (c * 255.0) as u8);
+
+    let instanced_tris: usize = scene
+        .instanced_groups
+        .iter()
+        .map(|group| group.mesh.triangle_count())
+        .sum();
+    let instanced_total_drawn: usize = scene
+        .instanced_groups
+        .iter()
+        .map(|group| group.mesh.triangle_count() * group.transforms.len())
+        .sum();
+    let instanced_count: usize = scene.instanced_groups.iter().map(|group| group.transforms.len()).sum();
+    debug!("Instancing: {} groups ({} meshes → {} base geometries, {} instanced tris drawn as {})",
+        scene.instanced_groups.len(),
+        instanced_count,
+        scene.instanced_groups.len(),
+        instanced_tris,
+        instanced_total_drawn);
+
+    // --- Budget allocation for regular (non-instanced) meshes ---
+    let regular_count = meshes.len();
+    let mut all_color_groups: BTreeMap<[u8; 3], Vec<(usize, usize)>> = BTreeMap::new();
+    for (idx, scene_mesh) in meshes.iter().enumerate() {
+        all_color_groups
+            .entry(color_key(scene_mesh.material.base_color))
+            .or_default()
+            .push((idx, scene_mesh.mesh.triangle_count()));
+    }
+    for group in all_color_groups.values_mut() {
+        group.sort_by_key(|&(_, tris)| std::cmp::Reverse(tris));
+    }
+
+    // Remaining budget for regular meshes (instanced already counted)
+    let regular_budget = max_tris.saturating_sub(instanced_tris);
+
+    // Step 2: Compute group stats
+    let num_groups = all_color_groups.len();
+    let grand_total: usize = all_color_groups.values()
+        .flat_map(|g| g.iter()).map(|(_, t)| t).sum();
+
+    // Step 3: Balanced allocation
+    let max_share_pct = 0.35;
+    let min_share = regular_budget / (num_groups * 2).max(1);
+
+    let mut group_shares: Vec<([u8; 3], usize, usize)> = Vec::new();
+    let mut capped_total = 0usize;
+    let mut uncapped_total = 0usize;
+
+    for (key, group) in &all_color_groups {
+        let group_total: usize = group.iter().map(|(_, t)| *t).sum();
+        let raw_share = if grand_total > 0 {
+            ((group_total as f64 / grand_total as f64) * regular_budget as f64) as usize
+        } else { 0 };
+        let max_cap = (regular_budget as f64 * max_share_pct) as usize;
+        if raw_share > max_cap {
+            group_shares.push((*key, max_cap, group_total));
+            capped_total += max_cap;
+        } else {
+            group_shares.push((*key, raw_share, group_total));
+            uncapped_total += raw_share;
+        }
+    }
+
+    let excess = regular_budget.saturating_sub(capped_total + uncapped_total);
+    let mut budget_indices = Vec::new();
+
+    for (key, base_share, _group_total) in &group_shares {
+        let mut share = *base_share;
+        if share < (regular_budget as f64 * max_share_pct) as usize && uncapped_total > 0 {
+            share += ((share as f64 / uncapped_total as f64) * excess as f64) as usize;
+        }
+        let share = share.max(min_share);
+
+        let group = &all_color_groups[key];
+        let mut used = 0usize;
+        for (idx, tris) in group {
+            if used + tris > share && used > 0 {
+                break;
+            }
+            used += tris;
+            budget_indices.push(*idx);
+        }
+        total_tris += used;
+    }
+
+    debug!("Regular meshes: {} of {} using {} tris (budget {})",
+        budget_indices.len(), regular_count, total_tris, regular_budget);
+    debug!("Total display: {} regular tris + {} instanced drawn = {} effective tris",
+        total_tris, instanced_total_drawn, total_tris + instanced_total_drawn);
+
+    // Group budget meshes by color for batch merge
+    let mut color_groups: BTreeMap<[u8; 3], Vec<usize>> = BTreeMap::new();
+    for &idx in &budget_indices {
+        let key = color_key(meshes[idx].material.base_color);
+        color_groups.entry(key).or_default().push(idx);
+    }
+
+    // Merge each color group into batches
+    for (color_key, group_indices) in &color_groups {
+        let color = [
+            color_key[0] as f32 / 255.0,
+            color_key[1] as f32 / 255.0,
+            color_key[2] as f32 / 255.0,
+        ];
+        let max_per_batch = (group_indices.len() + options.max_batches - 1).max(1);
+        let sub_batch_size = (group_indices.len() / ((group_indices.len() / max_per_batch).max(1))).max(1);
+        for (bi, chunk) in group_indices.chunks(sub_batch_size).enumerate() {
+            let mut positions = Vec::new();
+            let mut normals = Vec::new();
+            let mut indices = Vec::new();
+            let mut offset = 0u32;
+            for &idx in chunk {
+                let m = &meshes[idx].mesh;
+                positions.extend_from_slice(&m.positions);
+                normals.extend_from_slice(&m.normals);
+                for &i in &m.indices {
+                    indices.push(i + offset);
+                }
+                offset += m.positions.len() as u32;
+            }
+            let merged = cst_mesh::TriangleMesh {
+                positions, normals, indices, uvs: vec![],
+            };
+            scene.add_mesh(
+                &format!("Color_{:02x}{:02x}{:02x}_{}", color_key[0], color_key[1], color_key[2], bi),
+                merged,
+                color,
+            );
+        }
+    }
+
+    // Export binary mesh data in the chunked layout so the viewer
+    // can draw the largest elements before the download finishes,
+    // with compact normals for smooth shading
+    let bin_path = out_dir.join("mesh.bin");
+    let options = cst_render::BinaryMeshOptions {
+        chunked: true,
+        normals: Some(cst_render::NormalEncoding::Octahedral),
+        eye: None,
+    };
+    match scene.export_binary_mesh_with_options(&bin_path, &options) {
+        Ok(()) => {
+            let size = std::fs::metadata(&bin_path).map(|m| m.len()).unwrap_or(0);
+            info!("Exported mesh.bin: {} bytes ({:.1} MB)",
+                size, size as f64 / 1_048_576.0);
+        }
+        Err(e) => {
+            error!("Failed to export binary mesh: {}", e);
+            process::exit(EXIT_FAILURE);
+        }
+    }
+
+    // Viewer page and the streaming reader it loads mesh.bin with
+    let files = [
+        ("index.html", cst_render::WEB_VIEWER_HTML),
+        ("mesh_reader.js", cst_render::MESH_READER_JS),
+    ];
+    for (name, contents) in files {
+        let path = out_dir.join(name);
+        if let Err(e) = std::fs::write(&path, contents) {
+            error!("Failed to write {}: {}", path.display(), e);
+            process::exit(EXIT_FAILURE);
+        }
+    }
+
+    info!("✓ Web export complete! Files in: {}", out_dir.display());
+    info!("To start the viewer:");
+    info!("  cst_viewer serve {}", out_dir.display());
+    info!("  Then open http://localhost:3000");
+}
+
+/// Whether the web export in `out_dir` is missing or older than `ifc_path`
+fn export_is_stale(ifc_path: &Path, out_dir: &Path) -> bool {
+    let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    match (modified(ifc_path), modified(&out_dir.join("mesh.bin"))) {
+        _ if !out_dir.join("index.html").exists() => true,
+        (Some(ifc), Some(mesh)) => mesh < ifc,
+        (_, mesh) => mesh.is_none(),
+    }
+}
+
+fn handle_serve(root: &Path, bind: &str, port: u16) {
+    let listener = bind_listener(bind, port);
+    info!("Serving {} at http://{}:{}/ (Ctrl+C to stop)", root.display(), bind, port);
+    let root = root.to_path_buf();
+    if let Err(e) = cst_server::serve(listener, 0, move |request| cst_server::static_file(&root, request)) {
+        error!("Server failed: {}", e);
+        process::exit(EXIT_FAILURE);
+    }
+}
+
+fn handle_api(models: &[PathBuf], bind: &str, port: u16, max_upload_mb: u64, options: IfcPipelineOptions) {
+    let api = cst_server::ApiServer::new(options);
+    for path in models {
+        let name = path.file_stem().unwrap_or_default().to_string_lossy();
+        match api.add_model(&name, path) {
+            Ok(model) => info!(
+                "Loaded {} as model {} ({} elements)",
+                path.display(),
+                model.manifest.id,
+                model.manifest.element_count
+            ),
+            Err(e) => {
+                error!("Failed to load {}: {}", path.display(), e);
+                process::exit(EXIT_FAILURE);
+            }
+        }
+    }
+
+    let listener = bind_listener(bind, port);
+    info!("API listening on http://{}:{}/api/models (Ctrl+C to stop)", bind, port);
+    if let Err(e) = cst_server::serve(listener, max_upload_mb << 20, move |request| api.handle(request)) {
+        error!("Server failed: {}", e);
+        process::exit(EXIT_FAILURE);
+    }
+}
+
+fn bind_listener(bind: &str, port: u16) -> TcpListener {
+    TcpListener::bind((bind, port)).unwrap_or_else(|e| {
+        error!("Failed to listen on {}:{}: {}", bind, port, e);
+        process::exit(EXIT_FAILURE);
+    })
+}
+
+fn handle_gltf_export(ifc_path: &Path, gltf_path: &Path, meshopt: bool, options: &IfcPipelineOptions) {
+    info!("Reading IFC file: {}", ifc_path.display());
+
+
+    let scene = load_scene(ifc_path, options);
+
+    // GLB unless a .gltf (JSON with embedded base64 buffer) is asked for
+    let is_json = gltf_path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gltf"));
+    let gltf_options = cst_render::GltfExportOptions {
+        meshopt_compression: meshopt,
+    };
+    let result = if is_json {
+        std::fs::write(gltf_path, scene.export_gltf_json_with_options(&gltf_options))
+    } else {
+        scene.export_glb_with_options(gltf_path, &gltf_options)
+    };
+
+    match result {
+        Ok(()) => {
+            info!("✓ Export successful!");
+            info!("Exported glTF file: {}", gltf_path.display());
+        }
+        Err(e) => {
+            error!("Export failed: {}", e);
+            process::exit(EXIT_FAILURE);
+        }
+    }
+}
+
+fn handle_obj_export(ifc_path: &Path, obj_path: &Path, options: &IfcPipelineOptions) {
+    info!("Reading IFC file: {}", ifc_path.display());
+
+
+    let scene = load_scene(ifc_path, options);
+    match scene.export_obj(obj_path) {
+        Ok(()) => {
+            info!("✓ Export successful!");
+            info!("Exported OBJ file: {}", obj_path.display());
+            info!("Material library:  {}", obj_path.with_extension("mtl").display());
+        }
+        Err(e) => {
+            error!("Export failed: {}", e);
+            process::exit(EXIT_FAILURE);
+        }
+    }
+}
+
+fn handle_stl_export(ifc_path: &Path, stl_path: &Path, options: &IfcPipelineOptions) {
+    info!("Reading IFC file: {}", ifc_path.display());
+
+
+    let scene = load_scene(ifc_path, options);
+    match scene.export_stl(stl_path) {
+        Ok(()) => {
+            info!("✓ Export successful!");
+            info!("Exported STL file: {}", stl_path.display());
+        }
+        Err(e) => {
+            error!("Export failed: {}", e);
+            process::exit(EXIT_FAILURE);
+        }
+    }
+}
+
+/// Tessellate an IFC file into a scene with [`cst_render::model_scene`]
+fn load_scene(ifc_path: &Path, options: &IfcPipelineOptions) -> cst_render::Scene {
+    cst_render::ifc_to_scene_with_progress(ifc_path, options, &CliProgress::default()).unwrap_or_else(|e| {
+        error!("Failed to read IFC: {}", e);
+        process::exit(EXIT_FAILURE);
+    })
+}
+
+/// Find a named view in a views file saved with `cst_render::save_views`
+fn load_view(views_path: &Path, name: &str) -> cst_render::CameraView {
+    let views = cst_render::load_views(views_path).unwrap_or_else(|e| {
+        error!("Failed to read views: {}", e);
+        process::exit(EXIT_FAILURE);
+    });
+    views.into_iter().find(|view| view.name == name).unwrap_or_else(|| {
+        error!("No view named '{}' in {}", name, views_path.display());
+        process::exit(EXIT_FAILURE);
+    })
+}
+
+fn parse_size(spec: &str) -> Result<(u32, u32), String> {
+    let invalid = || format!("invalid size '{}', expected WIDTHxHEIGHT", spec);
+    let (w, h) = spec.split_once(['x', 'X']).ok_or_else(invalid)?;
+    let (w, h): (u32, u32) = (w.parse().map_err(|_| invalid())?, h.parse().map_err(|_| invalid())?);
+    if w > 0 && h > 0 {
+        Ok((w, h))
+    } else {
+        Err(invalid())
+    }
+}
+
+fn parse_standard_view(key: &str) -> Result<cst_render::StandardView, String> {
+    cst_render::StandardView::from_key(key).ok_or_else(|| {
+        let keys: Vec<&str> = cst_render::StandardView::ALL.iter().map(|v| v.key()).collect();
+        format!("unknown view '{}', expected one of {}", key, keys.join(", "))
+    })
+}
+
+fn parse_property(spec: &str) -> Result<(String, String), String> {
+    let (name, value) = spec
+        .split_once('=')
+        .ok_or_else(|| format!("invalid property '{}', expected NAME=VALUE", spec))?;
+    Ok((name.to_string(), value.to_string()))
+}
+
+fn parse_point(spec: &str) -> Result<cst_math::DVec3, String> {
+    let invalid = || format!("invalid point '{}', expected X,Y,Z", spec);
+    let coords: Vec<f64> = spec
+        .split(',')
+        .map(|c| c.trim().parse().map_err(|_| invalid()))
+        .collect::<Result<_, _>>()?;
+    match coords[..] {
+        [x, y, z] => Ok(cst_math::DVec3::new(x, y, z)),
+        _ => Err(invalid()),
+    }
+}
+
+fn handle_thumbnail(
+    ifc_path: &Path,
+    png_path: &Path,
+    (width, height): (u32, u32),
+    preview: Preview,
+    options: &IfcPipelineOptions,
+) {
+    info!("Reading IFC file: {}", ifc_path.display());
+
+
+    let scene = load_scene(ifc_path, options);
+    let camera = preview_camera(&scene, (width, height), preview);
+    let image = render_preview(&scene, &camera, (width, height));
+    match image.save_png(png_path) {
+        Ok(()) => info!("✓ Rendered {}x{} preview: {}", width, height, png_path.display()),
+        Err(e) => {
+            error!("Failed to write PNG: {}", e);
+            process::exit(EXIT_FAILURE);
+        }
+    }
+}
+
+/// Render a PNG preview on the GPU, or on the CPU when there is no adapter
+fn render_preview(
+    scene: &cst_render::Scene,
+    camera: &cst_render::Camera,
+    (width, height): (u32, u32),
+) -> cst_render::RgbaImage {
+    static RENDERER: OnceLock<Option<cst_render::OffscreenRenderer>> = OnceLock::new();
+    let renderer = RENDERER.get_or_init(|| match cst_render::OffscreenRenderer::new() {
+        Ok(renderer) => {
+            debug!("Rendering previews on {}", renderer.adapter_name());
+            Some(renderer)
+        }
+        Err(e) => {
+            debug!("{}; rendering previews on the CPU", e);
+            None
+        }
+    });
+    match renderer {
+        Some(renderer) => renderer.render(scene, camera, width, height),
+        None => scene.render_to_image(camera, width, height),
+    }
+}
+
+/// PNG preview camera: a standard view, unless a saved view is given
+type Preview<'a> = (cst_render::StandardView, Option<&'a cst_render::CameraView>);
+
+/// The standard view framed on the model, or the saved view
+fn preview_camera(
+    scene: &cst_render::Scene,
+    (width, height): (u32, u32),
+    (standard, view): Preview,
+) -> cst_render::Camera {
+    let aspect = width as f64 / height as f64;
+    let mut camera = match scene.bounds() {
+        Some(bounds) => cst_render::Camera::standard_view(standard, &bounds, aspect),
+        None => cst_render::Camera { aspect, ..Default::default() },
+    };
+    if let Some(view) = view {
+        view.apply(&mut camera);
+    }
+    camera
+}
+
+fn handle_batch_convert(
+    in_dir: &Path,
+    out_dir: &Path,
+    format: Format,
+    size: (u32, u32),
+    preview: Preview,
+    html: &cst_render::HtmlExportOptions,
+    options: &IfcPipelineOptions,
+) {
+    let mut inputs = Vec::new();
+    collect_ifc_files(in_dir, &mut inputs).unwrap_or_else(|e| {
+        error!("Failed to read directory {}: {}", in_dir.display(), e);
+        process::exit(EXIT_FAILURE);
+    });
+    inputs.sort();
+    info!("Converting {} IFC files from {}", inputs.len(), in_dir.display());
+
+    let started = Instant::now();
+    let results: Vec<(&PathBuf, Result<(), String>)> = inputs
+        .par_iter()
+        .map(|input| {
+            let relative = input.strip_prefix(in_dir).unwrap_or(input);
+            let output = out_dir.join(relative).with_extension(format.extension());
+            // A panic in one file must not take down the rest of the batch
+            let result = std::panic::catch_unwind(|| convert_file(input, &output, format, size, preview, html, options))
+                .unwrap_or_else(|_| Err("panicked during conversion".to_string()));
+            match &result {
+                Ok(()) => info!("✓ {}", relative.display()),
+                Err(e) => warn!("✗ {}: {}", relative.display(), e),
+            }
+            (input, result)
+        })
+        .collect();
+
+    let failed: Vec<_> = results.iter().filter(|(_, result)| result.is_err()).collect();
+    info!(
+        "Converted {} of {} files in {:.1}s",
+        results.len() - failed.len(),
+        results.len(),
+        started.elapsed().as_secs_f64()
+    );
+    for (input, result) in &failed {
+        if let Err(e) = result {
+            warn!("Failed: {}: {}", input.display(), e);
+        }
+    }
+    if !failed.is_empty() {
+        process::exit(EXIT_FAILURE);
+    }
+}
+
+/// All `.ifc` files below `dir`, recursively
+fn collect_ifc_files(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_ifc_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("ifc")) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Convert one file for batch mode, reporting errors instead of exiting
+fn convert_file(
+    input: &Path,
+    output: &Path,
+    format: Format,
+    size: (u32, u32),
+    preview: Preview,
+    html: &cst_render::HtmlExportOptions,
+    options: &IfcPipelineOptions,
+) -> Result<(), String> {
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    // Files convert in parallel, so per-stage bars would interleave
+    let load = || cst_render::ifc_to_scene(input, options).map_err(|e| e.to_string());
+    let result = match format {
+        Format::Html => load()?.export_html_with_options(output, html),
+        Format::Obj => load()?.export_obj(output),
+        Format::Stl => load()?.export_stl(output),
+        Format::Glb => load()?.export_glb_with_options(output, &Default::default()),
+        Format::Png => {
+            let scene = load()?;
+            let camera = preview_camera(&scene, size, preview);
+            render_preview(&scene, &camera, size).save_png(output)
+        }
+    };
+    result.map_err(|e| e.to_string())
+}
+
+fn handle_validate(ifc_path: &Path, planarity_tolerance: f64) {
+    let report = cst_ifc::ifc_validate::validate_ifc(ifc_path, planarity_tolerance).unwrap_or_else(|e| {
+        error!("Failed to read IFC: {}", e);
+        process::exit(EXIT_FAILURE);
+    });
+
+    for issue in &report.issues {
+        println!("[{}] {}: {}", issue.kind, issue.entity_id, issue.message);
+    }
+    for (kind, count) in report.counts() {
+        info!("{}: {}", kind, count);
+    }
+    info!(
+        "Checked {} elements and {} breps, {} issue(s)",
+        report.products_checked,
+        report.breps_checked,
+        report.issues.len()
+    );
+    if !report.is_clean() {
+        process::exit(EXIT_INVALID);
+    }
+}
+
+fn handle_query(
+    ifc_path: &Path,
+    ifc_type: Option<&str>,
+    storey: Option<&str>,
+    property: Option<&(String, String)>,
+) {
+    let query = cst_ifc::ifc_query::IfcQuery::open(ifc_path).unwrap_or_else(|e| {
+        error!("Failed to read IFC: {}", e);
+        process::exit(EXIT_FAILURE);
+    });
+
+    // Intersect the id lists of every given filter
+    let mut filters = Vec::new();
+    if let Some(ifc_type) = ifc_type {
+        filters.push(query.elements_of_type(ifc_type));
+    }
+    if let Some(storey) = storey {
+        filters.push(query.elements_in_storey(storey));
+    }
+    if let Some((name, value)) = property {
+        filters.push(query.elements_with_property(name, value));
+    }
+    let mut filters = filters.into_iter();
+    let mut ids = filters.next().unwrap_or_default();
+    for other in filters {
+        ids.retain(|id| other.binary_search(id).is_ok());
+    }
+
+    for id in &ids {
+        println!("{} {}", id, query.type_of(*id).unwrap_or("?"));
+    }
+    info!("{} matching elements", ids.len());
+}
+
+fn handle_measure(
+    ifc_path: &Path,
+    elements: &[String],
+    point: Option<cst_math::DVec3>,
+    json: bool,
+    options: &IfcPipelineOptions,
+) {
+    let model = load_model(ifc_path, options);
+    let measured: Vec<(cst_core::ProductId, cst_mesh::TriangleMesh)> =
+        elements.iter().map(|spec| element_mesh(&model, spec)).collect();
+    let label = |id: cst_core::ProductId| {
+        let product = &model.products[&id];
+        match &product.name {
+            Some(name) => format!("{} {} '{}'", id, product.ifc_type, name),
+            None => format!("{} {}", id, product.ifc_type),
+        }
+    };
+    let coords = |p: cst_math::DVec3| [p.x, p.y, p.z];
+
+    match (&measured[..], point) {
+        ([(id, mesh)], None) => {
+            let bounds = mesh.bounding_box();
+            let (area, volume) = (mesh.surface_area(), mesh.signed_volume().abs());
+            if json {
+                let report = serde_json::json!({
+                    "id": id,
+                    "type": model.products[id].ifc_type,
+                    "area": area,
+                    "volume": volume,
+                    "bounds": { "min": coords(bounds.min), "max": coords(bounds.max) },
+                });
+                println!("{}", report);
+            } else {
+                println!("{}", label(*id));
+                println!("Area:   {:.4}", area);
+                println!("Volume: {:.4}", volume);
+                println!("Bounds: {:.4?} .. {:.4?}", coords(bounds.min), coords(bounds.max));
+            }
+        }
+        ([(id, mesh)], Some(point)) => {
+            let distance = cst_render::measure::point_mesh_distance(point, mesh);
+            print_distance(distance, json, &format!("{:?}", coords(point)), &label(*id));
+        }
+        ([(a, a_mesh), (b, b_mesh)], _) => {
+            let distance = cst_render::measure::mesh_distance(a_mesh, b_mesh);
+            print_distance(distance, json, &label(*a), &label(*b));
+        }
+        _ => unreachable!("clap accepts one or two elements"),
+    }
+}
+
+/// Merged mesh of the element given as `#id`, `id` or GlobalId
+fn element_mesh(
+    model: &cst_ifc::ifc_cache::CachedModel,
+    spec: &str,
+) -> (cst_core::ProductId, cst_mesh::TriangleMesh) {
+    let id = spec
+        .trim_start_matches('#')
+        .parse()
+        .ok()
+        .map(cst_core::ProductId::new)
+        .filter(|id| model.products.contains_key(id))
+        .or_else(|| {
+            model
+                .products
+                .iter()
+                .find(|(_, product)| product.global_id.as_deref() == Some(spec))
+                .map(|(&id, _)| id)
+        })
+        .unwrap_or_else(|| {
+            error!("No element with geometry matches '{}'", spec);
+            process::exit(EXIT_FAILURE);
+        });
+
+    let mut mesh = cst_mesh::TriangleMesh::default();
+    for cached in model.meshes.iter().filter(|m| m.product == Some(id)) {
+        mesh.merge(&cst_mesh::TriangleMesh {
+            positions: cached.mesh.positions.clone(),
+            normals: cached.mesh.normals.clone(),
+            indices: cached.mesh.indices.clone(),
+            uvs: vec![],
+        });
+    }
+    (id, mesh)
+}
+
+fn print_distance(distance: Option<cst_render::Distance>, json: bool, from: &str, to: &str) {
+    let Some(distance) = distance else {
+        error!("Nothing to measure: an element has no triangles");
+        process::exit(EXIT_FAILURE);
+    };
+    if json {
+        println!("{}", serde_json::to_string(&distance).expect("distance serializes"));
+    } else {
+        println!("{} -> {}", from, to);
+        println!("Distance: {:.4}", distance.distance);
+        println!("Closest:  {:.4?} .. {:.4?}", distance.from.to_array(), distance.to.to_array());
+    }
+}
+
+fn handle_floor_plans(
+    ifc_path: &Path,
+    out_dir: &Path,
+    format: PlanFormat,
+    cut_height: f64,
+    options: &IfcPipelineOptions,
+) {
+    // Plans cut each element's own mesh, so nothing is instanced
+    let options = &IfcPipelineOptions {
+        instancing: false,
+        ..options.clone()
+    };
+    let scene = load_scene(ifc_path, options);
+    let plan_options = cst_render::FloorPlanOptions { cut_height: cut_height * options.scale() };
+    let plans = scene.floor_plans(&plan_options);
+    if plans.is_empty() {
+        error!("No storeys with geometry in {}", ifc_path.display());
+        process::exit(EXIT_FAILURE);
+    }
+    std::fs::create_dir_all(out_dir).unwrap_or_else(|e| {
+        error!("Failed to create directory: {}", e);
+        process::exit(EXIT_FAILURE);
+    });
+
+    for plan in &plans {
+        // Storey names become file names; keep them portable
+        let stem: String = plan
+            .name
+            .chars()
+            .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        let (path, content) = match format {
+            PlanFormat::Svg => (out_dir.join(format!("{}.svg", stem)), plan.to_svg()),
+            PlanFormat::Dxf => (out_dir.join(format!("{}.dxf", stem)), plan.to_dxf()),
+        };
+        if let Err(e) = std::fs::write(&path, content) {
+            error!("Failed to write {}: {}", path.display(), e);
+            process::exit(EXIT_FAILURE);
+        }
+        info!("{}: {} elements cut at {} -> {}", plan.name, plan.elements.len(), plan.cut_elevation, path.display());
+    }
+}
+
+fn handle_csv_export(
+    ifc_path: &Path,
+    output: Option<&Path>,
+    psets: bool,
+    ifc_type: Option<&str>,
+    storey: Option<&str>,
+) {
+    let query = cst_ifc::ifc_query::IfcQuery::open(ifc_path).unwrap_or_else(|e| {
+        error!("Failed to read IFC: {}", e);
+        process::exit(EXIT_FAILURE);
+    });
+    let mut ids = match ifc_type {
+        Some(ifc_type) => query.elements_of_type(ifc_type),
+        None => query.elements(),
+    };
+    if let Some(storey) = storey {
+        let contained = query.elements_in_storey(storey);
+        ids.retain(|id| contained.binary_search(id).is_ok());
+    }
+
+    let mut out = output_writer(output);
+    if let Err(e) = cst_ifc::ifc_csv::write_csv(&query, &ids, psets, &mut out).and_then(|()| out.flush()) {
+        error!("Failed to write CSV: {}", e);
+        process::exit(EXIT_FAILURE);
+    }
+    info!("Exported {} elements", ids.len());
+}
+
+/// A buffered writer to `path`, or to stdout when there is none
+fn output_writer(path: Option<&Path>) -> Box<dyn std::io::Write> {
+    match path {
+        Some(path) => Box::new(std::io::BufWriter::new(std::fs::File::create(path).unwrap_or_else(|e| {
+            error!("Failed to create {}: {}", path.display(), e);
+            process::exit(EXIT_FAILURE);
+        }))),
+        None => Box::new(std::io::stdout().lock()),
+    }
+}
+
+fn handle_json_dump(ifc_path: &Path, output: Option<&Path>, options: &cst_ifc::ifc_json::JsonDumpOptions, pretty: bool) {
+    let dump = std::fs::read_to_string(ifc_path)
+        .map_err(cst_core::CstError::from)
+        .and_then(|text| cst_ifc::step_parser::parse_step(&text))
+        .and_then(|file| cst_ifc::ifc_json::step_to_json(&file, options))
+        .unwrap_or_else(|e| {
+            error!("Failed to dump {}: {}", ifc_path.display(), e);
+            process::exit(EXIT_FAILURE);
+        });
+    let mut out = output_writer(output);
+    let written = if pretty {
+        serde_json::to_writer_pretty(&mut out, &dump)
+    } else {
+        serde_json::to_writer(&mut out, &dump)
+    };
+    if let Err(e) = written.map_err(std::io::Error::from).and_then(|()| writeln!(out)).and_then(|()| out.flush()) {
+        error!("Failed to write JSON: {}", e);
+        process::exit(EXIT_FAILURE);
+    }
+}
+
+fn handle_point_cloud(ifc_path: &Path, output: &Path, spacing: f64, seed: u64, options: &IfcPipelineOptions) {
+    if spacing.is_nan() || spacing <= 0.0 {
+        error!("--spacing must be positive");
+        process::exit(EXIT_FAILURE);
+    }
+    let scene = load_scene(ifc_path, options);
+    let cloud = scene.sample_points(&cst_render::PointCloudOptions { spacing: spacing * options.scale(), seed });
+    let xyz = output.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("xyz"));
+    let result = if xyz { cloud.export_xyz(output) } else { cloud.export_ply(output) };
+    if let Err(e) = result {
+        error!("Failed to write {}: {}", output.display(), e);
+        process::exit(EXIT_FAILURE);
+    }
+    info!("Sampled {} points from {} elements -> {}", cloud.len(), cloud.elements.len(), output.display());
+}
+
+/// Parse and tessellate an IFC file with its metadata, through the cache
+/// when `--cache` is given
+fn load_model(ifc_path: &Path, options: &IfcPipelineOptions) -> cst_ifc::ifc_cache::CachedModel {
+    let progress = CliProgress::default();
+    let model = if options.cache {
+        cst_ifc::ifc_cache::load_or_build(ifc_path, options, &progress)
+    } else {
+        cst_ifc::ifc_cache::CachedModel::build(ifc_path, options, &progress)
+    };
+    model.unwrap_or_else(|e| {
+        error!("Failed to read IFC: {}", e);
+        process::exit(EXIT_FAILURE);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+        Cli::try_parse_from(std::iter::once("cst_viewer").chain(args.iter().copied()))
+    }
+
+    fn sample() -> &'static str {
+        concat!(env!("CARGO_MANIFEST_DIR"), "/../../samples/office.ifc")
+    }
+
+    #[test]
+    fn test_cli_definition_is_consistent() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_parse_convert_with_pipeline_flags() {
+        let cli = parse(&[
+            "-v",
+            "convert",
+            "building.ifc",
+            "walls.stl",
+            "--include-types",
+            "IfcWall,IfcSlab",
+            "--unit-scale",
+            "0.001",
+            "--max-triangles",
+            "5000",
+            "--size",
+            "800x600",
+        ])
+        .unwrap();
+        assert_eq!(cli.verbose, 1);
+        let Command::Convert { input, output, format, size, pipeline, .. } = cli.command else {
+            panic!("expected convert");
+        };
+        assert_eq!(input, Path::new("building.ifc"));
+        let output = output.unwrap();
+        assert!(format.is_none());
+        assert!(Format::from_path(&output) == Some(Format::Stl));
+        assert_eq!(size, (800, 600));
+
+        let options = pipeline.options();
+        let types = options.type_filter.unwrap();
+        assert_eq!(types, ["IfcWall", "IfcSlab"]);
+        assert_eq!(options.unit_scale, Some(0.001));
+        assert_eq!(options.triangle_budget, Some(5000));
+        assert!(options.instancing && !options.cache);
+    }
+
+    #[test]
+    fn test_parse_measure_and_dump_ids() {
+        let cli = parse(&["measure", "building.ifc", "#12", "--point", "1,2.5,0", "--json"]).unwrap();
+        let Command::Measure { elements, point, json, .. } = cli.command else {
+            panic!("expected measure");
+        };
+        assert_eq!(elements, ["#12"]);
+        assert_eq!(point, Some(cst_math::DVec3::new(1.0, 2.5, 0.0)));
+        assert!(json);
+
+        let cli = parse(&["dump", "building.ifc", "--id", "#12,40"]).unwrap();
+        let Command::Dump { ids, .. } = cli.command else {
+            panic!("expected dump");
+        };
+        assert_eq!(ids, [cst_core::StepId(12), cst_core::StepId(40)]);
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_arguments() {
+        // Missing subcommand, conflicting flags, a bad size and a query
+        // without filters
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["-q", "-v", "summary", "building.ifc"]).is_err());
+        assert!(parse(&["convert", "building.ifc", "--size", "800"]).is_err());
+        assert!(parse(&["query", "building.ifc"]).is_err());
+        assert!(parse(&["convert", "building.ifc", "--standard-view", "up"]).is_err());
+    }
+
+    #[test]
+    fn test_html_export_carries_element_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let html = dir.path().join("office.html");
+        let html_arg = html.to_str().unwrap();
+        let cli = parse(&["convert", sample(), html_arg, "--unit-scale", "0.001"]).unwrap();
+        let Command::Convert { input, pipeline, .. } = cli.command else {
+            panic!("expected convert");
+        };
+        handle_html_export(&input, &html, &Default::default(), &pipeline.options());
+
+        let text = std::fs::read_to_string(&html).unwrap();
+        assert!(text.contains(r#"ifcType: "IFCWALLSTANDARDCASE","#));
+        assert!(text.contains(r#"storey: "Level 1","#));
+        assert!(text.contains("globalId: \""));
+        assert!(!text.contains("ifcType: null"));
+        assert!(!text.contains("storey: null"));
+    }
+
+    #[test]
+    fn test_no_instancing_gives_every_element_a_mesh() {
+        let scene_for = |args: &[&str]| {
+            let cli = parse(args).unwrap();
+            let Command::Convert { input, pipeline, .. } = cli.command else {
+                panic!("expected convert");
+            };
+            load_scene(&input, &pipeline.options())
+        };
+        let instanced = scene_for(&["convert", sample(), "office.glb", "--unit-scale", "0.001"]);
+        assert!(!instanced.instanced_groups.is_empty());
+        let flat = scene_for(&["convert", sample(), "office.glb", "--unit-scale", "0.001", "--no-instancing"]);
+        assert!(flat.instanced_groups.is_empty());
+        let instances: usize = instanced.instanced_groups.iter().map(|g| g.transforms.len()).sum();
+        assert_eq!(flat.meshes.len(), instanced.meshes.len() + instances);
+    }
+
+    #[test]
+    fn test_convert_edges_defaults_to_thirty_degrees() {
+        let edges = |args: &[&str]| match parse(args).unwrap().command {
+            Command::Convert { edges, .. } => edges,
+            _ => panic!("expected convert"),
+        };
+        assert_eq!(edges(&["convert", "building.ifc", "building.html"]), None);
+        assert_eq!(edges(&["convert", "building.ifc", "building.html", "--edges"]), Some(30.0));
+        assert_eq!(edges(&["convert", "building.ifc", "building.html", "--edges", "45"]), Some(45.0));
+
+        let dir = tempfile::tempdir().unwrap();
+        let html = dir.path().join("office.html");
+        let options = IfcPipelineOptions { unit_scale: Some(0.001), ..Default::default() };
+        let with_edges = cst_render::HtmlExportOptions {
+            edge_overlay: Some(30f64.to_radians()),
+            ..Default::default()
+        };
+        handle_html_export(Path::new(sample()), &html, &with_edges, &options);
+        let text = std::fs::read_to_string(&html).unwrap();
+        assert!(!text.contains("edges: []"));
+    }
+}
@@ -0,0 +1,36 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::error::{CstError, Result};
+
+/// Shared flag for aborting a long-running operation.
+///
+/// Clones share the flag: a GUI host keeps one clone and calls
+/// [`cancel`](Self::cancel), while the worker polls another between units
+/// of work and stops with [`CstError::Cancelled`].
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask every holder of the token to stop.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// `Err(CstError::Cancelled)` once the token is cancelled.
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            Err(CstError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
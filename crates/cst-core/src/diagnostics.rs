@@ -0,0 +1,263 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Serialize, Serializer};
+
+use crate::id::StepId;
+
+/// How much a diagnostic matters to the result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Worth knowing, nothing was lost
+    Info,
+    /// Something was skipped or approximated
+    Warning,
+    /// Something that should have worked failed
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// A kind of diagnostic: a stable id for tools and a phrase for counted
+/// summaries, e.g. `faces skipped as degenerate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DiagnosticCode {
+    pub id: &'static str,
+    pub summary: &'static str,
+}
+
+impl DiagnosticCode {
+    pub const fn new(id: &'static str, summary: &'static str) -> Self {
+        Self { id, summary }
+    }
+}
+
+impl fmt::Display for DiagnosticCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id)
+    }
+}
+
+/// Serialized as its id.
+impl Serialize for DiagnosticCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.id)
+    }
+}
+
+/// One thing that went wrong without stopping the operation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: DiagnosticCode,
+    /// Entity the diagnostic is about, when known
+    pub entity: Option<StepId>,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}]", self.severity, self.code)?;
+        if let Some(entity) = self.entity {
+            write!(f, " {}", entity)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// Diagnostics collected while parsing, resolving or tessellating, so that
+/// callers can report what was skipped instead of losing it silently.
+///
+/// Parallel stages collect into one `Diagnostics` per work item and
+/// [`append`](Self::append) them in input order, which keeps the list the
+/// same between runs.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct Diagnostics(Vec<Diagnostic>);
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        self.0.push(diagnostic);
+    }
+
+    pub fn report(
+        &mut self,
+        severity: Severity,
+        code: DiagnosticCode,
+        entity: Option<StepId>,
+        message: impl Into<String>,
+    ) {
+        self.push(Diagnostic {
+            severity,
+            code,
+            entity,
+            message: message.into(),
+        });
+    }
+
+    pub fn info(
+        &mut self,
+        code: DiagnosticCode,
+        entity: Option<StepId>,
+        message: impl Into<String>,
+    ) {
+        self.report(Severity::Info, code, entity, message);
+    }
+
+    pub fn warn(
+        &mut self,
+        code: DiagnosticCode,
+        entity: Option<StepId>,
+        message: impl Into<String>,
+    ) {
+        self.report(Severity::Warning, code, entity, message);
+    }
+
+    pub fn error(
+        &mut self,
+        code: DiagnosticCode,
+        entity: Option<StepId>,
+        message: impl Into<String>,
+    ) {
+        self.report(Severity::Error, code, entity, message);
+    }
+
+    /// Move every diagnostic of `other` to the end of this list.
+    pub fn append(&mut self, other: &mut Diagnostics) {
+        self.0.append(&mut other.0);
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Diagnostic> {
+        self.0.iter()
+    }
+
+    /// The most severe diagnostic's severity, `None` when empty.
+    pub fn max_severity(&self) -> Option<Severity> {
+        self.0.iter().map(|d| d.severity).max()
+    }
+
+    /// Number of diagnostics of each code.
+    pub fn counts(&self) -> BTreeMap<DiagnosticCode, usize> {
+        let mut counts = BTreeMap::new();
+        for diagnostic in &self.0 {
+            *counts.entry(diagnostic.code).or_default() += 1;
+        }
+        counts
+    }
+
+    /// One line per code, most severe first, e.g.
+    /// `173 faces skipped as degenerate`.
+    pub fn summary(&self) -> Vec<(Severity, String)> {
+        let mut severities: BTreeMap<DiagnosticCode, Severity> = BTreeMap::new();
+        for diagnostic in &self.0 {
+            let severity = severities
+                .entry(diagnostic.code)
+                .or_insert(diagnostic.severity);
+            *severity = (*severity).max(diagnostic.severity);
+        }
+        let mut lines: Vec<(Severity, String)> = self
+            .counts()
+            .into_iter()
+            .map(|(code, count)| (severities[&code], format!("{} {}", count, code.summary)))
+            .collect();
+        lines.sort_by_key(|(severity, _)| std::cmp::Reverse(*severity));
+        lines
+    }
+}
+
+impl<'a> IntoIterator for &'a Diagnostics {
+    type Item = &'a Diagnostic;
+    type IntoIter = std::slice::Iter<'a, Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl IntoIterator for Diagnostics {
+    type Item = Diagnostic;
+    type IntoIter = std::vec::IntoIter<Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl Extend<Diagnostic> for Diagnostics {
+    fn extend<I: IntoIterator<Item = Diagnostic>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEGENERATE: DiagnosticCode =
+        DiagnosticCode::new("degenerate-face", "faces skipped as degenerate");
+    const UNRESOLVED: DiagnosticCode =
+        DiagnosticCode::new("unresolved-geometry", "geometry items skipped");
+
+    #[test]
+    fn test_summary_counts_by_code() {
+        let mut diagnostics = Diagnostics::new();
+        assert_eq!(diagnostics.max_severity(), None);
+        for face in [10, 11, 12] {
+            diagnostics.warn(DEGENERATE, Some(StepId(face)), "fewer than three points");
+        }
+        let mut resolved = Diagnostics::new();
+        resolved.error(UNRESOLVED, Some(StepId(20)), "missing #21");
+        diagnostics.append(&mut resolved);
+        assert!(resolved.is_empty());
+
+        assert_eq!(diagnostics.len(), 4);
+        assert_eq!(diagnostics.max_severity(), Some(Severity::Error));
+        assert_eq!(diagnostics.counts()[&DEGENERATE], 3);
+        assert_eq!(
+            diagnostics.summary(),
+            [
+                (Severity::Error, "1 geometry items skipped".to_string()),
+                (
+                    Severity::Warning,
+                    "3 faces skipped as degenerate".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.warn(DEGENERATE, Some(StepId(7)), "zero area");
+        diagnostics.info(DEGENERATE, None, "zero area");
+        let lines: Vec<String> = diagnostics.iter().map(|d| d.to_string()).collect();
+        assert_eq!(
+            lines,
+            [
+                "warning [degenerate-face] #7: zero area",
+                "info [degenerate-face]: zero area",
+            ]
+        );
+    }
+}
@@ -1,5 +1,9 @@
+use std::fmt;
+
 use thiserror::Error;
 
+use crate::id::StepId;
+
 #[derive(Debug, Error)]
 pub enum CstError {
     #[error("Topology error: {0}")]
@@ -11,6 +15,27 @@ pub enum CstError {
     #[error("Parse error: {0}")]
     Parse(String),
 
+    /// Malformed input text, with where it went wrong and what was there.
+    #[error(
+        "Syntax error at {location}{}: expected {expected}, found {found}",
+        .entity.map(|id| format!(" in {}", id)).unwrap_or_default()
+    )]
+    Syntax {
+        location: SourceLocation,
+        /// The entity being read, once its id is known
+        entity: Option<StepId>,
+        expected: String,
+        found: String,
+    },
+
+    /// A reference that could not be followed. `path` runs from the entity
+    /// being resolved to the one that failed.
+    #[error("Unresolved reference {}: {reason}", EntityPath(.path))]
+    Unresolved {
+        path: Vec<EntityLink>,
+        reason: String,
+    },
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -22,6 +47,164 @@ pub enum CstError {
 
     #[error("Not found: {0}")]
     NotFound(String),
+
+    #[error("Operation cancelled")]
+    Cancelled,
 }
 
 pub type Result<T> = std::result::Result<T, CstError>;
+
+/// A position in a text file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// Bytes from the start of the file
+    pub offset: usize,
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column, in bytes
+    pub column: usize,
+}
+
+impl SourceLocation {
+    /// The first byte of a file.
+    pub const START: Self = Self {
+        offset: 0,
+        line: 1,
+        column: 1,
+    };
+
+    /// The location of byte `offset` in `text`.
+    pub fn from_offset(text: &str, offset: usize) -> Self {
+        Self::from_bytes(text.as_bytes(), offset)
+    }
+
+    /// The location of byte `offset` in `bytes`, which need not be UTF-8.
+    pub fn from_bytes(bytes: &[u8], offset: usize) -> Self {
+        let before = &bytes[..offset.min(bytes.len())];
+        let line_start = before
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |i| i + 1);
+        Self {
+            offset,
+            line: before.iter().filter(|&&b| b == b'\n').count() + 1,
+            column: before.len() - line_start + 1,
+        }
+    }
+
+    /// Turn `relative`, a location in text that starts at `self`, into a
+    /// location in the whole file.
+    pub fn advance(self, relative: SourceLocation) -> Self {
+        Self {
+            offset: self.offset + relative.offset,
+            line: self.line + relative.line - 1,
+            column: if relative.line == 1 {
+                self.column + relative.column - 1
+            } else {
+                relative.column
+            },
+        }
+    }
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// One entity on a reference path, e.g. `IFCFACETEDBREP #123`. The type is
+/// unknown for an entity that is missing from the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntityLink {
+    pub id: StepId,
+    pub type_name: Option<String>,
+}
+
+impl EntityLink {
+    pub fn new(id: StepId, type_name: impl Into<String>) -> Self {
+        Self {
+            id,
+            type_name: Some(type_name.into()),
+        }
+    }
+
+    pub fn missing(id: StepId) -> Self {
+        Self {
+            id,
+            type_name: None,
+        }
+    }
+}
+
+impl fmt::Display for EntityLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.type_name {
+            Some(type_name) => write!(f, "{} {}", type_name, self.id),
+            None => write!(f, "{}", self.id),
+        }
+    }
+}
+
+/// `A #1 → B #2 → #3`
+struct EntityPath<'a>(&'a [EntityLink]);
+
+impl fmt::Display for EntityPath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, link) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" → ")?;
+            }
+            write!(f, "{}", link)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_location() {
+        let text = "ab\ncd\n\nef";
+        assert_eq!(
+            SourceLocation::from_offset(text, 0),
+            SourceLocation {
+                offset: 0,
+                line: 1,
+                column: 1
+            }
+        );
+        assert_eq!(SourceLocation::from_offset(text, 4).line, 2);
+        assert_eq!(SourceLocation::from_offset(text, 4).column, 2);
+        assert_eq!(SourceLocation::from_offset(text, 8).line, 4);
+        assert_eq!(SourceLocation::from_offset(text, 99).offset, 99);
+    }
+
+    #[test]
+    fn test_error_messages() {
+        let syntax = CstError::Syntax {
+            location: SourceLocation::from_offset("x\n#5=IFCWALL(", 12),
+            entity: Some(StepId(5)),
+            expected: "')'".to_string(),
+            found: "end of file".to_string(),
+        };
+        assert_eq!(
+            syntax.to_string(),
+            "Syntax error at line 2, column 11 in #5: expected ')', found end of file"
+        );
+
+        let unresolved = CstError::Unresolved {
+            path: vec![
+                EntityLink::new(StepId(123), "IFCFACETEDBREP"),
+                EntityLink::missing(StepId(456)),
+            ],
+            reason: "missing".to_string(),
+        };
+        assert_eq!(
+            unresolved.to_string(),
+            "Unresolved reference IFCFACETEDBREP #123 → #456: missing"
+        );
+    }
+}
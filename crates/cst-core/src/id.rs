@@ -26,3 +26,83 @@ impl std::fmt::Display for EntityId {
         write!(f, "#{}", self.0)
     }
 }
+
+/// The instance name of an entity in a STEP file, the `123` of `#123`.
+/// Unlike [`EntityId`] it is read from the file, not generated.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
+#[serde(transparent)]
+pub struct StepId(pub u64);
+
+impl StepId {
+    pub fn value(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for StepId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+impl std::fmt::Display for StepId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// A [`StepId`] known to name a particular kind of entity. Distinct types
+/// keep a representation id from being looked up as a product and the like;
+/// `step()` drops back to the plain id for table lookups.
+macro_rules! typed_step_id {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(
+            Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize,
+            serde::Deserialize,
+        )]
+        #[serde(transparent)]
+        pub struct $name(pub StepId);
+
+        impl $name {
+            pub const fn new(id: u64) -> Self {
+                Self(StepId(id))
+            }
+
+            pub fn step(self) -> StepId {
+                self.0
+            }
+
+            pub fn value(self) -> u64 {
+                self.0 .0
+            }
+        }
+
+        impl From<$name> for StepId {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+    };
+}
+
+typed_step_id!(
+    /// An `IfcProduct`: a wall, slab, storey and so on.
+    ProductId
+);
+typed_step_id!(
+    /// An `IfcProductDefinitionShape` or `IfcShapeRepresentation`.
+    RepresentationId
+);
+typed_step_id!(
+    /// An `IfcStyledItem` or a style entity it leads to.
+    StyleId
+);
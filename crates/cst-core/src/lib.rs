@@ -1,8 +1,14 @@
+pub mod cancel;
+pub mod diagnostics;
 pub mod error;
 pub mod id;
 pub mod tolerance;
 pub mod traits;
+pub mod units;
 
-pub use error::{CstError, Result};
-pub use id::EntityId;
-pub use tolerance::Tolerance;
+pub use cancel::CancellationToken;
+pub use diagnostics::{Diagnostic, DiagnosticCode, Diagnostics, Severity};
+pub use error::{CstError, EntityLink, Result, SourceLocation};
+pub use id::{EntityId, ProductId, RepresentationId, StepId, StyleId};
+pub use tolerance::{Tolerance, ToleranceContext};
+pub use units::{AngleUnit, AreaUnit, LengthUnit, ModelUnits};
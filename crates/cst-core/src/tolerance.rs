@@ -57,3 +57,108 @@ impl Default for Tolerance {
         Self::default_precision()
     }
 }
+
+/// Tolerances threaded through tessellation, welding and intersection.
+///
+/// `linear` is a model-space distance, `angular` is in radians and
+/// `parametric` applies to curve/surface parameters. Use
+/// [`ToleranceContext::from_extent`] to scale the linear tolerance to the
+/// size of the model instead of hard-coding an epsilon.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ToleranceContext {
+    pub linear: f64,
+    pub angular: f64,
+    pub parametric: f64,
+}
+
+impl ToleranceContext {
+    pub const DEFAULT_PARAMETRIC: f64 = 1e-9;
+    /// Linear tolerance relative to the model extent in [`Self::from_extent`]
+    pub const RELATIVE_LINEAR: f64 = 1e-9;
+    /// Smallest linear tolerance [`Self::from_extent`] returns
+    pub const MIN_LINEAR: f64 = 1e-12;
+
+    pub fn new(linear: f64, angular: f64, parametric: f64) -> Self {
+        Self {
+            linear,
+            angular,
+            parametric,
+        }
+    }
+
+    /// Defaults for a model whose bounding box diagonal is `extent`.
+    ///
+    /// The linear tolerance is [`Self::RELATIVE_LINEAR`] times the extent,
+    /// so a building modelled in millimetres and the same building in
+    /// metres weld and intersect alike. Non-finite or empty extents give
+    /// the default context.
+    pub fn from_extent(extent: f64) -> Self {
+        if !(extent.is_finite() && extent > 0.0) {
+            return Self::default();
+        }
+        Self {
+            linear: (extent * Self::RELATIVE_LINEAR).max(Self::MIN_LINEAR),
+            ..Self::default()
+        }
+    }
+
+    /// Same context with a different linear tolerance.
+    pub fn with_linear(self, linear: f64) -> Self {
+        Self { linear, ..self }
+    }
+
+    /// The linear/angular pair as a [`Tolerance`].
+    pub fn tolerance(self) -> Tolerance {
+        Tolerance::new(self.linear, self.angular)
+    }
+}
+
+impl Default for ToleranceContext {
+    fn default() -> Self {
+        Tolerance::default().into()
+    }
+}
+
+impl From<Tolerance> for ToleranceContext {
+    fn from(tolerance: Tolerance) -> Self {
+        Self {
+            linear: tolerance.linear,
+            angular: tolerance.angular,
+            parametric: Self::DEFAULT_PARAMETRIC,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_from_extent() {
+        let metres = ToleranceContext::from_extent(50.0);
+        let millimetres = ToleranceContext::from_extent(50_000.0);
+        assert!((millimetres.linear / metres.linear - 1000.0).abs() < 1e-6);
+        assert_eq!(metres.angular, Tolerance::DEFAULT_ANGULAR);
+
+        assert_eq!(
+            ToleranceContext::from_extent(0.0),
+            ToleranceContext::default()
+        );
+        assert_eq!(
+            ToleranceContext::from_extent(f64::NAN),
+            ToleranceContext::default()
+        );
+        assert_eq!(
+            ToleranceContext::from_extent(1e-9).linear,
+            ToleranceContext::MIN_LINEAR
+        );
+    }
+
+    #[test]
+    fn test_context_tolerance_round_trip() {
+        let context: ToleranceContext = Tolerance::loose().into();
+        assert_eq!(context.parametric, ToleranceContext::DEFAULT_PARAMETRIC);
+        assert_eq!(context.tolerance().linear, Tolerance::loose().linear);
+        assert_eq!(context.with_linear(0.5).linear, 0.5);
+    }
+}
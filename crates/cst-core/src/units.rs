@@ -0,0 +1,258 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Relative difference below which two unit factors are the same unit.
+const FACTOR_TOLERANCE: f64 = 1e-9;
+
+fn same_factor(a: f64, b: f64) -> bool {
+    (a - b).abs() <= FACTOR_TOLERANCE * a.abs().max(b.abs())
+}
+
+/// Unit of lengths and coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LengthUnit {
+    Millimetre,
+    Centimetre,
+    Decimetre,
+    #[default]
+    Metre,
+    Kilometre,
+    Inch,
+    Foot,
+    /// Any other unit, given in metres
+    Other(f64),
+}
+
+impl LengthUnit {
+    const NAMED: [LengthUnit; 7] = [
+        LengthUnit::Millimetre,
+        LengthUnit::Centimetre,
+        LengthUnit::Decimetre,
+        LengthUnit::Metre,
+        LengthUnit::Kilometre,
+        LengthUnit::Inch,
+        LengthUnit::Foot,
+    ];
+
+    /// Length of one unit in metres.
+    pub fn metres(self) -> f64 {
+        match self {
+            LengthUnit::Millimetre => 0.001,
+            LengthUnit::Centimetre => 0.01,
+            LengthUnit::Decimetre => 0.1,
+            LengthUnit::Metre => 1.0,
+            LengthUnit::Kilometre => 1000.0,
+            LengthUnit::Inch => 0.0254,
+            LengthUnit::Foot => 0.3048,
+            LengthUnit::Other(metres) => metres,
+        }
+    }
+
+    /// The named unit `metres` long, or [`Other`](Self::Other).
+    pub fn from_metres(metres: f64) -> Self {
+        Self::NAMED
+            .into_iter()
+            .find(|unit| same_factor(unit.metres(), metres))
+            .unwrap_or(LengthUnit::Other(metres))
+    }
+
+    /// Factor that turns values in this unit into values in `to`, e.g.
+    /// 0.001 from millimetres to metres.
+    pub fn factor_to(self, to: LengthUnit) -> f64 {
+        self.metres() / to.metres()
+    }
+
+    /// The matching area unit.
+    pub fn squared(self) -> AreaUnit {
+        AreaUnit::from_square_metres(self.metres() * self.metres())
+    }
+}
+
+impl fmt::Display for LengthUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LengthUnit::Millimetre => f.write_str("mm"),
+            LengthUnit::Centimetre => f.write_str("cm"),
+            LengthUnit::Decimetre => f.write_str("dm"),
+            LengthUnit::Metre => f.write_str("m"),
+            LengthUnit::Kilometre => f.write_str("km"),
+            LengthUnit::Inch => f.write_str("in"),
+            LengthUnit::Foot => f.write_str("ft"),
+            LengthUnit::Other(metres) => write!(f, "{} m", metres),
+        }
+    }
+}
+
+/// Unit of plane angles.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AngleUnit {
+    #[default]
+    Radian,
+    Degree,
+    /// Any other unit, given in radians
+    Other(f64),
+}
+
+impl AngleUnit {
+    /// Size of one unit in radians.
+    pub fn radians(self) -> f64 {
+        match self {
+            AngleUnit::Radian => 1.0,
+            AngleUnit::Degree => std::f64::consts::PI / 180.0,
+            AngleUnit::Other(radians) => radians,
+        }
+    }
+
+    /// The named unit `radians` large, or [`Other`](Self::Other).
+    pub fn from_radians(radians: f64) -> Self {
+        [AngleUnit::Radian, AngleUnit::Degree]
+            .into_iter()
+            .find(|unit| same_factor(unit.radians(), radians))
+            .unwrap_or(AngleUnit::Other(radians))
+    }
+
+    pub fn factor_to(self, to: AngleUnit) -> f64 {
+        self.radians() / to.radians()
+    }
+}
+
+impl fmt::Display for AngleUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AngleUnit::Radian => f.write_str("rad"),
+            AngleUnit::Degree => f.write_str("°"),
+            AngleUnit::Other(radians) => write!(f, "{} rad", radians),
+        }
+    }
+}
+
+/// Unit of areas. Models often give areas in square metres while their
+/// coordinates are in millimetres, so it is kept apart from the length
+/// unit.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AreaUnit {
+    SquareMillimetre,
+    SquareCentimetre,
+    #[default]
+    SquareMetre,
+    SquareInch,
+    SquareFoot,
+    /// Any other unit, given in square metres
+    Other(f64),
+}
+
+impl AreaUnit {
+    const NAMED: [AreaUnit; 5] = [
+        AreaUnit::SquareMillimetre,
+        AreaUnit::SquareCentimetre,
+        AreaUnit::SquareMetre,
+        AreaUnit::SquareInch,
+        AreaUnit::SquareFoot,
+    ];
+
+    /// Size of one unit in square metres.
+    pub fn square_metres(self) -> f64 {
+        match self {
+            AreaUnit::SquareMillimetre => 1e-6,
+            AreaUnit::SquareCentimetre => 1e-4,
+            AreaUnit::SquareMetre => 1.0,
+            AreaUnit::SquareInch => 0.0254 * 0.0254,
+            AreaUnit::SquareFoot => 0.3048 * 0.3048,
+            AreaUnit::Other(square_metres) => square_metres,
+        }
+    }
+
+    /// The named unit `square_metres` large, or [`Other`](Self::Other).
+    pub fn from_square_metres(square_metres: f64) -> Self {
+        Self::NAMED
+            .into_iter()
+            .find(|unit| same_factor(unit.square_metres(), square_metres))
+            .unwrap_or(AreaUnit::Other(square_metres))
+    }
+
+    pub fn factor_to(self, to: AreaUnit) -> f64 {
+        self.square_metres() / to.square_metres()
+    }
+}
+
+impl fmt::Display for AreaUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AreaUnit::SquareMillimetre => f.write_str("mm²"),
+            AreaUnit::SquareCentimetre => f.write_str("cm²"),
+            AreaUnit::SquareMetre => f.write_str("m²"),
+            AreaUnit::SquareInch => f.write_str("in²"),
+            AreaUnit::SquareFoot => f.write_str("ft²"),
+            AreaUnit::Other(square_metres) => write!(f, "{} m²", square_metres),
+        }
+    }
+}
+
+/// The units a model's numbers are in. Defaults to SI.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct ModelUnits {
+    pub length: LengthUnit,
+    pub angle: AngleUnit,
+    pub area: AreaUnit,
+}
+
+impl ModelUnits {
+    /// The units after every coordinate was multiplied by `scale`, e.g.
+    /// metres for millimetres scaled by 0.001. Areas and angles are not
+    /// coordinates and keep their units.
+    pub fn with_length_scale(self, scale: f64) -> Self {
+        Self {
+            length: LengthUnit::from_metres(self.length.metres() / scale),
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for ModelUnits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}, {}, {}", self.length, self.area, self.angle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_length_conversion() {
+        assert_eq!(LengthUnit::Millimetre.factor_to(LengthUnit::Metre), 0.001);
+        assert!((LengthUnit::Foot.factor_to(LengthUnit::Inch) - 12.0).abs() < 1e-12);
+        assert_eq!(LengthUnit::from_metres(0.001), LengthUnit::Millimetre);
+        assert_eq!(LengthUnit::from_metres(0.5), LengthUnit::Other(0.5));
+        assert_eq!(LengthUnit::Centimetre.squared(), AreaUnit::SquareCentimetre);
+        assert_eq!(
+            AngleUnit::from_radians(1.0_f64.to_radians()),
+            AngleUnit::Degree
+        );
+    }
+
+    #[test]
+    fn test_model_units_scale_and_serialize() {
+        let units = ModelUnits {
+            length: LengthUnit::Millimetre,
+            ..Default::default()
+        };
+        assert_eq!(units.to_string(), "mm, m², rad");
+        let metres = units.with_length_scale(0.001);
+        assert_eq!(metres, ModelUnits::default());
+
+        let json = serde_json::to_value(units).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "length": "millimetre",
+                "angle": "radian",
+                "area": "square_metre",
+            })
+        );
+    }
+}
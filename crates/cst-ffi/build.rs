@@ -0,0 +1,24 @@
+//! Generates cst_ffi.h from the exported functions into OUT_DIR, and
+//! refreshes the checked-in include/cst_ffi.h when CST_FFI_REGEN_HEADER is
+//! set.
+
+use std::env;
+use std::path::PathBuf;
+
+fn main() {
+    let crate_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-env-changed=CST_FFI_REGEN_HEADER");
+    match cbindgen::generate(&crate_dir) {
+        Ok(bindings) => {
+            bindings.write_to_file(out_dir.join("cst_ffi.h"));
+            // Builds leave the source tree alone unless asked to
+            if env::var_os("CST_FFI_REGEN_HEADER").is_some() {
+                bindings.write_to_file(crate_dir.join("include/cst_ffi.h"));
+            }
+        }
+        Err(e) => println!("cargo:warning=cst_ffi.h not generated: {}", e),
+    }
+}
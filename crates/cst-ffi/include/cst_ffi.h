@@ -0,0 +1,164 @@
+#ifndef CST_FFI_H
+#define CST_FFI_H
+
+/* Generated by cbindgen from crates/cst-ffi/src/lib.rs; do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Shared flag for aborting `cst_model_open_cancellable`. Opaque to C.
+typedef struct CstCancelToken CstCancelToken;
+
+// An opened model. Opaque to C.
+typedef struct CstModel CstModel;
+
+// Conversion settings for `cst_model_open`.
+typedef struct CstOpenOptions {
+  // Drop faces smaller than a square of this size, in model units;
+  // 0 keeps all detail
+  double tolerance;
+  // Factor applied to every coordinate, e.g. 0.001 for millimetres to
+  // metres; 0 keeps the model's units
+  double unit_scale;
+} CstOpenOptions;
+
+// One tessellated mesh of a model.
+typedef struct CstMesh {
+  const char *name;
+  // IFC GlobalId of the product, or NULL for geometry without one
+  const char *global_id;
+  // Upper-case IFC type such as `IFCWALL`, or NULL
+  const char *ifc_type;
+  // `vertex_count * 3` floats, x y z per vertex
+  const float *positions;
+  // `vertex_count * 3` floats, one unit normal per vertex
+  const float *normals;
+  size_t vertex_count;
+  // Three indices per triangle
+  const uint32_t *indices;
+  size_t index_count;
+  // Linear RGB from the IFC style, valid when `has_color` is set
+  float color[3];
+  bool has_color;
+} CstMesh;
+
+// A product property as name and value.
+typedef struct CstProperty {
+  const char *name;
+  const char *value;
+} CstProperty;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Message of the last failed call on this thread; empty when none failed.
+// Valid until the next failing call on the same thread.
+const char *cst_last_error(void);
+
+// Library version, e.g. `0.1.0`.
+const char *cst_version(void);
+
+// Parse and tessellate the IFC file at `path` (UTF-8). `options` may be
+// NULL for the defaults. Returns NULL on failure; see `cst_last_error`.
+//
+// # Safety
+//
+// `path` must be NULL or a NUL-terminated string, and `options` NULL or
+// a valid `CstOpenOptions`.
+struct CstModel *cst_model_open(const char *path, const struct CstOpenOptions *options);
+
+// A new, uncancelled token. Free it with `cst_cancel_token_free`.
+struct CstCancelToken *cst_cancel_token_new(void);
+
+// Ask the conversion using `token` to stop. Safe to call from any thread
+// while the conversion runs. NULL is ignored.
+//
+// # Safety
+//
+// `token` must be NULL or a live handle from `cst_cancel_token_new`.
+void cst_cancel_token_cancel(const struct CstCancelToken *token);
+
+// Release a token. NULL is ignored.
+//
+// # Safety
+//
+// `token` must be NULL or a handle from `cst_cancel_token_new` that has
+// not been freed, and no conversion may still be using it.
+void cst_cancel_token_free(struct CstCancelToken *token);
+
+// Like `cst_model_open`, stopping early once `cancel` is cancelled; the
+// call then returns NULL with `cst_last_error` reporting the
+// cancellation. `cancel` may be NULL.
+//
+// # Safety
+//
+// As for `cst_model_open`; `cancel` must be NULL or a live handle from
+// `cst_cancel_token_new`.
+struct CstModel *cst_model_open_cancellable(const char *path,
+                                            const struct CstOpenOptions *options,
+                                            const struct CstCancelToken *cancel);
+
+// Release a model and every pointer obtained from it. NULL is ignored.
+//
+// # Safety
+//
+// `model` must be NULL or a handle from `cst_model_open` that has not
+// been freed.
+void cst_model_free(struct CstModel *model);
+
+// Number of meshes in the model; 0 for NULL.
+//
+// # Safety
+//
+// `model` must be NULL or a live handle from `cst_model_open`.
+size_t cst_model_mesh_count(const struct CstModel *model);
+
+// Fill `out` with mesh `index`. Returns false when the index is out of
+// range or an argument is NULL.
+//
+// # Safety
+//
+// `model` must be NULL or a live handle, and `out` NULL or writable.
+bool cst_model_mesh(const struct CstModel *model, size_t index, struct CstMesh *out);
+
+// Number of properties of the product with `global_id`; 0 when there is
+// no such product.
+//
+// # Safety
+//
+// `model` must be NULL or a live handle, and `global_id` NULL or a
+// NUL-terminated string.
+size_t cst_model_property_count(const struct CstModel *model, const char *global_id);
+
+// Fill `out` with property `index` of the product with `global_id`.
+// Returns false when there is no such property.
+//
+// # Safety
+//
+// `model` must be NULL or a live handle, `global_id` NULL or a
+// NUL-terminated string, and `out` NULL or writable.
+bool cst_model_property(const struct CstModel *model,
+                        const char *global_id,
+                        size_t index,
+                        struct CstProperty *out);
+
+// Value of the property called `name` of the product with `global_id`,
+// or NULL.
+//
+// # Safety
+//
+// `model` must be NULL or a live handle, and `global_id` and `name` NULL
+// or NUL-terminated strings.
+const char *cst_model_find_property(const struct CstModel *model,
+                                    const char *global_id,
+                                    const char *name);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CST_FFI_H */
@@ -0,0 +1,552 @@
+//! C API for embedding the IFC converter in C, C++ or C# applications.
+//!
+//! A model is opened once, parsed and tessellated, and then read through
+//! plain structs of pointers into buffers owned by the model:
+//!
+//! ```c
+//! #include "cst_ffi.h"
+//!
+//! CstModel *model = cst_model_open("building.ifc", NULL);
+//! if (!model) {
+//!     fprintf(stderr, "%s\n", cst_last_error());
+//!     return 1;
+//! }
+//! for (size_t i = 0; i < cst_model_mesh_count(model); i++) {
+//!     CstMesh mesh;
+//!     cst_model_mesh(model, i, &mesh);
+//!     upload(mesh.positions, mesh.vertex_count, mesh.indices, mesh.index_count);
+//!     const char *rating = cst_model_find_property(model, mesh.global_id, "FireRating");
+//! }
+//! cst_model_free(model);
+//! ```
+//!
+//! A GUI can abort a long conversion: open with `cst_model_open_cancellable`
+//! on a worker thread and call `cst_cancel_token_cancel` from any other.
+//!
+//! Every pointer handed out stays valid until `cst_model_free`. Functions
+//! accept NULL handles and report failure through their return value; the
+//! reason is available from `cst_last_error`. The checked-in header
+//! `include/cst_ffi.h` is generated by cbindgen; rebuild with
+//! `CST_FFI_REGEN_HEADER=1` after changing the exported functions.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::Path;
+use std::ptr;
+
+use cst_ifc::ifc_cache::CachedModel;
+use cst_ifc::ifc_options::IfcPipelineOptions;
+use cst_ifc::ifc_progress::{CancellationToken, NoProgress};
+
+/// Conversion settings for `cst_model_open`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CstOpenOptions {
+    /// Drop faces smaller than a square of this size, in model units;
+    /// 0 keeps all detail
+    pub tolerance: f64,
+    /// Factor applied to every coordinate, e.g. 0.001 for millimetres to
+    /// metres; 0 keeps the model's units
+    pub unit_scale: f64,
+}
+
+/// One tessellated mesh of a model.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CstMesh {
+    pub name: *const c_char,
+    /// IFC GlobalId of the product, or NULL for geometry without one
+    pub global_id: *const c_char,
+    /// Upper-case IFC type such as `IFCWALL`, or NULL
+    pub ifc_type: *const c_char,
+    /// `vertex_count * 3` floats, x y z per vertex
+    pub positions: *const f32,
+    /// `vertex_count * 3` floats, one unit normal per vertex
+    pub normals: *const f32,
+    pub vertex_count: usize,
+    /// Three indices per triangle
+    pub indices: *const u32,
+    pub index_count: usize,
+    /// Linear RGB from the IFC style, valid when `has_color` is set
+    pub color: [f32; 3],
+    pub has_color: bool,
+}
+
+/// A product property as name and value.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CstProperty {
+    pub name: *const c_char,
+    pub value: *const c_char,
+}
+
+struct MeshData {
+    name: CString,
+    global_id: Option<CString>,
+    ifc_type: Option<CString>,
+    positions: Vec<f32>,
+    normals: Vec<f32>,
+    indices: Vec<u32>,
+    color: Option<[f32; 3]>,
+}
+
+/// Shared flag for aborting `cst_model_open_cancellable`. Opaque to C.
+pub struct CstCancelToken(CancellationToken);
+
+/// An opened model. Opaque to C.
+pub struct CstModel {
+    meshes: Vec<MeshData>,
+    /// GlobalId -> (name, value) properties
+    properties: HashMap<String, Vec<(CString, CString)>>,
+}
+
+impl CstModel {
+    fn from_cached(model: CachedModel) -> Self {
+        let flatten = |points: &[cst_math::DVec3]| {
+            points
+                .iter()
+                .flat_map(|p| [p.x as f32, p.y as f32, p.z as f32])
+                .collect()
+        };
+        let meshes = model
+            .meshes
+            .iter()
+            .map(|cached| {
+                let product = cached.product.and_then(|id| model.products.get(&id));
+                MeshData {
+                    name: c_string(&cached.mesh.name),
+                    global_id: product.and_then(|p| p.global_id.as_deref()).map(c_string),
+                    ifc_type: product.map(|p| c_string(&p.ifc_type)),
+                    positions: flatten(&cached.mesh.positions),
+                    normals: flatten(&cached.mesh.normals),
+                    indices: cached.mesh.indices.clone(),
+                    color: cached.color,
+                }
+            })
+            .collect();
+        let properties = model
+            .products
+            .values()
+            .filter_map(|product| {
+                let properties = product
+                    .properties
+                    .iter()
+                    .map(|(name, value)| (c_string(name), c_string(value)))
+                    .collect();
+                Some((product.global_id.clone()?, properties))
+            })
+            .collect();
+        Self { meshes, properties }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+fn set_last_error(message: &str) {
+    LAST_ERROR.with(|error| *error.borrow_mut() = c_string(message));
+}
+
+/// `text` as a C string, dropping interior NUL bytes.
+fn c_string(text: &str) -> CString {
+    CString::new(text.replace('\0', "")).unwrap_or_default()
+}
+
+fn opt_ptr(text: &Option<CString>) -> *const c_char {
+    text.as_ref().map_or(ptr::null(), |text| text.as_ptr())
+}
+
+/// # Safety
+///
+/// `text` must be NULL or point to a NUL-terminated string.
+unsafe fn str_arg<'a>(text: *const c_char) -> Option<&'a str> {
+    if text.is_null() {
+        return None;
+    }
+    CStr::from_ptr(text).to_str().ok()
+}
+
+/// Message of the last failed call on this thread; empty when none failed.
+/// Valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn cst_last_error() -> *const c_char {
+    LAST_ERROR.with(|error| error.borrow().as_ptr())
+}
+
+/// Library version, e.g. `0.1.0`.
+#[no_mangle]
+pub extern "C" fn cst_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+/// Parse and tessellate the IFC file at `path` (UTF-8). `options` may be
+/// NULL for the defaults. Returns NULL on failure; see `cst_last_error`.
+///
+/// # Safety
+///
+/// `path` must be NULL or a NUL-terminated string, and `options` NULL or
+/// a valid `CstOpenOptions`.
+#[no_mangle]
+pub unsafe extern "C" fn cst_model_open(
+    path: *const c_char,
+    options: *const CstOpenOptions,
+) -> *mut CstModel {
+    cst_model_open_cancellable(path, options, ptr::null())
+}
+
+/// A new, uncancelled token. Free it with `cst_cancel_token_free`.
+#[no_mangle]
+pub extern "C" fn cst_cancel_token_new() -> *mut CstCancelToken {
+    Box::into_raw(Box::new(CstCancelToken(CancellationToken::new())))
+}
+
+/// Ask the conversion using `token` to stop. Safe to call from any thread
+/// while the conversion runs. NULL is ignored.
+///
+/// # Safety
+///
+/// `token` must be NULL or a live handle from `cst_cancel_token_new`.
+#[no_mangle]
+pub unsafe extern "C" fn cst_cancel_token_cancel(token: *const CstCancelToken) {
+    if let Some(token) = token.as_ref() {
+        token.0.cancel();
+    }
+}
+
+/// Release a token. NULL is ignored.
+///
+/// # Safety
+///
+/// `token` must be NULL or a handle from `cst_cancel_token_new` that has
+/// not been freed, and no conversion may still be using it.
+#[no_mangle]
+pub unsafe extern "C" fn cst_cancel_token_free(token: *mut CstCancelToken) {
+    if !token.is_null() {
+        drop(Box::from_raw(token));
+    }
+}
+
+/// Like `cst_model_open`, stopping early once `cancel` is cancelled; the
+/// call then returns NULL with `cst_last_error` reporting the
+/// cancellation. `cancel` may be NULL.
+///
+/// # Safety
+///
+/// As for `cst_model_open`; `cancel` must be NULL or a live handle from
+/// `cst_cancel_token_new`.
+#[no_mangle]
+pub unsafe extern "C" fn cst_model_open_cancellable(
+    path: *const c_char,
+    options: *const CstOpenOptions,
+    cancel: *const CstCancelToken,
+) -> *mut CstModel {
+    let Some(path) = str_arg(path) else {
+        set_last_error("path is NULL or not UTF-8");
+        return ptr::null_mut();
+    };
+    let open = options.as_ref().copied().unwrap_or_default();
+    let options = IfcPipelineOptions {
+        tessellation_tolerance: open.tolerance,
+        unit_scale: (open.unit_scale > 0.0).then_some(open.unit_scale),
+        ..Default::default()
+    };
+    // Unwinding into C is undefined, so report panics as errors
+    let result = catch_unwind(AssertUnwindSafe(|| match cancel.as_ref() {
+        Some(token) => CachedModel::build(Path::new(path), &options, &token.0),
+        None => CachedModel::build(Path::new(path), &options, &NoProgress),
+    }));
+    match result {
+        Ok(Ok(model)) => Box::into_raw(Box::new(CstModel::from_cached(model))),
+        Ok(Err(e)) => {
+            set_last_error(&format!("failed to read {}: {}", path, e));
+            ptr::null_mut()
+        }
+        Err(_) => {
+            set_last_error(&format!("internal error while reading {}", path));
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Release a model and every pointer obtained from it. NULL is ignored.
+///
+/// # Safety
+///
+/// `model` must be NULL or a handle from `cst_model_open` that has not
+/// been freed.
+#[no_mangle]
+pub unsafe extern "C" fn cst_model_free(model: *mut CstModel) {
+    if !model.is_null() {
+        drop(Box::from_raw(model));
+    }
+}
+
+/// Number of meshes in the model; 0 for NULL.
+///
+/// # Safety
+///
+/// `model` must be NULL or a live handle from `cst_model_open`.
+#[no_mangle]
+pub unsafe extern "C" fn cst_model_mesh_count(model: *const CstModel) -> usize {
+    model.as_ref().map_or(0, |model| model.meshes.len())
+}
+
+/// Fill `out` with mesh `index`. Returns false when the index is out of
+/// range or an argument is NULL.
+///
+/// # Safety
+///
+/// `model` must be NULL or a live handle, and `out` NULL or writable.
+#[no_mangle]
+pub unsafe extern "C" fn cst_model_mesh(
+    model: *const CstModel,
+    index: usize,
+    out: *mut CstMesh,
+) -> bool {
+    let (Some(model), Some(out)) = (model.as_ref(), out.as_mut()) else {
+        set_last_error("model or out is NULL");
+        return false;
+    };
+    let Some(mesh) = model.meshes.get(index) else {
+        set_last_error(&format!("mesh index {} out of range", index));
+        return false;
+    };
+    *out = CstMesh {
+        name: mesh.name.as_ptr(),
+        global_id: opt_ptr(&mesh.global_id),
+        ifc_type: opt_ptr(&mesh.ifc_type),
+        positions: mesh.positions.as_ptr(),
+        normals: mesh.normals.as_ptr(),
+        vertex_count: mesh.positions.len() / 3,
+        indices: mesh.indices.as_ptr(),
+        index_count: mesh.indices.len(),
+        color: mesh.color.unwrap_or_default(),
+        has_color: mesh.color.is_some(),
+    };
+    true
+}
+
+/// Number of properties of the product with `global_id`; 0 when there is
+/// no such product.
+///
+/// # Safety
+///
+/// `model` must be NULL or a live handle, and `global_id` NULL or a
+/// NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn cst_model_property_count(
+    model: *const CstModel,
+    global_id: *const c_char,
+) -> usize {
+    product_properties(model, global_id).map_or(0, <[_]>::len)
+}
+
+/// Fill `out` with property `index` of the product with `global_id`.
+/// Returns false when there is no such property.
+///
+/// # Safety
+///
+/// `model` must be NULL or a live handle, `global_id` NULL or a
+/// NUL-terminated string, and `out` NULL or writable.
+#[no_mangle]
+pub unsafe extern "C" fn cst_model_property(
+    model: *const CstModel,
+    global_id: *const c_char,
+    index: usize,
+    out: *mut CstProperty,
+) -> bool {
+    let property = product_properties(model, global_id).and_then(|p| p.get(index));
+    match (property, out.as_mut()) {
+        (Some((name, value)), Some(out)) => {
+            *out = CstProperty {
+                name: name.as_ptr(),
+                value: value.as_ptr(),
+            };
+            true
+        }
+        _ => {
+            set_last_error("no such property");
+            false
+        }
+    }
+}
+
+/// Value of the property called `name` of the product with `global_id`,
+/// or NULL.
+///
+/// # Safety
+///
+/// `model` must be NULL or a live handle, and `global_id` and `name` NULL
+/// or NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn cst_model_find_property(
+    model: *const CstModel,
+    global_id: *const c_char,
+    name: *const c_char,
+) -> *const c_char {
+    let Some(name) = str_arg(name) else {
+        return ptr::null();
+    };
+    product_properties(model, global_id)
+        .and_then(|properties| {
+            properties
+                .iter()
+                .find(|(n, _)| n.to_bytes() == name.as_bytes())
+        })
+        .map_or(ptr::null(), |(_, value)| value.as_ptr())
+}
+
+/// # Safety
+///
+/// Same requirements as `cst_model_property_count`.
+unsafe fn product_properties<'a>(
+    model: *const CstModel,
+    global_id: *const c_char,
+) -> Option<&'a [(CString, CString)]> {
+    let model = model.as_ref()?;
+    let global_id = str_arg(global_id)?;
+    model.properties.get(global_id).map(Vec::as_slice)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODEL: &str = "ISO-10303-21;
+HEADER;
+FILE_SCHEMA(('IFC2X3'));
+ENDSEC;
+DATA;
+#1= IFCCARTESIANPOINT((0.,0.,0.));
+#2= IFCCARTESIANPOINT((1000.,0.,0.));
+#3= IFCCARTESIANPOINT((1000.,1000.,0.));
+#4= IFCCARTESIANPOINT((0.,1000.,0.));
+#5= IFCPOLYLOOP((#1,#2,#3,#4));
+#6= IFCFACEOUTERBOUND(#5,.T.);
+#7= IFCFACE((#6));
+#8= IFCCLOSEDSHELL((#7));
+#9= IFCFACETEDBREP(#8);
+#10= IFCSHAPEREPRESENTATION($,'Body','Brep',(#9));
+#11= IFCPRODUCTDEFINITIONSHAPE($,$,(#10));
+#20= IFCWALL('2O2Fr$t4X7Zf8NOew3FLOH',$,'Wall A',$,$,$,#11,$);
+#40= IFCPROPERTYSINGLEVALUE('FireRating',$,IFCLABEL('REI120'),$);
+#41= IFCPROPERTYSET('p1',$,'Pset_WallCommon',$,(#40));
+#42= IFCRELDEFINESBYPROPERTIES('r2',$,$,$,(#20),#41);
+ENDSEC;
+END-ISO-10303-21;
+";
+
+    fn open(options: Option<&CstOpenOptions>) -> *mut CstModel {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("model.ifc");
+        std::fs::write(&path, MODEL).unwrap();
+        let path = CString::new(path.to_str().unwrap()).unwrap();
+        unsafe { cst_model_open(path.as_ptr(), options.map_or(ptr::null(), |o| o)) }
+    }
+
+    fn text(ptr: *const c_char) -> &'static str {
+        assert!(!ptr.is_null());
+        unsafe { CStr::from_ptr(ptr).to_str().unwrap() }
+    }
+
+    #[test]
+    fn test_iterate_meshes() {
+        let options = CstOpenOptions {
+            unit_scale: 0.001,
+            ..Default::default()
+        };
+        let model = open(Some(&options));
+        assert!(!model.is_null());
+        unsafe {
+            assert_eq!(cst_model_mesh_count(model), 1);
+            let mut mesh = std::mem::zeroed::<CstMesh>();
+            assert!(cst_model_mesh(model, 0, &mut mesh));
+            assert_eq!(text(mesh.ifc_type), "IFCWALL");
+            assert_eq!(text(mesh.global_id), "2O2Fr$t4X7Zf8NOew3FLOH");
+            assert_eq!(mesh.vertex_count, 4);
+            assert_eq!(mesh.index_count, 6);
+            let positions = std::slice::from_raw_parts(mesh.positions, mesh.vertex_count * 3);
+            assert!(positions
+                .iter()
+                .all(|&c| c == 0.0 || (c - 1.0).abs() < 1e-6));
+            let indices = std::slice::from_raw_parts(mesh.indices, mesh.index_count);
+            assert!(indices.iter().all(|&i| (i as usize) < mesh.vertex_count));
+
+            assert!(!cst_model_mesh(model, 1, &mut mesh));
+            assert_eq!(text(cst_last_error()), "mesh index 1 out of range");
+            cst_model_free(model);
+        }
+    }
+
+    #[test]
+    fn test_properties() {
+        let model = open(None);
+        let guid = CString::new("2O2Fr$t4X7Zf8NOew3FLOH").unwrap();
+        let name = CString::new("FireRating").unwrap();
+        unsafe {
+            assert_eq!(cst_model_property_count(model, guid.as_ptr()), 1);
+            let mut property = std::mem::zeroed::<CstProperty>();
+            assert!(cst_model_property(model, guid.as_ptr(), 0, &mut property));
+            assert_eq!(text(property.name), "FireRating");
+            assert_eq!(text(property.value), "REI120");
+            let value = cst_model_find_property(model, guid.as_ptr(), name.as_ptr());
+            assert_eq!(text(value), "REI120");
+
+            let unknown = CString::new("nope").unwrap();
+            assert_eq!(cst_model_property_count(model, unknown.as_ptr()), 0);
+            assert!(cst_model_find_property(model, guid.as_ptr(), unknown.as_ptr()).is_null());
+            cst_model_free(model);
+        }
+    }
+
+    #[test]
+    fn test_errors_and_null_handles() {
+        let missing = CString::new("/nonexistent/model.ifc").unwrap();
+        unsafe {
+            assert!(cst_model_open(missing.as_ptr(), ptr::null()).is_null());
+            assert!(text(cst_last_error()).starts_with("failed to read /nonexistent/model.ifc"));
+            assert!(cst_model_open(ptr::null(), ptr::null()).is_null());
+
+            assert_eq!(cst_model_mesh_count(ptr::null()), 0);
+            assert!(!cst_model_mesh(ptr::null(), 0, ptr::null_mut()));
+            assert_eq!(cst_model_property_count(ptr::null(), ptr::null()), 0);
+            cst_model_free(ptr::null_mut());
+        }
+        assert_eq!(text(cst_version()), env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    fn test_cancelled_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("model.ifc");
+        std::fs::write(&path, MODEL).unwrap();
+        let path = CString::new(path.to_str().unwrap()).unwrap();
+        unsafe {
+            let token = cst_cancel_token_new();
+            let model = cst_model_open_cancellable(path.as_ptr(), ptr::null(), token);
+            assert!(!model.is_null());
+            cst_model_free(model);
+
+            cst_cancel_token_cancel(token);
+            let model = cst_model_open_cancellable(path.as_ptr(), ptr::null(), token);
+            assert!(model.is_null());
+            assert!(text(cst_last_error()).ends_with("Operation cancelled"));
+            cst_cancel_token_free(token);
+            cst_cancel_token_cancel(ptr::null());
+            cst_cancel_token_free(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn test_checked_in_header_is_current() {
+        let generated = std::fs::read_to_string(concat!(env!("OUT_DIR"), "/cst_ffi.h")).unwrap();
+        let checked_in = include_str!("../include/cst_ffi.h");
+        assert!(
+            generated == checked_in,
+            "include/cst_ffi.h is stale; rebuild with CST_FFI_REGEN_HEADER=1"
+        );
+    }
+}
@@ -0,0 +1,141 @@
+//! Circular arc curve.
+
+use cst_math::{Point3, Vector3};
+use serde::{Deserialize, Serialize};
+
+use super::Curve;
+
+/// A circular arc, parameterized by angle over `[start_angle, end_angle]`.
+///
+/// Angles are measured from `x_axis` towards `y_axis`; the arc runs
+/// counter-clockwise about `x_axis × y_axis` when `end_angle > start_angle`
+/// and clockwise otherwise.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircularArc {
+    pub center: Point3,
+    pub x_axis: Vector3,
+    pub y_axis: Vector3,
+    pub radius: f64,
+    pub start_angle: f64,
+    pub end_angle: f64,
+}
+
+impl CircularArc {
+    /// Arc in the plane through `center` with normal `normal`, angles
+    /// measured from `ref_direction` (projected into that plane).
+    pub fn new(
+        center: Point3,
+        normal: Vector3,
+        ref_direction: Vector3,
+        radius: f64,
+        start_angle: f64,
+        end_angle: f64,
+    ) -> Self {
+        let normal = normal.normalize();
+        let x_axis = (ref_direction - ref_direction.dot(normal) * normal).normalize();
+        Self {
+            center,
+            x_axis,
+            y_axis: normal.cross(x_axis),
+            radius,
+            start_angle,
+            end_angle,
+        }
+    }
+
+    /// The arc from `start` through `mid` to `end`, or `None` when the
+    /// points are collinear or coincide.
+    pub fn through(start: Point3, mid: Point3, end: Point3) -> Option<Self> {
+        let a = start - end;
+        let b = mid - end;
+        let normal = a.cross(b);
+        let denom = 2.0 * normal.length_squared();
+        if denom <= f64::EPSILON * a.length_squared() * b.length_squared() {
+            return None;
+        }
+        // Circumcenter of the triangle (start, mid, end)
+        let center = end + (a.length_squared() * b - b.length_squared() * a).cross(normal) / denom;
+        let x_axis = (start - center).normalize();
+        let y_axis = normal.normalize().cross(x_axis);
+        let angle_of = |p: Point3| {
+            let d = p - center;
+            let angle = d.dot(y_axis).atan2(d.dot(x_axis));
+            if angle < 0.0 {
+                angle + std::f64::consts::TAU
+            } else {
+                angle
+            }
+        };
+        Some(Self {
+            center,
+            x_axis,
+            y_axis,
+            radius: (start - center).length(),
+            start_angle: 0.0,
+            end_angle: angle_of(end),
+        })
+    }
+
+    /// Run from the end angle back to the start angle. Every parameter
+    /// keeps its point; the domain runs the other way.
+    pub fn reverse(&mut self) {
+        std::mem::swap(&mut self.start_angle, &mut self.end_angle);
+    }
+}
+
+impl Curve for CircularArc {
+    fn point_at(&self, t: f64) -> Point3 {
+        self.center + self.radius * (t.cos() * self.x_axis + t.sin() * self.y_axis)
+    }
+
+    fn tangent_at(&self, t: f64) -> Vector3 {
+        let tangent = self.radius * (-t.sin() * self.x_axis + t.cos() * self.y_axis);
+        if self.end_angle < self.start_angle {
+            -tangent
+        } else {
+            tangent
+        }
+    }
+
+    fn domain(&self) -> (f64, f64) {
+        (self.start_angle, self.end_angle)
+    }
+
+    fn is_closed(&self) -> bool {
+        (self.end_angle - self.start_angle).abs() >= std::f64::consts::TAU
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cst_math::DVec3;
+    use std::f64::consts::FRAC_PI_2;
+
+    #[test]
+    fn test_arc_through_three_points() {
+        let arc = CircularArc::through(
+            DVec3::new(1.0, 0.0, 0.0),
+            DVec3::new(0.0, 1.0, 0.0),
+            DVec3::new(-1.0, 0.0, 0.0),
+        )
+        .unwrap();
+        assert!(arc.center.length() < 1e-10);
+        assert!((arc.radius - 1.0).abs() < 1e-10);
+        assert!((arc.point_at(FRAC_PI_2) - DVec3::new(0.0, 1.0, 0.0)).length() < 1e-10);
+        let (t0, t1) = arc.domain();
+        assert!((arc.point_at(t1) - DVec3::new(-1.0, 0.0, 0.0)).length() < 1e-10);
+        assert!((t1 - t0 - std::f64::consts::PI).abs() < 1e-10);
+
+        let collinear = CircularArc::through(DVec3::ZERO, DVec3::X, 2.0 * DVec3::X);
+        assert!(collinear.is_none());
+    }
+
+    #[test]
+    fn test_arc_angles_from_ref_direction() {
+        let arc = CircularArc::new(DVec3::ZERO, DVec3::Z, DVec3::Y, 2.0, 0.0, FRAC_PI_2);
+        assert!((arc.point_at(0.0) - DVec3::new(0.0, 2.0, 0.0)).length() < 1e-10);
+        assert!((arc.point_at(FRAC_PI_2) - DVec3::new(-2.0, 0.0, 0.0)).length() < 1e-10);
+        assert!(!arc.is_closed());
+    }
+}
@@ -4,7 +4,9 @@ use cst_math::{Point3, Vector3};
 use serde::{Deserialize, Serialize};
 
 use super::Curve;
-use crate::nurbs::deboor;
+use crate::nurbs::{
+    batch, deboor, elevate_degree, insert_knots, reparameterize_knots, reverse_knots,
+};
 
 /// A B-spline curve defined by degree, knot vector, and control points.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,6 +31,38 @@ impl BSplineCurve {
             control_points,
         }
     }
+
+    /// Run the other way over the same domain: `t` of the reversed curve
+    /// is `t0 + t1 - t` of this one.
+    pub fn reverse(&mut self) {
+        reverse_knots(self.degree, &mut self.knots);
+        self.control_points.reverse();
+    }
+
+    /// Map the domain linearly onto `[a, b]`, with `a < b`.
+    pub fn reparameterize(&mut self, a: f64, b: f64) {
+        reparameterize_knots(self.degree, &mut self.knots, a, b);
+    }
+
+    /// Insert `knots`, in non-decreasing order and inside the domain,
+    /// without changing the shape.
+    pub fn insert_knots(&mut self, knots: &[f64]) {
+        (self.knots, self.control_points) =
+            insert_knots(self.degree, &self.knots, &self.control_points, knots);
+    }
+
+    /// Raise the degree by `times` without changing the shape.
+    pub fn elevate_degree(&mut self, times: usize) {
+        (self.knots, self.control_points) =
+            elevate_degree(self.degree, &self.knots, &self.control_points, times);
+        self.degree += times;
+    }
+
+    /// The point and its derivatives up to `order` at `t`; element `k` is
+    /// the k-th derivative.
+    pub fn derivatives_at(&self, t: f64, order: usize) -> Vec<Vector3> {
+        deboor::curve_derivatives(self.degree, &self.knots, &self.control_points, t, order)
+    }
 }
 
 impl Curve for BSplineCurve {
@@ -40,6 +74,10 @@ impl Curve for BSplineCurve {
         deboor::curve_tangent(self.degree, &self.knots, &self.control_points, t)
     }
 
+    fn points_at(&self, ts: &[f64]) -> Vec<Point3> {
+        batch::curve_points(self.degree, &self.knots, &self.control_points, ts)
+    }
+
     fn domain(&self) -> (f64, f64) {
         let p = self.degree;
         (self.knots[p], self.knots[self.knots.len() - p - 1])
@@ -83,6 +121,32 @@ impl NurbsCurve {
             weights,
         }
     }
+
+    /// Run the other way over the same domain: `t` of the reversed curve
+    /// is `t0 + t1 - t` of this one.
+    pub fn reverse(&mut self) {
+        reverse_knots(self.degree, &mut self.knots);
+        self.control_points.reverse();
+        self.weights.reverse();
+    }
+
+    /// Map the domain linearly onto `[a, b]`, with `a < b`.
+    pub fn reparameterize(&mut self, a: f64, b: f64) {
+        reparameterize_knots(self.degree, &mut self.knots, a, b);
+    }
+
+    /// The point and its derivatives up to `order` at `t`; element `k` is
+    /// the k-th derivative.
+    pub fn derivatives_at(&self, t: f64, order: usize) -> Vec<Vector3> {
+        deboor::nurbs_curve_derivatives(
+            self.degree,
+            &self.knots,
+            &self.control_points,
+            &self.weights,
+            t,
+            order,
+        )
+    }
 }
 
 impl Curve for NurbsCurve {
@@ -106,6 +170,16 @@ impl Curve for NurbsCurve {
         )
     }
 
+    fn points_at(&self, ts: &[f64]) -> Vec<Point3> {
+        batch::nurbs_curve_points(
+            self.degree,
+            &self.knots,
+            &self.control_points,
+            &self.weights,
+            ts,
+        )
+    }
+
     fn domain(&self) -> (f64, f64) {
         let p = self.degree;
         (self.knots[p], self.knots[self.knots.len() - p - 1])
@@ -195,6 +269,17 @@ mod tests {
             );
             assert!(p.z.abs() < 1e-10);
         }
+
+        // Curvature is continuous across the double knots where the arcs
+        // meet, although the second derivative is not
+        let curvature = |t: f64| {
+            let ders = curve.derivatives_at(t, 2);
+            ders[1].cross(ders[2]).length() / ders[1].length().powi(3)
+        };
+        for knot in [0.25, 0.5, 0.75] {
+            assert!((curvature(knot - 1e-12) - 1.0).abs() < 1e-9);
+            assert!((curvature(knot) - 1.0).abs() < 1e-9);
+        }
     }
 
     #[test]
@@ -212,4 +297,51 @@ mod tests {
         assert!(t.x > 0.0);
         assert!(t.y.abs() < 1e-10);
     }
+
+    #[test]
+    fn test_reverse_and_reparameterize() {
+        let knots = vec![0.0, 0.0, 0.0, 0.5, 2.0, 2.0, 2.0];
+        let points = vec![
+            DVec3::new(0.0, 0.0, 0.0),
+            DVec3::new(1.0, 2.0, 0.0),
+            DVec3::new(3.0, 1.0, 1.0),
+            DVec3::new(4.0, 0.0, 2.0),
+        ];
+        let bspline = BSplineCurve::new(2, knots.clone(), points.clone());
+        let nurbs = NurbsCurve::new(2, knots, points, vec![1.0, 0.5, 2.0, 1.0]);
+        let curves: [&dyn Curve; 2] = [&bspline, &nurbs];
+
+        let mut reversed_bspline = bspline.clone();
+        reversed_bspline.reverse();
+        let mut reversed_nurbs = nurbs.clone();
+        reversed_nurbs.reverse();
+        let reversed: [&dyn Curve; 2] = [&reversed_bspline, &reversed_nurbs];
+        for (curve, reversed) in curves.iter().zip(reversed) {
+            assert_eq!(reversed.domain(), (0.0, 2.0));
+            for i in 0..=10 {
+                let t = 0.2 * i as f64;
+                let p = curve.point_at(2.0 - t);
+                assert!((reversed.point_at(t) - p).length() < 1e-12);
+                let tangent = -curve.tangent_at(2.0 - t);
+                assert!((reversed.tangent_at(t) - tangent).length() < 1e-12);
+            }
+        }
+
+        // Onto [10, 14]: the same points, the tangent halved
+        let mut moved_bspline = bspline.clone();
+        moved_bspline.reparameterize(10.0, 14.0);
+        let mut moved_nurbs = nurbs.clone();
+        moved_nurbs.reparameterize(10.0, 14.0);
+        let moved: [&dyn Curve; 2] = [&moved_bspline, &moved_nurbs];
+        for (curve, moved) in curves.iter().zip(moved) {
+            assert_eq!(moved.domain(), (10.0, 14.0));
+            for i in 0..=10 {
+                let t = 0.2 * i as f64;
+                let s = 10.0 + 2.0 * t;
+                assert!((moved.point_at(s) - curve.point_at(t)).length() < 1e-12);
+                let tangent = curve.tangent_at(t) / 2.0;
+                assert!((moved.tangent_at(s) - tangent).length() < 1e-12);
+            }
+        }
+    }
 }
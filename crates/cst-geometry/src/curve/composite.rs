@@ -0,0 +1,137 @@
+//! Composite curve of line and arc segments.
+
+use cst_math::{Point3, Vector3};
+use serde::{Deserialize, Serialize};
+
+use super::{CircularArc, Curve, Line};
+
+/// One segment of a [`CompositeCurve`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum CurveSegment {
+    Line(Line),
+    Arc(CircularArc),
+}
+
+impl CurveSegment {
+    pub fn as_curve(&self) -> &dyn Curve {
+        match self {
+            CurveSegment::Line(line) => line,
+            CurveSegment::Arc(arc) => arc,
+        }
+    }
+
+    pub fn reverse(&mut self) {
+        match self {
+            CurveSegment::Line(line) => line.reverse(),
+            CurveSegment::Arc(arc) => arc.reverse(),
+        }
+    }
+}
+
+/// Segments joined end to end, e.g. the boundary of a profile with rounded
+/// corners. Segment `i` covers the parameters `[i, i + 1]`; an empty curve
+/// cannot be evaluated.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CompositeCurve {
+    pub segments: Vec<CurveSegment>,
+}
+
+impl CompositeCurve {
+    pub fn new(segments: Vec<CurveSegment>) -> Self {
+        Self { segments }
+    }
+
+    /// Run from the end to the start, e.g. for a segment whose sense flag
+    /// is false. `t` of the reversed curve is `n - t` of this one.
+    pub fn reverse(&mut self) {
+        self.segments.reverse();
+        for segment in &mut self.segments {
+            segment.reverse();
+        }
+    }
+
+    /// The segment at `t` and the parameter within it.
+    fn locate(&self, t: f64) -> (&dyn Curve, f64) {
+        let last = self.segments.len() - 1;
+        let index = (t.max(0.0).floor() as usize).min(last);
+        let curve = self.segments[index].as_curve();
+        let (t0, t1) = curve.domain();
+        (curve, t0 + (t - index as f64) * (t1 - t0))
+    }
+}
+
+impl Curve for CompositeCurve {
+    fn point_at(&self, t: f64) -> Point3 {
+        let (curve, local) = self.locate(t);
+        curve.point_at(local)
+    }
+
+    fn tangent_at(&self, t: f64) -> Vector3 {
+        let (curve, local) = self.locate(t);
+        let (t0, t1) = curve.domain();
+        curve.tangent_at(local) * (t1 - t0)
+    }
+
+    fn domain(&self) -> (f64, f64) {
+        (0.0, self.segments.len() as f64)
+    }
+
+    fn is_closed(&self) -> bool {
+        let (t0, t1) = self.domain();
+        !self.segments.is_empty()
+            && (self.point_at(t0) - self.point_at(t1)).length()
+                <= cst_core::Tolerance::DEFAULT_LINEAR
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cst_math::DVec3;
+
+    #[test]
+    fn test_composite_of_line_and_arc() {
+        // A half disc: diameter from (-1,0) to (1,0), then the upper arc back
+        let curve = CompositeCurve::new(vec![
+            CurveSegment::Line(Line::new(
+                DVec3::new(-1.0, 0.0, 0.0),
+                DVec3::new(1.0, 0.0, 0.0),
+            )),
+            CurveSegment::Arc(
+                CircularArc::through(
+                    DVec3::new(1.0, 0.0, 0.0),
+                    DVec3::new(0.0, 1.0, 0.0),
+                    DVec3::new(-1.0, 0.0, 0.0),
+                )
+                .unwrap(),
+            ),
+        ]);
+        assert_eq!(curve.domain(), (0.0, 2.0));
+        assert!((curve.point_at(0.5) - DVec3::ZERO).length() < 1e-10);
+        assert!((curve.point_at(1.5) - DVec3::new(0.0, 1.0, 0.0)).length() < 1e-10);
+        assert!(curve.is_closed());
+    }
+
+    #[test]
+    fn test_reverse_composite() {
+        let curve = CompositeCurve::new(vec![
+            CurveSegment::Line(Line::new(DVec3::ZERO, DVec3::X)),
+            CurveSegment::Arc(CircularArc::new(
+                DVec3::new(1.0, 1.0, 0.0),
+                DVec3::Z,
+                DVec3::NEG_Y,
+                1.0,
+                0.0,
+                std::f64::consts::FRAC_PI_2,
+            )),
+        ]);
+        let mut reversed = curve.clone();
+        reversed.reverse();
+        assert_eq!(reversed.domain(), (0.0, 2.0));
+        for i in 0..=8 {
+            let t = i as f64 / 4.0;
+            assert!((reversed.point_at(t) - curve.point_at(2.0 - t)).length() < 1e-10);
+        }
+        assert!((reversed.point_at(0.0) - DVec3::new(2.0, 1.0, 0.0)).length() < 1e-10);
+    }
+}
@@ -29,6 +29,12 @@ impl Ellipse {
         }
     }
 
+    /// Run the other way round from the same start point, so that `t` of
+    /// the reversed ellipse is `2*PI - t` of this one.
+    pub fn reverse(&mut self) {
+        self.normal = -self.normal;
+    }
+
     /// Major radius (length of major_axis).
     pub fn major_radius(&self) -> f64 {
         self.major_axis.length()
@@ -117,4 +123,15 @@ mod tests {
         let ellipse = Ellipse::new(DVec3::ZERO, DVec3::Z, DVec3::X, 1.0);
         assert!(ellipse.is_closed());
     }
+
+    #[test]
+    fn test_ellipse_reverse() {
+        let ellipse = Ellipse::new(DVec3::ZERO, DVec3::Z, DVec3::new(2.0, 0.0, 0.0), 1.0);
+        let mut reversed = ellipse.clone();
+        reversed.reverse();
+        for i in 0..=8 {
+            let t = i as f64 * PI / 4.0;
+            assert!((reversed.point_at(t) - ellipse.point_at(2.0 * PI - t)).length() < 1e-10);
+        }
+    }
 }
@@ -16,6 +16,11 @@ impl Line {
     pub fn new(start: Point3, end: Point3) -> Self {
         Self { start, end }
     }
+
+    /// Run from `end` to `start` instead.
+    pub fn reverse(&mut self) {
+        std::mem::swap(&mut self.start, &mut self.end);
+    }
 }
 
 impl Curve for Line {
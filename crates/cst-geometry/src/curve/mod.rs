@@ -1,16 +1,22 @@
 //! Curve traits and implementations.
 
 mod line;
+mod arc;
 mod circle;
+mod composite;
 mod ellipse;
 mod bspline;
+mod reparameterized;
 
 use cst_math::{Point3, Vector3};
 
 pub use line::Line;
+pub use arc::CircularArc;
 pub use circle::Circle;
+pub use composite::{CompositeCurve, CurveSegment};
 pub use ellipse::Ellipse;
 pub use bspline::{BSplineCurve, NurbsCurve};
+pub use reparameterized::ReparameterizedCurve;
 
 /// Trait for parametric curves in 3D space.
 pub trait Curve: Send + Sync {
@@ -23,6 +29,12 @@ pub trait Curve: Send + Sync {
     /// Return the parameter domain `(t_min, t_max)`.
     fn domain(&self) -> (f64, f64);
 
+    /// Evaluate the curve at each parameter in `ts`. B-spline curves share
+    /// span searches and basis evaluations across the batch.
+    fn points_at(&self, ts: &[f64]) -> Vec<Point3> {
+        ts.iter().map(|&t| self.point_at(t)).collect()
+    }
+
     /// Whether the curve is closed (start == end).
     fn is_closed(&self) -> bool {
         false
@@ -0,0 +1,113 @@
+//! Reversal and linear reparameterization of any curve.
+
+use cst_math::{Point3, Vector3};
+use serde::{Deserialize, Serialize};
+
+use super::Curve;
+
+/// A curve evaluated over a different domain, optionally in the other
+/// direction.
+///
+/// For curves whose parameterization is fixed by their definition, such as
+/// a [`Circle`](super::Circle) over `[0, 2*PI]`. Lines, arcs, ellipses,
+/// composite and B-spline curves can also reverse themselves in place.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReparameterizedCurve<C> {
+    pub curve: C,
+    /// Domain of the reparameterized curve, increasing or decreasing like
+    /// the domain of the wrapped curve
+    pub domain: (f64, f64),
+    /// Whether the start of `domain` maps to the end of the wrapped domain
+    pub reversed: bool,
+}
+
+impl<C: Curve> ReparameterizedCurve<C> {
+    /// The curve over its own domain.
+    pub fn new(curve: C) -> Self {
+        let domain = curve.domain();
+        Self {
+            curve,
+            domain,
+            reversed: false,
+        }
+    }
+
+    /// Run the other way over the same domain: `t` of the reversed curve is
+    /// `t0 + t1 - t` of the curve before.
+    pub fn reverse(&mut self) {
+        self.reversed = !self.reversed;
+    }
+
+    /// Map the domain linearly onto `[a, b]`.
+    pub fn reparameterize(&mut self, a: f64, b: f64) {
+        self.domain = (a, b);
+    }
+
+    /// Parameter of the wrapped curve at `t`, and its rate of change.
+    fn inner(&self, t: f64) -> (f64, f64) {
+        let (a, b) = self.domain;
+        let (t0, t1) = self.curve.domain();
+        let (t0, t1) = if self.reversed { (t1, t0) } else { (t0, t1) };
+        let scale = (t1 - t0) / (b - a);
+        (t0 + (t - a) * scale, scale)
+    }
+}
+
+impl<C: Curve> Curve for ReparameterizedCurve<C> {
+    fn point_at(&self, t: f64) -> Point3 {
+        self.curve.point_at(self.inner(t).0)
+    }
+
+    fn tangent_at(&self, t: f64) -> Vector3 {
+        let (inner, scale) = self.inner(t);
+        self.curve.tangent_at(inner) * scale
+    }
+
+    fn domain(&self) -> (f64, f64) {
+        self.domain
+    }
+
+    fn points_at(&self, ts: &[f64]) -> Vec<Point3> {
+        let inner: Vec<f64> = ts.iter().map(|&t| self.inner(t).0).collect();
+        self.curve.points_at(&inner)
+    }
+
+    fn is_closed(&self) -> bool {
+        self.curve.is_closed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::Circle;
+    use cst_math::DVec3;
+    use std::f64::consts::TAU;
+
+    #[test]
+    fn test_reversed_and_reparameterized_circle() {
+        let circle = Circle::new(DVec3::ZERO, DVec3::Z, 2.0);
+        let mut curve = ReparameterizedCurve::new(circle.clone());
+        curve.reparameterize(0.0, 1.0);
+        curve.reverse();
+        assert_eq!(curve.domain(), (0.0, 1.0));
+        assert!(curve.is_closed());
+
+        for i in 0..=8 {
+            let s = i as f64 / 8.0;
+            let t = TAU * (1.0 - s);
+            assert!((curve.point_at(s) - circle.point_at(t)).length() < 1e-12);
+            let tangent = -TAU * circle.tangent_at(t);
+            assert!((curve.tangent_at(s) - tangent).length() < 1e-12);
+        }
+        let ts = [0.0, 0.3, 1.0];
+        let points = curve.points_at(&ts);
+        for (p, &t) in points.iter().zip(&ts) {
+            assert!((*p - curve.point_at(t)).length() < 1e-12);
+        }
+
+        // Reversing twice restores the direction
+        curve.reverse();
+        assert!((curve.point_at(0.25) - circle.point_at(TAU / 4.0)).length() < 1e-12);
+    }
+}
@@ -2,8 +2,10 @@
 
 pub mod curve;
 pub mod nurbs;
+pub mod pool;
 pub mod surface;
 pub mod tessellate;
 
 pub use curve::Curve;
-pub use surface::Surface;
+pub use pool::GeometryPool;
+pub use surface::{Surface, SurfaceKey};
@@ -0,0 +1,309 @@
+//! Batched B-spline and NURBS evaluation.
+//!
+//! Evaluating point by point searches the knot span and rebuilds the basis
+//! functions on every call. Tessellation evaluates whole rows and grids of
+//! parameters, so here the span and basis values of each parameter are
+//! computed once into a [`BasisTable`] and shared. On a surface grid the
+//! u-basis of a row blends the control net into a single curve, which the
+//! v-basis then evaluates, so each grid point costs `degree_v + 1` control
+//! point products instead of `(degree_u + 1) * (degree_v + 1)`. The row
+//! blend is a plain multiply-add over contiguous control points, which
+//! leaves it to the compiler to vectorize.
+
+use cst_math::{DVec3, DVec4, Point3};
+
+use super::knot::find_span;
+
+/// Knot spans and non-vanishing basis functions of a list of parameters.
+#[derive(Debug, Clone)]
+pub struct BasisTable {
+    degree: usize,
+    spans: Vec<usize>,
+    /// `degree + 1` values per parameter
+    values: Vec<f64>,
+}
+
+impl BasisTable {
+    /// Evaluate the basis at every parameter in `params`; `n` is the number
+    /// of control points minus 1, as for [`find_span`].
+    pub fn new(degree: usize, knots: &[f64], n: usize, params: &[f64]) -> Self {
+        let mut spans = Vec::with_capacity(params.len());
+        let mut values = vec![0.0; params.len() * (degree + 1)];
+        let mut left = vec![0.0; degree + 1];
+        let mut right = vec![0.0; degree + 1];
+        let mut span = degree;
+
+        for (t, basis) in params.iter().zip(values.chunks_exact_mut(degree + 1)) {
+            let t = *t;
+            // Increasing parameters mostly stay in the previous span
+            let same_span = t > knots[degree] && knots[span] <= t && t < knots[span + 1];
+            if !same_span {
+                span = find_span(degree, knots, n, t);
+            }
+            spans.push(span);
+
+            basis[0] = 1.0;
+            for j in 1..=degree {
+                left[j] = t - knots[span + 1 - j];
+                right[j] = knots[span + j] - t;
+                let mut saved = 0.0;
+                for r in 0..j {
+                    let temp = basis[r] / (right[r + 1] + left[j - r]);
+                    basis[r] = saved + right[r + 1] * temp;
+                    saved = left[j - r] * temp;
+                }
+                basis[j] = saved;
+            }
+        }
+
+        Self {
+            degree,
+            spans,
+            values,
+        }
+    }
+
+    /// Number of parameters.
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// Knot span of parameter `k`.
+    pub fn span(&self, k: usize) -> usize {
+        self.spans[k]
+    }
+
+    /// Basis functions N_{span-degree}..N_{span} at parameter `k`.
+    pub fn basis(&self, k: usize) -> &[f64] {
+        let width = self.degree + 1;
+        &self.values[k * width..(k + 1) * width]
+    }
+
+    /// Index of the first control point that parameter `k` depends on.
+    fn first(&self, k: usize) -> usize {
+        self.spans[k] - self.degree
+    }
+}
+
+/// Evaluate a B-spline curve at every parameter in `ts`, like
+/// [`curve_point`](super::curve_point).
+pub fn curve_points(
+    degree: usize,
+    knots: &[f64],
+    control_points: &[Point3],
+    ts: &[f64],
+) -> Vec<Point3> {
+    let table = BasisTable::new(degree, knots, control_points.len() - 1, ts);
+    (0..ts.len())
+        .map(|k| blend(table.basis(k), &control_points[table.first(k)..]))
+        .collect()
+}
+
+/// Evaluate a NURBS curve at every parameter in `ts`, like
+/// [`nurbs_curve_point`](super::nurbs_curve_point).
+pub fn nurbs_curve_points(
+    degree: usize,
+    knots: &[f64],
+    control_points: &[Point3],
+    weights: &[f64],
+    ts: &[f64],
+) -> Vec<Point3> {
+    let table = BasisTable::new(degree, knots, control_points.len() - 1, ts);
+    let homogeneous = weighted(control_points, weights);
+    (0..ts.len())
+        .map(|k| project(blend4(table.basis(k), &homogeneous[table.first(k)..])))
+        .collect()
+}
+
+/// Evaluate a B-spline surface on the grid `us` × `vs`, like
+/// [`surface_point`](super::surface_point).
+///
+/// Points are in row-major order: the point at `(us[i], vs[j])` is at
+/// `i * vs.len() + j`.
+pub fn surface_points(
+    degree_u: usize,
+    degree_v: usize,
+    knots_u: &[f64],
+    knots_v: &[f64],
+    control_points: &[Vec<Point3>],
+    us: &[f64],
+    vs: &[f64],
+) -> Vec<Point3> {
+    let table_u = BasisTable::new(degree_u, knots_u, control_points.len() - 1, us);
+    let table_v = BasisTable::new(degree_v, knots_v, control_points[0].len() - 1, vs);
+
+    let mut points = Vec::with_capacity(us.len() * vs.len());
+    let mut row = vec![DVec3::ZERO; control_points[0].len()];
+    for i in 0..us.len() {
+        // The control rows blended at this u form a curve in v
+        row.fill(DVec3::ZERO);
+        let rows = &control_points[table_u.first(i)..];
+        for (&b, control_row) in table_u.basis(i).iter().zip(rows) {
+            for (r, &p) in row.iter_mut().zip(control_row) {
+                *r += b * p;
+            }
+        }
+        points.extend((0..vs.len()).map(|j| blend(table_v.basis(j), &row[table_v.first(j)..])));
+    }
+    points
+}
+
+/// Evaluate a NURBS surface on the grid `us` × `vs`, like
+/// [`nurbs_surface_point`](super::nurbs_surface_point), in the order of
+/// [`surface_points`].
+#[allow(clippy::too_many_arguments)]
+pub fn nurbs_surface_points(
+    degree_u: usize,
+    degree_v: usize,
+    knots_u: &[f64],
+    knots_v: &[f64],
+    control_points: &[Vec<Point3>],
+    weights: &[Vec<f64>],
+    us: &[f64],
+    vs: &[f64],
+) -> Vec<Point3> {
+    let table_u = BasisTable::new(degree_u, knots_u, control_points.len() - 1, us);
+    let table_v = BasisTable::new(degree_v, knots_v, control_points[0].len() - 1, vs);
+    let homogeneous: Vec<Vec<DVec4>> = control_points
+        .iter()
+        .zip(weights)
+        .map(|(points, weights)| weighted(points, weights))
+        .collect();
+
+    let mut points = Vec::with_capacity(us.len() * vs.len());
+    let mut row = vec![DVec4::ZERO; control_points[0].len()];
+    for i in 0..us.len() {
+        row.fill(DVec4::ZERO);
+        let rows = &homogeneous[table_u.first(i)..];
+        for (&b, control_row) in table_u.basis(i).iter().zip(rows) {
+            for (r, &p) in row.iter_mut().zip(control_row) {
+                *r += b * p;
+            }
+        }
+        points.extend(
+            (0..vs.len()).map(|j| project(blend4(table_v.basis(j), &row[table_v.first(j)..]))),
+        );
+    }
+    points
+}
+
+/// Sum of `basis[i] * points[i]`.
+fn blend(basis: &[f64], points: &[Point3]) -> Point3 {
+    basis
+        .iter()
+        .zip(points)
+        .fold(DVec3::ZERO, |sum, (&b, &p)| sum + b * p)
+}
+
+fn blend4(basis: &[f64], points: &[DVec4]) -> DVec4 {
+    basis
+        .iter()
+        .zip(points)
+        .fold(DVec4::ZERO, |sum, (&b, &p)| sum + b * p)
+}
+
+/// Control points as `(w·x, w·y, w·z, w)`.
+fn weighted(points: &[Point3], weights: &[f64]) -> Vec<DVec4> {
+    points
+        .iter()
+        .zip(weights)
+        .map(|(&p, &w)| (p * w).extend(w))
+        .collect()
+}
+
+/// Back from homogeneous coordinates, leaving points with no weight as they
+/// are.
+fn project(h: DVec4) -> Point3 {
+    if h.w.abs() < 1e-15 {
+        h.truncate()
+    } else {
+        h.truncate() / h.w
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nurbs::{
+        basis_functions, curve_point, nurbs_curve_point, nurbs_surface_point, surface_point,
+    };
+
+    /// Cubic knots over `[0, 3]` with a double knot at 1.5, for 6 control
+    /// points.
+    fn knots() -> Vec<f64> {
+        vec![0.0, 0.0, 0.0, 0.0, 1.5, 1.5, 3.0, 3.0, 3.0, 3.0]
+    }
+
+    fn params(n: usize) -> Vec<f64> {
+        (0..=n).map(|i| 3.0 * i as f64 / n as f64).collect()
+    }
+
+    #[test]
+    fn test_basis_table_matches_single_evaluation() {
+        let knots = knots();
+        // Out of order, on knots and at both ends
+        let ts = [0.0, 2.5, 1.0, 1.2, 1.5, 0.3, 3.0, 2.999];
+        let table = BasisTable::new(3, &knots, 5, &ts);
+        assert_eq!(table.len(), ts.len());
+        for (k, &t) in ts.iter().enumerate() {
+            let span = find_span(3, &knots, 5, t);
+            assert_eq!(table.span(k), span);
+            let expected = basis_functions(3, &knots, span, t);
+            for (a, b) in table.basis(k).iter().zip(&expected) {
+                assert!((a - b).abs() < 1e-14);
+            }
+        }
+    }
+
+    #[test]
+    fn test_curve_points_match_single_evaluation() {
+        let knots = knots();
+        let points: Vec<Point3> = (0..6)
+            .map(|i| DVec3::new(i as f64, (i * i % 5) as f64, (i % 2) as f64))
+            .collect();
+        let weights = [1.0, 0.5, 2.0, 1.0, 0.8, 1.0];
+        let ts = params(40);
+
+        let batch = curve_points(3, &knots, &points, &ts);
+        let rational = nurbs_curve_points(3, &knots, &points, &weights, &ts);
+        for (k, &t) in ts.iter().enumerate() {
+            assert!((batch[k] - curve_point(3, &knots, &points, t)).length() < 1e-12);
+            let single = nurbs_curve_point(3, &knots, &points, &weights, t);
+            assert!((rational[k] - single).length() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_surface_points_match_single_evaluation() {
+        let knots_u = knots();
+        let knots_v = vec![0.0, 0.0, 0.0, 1.5, 3.0, 3.0, 3.0];
+        let control_points: Vec<Vec<Point3>> = (0..6)
+            .map(|i| {
+                (0..4)
+                    .map(|j| DVec3::new(i as f64, j as f64, ((i + 2 * j) % 3) as f64))
+                    .collect()
+            })
+            .collect();
+        let weights: Vec<Vec<f64>> = (0..6)
+            .map(|i| (0..4).map(|j| 1.0 + 0.25 * ((i + j) % 3) as f64).collect())
+            .collect();
+        let (us, vs) = (params(12), params(7));
+
+        let cps = &control_points;
+        let plain = surface_points(3, 2, &knots_u, &knots_v, cps, &us, &vs);
+        let rational = nurbs_surface_points(3, 2, &knots_u, &knots_v, cps, &weights, &us, &vs);
+        assert_eq!(plain.len(), us.len() * vs.len());
+        for (i, &u) in us.iter().enumerate() {
+            for (j, &v) in vs.iter().enumerate() {
+                let single = surface_point(3, 2, &knots_u, &knots_v, cps, u, v);
+                assert!((plain[i * vs.len() + j] - single).length() < 1e-12);
+                let single = nurbs_surface_point(3, 2, &knots_u, &knots_v, cps, &weights, u, v);
+                assert!((rational[i * vs.len() + j] - single).length() < 1e-12);
+            }
+        }
+    }
+}
@@ -1,8 +1,12 @@
 //! De Boor algorithm for B-spline and NURBS evaluation.
 
-use cst_math::{Point3, Vector3, DVec3};
+use std::ops::{Add, Mul};
 
-use super::knot::{basis_functions, basis_functions_derivs, find_span};
+use cst_math::{Point3, Vector3, DVec3, DVec4};
+
+use super::knot::{
+    basis_function_derivatives, basis_functions, basis_functions_derivs, binomial, find_span,
+};
 
 /// Evaluate a B-spline curve point at parameter `t` using the De Boor algorithm.
 pub fn curve_point(degree: usize, knots: &[f64], control_points: &[Point3], t: f64) -> Point3 {
@@ -103,6 +107,49 @@ pub fn nurbs_curve_tangent(
     }
 }
 
+/// Evaluate a B-spline curve and its derivatives up to `order` at `t`.
+///
+/// Element `k` of the result is the k-th derivative, so element 0 is the
+/// point and element 1 the tangent of [`curve_tangent`].
+pub fn curve_derivatives(
+    degree: usize,
+    knots: &[f64],
+    control_points: &[Point3],
+    t: f64,
+    order: usize,
+) -> Vec<Vector3> {
+    blend_curve_derivatives(degree, knots, control_points, t, order)
+}
+
+/// Evaluate a NURBS curve and its derivatives up to `order` at `t`, in the
+/// order of [`curve_derivatives`].
+#[allow(clippy::needless_range_loop)]
+pub fn nurbs_curve_derivatives(
+    degree: usize,
+    knots: &[f64],
+    control_points: &[Point3],
+    weights: &[f64],
+    t: f64,
+    order: usize,
+) -> Vec<Vector3> {
+    let homogeneous = homogeneous_points(control_points, weights);
+    let ders = blend_curve_derivatives(degree, knots, &homogeneous, t, order);
+    if ders[0].w.abs() < 1e-15 {
+        return ders.iter().map(|d| d.truncate()).collect();
+    }
+
+    // Quotient rule for A(t) / w(t) (The NURBS Book, A4.2)
+    let mut result: Vec<Vector3> = Vec::with_capacity(order + 1);
+    for k in 0..=order {
+        let mut v = ders[k].truncate();
+        for i in 1..=k {
+            v -= binomial(k, i) * ders[i].w * result[k - i];
+        }
+        result.push(v / ders[0].w);
+    }
+    result
+}
+
 /// Evaluate a B-spline surface point at parameters `(u, v)`.
 #[allow(clippy::needless_range_loop)]
 pub fn surface_point(
@@ -169,6 +216,94 @@ pub fn surface_derivs(
     (du, dv)
 }
 
+/// Evaluate a B-spline surface and its partial derivatives up to `order`
+/// at `(u, v)`.
+///
+/// Element `[k][l]` of the result is the derivative k times in u and l
+/// times in v, for `k + l <= order`; the others are zero. Element `[0][0]`
+/// is the point.
+#[allow(clippy::too_many_arguments)]
+pub fn surface_derivatives(
+    degree_u: usize,
+    degree_v: usize,
+    knots_u: &[f64],
+    knots_v: &[f64],
+    control_points: &[Vec<Point3>],
+    u: f64,
+    v: f64,
+    order: usize,
+) -> Vec<Vec<Vector3>> {
+    blend_surface_derivatives(
+        degree_u,
+        degree_v,
+        knots_u,
+        knots_v,
+        control_points,
+        u,
+        v,
+        order,
+    )
+}
+
+/// Evaluate a NURBS surface and its partial derivatives up to `order` at
+/// `(u, v)`, in the layout of [`surface_derivatives`].
+#[allow(clippy::needless_range_loop, clippy::too_many_arguments)]
+pub fn nurbs_surface_derivatives(
+    degree_u: usize,
+    degree_v: usize,
+    knots_u: &[f64],
+    knots_v: &[f64],
+    control_points: &[Vec<Point3>],
+    weights: &[Vec<f64>],
+    u: f64,
+    v: f64,
+    order: usize,
+) -> Vec<Vec<Vector3>> {
+    let homogeneous: Vec<Vec<DVec4>> = control_points
+        .iter()
+        .zip(weights)
+        .map(|(points, weights)| homogeneous_points(points, weights))
+        .collect();
+    let ders = blend_surface_derivatives(
+        degree_u,
+        degree_v,
+        knots_u,
+        knots_v,
+        &homogeneous,
+        u,
+        v,
+        order,
+    );
+    let w = ders[0][0].w;
+    if w.abs() < 1e-15 {
+        return ders
+            .iter()
+            .map(|row| row.iter().map(|d| d.truncate()).collect())
+            .collect();
+    }
+
+    // Quotient rule for A(u, v) / w(u, v) (The NURBS Book, A4.4)
+    let mut result = vec![vec![DVec3::ZERO; order + 1]; order + 1];
+    for k in 0..=order {
+        for l in 0..=order - k {
+            let mut d = ders[k][l].truncate();
+            for j in 1..=l {
+                d -= binomial(l, j) * ders[0][j].w * result[k][l - j];
+            }
+            for i in 1..=k {
+                d -= binomial(k, i) * ders[i][0].w * result[k - i][l];
+                let mut mixed = DVec3::ZERO;
+                for j in 1..=l {
+                    mixed += binomial(l, j) * ders[i][j].w * result[k - i][l - j];
+                }
+                d -= binomial(k, i) * mixed;
+            }
+            result[k][l] = d / w;
+        }
+    }
+    result
+}
+
 /// Evaluate a NURBS surface point at parameters `(u, v)`.
 #[allow(clippy::needless_range_loop, clippy::too_many_arguments)]
 pub fn nurbs_surface_point(
@@ -273,6 +408,85 @@ pub fn nurbs_surface_normal(
     }
 }
 
+/// Derivatives up to `order` of the curve blending `points` (The NURBS
+/// Book, A3.2). Shared by the polynomial and homogeneous evaluations.
+fn blend_curve_derivatives<T>(
+    degree: usize,
+    knots: &[f64],
+    points: &[T],
+    t: f64,
+    order: usize,
+) -> Vec<T>
+where
+    T: Copy + Default + Add<Output = T> + Mul<f64, Output = T>,
+{
+    let n = points.len() - 1;
+    let span = find_span(degree, knots, n, t);
+    let nders = basis_function_derivatives(degree, knots, span, t, order.min(degree));
+
+    let mut ders = vec![T::default(); order + 1];
+    for (d, basis) in ders.iter_mut().zip(&nders) {
+        for (j, &b) in basis.iter().enumerate() {
+            *d = *d + points[span - degree + j] * b;
+        }
+    }
+    ders
+}
+
+/// Partial derivatives with `k + l <= order` of the surface blending
+/// `points` (The NURBS Book, A3.6).
+#[allow(clippy::too_many_arguments)]
+fn blend_surface_derivatives<T>(
+    degree_u: usize,
+    degree_v: usize,
+    knots_u: &[f64],
+    knots_v: &[f64],
+    points: &[Vec<T>],
+    u: f64,
+    v: f64,
+    order: usize,
+) -> Vec<Vec<T>>
+where
+    T: Copy + Default + Add<Output = T> + Mul<f64, Output = T>,
+{
+    let n_u = points.len() - 1;
+    let span_u = find_span(degree_u, knots_u, n_u, u);
+    let nders_u =
+        basis_function_derivatives(degree_u, knots_u, span_u, u, order.min(degree_u));
+
+    let n_v = points[0].len() - 1;
+    let span_v = find_span(degree_v, knots_v, n_v, v);
+    let nders_v =
+        basis_function_derivatives(degree_v, knots_v, span_v, v, order.min(degree_v));
+
+    let mut ders = vec![vec![T::default(); order + 1]; order + 1];
+    let mut temp = vec![T::default(); degree_v + 1];
+    for (k, basis_u) in nders_u.iter().enumerate() {
+        // The k-th u-derivative of the control rows, as a curve in v
+        for (s, t) in temp.iter_mut().enumerate() {
+            *t = T::default();
+            for (r, &b) in basis_u.iter().enumerate() {
+                *t = *t + points[span_u - degree_u + r][span_v - degree_v + s] * b;
+            }
+        }
+        for (l, basis_v) in nders_v.iter().enumerate().take(order - k + 1) {
+            for (&t, &b) in temp.iter().zip(basis_v) {
+                ders[k][l] = ders[k][l] + t * b;
+            }
+        }
+    }
+    ders
+}
+
+/// Control points as `(w·x, w·y, w·z, w)`.
+fn homogeneous_points(points: &[Point3], weights: &[f64]) -> Vec<DVec4> {
+    points
+        .iter()
+        .zip(weights)
+        .map(|(&p, &w)| (p * w).extend(w))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -333,4 +547,124 @@ mod tests {
         assert!((p.y - 0.5).abs() < 1e-10);
         assert!(p.z.abs() < 1e-10);
     }
+
+    /// Quarter circle of radius 2 in the xy plane, from (2, 0) to (0, 2).
+    fn quarter_circle() -> (Vec<f64>, Vec<Point3>, Vec<f64>) {
+        let knots = vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0];
+        let cps = vec![
+            DVec3::new(2.0, 0.0, 0.0),
+            DVec3::new(2.0, 2.0, 0.0),
+            DVec3::new(0.0, 2.0, 0.0),
+        ];
+        let weights = vec![1.0, std::f64::consts::FRAC_1_SQRT_2, 1.0];
+        (knots, cps, weights)
+    }
+
+    #[test]
+    fn test_curve_derivatives_match_finite_differences() {
+        let degree = 3;
+        let knots = vec![0.0, 0.0, 0.0, 0.0, 1.0, 2.0, 2.0, 2.0, 2.0];
+        let cps: Vec<Point3> = (0..5)
+            .map(|i| DVec3::new(i as f64, ((i * 3) % 4) as f64, (i % 2) as f64))
+            .collect();
+        let h = 1e-5;
+
+        for &t in &[0.25, 0.8, 1.5] {
+            let ders = curve_derivatives(degree, &knots, &cps, t, 5);
+            assert_eq!(ders.len(), 6);
+            assert!((ders[0] - curve_point(degree, &knots, &cps, t)).length() < 1e-12);
+            assert!((ders[1] - curve_tangent(degree, &knots, &cps, t)).length() < 1e-12);
+            for k in 1..=3 {
+                let before = curve_derivatives(degree, &knots, &cps, t - h, k - 1);
+                let after = curve_derivatives(degree, &knots, &cps, t + h, k - 1);
+                let estimate = (after[k - 1] - before[k - 1]) / (2.0 * h);
+                assert!((ders[k] - estimate).length() < 1e-4);
+            }
+            assert_eq!(ders[4], DVec3::ZERO);
+            assert_eq!(ders[5], DVec3::ZERO);
+        }
+    }
+
+    #[test]
+    fn test_nurbs_circle_curvature_and_torsion() {
+        let (knots, cps, weights) = quarter_circle();
+        let h = 1e-5;
+        for i in 0..=10 {
+            let t = i as f64 / 10.0;
+            let ders = nurbs_curve_derivatives(2, &knots, &cps, &weights, t, 3);
+            assert!((ders[0].length() - 2.0).abs() < 1e-12);
+            let tangent = nurbs_curve_tangent(2, &knots, &cps, &weights, t);
+            assert!((ders[1] - tangent).length() < 1e-12);
+
+            // Curvature |C' x C''| / |C'|^3 of a circle is 1 / radius
+            let binormal = ders[1].cross(ders[2]);
+            let curvature = binormal.length() / ders[1].length().powi(3);
+            assert!((curvature - 0.5).abs() < 1e-12, "curvature {}", curvature);
+            // A plane curve has no torsion
+            assert!(binormal.dot(ders[3]).abs() < 1e-9);
+
+            // Rational derivatives above the degree do not vanish
+            let t = t.clamp(h, 1.0 - h);
+            let ders = nurbs_curve_derivatives(2, &knots, &cps, &weights, t, 3);
+            let before = nurbs_curve_derivatives(2, &knots, &cps, &weights, t - h, 2);
+            let after = nurbs_curve_derivatives(2, &knots, &cps, &weights, t + h, 2);
+            assert!((ders[3] - (after[2] - before[2]) / (2.0 * h)).length() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_surface_derivatives_match_finite_differences() {
+        // A quarter cylinder of radius 2: the circle in u, straight in v
+        let (knots_u, circle, weights_u) = quarter_circle();
+        let knots_v = vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0];
+        let cps: Vec<Vec<Point3>> = circle
+            .iter()
+            .map(|&p| (0..3).map(|j| p + DVec3::new(0.0, 0.0, j as f64 * j as f64)).collect())
+            .collect();
+        let weights: Vec<Vec<f64>> = weights_u
+            .iter()
+            .map(|&w| (0..3).map(|j| w * (1.0 + 0.5 * (j % 2) as f64)).collect())
+            .collect();
+        let h = 1e-5;
+
+        for &(u, v) in &[(0.2, 0.3), (0.5, 0.5), (0.9, 0.7)] {
+            let eval = |u, v, order| {
+                nurbs_surface_derivatives(2, 2, &knots_u, &knots_v, &cps, &weights, u, v, order)
+            };
+            let ders = eval(u, v, 3);
+            let point = nurbs_surface_point(2, 2, &knots_u, &knots_v, &cps, &weights, u, v);
+            assert!((ders[0][0] - point).length() < 1e-12);
+            let normal = nurbs_surface_normal(2, 2, &knots_u, &knots_v, &cps, &weights, u, v);
+            assert!((ders[1][0].cross(ders[0][1]).normalize() - normal).length() < 1e-12);
+
+            for k in 0..=3 {
+                for l in 0..=3 - k {
+                    if k > 0 {
+                        let before = eval(u - h, v, 2);
+                        let after = eval(u + h, v, 2);
+                        let estimate = (after[k - 1][l] - before[k - 1][l]) / (2.0 * h);
+                        assert!((ders[k][l] - estimate).length() < 1e-4, "S{}{}", k, l);
+                    }
+                    if l > 0 {
+                        let before = eval(u, v - h, 2);
+                        let after = eval(u, v + h, 2);
+                        let estimate = (after[k][l - 1] - before[k][l - 1]) / (2.0 * h);
+                        assert!((ders[k][l] - estimate).length() < 1e-4, "S{}{}", k, l);
+                    }
+                }
+            }
+        }
+
+        // Unit weights reduce to the polynomial surface
+        let ones = vec![vec![1.0; 3]; 3];
+        let (u, v) = (0.4, 0.6);
+        let plain = surface_derivatives(2, 2, &knots_u, &knots_v, &cps, u, v, 2);
+        let rational = nurbs_surface_derivatives(2, 2, &knots_u, &knots_v, &cps, &ones, u, v, 2);
+        let (du, dv) = surface_derivs(2, 2, &knots_u, &knots_v, &cps, u, v);
+        assert!((plain[1][0] - du).length() < 1e-12);
+        assert!((plain[0][1] - dv).length() < 1e-12);
+        for (a, b) in plain.iter().flatten().zip(rational.iter().flatten()) {
+            assert!((*a - *b).length() < 1e-12);
+        }
+    }
 }
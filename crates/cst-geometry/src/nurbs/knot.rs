@@ -80,9 +80,30 @@ pub fn basis_functions_derivs(
     span: usize,
     t: f64,
 ) -> (Vec<f64>, Vec<f64>) {
+    let mut ders = basis_function_derivatives(degree, knots, span, t, 1);
+    let dn_vals = ders.pop().unwrap();
+    let n_vals = ders.pop().unwrap();
+    (n_vals, dn_vals)
+}
+
+/// Compute the non-vanishing basis functions and their derivatives up to
+/// `order` at parameter `t`.
+///
+/// Returns `order + 1` rows of `degree + 1` values: row `k` holds the k-th
+/// derivatives of N_{span-degree,degree}(t) through N_{span,degree}(t), so
+/// row 0 is the same as [`basis_functions`]. Derivatives above the degree
+/// are zero.
+#[allow(clippy::needless_range_loop)]
+pub fn basis_function_derivatives(
+    degree: usize,
+    knots: &[f64],
+    span: usize,
+    t: f64,
+    order: usize,
+) -> Vec<Vec<f64>> {
     let p = degree;
 
-    // Compute basis functions (same as basis_functions but we also need the triangular table)
+    // Basis functions in the upper triangle, knot differences in the lower
     let mut ndu = vec![vec![0.0; p + 1]; p + 1];
     let mut left = vec![0.0; p + 1];
     let mut right = vec![0.0; p + 1];
@@ -106,14 +127,14 @@ pub fn basis_functions_derivs(
         ndu[j][j] = saved;
     }
 
-    // Basis function values
-    let mut n_vals = vec![0.0; p + 1];
+    let mut ders = vec![vec![0.0; p + 1]; order + 1];
     for j in 0..=p {
-        n_vals[j] = ndu[j][p];
+        ders[0][j] = ndu[j][p];
     }
 
-    // Derivatives
-    let mut dn_vals = vec![0.0; p + 1];
+    // Derivatives, from the coefficients of the differences of lower
+    // degree basis functions (The NURBS Book, A2.3)
+    let max_order = order.min(p);
     let mut a = vec![vec![0.0; p + 1]; 2];
 
     for r in 0..=p {
@@ -121,41 +142,76 @@ pub fn basis_functions_derivs(
         let mut s2 = 1usize;
         a[0][0] = 1.0;
 
-        // Compute first derivative only (k=1)
-        let mut d = 0.0;
-        let rk = r as isize - 1;
-        let pk = p as isize - 1;
+        for k in 1..=max_order {
+            let mut d = 0.0;
+            let pk = p - k;
 
-        if r >= 1 {
-            a[s2][0] = a[s1][0] / ndu[p][r - 1];
-            d = a[s2][0] * ndu[r - 1][p - 1];
-        }
+            if r >= k {
+                let rk = r - k;
+                a[s2][0] = a[s1][0] / ndu[pk + 1][rk];
+                d = a[s2][0] * ndu[rk][pk];
+            }
 
-        let j1 = if rk >= 0 { 1 } else { (-rk) as usize };
-        let j2 = if (r as isize - 1) <= pk {
-            p - r
-        } else {
-            (p as isize - rk) as usize
-        };
+            // Only the terms with r - k + j in 0..=pk + 1
+            let j1 = if r + 1 >= k { 1 } else { k - r };
+            let j2 = if r <= pk + 1 { k - 1 } else { p - r };
+
+            for j in j1..=j2 {
+                let rkj = r + j - k;
+                a[s2][j] = (a[s1][j] - a[s1][j - 1]) / ndu[pk + 1][rkj];
+                d += a[s2][j] * ndu[rkj][pk];
+            }
+
+            if r <= pk {
+                a[s2][k] = -a[s1][k - 1] / ndu[pk + 1][r];
+                d += a[s2][k] * ndu[r][pk];
+            }
+
+            ders[k][r] = d;
+
+            // Swap rows
+            std::mem::swap(&mut s1, &mut s2);
+        }
+    }
 
-        for j in j1..=j2 {
-            a[s2][j] = (a[s1][j] - a[s1][j - 1]) / ndu[p][r + j - 1];
-            d += a[s2][j] * ndu[r + j - 1][p - 1];
+    // Multiply through by p! / (p - k)!
+    let mut factor = p as f64;
+    for k in 1..=max_order {
+        for val in &mut ders[k] {
+            *val *= factor;
         }
+        factor *= (p - k) as f64;
+    }
 
-        dn_vals[r] = d;
+    ders
+}
 
-        // Swap rows
-        std::mem::swap(&mut s1, &mut s2);
+/// Reverse a knot vector in place, so that a curve with the reversed control
+/// points runs the other way over the same domain.
+pub fn reverse_knots(degree: usize, knots: &mut [f64]) {
+    let last = knots.len() - 1;
+    let sum = knots[degree] + knots[last - degree];
+    knots.reverse();
+    for k in knots.iter_mut() {
+        *k = sum - *k;
     }
+}
 
-    // Multiply through by the correct factors
-    let factor = p as f64;
-    for val in &mut dn_vals {
-        *val *= factor;
+/// Map a knot vector linearly so that its domain becomes `[a, b]`, which
+/// must be increasing.
+pub fn reparameterize_knots(degree: usize, knots: &mut [f64], a: f64, b: f64) {
+    debug_assert!(a < b, "reparameterized domain must be increasing");
+    let last = knots.len() - 1;
+    let (d0, d1) = (knots[degree], knots[last - degree]);
+    let scale = (b - a) / (d1 - d0);
+    for k in knots.iter_mut() {
+        *k = a + (*k - d0) * scale;
     }
+}
 
-    (n_vals, dn_vals)
+/// The binomial coefficient `n` choose `k`.
+pub(crate) fn binomial(n: usize, k: usize) -> f64 {
+    (0..k).fold(1.0, |c, i| c * (n - i) as f64 / (i + 1) as f64)
 }
 
 #[cfg(test)]
@@ -218,4 +274,58 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_basis_function_derivatives_match_finite_differences() {
+        let knots = vec![0.0, 0.0, 0.0, 0.0, 1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 3.0];
+        let degree = 3;
+        let n = 6;
+        let h = 1e-4;
+
+        for &t in &[0.3, 1.2, 1.7, 2.5] {
+            let span = find_span(degree, &knots, n, t);
+            let ders = basis_function_derivatives(degree, &knots, span, t, 4);
+            assert_eq!(ders.len(), 5);
+            assert_eq!(ders[0], basis_functions(degree, &knots, span, t));
+
+            // Each row is the central difference of the row above
+            for k in 1..=3 {
+                let before = basis_function_derivatives(degree, &knots, span, t - h, k - 1);
+                let after = basis_function_derivatives(degree, &knots, span, t + h, k - 1);
+                for j in 0..=degree {
+                    let estimate = (after[k - 1][j] - before[k - 1][j]) / (2.0 * h);
+                    assert!(
+                        (ders[k][j] - estimate).abs() < 1e-5,
+                        "d{}N{} at t={}: {} vs {}",
+                        k,
+                        j,
+                        t,
+                        ders[k][j],
+                        estimate
+                    );
+                }
+                // Derivatives of a partition of unity sum to zero
+                assert!(ders[k].iter().sum::<f64>().abs() < 1e-9);
+            }
+            assert!(ders[4].iter().all(|&d| d == 0.0));
+
+            let (basis, dbasis) = basis_functions_derivs(degree, &knots, span, t);
+            assert_eq!(basis, ders[0]);
+            assert_eq!(dbasis, ders[1]);
+        }
+    }
+
+    #[test]
+    fn test_reverse_and_reparameterize_knots() {
+        let mut knots = vec![0.0, 0.0, 0.0, 1.0, 3.0, 4.0, 4.0, 4.0];
+        reverse_knots(2, &mut knots);
+        assert_eq!(knots, vec![0.0, 0.0, 0.0, 1.0, 3.0, 4.0, 4.0, 4.0]);
+
+        let mut knots = vec![0.0, 0.0, 0.0, 1.0, 1.5, 4.0, 4.0, 4.0];
+        reverse_knots(2, &mut knots);
+        assert_eq!(knots, vec![0.0, 0.0, 0.0, 2.5, 3.0, 4.0, 4.0, 4.0]);
+
+        reparameterize_knots(2, &mut knots, -1.0, 1.0);
+        assert_eq!(knots, vec![-1.0, -1.0, -1.0, 0.25, 0.5, 1.0, 1.0, 1.0]);
+    }
 }
@@ -1,7 +1,17 @@
-//! NURBS core algorithms: knot vector utilities and De Boor evaluation.
+//! NURBS core algorithms: knot vector utilities, De Boor evaluation,
+//! derivatives of any order, knot insertion and degree elevation.
 
+pub mod batch;
 pub mod deboor;
 pub mod knot;
+pub mod refine;
 
+pub use batch::{
+    curve_points, nurbs_curve_points, nurbs_surface_points, surface_points, BasisTable,
+};
 pub use deboor::*;
-pub use knot::{basis_functions, basis_functions_derivs, find_span};
+pub use knot::{
+    basis_function_derivatives, basis_functions, basis_functions_derivs, find_span,
+    reparameterize_knots, reverse_knots,
+};
+pub use refine::{elevate_degree, insert_knots};
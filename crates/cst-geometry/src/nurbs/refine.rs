@@ -0,0 +1,287 @@
+//! Knot refinement and degree elevation of B-spline curves.
+//!
+//! Both change the representation of a curve without changing its shape:
+//! refinement adds knots, elevation raises the degree. Together they bring
+//! curves onto a common degree and knot vector, e.g. to skin a surface
+//! through them. Algorithms A5.4 and A5.9 of The NURBS Book.
+
+use cst_math::{DVec3, Point3};
+
+use super::knot::{binomial, find_span};
+
+/// Insert the knots `new_knots`, in non-decreasing order and inside the
+/// domain, into a curve. Returns the refined knot vector and control
+/// points.
+pub fn insert_knots(
+    degree: usize,
+    knots: &[f64],
+    control_points: &[Point3],
+    new_knots: &[f64],
+) -> (Vec<f64>, Vec<Point3>) {
+    if new_knots.is_empty() {
+        return (knots.to_vec(), control_points.to_vec());
+    }
+    let p = degree;
+    let n = control_points.len() - 1;
+    let m = n + p + 1;
+    let r = new_knots.len() - 1;
+    let a = find_span(p, knots, n, new_knots[0]);
+    let b = find_span(p, knots, n, new_knots[r]) + 1;
+
+    let mut points = vec![DVec3::ZERO; n + r + 2];
+    let mut refined = vec![0.0; m + r + 2];
+    points[..=a - p].copy_from_slice(&control_points[..=a - p]);
+    points[b + r..=n + r + 1].copy_from_slice(&control_points[b - 1..=n]);
+    refined[..=a].copy_from_slice(&knots[..=a]);
+    refined[b + p + r + 1..].copy_from_slice(&knots[b + p..]);
+
+    let mut i = b + p - 1;
+    let mut k = b + p + r;
+    for &x in new_knots.iter().rev() {
+        while x <= knots[i] && i > a {
+            points[k - p - 1] = control_points[i - p - 1];
+            refined[k] = knots[i];
+            k -= 1;
+            i -= 1;
+        }
+        points[k - p - 1] = points[k - p];
+        for l in 1..=p {
+            let ind = k - p + l;
+            let alpha = refined[k + l] - x;
+            if alpha.abs() == 0.0 {
+                points[ind - 1] = points[ind];
+            } else {
+                let alpha = alpha / (refined[k + l] - knots[i - p + l]);
+                points[ind - 1] = alpha * points[ind - 1] + (1.0 - alpha) * points[ind];
+            }
+        }
+        refined[k] = x;
+        k -= 1;
+    }
+    (refined, points)
+}
+
+/// Raise the degree of a curve by `times`. Every knot keeps its place with
+/// its multiplicity raised by `times`, so the curve keeps its continuity.
+/// Returns the new knot vector and control points.
+#[allow(clippy::needless_range_loop)]
+pub fn elevate_degree(
+    degree: usize,
+    knots: &[f64],
+    control_points: &[Point3],
+    times: usize,
+) -> (Vec<f64>, Vec<Point3>) {
+    if times == 0 {
+        return (knots.to_vec(), control_points.to_vec());
+    }
+    let p = degree;
+    let t = times;
+    let n = control_points.len() - 1;
+    let m = n + p + 1;
+    let ph = p + t;
+    let ph2 = ph / 2;
+
+    // Coefficients for degree elevating a Bezier segment
+    let mut bezalfs = vec![vec![0.0; p + 1]; ph + 1];
+    bezalfs[0][0] = 1.0;
+    bezalfs[ph][p] = 1.0;
+    for i in 1..=ph2 {
+        let inv = 1.0 / binomial(ph, i);
+        for j in i.saturating_sub(t)..=p.min(i) {
+            bezalfs[i][j] = inv * binomial(p, j) * binomial(t, i - j);
+        }
+    }
+    for i in ph2 + 1..ph {
+        for j in i.saturating_sub(t)..=p.min(i) {
+            bezalfs[i][j] = bezalfs[ph - i][p - j];
+        }
+    }
+
+    // Each of the at most m Bezier segments gains t control points
+    let mut points = vec![DVec3::ZERO; n + 1 + t * m];
+    let mut elevated = vec![0.0; (m + 1) * (t + 1)];
+    let mut bpts = vec![DVec3::ZERO; p + 1];
+    let mut ebpts = vec![DVec3::ZERO; ph + 1];
+    let mut next_bpts = vec![DVec3::ZERO; p.saturating_sub(1)];
+    let mut alfs = vec![0.0; p.saturating_sub(1)];
+
+    let mut mh = ph;
+    let mut kind = ph + 1;
+    let mut r: isize = -1;
+    let mut a = p;
+    let mut b = p + 1;
+    let mut cind = 1;
+    let mut ua = knots[0];
+    points[0] = control_points[0];
+    elevated[..=ph].fill(ua);
+    bpts.copy_from_slice(&control_points[..=p]);
+
+    while b < m {
+        let i = b;
+        while b < m && knots[b] == knots[b + 1] {
+            b += 1;
+        }
+        let mul = b - i + 1;
+        mh += mul + t;
+        let ub = knots[b];
+        let oldr = r;
+        r = p as isize - mul as isize;
+        let lbz = if oldr > 0 { (oldr as usize + 2) / 2 } else { 1 };
+        let rbz = if r > 0 {
+            ph - (r as usize).div_ceil(2)
+        } else {
+            ph
+        };
+
+        // Insert ub r times to split off the Bezier segment
+        if r > 0 {
+            let r = r as usize;
+            let numer = ub - ua;
+            for k in (mul + 1..=p).rev() {
+                alfs[k - mul - 1] = numer / (knots[a + k] - ua);
+            }
+            for j in 1..=r {
+                let s = mul + j;
+                for k in (s..=p).rev() {
+                    bpts[k] = alfs[k - s] * bpts[k] + (1.0 - alfs[k - s]) * bpts[k - 1];
+                }
+                next_bpts[r - j] = bpts[p];
+            }
+        }
+
+        for (i, e) in ebpts.iter_mut().enumerate().skip(lbz) {
+            *e = (i.saturating_sub(t)..=p.min(i))
+                .map(|j| bezalfs[i][j] * bpts[j])
+                .sum();
+        }
+
+        // Remove ua oldr times where the previous segment was split off
+        if oldr > 1 {
+            let oldr = oldr as usize;
+            let den = ub - ua;
+            let bet = (ub - elevated[kind - 1]) / den;
+            for tr in 1..oldr {
+                let mut i = kind - 1 - tr;
+                let mut j = kind - 1 + tr;
+                let mut kj = j - kind + 1;
+                while j - i > tr {
+                    if i < cind {
+                        let alf = (ub - elevated[i]) / (ua - elevated[i]);
+                        points[i] = alf * points[i] + (1.0 - alf) * points[i - 1];
+                    }
+                    if j >= lbz {
+                        if j + ph <= kind + oldr + tr {
+                            let gam = (ub - elevated[j - tr]) / den;
+                            ebpts[kj] = gam * ebpts[kj] + (1.0 - gam) * ebpts[kj + 1];
+                        } else {
+                            ebpts[kj] = bet * ebpts[kj] + (1.0 - bet) * ebpts[kj + 1];
+                        }
+                    }
+                    i += 1;
+                    j -= 1;
+                    kj = kj.wrapping_sub(1);
+                }
+            }
+        }
+
+        if a != p {
+            let count = (ph as isize - oldr) as usize;
+            elevated[kind..kind + count].fill(ua);
+            kind += count;
+        }
+        for e in &ebpts[lbz..=rbz] {
+            points[cind] = *e;
+            cind += 1;
+        }
+
+        if b < m {
+            let r = r.max(0) as usize;
+            bpts[..r].copy_from_slice(&next_bpts[..r]);
+            bpts[r..].copy_from_slice(&control_points[b - p + r..=b]);
+            a = b;
+            b += 1;
+            ua = ub;
+        } else {
+            elevated[kind..=kind + ph].fill(ub);
+        }
+    }
+
+    let nh = mh - ph - 1;
+    points.truncate(nh + 1);
+    elevated.truncate(nh + ph + 2);
+    (elevated, points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nurbs::curve_point;
+
+    /// A cubic with a double interior knot over `[0, 4]`.
+    fn cubic() -> (Vec<f64>, Vec<Point3>) {
+        let knots = vec![0.0, 0.0, 0.0, 0.0, 1.0, 2.5, 2.5, 4.0, 4.0, 4.0, 4.0];
+        let points = (0..7)
+            .map(|i| DVec3::new(i as f64, ((i * 5) % 3) as f64, (i % 2) as f64))
+            .collect();
+        (knots, points)
+    }
+
+    fn assert_same_curve(
+        degree: usize,
+        knots: &[f64],
+        points: &[Point3],
+        other: (usize, &[f64], &[Point3]),
+    ) {
+        let (other_degree, other_knots, other_points) = other;
+        assert_eq!(other_knots.len(), other_points.len() + other_degree + 1);
+        for i in 0..=40 {
+            let t = 0.1 * i as f64;
+            let expected = curve_point(degree, knots, points, t);
+            let p = curve_point(other_degree, other_knots, other_points, t);
+            assert!(
+                (p - expected).length() < 1e-12,
+                "t={}: {:?} vs {:?}",
+                t,
+                p,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_insert_knots_keeps_shape() {
+        let (knots, points) = cubic();
+        let new_knots = [0.5, 1.0, 2.5, 3.0, 3.0];
+        let (refined, refined_points) = insert_knots(3, &knots, &points, &new_knots);
+        assert_eq!(
+            refined,
+            vec![0.0, 0.0, 0.0, 0.0, 0.5, 1.0, 1.0, 2.5, 2.5, 2.5, 3.0, 3.0, 4.0, 4.0, 4.0, 4.0]
+        );
+        assert_same_curve(3, &knots, &points, (3, &refined, &refined_points));
+    }
+
+    #[test]
+    fn test_elevate_degree_keeps_shape() {
+        let (knots, points) = cubic();
+        for times in 1..=2 {
+            let (elevated, elevated_points) = elevate_degree(3, &knots, &points, times);
+            let mut expected = vec![0.0; 4 + times];
+            expected.extend(vec![1.0; 1 + times]);
+            expected.extend(vec![2.5; 2 + times]);
+            expected.extend(vec![4.0; 4 + times]);
+            assert_eq!(elevated, expected);
+            assert_same_curve(3, &knots, &points, (3 + times, &elevated, &elevated_points));
+        }
+
+        // A line and a Bezier segment
+        let line = [DVec3::ZERO, DVec3::new(2.0, 1.0, 0.0)];
+        let (elevated, elevated_points) = elevate_degree(1, &[0.0, 0.0, 4.0, 4.0], &line, 2);
+        assert_eq!(elevated, vec![0.0, 0.0, 0.0, 0.0, 4.0, 4.0, 4.0, 4.0]);
+        assert_same_curve(
+            1,
+            &[0.0, 0.0, 4.0, 4.0],
+            &line,
+            (3, &elevated, &elevated_points),
+        );
+    }
+}
@@ -0,0 +1,84 @@
+//! Shared storage for the curves and surfaces referenced by topology.
+//!
+//! Topological entities (faces, edges) only carry an index into a
+//! [`GeometryPool`], which keeps the topology serializable and lets several
+//! faces share one underlying surface.
+
+use crate::curve::Curve;
+use crate::surface::Surface;
+
+/// Indexed collection of curves and surfaces.
+#[derive(Default)]
+pub struct GeometryPool {
+    surfaces: Vec<Box<dyn Surface>>,
+    curves: Vec<Box<dyn Curve>>,
+}
+
+impl GeometryPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store a surface, returning its index.
+    pub fn add_surface(&mut self, surface: impl Surface + 'static) -> usize {
+        self.surfaces.push(Box::new(surface));
+        self.surfaces.len() - 1
+    }
+
+    /// Store a curve, returning its index.
+    pub fn add_curve(&mut self, curve: impl Curve + 'static) -> usize {
+        self.curves.push(Box::new(curve));
+        self.curves.len() - 1
+    }
+
+    /// Look up a surface by index.
+    pub fn surface(&self, index: usize) -> Option<&dyn Surface> {
+        self.surfaces.get(index).map(|s| s.as_ref())
+    }
+
+    /// Look up a curve by index.
+    pub fn curve(&self, index: usize) -> Option<&dyn Curve> {
+        self.curves.get(index).map(|c| c.as_ref())
+    }
+
+    pub fn surface_count(&self) -> usize {
+        self.surfaces.len()
+    }
+
+    pub fn curve_count(&self) -> usize {
+        self.curves.len()
+    }
+}
+
+impl std::fmt::Debug for GeometryPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GeometryPool")
+            .field("surfaces", &self.surfaces.len())
+            .field("curves", &self.curves.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::Line;
+    use crate::surface::SphericalSurface;
+    use cst_math::DVec3;
+
+    #[test]
+    fn test_pool_indices() {
+        let mut pool = GeometryPool::new();
+        let s0 = pool.add_surface(SphericalSurface::new(DVec3::ZERO, 1.0));
+        let s1 = pool.add_surface(SphericalSurface::new(DVec3::ZERO, 2.0));
+        let c0 = pool.add_curve(Line::new(DVec3::ZERO, DVec3::X));
+        assert_eq!((s0, s1, c0), (0, 1, 0));
+        assert_eq!(pool.surface_count(), 2);
+        assert_eq!(pool.curve_count(), 1);
+
+        let p = pool.surface(s1).unwrap().point_at(0.0, 0.0);
+        assert!((p.length() - 2.0).abs() < 1e-12);
+        assert!(pool.surface(5).is_none());
+        assert!(pool.curve(c0).is_some());
+    }
+}
@@ -3,8 +3,8 @@
 use cst_math::{Point3, Vector3, DVec3};
 use serde::{Deserialize, Serialize};
 
-use super::Surface;
-use crate::nurbs::deboor;
+use super::{Surface, SurfaceKey};
+use crate::nurbs::{batch, deboor, reverse_knots};
 
 /// A B-spline surface defined by degrees, knot vectors, and a 2D grid of control points.
 ///
@@ -50,6 +50,42 @@ impl BSplineSurface {
             control_points,
         }
     }
+
+    /// Run u the other way over the same domain, flipping the normal.
+    pub fn reverse_u(&mut self) {
+        reverse_knots(self.degree_u, &mut self.knots_u);
+        self.control_points.reverse();
+    }
+
+    /// Run v the other way over the same domain, flipping the normal.
+    pub fn reverse_v(&mut self) {
+        reverse_knots(self.degree_v, &mut self.knots_v);
+        for row in &mut self.control_points {
+            row.reverse();
+        }
+    }
+
+    /// Exchange u and v, flipping the normal.
+    pub fn swap_uv(&mut self) {
+        std::mem::swap(&mut self.degree_u, &mut self.degree_v);
+        std::mem::swap(&mut self.knots_u, &mut self.knots_v);
+        self.control_points = transpose(&self.control_points);
+    }
+
+    /// The point and its partial derivatives up to `order` at `(u, v)`;
+    /// element `[k][l]` is differentiated k times in u and l times in v.
+    pub fn derivatives_at(&self, u: f64, v: f64, order: usize) -> Vec<Vec<Vector3>> {
+        deboor::surface_derivatives(
+            self.degree_u,
+            self.degree_v,
+            &self.knots_u,
+            &self.knots_v,
+            &self.control_points,
+            u,
+            v,
+            order,
+        )
+    }
 }
 
 impl Surface for BSplineSurface {
@@ -93,6 +129,29 @@ impl Surface for BSplineSurface {
         let p = self.degree_v;
         (self.knots_v[p], self.knots_v[self.knots_v.len() - p - 1])
     }
+
+    fn points_at_grid(&self, us: &[f64], vs: &[f64]) -> Vec<Point3> {
+        batch::surface_points(
+            self.degree_u,
+            self.degree_v,
+            &self.knots_u,
+            &self.knots_v,
+            &self.control_points,
+            us,
+            vs,
+        )
+    }
+
+    fn cache_key(&self) -> Option<SurfaceKey> {
+        let parameters = spline_parameters(
+            self.degree_u,
+            self.degree_v,
+            &self.knots_u,
+            &self.knots_v,
+            &self.control_points,
+        );
+        Some(SurfaceKey::new("bspline", parameters))
+    }
 }
 
 /// A NURBS surface (rational B-spline surface).
@@ -132,6 +191,46 @@ impl NurbsSurface {
             weights,
         }
     }
+
+    /// Run u the other way over the same domain, flipping the normal.
+    pub fn reverse_u(&mut self) {
+        reverse_knots(self.degree_u, &mut self.knots_u);
+        self.control_points.reverse();
+        self.weights.reverse();
+    }
+
+    /// Run v the other way over the same domain, flipping the normal.
+    pub fn reverse_v(&mut self) {
+        reverse_knots(self.degree_v, &mut self.knots_v);
+        for (row, weights) in self.control_points.iter_mut().zip(&mut self.weights) {
+            row.reverse();
+            weights.reverse();
+        }
+    }
+
+    /// Exchange u and v, flipping the normal.
+    pub fn swap_uv(&mut self) {
+        std::mem::swap(&mut self.degree_u, &mut self.degree_v);
+        std::mem::swap(&mut self.knots_u, &mut self.knots_v);
+        self.control_points = transpose(&self.control_points);
+        self.weights = transpose(&self.weights);
+    }
+
+    /// The point and its partial derivatives up to `order` at `(u, v)`;
+    /// element `[k][l]` is differentiated k times in u and l times in v.
+    pub fn derivatives_at(&self, u: f64, v: f64, order: usize) -> Vec<Vec<Vector3>> {
+        deboor::nurbs_surface_derivatives(
+            self.degree_u,
+            self.degree_v,
+            &self.knots_u,
+            &self.knots_v,
+            &self.control_points,
+            &self.weights,
+            u,
+            v,
+            order,
+        )
+    }
 }
 
 impl Surface for NurbsSurface {
@@ -170,6 +269,58 @@ impl Surface for NurbsSurface {
         let p = self.degree_v;
         (self.knots_v[p], self.knots_v[self.knots_v.len() - p - 1])
     }
+
+    fn points_at_grid(&self, us: &[f64], vs: &[f64]) -> Vec<Point3> {
+        batch::nurbs_surface_points(
+            self.degree_u,
+            self.degree_v,
+            &self.knots_u,
+            &self.knots_v,
+            &self.control_points,
+            &self.weights,
+            us,
+            vs,
+        )
+    }
+
+    fn cache_key(&self) -> Option<SurfaceKey> {
+        let mut parameters = spline_parameters(
+            self.degree_u,
+            self.degree_v,
+            &self.knots_u,
+            &self.knots_v,
+            &self.control_points,
+        );
+        parameters.extend(self.weights.iter().flatten());
+        Some(SurfaceKey::new("nurbs", parameters))
+    }
+}
+
+/// Degrees, knot vectors and control grid as one list of numbers, with the
+/// lengths that make it unambiguous.
+fn spline_parameters(
+    degree_u: usize,
+    degree_v: usize,
+    knots_u: &[f64],
+    knots_v: &[f64],
+    control_points: &[Vec<Point3>],
+) -> Vec<f64> {
+    let mut parameters = vec![degree_u as f64, degree_v as f64, knots_u.len() as f64];
+    parameters.extend(knots_u);
+    parameters.extend(knots_v);
+    parameters.push(control_points.len() as f64);
+    for row in control_points {
+        parameters.push(row.len() as f64);
+        parameters.extend(row.iter().flat_map(|p| [p.x, p.y, p.z]));
+    }
+    parameters
+}
+
+/// Columns of a control grid as rows.
+fn transpose<T: Copy>(grid: &[Vec<T>]) -> Vec<Vec<T>> {
+    (0..grid[0].len())
+        .map(|j| grid.iter().map(|row| row[j]).collect())
+        .collect()
 }
 
 #[cfg(test)]
@@ -242,4 +393,39 @@ mod tests {
         let p = surf.point_at(0.5, 0.5);
         assert!((p - DVec3::new(0.5, 0.5, 0.0)).length() < 1e-10);
     }
+
+    #[test]
+    fn test_nurbs_surface_reverse_and_swap() {
+        let knots_u = vec![0.0, 0.0, 0.0, 0.4, 1.0, 1.0, 1.0];
+        let knots_v = vec![0.0, 0.0, 2.0, 2.0];
+        let control_points: Vec<Vec<Point3>> = (0..4)
+            .map(|i| {
+                (0..2)
+                    .map(|j| DVec3::new(j as f64, i as f64, ((i * j + i) % 3) as f64))
+                    .collect()
+            })
+            .collect();
+        let weights: Vec<Vec<f64>> = (0..4)
+            .map(|i| (0..2).map(|j| 1.0 + 0.5 * ((i + j) % 2) as f64).collect())
+            .collect();
+        let surf = NurbsSurface::new(2, 1, knots_u, knots_v, control_points, weights);
+
+        let mut reversed = surf.clone();
+        reversed.reverse_u();
+        reversed.reverse_v();
+        let mut swapped = surf.clone();
+        swapped.swap_uv();
+        assert_eq!(swapped.domain_u(), (0.0, 2.0));
+        assert_eq!(swapped.domain_v(), (0.0, 1.0));
+
+        for (u, v) in [(0.1, 0.3), (0.5, 1.0), (0.9, 1.7)] {
+            let p = surf.point_at(u, v);
+            let n = surf.normal_at(u, v);
+            assert!((reversed.point_at(1.0 - u, 2.0 - v) - p).length() < 1e-12);
+            // Two reversals keep the normal
+            assert!((reversed.normal_at(1.0 - u, 2.0 - v) - n).length() < 1e-12);
+            assert!((swapped.point_at(v, u) - p).length() < 1e-12);
+            assert!((swapped.normal_at(v, u) + n).length() < 1e-12);
+        }
+    }
 }
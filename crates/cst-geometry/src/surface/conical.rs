@@ -5,7 +5,7 @@ use std::f64::consts::PI;
 use cst_math::{Point3, Vector3, DVec3};
 use serde::{Deserialize, Serialize};
 
-use super::Surface;
+use super::{Surface, SurfaceKey};
 
 /// A conical surface parameterized by angle `u` in `[0, 2*PI]` and distance `v` from apex.
 ///
@@ -64,6 +64,11 @@ impl Surface for ConicalSurface {
     fn domain_v(&self) -> (f64, f64) {
         (0.0, 1e6)
     }
+
+    fn cache_key(&self) -> Option<SurfaceKey> {
+        let (p, a) = (self.apex, self.axis);
+        Some(SurfaceKey::new("cone", [p.x, p.y, p.z, a.x, a.y, a.z, self.half_angle]))
+    }
 }
 
 #[cfg(test)]
@@ -2,10 +2,11 @@
 
 use std::f64::consts::PI;
 
-use cst_math::{Point3, Vector3, DVec3};
+use cst_math::coords::Cylindrical;
+use cst_math::{Point3, Vector3, DMat3, DVec3};
 use serde::{Deserialize, Serialize};
 
-use super::Surface;
+use super::{Surface, SurfaceKey};
 
 /// A cylindrical surface parameterized by angle `u` in `[0, 2*PI]` and height `v`.
 ///
@@ -39,15 +40,14 @@ impl CylindricalSurface {
 impl Surface for CylindricalSurface {
     fn point_at(&self, u: f64, v: f64) -> Point3 {
         let (ref_dir, cross_dir) = self.local_frame();
-        self.origin
-            + self.radius * (u.cos() * ref_dir + u.sin() * cross_dir)
-            + v * self.axis
+        let frame = DMat3::from_cols(ref_dir, cross_dir, self.axis);
+        self.origin + frame * Cylindrical::new(self.radius, u, v).to_cartesian()
     }
 
     fn normal_at(&self, u: f64, _v: f64) -> Vector3 {
         let (ref_dir, cross_dir) = self.local_frame();
-        let n = u.cos() * ref_dir + u.sin() * cross_dir;
-        n.normalize()
+        let frame = DMat3::from_cols(ref_dir, cross_dir, self.axis);
+        frame * Cylindrical::new(1.0, u, 0.0).to_cartesian()
     }
 
     fn domain_u(&self) -> (f64, f64) {
@@ -57,6 +57,11 @@ impl Surface for CylindricalSurface {
     fn domain_v(&self) -> (f64, f64) {
         (-1e6, 1e6)
     }
+
+    fn cache_key(&self) -> Option<SurfaceKey> {
+        let (o, a) = (self.origin, self.axis);
+        Some(SurfaceKey::new("cylinder", [o.x, o.y, o.z, a.x, a.y, a.z, self.radius]))
+    }
 }
 
 #[cfg(test)]
@@ -6,6 +6,8 @@ mod conical;
 mod spherical;
 mod toroidal;
 mod bspline;
+mod reparameterized;
+mod skin;
 
 use cst_math::{Point3, Vector3};
 
@@ -15,6 +17,27 @@ pub use conical::ConicalSurface;
 pub use spherical::SphericalSurface;
 pub use toroidal::ToroidalSurface;
 pub use bspline::{BSplineSurface, NurbsSurface};
+pub use reparameterized::ReparameterizedSurface;
+pub use skin::skin_surface;
+
+/// A surface's kind and the exact bits of its defining parameters.
+///
+/// Two surfaces with equal keys evaluate identically, so a key can stand in
+/// for the surface, e.g. to reuse its tessellation. See [`Surface::cache_key`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SurfaceKey {
+    kind: &'static str,
+    bits: Vec<u64>,
+}
+
+impl SurfaceKey {
+    pub fn new(kind: &'static str, parameters: impl IntoIterator<Item = f64>) -> Self {
+        Self {
+            kind,
+            bits: parameters.into_iter().map(f64::to_bits).collect(),
+        }
+    }
+}
 
 /// Trait for parametric surfaces in 3D space.
 pub trait Surface: Send + Sync {
@@ -29,4 +52,19 @@ pub trait Surface: Send + Sync {
 
     /// Return the v-parameter domain `(v_min, v_max)`.
     fn domain_v(&self) -> (f64, f64);
+
+    /// Evaluate the surface on the grid `us` × `vs`, in row-major order: the
+    /// point at `(us[i], vs[j])` is at `i * vs.len() + j`. B-spline surfaces
+    /// share basis evaluations across the grid.
+    fn points_at_grid(&self, us: &[f64], vs: &[f64]) -> Vec<Point3> {
+        us.iter()
+            .flat_map(|&u| vs.iter().map(move |&v| self.point_at(u, v)))
+            .collect()
+    }
+
+    /// Identity of the surface for caching; `None` (the default) when it
+    /// cannot be keyed.
+    fn cache_key(&self) -> Option<SurfaceKey> {
+        None
+    }
 }
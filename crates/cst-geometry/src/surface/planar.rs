@@ -3,7 +3,7 @@
 use cst_math::{Point3, Vector3, DVec3};
 use serde::{Deserialize, Serialize};
 
-use super::Surface;
+use super::{Surface, SurfaceKey};
 
 /// An infinite planar surface parameterized by `origin + u * u_axis + v * v_axis`.
 ///
@@ -24,6 +24,22 @@ impl PlanarSurface {
         }
     }
 
+    /// Run u the other way, flipping the normal. The domain is symmetric,
+    /// so `u` of the reversed plane is `-u` of this one.
+    pub fn reverse_u(&mut self) {
+        self.u_axis = -self.u_axis;
+    }
+
+    /// Run v the other way, flipping the normal.
+    pub fn reverse_v(&mut self) {
+        self.v_axis = -self.v_axis;
+    }
+
+    /// Exchange u and v, flipping the normal.
+    pub fn swap_uv(&mut self) {
+        std::mem::swap(&mut self.u_axis, &mut self.v_axis);
+    }
+
     /// XY plane centered at origin.
     pub fn xy() -> Self {
         Self::new(DVec3::ZERO, DVec3::X, DVec3::Y)
@@ -52,6 +68,11 @@ impl Surface for PlanarSurface {
     fn domain_v(&self) -> (f64, f64) {
         (-1e6, 1e6)
     }
+
+    fn cache_key(&self) -> Option<SurfaceKey> {
+        let (o, u, v) = (self.origin, self.u_axis, self.v_axis);
+        Some(SurfaceKey::new("plane", [o.x, o.y, o.z, u.x, u.y, u.z, v.x, v.y, v.z]))
+    }
 }
 
 #[cfg(test)]
@@ -81,4 +102,17 @@ mod tests {
         let n2 = plane.normal_at(100.0, -50.0);
         assert!((n1 - n2).length() < 1e-10);
     }
+
+    #[test]
+    fn test_planar_reverse_and_swap() {
+        let mut plane = PlanarSurface::new(DVec3::Z, DVec3::X, 2.0 * DVec3::Y);
+        plane.reverse_u();
+        assert!((plane.point_at(1.0, 1.0) - DVec3::new(-1.0, 2.0, 1.0)).length() < 1e-10);
+        assert!((plane.normal_at(0.0, 0.0) + DVec3::Z).length() < 1e-10);
+        plane.swap_uv();
+        assert!((plane.point_at(1.0, 2.0) - DVec3::new(-2.0, 2.0, 1.0)).length() < 1e-10);
+        assert!((plane.normal_at(0.0, 0.0) - DVec3::Z).length() < 1e-10);
+        plane.reverse_v();
+        assert!((plane.point_at(1.0, 2.0) - DVec3::new(2.0, 2.0, 1.0)).length() < 1e-10);
+    }
 }
@@ -0,0 +1,177 @@
+//! Reversal of the parameter directions of any surface.
+
+use cst_math::{Point3, Vector3};
+use serde::{Deserialize, Serialize};
+
+use super::{Surface, SurfaceKey};
+
+/// A surface with its u and/or v direction reversed or the two exchanged,
+/// e.g. to match the sense of an IFC face to its surface.
+///
+/// Each reversal and the exchange flip the normal. For surfaces whose
+/// frame is fixed by their definition, such as cylinders and spheres;
+/// planes and B-spline surfaces can also reorient themselves in place.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReparameterizedSurface<S> {
+    pub surface: S,
+    /// Whether u of this surface is v of the wrapped one and vice versa
+    pub swapped: bool,
+    /// Whether the u-direction of the wrapped surface runs backwards
+    pub reversed_u: bool,
+    /// Whether the v-direction of the wrapped surface runs backwards
+    pub reversed_v: bool,
+}
+
+impl<S: Surface> ReparameterizedSurface<S> {
+    pub fn new(surface: S) -> Self {
+        Self {
+            surface,
+            swapped: false,
+            reversed_u: false,
+            reversed_v: false,
+        }
+    }
+
+    /// Run u the other way over the same domain.
+    pub fn reverse_u(&mut self) {
+        if self.swapped {
+            self.reversed_v = !self.reversed_v;
+        } else {
+            self.reversed_u = !self.reversed_u;
+        }
+    }
+
+    /// Run v the other way over the same domain.
+    pub fn reverse_v(&mut self) {
+        if self.swapped {
+            self.reversed_u = !self.reversed_u;
+        } else {
+            self.reversed_v = !self.reversed_v;
+        }
+    }
+
+    /// Exchange u and v.
+    pub fn swap_uv(&mut self) {
+        self.swapped = !self.swapped;
+    }
+
+    /// Parameters of the wrapped surface at `(u, v)`.
+    fn inner(&self, u: f64, v: f64) -> (f64, f64) {
+        let (a, b) = if self.swapped { (v, u) } else { (u, v) };
+        (self.inner_u(a), self.inner_v(b))
+    }
+
+    fn inner_u(&self, u: f64) -> f64 {
+        flip(u, self.surface.domain_u(), self.reversed_u)
+    }
+
+    fn inner_v(&self, v: f64) -> f64 {
+        flip(v, self.surface.domain_v(), self.reversed_v)
+    }
+}
+
+impl<S: Surface> Surface for ReparameterizedSurface<S> {
+    fn point_at(&self, u: f64, v: f64) -> Point3 {
+        let (u, v) = self.inner(u, v);
+        self.surface.point_at(u, v)
+    }
+
+    fn normal_at(&self, u: f64, v: f64) -> Vector3 {
+        let (u, v) = self.inner(u, v);
+        let normal = self.surface.normal_at(u, v);
+        if self.swapped ^ self.reversed_u ^ self.reversed_v {
+            -normal
+        } else {
+            normal
+        }
+    }
+
+    fn domain_u(&self) -> (f64, f64) {
+        if self.swapped {
+            self.surface.domain_v()
+        } else {
+            self.surface.domain_u()
+        }
+    }
+
+    fn domain_v(&self) -> (f64, f64) {
+        if self.swapped {
+            self.surface.domain_u()
+        } else {
+            self.surface.domain_v()
+        }
+    }
+
+    fn points_at_grid(&self, us: &[f64], vs: &[f64]) -> Vec<Point3> {
+        if !self.swapped {
+            let us: Vec<f64> = us.iter().map(|&u| self.inner_u(u)).collect();
+            let vs: Vec<f64> = vs.iter().map(|&v| self.inner_v(v)).collect();
+            return self.surface.points_at_grid(&us, &vs);
+        }
+        // The wrapped grid has a row per v; transpose it
+        let rows: Vec<f64> = vs.iter().map(|&v| self.inner_u(v)).collect();
+        let columns: Vec<f64> = us.iter().map(|&u| self.inner_v(u)).collect();
+        let grid = self.surface.points_at_grid(&rows, &columns);
+        (0..us.len())
+            .flat_map(|i| (0..vs.len()).map(move |j| (i, j)))
+            .map(|(i, j)| grid[j * us.len() + i])
+            .collect()
+    }
+
+    fn cache_key(&self) -> Option<SurfaceKey> {
+        let mut key = self.surface.cache_key()?;
+        let flags = [self.swapped, self.reversed_u, self.reversed_v];
+        key.bits.extend(flags.map(u64::from));
+        Some(key)
+    }
+}
+
+/// `t` in `(t0, t1)` counted from the other end when `reversed`.
+fn flip(t: f64, (t0, t1): (f64, f64), reversed: bool) -> f64 {
+    if reversed {
+        t0 + t1 - t
+    } else {
+        t
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::surface::CylindricalSurface;
+    use cst_math::DVec3;
+    use std::f64::consts::TAU;
+
+    #[test]
+    fn test_reversed_and_swapped_cylinder() {
+        let cylinder = CylindricalSurface::new(DVec3::ZERO, DVec3::Z, 1.5);
+        let mut surface = ReparameterizedSurface::new(cylinder.clone());
+        surface.swap_uv();
+        surface.reverse_u();
+        assert_eq!(surface.domain_v(), (0.0, TAU));
+        assert_eq!(surface.domain_u(), cylinder.domain_v());
+
+        // u is the height, now running down; v is the angle
+        let (u, v) = (2.0, 1.0);
+        let expected = cylinder.point_at(1.0, -2.0);
+        assert!((surface.point_at(u, v) - expected).length() < 1e-12);
+        // Two flips keep the normal outward
+        let normal = cylinder.normal_at(1.0, -2.0);
+        assert!((surface.normal_at(u, v) - normal).length() < 1e-12);
+        surface.reverse_v();
+        let normal = cylinder.normal_at(TAU - 1.0, -2.0);
+        assert!((surface.normal_at(u, v) + normal).length() < 1e-12);
+
+        let (us, vs) = ([0.0, 1.0, 3.0], [0.5, 2.0]);
+        let grid = surface.points_at_grid(&us, &vs);
+        for (i, &u) in us.iter().enumerate() {
+            for (j, &v) in vs.iter().enumerate() {
+                let p = surface.point_at(u, v);
+                assert!((grid[i * vs.len() + j] - p).length() < 1e-12);
+            }
+        }
+
+        assert_ne!(surface.cache_key(), cylinder.cache_key());
+        assert!(surface.cache_key().is_some());
+    }
+}
@@ -0,0 +1,273 @@
+//! Skinning: a surface through a sequence of section curves.
+//!
+//! Lofts such as tapered columns and ducts are given by their cross
+//! sections. Skinning makes the sections compatible (one degree, one knot
+//! vector) and interpolates their control points across the sections
+//! (The NURBS Book, section 10.3).
+
+use cst_core::Tolerance;
+use cst_math::linalg::BandedMatrix;
+use cst_math::{DVec3, Point3};
+
+use super::BSplineSurface;
+use crate::curve::BSplineCurve;
+use crate::nurbs::{basis_functions, find_span};
+
+/// Highest degree of a skinned surface across its sections.
+const MAX_DEGREE_V: usize = 3;
+
+/// Interior knots of different sections closer than this, in the common
+/// domain `[0, 1]`, are the same knot.
+const KNOT_TOLERANCE: f64 = 1e-10;
+
+/// A surface through `sections` in order.
+///
+/// The sections become the u-curves of the surface, at increasing v from
+/// `v = 0` for the first to `v = 1` for the last. They are raised to the
+/// highest degree among them, mapped onto `u` in `[0, 1]` and refined to a
+/// common knot vector. Across the sections the surface is cubic, or of
+/// lower degree for fewer than four sections, with the sections spaced by
+/// the mean distance between their corresponding control points.
+///
+/// `None` for fewer than two sections, for sections that are not clamped
+/// (end knots repeated `degree + 1` times) and for consecutive sections
+/// that coincide.
+pub fn skin_surface(sections: &[BSplineCurve]) -> Option<BSplineSurface> {
+    if sections.len() < 2 {
+        return None;
+    }
+    let sections = compatible_sections(sections)?;
+    let params = section_parameters(&sections)?;
+
+    // Interpolate each column of control points across the sections
+    let degree_v = MAX_DEGREE_V.min(sections.len() - 1);
+    let knots_v = averaged_knots(&params, degree_v);
+    let last = sections.len() - 1;
+    let mut collocation = BandedMatrix::zeros(sections.len(), degree_v, degree_v);
+    for (k, &v) in params.iter().enumerate() {
+        let span = find_span(degree_v, &knots_v, last, v);
+        let basis = basis_functions(degree_v, &knots_v, span, v);
+        for (j, &b) in basis.iter().enumerate() {
+            if b != 0.0 {
+                collocation.set(k, span - degree_v + j, b);
+            }
+        }
+    }
+
+    let mut control_points = Vec::with_capacity(sections[0].control_points.len());
+    for i in 0..sections[0].control_points.len() {
+        let column: Vec<Point3> = sections.iter().map(|s| s.control_points[i]).collect();
+        let solve = |axis: fn(&Point3) -> f64| {
+            let values: Vec<f64> = column.iter().map(axis).collect();
+            collocation.solve(&values)
+        };
+        let (x, y, z) = (solve(|p| p.x)?, solve(|p| p.y)?, solve(|p| p.z)?);
+        control_points.push(
+            (0..sections.len())
+                .map(|k| DVec3::new(x[k], y[k], z[k]))
+                .collect(),
+        );
+    }
+
+    let first = &sections[0];
+    Some(BSplineSurface::new(
+        first.degree,
+        degree_v,
+        first.knots.clone(),
+        knots_v,
+        control_points,
+    ))
+}
+
+/// The sections on a common degree and knot vector over `[0, 1]`.
+fn compatible_sections(sections: &[BSplineCurve]) -> Option<Vec<BSplineCurve>> {
+    let degree = sections.iter().map(|s| s.degree).max()?;
+    let mut sections: Vec<BSplineCurve> = sections.to_vec();
+    for section in &mut sections {
+        let p = section.degree;
+        let end = section.knots.len() - p - 1;
+        let clamped = section.knots[..p].iter().all(|&k| k == section.knots[p])
+            && section.knots[end + 1..]
+                .iter()
+                .all(|&k| k == section.knots[end]);
+        if !clamped {
+            return None;
+        }
+        section.elevate_degree(degree - p);
+        section.reparameterize(0.0, 1.0);
+        // Exact ends, so only interior knots can differ between sections
+        let len = section.knots.len();
+        section.knots[..=degree].fill(0.0);
+        section.knots[len - degree - 1..].fill(1.0);
+    }
+
+    // Distinct interior knots over all sections, within the tolerance
+    let interior = |s: &BSplineCurve| degree + 1..s.knots.len() - degree - 1;
+    let mut values: Vec<f64> = sections
+        .iter()
+        .flat_map(|s| s.knots[interior(s)].iter().copied())
+        .collect();
+    values.sort_by(f64::total_cmp);
+    values.dedup_by(|k, first| *k - *first <= KNOT_TOLERANCE);
+
+    // Snap each section's knots to those values and count multiplicities
+    let mut multiplicities = vec![vec![0; values.len()]; sections.len()];
+    for (section, counts) in sections.iter_mut().zip(&mut multiplicities) {
+        let range = interior(section);
+        for knot in &mut section.knots[range] {
+            let i = values.partition_point(|&v| v <= *knot) - 1;
+            *knot = values[i];
+            counts[i] += 1;
+        }
+    }
+    let target: Vec<usize> = (0..values.len())
+        .map(|i| multiplicities.iter().map(|m| m[i]).max().unwrap_or(0))
+        .collect();
+
+    for (section, counts) in sections.iter_mut().zip(&multiplicities) {
+        let missing: Vec<f64> = values
+            .iter()
+            .zip(target.iter().zip(counts))
+            .flat_map(|(&v, (&target, &count))| std::iter::repeat(v).take(target - count))
+            .collect();
+        section.insert_knots(&missing);
+    }
+    Some(sections)
+}
+
+/// Parameter of each section across the surface: the mean over the columns
+/// of control points of the normalized chord length along the column.
+/// Columns that collapse to a point, e.g. at the apex of a cone, are left
+/// out.
+fn section_parameters(sections: &[BSplineCurve]) -> Option<Vec<f64>> {
+    let mut params = vec![0.0; sections.len()];
+    let mut columns = 0;
+    for i in 0..sections[0].control_points.len() {
+        let lengths: Vec<f64> = sections
+            .windows(2)
+            .map(|pair| (pair[1].control_points[i] - pair[0].control_points[i]).length())
+            .collect();
+        let total: f64 = lengths.iter().sum();
+        if total <= Tolerance::DEFAULT_LINEAR {
+            continue;
+        }
+        columns += 1;
+        let mut along = 0.0;
+        for (param, length) in params[1..].iter_mut().zip(&lengths) {
+            along += length / total;
+            *param += along;
+        }
+    }
+    if columns == 0 {
+        return None;
+    }
+    for param in &mut params {
+        *param /= columns as f64;
+    }
+    *params.last_mut()? = 1.0;
+    params
+        .windows(2)
+        .all(|pair| pair[0] < pair[1])
+        .then_some(params)
+}
+
+/// Clamped knot vector whose interior knots average `degree` consecutive
+/// parameters, which keeps the interpolation well conditioned.
+fn averaged_knots(params: &[f64], degree: usize) -> Vec<f64> {
+    let mut knots = vec![0.0; degree + 1];
+    for j in 1..params.len() - degree {
+        knots.push(params[j..j + degree].iter().sum::<f64>() / degree as f64);
+    }
+    knots.extend(std::iter::repeat(1.0).take(degree + 1));
+    knots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::Curve;
+    use crate::surface::Surface;
+
+    /// An open quadratic profile in the xy plane, lifted to `z`.
+    fn profile(z: f64, scale: f64) -> BSplineCurve {
+        let points = [
+            (1.0, 0.0),
+            (1.0, 1.0),
+            (-0.5, 1.5),
+            (-1.0, 0.0),
+            (0.0, -1.0),
+        ];
+        BSplineCurve::new(
+            2,
+            vec![0.0, 0.0, 0.0, 0.3, 0.6, 1.0, 1.0, 1.0],
+            points
+                .iter()
+                .map(|&(x, y)| DVec3::new(scale * x, scale * y, z))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_skin_through_differently_represented_sections() {
+        // The same profile three times over, as given, refined onto
+        // [0, 2] and raised to a cubic
+        let bottom = profile(0.0, 1.0);
+        let mut middle = profile(1.0, 1.0);
+        middle.insert_knots(&[0.45, 0.8]);
+        middle.reparameterize(0.0, 2.0);
+        let mut top = profile(2.0, 1.0);
+        top.elevate_degree(1);
+
+        let surface = skin_surface(&[bottom.clone(), middle, top.clone()]).unwrap();
+        assert_eq!(surface.degree_u, 3);
+        assert_eq!(surface.degree_v, 2);
+        assert_eq!(surface.domain_u(), (0.0, 1.0));
+        assert_eq!(surface.domain_v(), (0.0, 1.0));
+
+        // Evenly spaced sections sit at v = 0, 0.5 and 1
+        for i in 0..=10 {
+            let u = i as f64 / 10.0;
+            let p = bottom.point_at(u);
+            assert!((surface.point_at(u, 0.0) - p).length() < 1e-9);
+            let lifted = p + DVec3::Z;
+            assert!((surface.point_at(u, 0.5) - lifted).length() < 1e-9);
+            assert!((surface.point_at(u, 1.0) - top.point_at(u)).length() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_skin_tapered_column() {
+        // Four sections narrowing upwards, unevenly spaced
+        let heights = [0.0, 1.0, 3.0, 6.0];
+        let sections: Vec<BSplineCurve> = heights
+            .iter()
+            .map(|&z| profile(z, 1.0 - z / 10.0))
+            .collect();
+        let surface = skin_surface(&sections).unwrap();
+        assert_eq!(surface.degree_v, 3);
+
+        // Each section lies on the surface at its parameter
+        let params = section_parameters(&compatible_sections(&sections).unwrap()).unwrap();
+        assert_eq!(params[0], 0.0);
+        assert_eq!(params[3], 1.0);
+        assert!(params[1] < 0.25 && params[2] < 0.6);
+        for (section, &v) in sections.iter().zip(&params) {
+            for i in 0..=10 {
+                let u = i as f64 / 10.0;
+                let p = section.point_at(u);
+                assert!((surface.point_at(u, v) - p).length() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_skin_rejects_degenerate_input() {
+        assert!(skin_surface(&[]).is_none());
+        assert!(skin_surface(&[profile(0.0, 1.0)]).is_none());
+        assert!(skin_surface(&[profile(0.0, 1.0), profile(0.0, 1.0)]).is_none());
+
+        let mut unclamped = profile(1.0, 1.0);
+        unclamped.knots = vec![0.0, 0.1, 0.2, 0.3, 0.6, 0.8, 0.9, 1.0];
+        assert!(skin_surface(&[profile(0.0, 1.0), unclamped]).is_none());
+    }
+}
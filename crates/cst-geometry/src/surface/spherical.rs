@@ -2,10 +2,11 @@
 
 use std::f64::consts::PI;
 
-use cst_math::{Point3, Vector3, DVec3};
+use cst_math::coords::Spherical;
+use cst_math::{Point3, Vector3};
 use serde::{Deserialize, Serialize};
 
-use super::Surface;
+use super::{Surface, SurfaceKey};
 
 /// A spherical surface parameterized by longitude `u` in `[0, 2*PI]` and
 /// latitude `v` in `[-PI/2, PI/2]`.
@@ -27,17 +28,11 @@ impl SphericalSurface {
 
 impl Surface for SphericalSurface {
     fn point_at(&self, u: f64, v: f64) -> Point3 {
-        let x = self.radius * v.cos() * u.cos();
-        let y = self.radius * v.cos() * u.sin();
-        let z = self.radius * v.sin();
-        self.center + DVec3::new(x, y, z)
+        self.center + Spherical::new(self.radius, PI / 2.0 - v, u).to_cartesian()
     }
 
     fn normal_at(&self, u: f64, v: f64) -> Vector3 {
-        let x = v.cos() * u.cos();
-        let y = v.cos() * u.sin();
-        let z = v.sin();
-        DVec3::new(x, y, z).normalize()
+        Spherical::new(1.0, PI / 2.0 - v, u).to_cartesian()
     }
 
     fn domain_u(&self) -> (f64, f64) {
@@ -47,11 +42,17 @@ impl Surface for SphericalSurface {
     fn domain_v(&self) -> (f64, f64) {
         (-PI / 2.0, PI / 2.0)
     }
+
+    fn cache_key(&self) -> Option<SurfaceKey> {
+        let c = self.center;
+        Some(SurfaceKey::new("sphere", [c.x, c.y, c.z, self.radius]))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use cst_math::DVec3;
 
     #[test]
     fn test_spherical_points_on_sphere() {
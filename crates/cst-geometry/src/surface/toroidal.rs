@@ -5,7 +5,7 @@ use std::f64::consts::PI;
 use cst_math::{Point3, Vector3, DVec3};
 use serde::{Deserialize, Serialize};
 
-use super::Surface;
+use super::{Surface, SurfaceKey};
 
 /// A toroidal surface (torus) parameterized by `u` (major angle) and `v` (minor angle),
 /// both in `[0, 2*PI]`.
@@ -62,6 +62,12 @@ impl Surface for ToroidalSurface {
     fn domain_v(&self) -> (f64, f64) {
         (0.0, 2.0 * PI)
     }
+
+    fn cache_key(&self) -> Option<SurfaceKey> {
+        let (c, a) = (self.center, self.axis);
+        let parameters = [c.x, c.y, c.z, a.x, a.y, a.z, self.major_radius, self.minor_radius];
+        Some(SurfaceKey::new("torus", parameters))
+    }
 }
 
 #[cfg(test)]
@@ -2,7 +2,7 @@
 
 use cst_math::Point3;
 
-use crate::curve::Curve;
+use crate::curve::{CompositeCurve, Curve};
 use crate::surface::Surface;
 
 /// Convert a curve to a polyline using adaptive subdivision.
@@ -24,6 +24,19 @@ pub fn curve_to_polyline(curve: &dyn Curve, tolerance: f64) -> Vec<Point3> {
     points
 }
 
+/// Convert a composite curve to a polyline, one segment at a time so that
+/// the corners between segments are kept exactly. Shared end points appear
+/// once.
+pub fn composite_to_polyline(curve: &CompositeCurve, tolerance: f64) -> Vec<Point3> {
+    let mut points: Vec<Point3> = Vec::new();
+    for segment in &curve.segments {
+        let segment_points = curve_to_polyline(segment.as_curve(), tolerance);
+        let skip = usize::from(!points.is_empty());
+        points.extend(segment_points.into_iter().skip(skip));
+    }
+    points
+}
+
 /// Maximum recursion depth for adaptive subdivision.
 const MAX_DEPTH: u32 = 12;
 
@@ -78,14 +91,13 @@ pub fn surface_to_triangles(
     let v_count = v_divs + 1;
 
     // Generate vertices
-    let mut vertices = Vec::with_capacity(u_count * v_count);
-    for i in 0..u_count {
-        let u = u_min + (u_max - u_min) * i as f64 / u_divs as f64;
-        for j in 0..v_count {
-            let v = v_min + (v_max - v_min) * j as f64 / v_divs as f64;
-            vertices.push(surface.point_at(u, v));
-        }
-    }
+    let us: Vec<f64> = (0..u_count)
+        .map(|i| u_min + (u_max - u_min) * i as f64 / u_divs as f64)
+        .collect();
+    let vs: Vec<f64> = (0..v_count)
+        .map(|j| v_min + (v_max - v_min) * j as f64 / v_divs as f64)
+        .collect();
+    let vertices = surface.points_at_grid(&us, &vs);
 
     // Generate triangles (two triangles per quad)
     let mut triangles = Vec::with_capacity(u_divs * v_divs * 2);
@@ -165,4 +177,37 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_composite_to_polyline_keeps_corners() {
+        use crate::curve::{CircularArc, CompositeCurve, CurveSegment};
+
+        // Square with one rounded corner of radius 1 at (10, 10)
+        let arc = CircularArc::new(
+            DVec3::new(9.0, 9.0, 0.0),
+            DVec3::Z,
+            DVec3::X,
+            1.0,
+            0.0,
+            std::f64::consts::FRAC_PI_2,
+        );
+        let curve = CompositeCurve::new(vec![
+            CurveSegment::Line(Line::new(DVec3::ZERO, DVec3::new(10.0, 0.0, 0.0))),
+            CurveSegment::Line(Line::new(DVec3::new(10.0, 0.0, 0.0), DVec3::new(10.0, 9.0, 0.0))),
+            CurveSegment::Arc(arc),
+            CurveSegment::Line(Line::new(DVec3::new(9.0, 10.0, 0.0), DVec3::new(0.0, 10.0, 0.0))),
+            CurveSegment::Line(Line::new(DVec3::new(0.0, 10.0, 0.0), DVec3::ZERO)),
+        ]);
+        let points = composite_to_polyline(&curve, 0.001);
+        assert!(points.len() > 10);
+        assert!(points.windows(2).all(|w| (w[1] - w[0]).length() > 1e-9));
+        assert!(points.contains(&DVec3::new(10.0, 0.0, 0.0)));
+        assert!((points[points.len() - 1] - points[0]).length() < 1e-10);
+        for p in &points {
+            if p.x > 9.0 && p.y > 9.0 {
+                let r = (*p - DVec3::new(9.0, 9.0, 0.0)).length();
+                assert!((r - 1.0).abs() < 1e-10);
+            }
+        }
+    }
 }
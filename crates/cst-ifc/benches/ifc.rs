@@ -0,0 +1,60 @@
+//! Parser and mesh conversion hot paths, measured on `samples/office.ifc`.
+//!
+//! `cargo bench -p cst-ifc -- --save-baseline main` on the base branch, then
+//! `cargo bench -p cst-ifc -- --baseline main` to compare a change against it.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use cst_ifc::ifc_options::IfcPipelineOptions;
+use cst_ifc::ifc_query::IfcQuery;
+use cst_ifc::ifc_reader::read_ifc;
+use cst_ifc::ifc_to_mesh::faces_to_trimesh;
+use cst_ifc::step_lexer::tokenize;
+use cst_ifc::step_parser::parse_step;
+
+const SAMPLE: &str = include_str!("../../../samples/office.ifc");
+
+fn step_lexing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("step");
+    group.throughput(Throughput::Bytes(SAMPLE.len() as u64));
+    group.bench_function("tokenize", |b| {
+        b.iter(|| tokenize(black_box(SAMPLE)).unwrap())
+    });
+    group.bench_function("parse_step", |b| {
+        b.iter(|| parse_step(black_box(SAMPLE)).unwrap())
+    });
+    group.finish();
+}
+
+fn entity_resolution(c: &mut Criterion) {
+    let options = IfcPipelineOptions::default();
+    let mut group = c.benchmark_group("resolve");
+    group.throughput(Throughput::Bytes(SAMPLE.len() as u64));
+    group.bench_function("read_ifc", |b| {
+        b.iter(|| read_ifc(black_box(SAMPLE.as_bytes()), &options).unwrap())
+    });
+    group.bench_function("query_index", |b| {
+        b.iter(|| IfcQuery::from_reader(black_box(SAMPLE.as_bytes())).unwrap())
+    });
+    group.finish();
+}
+
+fn triangulation(c: &mut Criterion) {
+    let meshes = read_ifc(SAMPLE.as_bytes(), &IfcPipelineOptions::default()).unwrap();
+    let faces: usize = meshes.iter().map(|m| m.faces.len()).sum();
+
+    let mut group = c.benchmark_group("triangulate");
+    group.throughput(Throughput::Elements(faces as u64));
+    group.bench_function("faces_to_trimesh", |b| {
+        b.iter(|| {
+            for mesh in &meshes {
+                black_box(faces_to_trimesh(&mesh.name, &mesh.faces));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, step_lexing, entity_resolution, triangulation);
+criterion_main!(benches);
@@ -0,0 +1,353 @@
+//! Compact storage for the text of parsed entities.
+//!
+//! Large models keep millions of entities in memory. Instead of two owned
+//! strings each, an entity's type name points into the static table of
+//! types the reader keeps and its argument text is a byte range of a buffer
+//! shared with the entities parsed around it.
+
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::sync::Arc;
+
+use cst_core::StepId;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::ifc_reader::{kept_types, IfcRawEntity};
+
+/// Size at which the parser hands a filled argument buffer to its entities.
+const CHUNK_BYTES: usize = 4 << 20;
+
+/// An entity type name such as `IFCWALL`. Names of the types the reader
+/// keeps are shared from a static table, so copying and comparing them
+/// never touches the heap; any other name owns its text.
+#[derive(Clone)]
+pub struct TypeName(Name);
+
+#[derive(Clone)]
+enum Name {
+    Known(&'static str),
+    Other(Arc<str>),
+}
+
+impl TypeName {
+    /// The type name `name`, from the static table when it is there.
+    pub fn new(name: &str) -> Self {
+        match kept_types().get(name) {
+            Some(&known) => TypeName(Name::Known(known)),
+            None => TypeName(Name::Other(Arc::from(name))),
+        }
+    }
+
+    /// A name that is already `'static`, e.g. from a type filter. Needs no
+    /// lookup.
+    pub const fn from_static(name: &'static str) -> Self {
+        TypeName(Name::Known(name))
+    }
+
+    pub fn as_str(&self) -> &str {
+        match &self.0 {
+            Name::Known(name) => name,
+            Name::Other(name) => name,
+        }
+    }
+}
+
+impl Deref for TypeName {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for TypeName {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl PartialEq for TypeName {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for TypeName {}
+
+impl PartialOrd for TypeName {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TypeName {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl Hash for TypeName {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl PartialEq<str> for TypeName {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for TypeName {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl From<&str> for TypeName {
+    fn from(name: &str) -> Self {
+        TypeName::new(name)
+    }
+}
+
+impl fmt::Debug for TypeName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for TypeName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for TypeName {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for TypeName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(TypeName::new(&name))
+    }
+}
+
+/// The type names of one model, so that each name outside the static
+/// table is stored once per model rather than once per entity. Dropped
+/// with the parse that filled it.
+#[derive(Debug, Default)]
+pub struct TypeNames {
+    names: HashSet<TypeName>,
+}
+
+impl TypeNames {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The type name `name`, shared with earlier entities of its type.
+    pub fn get(&mut self, name: &str) -> TypeName {
+        if let Some(known) = self.names.get(name) {
+            return known.clone();
+        }
+        let type_name = TypeName::new(name);
+        self.names.insert(type_name.clone());
+        type_name
+    }
+}
+
+/// Raw argument text of an entity (between the outer parentheses), a
+/// range of a buffer shared with other entities. Cloning it is cheap.
+#[derive(Clone)]
+pub struct RawArgs {
+    buffer: Arc<str>,
+    start: u32,
+    end: u32,
+}
+
+impl RawArgs {
+    /// Arguments in a buffer of their own.
+    pub fn new(text: &str) -> Self {
+        RawArgs {
+            buffer: Arc::from(text),
+            start: 0,
+            end: text.len() as u32,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.buffer[self.start as usize..self.end as usize]
+    }
+}
+
+impl Deref for RawArgs {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl PartialEq for RawArgs {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for RawArgs {}
+
+impl PartialEq<str> for RawArgs {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for RawArgs {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl From<&str> for RawArgs {
+    fn from(text: &str) -> Self {
+        RawArgs::new(text)
+    }
+}
+
+impl fmt::Debug for RawArgs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for RawArgs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for RawArgs {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for RawArgs {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Ok(RawArgs::new(&text))
+    }
+}
+
+/// Collects the argument text of parsed entities into shared buffers.
+/// Entities reach the table once their buffer is full or on
+/// [`finish`](Self::finish).
+pub(crate) struct EntityArena {
+    buffer: String,
+    pending: Vec<(StepId, TypeName, u32, u32)>,
+}
+
+impl EntityArena {
+    pub(crate) fn new() -> Self {
+        EntityArena {
+            buffer: String::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    pub(crate) fn push(
+        &mut self,
+        entity_id: StepId,
+        type_name: TypeName,
+        args: &str,
+        entities: &mut HashMap<StepId, IfcRawEntity>,
+    ) {
+        if !self.pending.is_empty() && self.buffer.len() + args.len() > CHUNK_BYTES {
+            self.flush(entities);
+        }
+        let start = self.buffer.len() as u32;
+        self.buffer.push_str(args);
+        self.pending
+            .push((entity_id, type_name, start, self.buffer.len() as u32));
+    }
+
+    /// Hand the last buffer to its entities.
+    pub(crate) fn finish(mut self, entities: &mut HashMap<StepId, IfcRawEntity>) {
+        self.flush(entities);
+    }
+
+    fn flush(&mut self, entities: &mut HashMap<StepId, IfcRawEntity>) {
+        let buffer: Arc<str> = Arc::from(std::mem::take(&mut self.buffer));
+        for (entity_id, type_name, start, end) in self.pending.drain(..) {
+            let raw_args = RawArgs {
+                buffer: Arc::clone(&buffer),
+                start,
+                end,
+            };
+            entities.insert(
+                entity_id,
+                IfcRawEntity {
+                    entity_id,
+                    type_name,
+                    raw_args,
+                },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_type_names_are_shared() {
+        let wall = TypeName::new(&String::from("IFCWALL"));
+        assert!(std::ptr::eq(
+            wall.as_str(),
+            TypeName::new("IFCWALL").as_str()
+        ));
+        assert_eq!(wall, TypeName::from_static("IFCWALL"));
+        assert_eq!(wall, "IFCWALL");
+        assert_eq!(format!("{}_{}", wall, 5), "IFCWALL_5");
+    }
+
+    #[test]
+    fn test_other_type_names_are_owned_per_model() {
+        // Unknown names, e.g. from an uploaded file, are not kept globally
+        let name = TypeName::new("IFCMADEUP");
+        assert!(matches!(name.0, Name::Other(_)));
+        assert!(!kept_types().contains("IFCMADEUP"));
+
+        let mut names = TypeNames::new();
+        let first = names.get("IFCMADEUP");
+        let second = names.get(&String::from("IFCMADEUP"));
+        assert!(std::ptr::eq(first.as_str(), second.as_str()));
+        assert_eq!(first, name);
+        assert!(matches!(names.get("IFCWALL").0, Name::Known(_)));
+    }
+
+    #[test]
+    fn test_entities_share_argument_buffers() {
+        let mut entities = HashMap::new();
+        let mut arena = EntityArena::new();
+        let point = TypeName::from_static("IFCCARTESIANPOINT");
+        arena.push(StepId(1), point.clone(), "(0.,0.,0.)", &mut entities);
+        arena.push(StepId(2), point, "(1.,2.,3.)", &mut entities);
+        assert!(entities.is_empty());
+        arena.finish(&mut entities);
+
+        let (first, second) = (&entities[&StepId(1)], &entities[&StepId(2)]);
+        assert_eq!(first.raw_args, "(0.,0.,0.)");
+        assert_eq!(second.raw_args, "(1.,2.,3.)");
+        assert!(Arc::ptr_eq(&first.raw_args.buffer, &second.raw_args.buffer));
+        assert_eq!(second.raw_args, RawArgs::new("(1.,2.,3.)"));
+    }
+}
@@ -0,0 +1,148 @@
+//! Element assemblies such as stairs.
+//!
+//! An IFCSTAIR rarely carries geometry of its own: IFCRELAGGREGATES
+//! decomposes it into flights, landing slabs, railings and members, each a
+//! product with its own meshes. [`stair_assemblies`] gathers those parts
+//! back under their stair so a scene tree can show each stair as one
+//! group.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use cst_core::{ProductId, StepId};
+use serde::{Deserialize, Serialize};
+
+use crate::ifc_reader::{
+    extract_single_ref, parse_entity_refs, product_name, split_ifc_args, IfcRawEntity,
+    PRODUCT_TYPES,
+};
+
+/// Entity types the parser has to keep for [`stair_assemblies`].
+pub(crate) const ASSEMBLY_TYPES: &[&str] = &["IFCRELAGGREGATES"];
+
+/// A product and the products it is decomposed into.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IfcAssembly {
+    pub id: ProductId,
+    /// The product's Name, or `<TYPE>_<id>` when it has none
+    pub name: String,
+    /// Upper-case IFC type of the assembly, e.g. `IFCSTAIR`
+    pub ifc_type: String,
+    /// Products aggregated into the assembly, directly or through
+    /// sub-assemblies, in id order
+    pub parts: Vec<ProductId>,
+}
+
+impl IfcAssembly {
+    /// Whether `id` is the assembly itself or one of its parts.
+    pub fn contains(&self, id: ProductId) -> bool {
+        self.id == id || self.parts.binary_search(&id).is_ok()
+    }
+}
+
+/// Every IFCSTAIR with the flights, landings and railings it aggregates,
+/// in id order.
+pub fn stair_assemblies(entities: &HashMap<StepId, IfcRawEntity>) -> Vec<IfcAssembly> {
+    let decomposition = aggregates(entities);
+    let mut stairs: Vec<&IfcRawEntity> = entities
+        .values()
+        .filter(|e| e.type_name == "IFCSTAIR")
+        .collect();
+    stairs.sort_by_key(|e| e.entity_id);
+
+    stairs
+        .into_iter()
+        .map(|stair| {
+            let mut parts = Vec::new();
+            let mut seen = HashSet::from([stair.entity_id]);
+            let mut pending = vec![stair.entity_id];
+            while let Some(whole) = pending.pop() {
+                for &part in decomposition.get(&whole).into_iter().flatten() {
+                    if !seen.insert(part) {
+                        continue;
+                    }
+                    let is_product = entities
+                        .get(&part)
+                        .is_some_and(|e| PRODUCT_TYPES.contains(&e.type_name.as_str()));
+                    if is_product {
+                        parts.push(ProductId(part));
+                    }
+                    pending.push(part);
+                }
+            }
+            parts.sort_unstable();
+            let id = ProductId(stair.entity_id);
+            IfcAssembly {
+                id,
+                name: product_name(id, stair),
+                ifc_type: stair.type_name.to_string(),
+                parts,
+            }
+        })
+        .collect()
+}
+
+/// Whole -> parts from every IFCRELAGGREGATES.
+pub(crate) fn aggregates(entities: &HashMap<StepId, IfcRawEntity>) -> BTreeMap<StepId, Vec<StepId>> {
+    let mut decomposition: BTreeMap<StepId, Vec<StepId>> = BTreeMap::new();
+    for entity in entities.values() {
+        // IFCRELAGGREGATES(GlobalId, OwnerHistory, Name, Description,
+        //   RelatingObject, RelatedObjects)
+        if entity.type_name != "IFCRELAGGREGATES" {
+            continue;
+        }
+        let args = split_ifc_args(&entity.raw_args);
+        let Some(whole) = args.get(4).and_then(|a| extract_single_ref(a)) else {
+            continue;
+        };
+        let parts = args
+            .get(5)
+            .map(|a| parse_entity_refs(a))
+            .unwrap_or_default();
+        decomposition.entry(whole).or_default().extend(parts);
+    }
+    decomposition
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ifc_progress::NoProgress;
+    use crate::ifc_reader::parse_ifc_entities_from_reader;
+
+    #[test]
+    fn test_stair_gathers_nested_parts() {
+        // The stair aggregates a flight and a landing; the flight in turn
+        // aggregates a railing. The proxy is not part of any stair.
+        let model = "ISO-10303-21;
+HEADER;
+FILE_SCHEMA(('IFC4'));
+ENDSEC;
+DATA;
+#10= IFCSTAIR('g10',$,'Main stair',$,$,$,$,$,$);
+#11= IFCSTAIRFLIGHT('g11',$,'Flight',$,$,$,$,$,$,$,$,$,$);
+#12= IFCSLAB('g12',$,'Landing',$,$,$,$,$,$);
+#13= IFCRAILING('g13',$,'Railing',$,$,$,$,$,$);
+#14= IFCBUILDINGELEMENTPROXY('g14',$,$,$,$,$,$,$,$);
+#15= IFCSTAIR('g15',$,$,$,$,$,$,$,$);
+#20= IFCRELAGGREGATES('g20',$,$,$,#10,(#11,#12));
+#21= IFCRELAGGREGATES('g21',$,$,$,#11,(#13));
+ENDSEC;
+END-ISO-10303-21;
+";
+        let entities = parse_ifc_entities_from_reader(model.as_bytes(), 0, &NoProgress).unwrap();
+        let stairs = stair_assemblies(&entities);
+        assert_eq!(stairs.len(), 2);
+
+        let main = &stairs[0];
+        assert_eq!(main.id, ProductId(StepId(10)));
+        assert_eq!(main.name, "Main stair");
+        assert_eq!(main.ifc_type, "IFCSTAIR");
+        let parts: Vec<u64> = main.parts.iter().map(|p| p.value()).collect();
+        assert_eq!(parts, vec![11, 12, 13]);
+        assert!(main.contains(ProductId(StepId(13))));
+        assert!(!main.contains(ProductId(StepId(14))));
+
+        assert_eq!(stairs[1].name, "IFCSTAIR_15");
+        assert!(stairs[1].parts.is_empty());
+    }
+}
@@ -0,0 +1,466 @@
+//! Binary cache of parsed and tessellated IFC models.
+//!
+//! Parsing and tessellating a large model takes minutes, while reading the
+//! finished meshes back takes about as long as hashing the file.
+//! [`load_or_build`] keeps one cache file next to the model
+//! (`building.ifc.cstcache`) with the tessellated meshes, the storey tree
+//! and the element properties. The cache is keyed by a hash of the IFC
+//! content and of the options that change geometry, so editing either
+//! rebuilds it on the next run.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use cst_core::{CstError, Diagnostics, ModelUnits, ProductId, Result, StepId};
+use cst_math::rtree::PackedRTree;
+use cst_math::Aabb3;
+use log::{debug, warn};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use xxhash_rust::xxh3::Xxh3;
+
+use crate::ifc_assembly::{stair_assemblies, IfcAssembly};
+use crate::ifc_options::IfcPipelineOptions;
+use crate::ifc_progress::{check_cancelled, ProgressSink, ProgressStage};
+use crate::ifc_query::IfcQuery;
+use crate::ifc_reader::{
+    log_diagnostics, parse_ifc_entities_from_reader, parse_ifc_entities_with_progress,
+    resolve_meshes, IfcInstance, IfcRawEntity,
+};
+use crate::ifc_spatial::{spatial_tree, SpatialNode};
+use crate::ifc_to_mesh::{faces_to_trimesh_with_diagnostics, IfcTriMesh};
+
+const MAGIC: &[u8; 4] = b"CSTC";
+/// Bump when the layout of [`CachedModel`] or the tessellation changes.
+const FORMAT_VERSION: u32 = 9;
+const EXTENSION: &str = "cstcache";
+
+/// A tessellated element mesh.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedMesh {
+    pub mesh: IfcTriMesh,
+    pub color: Option<[f32; 3]>,
+    /// Product the mesh belongs to; `None` for meshes from the brep-only
+    /// fallback.
+    pub product: Option<ProductId>,
+    /// Representation map the mesh was placed from, see
+    /// [`instance_groups`](crate::ifc_reader::instance_groups)
+    pub instance: Option<IfcInstance>,
+    /// World box around the mesh; `None` when it has no vertices
+    pub bounds: Option<Aabb3>,
+}
+
+/// Metadata of a product with geometry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedProduct {
+    /// Upper-case IFC type, e.g. `IFCWALL`
+    pub ifc_type: String,
+    pub global_id: Option<String>,
+    pub name: Option<String>,
+    pub storey: Option<String>,
+    pub properties: Vec<(String, String)>,
+}
+
+/// Everything the exporters need from an IFC file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CachedModel {
+    /// Meshes in reader order
+    pub meshes: Vec<CachedMesh>,
+    /// Product id -> metadata, for every product in `meshes`
+    pub products: BTreeMap<ProductId, CachedProduct>,
+    /// Storey name -> contained product ids
+    pub storeys: BTreeMap<String, Vec<ProductId>>,
+    /// Storey name -> elevation, scaled like the meshes
+    pub elevations: BTreeMap<String, f64>,
+    /// Units of the meshes and elevations, after the unit override
+    pub units: ModelUnits,
+    /// Stairs with the parts they aggregate, for those with meshes
+    pub assemblies: Vec<IfcAssembly>,
+    /// Project -> site -> building -> storey tree; `None` when the file
+    /// has no project
+    pub spatial: Option<SpatialNode>,
+    /// Mesh bounds by position in `meshes`, for box queries
+    pub index: PackedRTree,
+}
+
+impl CachedModel {
+    /// Parse and tessellate the IFC file at `path` without touching the
+    /// cache.
+    pub fn build(
+        path: &Path,
+        options: &IfcPipelineOptions,
+        progress: &dyn ProgressSink,
+    ) -> Result<Self> {
+        let entities = parse_ifc_entities_with_progress(path, progress)?;
+        Self::from_entities(entities, options, progress)
+    }
+
+    /// Parse and tessellate IFC text from `reader`, e.g. a byte slice.
+    pub fn from_reader<R: BufRead>(
+        reader: R,
+        options: &IfcPipelineOptions,
+        progress: &dyn ProgressSink,
+    ) -> Result<Self> {
+        let entities = parse_ifc_entities_from_reader(reader, 0, progress)?;
+        Self::from_entities(entities, options, progress)
+    }
+
+    /// Positions in `meshes` of the meshes whose bounds intersect `query`,
+    /// in ascending order.
+    pub fn meshes_in(&self, query: &Aabb3) -> Vec<usize> {
+        self.index.search(query)
+    }
+
+    fn from_entities(
+        entities: HashMap<StepId, IfcRawEntity>,
+        options: &IfcPipelineOptions,
+        progress: &dyn ProgressSink,
+    ) -> Result<Self> {
+        let mut diagnostics = Diagnostics::new();
+        let resolved = resolve_meshes(&entities, options, progress, &mut diagnostics)?;
+
+        progress.start(ProgressStage::Tessellate, resolved.len() as u64);
+        let tessellated: Vec<(CachedMesh, Diagnostics)> = resolved
+            .into_par_iter()
+            .filter_map(|(product, data)| {
+                if progress.is_cancelled() {
+                    return None;
+                }
+                let mut mesh_diagnostics = Diagnostics::new();
+                let mesh = faces_to_trimesh_with_diagnostics(
+                    &data.name,
+                    &data.faces,
+                    options.tessellation_tolerance,
+                    &mut mesh_diagnostics,
+                );
+                progress.advance(ProgressStage::Tessellate, 1);
+                let cached = CachedMesh {
+                    bounds: Aabb3::from_points(&mesh.positions),
+                    mesh,
+                    color: data.color,
+                    product,
+                    instance: data.instance,
+                };
+                Some((cached, mesh_diagnostics))
+            })
+            .collect();
+        progress.finish(ProgressStage::Tessellate);
+        check_cancelled(progress)?;
+        let mut meshes = Vec::with_capacity(tessellated.len());
+        for (mesh, mut mesh_diagnostics) in tessellated {
+            meshes.push(mesh);
+            diagnostics.append(&mut mesh_diagnostics);
+        }
+        log_diagnostics(&diagnostics);
+        let index = mesh_index(&meshes);
+
+        let ids: HashSet<ProductId> = meshes.iter().filter_map(|m| m.product).collect();
+        let assemblies: Vec<IfcAssembly> = stair_assemblies(&entities)
+            .into_iter()
+            .filter(|a| ids.contains(&a.id) || a.parts.iter().any(|p| ids.contains(p)))
+            .collect();
+        let spatial = spatial_tree(&entities);
+        let query = IfcQuery::from_entities(entities);
+        let products = ids
+            .iter()
+            .map(|&id| {
+                let product = CachedProduct {
+                    ifc_type: query.type_of(id).unwrap_or_default().to_string(),
+                    global_id: query.global_id(id),
+                    name: query.name(id),
+                    storey: query.storey_of(id).map(str::to_string),
+                    properties: query.properties(id).to_vec(),
+                };
+                (id, product)
+            })
+            .collect();
+        let storeys = query
+            .storeys()
+            .into_iter()
+            .map(|storey| (storey.to_string(), query.elements_in_storey(storey)))
+            .collect();
+        let elevations = query
+            .storeys()
+            .into_iter()
+            .filter_map(|storey| {
+                let elevation = query.storey_elevation(storey)?;
+                Some((storey.to_string(), elevation * options.scale()))
+            })
+            .collect();
+        Ok(Self {
+            meshes,
+            products,
+            storeys,
+            elevations,
+            units: query.units().with_length_scale(options.scale()),
+            assemblies,
+            spatial,
+            index,
+        })
+    }
+}
+
+/// Index of the mesh bounds by position in `meshes`; meshes without
+/// vertices are left out.
+fn mesh_index(meshes: &[CachedMesh]) -> PackedRTree {
+    let entries: Vec<(usize, Aabb3)> = meshes
+        .iter()
+        .enumerate()
+        .filter_map(|(i, m)| Some((i, m.bounds?)))
+        .collect();
+    PackedRTree::from_entries(&entries)
+}
+
+/// Cache file for `ifc_path`: the same name with `.cstcache` appended.
+pub fn cache_path(ifc_path: &Path) -> PathBuf {
+    let mut name = ifc_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(EXTENSION);
+    ifc_path.with_file_name(name)
+}
+
+/// Hash of the file content and of the options that affect the cached
+/// geometry.
+pub fn cache_key(ifc_path: &Path, options: &IfcPipelineOptions) -> Result<u128> {
+    let mut hasher = Xxh3::new();
+    hasher.update(&FORMAT_VERSION.to_le_bytes());
+    let settings = (
+        options.tessellation_tolerance,
+        options.unit_scale,
+        &options.type_filter,
+        &options.exclude_types,
+        &options.storey,
+        &options.type_colors,
+    );
+    hasher.update(format!("{:?}", settings).as_bytes());
+
+    let mut file = File::open(ifc_path)?;
+    let mut buffer = vec![0u8; 1 << 20];
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    Ok(hasher.digest128())
+}
+
+/// The model from the cache next to `ifc_path` when it matches the file
+/// and `options`; otherwise build it and refresh the cache. Failing to
+/// write the cache is logged, not returned.
+pub fn load_or_build(
+    ifc_path: &Path,
+    options: &IfcPipelineOptions,
+    progress: &dyn ProgressSink,
+) -> Result<CachedModel> {
+    let key = cache_key(ifc_path, options)?;
+    let path = cache_path(ifc_path);
+    match read_cache(&path, key) {
+        Ok(Some(model)) => {
+            debug!(
+                "Loaded {} from cache {}",
+                ifc_path.display(),
+                path.display()
+            );
+            return Ok(model);
+        }
+        Ok(None) => debug!("Cache {} is missing or stale", path.display()),
+        Err(e) => warn!("Ignoring unreadable cache {}: {}", path.display(), e),
+    }
+
+    let model = CachedModel::build(ifc_path, options, progress)?;
+    match write_cache(&path, key, &model) {
+        Ok(()) => debug!("Wrote cache {}", path.display()),
+        Err(e) => warn!("Failed to write cache {}: {}", path.display(), e),
+    }
+    Ok(model)
+}
+
+/// `Ok(None)` when there is no cache file or it was written for another
+/// key or format version.
+fn read_cache(path: &Path, key: u128) -> Result<Option<CachedModel>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let mut reader = BufReader::new(file);
+    let mut header = [0u8; 24];
+    if reader.read_exact(&mut header).is_err()
+        || &header[..4] != MAGIC
+        || header[4..8] != FORMAT_VERSION.to_le_bytes()
+        || header[8..24] != key.to_le_bytes()
+    {
+        return Ok(None);
+    }
+    bincode::deserialize_from(reader)
+        .map(Some)
+        .map_err(|e| CstError::Parse(format!("corrupt cache: {}", e)))
+}
+
+/// Write to a temporary file first so readers never see a partial cache.
+fn write_cache(path: &Path, key: u128, model: &CachedModel) -> Result<()> {
+    let partial = path.with_extension(format!("{}.partial", EXTENSION));
+    let mut out = BufWriter::new(File::create(&partial)?);
+    out.write_all(MAGIC)?;
+    out.write_all(&FORMAT_VERSION.to_le_bytes())?;
+    out.write_all(&key.to_le_bytes())?;
+    bincode::serialize_into(&mut out, model)
+        .map_err(|e| CstError::InvalidOperation(format!("failed to encode cache: {}", e)))?;
+    out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+    std::fs::rename(&partial, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ifc_progress::NoProgress;
+    use cst_core::LengthUnit;
+    use cst_math::DVec3;
+
+    const MODEL: &str = "ISO-10303-21;
+HEADER;
+FILE_SCHEMA(('IFC2X3'));
+ENDSEC;
+DATA;
+#1= IFCCARTESIANPOINT((0.,0.,0.));
+#2= IFCCARTESIANPOINT((1.,0.,0.));
+#3= IFCCARTESIANPOINT((1.,1.,0.));
+#4= IFCCARTESIANPOINT((0.,1.,0.));
+#5= IFCPOLYLOOP((#1,#2,#3,#4));
+#6= IFCFACEOUTERBOUND(#5,.T.);
+#7= IFCFACE((#6));
+#8= IFCCLOSEDSHELL((#7));
+#9= IFCFACETEDBREP(#8);
+#10= IFCSHAPEREPRESENTATION($,'Body','Brep',(#9));
+#11= IFCPRODUCTDEFINITIONSHAPE($,$,(#10));
+#20= IFCWALL('2O2Fr$t4X7Zf8NOew3FLOH',$,'Wall A',$,$,$,#11,$);
+#30= IFCBUILDINGSTOREY('st1',$,'Level 1',$,$,$,$,$,.ELEMENT.,0.);
+#31= IFCRELCONTAINEDINSPATIALSTRUCTURE('r1',$,$,$,(#20),#30);
+#40= IFCPROPERTYSINGLEVALUE('FireRating',$,IFCLABEL('REI120'),$);
+#41= IFCPROPERTYSET('p1',$,'Pset_WallCommon',$,(#40));
+#42= IFCRELDEFINESBYPROPERTIES('r2',$,$,$,(#20),#41);
+#50= IFCSIUNIT(*,.LENGTHUNIT.,.MILLI.,.METRE.);
+ENDSEC;
+END-ISO-10303-21;
+";
+
+    fn write_model(dir: &Path) -> PathBuf {
+        let path = dir.join("model.ifc");
+        std::fs::write(&path, MODEL).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_build_collects_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_model(dir.path());
+        let model = CachedModel::build(&path, &IfcPipelineOptions::default(), &NoProgress).unwrap();
+        assert_eq!(model.meshes.len(), 1);
+        assert_eq!(model.meshes[0].product, Some(ProductId::new(20)));
+        assert_eq!(model.meshes[0].mesh.indices.len(), 6);
+        let wall = &model.products[&ProductId::new(20)];
+        assert_eq!(wall.ifc_type, "IFCWALL");
+        assert_eq!(wall.storey.as_deref(), Some("Level 1"));
+        assert_eq!(
+            wall.properties,
+            vec![("FireRating".to_string(), "REI120".to_string())]
+        );
+        assert_eq!(model.storeys["Level 1"], [ProductId::new(20)]);
+        assert_eq!(model.elevations["Level 1"], 0.0);
+        assert_eq!(model.units.length, LengthUnit::Millimetre);
+
+        let metres = IfcPipelineOptions {
+            unit_scale: Some(0.001),
+            ..Default::default()
+        };
+        let model = CachedModel::build(&path, &metres, &NoProgress).unwrap();
+        assert_eq!(model.units.length, LengthUnit::Metre);
+    }
+
+    #[test]
+    fn test_mesh_bounds_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_model(dir.path());
+        let model = CachedModel::build(&path, &IfcPipelineOptions::default(), &NoProgress).unwrap();
+        let bounds = model.meshes[0].bounds.unwrap();
+        assert_eq!(bounds.min, DVec3::ZERO);
+        assert_eq!(bounds.max, DVec3::new(1.0, 1.0, 0.0));
+
+        let around = |min: DVec3, max: DVec3| model.meshes_in(&Aabb3::new(min, max));
+        assert_eq!(
+            around(DVec3::new(0.5, 0.5, -1.0), DVec3::splat(2.0)),
+            vec![0]
+        );
+        assert!(around(DVec3::splat(1.5), DVec3::splat(2.0)).is_empty());
+        assert!(around(DVec3::new(0.0, 0.0, 0.5), DVec3::ONE).is_empty());
+    }
+
+    #[test]
+    fn test_load_or_build_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_model(dir.path());
+        let options = IfcPipelineOptions::default();
+        assert_eq!(cache_path(&path), dir.path().join("model.ifc.cstcache"));
+
+        let built = load_or_build(&path, &options, &NoProgress).unwrap();
+        assert!(cache_path(&path).exists());
+        let key = cache_key(&path, &options).unwrap();
+        let cached = read_cache(&cache_path(&path), key).unwrap();
+        assert_eq!(cached.as_ref(), Some(&built));
+        assert_eq!(load_or_build(&path, &options, &NoProgress).unwrap(), built);
+    }
+
+    #[test]
+    fn test_key_tracks_content_and_options() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_model(dir.path());
+        let options = IfcPipelineOptions::default();
+        let key = cache_key(&path, &options).unwrap();
+
+        let scaled = IfcPipelineOptions {
+            unit_scale: Some(0.001),
+            ..Default::default()
+        };
+        assert_ne!(cache_key(&path, &scaled).unwrap(), key);
+        // Options that only affect scene assembly share the cache
+        let no_instancing = IfcPipelineOptions {
+            instancing: false,
+            ..Default::default()
+        };
+        assert_eq!(cache_key(&path, &no_instancing).unwrap(), key);
+
+        load_or_build(&path, &options, &NoProgress).unwrap();
+        std::fs::write(&path, MODEL.replace("'Wall A'", "'Wall B'")).unwrap();
+        let new_key = cache_key(&path, &options).unwrap();
+        assert_ne!(new_key, key);
+        assert_eq!(read_cache(&cache_path(&path), new_key).unwrap(), None);
+        let rebuilt = load_or_build(&path, &options, &NoProgress).unwrap();
+        assert_eq!(
+            rebuilt.products[&ProductId::new(20)].name.as_deref(),
+            Some("Wall B")
+        );
+    }
+
+    #[test]
+    fn test_corrupt_cache_is_rebuilt() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_model(dir.path());
+        let options = IfcPipelineOptions::default();
+        let key = cache_key(&path, &options).unwrap();
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&key.to_le_bytes());
+        bytes.extend_from_slice(b"garbage");
+        std::fs::write(cache_path(&path), bytes).unwrap();
+
+        assert!(read_cache(&cache_path(&path), key).is_err());
+        let model = load_or_build(&path, &options, &NoProgress).unwrap();
+        assert_eq!(model.meshes.len(), 1);
+        assert!(read_cache(&cache_path(&path), key).unwrap().is_some());
+    }
+}
@@ -0,0 +1,122 @@
+//! Element schedules as CSV.
+//!
+//! One row per element with its GlobalId, type, storey and name, and
+//! optionally one column per property or quantity, named `Set.Property`
+//! so equally named values from different sets stay apart.
+
+use std::collections::BTreeSet;
+use std::io::Write;
+
+use cst_core::ProductId;
+
+use crate::ifc_query::IfcQuery;
+
+/// Write a schedule of the elements `ids`. With `properties`, the columns
+/// after `Name` are the union of the elements' property and quantity
+/// names, sorted; elements without a value leave the cell empty.
+pub fn write_csv<W: Write>(
+    query: &IfcQuery,
+    ids: &[ProductId],
+    properties: bool,
+    out: &mut W,
+) -> std::io::Result<()> {
+    let columns: Vec<&str> = if properties {
+        ids.iter()
+            .flat_map(|&id| query.qualified_properties(id))
+            .map(|(name, _)| name.as_str())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let header = ["GlobalId", "Type", "Storey", "Name"];
+    write_row(out, header.iter().chain(&columns).map(|c| c.to_string()))?;
+    for &id in ids {
+        let values = query.qualified_properties(id);
+        let fixed = [
+            query.global_id(id).unwrap_or_default(),
+            query.type_of(id).unwrap_or_default().to_string(),
+            query.storey_of(id).unwrap_or_default().to_string(),
+            query.name(id).unwrap_or_default(),
+        ];
+        let cells = columns.iter().map(|column| {
+            values
+                .iter()
+                .find(|(name, _)| name == column)
+                .map(|(_, value)| value.clone())
+                .unwrap_or_default()
+        });
+        write_row(out, fixed.into_iter().chain(cells))?;
+    }
+    Ok(())
+}
+
+fn write_row<W: Write>(out: &mut W, cells: impl Iterator<Item = String>) -> std::io::Result<()> {
+    let row: Vec<String> = cells.map(|cell| csv_field(&cell)).collect();
+    writeln!(out, "{}", row.join(","))
+}
+
+/// Quote a field when it holds a separator, quote or line break (RFC 4180).
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODEL: &str = r#"ISO-10303-21;
+HEADER;
+FILE_SCHEMA(('IFC2X3'));
+ENDSEC;
+DATA;
+#20= IFCWALL('w1',$,'Wall, north',$,$,$,$,$);
+#21= IFCSLAB('s1',$,'Slab',$,$,$,$,$);
+#30= IFCBUILDINGSTOREY('st1',$,'Level 1',$,$,$,$,$,.ELEMENT.,0.);
+#31= IFCRELCONTAINEDINSPATIALSTRUCTURE('r1',$,$,$,(#20,#21),#30);
+#40= IFCPROPERTYSINGLEVALUE('FireRating',$,IFCLABEL('REI120'),$);
+#41= IFCPROPERTYSET('p1',$,'Pset_WallCommon',$,(#40));
+#42= IFCRELDEFINESBYPROPERTIES('r2',$,$,$,(#20),#41);
+#43= IFCQUANTITYAREA('GrossArea',$,$,12.5);
+#44= IFCELEMENTQUANTITY('q1',$,'Qto_SlabBaseQuantities',$,$,(#43));
+#45= IFCRELDEFINESBYPROPERTIES('r3',$,$,$,(#21),#44);
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+    fn schedule(properties: bool) -> String {
+        let query = IfcQuery::from_reader(MODEL.as_bytes()).unwrap();
+        let mut out = Vec::new();
+        write_csv(&query, &query.elements(), properties, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_schedule_columns() {
+        assert_eq!(
+            schedule(false),
+            "GlobalId,Type,Storey,Name\n\
+             w1,IFCWALL,Level 1,\"Wall, north\"\n\
+             s1,IFCSLAB,Level 1,Slab\n"
+        );
+        assert_eq!(
+            schedule(true),
+            "GlobalId,Type,Storey,Name,Pset_WallCommon.FireRating,Qto_SlabBaseQuantities.GrossArea\n\
+             w1,IFCWALL,Level 1,\"Wall, north\",REI120,\n\
+             s1,IFCSLAB,Level 1,Slab,,12.5\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_field_quoting() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+}
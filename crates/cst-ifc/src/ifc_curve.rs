@@ -0,0 +1,470 @@
+//! IFC curve resolution - converts curve entities to cst-geometry curves.
+//!
+//! Bounded curves resolve to a [`CompositeCurve`] of lines and arcs, the
+//! form profiles, sweeps and alignments consume. A trimmed curve is cut
+//! from its basis line or circle by parameter or by cartesian point, and
+//! runs against the basis curve when its `SenseAgreement` is false.
+
+use std::collections::HashMap;
+use std::f64::consts::TAU;
+
+use cst_core::{AngleUnit, EntityLink, Result, StepId, Tolerance};
+use cst_geometry::curve::{CircularArc, CompositeCurve, CurveSegment, Line};
+use cst_math::DVec3;
+
+use crate::ifc_reader::{
+    extract_single_ref, link, parse_direction, parse_entity_refs, parse_real_list, prepend_path,
+    resolve_axis2placement3d, split_ifc_args, unresolved, IfcRawEntity,
+};
+
+/// Curve entity types the parser has to keep for [`resolve_curve`].
+pub(crate) const CURVE_TYPES: &[&str] = &[
+    "IFCPOLYLINE",
+    "IFCLINE",
+    "IFCVECTOR",
+    "IFCCIRCLE",
+    "IFCAXIS2PLACEMENT2D",
+    "IFCTRIMMEDCURVE",
+    "IFCCOMPOSITECURVE",
+    "IFCCOMPOSITECURVESEGMENT",
+    "IFCREPARAMETRISEDCOMPOSITECURVESEGMENT",
+];
+
+/// Resolve a bounded curve entity (`IFCPOLYLINE`, `IFCCIRCLE`,
+/// `IFCTRIMMEDCURVE` or `IFCCOMPOSITECURVE`) into lines and arcs.
+/// Parameters trimming a circle are read in `angle` units.
+pub fn resolve_curve(
+    curve_id: StepId,
+    entities: &HashMap<StepId, IfcRawEntity>,
+    angle: AngleUnit,
+) -> Result<CompositeCurve> {
+    let entity = entities
+        .get(&curve_id)
+        .ok_or_else(|| unresolved(vec![EntityLink::missing(curve_id)], "missing"))?;
+    let args = split_ifc_args(&entity.raw_args);
+    let path = || vec![link(curve_id, entity)];
+
+    match entity.type_name.as_str() {
+        "IFCPOLYLINE" => {
+            let point_ids = args
+                .first()
+                .map(|a| parse_entity_refs(a))
+                .unwrap_or_default();
+            let mut points = Vec::with_capacity(point_ids.len());
+            for point_id in point_ids {
+                let point = curve_point(point_id, entities).ok_or_else(|| {
+                    unresolved(
+                        vec![link(curve_id, entity), EntityLink::missing(point_id)],
+                        "not a point",
+                    )
+                })?;
+                points.push(point);
+            }
+            if points.len() < 2 {
+                return Err(unresolved(path(), "fewer than 2 points"));
+            }
+            let segments = points
+                .windows(2)
+                .map(|pair| CurveSegment::Line(Line::new(pair[0], pair[1])))
+                .collect();
+            Ok(CompositeCurve::new(segments))
+        }
+        "IFCCIRCLE" => {
+            // Trimmed where it starts, so all the way round
+            let circle = resolve_basis(curve_id, entities)?;
+            Ok(CompositeCurve::new(vec![circle.trimmed(0.0, 0.0, true)]))
+        }
+        "IFCTRIMMEDCURVE" => {
+            // (BasisCurve, Trim1, Trim2, SenseAgreement, MasterRepresentation)
+            let basis_id = args
+                .first()
+                .and_then(|a| extract_single_ref(a))
+                .ok_or_else(|| unresolved(path(), "no basis curve"))?;
+            let basis = resolve_basis(basis_id, entities).map_err(|e| prepend_path(path(), e))?;
+            let trim1 = parse_trim(args.get(1), entities);
+            let trim2 = parse_trim(args.get(2), entities);
+            let sense = args.get(3).map_or(true, |a| a.trim() != ".F.");
+            // Points do not depend on the angle unit, so they win unless
+            // the file says its parameters are the master representation
+            let prefer_point = args.get(4).map_or(true, |a| a.trim() != ".PARAMETER.");
+            let (Some(t1), Some(t2)) = (
+                basis.trim_parameter(&trim1, prefer_point, angle),
+                basis.trim_parameter(&trim2, prefer_point, angle),
+            ) else {
+                return Err(unresolved(path(), "no usable trim"));
+            };
+            Ok(CompositeCurve::new(vec![basis.trimmed(t1, t2, sense)]))
+        }
+        "IFCCOMPOSITECURVE" => {
+            // (Segments, SelfIntersect)
+            let segment_ids = args
+                .first()
+                .map(|a| parse_entity_refs(a))
+                .unwrap_or_default();
+            let mut segments = Vec::new();
+            for segment_id in segment_ids {
+                let segment = entities.get(&segment_id).ok_or_else(|| {
+                    unresolved(
+                        vec![link(curve_id, entity), EntityLink::missing(segment_id)],
+                        "missing",
+                    )
+                })?;
+                let segment_path = || vec![link(curve_id, entity), link(segment_id, segment)];
+                // (Transition, SameSense, ParentCurve, ...)
+                let segment_args = split_ifc_args(&segment.raw_args);
+                let parent_id = segment_args
+                    .get(2)
+                    .and_then(|a| extract_single_ref(a))
+                    .ok_or_else(|| unresolved(segment_path(), "no parent curve"))?;
+                let mut parent = resolve_curve(parent_id, entities, angle)
+                    .map_err(|e| prepend_path(segment_path(), e))?;
+                if segment_args.get(1).is_some_and(|a| a.trim() == ".F.") {
+                    parent.reverse();
+                }
+                segments.extend(parent.segments);
+            }
+            if segments.is_empty() {
+                return Err(unresolved(path(), "no segments"));
+            }
+            Ok(CompositeCurve::new(segments))
+        }
+        "IFCLINE" => Err(unresolved(path(), "unbounded line")),
+        _ => Err(unresolved(path(), "unsupported curve")),
+    }
+}
+
+/// An unbounded curve that a trimmed curve is cut from.
+enum Basis {
+    /// The points `origin + t * direction`.
+    Line { origin: DVec3, direction: DVec3 },
+    /// Parameterized by angle in radians from `x_axis` towards `y_axis`.
+    Circle {
+        center: DVec3,
+        x_axis: DVec3,
+        y_axis: DVec3,
+        radius: f64,
+    },
+}
+
+impl Basis {
+    /// The parameter at one end of a trimmed curve, `None` when the trim
+    /// has neither a parameter nor a point.
+    fn trim_parameter(&self, trim: &Trim, prefer_point: bool, angle: AngleUnit) -> Option<f64> {
+        let from_parameter = trim.parameter.map(|t| match self {
+            Basis::Line { .. } => t,
+            Basis::Circle { .. } => t * angle.radians(),
+        });
+        let from_point = trim.point.map(|p| self.parameter_of(p));
+        if prefer_point {
+            from_point.or(from_parameter)
+        } else {
+            from_parameter.or(from_point)
+        }
+    }
+
+    /// The parameter of the point of the curve closest to `point`.
+    fn parameter_of(&self, point: DVec3) -> f64 {
+        match self {
+            Basis::Line { origin, direction } => {
+                (point - *origin).dot(*direction) / direction.length_squared()
+            }
+            Basis::Circle {
+                center,
+                x_axis,
+                y_axis,
+                ..
+            } => {
+                let d = point - *center;
+                d.dot(*y_axis).atan2(d.dot(*x_axis))
+            }
+        }
+    }
+
+    /// The part of the curve from `t1` to `t2`. Along a circle it runs
+    /// counter-clockwise when `sense` is true and clockwise otherwise,
+    /// going all the way round when the ends meet.
+    fn trimmed(&self, t1: f64, t2: f64, sense: bool) -> CurveSegment {
+        match *self {
+            Basis::Line { origin, direction } => {
+                CurveSegment::Line(Line::new(origin + t1 * direction, origin + t2 * direction))
+            }
+            Basis::Circle {
+                center,
+                x_axis,
+                y_axis,
+                radius,
+            } => {
+                let sweep = if sense { t2 - t1 } else { t1 - t2 }.rem_euclid(TAU);
+                let sweep = if sweep <= Tolerance::DEFAULT_ANGULAR {
+                    TAU
+                } else {
+                    sweep
+                };
+                CurveSegment::Arc(CircularArc {
+                    center,
+                    x_axis,
+                    y_axis,
+                    radius,
+                    start_angle: t1,
+                    end_angle: if sense { t1 + sweep } else { t1 - sweep },
+                })
+            }
+        }
+    }
+}
+
+/// Resolve the basis of a trimmed curve, an `IFCLINE` or `IFCCIRCLE`.
+fn resolve_basis(id: StepId, entities: &HashMap<StepId, IfcRawEntity>) -> Result<Basis> {
+    let entity = entities
+        .get(&id)
+        .ok_or_else(|| unresolved(vec![EntityLink::missing(id)], "missing"))?;
+    let args = split_ifc_args(&entity.raw_args);
+    let path = || vec![link(id, entity)];
+
+    match entity.type_name.as_str() {
+        "IFCLINE" => {
+            // (Pnt, Dir) with Dir an IFCVECTOR(Orientation, Magnitude)
+            let origin = args
+                .first()
+                .and_then(|a| extract_single_ref(a))
+                .and_then(|pid| curve_point(pid, entities))
+                .ok_or_else(|| unresolved(path(), "no point"))?;
+            let vector = args
+                .get(1)
+                .and_then(|a| extract_single_ref(a))
+                .and_then(|vid| entities.get(&vid))
+                .filter(|v| v.type_name == "IFCVECTOR")
+                .ok_or_else(|| unresolved(path(), "no vector"))?;
+            let vector_args = split_ifc_args(&vector.raw_args);
+            let orientation = vector_args
+                .first()
+                .and_then(|a| extract_single_ref(a))
+                .and_then(|did| parse_direction(did, entities))
+                .map(DVec3::normalize_or_zero)
+                .unwrap_or(DVec3::ZERO);
+            let magnitude = vector_args
+                .get(1)
+                .and_then(|a| a.trim().parse::<f64>().ok())
+                .unwrap_or(1.0);
+            let direction = orientation * magnitude;
+            if direction.length_squared() == 0.0 {
+                return Err(unresolved(path(), "zero direction"));
+            }
+            Ok(Basis::Line { origin, direction })
+        }
+        "IFCCIRCLE" => {
+            // (Position, Radius)
+            let (center, x_axis, y_axis) = args
+                .first()
+                .and_then(|a| extract_single_ref(a))
+                .and_then(|pid| resolve_position(pid, entities))
+                .ok_or_else(|| unresolved(path(), "no position"))?;
+            let radius = args
+                .get(1)
+                .and_then(|a| a.trim().parse::<f64>().ok())
+                .filter(|r| *r > 0.0)
+                .ok_or_else(|| unresolved(path(), "no radius"))?;
+            Ok(Basis::Circle {
+                center,
+                x_axis,
+                y_axis,
+                radius,
+            })
+        }
+        _ => Err(unresolved(path(), "unsupported basis curve")),
+    }
+}
+
+/// One end of a trimmed curve: a parameter, a point, or both.
+#[derive(Default)]
+struct Trim {
+    parameter: Option<f64>,
+    point: Option<DVec3>,
+}
+
+/// Parse a trim select set like `(#12,IFCPARAMETERVALUE(90.))`.
+fn parse_trim(arg: Option<&String>, entities: &HashMap<StepId, IfcRawEntity>) -> Trim {
+    let mut trim = Trim::default();
+    let Some(arg) = arg else {
+        return trim;
+    };
+    let arg = arg.trim();
+    let inner = arg
+        .strip_prefix('(')
+        .and_then(|a| a.strip_suffix(')'))
+        .unwrap_or(arg);
+    for item in split_ifc_args(inner) {
+        if let Some(value) = item.strip_prefix("IFCPARAMETERVALUE") {
+            trim.parameter = parse_real_list(value).first().copied();
+        } else if let Some(point_id) = extract_single_ref(&item) {
+            trim.point = curve_point(point_id, entities);
+        }
+    }
+    trim
+}
+
+/// The origin and in-plane axes of an `IFCAXIS2PLACEMENT2D` or
+/// `IFCAXIS2PLACEMENT3D`.
+pub(crate) fn resolve_position(
+    id: StepId,
+    entities: &HashMap<StepId, IfcRawEntity>,
+) -> Option<(DVec3, DVec3, DVec3)> {
+    let entity = entities.get(&id)?;
+    match entity.type_name.as_str() {
+        "IFCAXIS2PLACEMENT3D" => {
+            let m = resolve_axis2placement3d(id, entities);
+            Some((
+                m.w_axis.truncate(),
+                m.x_axis.truncate(),
+                m.y_axis.truncate(),
+            ))
+        }
+        "IFCAXIS2PLACEMENT2D" => {
+            // (Location, RefDirection)
+            let args = split_ifc_args(&entity.raw_args);
+            let location = args
+                .first()
+                .and_then(|a| extract_single_ref(a))
+                .and_then(|pid| curve_point(pid, entities))
+                .unwrap_or(DVec3::ZERO);
+            let x_axis = args
+                .get(1)
+                .and_then(|a| extract_single_ref(a))
+                .and_then(|did| parse_direction(did, entities))
+                .map(|d| DVec3::new(d.x, d.y, 0.0).normalize_or_zero())
+                .filter(|d| *d != DVec3::ZERO)
+                .unwrap_or(DVec3::X);
+            Some((location, x_axis, DVec3::Z.cross(x_axis)))
+        }
+        _ => None,
+    }
+}
+
+/// Parse an IFCCARTESIANPOINT of two or three coordinates; 2D points lie
+/// at z = 0.
+fn curve_point(point_id: StepId, entities: &HashMap<StepId, IfcRawEntity>) -> Option<DVec3> {
+    let entity = entities.get(&point_id)?;
+    if entity.type_name != "IFCCARTESIANPOINT" {
+        return None;
+    }
+    match parse_real_list(&entity.raw_args)[..] {
+        [x, y, z, ..] => Some(DVec3::new(x, y, z)),
+        [x, y] => Some(DVec3::new(x, y, 0.0)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ifc_progress::NoProgress;
+    use crate::ifc_reader::parse_ifc_entities_from_reader;
+    use cst_geometry::curve::Curve;
+
+    fn entities_of(data: &str) -> HashMap<StepId, IfcRawEntity> {
+        let model = format!(
+            "ISO-10303-21;\nHEADER;\nFILE_SCHEMA(('IFC4'));\nENDSEC;\nDATA;\n{}\nENDSEC;\nEND-ISO-10303-21;\n",
+            data
+        );
+        parse_ifc_entities_from_reader(model.as_bytes(), 0, &NoProgress).unwrap()
+    }
+
+    fn assert_near(a: DVec3, b: DVec3) {
+        assert!((a - b).length() < 1e-9, "{:?} != {:?}", a, b);
+    }
+
+    /// A unit circle about the origin, its axis placement and two points on it.
+    const CIRCLE: &str = "#1= IFCCARTESIANPOINT((0.,0.));
+#2= IFCDIRECTION((1.,0.));
+#3= IFCAXIS2PLACEMENT2D(#1,#2);
+#4= IFCCIRCLE(#3,1.);
+#5= IFCCARTESIANPOINT((1.,0.));
+#6= IFCCARTESIANPOINT((0.,1.));";
+
+    #[test]
+    fn test_trimmed_circle_by_parameter() {
+        let entities = entities_of(&format!(
+            "{}\n#10= IFCTRIMMEDCURVE(#4,(IFCPARAMETERVALUE(0.)),(IFCPARAMETERVALUE(90.)),.T.,.PARAMETER.);",
+            CIRCLE
+        ));
+        let curve = resolve_curve(StepId(10), &entities, AngleUnit::Degree).unwrap();
+        let (t0, t1) = curve.domain();
+        assert_near(curve.point_at(t0), DVec3::X);
+        assert_near(curve.point_at(t1), DVec3::Y);
+        // Counter-clockwise through the first quadrant
+        let mid = curve.point_at((t0 + t1) / 2.0);
+        assert!(mid.x > 0.5 && mid.y > 0.5);
+
+        // Untrimmed, the circle goes all the way round
+        let circle = resolve_curve(StepId(4), &entities, AngleUnit::Degree).unwrap();
+        assert!(circle.is_closed());
+        assert_near(circle.point_at(0.25), DVec3::Y);
+    }
+
+    #[test]
+    fn test_trimmed_circle_by_point_against_sense() {
+        let entities = entities_of(&format!(
+            "{}\n#10= IFCTRIMMEDCURVE(#4,(#5,IFCPARAMETERVALUE(7.)),(#6),.F.,.CARTESIAN.);",
+            CIRCLE
+        ));
+        let curve = resolve_curve(StepId(10), &entities, AngleUnit::Radian).unwrap();
+        let (t0, t1) = curve.domain();
+        assert_near(curve.point_at(t0), DVec3::X);
+        assert_near(curve.point_at(t1), DVec3::Y);
+        // Clockwise, the long way round through the lower half
+        let mid = curve.point_at((t0 + t1) / 2.0);
+        assert_near(mid, DVec3::new(-1.0, -1.0, 0.0).normalize());
+    }
+
+    #[test]
+    fn test_trimmed_line() {
+        let entities = entities_of(
+            "#1= IFCCARTESIANPOINT((1.,1.,0.));
+#2= IFCDIRECTION((0.,1.,0.));
+#3= IFCVECTOR(#2,2.);
+#4= IFCLINE(#1,#3);
+#5= IFCCARTESIANPOINT((1.,9.,0.));
+#10= IFCTRIMMEDCURVE(#4,(IFCPARAMETERVALUE(0.5)),(#5),.T.,.UNSPECIFIED.);",
+        );
+        let curve = resolve_curve(StepId(10), &entities, AngleUnit::Radian).unwrap();
+        let (t0, t1) = curve.domain();
+        assert_near(curve.point_at(t0), DVec3::new(1.0, 2.0, 0.0));
+        assert_near(curve.point_at(t1), DVec3::new(1.0, 9.0, 0.0));
+
+        let err = resolve_curve(StepId(4), &entities, AngleUnit::Radian).unwrap_err();
+        assert!(err.to_string().contains("unbounded line"));
+    }
+
+    #[test]
+    fn test_composite_curve_segments() {
+        // A half disc: the diameter, then the upper half circle reversed
+        let entities = entities_of(&format!(
+            "{}
+#7= IFCCARTESIANPOINT((-1.,0.));
+#8= IFCPOLYLINE((#7,#5));
+#9= IFCTRIMMEDCURVE(#4,(#7),(#5),.F.,.CARTESIAN.);
+#11= IFCCOMPOSITECURVESEGMENT(.CONTINUOUS.,.T.,#8);
+#12= IFCCOMPOSITECURVESEGMENT(.CONTINUOUS.,.F.,#9);
+#13= IFCCOMPOSITECURVE((#11,#12),.F.);",
+            CIRCLE
+        ));
+        let curve = resolve_curve(StepId(13), &entities, AngleUnit::Radian).unwrap();
+        assert_eq!(curve.segments.len(), 2);
+        assert!(curve.is_closed());
+        assert_near(curve.point_at(1.0), DVec3::X);
+        assert_near(curve.point_at(1.5), DVec3::Y);
+    }
+
+    #[test]
+    fn test_unresolved_curve_reports_path() {
+        let entities = entities_of(
+            "#11= IFCCOMPOSITECURVESEGMENT(.CONTINUOUS.,.T.,#99);
+#13= IFCCOMPOSITECURVE((#11),.F.);",
+        );
+        let err = resolve_curve(StepId(13), &entities, AngleUnit::Radian).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unresolved reference IFCCOMPOSITECURVE #13 → IFCCOMPOSITECURVESEGMENT #11 → #99: missing"
+        );
+    }
+}
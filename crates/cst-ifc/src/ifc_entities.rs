@@ -5,6 +5,7 @@
 
 use cst_math::{DVec2, DVec3};
 use cst_math::transform::Transform;
+use cst_geometry::curve::CompositeCurve;
 
 /// IFC geometry representations.
 #[derive(Debug, Clone)]
@@ -48,6 +49,11 @@ pub enum IfcProfile {
     ArbitraryClosedProfile {
         points: Vec<DVec2>,
     },
+    /// Closed profile bounded by lines and arcs in the XY plane, e.g. an
+    /// `IfcCompositeCurve` outer curve.
+    CurveProfile {
+        boundary: CompositeCurve,
+    },
 }
 
 /// Placement / axis definition used in IFC.
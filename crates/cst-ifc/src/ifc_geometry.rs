@@ -1,12 +1,51 @@
-//! IFC geometry resolution - converts IFC geometry descriptions to point data.
+//! IFC geometry resolution - converts IFC geometry descriptions to solid
+//! triangle meshes.
 
-use cst_math::DVec3;
+use std::collections::HashMap;
 
+use cst_geometry::curve::{CompositeCurve, CurveSegment};
+use cst_geometry::tessellate::composite_to_polyline;
+use cst_math::transform::Transform;
+use cst_math::{DMat4, DVec3};
+use cst_mesh::TriangleMesh;
+
+use crate::ifc_curve::{resolve_curve, resolve_position};
 use crate::ifc_entities::{IfcGeometry, IfcProfile};
-use cst_core::Result;
+use crate::ifc_options::IfcPipelineOptions;
+use crate::ifc_reader::{
+    extract_single_ref, link, parse_direction, prepend_path, resolve_axis2placement3d,
+    split_ifc_args, unresolved, IfcFaceData, IfcMeshData, IfcRawEntity,
+};
+use crate::ifc_to_mesh::faces_to_trimesh;
+use cst_core::{AngleUnit, CstError, EntityLink, ModelUnits, Result, StepId, Tolerance};
+
+/// Entity types the parser has to keep for [`resolve_swept_solid`],
+/// besides the curves of [`resolve_curve`].
+pub(crate) const SWEPT_SOLID_TYPES: &[&str] = &[
+    "IFCEXTRUDEDAREASOLID",
+    "IFCRECTANGLEPROFILEDEF",
+    "IFCCIRCLEPROFILEDEF",
+    "IFCARBITRARYCLOSEDPROFILEDEF",
+];
+
+/// Segments used for a full circle without a tolerance, and the default
+/// smoothness of arcs.
+const CIRCLE_SEGMENTS: usize = 32;
+
+/// Bounds on the segments of a full circle drawn to a tolerance.
+const MIN_CIRCLE_SEGMENTS: usize = 8;
+const MAX_CIRCLE_SEGMENTS: usize = 1024;
 
 /// Generate 2D profile points (in the XY plane, Z=0).
 pub fn profile_points(profile: &IfcProfile) -> Vec<DVec3> {
+    profile_points_with_tolerance(profile, 0.0)
+}
+
+/// Like [`profile_points`], tessellating circles and curved boundaries so
+/// that no point of the curve is further than `tolerance` (the sagitta)
+/// from the polygon. A tolerance of 0 draws circles with 32 segments and
+/// arcs as smoothly.
+pub fn profile_points_with_tolerance(profile: &IfcProfile, tolerance: f64) -> Vec<DVec3> {
     match profile {
         IfcProfile::RectangleProfile { x_dim, y_dim } => {
             let hx = x_dim / 2.0;
@@ -19,8 +58,7 @@ pub fn profile_points(profile: &IfcProfile) -> Vec<DVec3> {
             ]
         }
         IfcProfile::CircleProfile { radius } => {
-            // Approximate circle with 32 segments
-            let n = 32;
+            let n = circle_segments(*radius, tolerance);
             (0..n)
                 .map(|i| {
                     let angle = 2.0 * std::f64::consts::PI * (i as f64) / (n as f64);
@@ -34,33 +72,144 @@ pub fn profile_points(profile: &IfcProfile) -> Vec<DVec3> {
                 .map(|p| DVec3::new(p.x, p.y, 0.0))
                 .collect()
         }
+        IfcProfile::CurveProfile { boundary } => {
+            let tolerance = if tolerance > 0.0 {
+                tolerance
+            } else {
+                default_arc_tolerance(boundary)
+            };
+            let mut points: Vec<DVec3> = composite_to_polyline(boundary, tolerance)
+                .into_iter()
+                .map(|p| DVec3::new(p.x, p.y, 0.0))
+                .collect();
+            // The boundary is closed; keep the first point only once
+            if points.len() > 1
+                && points[0].distance(points[points.len() - 1]) <= Tolerance::DEFAULT_LINEAR
+            {
+                points.pop();
+            }
+            points
+        }
+    }
+}
+
+/// Number of segments for a full circle of `radius` whose chords stay
+/// within `tolerance` of the circle, or 32 when `tolerance` is 0.
+fn circle_segments(radius: f64, tolerance: f64) -> usize {
+    if tolerance <= 0.0 {
+        return CIRCLE_SEGMENTS;
+    }
+    if tolerance >= radius {
+        return MIN_CIRCLE_SEGMENTS;
+    }
+    // A chord spanning the angle 2a strays r (1 - cos a) from the circle
+    let half_angle = (1.0 - tolerance / radius).acos();
+    let n = (std::f64::consts::PI / half_angle).ceil() as usize;
+    n.clamp(MIN_CIRCLE_SEGMENTS, MAX_CIRCLE_SEGMENTS)
+}
+
+/// Chord deviation of the circle profile's segments for the tightest arc
+/// of `boundary`.
+fn default_arc_tolerance(boundary: &CompositeCurve) -> f64 {
+    let min_radius = boundary
+        .segments
+        .iter()
+        .filter_map(|segment| match segment {
+            CurveSegment::Arc(arc) => Some(arc.radius),
+            CurveSegment::Line(_) => None,
+        })
+        .fold(f64::INFINITY, f64::min);
+    if !min_radius.is_finite() {
+        // Only lines: any tolerance reproduces them exactly
+        return 1.0;
     }
+    let half_angle = std::f64::consts::PI / CIRCLE_SEGMENTS as f64;
+    min_radius * (1.0 - half_angle.cos())
 }
 
-/// Extrude a profile along a direction by the given depth.
-///
-/// Returns the vertices of the extruded solid: bottom face followed by top face.
-pub fn extrude_profile(profile: &IfcProfile, direction: DVec3, depth: f64) -> Vec<DVec3> {
-    let base = profile_points(profile);
+/// Sweep a profile along a direction by the given depth into a closed
+/// solid: one quad per profile edge and both end caps, ear-clipped so that
+/// concave profiles are capped correctly. Every face has its own vertices,
+/// keeping normals flat. Curved profiles are tessellated at `tolerance`
+/// (see [`profile_points_with_tolerance`]).
+pub fn extrude_solid(
+    profile: &IfcProfile,
+    direction: DVec3,
+    depth: f64,
+    tolerance: f64,
+) -> Result<TriangleMesh> {
+    let mut base = profile_points_with_tolerance(profile, tolerance);
     let offset = direction.normalize_or_zero() * depth;
+    if base.len() < 3 || offset.z.abs() <= Tolerance::DEFAULT_LINEAR {
+        return Err(CstError::Geometry(format!(
+            "cannot extrude a profile of {} points by {:?}",
+            base.len(),
+            offset
+        )));
+    }
+    // Walk the profile so that side faces point outwards
+    if signed_area(&base) * offset.z < 0.0 {
+        base.reverse();
+    }
 
-    let mut points = Vec::with_capacity(base.len() * 2);
-    // Bottom face
-    for p in &base {
-        points.push(*p);
+    let coords: Vec<f64> = base.iter().flat_map(|p| [p.x, p.y]).collect();
+    let cap = match earcutr::earcut(&coords, &[], 2) {
+        Ok(indices) if !indices.is_empty() => indices,
+        _ => {
+            return Err(CstError::Geometry(format!(
+                "cannot triangulate a profile of {} points",
+                base.len()
+            )))
+        }
+    };
+
+    let mut mesh = TriangleMesh::default();
+    let n = base.len();
+    for i in 0..n {
+        let (b0, b1) = (base[i], base[(i + 1) % n]);
+        let start = mesh.positions.len() as u32;
+        mesh.positions
+            .extend_from_slice(&[b0, b1, b1 + offset, b0 + offset]);
+        mesh.indices
+            .extend_from_slice(&[start, start + 1, start + 2, start, start + 2, start + 3]);
     }
-    // Top face
-    for p in &base {
-        points.push(*p + offset);
+    // Bottom cap faces against the sweep, top cap along it
+    for (shift, outward) in [(DVec3::ZERO, -offset), (offset, offset)] {
+        let start = mesh.positions.len() as u32;
+        mesh.positions.extend(base.iter().map(|p| *p + shift));
+        for tri in cap.chunks_exact(3) {
+            let (a, b, c) = (base[tri[0]], base[tri[1]], base[tri[2]]);
+            let (i0, mut i1, mut i2) = (tri[0] as u32, tri[1] as u32, tri[2] as u32);
+            if (b - a).cross(c - a).dot(outward) < 0.0 {
+                std::mem::swap(&mut i1, &mut i2);
+            }
+            mesh.indices
+                .extend_from_slice(&[start + i0, start + i1, start + i2]);
+        }
     }
-    points
+    mesh.compute_normals();
+    Ok(mesh)
+}
+
+/// Twice the signed area of a profile in the XY plane, positive when it
+/// runs counter-clockwise.
+fn signed_area(points: &[DVec3]) -> f64 {
+    let n = points.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % n]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum()
+}
+
+/// Resolve an IFC geometry description into a solid triangle mesh.
+pub fn resolve_solid(geom: &IfcGeometry) -> Result<TriangleMesh> {
+    resolve_solid_with_tolerance(geom, 0.0)
 }
 
-/// Resolve an IFC geometry description into a set of points.
-///
-/// This is a simplified resolution that produces representative vertices,
-/// not full triangulated meshes.
-pub fn resolve_geometry(geom: &IfcGeometry) -> Result<Vec<DVec3>> {
+/// Like [`resolve_solid`], tessellating curved profiles at `tolerance`.
+pub fn resolve_solid_with_tolerance(geom: &IfcGeometry, tolerance: f64) -> Result<TriangleMesh> {
     match geom {
         IfcGeometry::ExtrudedAreaSolid {
             profile,
@@ -68,33 +217,188 @@ pub fn resolve_geometry(geom: &IfcGeometry) -> Result<Vec<DVec3>> {
             direction,
             depth,
         } => {
-            let local_pts = extrude_profile(profile, *direction, *depth);
-            let world_pts = local_pts
-                .into_iter()
-                .map(|p| position.transform_point(p))
-                .collect();
-            Ok(world_pts)
+            let mut mesh = extrude_solid(profile, *direction, *depth, tolerance)?;
+            mesh.transform(&position.to_mat4());
+            Ok(mesh)
         }
         IfcGeometry::FacetedBrep { faces } => {
-            let pts: Vec<DVec3> = faces.iter().flat_map(|f| f.iter().copied()).collect();
-            Ok(pts)
+            let faces: Vec<IfcFaceData> = faces
+                .iter()
+                .map(|outer| IfcFaceData {
+                    outer: outer.clone(),
+                    holes: Vec::new(),
+                })
+                .collect();
+            let trimesh = faces_to_trimesh("", &faces);
+            Ok(TriangleMesh {
+                positions: trimesh.positions,
+                normals: trimesh.normals,
+                indices: trimesh.indices,
+                uvs: Vec::new(),
+            })
         }
         IfcGeometry::MappedItem { source, transform } => {
-            let source_pts = resolve_geometry(source)?;
-            let transformed = source_pts
-                .into_iter()
-                .map(|p| transform.transform_point(p))
-                .collect();
-            Ok(transformed)
+            let mut mesh = resolve_solid_with_tolerance(source, tolerance)?;
+            mesh.transform(&transform.to_mat4());
+            Ok(mesh)
         }
         IfcGeometry::BooleanClippingResult { first, .. } => {
             // Simplified: just return the first operand's geometry.
             // Full boolean operations require CSG, which is in cst-topology.
-            resolve_geometry(first)
+            resolve_solid_with_tolerance(first, tolerance)
         }
     }
 }
 
+/// How the reader tessellates swept solids, worked out once per model.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SweepTessellation {
+    /// Unit of the trimming parameters of profile curves
+    angle: AngleUnit,
+    /// Sagitta of curved profiles, in model units
+    sagitta: f64,
+}
+
+impl SweepTessellation {
+    pub(crate) fn new(units: &ModelUnits, options: &IfcPipelineOptions) -> Self {
+        Self {
+            angle: units.angle,
+            sagitta: options.sagitta(units.length),
+        }
+    }
+}
+
+/// Resolve an IFCEXTRUDEDAREASOLID into a closed mesh with one face per
+/// triangle, in the coordinates of its representation.
+pub(crate) fn resolve_swept_solid(
+    solid_id: StepId,
+    entities: &HashMap<StepId, IfcRawEntity>,
+    tessellation: &SweepTessellation,
+) -> Result<IfcMeshData> {
+    let geometry = parse_extruded_solid(solid_id, entities, tessellation.angle)?;
+    let mesh = resolve_solid_with_tolerance(&geometry, tessellation.sagitta).map_err(|e| {
+        let path = entities
+            .get(&solid_id)
+            .map_or_else(|| EntityLink::missing(solid_id), |solid| link(solid_id, solid));
+        unresolved(vec![path], &e.to_string())
+    })?;
+    let faces = mesh
+        .indices
+        .chunks_exact(3)
+        .map(|tri| IfcFaceData {
+            outer: tri.iter().map(|&i| mesh.positions[i as usize]).collect(),
+            holes: Vec::new(),
+        })
+        .collect();
+    Ok(IfcMeshData {
+        name: format!("Solid_{}", solid_id.value()),
+        faces,
+        placement: None,
+        color: None,
+        surfaces: Vec::new(),
+        instance: None,
+    })
+}
+
+/// Parse an IFCEXTRUDEDAREASOLID into an
+/// [`IfcGeometry::ExtrudedAreaSolid`]. Trimmed profile curves take their
+/// parameters in `angle` units.
+pub(crate) fn parse_extruded_solid(
+    solid_id: StepId,
+    entities: &HashMap<StepId, IfcRawEntity>,
+    angle: AngleUnit,
+) -> Result<IfcGeometry> {
+    let solid = entities
+        .get(&solid_id)
+        .ok_or_else(|| unresolved(vec![EntityLink::missing(solid_id)], "missing"))?;
+    let path = || vec![link(solid_id, solid)];
+    // (SweptArea, Position, ExtrudedDirection, Depth)
+    let args = split_ifc_args(&solid.raw_args);
+    let profile_id = args
+        .first()
+        .and_then(|a| extract_single_ref(a))
+        .ok_or_else(|| unresolved(path(), "no swept area"))?;
+    let (profile, placement) =
+        parse_profile(profile_id, entities, angle).map_err(|e| prepend_path(path(), e))?;
+    let position = args
+        .get(1)
+        .and_then(|a| extract_single_ref(a))
+        .map(|id| resolve_axis2placement3d(id, entities))
+        .unwrap_or(DMat4::IDENTITY);
+    let direction = args
+        .get(2)
+        .and_then(|a| extract_single_ref(a))
+        .and_then(|id| parse_direction(id, entities))
+        .ok_or_else(|| unresolved(path(), "no direction"))?;
+    let depth = args
+        .get(3)
+        .and_then(|a| a.trim().parse::<f64>().ok())
+        .filter(|depth| *depth > 0.0)
+        .ok_or_else(|| unresolved(path(), "no depth"))?;
+    // The profile's own placement moves it within the solid's XY plane,
+    // while the direction is given in the solid's coordinates
+    Ok(IfcGeometry::ExtrudedAreaSolid {
+        profile,
+        position: Transform::from_mat4(position * placement),
+        direction: placement.inverse().transform_vector3(direction),
+        depth,
+    })
+}
+
+/// A profile definition and its placement in the XY plane.
+fn parse_profile(
+    profile_id: StepId,
+    entities: &HashMap<StepId, IfcRawEntity>,
+    angle: AngleUnit,
+) -> Result<(IfcProfile, DMat4)> {
+    let entity = entities
+        .get(&profile_id)
+        .ok_or_else(|| unresolved(vec![EntityLink::missing(profile_id)], "missing"))?;
+    let path = || vec![link(profile_id, entity)];
+    let args = split_ifc_args(&entity.raw_args);
+    let size = |i: usize| {
+        args.get(i)
+            .and_then(|a| a.trim().parse::<f64>().ok())
+            .filter(|v| *v > 0.0)
+            .ok_or_else(|| unresolved(path(), "no size"))
+    };
+    // Parameterized profiles: (ProfileType, ProfileName, Position, ...)
+    let placement = || {
+        args.get(2)
+            .and_then(|a| extract_single_ref(a))
+            .and_then(|id| resolve_position(id, entities))
+            .map_or(DMat4::IDENTITY, |(origin, x_axis, y_axis)| {
+                DMat4::from_cols(
+                    x_axis.extend(0.0),
+                    y_axis.extend(0.0),
+                    DVec3::Z.extend(0.0),
+                    origin.extend(1.0),
+                )
+            })
+    };
+    match entity.type_name.as_str() {
+        "IFCRECTANGLEPROFILEDEF" => {
+            let profile = IfcProfile::RectangleProfile {
+                x_dim: size(3)?,
+                y_dim: size(4)?,
+            };
+            Ok((profile, placement()))
+        }
+        "IFCCIRCLEPROFILEDEF" => Ok((IfcProfile::CircleProfile { radius: size(3)? }, placement())),
+        "IFCARBITRARYCLOSEDPROFILEDEF" => {
+            // (ProfileType, ProfileName, OuterCurve)
+            let curve_id = args
+                .get(2)
+                .and_then(|a| extract_single_ref(a))
+                .ok_or_else(|| unresolved(path(), "no outer curve"))?;
+            let boundary =
+                resolve_curve(curve_id, entities, angle).map_err(|e| prepend_path(path(), e))?;
+            Ok((IfcProfile::CurveProfile { boundary }, DMat4::IDENTITY))
+        }
+        _ => Err(unresolved(path(), "unsupported profile")),
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -102,6 +406,8 @@ pub fn resolve_geometry(geom: &IfcGeometry) -> Result<Vec<DVec3>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use cst_math::transform::Transform;
+    use cst_math::{DMat4, DVec2};
     use crate::ifc_entities::IfcProfile;
 
     #[test]
@@ -133,58 +439,110 @@ mod tests {
     }
 
     #[test]
-    fn test_extrude_rectangle() {
-        let profile = IfcProfile::RectangleProfile {
-            x_dim: 2.0,
-            y_dim: 2.0,
+    fn test_circle_segments_follow_radius() {
+        // 1 mm sagitta: thin rebar stays coarse, a large column gets smooth
+        assert_eq!(circle_segments(6.0, 1.0), 8);
+        let column = circle_segments(500.0, 1.0);
+        assert!(column > 32);
+        let half_angle = std::f64::consts::PI / column as f64;
+        assert!(500.0 * (1.0 - half_angle.cos()) <= 1.0);
+
+        let profile = IfcProfile::CircleProfile { radius: 500.0 };
+        assert_eq!(profile_points_with_tolerance(&profile, 1.0).len(), column);
+        assert_eq!(circle_segments(500.0, 0.0), 32);
+        assert_eq!(circle_segments(1e6, 1e-9), 1024);
+    }
+
+    #[test]
+    fn test_extrude_rounded_profile() {
+        use cst_geometry::curve::{CircularArc, Line};
+
+        // A slot: two straight edges joined by half circles of radius 1
+        let line = |x0: f64, y0: f64, x1: f64, y1: f64| {
+            CurveSegment::Line(Line::new(DVec3::new(x0, y0, 0.0), DVec3::new(x1, y1, 0.0)))
         };
-        let pts = extrude_profile(&profile, DVec3::Z, 10.0);
-        assert_eq!(pts.len(), 8); // 4 bottom + 4 top
+        let arc = |start: DVec3, mid: DVec3, end: DVec3| {
+            CurveSegment::Arc(CircularArc::through(start, mid, end).unwrap())
+        };
+        let boundary = CompositeCurve::new(vec![
+            line(-2.0, -1.0, 2.0, -1.0),
+            arc(DVec3::new(2.0, -1.0, 0.0), DVec3::new(3.0, 0.0, 0.0), DVec3::new(2.0, 1.0, 0.0)),
+            line(2.0, 1.0, -2.0, 1.0),
+            arc(DVec3::new(-2.0, 1.0, 0.0), DVec3::new(-3.0, 0.0, 0.0), DVec3::new(-2.0, -1.0, 0.0)),
+        ]);
+        let profile = IfcProfile::CurveProfile { boundary };
 
-        // Bottom face at z=0
-        for p in &pts[..4] {
-            assert!(p.z.abs() < 1e-10);
-        }
-        // Top face at z=10
-        for p in &pts[4..] {
-            assert!((p.z - 10.0).abs() < 1e-10);
+        let coarse = profile_points_with_tolerance(&profile, 0.1);
+        let fine = profile_points(&profile);
+        assert!(fine.len() > coarse.len());
+        assert!(coarse.len() > 4);
+        // No duplicate closing point
+        assert!(fine[0].distance(fine[fine.len() - 1]) > 1e-6);
+
+        let mesh = extrude_solid(&profile, DVec3::Z, 3.0, 0.0).unwrap();
+        assert!(mesh.is_watertight());
+        for p in &mesh.positions {
+            assert!(p.z.abs() < 1e-10 || (p.z - 3.0).abs() < 1e-10);
+            // Every point lies on a straight edge or on one of the arcs
+            if p.x.abs() > 2.0 + 1e-10 {
+                let center = DVec3::new(2.0 * p.x.signum(), 0.0, p.z);
+                assert!((p.distance(center) - 1.0).abs() < 1e-10);
+            } else {
+                assert!((p.y.abs() - 1.0).abs() < 1e-10);
+            }
         }
     }
 
     #[test]
-    fn test_resolve_extruded_solid() {
-        use cst_math::transform::Transform;
-
-        let geom = IfcGeometry::ExtrudedAreaSolid {
-            profile: IfcProfile::RectangleProfile {
-                x_dim: 1.0,
-                y_dim: 1.0,
-            },
-            position: Transform::identity(),
-            direction: DVec3::Z,
-            depth: 5.0,
+    fn test_extrude_solid_is_closed() {
+        let profile = IfcProfile::RectangleProfile {
+            x_dim: 2.0,
+            y_dim: 3.0,
         };
-        let pts = resolve_geometry(&geom).unwrap();
-        assert_eq!(pts.len(), 8);
+        let mesh = extrude_solid(&profile, DVec3::Z, 4.0, 0.0).unwrap();
+        // 4 side quads and 2 triangles per cap
+        assert_eq!(mesh.triangle_count(), 12);
+        assert!(mesh.is_watertight());
+        assert!((mesh.signed_volume() - 24.0).abs() < 1e-9);
+
+        // Sweeping downwards still gives outward faces
+        let down = extrude_solid(&profile, -DVec3::Z, 4.0, 0.0).unwrap();
+        assert!((down.signed_volume() - 24.0).abs() < 1e-9);
+        assert!(down.bounding_box().min.z < -3.9);
+    }
+
+    #[test]
+    fn test_extrude_solid_caps_concave_profile() {
+        // An L shape, clockwise
+        let corners = [(0.0, 0.0), (0.0, 2.0), (1.0, 2.0), (1.0, 1.0), (2.0, 1.0), (2.0, 0.0)];
+        let points = corners
+            .iter()
+            .map(|&(x, y)| DVec2::new(x, y))
+            .collect();
+        let profile = IfcProfile::ArbitraryClosedProfile { points };
+        let mesh = extrude_solid(&profile, DVec3::Z, 1.0, 0.0).unwrap();
+        assert!(mesh.is_watertight());
+        assert!((mesh.signed_volume() - 3.0).abs() < 1e-9);
+
+        let flat = extrude_solid(&profile, DVec3::X, 1.0, 0.0);
+        assert!(flat.is_err());
     }
 
     #[test]
-    fn test_resolve_faceted_brep() {
-        let geom = IfcGeometry::FacetedBrep {
-            faces: vec![
-                vec![
-                    DVec3::new(0.0, 0.0, 0.0),
-                    DVec3::new(1.0, 0.0, 0.0),
-                    DVec3::new(1.0, 1.0, 0.0),
-                ],
-                vec![
-                    DVec3::new(0.0, 0.0, 1.0),
-                    DVec3::new(1.0, 0.0, 1.0),
-                    DVec3::new(1.0, 1.0, 1.0),
-                ],
-            ],
+    fn test_resolve_solid_mirrored_item() {
+        let source = IfcGeometry::ExtrudedAreaSolid {
+            profile: IfcProfile::CircleProfile { radius: 1.0 },
+            position: Transform::from_translation(DVec3::new(5.0, 0.0, 0.0)),
+            direction: DVec3::Z,
+            depth: 2.0,
+        };
+        let geom = IfcGeometry::MappedItem {
+            source: Box::new(source),
+            transform: Transform::from_mat4(DMat4::from_scale(DVec3::new(-1.0, 1.0, 1.0))),
         };
-        let pts = resolve_geometry(&geom).unwrap();
-        assert_eq!(pts.len(), 6);
+        let mesh = resolve_solid(&geom).unwrap();
+        assert!(mesh.signed_volume() > 6.0);
+        assert!(mesh.bounding_box().max.x < -3.9);
+        assert!(mesh.is_watertight());
     }
 }
@@ -0,0 +1,200 @@
+//! JSON dump of parsed STEP entities for debugging and ETL pipelines.
+//!
+//! Each entity becomes `{"id", "type", "attributes"}` with its attributes in
+//! file order. References stay ids (`{"ref": 12}`) instead of being inlined,
+//! so the dump mirrors the file and shared or cyclic graphs are harmless.
+
+use std::collections::{BTreeSet, HashMap};
+
+use cst_core::{CstError, Result, StepId};
+use serde_json::{json, Value};
+
+use crate::step_parser::{StepAttribute, StepEntity, StepFile};
+
+/// Which entities to dump. With no ids and no types, the whole data
+/// section is dumped.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JsonDumpOptions {
+    /// Entity ids to include
+    pub ids: Vec<StepId>,
+    /// Entity types to include, case-insensitive, e.g. `IfcWall`
+    pub types: Vec<String>,
+    /// Also include everything the selected entities reference, transitively
+    pub follow_references: bool,
+}
+
+/// Dump the header and the selected entities of `file`, in file order.
+/// Fails when a requested id is not in the file.
+pub fn step_to_json(file: &StepFile, options: &JsonDumpOptions) -> Result<Value> {
+    let by_id: HashMap<StepId, &StepEntity> = file.entities.iter().map(|e| (e.entity_id, e)).collect();
+    if let Some(missing) = options.ids.iter().find(|id| !by_id.contains_key(id)) {
+        return Err(CstError::NotFound(format!("entity {}", missing)));
+    }
+
+    let entities: Vec<Value> = if options.ids.is_empty() && options.types.is_empty() {
+        file.entities.iter().map(entity_to_json).collect()
+    } else {
+        let mut selected: BTreeSet<StepId> = options.ids.iter().copied().collect();
+        selected.extend(
+            file.entities
+                .iter()
+                .filter(|e| {
+                    options
+                        .types
+                        .iter()
+                        .any(|t| t.eq_ignore_ascii_case(&e.type_name))
+                })
+                .map(|e| e.entity_id),
+        );
+        if options.follow_references {
+            let mut pending: Vec<StepId> = selected.iter().copied().collect();
+            while let Some(id) = pending.pop() {
+                let Some(entity) = by_id.get(&id) else {
+                    continue;
+                };
+                let mut refs = Vec::new();
+                for attribute in &entity.attributes {
+                    collect_refs(attribute, &mut refs);
+                }
+                // Dangling references are dumped as ids but not followed
+                for r in refs {
+                    if by_id.contains_key(&r) && selected.insert(r) {
+                        pending.push(r);
+                    }
+                }
+            }
+        }
+        file.entities
+            .iter()
+            .filter(|e| selected.contains(&e.entity_id))
+            .map(entity_to_json)
+            .collect()
+    };
+
+    Ok(json!({
+        "header": {
+            "description": file.header.description,
+            "file_name": file.header.file_name,
+            "file_schema": file.header.file_schema,
+        },
+        "entities": entities,
+    }))
+}
+
+/// `{"id": 1, "type": "IFCWALL", "attributes": [...]}`
+pub fn entity_to_json(entity: &StepEntity) -> Value {
+    json!({
+        "id": entity.entity_id,
+        "type": entity.type_name,
+        "attributes": entity.attributes.iter().map(attribute_to_json).collect::<Vec<_>>(),
+    })
+}
+
+/// Plain values map onto JSON values and `$` onto `null`. The rest become
+/// tagged objects: `{"ref": 12}`, `{"enum": "ELEMENT"}`, `{"derived": true}`
+/// for `*` and `{"type": "IFCLABEL", "value": ...}` for typed values.
+pub fn attribute_to_json(attribute: &StepAttribute) -> Value {
+    match attribute {
+        StepAttribute::Integer(v) => json!(v),
+        // Non-finite reals have no JSON form and become null
+        StepAttribute::Real(v) => json!(v),
+        StepAttribute::String(s) => json!(s),
+        StepAttribute::Bool(b) => json!(b),
+        StepAttribute::Enum(e) => json!({ "enum": e }),
+        StepAttribute::EntityRef(id) => json!({ "ref": id }),
+        StepAttribute::List(items) => Value::Array(items.iter().map(attribute_to_json).collect()),
+        StepAttribute::Typed(type_name, value) => {
+            json!({ "type": type_name, "value": attribute_to_json(value) })
+        }
+        StepAttribute::Null => Value::Null,
+        StepAttribute::Derived => json!({ "derived": true }),
+    }
+}
+
+fn collect_refs(attribute: &StepAttribute, refs: &mut Vec<StepId>) {
+    match attribute {
+        StepAttribute::EntityRef(id) => refs.push(*id),
+        StepAttribute::List(items) => items.iter().for_each(|item| collect_refs(item, refs)),
+        StepAttribute::Typed(_, value) => collect_refs(value, refs),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::step_parser::parse_step;
+
+    const INPUT: &str = r#"ISO-10303-21;
+HEADER;
+FILE_SCHEMA(('IFC4'));
+ENDSEC;
+DATA;
+#1=IFCCARTESIANPOINT((0.,0.,1.5));
+#2=IFCAXIS2PLACEMENT3D(#1,$,$);
+#3=IFCWALL('2O2Fr$t4X7Zf8NOew3FLOH',$,'Wall',$,$,#2,$,$,.STANDARD.);
+#4=IFCPROPERTYSINGLEVALUE('FireRating',$,IFCLABEL('REI90'),$);
+#5=IFCSLAB('1',$,'Slab',$,$,#2,$,$,*);
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+    fn ids(dump: &Value) -> Vec<u64> {
+        dump["entities"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["id"].as_u64().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_dump_whole_file() {
+        let file = parse_step(INPUT).unwrap();
+        let dump = step_to_json(&file, &JsonDumpOptions::default()).unwrap();
+        assert_eq!(dump["header"]["file_schema"], json!(["IFC4"]));
+        assert_eq!(ids(&dump), [1, 2, 3, 4, 5]);
+
+        let entities = &dump["entities"];
+        assert_eq!(entities[0]["type"], "IFCCARTESIANPOINT");
+        assert_eq!(entities[0]["attributes"], json!([[0.0, 0.0, 1.5]]));
+        assert_eq!(entities[1]["attributes"], json!([{ "ref": 1 }, null, null]));
+        assert_eq!(entities[2]["attributes"][8], json!({ "enum": "STANDARD" }));
+        assert_eq!(
+            entities[3]["attributes"][2],
+            json!({ "type": "IFCLABEL", "value": "REI90" })
+        );
+        assert_eq!(entities[4]["attributes"][8], json!({ "derived": true }));
+    }
+
+    #[test]
+    fn test_dump_selection() {
+        let file = parse_step(INPUT).unwrap();
+        let by_type = JsonDumpOptions {
+            types: vec!["IfcWall".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(ids(&step_to_json(&file, &by_type).unwrap()), [3]);
+
+        let followed = JsonDumpOptions {
+            follow_references: true,
+            ..by_type
+        };
+        assert_eq!(ids(&step_to_json(&file, &followed).unwrap()), [1, 2, 3]);
+
+        let by_id = JsonDumpOptions {
+            ids: vec![StepId(4), StepId(1)],
+            ..Default::default()
+        };
+        assert_eq!(ids(&step_to_json(&file, &by_id).unwrap()), [1, 4]);
+
+        let missing = JsonDumpOptions {
+            ids: vec![StepId(99)],
+            ..Default::default()
+        };
+        assert!(matches!(
+            step_to_json(&file, &missing),
+            Err(CstError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,233 @@
+//! Stand-in geometry for doors and windows without a body.
+//!
+//! Some exporters write doors and windows with only their overall size,
+//! leaving the opening they fill as a hole in the wall. Such elements get a
+//! box of `OverallWidth` × `OverallHeight` in their own placement, as deep
+//! as the extrusion of the opening (usually the wall thickness).
+
+use std::collections::HashMap;
+
+use cst_core::{LengthUnit, StepId};
+use cst_math::transform::has_mirror;
+use cst_math::{DMat4, DVec3};
+
+use crate::ifc_reader::{
+    extract_single_ref, parse_entity_refs, resolve_placement_chain, split_ifc_args, IfcFaceData,
+    IfcRawEntity,
+};
+
+/// Entity types the parser has to keep for [`OpeningFills`].
+pub(crate) const OPENING_TYPES: &[&str] = &[
+    "IFCOPENINGELEMENT",
+    "IFCRELFILLSELEMENT",
+    "IFCEXTRUDEDAREASOLID",
+];
+
+/// Depth, in metres, of a stand-in whose opening has no extrusion.
+const DEFAULT_DEPTH_METRES: f64 = 0.05;
+
+/// The openings doors and windows fill, for [`fallback_faces`](Self::fallback_faces).
+#[derive(Debug, Clone, Default)]
+pub(crate) struct OpeningFills {
+    /// Filling element -> opening element
+    openings: HashMap<StepId, StepId>,
+    /// [`DEFAULT_DEPTH_METRES`] in model units
+    default_depth: f64,
+}
+
+impl OpeningFills {
+    pub(crate) fn new(entities: &HashMap<StepId, IfcRawEntity>, length: LengthUnit) -> Self {
+        let mut openings = HashMap::new();
+        for entity in entities.values() {
+            // IFCRELFILLSELEMENT(GlobalId, OwnerHistory, Name, Description,
+            //   RelatingOpeningElement, RelatedBuildingElement)
+            if entity.type_name != "IFCRELFILLSELEMENT" {
+                continue;
+            }
+            let args = split_ifc_args(&entity.raw_args);
+            let opening = args.get(4).and_then(|a| extract_single_ref(a));
+            let element = args.get(5).and_then(|a| extract_single_ref(a));
+            if let (Some(opening), Some(element)) = (opening, element) {
+                openings.insert(element, opening);
+            }
+        }
+        Self {
+            openings,
+            default_depth: DEFAULT_DEPTH_METRES / length.metres(),
+        }
+    }
+
+    /// A box in world coordinates for an IFCDOOR or IFCWINDOW with an
+    /// overall height and width, spanning x in `[0, width]`, y in
+    /// `[0, depth]` and z in `[0, height]` of its placement. Elements
+    /// without a placement of their own take the opening's. `None` for
+    /// other products and for doors and windows without a size.
+    pub(crate) fn fallback_faces(
+        &self,
+        product: &IfcRawEntity,
+        entities: &HashMap<StepId, IfcRawEntity>,
+    ) -> Option<Vec<IfcFaceData>> {
+        if !matches!(product.type_name.as_str(), "IFCDOOR" | "IFCWINDOW") {
+            return None;
+        }
+        // 5=ObjectPlacement, 8=OverallHeight, 9=OverallWidth
+        let args = split_ifc_args(&product.raw_args);
+        let size = |i: usize| {
+            args.get(i)
+                .and_then(|a| a.trim().parse::<f64>().ok())
+                .filter(|v| *v > 0.0)
+        };
+        let (height, width) = (size(8)?, size(9)?);
+
+        let opening = self
+            .openings
+            .get(&product.entity_id)
+            .and_then(|id| entities.get(id));
+        let placement = args.get(5).and_then(|a| extract_single_ref(a)).or_else(|| {
+            let opening_args = split_ifc_args(&opening?.raw_args);
+            opening_args.get(5).and_then(|a| extract_single_ref(a))
+        });
+        let transform = placement
+            .map(|id| resolve_placement_chain(id, entities))
+            .unwrap_or(DMat4::IDENTITY);
+        let depth = opening
+            .and_then(|opening| extrusion_depth(opening, entities))
+            .unwrap_or(self.default_depth);
+
+        Some(box_faces(DVec3::new(width, depth, height), &transform))
+    }
+}
+
+/// Depth of the first IFCEXTRUDEDAREASOLID in an element's representations.
+fn extrusion_depth(
+    element: &IfcRawEntity,
+    entities: &HashMap<StepId, IfcRawEntity>,
+) -> Option<f64> {
+    // 6=Representation -> IFCPRODUCTDEFINITIONSHAPE($,$,(#rep,...))
+    let args = split_ifc_args(&element.raw_args);
+    let shape = entities.get(&extract_single_ref(args.get(6)?)?)?;
+    let shape_args = split_ifc_args(&shape.raw_args);
+    parse_entity_refs(shape_args.get(2)?)
+        .into_iter()
+        .filter_map(|id| entities.get(&id))
+        .filter(|rep| rep.type_name == "IFCSHAPEREPRESENTATION")
+        .flat_map(|rep| {
+            // (Context, Identifier, Type, Items)
+            let rep_args = split_ifc_args(&rep.raw_args);
+            rep_args
+                .get(3)
+                .map(|a| parse_entity_refs(a))
+                .unwrap_or_default()
+        })
+        .filter_map(|id| entities.get(&id))
+        .filter(|item| item.type_name == "IFCEXTRUDEDAREASOLID")
+        .find_map(|solid| {
+            // (SweptArea, Position, ExtrudedDirection, Depth)
+            let solid_args = split_ifc_args(&solid.raw_args);
+            solid_args
+                .get(3)?
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|depth| *depth > 0.0)
+        })
+}
+
+/// The six outward faces of the box from the origin to `size`, transformed.
+fn box_faces(size: DVec3, transform: &DMat4) -> Vec<IfcFaceData> {
+    let corner = |i: usize| {
+        let p = DVec3::new(
+            if i & 1 == 0 { 0.0 } else { size.x },
+            if i & 2 == 0 { 0.0 } else { size.y },
+            if i & 4 == 0 { 0.0 } else { size.z },
+        );
+        transform.transform_point3(p)
+    };
+    // Corner indices of each face, counter-clockwise seen from outside
+    const FACES: [[usize; 4]; 6] = [
+        [0, 2, 3, 1],
+        [4, 5, 7, 6],
+        [0, 1, 5, 4],
+        [2, 6, 7, 3],
+        [0, 4, 6, 2],
+        [1, 3, 7, 5],
+    ];
+    // A mirroring placement turns the box inside out
+    let mirrored = has_mirror(*transform);
+    FACES
+        .iter()
+        .map(|face| {
+            let mut outer: Vec<DVec3> = face.iter().map(|&i| corner(i)).collect();
+            if mirrored {
+                outer.reverse();
+            }
+            IfcFaceData {
+                outer,
+                holes: Vec::new(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ifc_query::IfcQuery;
+    use cst_core::ProductId;
+
+    /// A 900 × 2100 door without a body in a 200 deep wall opening placed
+    /// at (1000, 0, 0), and a window with neither size nor body.
+    const DOOR_IN_OPENING: &str = "ISO-10303-21;
+HEADER;
+FILE_SCHEMA(('IFC2X3'));
+ENDSEC;
+DATA;
+#1= IFCCARTESIANPOINT((1000.,0.,0.));
+#2= IFCAXIS2PLACEMENT3D(#1,$,$);
+#3= IFCLOCALPLACEMENT($,#2);
+#4= IFCCARTESIANPOINT((0.,0.));
+#5= IFCAXIS2PLACEMENT2D(#4,$);
+#6= IFCRECTANGLEPROFILEDEF(.AREA.,$,#5,900.,2100.);
+#7= IFCDIRECTION((0.,0.,1.));
+#8= IFCEXTRUDEDAREASOLID(#6,$,#7,200.);
+#9= IFCSHAPEREPRESENTATION($,'Body','SweptSolid',(#8));
+#10= IFCPRODUCTDEFINITIONSHAPE($,$,(#9));
+#11= IFCOPENINGELEMENT('guid1',$,$,$,$,#3,#10,$);
+#20= IFCDOOR('guid2',$,'Door',$,$,$,$,$,2100.,900.);
+#21= IFCRELFILLSELEMENT('guid3',$,$,$,#11,#20);
+#30= IFCWINDOW('guid4',$,'Window',$,$,#3,$,$,$,$);
+ENDSEC;
+END-ISO-10303-21;
+";
+
+    #[test]
+    fn test_door_without_body_fills_its_opening() {
+        let query = IfcQuery::from_reader(DOOR_IN_OPENING.as_bytes()).unwrap();
+        let meshes = query.meshes(&[ProductId(StepId(20)), ProductId(StepId(30))]);
+        assert_eq!(meshes.len(), 1);
+        assert_eq!(meshes[0].name, "Door_20");
+        assert_eq!(meshes[0].faces.len(), 6);
+
+        let points = meshes[0].faces.iter().flat_map(|f| f.outer.iter().copied());
+        let (min, max) = points.fold(
+            (DVec3::splat(f64::INFINITY), DVec3::splat(f64::NEG_INFINITY)),
+            |(min, max), p| (min.min(p), max.max(p)),
+        );
+        assert!((min - DVec3::new(1000.0, 0.0, 0.0)).length() < 1e-9);
+        assert!((max - DVec3::new(1900.0, 200.0, 2100.0)).length() < 1e-9);
+    }
+
+    #[test]
+    fn test_box_faces_point_outward() {
+        let size = DVec3::new(1.0, 2.0, 3.0);
+        let mirror = DMat4::from_scale(DVec3::new(-1.0, 1.0, 1.0));
+        for transform in [DMat4::IDENTITY, mirror] {
+            let faces = box_faces(size, &transform);
+            let center = transform.transform_point3(size / 2.0);
+            for face in &faces {
+                let [a, b, c] = [face.outer[0], face.outer[1], face.outer[2]];
+                assert!((b - a).cross(c - a).dot(a - center) > 0.0);
+            }
+        }
+    }
+}
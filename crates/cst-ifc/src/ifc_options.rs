@@ -0,0 +1,248 @@
+//! Options shared by the IFC conversion entry points.
+
+use cst_core::LengthUnit;
+
+/// How far, in metres, circles may stray from their polygons when no
+/// tessellation tolerance is set.
+const DEFAULT_SAGITTA_METRES: f64 = 0.001;
+
+/// Colors for unstyled elements by product type, the default
+/// [`IfcPipelineOptions::type_colors`]. Neutral for the structure, warm for
+/// doors, blue for glazing and distinct hues per MEP system.
+pub const DEFAULT_TYPE_COLORS: &[(&str, [f32; 3])] = &[
+    ("IFCWALL", [0.85, 0.82, 0.76]),
+    ("IFCCURTAINWALL", [0.55, 0.72, 0.85]),
+    ("IFCSLAB", [0.62, 0.62, 0.6]),
+    ("IFCROOF", [0.62, 0.38, 0.32]),
+    ("IFCCOVERING", [0.8, 0.78, 0.72]),
+    ("IFCCOLUMN", [0.55, 0.6, 0.68]),
+    ("IFCBEAM", [0.5, 0.56, 0.66]),
+    ("IFCMEMBER", [0.56, 0.6, 0.64]),
+    ("IFCPLATE", [0.6, 0.66, 0.72]),
+    ("IFCFOOTING", [0.5, 0.47, 0.42]),
+    ("IFCPILE", [0.5, 0.47, 0.42]),
+    ("IFCSTAIR", [0.72, 0.68, 0.6]),
+    ("IFCSTAIRFLIGHT", [0.72, 0.68, 0.6]),
+    ("IFCRAMP", [0.72, 0.68, 0.6]),
+    ("IFCRAMPFLIGHT", [0.72, 0.68, 0.6]),
+    ("IFCRAILING", [0.4, 0.4, 0.45]),
+    ("IFCDOOR", [0.6, 0.42, 0.25]),
+    ("IFCWINDOW", [0.55, 0.78, 0.92]),
+    ("IFCREINFORCINGBAR", [0.36, 0.3, 0.28]),
+    ("IFCREINFORCINGMESH", [0.36, 0.3, 0.28]),
+    ("IFCTENDON", [0.36, 0.3, 0.28]),
+    ("IFCFLOWSEGMENT", [0.25, 0.55, 0.8]),
+    ("IFCFLOWFITTING", [0.25, 0.55, 0.8]),
+    ("IFCPIPESEGMENT", [0.2, 0.5, 0.8]),
+    ("IFCPIPEFITTING", [0.2, 0.5, 0.8]),
+    ("IFCDUCTSEGMENT", [0.7, 0.73, 0.8]),
+    ("IFCDUCTFITTING", [0.7, 0.73, 0.8]),
+    ("IFCCABLECARRIERSEGMENT", [0.9, 0.6, 0.15]),
+    ("IFCCABLECARRIERFITTING", [0.9, 0.6, 0.15]),
+    ("IFCFLOWTERMINAL", [0.88, 0.88, 0.92]),
+];
+
+/// Type name suffixes of IFC2x3 subtypes that share their supertype's
+/// color, e.g. IFCWALLSTANDARDCASE.
+const TYPE_SUFFIXES: &[&str] = &["STANDARDCASE", "ELEMENTEDCASE"];
+
+/// How an IFC model is read, tessellated and grouped into a scene.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IfcPipelineOptions {
+    /// Geometric tolerance in model units. Faces with less area than a
+    /// tolerance-sized square are dropped when tessellating; 0 keeps all
+    /// detail. Also the sagitta circles and arcs are polygonized with, see
+    /// [`sagitta`](Self::sagitta).
+    pub tessellation_tolerance: f64,
+    /// Factor applied to every coordinate, overriding the model's length
+    /// unit (e.g. `0.001` to turn millimetres into metres). `None` keeps
+    /// the coordinates as written.
+    pub unit_scale: Option<f64>,
+    /// Product types to keep, matched case-insensitively (`"IfcWall"`).
+    /// `None` keeps every product type.
+    pub type_filter: Option<Vec<String>>,
+    /// Product types to drop, matched case-insensitively. Applied after
+    /// `type_filter`.
+    pub exclude_types: Vec<String>,
+    /// Keep only products contained in the storey with this name.
+    pub storey: Option<String>,
+    /// Share identical geometry between elements as instanced groups.
+    pub instancing: bool,
+    /// Maximum number of triangles in the output. `None` is unlimited.
+    pub triangle_budget: Option<usize>,
+    /// Upper bound on merged draw batches per color.
+    pub max_batches: usize,
+    /// Color for elements without a style or a type color.
+    pub default_color: [f32; 3],
+    /// Colors for elements without a style, by product type matched
+    /// case-insensitively (`"IfcWall"`). Empty leaves every unstyled
+    /// element `default_color`.
+    pub type_colors: Vec<(String, [f32; 3])>,
+    /// Group elements by their containing storey.
+    pub spatial_grouping: bool,
+    /// Reuse tessellated results cached next to the IFC file, see
+    /// [`crate::ifc_cache`].
+    pub cache: bool,
+    /// Tag faces with the plane, cylinder or sphere they approximate, see
+    /// [`crate::ifc_surfaces`].
+    pub detect_surfaces: bool,
+}
+
+impl Default for IfcPipelineOptions {
+    fn default() -> Self {
+        Self {
+            tessellation_tolerance: 0.0,
+            unit_scale: None,
+            type_filter: None,
+            exclude_types: Vec::new(),
+            storey: None,
+            instancing: true,
+            triangle_budget: None,
+            max_batches: 200,
+            default_color: [0.7, 0.7, 0.7],
+            type_colors: DEFAULT_TYPE_COLORS
+                .iter()
+                .map(|(type_name, color)| (type_name.to_string(), *color))
+                .collect(),
+            spatial_grouping: false,
+            cache: false,
+            detect_surfaces: false,
+        }
+    }
+}
+
+impl IfcPipelineOptions {
+    /// Whether products of `type_name` pass the include and exclude lists.
+    pub fn accepts_type(&self, type_name: &str) -> bool {
+        let included = self.type_filter.as_ref().map_or(true, |types| {
+            types.iter().any(|t| t.eq_ignore_ascii_case(type_name))
+        });
+        included
+            && !self
+                .exclude_types
+                .iter()
+                .any(|t| t.eq_ignore_ascii_case(type_name))
+    }
+
+    /// Whether only a chosen subset of products is wanted.
+    pub fn filters_elements(&self) -> bool {
+        self.type_filter.is_some() || self.storey.is_some()
+    }
+
+    /// Largest distance, in model units of `length`, between a circle or
+    /// arc and the polygon it is drawn with: the tessellation tolerance, or
+    /// 1 mm when that is 0. Pass it to
+    /// [`profile_points_with_tolerance`](crate::ifc_geometry::profile_points_with_tolerance)
+    /// so the segment count follows the radius.
+    pub fn sagitta(&self, length: LengthUnit) -> f64 {
+        if self.tessellation_tolerance > 0.0 {
+            self.tessellation_tolerance
+        } else {
+            DEFAULT_SAGITTA_METRES / length.metres()
+        }
+    }
+
+    /// Coordinate scale factor, 1 without a unit override.
+    pub fn scale(&self) -> f64 {
+        self.unit_scale.unwrap_or(1.0)
+    }
+
+    /// The color of unstyled products of `type_name`, if `type_colors` has
+    /// one. Subtypes like IFCWALLSTANDARDCASE fall back to their supertype.
+    pub fn type_color(&self, type_name: &str) -> Option<[f32; 3]> {
+        let lookup = |name: &str| {
+            self.type_colors
+                .iter()
+                .find(|(t, _)| t.eq_ignore_ascii_case(name))
+                .map(|(_, color)| *color)
+        };
+        lookup(type_name).or_else(|| {
+            let upper = type_name.to_ascii_uppercase();
+            TYPE_SUFFIXES
+                .iter()
+                .find_map(|suffix| upper.strip_suffix(suffix))
+                .and_then(lookup)
+        })
+    }
+
+    /// `color`, or the default color when the element has none.
+    pub fn color_or_default(&self, color: Option<[f32; 3]>) -> [f32; 3] {
+        color.unwrap_or(self.default_color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_keep_everything() {
+        let options = IfcPipelineOptions::default();
+        assert!(options.accepts_type("IFCWALL"));
+        assert_eq!(options.scale(), 1.0);
+        assert_eq!(options.color_or_default(None), [0.7, 0.7, 0.7]);
+        assert_eq!(
+            options.color_or_default(Some([1.0, 0.0, 0.0])),
+            [1.0, 0.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn test_type_colors() {
+        let options = IfcPipelineOptions::default();
+        let wall = options.type_color("IFCWALL").unwrap();
+        assert_eq!(options.type_color("IfcWallStandardCase"), Some(wall));
+        assert_ne!(options.type_color("IFCDOOR"), Some(wall));
+        assert_eq!(options.type_color("IFCBUILDINGELEMENTPROXY"), None);
+
+        let custom = IfcPipelineOptions {
+            type_colors: vec![("IfcSlab".into(), [1.0, 0.0, 0.0])],
+            ..Default::default()
+        };
+        assert_eq!(custom.type_color("IFCSLAB"), Some([1.0, 0.0, 0.0]));
+        assert_eq!(custom.type_color("IFCWALL"), None);
+    }
+
+    #[test]
+    fn test_sagitta_in_model_units() {
+        let options = IfcPipelineOptions::default();
+        assert!((options.sagitta(LengthUnit::Millimetre) - 1.0).abs() < 1e-12);
+        assert!((options.sagitta(LengthUnit::Metre) - 0.001).abs() < 1e-12);
+
+        let coarse = IfcPipelineOptions {
+            tessellation_tolerance: 5.0,
+            ..Default::default()
+        };
+        assert_eq!(coarse.sagitta(LengthUnit::Millimetre), 5.0);
+    }
+
+    #[test]
+    fn test_type_filter_ignores_case() {
+        let options = IfcPipelineOptions {
+            type_filter: Some(vec!["IfcWall".into(), "IfcSlab".into()]),
+            ..Default::default()
+        };
+        assert!(options.accepts_type("IFCWALL"));
+        assert!(options.accepts_type("IFCSLAB"));
+        assert!(!options.accepts_type("IFCBEAM"));
+    }
+
+    #[test]
+    fn test_exclude_types_win_over_include() {
+        let options = IfcPipelineOptions {
+            type_filter: Some(vec!["IfcWall".into(), "IfcSlab".into()]),
+            exclude_types: vec!["ifcslab".into()],
+            ..Default::default()
+        };
+        assert!(options.accepts_type("IFCWALL"));
+        assert!(!options.accepts_type("IFCSLAB"));
+
+        let excluding = IfcPipelineOptions {
+            exclude_types: vec!["IfcDoor".into()],
+            ..Default::default()
+        };
+        assert!(excluding.accepts_type("IFCWALL"));
+        assert!(!excluding.accepts_type("IFCDOOR"));
+        assert!(!excluding.filters_elements());
+    }
+}
@@ -0,0 +1,73 @@
+//! Progress reporting for long-running IFC conversions.
+//!
+//! Large models take minutes to parse and resolve. Callers that want to
+//! show progress pass a [`ProgressSink`] to the `*_with_progress` entry
+//! points; everything else uses [`NoProgress`].
+//!
+//! The same sink lets callers abort: the pipeline polls
+//! [`ProgressSink::is_cancelled`] between units of work and stops with
+//! [`CstError::Cancelled`](cst_core::CstError::Cancelled). A bare
+//! [`CancellationToken`] is a sink that only cancels.
+
+pub use cst_core::CancellationToken;
+use cst_core::{CstError, Result};
+
+/// Pipeline stage a progress update belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProgressStage {
+    /// Reading the file, in bytes
+    Parse,
+    /// Resolving products to placed geometry, in products
+    Resolve,
+    /// Triangulating resolved faces, in meshes
+    Tessellate,
+}
+
+/// Receiver for pipeline progress.
+///
+/// `advance` is called from rayon workers, so implementations must be
+/// thread-safe and cheap.
+pub trait ProgressSink: Sync {
+    /// `stage` begins with `total` units of work.
+    fn start(&self, stage: ProgressStage, total: u64);
+
+    /// `units` more units of `stage` are done.
+    fn advance(&self, stage: ProgressStage, units: u64);
+
+    /// `stage` is complete.
+    fn finish(&self, _stage: ProgressStage) {}
+
+    /// Whether the caller wants the conversion to stop.
+    fn is_cancelled(&self) -> bool {
+        false
+    }
+}
+
+/// Sink that discards all progress.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoProgress;
+
+impl ProgressSink for NoProgress {
+    fn start(&self, _stage: ProgressStage, _total: u64) {}
+
+    fn advance(&self, _stage: ProgressStage, _units: u64) {}
+}
+
+/// `Err(CstError::Cancelled)` once `progress` asks to stop.
+pub(crate) fn check_cancelled(progress: &dyn ProgressSink) -> Result<()> {
+    if progress.is_cancelled() {
+        Err(CstError::Cancelled)
+    } else {
+        Ok(())
+    }
+}
+
+impl ProgressSink for CancellationToken {
+    fn start(&self, _stage: ProgressStage, _total: u64) {}
+
+    fn advance(&self, _stage: ProgressStage, _units: u64) {}
+
+    fn is_cancelled(&self) -> bool {
+        CancellationToken::is_cancelled(self)
+    }
+}
@@ -0,0 +1,588 @@
+//! Element queries over a parsed IFC model.
+//!
+//! [`IfcQuery`] parses a file once and indexes its products by entity
+//! type, containing storey and single-value properties, so applications
+//! can filter elements and resolve meshes for just the matching ids
+//! without reparsing.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::BufRead;
+use std::path::Path;
+
+use cst_core::{Diagnostics, ModelUnits, ProductId, Result, StepId};
+
+use crate::ifc_assembly::stair_assemblies;
+use crate::ifc_geometry::SweepTessellation;
+use crate::ifc_openings::OpeningFills;
+use crate::ifc_options::IfcPipelineOptions;
+use crate::ifc_progress::NoProgress;
+use crate::ifc_reader::{
+    build_brep_color_map, extract_single_ref, log_diagnostics, parse_entity_refs,
+    parse_ifc_entities, parse_ifc_entities_from_reader, resolve_product, split_ifc_args,
+    IfcMeshData, IfcRawEntity, PRODUCT_TYPES,
+};
+use crate::ifc_units::model_units;
+
+/// Indexed view of the products in an IFC model.
+///
+/// Query results are product entity ids in ascending order.
+#[derive(Debug, Clone)]
+pub struct IfcQuery {
+    entities: HashMap<StepId, IfcRawEntity>,
+    brep_color_map: HashMap<StepId, [f32; 3]>,
+    opening_fills: OpeningFills,
+    tessellation: SweepTessellation,
+    /// Upper-case type name -> product ids
+    by_type: BTreeMap<String, Vec<ProductId>>,
+    /// Storey name -> contained product ids
+    by_storey: BTreeMap<String, Vec<ProductId>>,
+    /// Storey name -> elevation, in model units
+    elevations: BTreeMap<String, f64>,
+    /// Product id -> (property name, value) from its property and
+    /// quantity sets
+    properties: HashMap<ProductId, Vec<(String, String)>>,
+    /// Product id -> (`Set.Property`, value), the same values qualified
+    /// by the name of their set
+    qualified: HashMap<ProductId, Vec<(String, String)>>,
+    /// IFC GlobalId -> product id
+    by_guid: HashMap<String, ProductId>,
+    units: ModelUnits,
+}
+
+impl IfcQuery {
+    /// Parse the IFC file at `path` and index it.
+    pub fn open(path: &Path) -> Result<Self> {
+        Ok(Self::from_entities(parse_ifc_entities(path)?))
+    }
+
+    /// Parse IFC text from `reader`, e.g. a byte slice, and index it.
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self> {
+        let entities = parse_ifc_entities_from_reader(reader, 0, &NoProgress)?;
+        Ok(Self::from_entities(entities))
+    }
+
+    /// Index already-parsed entities.
+    pub fn from_entities(entities: HashMap<StepId, IfcRawEntity>) -> Self {
+        let mut by_type: BTreeMap<String, Vec<ProductId>> = BTreeMap::new();
+        let mut by_guid = HashMap::new();
+        for (id, entity) in &entities {
+            if PRODUCT_TYPES.contains(&entity.type_name.as_str()) {
+                by_type
+                    .entry(entity.type_name.to_string())
+                    .or_default()
+                    .push(ProductId(*id));
+                if let Some(guid) = product_arg(entity, 0) {
+                    by_guid.insert(guid, ProductId(*id));
+                }
+            }
+        }
+
+        let by_storey = storey_containment(&entities);
+        let elevations = storey_elevations(&entities);
+        let units = model_units(&entities);
+        let mut properties: HashMap<ProductId, Vec<(String, String)>> = HashMap::new();
+        let mut qualified: HashMap<ProductId, Vec<(String, String)>> = HashMap::new();
+        // In id order, so every run lists an element's properties alike
+        let mut relations: Vec<&IfcRawEntity> = entities
+            .values()
+            .filter(|e| e.type_name == "IFCRELDEFINESBYPROPERTIES")
+            .collect();
+        relations.sort_by_key(|e| e.entity_id);
+        for entity in relations {
+            // IFCRELDEFINESBYPROPERTIES(GlobalId, OwnerHistory, Name,
+            //   Description, RelatedObjects, RelatingPropertyDefinition)
+            let args = split_ifc_args(&entity.raw_args);
+            if args.len() < 6 {
+                continue;
+            }
+            let Some((set_name, values)) =
+                extract_single_ref(&args[5]).and_then(|id| property_set_values(id, &entities))
+            else {
+                continue;
+            };
+            if values.is_empty() {
+                continue;
+            }
+            for object in parse_entity_refs(&args[4]).into_iter().map(ProductId) {
+                properties
+                    .entry(object)
+                    .or_default()
+                    .extend(values.iter().cloned());
+                qualified.entry(object).or_default().extend(
+                    values
+                        .iter()
+                        .map(|(name, value)| (format!("{}.{}", set_name, name), value.clone())),
+                );
+            }
+        }
+
+        for ids in by_type.values_mut() {
+            ids.sort_unstable();
+            ids.dedup();
+        }
+
+        let brep_color_map = build_brep_color_map(&entities);
+        let opening_fills = OpeningFills::new(&entities, units.length);
+        let tessellation = SweepTessellation::new(&units, &IfcPipelineOptions::default());
+        Self {
+            entities,
+            brep_color_map,
+            opening_fills,
+            tessellation,
+            by_type,
+            by_storey,
+            elevations,
+            properties,
+            qualified,
+            by_guid,
+            units,
+        }
+    }
+
+    /// Products of the given IFC type, matched case-insensitively
+    /// (`"IfcWall"` finds `IFCWALL`). Subtypes are not included.
+    pub fn elements_of_type(&self, type_name: &str) -> Vec<ProductId> {
+        self.by_type
+            .get(&type_name.to_ascii_uppercase())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Every product in the model.
+    pub fn elements(&self) -> Vec<ProductId> {
+        let mut ids: Vec<ProductId> = self.by_type.values().flatten().copied().collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Products contained in the storey with the given name.
+    pub fn elements_in_storey(&self, storey_name: &str) -> Vec<ProductId> {
+        self.by_storey.get(storey_name).cloned().unwrap_or_default()
+    }
+
+    /// Products with a single-value property `name` equal to `value`, in
+    /// any of their property sets.
+    pub fn elements_with_property(&self, name: &str, value: &str) -> Vec<ProductId> {
+        let mut ids: Vec<ProductId> = self
+            .properties
+            .iter()
+            .filter(|(_, props)| props.iter().any(|(n, v)| n == name && v == value))
+            .map(|(id, _)| *id)
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Value of property `name` on product `id`, if set.
+    pub fn property(&self, id: ProductId, name: &str) -> Option<&str> {
+        self.properties
+            .get(&id)?
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Names of all storeys that contain products, sorted.
+    pub fn storeys(&self) -> Vec<&str> {
+        self.by_storey.keys().map(String::as_str).collect()
+    }
+
+    /// Elevation of the named storey in model units, if the file sets it.
+    pub fn storey_elevation(&self, storey_name: &str) -> Option<f64> {
+        self.elevations.get(storey_name).copied()
+    }
+
+    /// Product with the given IFC `GlobalId`.
+    pub fn element_by_guid(&self, guid: &str) -> Option<ProductId> {
+        self.by_guid.get(guid).copied()
+    }
+
+    /// IFC `GlobalId` of product `id`.
+    pub fn global_id(&self, id: ProductId) -> Option<String> {
+        product_arg(self.entities.get(&id.step())?, 0)
+    }
+
+    /// Name of product `id`, if set.
+    pub fn name(&self, id: ProductId) -> Option<String> {
+        product_arg(self.entities.get(&id.step())?, 2)
+    }
+
+    /// Name of the storey containing product `id`.
+    pub fn storey_of(&self, id: ProductId) -> Option<&str> {
+        self.by_storey
+            .iter()
+            .find(|(_, ids)| ids.binary_search(&id).is_ok())
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// All single-value properties and quantities of product `id`, as
+    /// (name, value).
+    pub fn properties(&self, id: ProductId) -> &[(String, String)] {
+        self.properties.get(&id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Like [`properties`](Self::properties), with each name prefixed by
+    /// its set, e.g. `Pset_WallCommon.FireRating` or
+    /// `Qto_WallBaseQuantities.Length`.
+    pub fn qualified_properties(&self, id: ProductId) -> &[(String, String)] {
+        self.qualified.get(&id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Length, area and angle units of the model's numbers.
+    pub fn units(&self) -> ModelUnits {
+        self.units
+    }
+
+    /// Upper-case IFC type name of product `id`.
+    pub fn type_of(&self, id: ProductId) -> Option<&str> {
+        self.entities.get(&id.step()).map(|e| e.type_name.as_str())
+    }
+
+    /// Resolve the placed meshes of the given products. Ids that are not
+    /// products are skipped.
+    pub fn meshes(&self, ids: &[ProductId]) -> Vec<IfcMeshData> {
+        let mut diagnostics = Diagnostics::new();
+        let meshes = self.meshes_with_diagnostics(ids, &mut diagnostics);
+        log_diagnostics(&diagnostics);
+        meshes
+    }
+
+    /// Like [`meshes`](Self::meshes), recording skipped geometry in
+    /// `diagnostics` instead of logging it.
+    pub fn meshes_with_diagnostics(
+        &self,
+        ids: &[ProductId],
+        diagnostics: &mut Diagnostics,
+    ) -> Vec<IfcMeshData> {
+        ids.iter()
+            .filter_map(|id| self.entities.get(&id.step()).map(|e| (*id, e)))
+            .filter(|(_, e)| PRODUCT_TYPES.contains(&e.type_name.as_str()))
+            .flat_map(|(id, e)| {
+                resolve_product(
+                    id,
+                    e,
+                    &self.entities,
+                    &self.brep_color_map,
+                    &self.opening_fills,
+                    &self.tessellation,
+                    diagnostics,
+                )
+            })
+            .collect()
+    }
+}
+
+/// Storey name -> ids of the elements it contains, sorted.
+pub(crate) fn storey_containment(
+    entities: &HashMap<StepId, IfcRawEntity>,
+) -> BTreeMap<String, Vec<ProductId>> {
+    let mut by_storey: BTreeMap<String, Vec<ProductId>> = BTreeMap::new();
+    for entity in entities.values() {
+        // IFCRELCONTAINEDINSPATIALSTRUCTURE(GlobalId, OwnerHistory, Name,
+        //   Description, RelatedElements, RelatingStructure)
+        if entity.type_name != "IFCRELCONTAINEDINSPATIALSTRUCTURE" {
+            continue;
+        }
+        let args = split_ifc_args(&entity.raw_args);
+        if args.len() < 6 {
+            continue;
+        }
+        let Some(storey) = extract_single_ref(&args[5])
+            .and_then(|id| entities.get(&id))
+            .filter(|e| e.type_name == "IFCBUILDINGSTOREY")
+        else {
+            continue;
+        };
+        let Some(name) = split_ifc_args(&storey.raw_args)
+            .get(2)
+            .and_then(|arg| ifc_string(arg))
+        else {
+            continue;
+        };
+        by_storey
+            .entry(name)
+            .or_default()
+            .extend(parse_entity_refs(&args[4]).into_iter().map(ProductId));
+    }
+    // Stair parts are contained through their stair
+    let stairs = stair_assemblies(entities);
+    for ids in by_storey.values_mut() {
+        ids.sort_unstable();
+        let parts: Vec<ProductId> = stairs
+            .iter()
+            .filter(|stair| ids.binary_search(&stair.id).is_ok())
+            .flat_map(|stair| stair.parts.iter().copied())
+            .collect();
+        ids.extend(parts);
+        ids.sort_unstable();
+        ids.dedup();
+    }
+    by_storey
+}
+
+/// Storey name -> `Elevation` attribute, for storeys that set it.
+pub(crate) fn storey_elevations(entities: &HashMap<StepId, IfcRawEntity>) -> BTreeMap<String, f64> {
+    entities
+        .values()
+        .filter(|e| e.type_name == "IFCBUILDINGSTOREY")
+        .filter_map(|storey| {
+            // IFCBUILDINGSTOREY(GlobalId, OwnerHistory, Name, Description,
+            //   ObjectType, ObjectPlacement, Representation, LongName,
+            //   CompositionType, Elevation)
+            let args = split_ifc_args(&storey.raw_args);
+            let name = ifc_string(args.get(2)?)?;
+            let elevation = args.get(9)?.trim().parse().ok()?;
+            Some((name, elevation))
+        })
+        .collect()
+}
+
+/// Name and `(name, value)` pairs of property set or quantity set `id`:
+/// the single-value properties of an `IFCPROPERTYSET`, or the quantities
+/// of an `IFCELEMENTQUANTITY`.
+fn property_set_values(
+    id: StepId,
+    entities: &HashMap<StepId, IfcRawEntity>,
+) -> Option<(String, Vec<(String, String)>)> {
+    let set = entities.get(&id)?;
+    // IFCPROPERTYSET(GlobalId, OwnerHistory, Name, Description, HasProperties)
+    // IFCELEMENTQUANTITY(GlobalId, OwnerHistory, Name, Description,
+    //   MethodOfMeasurement, Quantities)
+    let members = match set.type_name.as_str() {
+        "IFCPROPERTYSET" => 4,
+        "IFCELEMENTQUANTITY" => 5,
+        _ => return None,
+    };
+    let args = split_ifc_args(&set.raw_args);
+    let set_name = ifc_string(args.get(2)?)?;
+    let values = parse_entity_refs(args.get(members)?)
+        .into_iter()
+        .filter_map(|member_id| {
+            let member = entities.get(&member_id)?;
+            let args = split_ifc_args(&member.raw_args);
+            let name = ifc_string(args.first()?)?;
+            let value = match member.type_name.as_str() {
+                // IFCPROPERTYSINGLEVALUE(Name, Description, NominalValue, Unit)
+                "IFCPROPERTYSINGLEVALUE" => nominal_value(args.get(2)?)?,
+                // IFCQUANTITYLENGTH(Name, Description, Unit, LengthValue,
+                //   [Formula]), and likewise for area, volume, count,
+                //   weight and time
+                "IFCQUANTITYLENGTH" | "IFCQUANTITYAREA" | "IFCQUANTITYVOLUME"
+                | "IFCQUANTITYCOUNT" | "IFCQUANTITYWEIGHT" | "IFCQUANTITYTIME" => {
+                    let value: f64 = args.get(3)?.trim().parse().ok()?;
+                    value.to_string()
+                }
+                _ => return None,
+            };
+            Some((name, value))
+        })
+        .collect();
+    Some((set_name, values))
+}
+
+/// String argument `index` of a product (0 = GlobalId, 2 = Name).
+fn product_arg(entity: &IfcRawEntity, index: usize) -> Option<String> {
+    ifc_string(split_ifc_args(&entity.raw_args).get(index)?)
+}
+
+/// Text of a quoted IFC string argument; `None` for `$` or empty.
+fn ifc_string(arg: &str) -> Option<String> {
+    let text = arg.trim().trim_matches('\'');
+    (text != "$" && !text.is_empty()).then(|| text.to_string())
+}
+
+/// Plain text of a typed nominal value such as `IFCLABEL('REI120')`,
+/// `IFCREAL(1.5)` or `IFCBOOLEAN(.T.)`.
+fn nominal_value(arg: &str) -> Option<String> {
+    let arg = arg.trim();
+    if arg == "$" {
+        return None;
+    }
+    let inner = match (arg.find('('), arg.rfind(')')) {
+        (Some(open), Some(close)) if open < close => &arg[open + 1..close],
+        _ => arg,
+    };
+    Some(
+        inner
+            .trim()
+            .trim_matches('\'')
+            .trim_matches('.')
+            .to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    const MODEL: &str = r#"ISO-10303-21;
+HEADER;
+FILE_SCHEMA(('IFC2X3'));
+ENDSEC;
+DATA;
+#1= IFCCARTESIANPOINT((0.,0.,0.));
+#2= IFCCARTESIANPOINT((1.,0.,0.));
+#3= IFCCARTESIANPOINT((1.,1.,0.));
+#4= IFCCARTESIANPOINT((0.,1.,0.));
+#5= IFCPOLYLOOP((#1,#2,#3,#4));
+#6= IFCFACEOUTERBOUND(#5,.T.);
+#7= IFCFACE((#6));
+#8= IFCCLOSEDSHELL((#7));
+#9= IFCFACETEDBREP(#8);
+#10= IFCSHAPEREPRESENTATION($,'Body','Brep',(#9));
+#11= IFCPRODUCTDEFINITIONSHAPE($,$,(#10));
+#20= IFCWALL('w1',$,'Wall A',$,$,$,#11,$);
+#21= IFCWALL('w2',$,'Wall B',$,$,$,#11,$);
+#22= IFCSLAB('s1',$,'Slab',$,$,$,#11,$);
+#30= IFCBUILDINGSTOREY('st1',$,'Level 1',$,$,$,$,$,.ELEMENT.,0.);
+#31= IFCBUILDINGSTOREY('st2',$,'Level 2',$,$,$,$,$,.ELEMENT.,3000.);
+#32= IFCRELCONTAINEDINSPATIALSTRUCTURE('r1',$,$,$,(#20,#22),#30);
+#33= IFCRELCONTAINEDINSPATIALSTRUCTURE('r2',$,$,$,(#21),#31);
+#40= IFCPROPERTYSINGLEVALUE('FireRating',$,IFCLABEL('REI120'),$);
+#41= IFCPROPERTYSINGLEVALUE('IsExternal',$,IFCBOOLEAN(.T.),$);
+#42= IFCPROPERTYSET('p1',$,'Pset_WallCommon',$,(#40,#41));
+#43= IFCRELDEFINESBYPROPERTIES('r3',$,$,$,(#21),#42);
+#44= IFCPROPERTYSINGLEVALUE('FireRating',$,IFCLABEL('REI60'),$);
+#45= IFCPROPERTYSET('p2',$,'Pset_SlabCommon',$,(#44));
+#46= IFCRELDEFINESBYPROPERTIES('r4',$,$,$,(#22),#45);
+#47= IFCQUANTITYLENGTH('Depth',$,$,200.);
+#48= IFCQUANTITYAREA('GrossArea',$,$,12.5);
+#49= IFCELEMENTQUANTITY('q1',$,'Qto_SlabBaseQuantities',$,$,(#47,#48));
+#50= IFCRELDEFINESBYPROPERTIES('r5',$,$,$,(#22),#49);
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+    fn model() -> IfcQuery {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(MODEL.as_bytes()).unwrap();
+        temp_file.flush().unwrap();
+        IfcQuery::open(temp_file.path()).unwrap()
+    }
+
+    fn ids(ids: &[u64]) -> Vec<ProductId> {
+        ids.iter().copied().map(ProductId::new).collect()
+    }
+
+    #[test]
+    fn test_elements_of_type() {
+        let query = model();
+        assert_eq!(query.elements_of_type("IfcWall"), ids(&[20, 21]));
+        assert_eq!(query.elements_of_type("IFCSLAB"), ids(&[22]));
+        assert!(query.elements_of_type("IfcDoor").is_empty());
+        assert_eq!(query.type_of(ProductId::new(22)), Some("IFCSLAB"));
+        assert_eq!(query.elements(), ids(&[20, 21, 22]));
+    }
+
+    #[test]
+    fn test_elements_in_storey() {
+        let query = model();
+        assert_eq!(query.storeys(), vec!["Level 1", "Level 2"]);
+        assert_eq!(query.elements_in_storey("Level 1"), ids(&[20, 22]));
+        assert_eq!(query.elements_in_storey("Level 2"), ids(&[21]));
+        assert!(query.elements_in_storey("Roof").is_empty());
+        assert_eq!(query.storey_elevation("Level 2"), Some(3000.0));
+        assert_eq!(query.storey_elevation("Roof"), None);
+    }
+
+    #[test]
+    fn test_stair_parts_share_its_storey() {
+        let model = "ISO-10303-21;
+HEADER;
+FILE_SCHEMA(('IFC4'));
+ENDSEC;
+DATA;
+#10= IFCSTAIR('g10',$,'Stair',$,$,$,$,$,$);
+#11= IFCSTAIRFLIGHT('g11',$,'Flight',$,$,$,$,$,$,$,$,$,$);
+#12= IFCRAILING('g12',$,'Railing',$,$,$,$,$,$);
+#20= IFCRELAGGREGATES('g20',$,$,$,#10,(#11,#12));
+#30= IFCBUILDINGSTOREY('st1',$,'Level 1',$,$,$,$,$,.ELEMENT.,0.);
+#31= IFCRELCONTAINEDINSPATIALSTRUCTURE('r1',$,$,$,(#10),#30);
+ENDSEC;
+END-ISO-10303-21;
+";
+        let query = IfcQuery::from_reader(model.as_bytes()).unwrap();
+        assert_eq!(query.elements_in_storey("Level 1"), ids(&[10, 11, 12]));
+        assert_eq!(query.storey_of(ProductId::new(12)), Some("Level 1"));
+    }
+
+    #[test]
+    fn test_elements_with_property() {
+        let query = model();
+        assert_eq!(
+            query.elements_with_property("FireRating", "REI120"),
+            ids(&[21])
+        );
+        assert_eq!(
+            query.elements_with_property("FireRating", "REI60"),
+            ids(&[22])
+        );
+        assert_eq!(query.elements_with_property("IsExternal", "T"), ids(&[21]));
+        assert!(query
+            .elements_with_property("FireRating", "REI30")
+            .is_empty());
+        assert_eq!(
+            query.property(ProductId::new(21), "FireRating"),
+            Some("REI120")
+        );
+        assert_eq!(query.property(ProductId::new(20), "FireRating"), None);
+        assert_eq!(query.properties(ProductId::new(21)).len(), 2);
+        assert!(query.properties(ProductId::new(20)).is_empty());
+    }
+
+    #[test]
+    fn test_quantities_and_qualified_properties() {
+        let query = model();
+        assert_eq!(query.property(ProductId::new(22), "Depth"), Some("200"));
+        assert_eq!(
+            query.property(ProductId::new(22), "GrossArea"),
+            Some("12.5")
+        );
+        let mut qualified = query.qualified_properties(ProductId::new(22)).to_vec();
+        qualified.sort();
+        assert_eq!(
+            qualified,
+            [
+                (
+                    "Pset_SlabCommon.FireRating".to_string(),
+                    "REI60".to_string()
+                ),
+                (
+                    "Qto_SlabBaseQuantities.Depth".to_string(),
+                    "200".to_string()
+                ),
+                (
+                    "Qto_SlabBaseQuantities.GrossArea".to_string(),
+                    "12.5".to_string()
+                ),
+            ]
+        );
+        assert!(query.qualified_properties(ProductId::new(20)).is_empty());
+    }
+
+    #[test]
+    fn test_element_by_guid() {
+        let query = model();
+        assert_eq!(query.element_by_guid("w2"), Some(ProductId::new(21)));
+        assert_eq!(query.element_by_guid("st1"), None);
+        assert_eq!(query.global_id(ProductId::new(22)).as_deref(), Some("s1"));
+        assert_eq!(query.name(ProductId::new(20)).as_deref(), Some("Wall A"));
+        assert_eq!(query.storey_of(ProductId::new(21)), Some("Level 2"));
+        assert_eq!(query.storey_of(ProductId::new(999)), None);
+    }
+
+    #[test]
+    fn test_meshes_for_query_result() {
+        let query = model();
+        let walls = query.elements_of_type("IfcWall");
+        let meshes = query.meshes(&walls);
+        assert_eq!(meshes.len(), 2);
+        assert_eq!(meshes[0].name, "Wall A_20");
+        assert_eq!(meshes[0].faces.len(), 1);
+
+        // Non-product ids resolve to nothing
+        assert!(query.meshes(&ids(&[30, 999])).is_empty());
+    }
+}
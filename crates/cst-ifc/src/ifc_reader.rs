@@ -1,60 +1,165 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
+use std::sync::OnceLock;
 use cst_math::{DVec3, DVec4, DMat4};
-use cst_core::Result;
+use cst_math::transform::has_mirror;
+use cst_mesh::TriangleMesh;
+use cst_core::{
+    CstError, DiagnosticCode, Diagnostics, EntityLink, ProductId, RepresentationId, Result, Severity, StepId,
+    StyleId,
+};
+use log::{debug, error, info, trace, warn};
+use crate::ifc_arena::{EntityArena, RawArgs, TypeName};
+use crate::ifc_assembly::ASSEMBLY_TYPES;
+use crate::ifc_curve::CURVE_TYPES;
+use crate::ifc_geometry::{resolve_swept_solid, SweepTessellation, SWEPT_SOLID_TYPES};
+use crate::ifc_openings::{OpeningFills, OPENING_TYPES};
+use crate::ifc_options::IfcPipelineOptions;
+use crate::ifc_progress::{check_cancelled, NoProgress, ProgressSink, ProgressStage};
+use crate::ifc_query::storey_containment;
+use crate::ifc_spatial::SPATIAL_TYPES;
+use crate::ifc_surfaces::{detect_surfaces, SurfacePatch};
+use crate::ifc_units::{model_units, UNIT_TYPES};
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 
 /// A lightweight parsed IFC entity from streaming reader
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IfcRawEntity {
-    pub entity_id: u64,
-    pub type_name: String,
-    pub raw_args: String,  // raw argument text between outer parens
+    pub entity_id: StepId,
+    pub type_name: TypeName,
+    pub raw_args: RawArgs,  // raw argument text between outer parens
 }
 
 /// Face data extracted from IFC: outer boundary + optional hole boundaries
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct IfcFaceData {
     pub outer: Vec<DVec3>,
     pub holes: Vec<Vec<DVec3>>,
 }
 
 /// Geometry data extracted from IFC file
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct IfcMeshData {
     pub name: String,
     pub faces: Vec<IfcFaceData>,  // each face has outer boundary + optional holes
     pub placement: Option<[f64; 12]>,  // 3x4 transform matrix (row major), or None
     pub color: Option<[f32; 3]>,  // RGB color from IFC style chain, if found
+    /// Analytic surfaces the faces lie on, filled in when
+    /// [`IfcPipelineOptions::detect_surfaces`] is set
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub surfaces: Vec<SurfacePatch>,
+    /// The shared geometry the faces are a placed copy of, for meshes
+    /// from an IFCMAPPEDITEM
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<IfcInstance>,
 }
 
+/// A placement of a brep or extruded solid from an IFCREPRESENTATIONMAP.
+/// Meshes with the same map and item share their geometry up to their
+/// transforms.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct IfcInstance {
+    pub representation_map: StepId,
+    /// The brep or solid within the map's representation
+    pub item: StepId,
+    /// Column-major 4x4 matrix taking the map's coordinates to the mesh's,
+    /// rotation, scale and mirroring included
+    pub transform: [f64; 16],
+}
+
+impl IfcInstance {
+    pub fn matrix(&self) -> DMat4 {
+        DMat4::from_cols_array(&self.transform)
+    }
+
+    /// The transform in the single precision instanced renderers take.
+    pub fn matrix_f32(&self) -> [f32; 16] {
+        self.transform.map(|v| v as f32)
+    }
+
+    /// Move `mesh`, tessellated from this instance's faces, back into the
+    /// coordinates of the representation map, undoing the winding fix for
+    /// mirrored placements. Returns `false` for a singular transform.
+    pub fn to_map_coordinates(&self, mesh: &mut TriangleMesh) -> bool {
+        let matrix = self.matrix();
+        if matrix.determinant().abs() < 1e-15 {
+            return false;
+        }
+        mesh.transform(&matrix.inverse());
+        true
+    }
+}
+
+/// Meshes placed from one representation map item, see [`instance_groups`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct IfcInstanceGroup {
+    pub representation_map: StepId,
+    pub item: StepId,
+    /// Indices of the meshes, ascending
+    pub members: Vec<usize>,
+}
+
+/// Group meshes by the representation map item they were placed from,
+/// given the [`IfcMeshData::instance`] of each mesh. Only items placed
+/// more than once form a group; groups come in map and item order.
+pub fn instance_groups(instances: &[Option<IfcInstance>]) -> Vec<IfcInstanceGroup> {
+    let mut groups: BTreeMap<(StepId, StepId), Vec<usize>> = BTreeMap::new();
+    for (index, instance) in instances.iter().enumerate() {
+        if let Some(instance) = instance {
+            groups
+                .entry((instance.representation_map, instance.item))
+                .or_default()
+                .push(index);
+        }
+    }
+    groups
+        .into_iter()
+        .filter(|(_, members)| members.len() > 1)
+        .map(|((representation_map, item), members)| IfcInstanceGroup {
+            representation_map,
+            item,
+            members,
+        })
+        .collect()
+}
+
+/// A representation item that could not be resolved was left out.
+pub const UNRESOLVED_GEOMETRY: DiagnosticCode =
+    DiagnosticCode::new("unresolved-geometry", "geometry items skipped as unresolved");
+
 /// Product types that carry geometry in IFC models
-const PRODUCT_TYPES: &[&str] = &[
+pub(crate) const PRODUCT_TYPES: &[&str] = &[
     "IFCBEAM", "IFCCOLUMN", "IFCSLAB", "IFCWALL", "IFCWALLSTANDARDCASE",
     "IFCPLATE", "IFCMEMBER", "IFCREINFORCINGBAR", "IFCFOOTING",
     "IFCBUILDINGELEMENTPROXY", "IFCROOF", "IFCSTAIR", "IFCSTAIRFLIGHT",
     "IFCRAILING", "IFCRAMP", "IFCRAMPFLIGHT", "IFCDOOR", "IFCWINDOW",
     "IFCCOVERING", "IFCCURTAINWALL", "IFCPILE", "IFCTENDON",
-    "IFCREINFORCINGMESH",
+    "IFCREINFORCINGMESH", "IFCFLOWSEGMENT", "IFCFLOWFITTING", "IFCFLOWTERMINAL",
+    "IFCPIPESEGMENT", "IFCPIPEFITTING", "IFCDUCTSEGMENT", "IFCDUCTFITTING",
+    "IFCCABLECARRIERSEGMENT", "IFCCABLECARRIERFITTING",
 ];
 
-/// Build a map from brep entity id -> [r, g, b] color by resolving the IFC style chain:
+/// Build a map from representation item id (usually a brep) -> [r, g, b]
+/// color by resolving the IFC style chain:
 ///   IFCSTYLEDITEM(brep_ref, (style_assignment), ...) ->
 ///   IFCPRESENTATIONSTYLEASSIGNMENT((surface_style, ...)) ->
 ///   IFCSURFACESTYLE(name, side, (rendering, ...)) ->
 ///   IFCSURFACESTYLERENDERING(colour_ref, ...) ->
 ///   IFCCOLOURRGB(name, r, g, b)
-fn build_brep_color_map(entities: &HashMap<u64, IfcRawEntity>) -> HashMap<u64, [f32; 3]> {
+pub(crate) fn build_brep_color_map(entities: &HashMap<StepId, IfcRawEntity>) -> HashMap<StepId, [f32; 3]> {
     let mut color_map = HashMap::new();
 
-    // Find all IFCSTYLEDITEM entities
-    for (_, entity) in entities.iter() {
-        if entity.type_name != "IFCSTYLEDITEM" {
-            continue;
-        }
-
+    // Find all IFCSTYLEDITEM entities, in id order so that the last styled
+    // item wins the same way on every run
+    let mut styled_items: Vec<&IfcRawEntity> = entities
+        .values()
+        .filter(|e| e.type_name == "IFCSTYLEDITEM")
+        .collect();
+    styled_items.sort_by_key(|e| e.entity_id);
+    for entity in styled_items {
         // IFCSTYLEDITEM(Item, Styles, Name)
         // Item = reference to a representation item (e.g., IFCFACETEDBREP)
         // Styles = set of style assignments
@@ -72,7 +177,7 @@ fn build_brep_color_map(entities: &HashMap<u64, IfcRawEntity>) -> HashMap<u64, [
         let style_refs = parse_entity_refs(&args[1]);
 
         for style_assign_id in style_refs {
-            if let Some(color) = resolve_style_assignment_to_color(style_assign_id, entities) {
+            if let Some(color) = resolve_style_assignment_to_color(StyleId(style_assign_id), entities) {
                 color_map.insert(item_id, color);
                 break;
             }
@@ -84,10 +189,10 @@ fn build_brep_color_map(entities: &HashMap<u64, IfcRawEntity>) -> HashMap<u64, [
 
 /// Resolve an IFCPRESENTATIONSTYLEASSIGNMENT to an RGB color.
 fn resolve_style_assignment_to_color(
-    assign_id: u64,
-    entities: &HashMap<u64, IfcRawEntity>,
+    assign_id: StyleId,
+    entities: &HashMap<StepId, IfcRawEntity>,
 ) -> Option<[f32; 3]> {
-    let assign = entities.get(&assign_id)?;
+    let assign = entities.get(&assign_id.step())?;
     if assign.type_name != "IFCPRESENTATIONSTYLEASSIGNMENT" {
         return None;
     }
@@ -100,7 +205,7 @@ fn resolve_style_assignment_to_color(
     let style_refs = parse_entity_refs(&assign_args[0]);
 
     for style_id in style_refs {
-        if let Some(color) = resolve_surface_style_to_color(style_id, entities) {
+        if let Some(color) = resolve_surface_style_to_color(StyleId(style_id), entities) {
             return Some(color);
         }
     }
@@ -109,10 +214,10 @@ fn resolve_style_assignment_to_color(
 
 /// Resolve an IFCSURFACESTYLE to an RGB color.
 fn resolve_surface_style_to_color(
-    style_id: u64,
-    entities: &HashMap<u64, IfcRawEntity>,
+    style_id: StyleId,
+    entities: &HashMap<StepId, IfcRawEntity>,
 ) -> Option<[f32; 3]> {
-    let style = entities.get(&style_id)?;
+    let style = entities.get(&style_id.step())?;
     if style.type_name != "IFCSURFACESTYLE" {
         return None;
     }
@@ -126,7 +231,7 @@ fn resolve_surface_style_to_color(
     let rendering_refs = parse_entity_refs(&style_args[2]);
 
     for rendering_id in rendering_refs {
-        if let Some(color) = resolve_rendering_to_color(rendering_id, entities) {
+        if let Some(color) = resolve_rendering_to_color(StyleId(rendering_id), entities) {
             return Some(color);
         }
     }
@@ -135,10 +240,10 @@ fn resolve_surface_style_to_color(
 
 /// Resolve an IFCSURFACESTYLERENDERING to an RGB color.
 fn resolve_rendering_to_color(
-    rendering_id: u64,
-    entities: &HashMap<u64, IfcRawEntity>,
+    rendering_id: StyleId,
+    entities: &HashMap<StepId, IfcRawEntity>,
 ) -> Option<[f32; 3]> {
-    let rendering = entities.get(&rendering_id)?;
+    let rendering = entities.get(&rendering_id.step())?;
     if rendering.type_name != "IFCSURFACESTYLERENDERING" {
         return None;
     }
@@ -156,8 +261,8 @@ fn resolve_rendering_to_color(
 
 /// Resolve an IFCCOLOURRGB to [r, g, b].
 fn resolve_colour_rgb(
-    colour_id: u64,
-    entities: &HashMap<u64, IfcRawEntity>,
+    colour_id: StepId,
+    entities: &HashMap<StepId, IfcRawEntity>,
 ) -> Option<[f32; 3]> {
     let colour = entities.get(&colour_id)?;
     if colour.type_name != "IFCCOLOURRGB" {
@@ -180,260 +285,530 @@ fn resolve_colour_rgb(
 /// Resolves product placement chains and IFCMAPPEDITEM instances so that
 /// geometry is placed at world coordinates rather than all at origin.
 pub fn read_ifc_file(path: &Path) -> Result<Vec<IfcMeshData>> {
-    use std::time::Instant;
-    let t_start = Instant::now();
+    read_ifc_file_with_options(path, &IfcPipelineOptions::default())
+}
+
+/// Like [`read_ifc_file`], keeping only products that pass the type and
+/// storey filters and applying the unit override to all coordinates.
+pub fn read_ifc_file_with_options(path: &Path, options: &IfcPipelineOptions) -> Result<Vec<IfcMeshData>> {
+    read_ifc_file_with_progress(path, options, &NoProgress)
+}
+
+/// Like [`read_ifc_file_with_options`], reporting bytes parsed and products
+/// resolved to `progress`. Geometry that had to be skipped is summarized
+/// in the log.
+pub fn read_ifc_file_with_progress(
+    path: &Path,
+    options: &IfcPipelineOptions,
+    progress: &dyn ProgressSink,
+) -> Result<Vec<IfcMeshData>> {
+    let (meshes, diagnostics) = read_ifc_file_with_diagnostics(path, options, progress)?;
+    log_diagnostics(&diagnostics);
+    Ok(meshes)
+}
+
+/// Like [`read_ifc_file_with_progress`], returning what had to be skipped
+/// instead of logging it.
+pub fn read_ifc_file_with_diagnostics(
+    path: &Path,
+    options: &IfcPipelineOptions,
+    progress: &dyn ProgressSink,
+) -> Result<(Vec<IfcMeshData>, Diagnostics)> {
+    let t_start = Stopwatch::now();
 
     // Phase 1: Stream through file, collect entities into HashMap by id
-    let entities = parse_ifc_entities(path)?;
-    let t_parse = t_start.elapsed();
-    eprintln!("[PERF] Phase 1 - Parse entities: {:.2}s ({} entities)", t_parse.as_secs_f64(), entities.len());
+    let entities = parse_ifc_entities_with_progress(path, progress)?;
+    debug!("Phase 1 - Parse entities: {:.2}s ({} entities)", t_start.elapsed().as_secs_f64(), entities.len());
+
+    let mut diagnostics = Diagnostics::new();
+    let meshes = resolve_meshes(&entities, options, progress, &mut diagnostics)?
+        .into_iter()
+        .map(|(_, mesh)| mesh)
+        .collect();
+    Ok((meshes, diagnostics))
+}
+
+/// Like [`read_ifc_file_with_options`], reading IFC text from `reader`.
+/// A byte slice is a reader, so in-memory files need no copy; this is the
+/// entry point for targets without a file system such as
+/// `wasm32-unknown-unknown`.
+pub fn read_ifc<R: BufRead>(reader: R, options: &IfcPipelineOptions) -> Result<Vec<IfcMeshData>> {
+    let entities = parse_ifc_entities_from_reader(reader, 0, &NoProgress)?;
+    let mut diagnostics = Diagnostics::new();
+    let meshes = resolve_meshes(&entities, options, &NoProgress, &mut diagnostics)?
+        .into_iter()
+        .map(|(_, mesh)| mesh)
+        .collect();
+    log_diagnostics(&diagnostics);
+    Ok(meshes)
+}
+
+/// Log one line per kind of diagnostic at its severity, e.g. "3 faces
+/// skipped as degenerate", and each diagnostic at debug level.
+pub(crate) fn log_diagnostics(diagnostics: &Diagnostics) {
+    for diagnostic in diagnostics {
+        debug!("{}", diagnostic);
+    }
+    for (severity, line) in diagnostics.summary() {
+        match severity {
+            Severity::Error => error!("{}", line),
+            Severity::Warning => warn!("{}", line),
+            Severity::Info => info!("{}", line),
+        }
+    }
+}
+
+/// Phase timer for the debug log. `Instant` panics on
+/// `wasm32-unknown-unknown`, so timings read as zero there.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+type Stopwatch = std::time::Instant;
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+#[derive(Clone, Copy)]
+struct Stopwatch;
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+impl Stopwatch {
+    fn now() -> Self {
+        Stopwatch
+    }
+
+    fn elapsed(&self) -> std::time::Duration {
+        std::time::Duration::ZERO
+    }
+}
+
+/// Phases 1b-3 of [`read_ifc_file_with_progress`] on parsed entities. Each
+/// mesh comes with the id of its product, or `None` for meshes from the
+/// brep-only fallback. Skipped geometry goes to `diagnostics`.
+pub(crate) fn resolve_meshes(
+    entities: &HashMap<StepId, IfcRawEntity>,
+    options: &IfcPipelineOptions,
+    progress: &dyn ProgressSink,
+    diagnostics: &mut Diagnostics,
+) -> Result<Vec<(Option<ProductId>, IfcMeshData)>> {
+    let t_start = Stopwatch::now();
 
     // Phase 1b: Build brep -> color lookup from style chain
-    let brep_color_map = build_brep_color_map(&entities);
+    let brep_color_map = build_brep_color_map(entities);
     let t_color = t_start.elapsed();
-    eprintln!("[PERF] Phase 1b - Color map: {:.2}s ({:.2}s total, {} entries)",
-        (t_color - t_parse).as_secs_f64(), t_color.as_secs_f64(), brep_color_map.len());
+    debug!("Phase 1b - Color map: {:.2}s ({} entries)", t_color.as_secs_f64(), brep_color_map.len());
+    let units = model_units(entities);
+    let opening_fills = OpeningFills::new(entities, units.length);
+    let tessellation = SweepTessellation::new(&units, options);
 
     // Phase 2: Find all product elements
-    let products: Vec<(u64, &IfcRawEntity)> = entities.iter()
+    let storey_members: Option<HashSet<ProductId>> = options.storey.as_ref().map(|name| {
+        storey_containment(entities).remove(name).unwrap_or_default().into_iter().collect()
+    });
+    let mut products: Vec<(ProductId, &IfcRawEntity)> = entities.iter()
         .filter(|(_, e)| PRODUCT_TYPES.contains(&e.type_name.as_str()))
-        .map(|(id, e)| (*id, e))
+        .filter(|(_, e)| options.accepts_type(&e.type_name))
+        .map(|(id, e)| (ProductId(*id), e))
+        .filter(|(id, _)| storey_members.as_ref().map_or(true, |members| members.contains(id)))
         .collect();
+    // Meshes come out in product id order, not HashMap order, so exports
+    // are identical between runs
+    products.sort_unstable_by_key(|(id, _)| *id);
     let t_products = t_start.elapsed();
-    eprintln!("[PERF] Phase 2 - Find products: {:.2}s ({:.2}s total, {} products)",
+    debug!("Phase 2 - Find products: {:.2}s ({:.2}s total, {} products)",
         (t_products - t_color).as_secs_f64(), t_products.as_secs_f64(), products.len());
 
     // Phase 3: Resolve each product to positioned mesh data (parallel with rayon)
-    let results: Vec<IfcMeshData> = products.par_iter()
-        .flat_map_iter(|(product_id, product)| {
-            resolve_product(*product_id, product, &entities, &brep_color_map)
+    progress.start(ProgressStage::Resolve, products.len() as u64);
+    let resolved: Vec<(ProductId, Vec<IfcMeshData>, Diagnostics)> = products.par_iter()
+        .map(|(product_id, product)| {
+            let mut product_diagnostics = Diagnostics::new();
+            // Once cancelled, drain the remaining products without work
+            let mut meshes = if progress.is_cancelled() {
+                Vec::new()
+            } else {
+                resolve_product(
+                    *product_id, product, entities, &brep_color_map, &opening_fills, &tessellation,
+                    &mut product_diagnostics,
+                )
+            };
+            // Unstyled meshes take the color of their product type
+            if let Some(color) = options.type_color(&product.type_name) {
+                for mesh in meshes.iter_mut().filter(|mesh| mesh.color.is_none()) {
+                    mesh.color = Some(color);
+                }
+            }
+            progress.advance(ProgressStage::Resolve, 1);
+            (*product_id, meshes, product_diagnostics)
         })
         .collect();
+    progress.finish(ProgressStage::Resolve);
+    check_cancelled(progress)?;
+    // Merged in product order, like the meshes
+    let mut results: Vec<(Option<ProductId>, IfcMeshData)> = Vec::new();
+    for (product_id, meshes, mut product_diagnostics) in resolved {
+        results.extend(meshes.into_iter().map(|mesh| (Some(product_id), mesh)));
+        diagnostics.append(&mut product_diagnostics);
+    }
 
     // Fallback: if no products found, use legacy brep-only approach
-    let results = if results.is_empty() {
-        eprintln!("No products found, falling back to direct brep extraction");
-        let brep_ids: Vec<u64> = entities.iter()
+    // (unless a filter asked for specific products)
+    let mut results = if results.is_empty() && !options.filters_elements() {
+        info!("No products found, falling back to direct brep extraction");
+        let mut brep_ids: Vec<StepId> = entities.iter()
             .filter(|(_, entity)| entity.type_name == "IFCFACETEDBREP")
             .map(|(id, _)| *id)
             .collect();
-        brep_ids.par_iter()
-            .filter_map(|&brep_id| {
-                let mut mesh = resolve_faceted_brep(brep_id, &entities)?;
-                mesh.color = brep_color_map.get(&brep_id).copied();
-                Some(mesh)
+        brep_ids.sort_unstable();
+        let resolved: Vec<(StepId, Option<Result<IfcMeshData>>)> = brep_ids.par_iter()
+            .map(|&brep_id| {
+                if progress.is_cancelled() {
+                    return (brep_id, None);
+                }
+                (brep_id, Some(resolve_faceted_brep(brep_id, entities)))
             })
-            .collect()
+            .collect();
+        check_cancelled(progress)?;
+        let mut meshes = Vec::new();
+        for (brep_id, result) in resolved {
+            match result {
+                Some(Ok(mut mesh)) => {
+                    mesh.color = brep_color_map.get(&brep_id).copied();
+                    meshes.push((None, mesh));
+                }
+                Some(Err(e)) => diagnostics.warn(UNRESOLVED_GEOMETRY, Some(brep_id), format!("Skipping brep: {}", e)),
+                None => {}
+            }
+        }
+        meshes
     } else {
         results
     };
 
     let t_resolve = t_start.elapsed();
-    eprintln!("[PERF] Phase 3 - Resolve meshes: {:.2}s ({:.2}s total, {} meshes)",
+    debug!("Phase 3 - Resolve meshes: {:.2}s ({:.2}s total, {} meshes)",
         (t_resolve - t_products).as_secs_f64(), t_resolve.as_secs_f64(), results.len());
+
+    if let Some(scale) = options.unit_scale {
+        let transform = DMat4::from_scale(DVec3::splat(scale));
+        for (_, mesh) in &mut results {
+            apply_transform_to_faces(&mut mesh.faces, &transform);
+            if let Some(instance) = &mut mesh.instance {
+                instance.transform = (transform * instance.matrix()).to_cols_array();
+            }
+        }
+    }
+    if options.detect_surfaces {
+        let tolerance = options.tessellation_tolerance * options.scale();
+        results.par_iter_mut().for_each(|(_, mesh)| {
+            mesh.surfaces = detect_surfaces(&mesh.faces, tolerance);
+        });
+    }
     Ok(results)
 }
 
 /// Resolve a single product element into its mesh data (may produce 0 or more meshes).
-/// This is the per-product work unit for parallel execution.
-fn resolve_product(
-    product_id: u64,
+/// This is the per-product work unit for parallel execution. Doors and
+/// windows without resolvable geometry get a box filling their opening.
+pub(crate) fn resolve_product(
+    product_id: ProductId,
+    product: &IfcRawEntity,
+    entities: &HashMap<StepId, IfcRawEntity>,
+    brep_color_map: &HashMap<StepId, [f32; 3]>,
+    opening_fills: &OpeningFills,
+    tessellation: &SweepTessellation,
+    diagnostics: &mut Diagnostics,
+) -> Vec<IfcMeshData> {
+    let meshes = resolve_product_shape(product_id, product, entities, brep_color_map, tessellation, diagnostics);
+    if !meshes.is_empty() {
+        return meshes;
+    }
+    match opening_fills.fallback_faces(product, entities) {
+        Some(faces) => vec![IfcMeshData {
+            name: format!("{}_{}", product_name(product_id, product), product_id.value()),
+            faces,
+            placement: None,
+            color: None,
+            surfaces: Vec::new(),
+            instance: None,
+        }],
+        None => Vec::new(),
+    }
+}
+
+/// The product's Name, or `<TYPE>_<id>` when it has none.
+pub(crate) fn product_name(product_id: ProductId, product: &IfcRawEntity) -> String {
+    let args = split_ifc_args(&product.raw_args);
+    let name = args.get(2).map_or("$", |a| a.trim().trim_matches('\''));
+    if name == "$" || name.is_empty() {
+        format!("{}_{}", product.type_name, product_id.value())
+    } else {
+        name.to_string()
+    }
+}
+
+/// The meshes of a product's shape representations.
+fn resolve_product_shape(
+    product_id: ProductId,
     product: &IfcRawEntity,
-    entities: &HashMap<u64, IfcRawEntity>,
-    brep_color_map: &HashMap<u64, [f32; 3]>,
+    entities: &HashMap<StepId, IfcRawEntity>,
+    brep_color_map: &HashMap<StepId, [f32; 3]>,
+    tessellation: &SweepTessellation,
+    diagnostics: &mut Diagnostics,
 ) -> Vec<IfcMeshData> {
+    let name = product_name(product_id, product);
+    let mut results = Vec::new();
+
+    for placed in product_items(product, entities) {
+        let resolved = match entities.get(&placed.item_id) {
+            Some(item) if item.type_name == "IFCEXTRUDEDAREASOLID" => {
+                resolve_swept_solid(placed.item_id, entities, tessellation)
+            }
+            _ => resolve_faceted_brep(placed.item_id, entities),
+        };
+        match resolved {
+            Ok(mut mesh) => {
+                mesh.name = format!("{}_{}", name, product_id.value());
+                mesh.color = brep_color_map.get(&placed.item_id).copied();
+                apply_transform_to_faces(&mut mesh.faces, &placed.transform);
+                mesh.instance = placed.instance;
+                results.push(mesh);
+            }
+            Err(e) => {
+                let message = match placed.instance {
+                    Some(_) => format!("Skipping mapped geometry of {}: {}", product_id, e),
+                    None => format!("Skipping geometry of {} {}: {}", product.type_name, product_id, e),
+                };
+                diagnostics.warn(UNRESOLVED_GEOMETRY, Some(product_id.step()), message);
+            }
+        }
+    }
+
+    results
+}
+
+/// A faceted brep or extruded solid placed by a product's shape
+/// representation.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PlacedItem {
+    pub item_id: StepId,
+    /// From the item's coordinates to world coordinates
+    pub transform: DMat4,
+    /// Set for items placed through an IFCMAPPEDITEM
+    pub instance: Option<IfcInstance>,
+}
+
+/// Representation item types the reader turns into meshes.
+const MESHED_ITEM_TYPES: [&str; 2] = ["IFCFACETEDBREP", "IFCEXTRUDEDAREASOLID"];
+
+/// The faceted breps and extruded solids of a product's shape
+/// representations, directly or through IFCMAPPEDITEM, in representation
+/// and item order.
+pub(crate) fn product_items(
+    product: &IfcRawEntity,
+    entities: &HashMap<StepId, IfcRawEntity>,
+) -> Vec<PlacedItem> {
     let args = split_ifc_args(&product.raw_args);
     // Product args layout (IFC2x3/IFC4):
     // 0=GlobalId, 1=OwnerHistory, 2=Name, 3=Description, 4=ObjectType,
     // 5=ObjectPlacement, 6=Representation, 7=Tag, [8..]=type-specific
     if args.len() < 7 { return Vec::new(); }
 
-    let name = args[2].trim().trim_matches('\'').to_string();
-    let name = if name == "$" || name.is_empty() {
-        format!("{}_{}", product.type_name, product_id)
-    } else {
-        name
-    };
-
-    let placement_id = extract_single_ref(&args[5]);
     let representation_id = match extract_single_ref(&args[6]) {
-        Some(id) => id,
+        Some(id) => RepresentationId(id),
         None => return Vec::new(),
     };
 
     // Resolve world transform from IFCLOCALPLACEMENT chain
-    let world_transform = placement_id
+    let world_transform = extract_single_ref(&args[5])
         .map(|pid| resolve_placement_chain(pid, entities))
         .unwrap_or(DMat4::IDENTITY);
 
-    let prod_def = match entities.get(&representation_id) {
+    let prod_def = match entities.get(&representation_id.step()) {
         Some(e) => e,
         None => return Vec::new(),
     };
 
     // IFCPRODUCTDEFINITIONSHAPE($,$,(#rep1,#rep2,...))
     let pd_args = split_ifc_args(&prod_def.raw_args);
-    let shape_rep_arg = if pd_args.len() >= 3 { &pd_args[2] } else { &prod_def.raw_args };
-    let shape_rep_refs = parse_entity_refs(shape_rep_arg);
-
-    let mut results = Vec::new();
-
-    for shape_rep_id in shape_rep_refs {
-        let shape_rep = match entities.get(&shape_rep_id) {
-            Some(e) if e.type_name == "IFCSHAPEREPRESENTATION" => e,
-            _ => continue,
-        };
-
-        let sr_args = split_ifc_args(&shape_rep.raw_args);
-        if sr_args.len() < 4 { continue; }
-        let item_refs = parse_entity_refs(&sr_args[3]);
-
-        for item_id in item_refs {
-            let item = match entities.get(&item_id) {
-                Some(e) => e,
-                None => continue,
-            };
-
-            match item.type_name.as_str() {
-                "IFCFACETEDBREP" => {
-                    if let Some(mut mesh) = resolve_faceted_brep(item_id, entities) {
-                        mesh.name = format!("{}_{}", name, product_id);
-                        mesh.color = brep_color_map.get(&item_id).copied();
-                        apply_transform_to_faces(&mut mesh.faces, &world_transform);
-                        results.push(mesh);
-                    }
-                }
-                "IFCMAPPEDITEM" => {
-                    let mut mapped = resolve_mapped_item(
-                        item, &name, product_id,
-                        &world_transform, entities, brep_color_map,
-                    );
-                    results.append(&mut mapped);
-                }
-                _ => {}
-            }
+    let shape_rep_arg = if pd_args.len() >= 3 { pd_args[2].as_str() } else { &prod_def.raw_args };
+
+    let mut placed = Vec::new();
+    for item in representation_items(shape_rep_arg, entities) {
+        match item.type_name.as_str() {
+            "IFCMAPPEDITEM" => placed.extend(mapped_items(item, &world_transform, entities)),
+            type_name if MESHED_ITEM_TYPES.contains(&type_name) => placed.push(PlacedItem {
+                item_id: item.entity_id,
+                transform: world_transform,
+                instance: None,
+            }),
+            _ => {}
         }
     }
-
-    results
+    placed
 }
 
-/// Resolve an IFCMAPPEDITEM into one or more meshes.
-fn resolve_mapped_item(
+/// The faceted breps and extruded solids an IFCMAPPEDITEM places.
+///
+/// The mapped representation is given relative to the map's MappingOrigin,
+/// which the item's MappingTarget operator then places within the product.
+fn mapped_items(
     item: &IfcRawEntity,
-    name: &str,
-    product_id: u64,
     world_transform: &DMat4,
-    entities: &HashMap<u64, IfcRawEntity>,
-    brep_color_map: &HashMap<u64, [f32; 3]>,
-) -> Vec<IfcMeshData> {
-    let mut results = Vec::new();
+    entities: &HashMap<StepId, IfcRawEntity>,
+) -> Vec<PlacedItem> {
+    // IFCMAPPEDITEM(MappingSource, MappingTarget)
     let mi_args = split_ifc_args(&item.raw_args);
-    if mi_args.len() < 2 { return results; }
-    let map_source_id = extract_single_ref(&mi_args[0]);
-    let map_target_id = extract_single_ref(&mi_args[1]);
+    if mi_args.len() < 2 { return Vec::new(); }
+    let Some((map_id, rep_map)) = extract_single_ref(&mi_args[0])
+        .and_then(|id| Some((id, entities.get(&id)?)))
+        .filter(|(_, e)| e.type_name == "IFCREPRESENTATIONMAP")
+    else {
+        return Vec::new();
+    };
 
-    // Resolve mapping target transform (IFCCARTESIANTRANSFORMATIONOPERATOR3D)
-    let mapping_transform = map_target_id
-        .map(|tid| resolve_cartesian_transform_operator(tid, entities))
+    // IFCREPRESENTATIONMAP(MappingOrigin, MappedRepresentation)
+    let rm_args = split_ifc_args(&rep_map.raw_args);
+    if rm_args.len() < 2 { return Vec::new(); }
+    let origin = extract_single_ref(&rm_args[0])
+        .map(|id| resolve_axis2placement3d(id, entities))
         .unwrap_or(DMat4::IDENTITY);
+    // Mapping target operator (IFCCARTESIANTRANSFORMATIONOPERATOR3D)
+    let target = extract_single_ref(&mi_args[1])
+        .map(|id| resolve_cartesian_transform_operator(id, entities))
+        .unwrap_or(DMat4::IDENTITY);
+    let combined = *world_transform * target * origin;
+
+    representation_items(&format!("({})", rm_args[1]), entities)
+        .into_iter()
+        .filter(|e| MESHED_ITEM_TYPES.contains(&e.type_name.as_str()))
+        .map(|mapped| PlacedItem {
+            item_id: mapped.entity_id,
+            transform: combined,
+            instance: Some(IfcInstance {
+                representation_map: map_id,
+                item: mapped.entity_id,
+                transform: combined.to_cols_array(),
+            }),
+        })
+        .collect()
+}
 
-    // Combined transform: world placement * mapping target operator
-    let combined = *world_transform * mapping_transform;
-
-    // Resolve RepresentationMap -> source shape rep -> find breps
-    if let Some(map_id) = map_source_id {
-        if let Some(rep_map) = entities.get(&map_id) {
-            if rep_map.type_name == "IFCREPRESENTATIONMAP" {
-                let rm_args = split_ifc_args(&rep_map.raw_args);
-                // IFCREPRESENTATIONMAP(MappingOrigin, MappedRepresentation)
-                if rm_args.len() >= 2 {
-                    let _origin_id = extract_single_ref(&rm_args[0]);
-                    let mapped_rep_id = extract_single_ref(&rm_args[1]);
-
-                    // Note: MappingOrigin (IFCAXIS2PLACEMENT3D) defines the coordinate
-                    // system of the mapped representation. In most IFC files this is
-                    // identity (origin at 0,0,0 with default axes). The actual instance
-                    // positioning comes from the CartesianTransformationOperator (map_target).
-                    // We skip applying origin_transform since the brep vertices are already
-                    // in the representation map's coordinate system.
-
-                    if let Some(srep_id) = mapped_rep_id {
-                        if let Some(srep) = entities.get(&srep_id) {
-                            if srep.type_name == "IFCSHAPEREPRESENTATION" {
-                                let srep_args = split_ifc_args(&srep.raw_args);
-                                if srep_args.len() >= 4 {
-                                    let brep_refs = parse_entity_refs(&srep_args[3]);
-                                    for brep_id in brep_refs {
-                                        if let Some(e) = entities.get(&brep_id) {
-                                            if e.type_name == "IFCFACETEDBREP" {
-                                                if let Some(mut mesh) = resolve_faceted_brep(brep_id, entities) {
-                                                    mesh.name = format!("{}_{}", name, product_id);
-                                                    mesh.color = brep_color_map.get(&brep_id).copied();
-                                                    apply_transform_to_faces(&mut mesh.faces, &combined);
-                                                    results.push(mesh);
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
-    results
+/// Items of every IFCSHAPEREPRESENTATION referenced in `refs`.
+fn representation_items<'a>(
+    refs: &str,
+    entities: &'a HashMap<StepId, IfcRawEntity>,
+) -> Vec<&'a IfcRawEntity> {
+    parse_entity_refs(refs)
+        .into_iter()
+        .filter_map(|id| entities.get(&id))
+        .filter(|e| e.type_name == "IFCSHAPEREPRESENTATION")
+        .flat_map(|shape_rep| {
+            // IFCSHAPEREPRESENTATION(Context, Identifier, Type, Items)
+            let sr_args = split_ifc_args(&shape_rep.raw_args);
+            sr_args.get(3).map(|a| parse_entity_refs(a)).unwrap_or_default()
+        })
+        .filter_map(|id| entities.get(&id))
+        .collect()
 }
 
 /// Parse IFC file line-by-line and collect geometry-related entities
-fn parse_ifc_entities(path: &Path) -> Result<HashMap<u64, IfcRawEntity>> {
+pub(crate) fn parse_ifc_entities(path: &Path) -> Result<HashMap<StepId, IfcRawEntity>> {
+    parse_ifc_entities_with_progress(path, &NoProgress)
+}
+
+pub(crate) fn parse_ifc_entities_with_progress(
+    path: &Path,
+    progress: &dyn ProgressSink,
+) -> Result<HashMap<StepId, IfcRawEntity>> {
     let file = File::open(path)?;
+    let len = file.metadata()?.len();
     // Use 1MB read buffer instead of default 8KB to reduce syscalls on large files
-    let reader = BufReader::with_capacity(1_048_576, file);
+    parse_ifc_entities_from_reader(BufReader::with_capacity(1_048_576, file), len, progress)
+}
+
+/// Entity types the reader keeps: geometry and the placement, style,
+/// containment, property, unit, curve, swept solid, opening, assembly and
+/// spatial structure entities it follows. Their names are the static table of [`TypeName`].
+pub(crate) fn kept_types() -> &'static HashSet<&'static str> {
+    static TYPES: OnceLock<HashSet<&'static str>> = OnceLock::new();
+    TYPES.get_or_init(|| {
+        [
+            // Points, directions, loops
+            "IFCCARTESIANPOINT", "IFCDIRECTION", "IFCPOLYLOOP",
+            // Face bounds (both outer and regular)
+            "IFCFACEOUTERBOUND", "IFCFACEBOUND",
+            // Face and shell entities
+            "IFCFACE", "IFCCLOSEDSHELL", "IFCOPENSHELL",
+            // Brep
+            "IFCFACETEDBREP",
+            // Representation entities
+            "IFCSHAPEREPRESENTATION", "IFCPRODUCTDEFINITIONSHAPE",
+            // Placement entities
+            "IFCAXIS2PLACEMENT3D", "IFCLOCALPLACEMENT",
+            // MappedItem chain
+            "IFCMAPPEDITEM", "IFCREPRESENTATIONMAP",
+            "IFCCARTESIANTRANSFORMATIONOPERATOR3D",
+            // Style chain for color extraction
+            "IFCSTYLEDITEM", "IFCPRESENTATIONSTYLEASSIGNMENT",
+            "IFCSURFACESTYLE", "IFCSURFACESTYLERENDERING", "IFCCOLOURRGB",
+            // Structural product types
+            "IFCSLAB", "IFCWALL", "IFCWALLSTANDARDCASE", "IFCBEAM", "IFCCOLUMN",
+            "IFCPLATE", "IFCMEMBER",
+            // Additional product types
+            "IFCREINFORCINGBAR", "IFCBUILDINGELEMENTPROXY", "IFCFOOTING", "IFCROOF",
+            "IFCSTAIR", "IFCSTAIRFLIGHT", "IFCRAILING", "IFCRAMP", "IFCRAMPFLIGHT",
+            "IFCDOOR", "IFCWINDOW", "IFCCOVERING", "IFCCURTAINWALL",
+            "IFCPILE", "IFCTENDON", "IFCREINFORCINGMESH",
+            // MEP product types
+            "IFCFLOWSEGMENT", "IFCFLOWFITTING", "IFCFLOWTERMINAL", "IFCPIPESEGMENT",
+            "IFCPIPEFITTING", "IFCDUCTSEGMENT", "IFCDUCTFITTING",
+            "IFCCABLECARRIERSEGMENT", "IFCCABLECARRIERFITTING",
+            // Spatial containment, property and quantity sets (used by IfcQuery)
+            "IFCBUILDINGSTOREY", "IFCRELCONTAINEDINSPATIALSTRUCTURE",
+            "IFCRELDEFINESBYPROPERTIES", "IFCPROPERTYSET", "IFCPROPERTYSINGLEVALUE",
+            "IFCELEMENTQUANTITY", "IFCQUANTITYLENGTH", "IFCQUANTITYAREA",
+            "IFCQUANTITYVOLUME", "IFCQUANTITYCOUNT", "IFCQUANTITYWEIGHT", "IFCQUANTITYTIME",
+        ]
+        .into_iter()
+        .chain(UNIT_TYPES.iter().copied())
+        .chain(CURVE_TYPES.iter().copied())
+        .chain(SWEPT_SOLID_TYPES.iter().copied())
+        .chain(OPENING_TYPES.iter().copied())
+        .chain(ASSEMBLY_TYPES.iter().copied())
+        .chain(SPATIAL_TYPES.iter().copied())
+        .collect()
+    })
+}
 
-    // Pre-allocate for large files (typical IFC: ~3.5M geometry entities)
-    let mut entities = HashMap::with_capacity(4_000_000);
+/// Parse entities from IFC text. `total_bytes` sizes the entity table and
+/// the parse progress; 0 when unknown.
+pub(crate) fn parse_ifc_entities_from_reader<R: BufRead>(
+    reader: R,
+    total_bytes: u64,
+    progress: &dyn ProgressSink,
+) -> Result<HashMap<StepId, IfcRawEntity>> {
+    progress.start(ProgressStage::Parse, total_bytes);
+    // Bytes read since the last progress update
+    let mut pending_bytes = 0u64;
+
+    // Pre-allocate for large files (typical IFC: ~3.5M geometry entities in
+    // ~400 MB); small inputs, e.g. in the browser, stay small
+    let mut entities = HashMap::with_capacity((total_bytes / 100).min(4_000_000) as usize);
+    let mut arena = EntityArena::new();
     let mut line_count = 0usize;
     let mut current_line = String::with_capacity(256);
 
-    // Geometry-related entity types we care about - use HashSet for O(1) lookup
-    let geometry_types: HashSet<&str> = [
-        // Points, directions, loops
-        "IFCCARTESIANPOINT", "IFCDIRECTION", "IFCPOLYLOOP",
-        // Face bounds (both outer and regular)
-        "IFCFACEOUTERBOUND", "IFCFACEBOUND",
-        // Face and shell entities
-        "IFCFACE", "IFCCLOSEDSHELL", "IFCOPENSHELL",
-        // Brep
-        "IFCFACETEDBREP",
-        // Representation entities
-        "IFCSHAPEREPRESENTATION", "IFCPRODUCTDEFINITIONSHAPE",
-        // Placement entities
-        "IFCAXIS2PLACEMENT3D", "IFCLOCALPLACEMENT",
-        // MappedItem chain
-        "IFCMAPPEDITEM", "IFCREPRESENTATIONMAP",
-        "IFCCARTESIANTRANSFORMATIONOPERATOR3D",
-        // Style chain for color extraction
-        "IFCSTYLEDITEM", "IFCPRESENTATIONSTYLEASSIGNMENT",
-        "IFCSURFACESTYLE", "IFCSURFACESTYLERENDERING", "IFCCOLOURRGB",
-        // Structural product types
-        "IFCSLAB", "IFCWALL", "IFCWALLSTANDARDCASE", "IFCBEAM", "IFCCOLUMN",
-        "IFCPLATE", "IFCMEMBER",
-        // Additional product types
-        "IFCREINFORCINGBAR", "IFCBUILDINGELEMENTPROXY", "IFCFOOTING", "IFCROOF",
-        "IFCSTAIR", "IFCSTAIRFLIGHT", "IFCRAILING", "IFCRAMP", "IFCRAMPFLIGHT",
-        "IFCDOOR", "IFCWINDOW", "IFCCOVERING", "IFCCURTAINWALL",
-        "IFCPILE", "IFCTENDON", "IFCREINFORCINGMESH",
-    ].into_iter().collect();
+    let geometry_types = kept_types();
 
     for line in reader.lines() {
         let line = line?;
         line_count += 1;
+        pending_bytes += line.len() as u64 + 1;
 
+        if line_count % 10_000 == 0 {
+            progress.advance(ProgressStage::Parse, pending_bytes);
+            pending_bytes = 0;
+            check_cancelled(progress)?;
+        }
         if line_count % 500_000 == 0 {
-            eprintln!("Parsed {} lines, {} entities...", line_count, entities.len());
+            trace!("Parsed {} lines, {} entities...", line_count, entities.len());
         }
 
         // Skip non-entity lines
@@ -449,16 +824,19 @@ fn parse_ifc_entities(path: &Path) -> Result<HashMap<u64, IfcRawEntity>> {
             continue;
         }
 
-        // Parse entity with early type filtering to avoid allocating raw_args
-        // for non-geometry entities (saves ~1M String allocations on large files)
-        if let Some(entity) = parse_entity_line_filtered(&current_line, &geometry_types) {
-            entities.insert(entity.entity_id, entity);
+        // Parse entity with early type filtering so that non-geometry entities
+        // are never copied; kept arguments go into the arena's shared buffers
+        if let Some((entity_id, type_name, raw_args)) = parse_entity_line_filtered(&current_line, geometry_types) {
+            arena.push(entity_id, type_name, raw_args, &mut entities);
         }
 
         current_line.clear();
     }
 
-    eprintln!("Finished parsing: {} total lines, {} geometry entities", line_count, entities.len());
+    arena.finish(&mut entities);
+    progress.advance(ProgressStage::Parse, pending_bytes);
+    progress.finish(ProgressStage::Parse);
+    debug!("Finished parsing: {} total lines, {} geometry entities", line_count, entities.len());
     Ok(entities)
 }
 
@@ -470,18 +848,18 @@ fn parse_entity_line(line: &str) -> Option<IfcRawEntity> {
     // Extract entity ID
     let id_end = line.find('=')?;
     let id_str = &line[1..id_end].trim();
-    let entity_id = id_str.parse::<u64>().ok()?;
+    let entity_id = StepId(id_str.parse::<u64>().ok()?);
 
     // Extract type name
     let type_start = id_end + 1;
     let type_section = &line[type_start..].trim();
     let paren_pos = type_section.find('(')?;
-    let type_name = type_section[..paren_pos].trim().to_string();
+    let type_name = TypeName::new(type_section[..paren_pos].trim());
 
     // Extract raw args (between outer parens, excluding the parens themselves)
     let args_start = type_section.find('(')?;
     let args_end = type_section.rfind(')')?;
-    let raw_args = type_section[args_start + 1..args_end].to_string();
+    let raw_args = RawArgs::new(&type_section[args_start + 1..args_end]);
 
     Some(IfcRawEntity {
         entity_id,
@@ -491,44 +869,39 @@ fn parse_entity_line(line: &str) -> Option<IfcRawEntity> {
 }
 
 /// Parse entity line with early type filtering.
-/// Extracts the type name first and checks against the geometry_types HashSet
-/// BEFORE allocating the raw_args String. This avoids ~1M unnecessary String
-/// allocations on large IFC files where most entities are non-geometry types.
-fn parse_entity_line_filtered(line: &str, geometry_types: &HashSet<&str>) -> Option<IfcRawEntity> {
+/// Extracts the type name first and checks it against the geometry_types
+/// HashSet before looking at the arguments, so the ~1M non-geometry
+/// entities of a large IFC file are never copied. Kept entities borrow
+/// their type name from the static table.
+fn parse_entity_line_filtered<'a>(
+    line: &'a str,
+    geometry_types: &HashSet<&'static str>,
+) -> Option<(StepId, TypeName, &'a str)> {
     let line = line.trim();
 
     // Extract entity ID
     let id_end = line.find('=')?;
     let id_str = &line[1..id_end].trim();
-    let entity_id = id_str.parse::<u64>().ok()?;
+    let entity_id = StepId(id_str.parse::<u64>().ok()?);
 
-    // Extract type name (without allocating yet)
+    // Extract type name (without allocating)
     let type_start = id_end + 1;
     let type_section = line[type_start..].trim();
     let paren_pos = type_section.find('(')?;
     let type_name_str = type_section[..paren_pos].trim();
 
-    // Early exit: skip non-geometry types BEFORE allocating raw_args String
-    if !geometry_types.contains(type_name_str) {
-        return None;
-    }
+    // Early exit: skip non-geometry types
+    let type_name = TypeName::from_static(geometry_types.get(type_name_str)?);
 
-    // Only allocate strings for geometry types we care about
     let args_end = type_section.rfind(')')?;
-    let raw_args = type_section[paren_pos + 1..args_end].to_string();
-
-    Some(IfcRawEntity {
-        entity_id,
-        type_name: type_name_str.to_string(),
-        raw_args,
-    })
+    Some((entity_id, type_name, &type_section[paren_pos + 1..args_end]))
 }
 
 /// Split IFC arguments at top-level commas, respecting nested parens and strings.
 ///
 /// For example, `"'name',$,#51,(#145),0.5,.NOTDEFINED."` produces:
 /// `["'name'", "$", "#51", "(#145)", "0.5", ".NOTDEFINED."]`
-fn split_ifc_args(raw_args: &str) -> Vec<String> {
+pub(crate) fn split_ifc_args(raw_args: &str) -> Vec<String> {
     let mut result = Vec::with_capacity(8); // Most IFC entities have <8 args
     let mut current = String::with_capacity(32);
     let mut depth = 0i32;
@@ -569,7 +942,7 @@ fn split_ifc_args(raw_args: &str) -> Vec<String> {
 
 /// Extract a single entity reference (#NNN) from a positional argument string.
 /// Returns None if the argument is "$", empty, or contains no reference.
-fn extract_single_ref(arg: &str) -> Option<u64> {
+pub(crate) fn extract_single_ref(arg: &str) -> Option<StepId> {
     let trimmed = arg.trim();
     if trimmed == "$" || trimmed.is_empty() {
         return None;
@@ -580,7 +953,7 @@ fn extract_single_ref(arg: &str) -> Option<u64> {
         let after_hash = &trimmed[hash_pos + 1..];
         let num_str: String = after_hash.chars().take_while(|c| c.is_ascii_digit()).collect();
         if !num_str.is_empty() {
-            return num_str.parse::<u64>().ok();
+            return num_str.parse::<u64>().ok().map(StepId);
         }
     }
 
@@ -593,7 +966,7 @@ fn extract_single_ref(arg: &str) -> Option<u64> {
 /// IFCLOCALPLACEMENT has two args: (PlacementRelTo, RelativePlacement).
 /// PlacementRelTo is another IFCLOCALPLACEMENT or $ (world origin).
 /// RelativePlacement is an IFCAXIS2PLACEMENT3D.
-fn resolve_placement_chain(placement_id: u64, entities: &HashMap<u64, IfcRawEntity>) -> DMat4 {
+pub(crate) fn resolve_placement_chain(placement_id: StepId, entities: &HashMap<StepId, IfcRawEntity>) -> DMat4 {
     let entity = match entities.get(&placement_id) {
         Some(e) if e.type_name == "IFCLOCALPLACEMENT" => e,
         _ => return DMat4::IDENTITY,
@@ -624,7 +997,7 @@ fn resolve_placement_chain(placement_id: u64, entities: &HashMap<u64, IfcRawEnti
 
 /// Resolve IFCAXIS2PLACEMENT3D to a DMat4 transformation matrix.
 /// Args: (Location, Axis, RefDirection) where Axis and RefDirection are optional.
-fn resolve_axis2placement3d(id: u64, entities: &HashMap<u64, IfcRawEntity>) -> DMat4 {
+pub(crate) fn resolve_axis2placement3d(id: StepId, entities: &HashMap<StepId, IfcRawEntity>) -> DMat4 {
     let entity = match entities.get(&id) {
         Some(e) if e.type_name == "IFCAXIS2PLACEMENT3D" => e,
         _ => return DMat4::IDENTITY,
@@ -632,7 +1005,7 @@ fn resolve_axis2placement3d(id: u64, entities: &HashMap<u64, IfcRawEntity>) -> D
 
     let args = split_ifc_args(&entity.raw_args);
 
-    let location = args.get(0)
+    let location = args.first()
         .and_then(|a| extract_single_ref(a))
         .and_then(|pid| parse_point(pid, entities))
         .unwrap_or(DVec3::ZERO);
@@ -662,7 +1035,7 @@ fn resolve_axis2placement3d(id: u64, entities: &HashMap<u64, IfcRawEntity>) -> D
 }
 
 /// Parse IFCDIRECTION to DVec3.
-fn parse_direction(dir_id: u64, entities: &HashMap<u64, IfcRawEntity>) -> Option<DVec3> {
+pub(crate) fn parse_direction(dir_id: StepId, entities: &HashMap<StepId, IfcRawEntity>) -> Option<DVec3> {
     let entity = entities.get(&dir_id)?;
     if entity.type_name != "IFCDIRECTION" { return None; }
     let coords = parse_real_list(&entity.raw_args);
@@ -678,7 +1051,7 @@ fn parse_direction(dir_id: u64, entities: &HashMap<u64, IfcRawEntity>) -> Option
 /// Resolve IFCCARTESIANTRANSFORMATIONOPERATOR3D to a DMat4 transformation matrix.
 /// Args: (Axis1, Axis2, LocalOrigin, Scale, Axis3)
 /// All args are optional except LocalOrigin.
-fn resolve_cartesian_transform_operator(id: u64, entities: &HashMap<u64, IfcRawEntity>) -> DMat4 {
+pub(crate) fn resolve_cartesian_transform_operator(id: StepId, entities: &HashMap<StepId, IfcRawEntity>) -> DMat4 {
     let entity = match entities.get(&id) {
         Some(e) if e.type_name == "IFCCARTESIANTRANSFORMATIONOPERATOR3D" => e,
         _ => return DMat4::IDENTITY,
@@ -686,7 +1059,7 @@ fn resolve_cartesian_transform_operator(id: u64, entities: &HashMap<u64, IfcRawE
 
     let args = split_ifc_args(&entity.raw_args);
 
-    let axis1 = args.get(0)
+    let axis1 = args.first()
         .and_then(|a| extract_single_ref(a))
         .and_then(|did| parse_direction(did, entities))
         .unwrap_or(DVec3::X);
@@ -728,95 +1101,147 @@ fn resolve_cartesian_transform_operator(id: u64, entities: &HashMap<u64, IfcRawE
 }
 
 /// Apply a 4x4 transform matrix to all face vertices in-place.
-fn apply_transform_to_faces(faces: &mut Vec<IfcFaceData>, transform: &DMat4) {
+///
+/// Mirroring transforms (e.g. a cartesian transformation operator with a
+/// flipped axis) reverse the loops so faces keep their outward winding.
+fn apply_transform_to_faces(faces: &mut [IfcFaceData], transform: &DMat4) {
     if *transform == DMat4::IDENTITY { return; }
+    let mirrored = has_mirror(*transform);
     for face in faces.iter_mut() {
         transform_points(&mut face.outer, transform);
         for hole in face.holes.iter_mut() {
             transform_points(hole, transform);
         }
+        if mirrored {
+            face.outer.reverse();
+            for hole in face.holes.iter_mut() {
+                hole.reverse();
+            }
+        }
     }
 }
 
 /// Apply a 4x4 transform to a list of points in-place.
 fn transform_points(points: &mut [DVec3], transform: &DMat4) {
     for point in points.iter_mut() {
-        let p4 = DVec4::new(point.x, point.y, point.z, 1.0);
-        let tp = *transform * p4;
-        *point = DVec3::new(tp.x, tp.y, tp.z);
+        *point = transform.transform_point3(*point);
     }
 }
 
 // ── Existing geometry resolution (unchanged) ────────────────────────────────
 
-/// Resolve a IFCFACETEDBREP entity to mesh data
-fn resolve_faceted_brep(brep_id: u64, entities: &HashMap<u64, IfcRawEntity>) -> Option<IfcMeshData> {
-    let brep = entities.get(&brep_id)?;
+/// A [`CstError::Unresolved`] for the reference path `path`.
+pub(crate) fn unresolved(path: Vec<EntityLink>, reason: &str) -> CstError {
+    CstError::Unresolved { path, reason: reason.to_string() }
+}
+
+/// `path` with `error`'s path appended, when it is an unresolved reference.
+pub(crate) fn prepend_path(mut path: Vec<EntityLink>, error: CstError) -> CstError {
+    match error {
+        CstError::Unresolved { path: rest, reason } => {
+            path.extend(rest);
+            CstError::Unresolved { path, reason }
+        }
+        other => other,
+    }
+}
+
+pub(crate) fn link(id: StepId, entity: &IfcRawEntity) -> EntityLink {
+    EntityLink::new(id, entity.type_name.as_str())
+}
+
+/// Resolve a IFCFACETEDBREP entity to mesh data. Faces that cannot be
+/// resolved are skipped; when none can, the error tells why the first
+/// one failed, e.g. `IFCFACETEDBREP #1 → IFCCLOSEDSHELL #2 → #3: missing`.
+fn resolve_faceted_brep(brep_id: StepId, entities: &HashMap<StepId, IfcRawEntity>) -> Result<IfcMeshData> {
+    let brep = entities.get(&brep_id)
+        .ok_or_else(|| unresolved(vec![EntityLink::missing(brep_id)], "missing"))?;
 
     // Get shell reference from brep args
     let shell_refs = parse_entity_refs(&brep.raw_args);
-    let shell_id = *shell_refs.first()?;
+    let shell_id = *shell_refs.first()
+        .ok_or_else(|| unresolved(vec![link(brep_id, brep)], "no shell"))?;
 
-    let shell = entities.get(&shell_id)?;
+    let shell = entities.get(&shell_id)
+        .ok_or_else(|| unresolved(vec![link(brep_id, brep), EntityLink::missing(shell_id)], "missing"))?;
 
     // Get face references from shell
     let face_refs = parse_entity_refs(&shell.raw_args);
 
     // Resolve each face to outer boundary + holes
     let mut faces = Vec::new();
+    let mut first_error = None;
     for face_id in face_refs {
-        if let Some(face_data) = resolve_face(face_id, entities) {
-            faces.push(face_data);
+        match resolve_face(face_id, entities) {
+            Ok(face_data) => faces.push(face_data),
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
         }
     }
 
     if faces.is_empty() {
-        return None;
+        let path = vec![link(brep_id, brep), link(shell_id, shell)];
+        return Err(match first_error {
+            Some(e) => prepend_path(path, e),
+            None => unresolved(path, "no faces"),
+        });
     }
 
-    Some(IfcMeshData {
-        name: format!("Brep_{}", brep_id),
+    Ok(IfcMeshData {
+        name: format!("Brep_{}", brep_id.value()),
         faces,
         placement: None,
         color: None,
+        surfaces: Vec::new(),
+        instance: None,
     })
 }
 
 /// Resolve an IFCFACE to an IfcFaceData with outer boundary and hole boundaries.
 /// IFCFACEOUTERBOUND marks the outer loop; IFCFACEBOUND marks inner (hole) loops.
-fn resolve_face(face_id: u64, entities: &HashMap<u64, IfcRawEntity>) -> Option<IfcFaceData> {
-    let face = entities.get(&face_id)?;
+fn resolve_face(face_id: StepId, entities: &HashMap<StepId, IfcRawEntity>) -> Result<IfcFaceData> {
+    let face = entities.get(&face_id)
+        .ok_or_else(|| unresolved(vec![EntityLink::missing(face_id)], "missing"))?;
 
     // Get all bound references for this face
     let bound_refs = parse_entity_refs(&face.raw_args);
     if bound_refs.is_empty() {
-        return None;
+        return Err(unresolved(vec![link(face_id, face)], "no bounds"));
     }
 
     let mut outer: Option<Vec<DVec3>> = None;
     let mut holes: Vec<Vec<DVec3>> = Vec::new();
+    // Why the first unusable bound was dropped
+    let mut first_error: Option<(Vec<EntityLink>, &str)> = None;
 
     for bound_id in bound_refs {
         let bound = match entities.get(&bound_id) {
             Some(b) => b,
-            None => continue,
+            None => {
+                first_error.get_or_insert((vec![EntityLink::missing(bound_id)], "missing"));
+                continue;
+            }
         };
 
         let is_outer = bound.type_name == "IFCFACEOUTERBOUND";
 
         // Resolve the polyloop from the bound
         let bound_args = split_ifc_args(&bound.raw_args);
-        if bound_args.is_empty() {
-            continue;
-        }
-        let loop_id = match extract_single_ref(&bound_args[0]) {
+        let loop_id = match bound_args.first().and_then(|arg| extract_single_ref(arg)) {
             Some(id) => id,
-            None => continue,
+            None => {
+                first_error.get_or_insert((vec![link(bound_id, bound)], "no loop"));
+                continue;
+            }
         };
 
         let poly_loop = match entities.get(&loop_id) {
             Some(e) => e,
-            None => continue,
+            None => {
+                first_error.get_or_insert((vec![link(bound_id, bound), EntityLink::missing(loop_id)], "missing"));
+                continue;
+            }
         };
 
         // Get point references from loop
@@ -829,6 +1254,7 @@ fn resolve_face(face_id: u64, entities: &HashMap<u64, IfcRawEntity>) -> Option<I
         }
 
         if points.is_empty() {
+            first_error.get_or_insert((vec![link(bound_id, bound), link(loop_id, poly_loop)], "no points"));
             continue;
         }
 
@@ -853,12 +1279,17 @@ fn resolve_face(face_id: u64, entities: &HashMap<u64, IfcRawEntity>) -> Option<I
         }
     }
 
-    let outer = outer?;
-    Some(IfcFaceData { outer, holes })
+    let outer = outer.ok_or_else(|| {
+        let (rest, reason) = first_error.unwrap_or_default();
+        let mut path = vec![link(face_id, face)];
+        path.extend(rest);
+        unresolved(path, reason)
+    })?;
+    Ok(IfcFaceData { outer, holes })
 }
 
 /// Parse IFCCARTESIANPOINT to DVec3
-fn parse_point(point_id: u64, entities: &HashMap<u64, IfcRawEntity>) -> Option<DVec3> {
+pub(crate) fn parse_point(point_id: StepId, entities: &HashMap<StepId, IfcRawEntity>) -> Option<DVec3> {
     let entity = entities.get(&point_id)?;
 
     if entity.type_name != "IFCCARTESIANPOINT" {
@@ -875,7 +1306,7 @@ fn parse_point(point_id: u64, entities: &HashMap<u64, IfcRawEntity>) -> Option<D
 }
 
 /// Parse entity references from raw args like "(#55,#56,#57,#58)"
-pub fn parse_entity_refs(raw_args: &str) -> Vec<u64> {
+pub fn parse_entity_refs(raw_args: &str) -> Vec<StepId> {
     let mut refs = Vec::with_capacity(8);
     let mut current_num = String::with_capacity(12);
     let mut in_hash = false;
@@ -890,7 +1321,7 @@ pub fn parse_entity_refs(raw_args: &str) -> Vec<u64> {
             } else {
                 if !current_num.is_empty() {
                     if let Ok(id) = current_num.parse::<u64>() {
-                        refs.push(id);
+                        refs.push(StepId(id));
                     }
                     current_num.clear();
                 }
@@ -902,7 +1333,7 @@ pub fn parse_entity_refs(raw_args: &str) -> Vec<u64> {
     // Handle last number if line ends with digit
     if in_hash && !current_num.is_empty() {
         if let Ok(id) = current_num.parse::<u64>() {
-            refs.push(id);
+            refs.push(StepId(id));
         }
     }
 
@@ -969,10 +1400,10 @@ mod tests {
 
     #[test]
     fn test_parse_entity_refs() {
-        assert_eq!(parse_entity_refs("(#55,#56,#57,#58)"), vec![55, 56, 57, 58]);
-        assert_eq!(parse_entity_refs("(#47)"), vec![47]);
-        assert_eq!(parse_entity_refs("(#95)"), vec![95]);
-        assert_eq!(parse_entity_refs(""), Vec::<u64>::new());
+        assert_eq!(parse_entity_refs("(#55,#56,#57,#58)"), vec![StepId(55), StepId(56), StepId(57), StepId(58)]);
+        assert_eq!(parse_entity_refs("(#47)"), vec![StepId(47)]);
+        assert_eq!(parse_entity_refs("(#95)"), vec![StepId(95)]);
+        assert_eq!(parse_entity_refs(""), Vec::<StepId>::new());
     }
 
     #[test]
@@ -987,13 +1418,13 @@ mod tests {
     #[test]
     fn test_parse_cartesian_point() {
         let mut entities = HashMap::new();
-        entities.insert(47, IfcRawEntity {
-            entity_id: 47,
-            type_name: "IFCCARTESIANPOINT".to_string(),
-            raw_args: "(165379.999999999,22500.,18830.)".to_string(),
+        entities.insert(StepId(47), IfcRawEntity {
+            entity_id: StepId(47),
+            type_name: "IFCCARTESIANPOINT".into(),
+            raw_args: "(165379.999999999,22500.,18830.)".into(),
         });
 
-        let point = parse_point(47, &entities).unwrap();
+        let point = parse_point(StepId(47), &entities).unwrap();
         assert!((point.x - 165379.999999999).abs() < 1e-6);
         assert!((point.y - 22500.0).abs() < 1e-6);
         assert!((point.z - 18830.0).abs() < 1e-6);
@@ -1004,7 +1435,7 @@ mod tests {
         let line = "#47= IFCCARTESIANPOINT((165379.999999999,22500.,18830.));";
         let entity = parse_entity_line(line).unwrap();
 
-        assert_eq!(entity.entity_id, 47);
+        assert_eq!(entity.entity_id, StepId(47));
         assert_eq!(entity.type_name, "IFCCARTESIANPOINT");
         assert_eq!(entity.raw_args, "(165379.999999999,22500.,18830.)");
     }
@@ -1012,11 +1443,11 @@ mod tests {
     #[test]
     fn test_handle_missing_entities() {
         let entities = HashMap::new();
-        let point = parse_point(999, &entities);
+        let point = parse_point(StepId(999), &entities);
         assert!(point.is_none());
 
-        let face = resolve_face(999, &entities);
-        assert!(face.is_none());
+        let face = resolve_face(StepId(999), &entities);
+        assert!(face.is_err());
     }
 
     #[test]
@@ -1055,6 +1486,15 @@ END-ISO-10303-21;
         assert!((p0.x - 0.0).abs() < 1e-6);
         assert!((p0.y - 0.0).abs() < 1e-6);
         assert!((p0.z - 0.0).abs() < 1e-6);
+
+        // Reading from memory gives the same result as reading the file
+        let from_bytes = read_ifc(ifc_content.as_bytes(), &IfcPipelineOptions::default()).unwrap();
+        assert_eq!(from_bytes, result);
+
+        // Results survive a serde round trip unchanged
+        let json = serde_json::to_string(&result).unwrap();
+        let restored: Vec<IfcMeshData> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, result);
     }
 
     // ── New tests for added functionality ───────────────────────────────
@@ -1091,8 +1531,8 @@ END-ISO-10303-21;
 
     #[test]
     fn test_extract_single_ref() {
-        assert_eq!(extract_single_ref("#51"), Some(51));
-        assert_eq!(extract_single_ref(" #123 "), Some(123));
+        assert_eq!(extract_single_ref("#51"), Some(StepId(51)));
+        assert_eq!(extract_single_ref(" #123 "), Some(StepId(123)));
         assert_eq!(extract_single_ref("$"), None);
         assert_eq!(extract_single_ref(""), None);
         assert_eq!(extract_single_ref(".T."), None);
@@ -1101,13 +1541,13 @@ END-ISO-10303-21;
     #[test]
     fn test_parse_direction() {
         let mut entities = HashMap::new();
-        entities.insert(10, IfcRawEntity {
-            entity_id: 10,
-            type_name: "IFCDIRECTION".to_string(),
-            raw_args: "(0.,0.,1.)".to_string(),
+        entities.insert(StepId(10), IfcRawEntity {
+            entity_id: StepId(10),
+            type_name: "IFCDIRECTION".into(),
+            raw_args: "(0.,0.,1.)".into(),
         });
 
-        let dir = parse_direction(10, &entities).unwrap();
+        let dir = parse_direction(StepId(10), &entities).unwrap();
         assert!((dir.x - 0.0).abs() < 1e-6);
         assert!((dir.y - 0.0).abs() < 1e-6);
         assert!((dir.z - 1.0).abs() < 1e-6);
@@ -1117,18 +1557,18 @@ END-ISO-10303-21;
     fn test_resolve_axis2placement3d_identity() {
         let mut entities = HashMap::new();
         // Origin at 0,0,0 with default axes
-        entities.insert(100, IfcRawEntity {
-            entity_id: 100,
-            type_name: "IFCAXIS2PLACEMENT3D".to_string(),
-            raw_args: "#101,$,$".to_string(),
+        entities.insert(StepId(100), IfcRawEntity {
+            entity_id: StepId(100),
+            type_name: "IFCAXIS2PLACEMENT3D".into(),
+            raw_args: "#101,$,$".into(),
         });
-        entities.insert(101, IfcRawEntity {
-            entity_id: 101,
-            type_name: "IFCCARTESIANPOINT".to_string(),
-            raw_args: "(0.,0.,0.)".to_string(),
+        entities.insert(StepId(101), IfcRawEntity {
+            entity_id: StepId(101),
+            type_name: "IFCCARTESIANPOINT".into(),
+            raw_args: "(0.,0.,0.)".into(),
         });
 
-        let mat = resolve_axis2placement3d(100, &entities);
+        let mat = resolve_axis2placement3d(StepId(100), &entities);
         // Should be identity
         assert!((mat.col(3).x - 0.0).abs() < 1e-6);
         assert!((mat.col(3).y - 0.0).abs() < 1e-6);
@@ -1138,28 +1578,28 @@ END-ISO-10303-21;
     #[test]
     fn test_resolve_axis2placement3d_translated() {
         let mut entities = HashMap::new();
-        entities.insert(100, IfcRawEntity {
-            entity_id: 100,
-            type_name: "IFCAXIS2PLACEMENT3D".to_string(),
-            raw_args: "#101,#102,#103".to_string(),
+        entities.insert(StepId(100), IfcRawEntity {
+            entity_id: StepId(100),
+            type_name: "IFCAXIS2PLACEMENT3D".into(),
+            raw_args: "#101,#102,#103".into(),
         });
-        entities.insert(101, IfcRawEntity {
-            entity_id: 101,
-            type_name: "IFCCARTESIANPOINT".to_string(),
-            raw_args: "(10.,20.,30.)".to_string(),
+        entities.insert(StepId(101), IfcRawEntity {
+            entity_id: StepId(101),
+            type_name: "IFCCARTESIANPOINT".into(),
+            raw_args: "(10.,20.,30.)".into(),
         });
-        entities.insert(102, IfcRawEntity {
-            entity_id: 102,
-            type_name: "IFCDIRECTION".to_string(),
-            raw_args: "(0.,0.,1.)".to_string(),
+        entities.insert(StepId(102), IfcRawEntity {
+            entity_id: StepId(102),
+            type_name: "IFCDIRECTION".into(),
+            raw_args: "(0.,0.,1.)".into(),
         });
-        entities.insert(103, IfcRawEntity {
-            entity_id: 103,
-            type_name: "IFCDIRECTION".to_string(),
-            raw_args: "(1.,0.,0.)".to_string(),
+        entities.insert(StepId(103), IfcRawEntity {
+            entity_id: StepId(103),
+            type_name: "IFCDIRECTION".into(),
+            raw_args: "(1.,0.,0.)".into(),
         });
 
-        let mat = resolve_axis2placement3d(100, &entities);
+        let mat = resolve_axis2placement3d(StepId(100), &entities);
         // Translation part
         assert!((mat.col(3).x - 10.0).abs() < 1e-6);
         assert!((mat.col(3).y - 20.0).abs() < 1e-6);
@@ -1171,40 +1611,40 @@ END-ISO-10303-21;
         let mut entities = HashMap::new();
 
         // Parent placement: translate by (100, 200, 0)
-        entities.insert(10, IfcRawEntity {
-            entity_id: 10,
-            type_name: "IFCLOCALPLACEMENT".to_string(),
-            raw_args: "$,#11".to_string(),
+        entities.insert(StepId(10), IfcRawEntity {
+            entity_id: StepId(10),
+            type_name: "IFCLOCALPLACEMENT".into(),
+            raw_args: "$,#11".into(),
         });
-        entities.insert(11, IfcRawEntity {
-            entity_id: 11,
-            type_name: "IFCAXIS2PLACEMENT3D".to_string(),
-            raw_args: "#12,$,$".to_string(),
+        entities.insert(StepId(11), IfcRawEntity {
+            entity_id: StepId(11),
+            type_name: "IFCAXIS2PLACEMENT3D".into(),
+            raw_args: "#12,$,$".into(),
         });
-        entities.insert(12, IfcRawEntity {
-            entity_id: 12,
-            type_name: "IFCCARTESIANPOINT".to_string(),
-            raw_args: "(100.,200.,0.)".to_string(),
+        entities.insert(StepId(12), IfcRawEntity {
+            entity_id: StepId(12),
+            type_name: "IFCCARTESIANPOINT".into(),
+            raw_args: "(100.,200.,0.)".into(),
         });
 
         // Child placement: translate by (10, 20, 0) relative to parent
-        entities.insert(20, IfcRawEntity {
-            entity_id: 20,
-            type_name: "IFCLOCALPLACEMENT".to_string(),
-            raw_args: "#10,#21".to_string(),
+        entities.insert(StepId(20), IfcRawEntity {
+            entity_id: StepId(20),
+            type_name: "IFCLOCALPLACEMENT".into(),
+            raw_args: "#10,#21".into(),
         });
-        entities.insert(21, IfcRawEntity {
-            entity_id: 21,
-            type_name: "IFCAXIS2PLACEMENT3D".to_string(),
-            raw_args: "#22,$,$".to_string(),
+        entities.insert(StepId(21), IfcRawEntity {
+            entity_id: StepId(21),
+            type_name: "IFCAXIS2PLACEMENT3D".into(),
+            raw_args: "#22,$,$".into(),
         });
-        entities.insert(22, IfcRawEntity {
-            entity_id: 22,
-            type_name: "IFCCARTESIANPOINT".to_string(),
-            raw_args: "(10.,20.,0.)".to_string(),
+        entities.insert(StepId(22), IfcRawEntity {
+            entity_id: StepId(22),
+            type_name: "IFCCARTESIANPOINT".into(),
+            raw_args: "(10.,20.,0.)".into(),
         });
 
-        let mat = resolve_placement_chain(20, &entities);
+        let mat = resolve_placement_chain(StepId(20), &entities);
         // Combined: 100+10=110, 200+20=220, 0+0=0
         let test_point = DVec4::new(0.0, 0.0, 0.0, 1.0);
         let result = mat * test_point;
@@ -1241,6 +1681,27 @@ END-ISO-10303-21;
         assert!((faces[0].outer[2].y - 21.0).abs() < 1e-6);
     }
 
+    #[test]
+    fn test_apply_mirror_transform_keeps_winding() {
+        let mut faces = vec![IfcFaceData {
+            outer: vec![
+                DVec3::new(0.0, 0.0, 0.0),
+                DVec3::new(1.0, 0.0, 0.0),
+                DVec3::new(0.0, 1.0, 0.0),
+            ],
+            holes: vec![],
+        }];
+
+        // Mirror in X: the counter-clockwise (+Z) triangle must stay +Z
+        let mirror = DMat4::from_scale(DVec3::new(-1.0, 1.0, 1.0));
+        apply_transform_to_faces(&mut faces, &mirror);
+
+        let outer = &faces[0].outer;
+        let normal = (outer[1] - outer[0]).cross(outer[2] - outer[0]);
+        assert!(normal.z > 0.0);
+        assert!(outer.contains(&DVec3::new(-1.0, 0.0, 0.0)));
+    }
+
     #[test]
     fn test_apply_transform_identity_noop() {
         let original = vec![IfcFaceData {
@@ -1298,6 +1759,368 @@ END-ISO-10303-21;
         assert!((p0.z - 300.0).abs() < 1e-6, "z={} expected 300", p0.z);
     }
 
+    #[test]
+    fn test_product_with_extruded_area_solid() {
+        // A 200 x 100 rectangle swept 3000 up, with the wall placed at
+        // (1000, 2000, 0)
+        let ifc_content = r#"ISO-10303-21;
+HEADER;
+FILE_SCHEMA(('IFC2X3'));
+ENDSEC;
+DATA;
+#1= IFCCARTESIANPOINT((0.,0.,0.));
+#2= IFCCARTESIANPOINT((0.,0.));
+#3= IFCAXIS2PLACEMENT2D(#2,$);
+#4= IFCRECTANGLEPROFILEDEF(.AREA.,$,#3,200.,100.);
+#5= IFCAXIS2PLACEMENT3D(#1,$,$);
+#6= IFCDIRECTION((0.,0.,1.));
+#7= IFCEXTRUDEDAREASOLID(#4,#5,#6,3000.);
+#8= IFCSHAPEREPRESENTATION($,'Body','SweptSolid',(#7));
+#9= IFCPRODUCTDEFINITIONSHAPE($,$,(#8));
+#10= IFCCARTESIANPOINT((1000.,2000.,0.));
+#11= IFCAXIS2PLACEMENT3D(#10,$,$);
+#12= IFCLOCALPLACEMENT($,#11);
+#13= IFCWALL('guid',$,'Wall',$,$,#12,#9,$);
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(ifc_content.as_bytes()).unwrap();
+        temp_file.flush().unwrap();
+
+        let result = read_ifc_file(temp_file.path()).unwrap();
+        assert_eq!(result.len(), 1, "Should find 1 mesh from the wall");
+        assert!(result[0].instance.is_none());
+
+        let points = result[0].faces.iter().flat_map(|f| f.outer.iter().copied());
+        let (min, max) = points.fold((DVec3::splat(f64::MAX), DVec3::splat(f64::MIN)), |(min, max), p| {
+            (min.min(p), max.max(p))
+        });
+        assert!((min - DVec3::new(900.0, 1950.0, 0.0)).length() < 1e-6, "{min:?}");
+        assert!((max - DVec3::new(1100.0, 2050.0, 3000.0)).length() < 1e-6, "{max:?}");
+    }
+
+    #[test]
+    fn test_read_with_type_filter_and_unit_scale() {
+        let ifc_content = r#"ISO-10303-21;
+HEADER;
+FILE_SCHEMA(('IFC2X3'));
+ENDSEC;
+DATA;
+#1= IFCCARTESIANPOINT((0.,0.,0.));
+#2= IFCCARTESIANPOINT((1.,0.,0.));
+#3= IFCCARTESIANPOINT((1.,1.,0.));
+#4= IFCCARTESIANPOINT((0.,1.,0.));
+#5= IFCPOLYLOOP((#1,#2,#3,#4));
+#6= IFCFACEOUTERBOUND(#5,.T.);
+#7= IFCFACE((#6));
+#8= IFCCLOSEDSHELL((#7));
+#9= IFCFACETEDBREP(#8);
+#10= IFCCARTESIANPOINT((100.,200.,300.));
+#11= IFCAXIS2PLACEMENT3D(#10,$,$);
+#12= IFCLOCALPLACEMENT($,#11);
+#13= IFCSHAPEREPRESENTATION($,'Body','Brep',(#9));
+#14= IFCPRODUCTDEFINITIONSHAPE($,$,(#13));
+#15= IFCBEAM('guid',$,'TestBeam',$,$,#12,#14,$);
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(ifc_content.as_bytes()).unwrap();
+        temp_file.flush().unwrap();
+
+        // Filtering out the only product must not fall back to bare breps
+        let walls_only = IfcPipelineOptions {
+            type_filter: Some(vec!["IfcWall".to_string()]),
+            ..Default::default()
+        };
+        assert!(read_ifc_file_with_options(temp_file.path(), &walls_only).unwrap().is_empty());
+
+        let millimetres = IfcPipelineOptions {
+            type_filter: Some(vec!["IfcBeam".to_string()]),
+            unit_scale: Some(0.001),
+            ..Default::default()
+        };
+        let result = read_ifc_file_with_options(temp_file.path(), &millimetres).unwrap();
+        assert_eq!(result.len(), 1);
+        let p0 = result[0].faces[0].outer[0];
+        assert!((p0 - DVec3::new(0.1, 0.2, 0.3)).length() < 1e-9);
+    }
+
+    #[test]
+    fn test_unstyled_products_take_type_colors() {
+        let ifc_content = r#"ISO-10303-21;
+HEADER;
+FILE_SCHEMA(('IFC2X3'));
+ENDSEC;
+DATA;
+#1= IFCCARTESIANPOINT((0.,0.,0.));
+#2= IFCCARTESIANPOINT((1.,0.,0.));
+#3= IFCCARTESIANPOINT((1.,1.,0.));
+#5= IFCPOLYLOOP((#1,#2,#3));
+#6= IFCFACEOUTERBOUND(#5,.T.);
+#7= IFCFACE((#6));
+#8= IFCCLOSEDSHELL((#7));
+#9= IFCFACETEDBREP(#8);
+#11= IFCAXIS2PLACEMENT3D(#1,$,$);
+#12= IFCLOCALPLACEMENT($,#11);
+#13= IFCSHAPEREPRESENTATION($,'Body','Brep',(#9));
+#14= IFCPRODUCTDEFINITIONSHAPE($,$,(#13));
+#15= IFCWALLSTANDARDCASE('guid1',$,'Wall',$,$,#12,#14,$);
+#16= IFCFLOWSEGMENT('guid2',$,'Pipe',$,$,#12,#14,$);
+#17= IFCBUILDINGELEMENTPROXY('guid3',$,'Proxy',$,$,#12,#14,$,$);
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(ifc_content.as_bytes()).unwrap();
+        temp_file.flush().unwrap();
+
+        let options = IfcPipelineOptions::default();
+        let result = read_ifc_file_with_options(temp_file.path(), &options).unwrap();
+        let colors: Vec<_> = result.iter().map(|m| m.color).collect();
+        assert_eq!(colors, vec![
+            options.type_color("IFCWALL"),
+            options.type_color("IFCFLOWSEGMENT"),
+            None,
+        ]);
+        assert!(colors[0].is_some() && colors[0] != colors[1]);
+
+        let gray = IfcPipelineOptions {
+            type_colors: Vec::new(),
+            ..Default::default()
+        };
+        let result = read_ifc_file_with_options(temp_file.path(), &gray).unwrap();
+        assert!(result.iter().all(|m| m.color.is_none()));
+    }
+
+    #[test]
+    fn test_read_with_storey_filter() {
+        let ifc_content = r#"ISO-10303-21;
+HEADER;
+FILE_SCHEMA(('IFC2X3'));
+ENDSEC;
+DATA;
+#1= IFCCARTESIANPOINT((0.,0.,0.));
+#2= IFCCARTESIANPOINT((1.,0.,0.));
+#3= IFCCARTESIANPOINT((1.,1.,0.));
+#5= IFCPOLYLOOP((#1,#2,#3));
+#6= IFCFACEOUTERBOUND(#5,.T.);
+#7= IFCFACE((#6));
+#8= IFCCLOSEDSHELL((#7));
+#9= IFCFACETEDBREP(#8);
+#13= IFCSHAPEREPRESENTATION($,'Body','Brep',(#9));
+#14= IFCPRODUCTDEFINITIONSHAPE($,$,(#13));
+#20= IFCWALL('w1',$,'Lower',$,$,$,#14,$);
+#21= IFCWALL('w2',$,'Upper',$,$,$,#14,$);
+#30= IFCBUILDINGSTOREY('s1',$,'Level 1',$,$,$,$,$,.ELEMENT.,0.);
+#31= IFCBUILDINGSTOREY('s2',$,'Level 2',$,$,$,$,$,.ELEMENT.,3000.);
+#32= IFCRELCONTAINEDINSPATIALSTRUCTURE('r1',$,$,$,(#20),#30);
+#33= IFCRELCONTAINEDINSPATIALSTRUCTURE('r2',$,$,$,(#21),#31);
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(ifc_content.as_bytes()).unwrap();
+        temp_file.flush().unwrap();
+
+        let upper = IfcPipelineOptions {
+            storey: Some("Level 2".to_string()),
+            ..Default::default()
+        };
+        let result = read_ifc_file_with_options(temp_file.path(), &upper).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].name, "Upper_21");
+
+        let missing = IfcPipelineOptions {
+            storey: Some("Roof".to_string()),
+            ..Default::default()
+        };
+        assert!(read_ifc_file_with_options(temp_file.path(), &missing).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_read_order_follows_entity_ids() {
+        let mut ifc_content = String::from(
+            "ISO-10303-21;\nHEADER;\nFILE_SCHEMA(('IFC2X3'));\nENDSEC;\nDATA;
+#1= IFCCARTESIANPOINT((0.,0.,0.));
+#2= IFCCARTESIANPOINT((1.,0.,0.));
+#3= IFCCARTESIANPOINT((1.,1.,0.));
+#5= IFCPOLYLOOP((#1,#2,#3));
+#6= IFCFACEOUTERBOUND(#5,.T.);
+#7= IFCFACE((#6));
+#8= IFCCLOSEDSHELL((#7));
+#9= IFCFACETEDBREP(#8);
+#13= IFCSHAPEREPRESENTATION($,'Body','Brep',(#9));
+#14= IFCPRODUCTDEFINITIONSHAPE($,$,(#13));\n",
+        );
+        // Written in descending order, read back in ascending id order
+        for id in (100..164).rev() {
+            ifc_content.push_str(&format!("#{id}= IFCWALL('w{id}',$,'Wall',$,$,$,#14,$);\n"));
+        }
+        ifc_content.push_str("ENDSEC;\nEND-ISO-10303-21;\n");
+
+        let names: Vec<String> = read_ifc(ifc_content.as_bytes(), &IfcPipelineOptions::default())
+            .unwrap()
+            .into_iter()
+            .map(|mesh| mesh.name)
+            .collect();
+        let expected: Vec<String> = (100..164).map(|id| format!("Wall_{id}")).collect();
+        assert_eq!(names, expected);
+    }
+
+    #[test]
+    fn test_read_reports_progress() {
+        use std::sync::Mutex;
+
+        #[derive(Default)]
+        struct Recorder {
+            totals: Mutex<HashMap<ProgressStage, u64>>,
+            done: Mutex<HashMap<ProgressStage, u64>>,
+        }
+        impl ProgressSink for Recorder {
+            fn start(&self, stage: ProgressStage, total: u64) {
+                self.totals.lock().unwrap().insert(stage, total);
+            }
+            fn advance(&self, stage: ProgressStage, units: u64) {
+                *self.done.lock().unwrap().entry(stage).or_default() += units;
+            }
+        }
+
+        let ifc_content = "ISO-10303-21;
+DATA;
+#1= IFCCARTESIANPOINT((0.,0.,0.));
+#2= IFCCARTESIANPOINT((1.,0.,0.));
+#3= IFCCARTESIANPOINT((1.,1.,0.));
+#5= IFCPOLYLOOP((#1,#2,#3));
+#6= IFCFACEOUTERBOUND(#5,.T.);
+#7= IFCFACE((#6));
+#8= IFCCLOSEDSHELL((#7));
+#9= IFCFACETEDBREP(#8);
+#13= IFCSHAPEREPRESENTATION($,'Body','Brep',(#9));
+#14= IFCPRODUCTDEFINITIONSHAPE($,$,(#13));
+#20= IFCWALL('w1',$,'A',$,$,$,#14,$);
+#21= IFCSLAB('s1',$,'B',$,$,$,#14,$);
+ENDSEC;
+END-ISO-10303-21;
+";
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(ifc_content.as_bytes()).unwrap();
+        temp_file.flush().unwrap();
+
+        let recorder = Recorder::default();
+        let result = read_ifc_file_with_progress(temp_file.path(), &IfcPipelineOptions::default(), &recorder).unwrap();
+        assert_eq!(result.len(), 2);
+
+        let totals = recorder.totals.lock().unwrap();
+        let done = recorder.done.lock().unwrap();
+        assert_eq!(totals[&ProgressStage::Parse], ifc_content.len() as u64);
+        assert_eq!(done[&ProgressStage::Parse], ifc_content.len() as u64);
+        assert_eq!(totals[&ProgressStage::Resolve], 2);
+        assert_eq!(done[&ProgressStage::Resolve], 2);
+    }
+
+    #[test]
+    fn test_read_cancelled() {
+        use cst_core::{CancellationToken, CstError};
+
+        /// Cancels as soon as the first product is resolved
+        struct CancelOnResolve(CancellationToken);
+
+        impl ProgressSink for CancelOnResolve {
+            fn start(&self, _stage: ProgressStage, _total: u64) {}
+
+            fn advance(&self, stage: ProgressStage, _units: u64) {
+                if stage == ProgressStage::Resolve {
+                    self.0.cancel();
+                }
+            }
+
+            fn is_cancelled(&self) -> bool {
+                self.0.is_cancelled()
+            }
+        }
+
+        let ifc_content = r#"ISO-10303-21;
+HEADER;
+FILE_SCHEMA(('IFC2X3'));
+ENDSEC;
+DATA;
+#1= IFCCARTESIANPOINT((0.,0.,0.));
+#2= IFCCARTESIANPOINT((1.,0.,0.));
+#3= IFCCARTESIANPOINT((1.,1.,0.));
+#5= IFCPOLYLOOP((#1,#2,#3));
+#6= IFCFACEOUTERBOUND(#5,.T.);
+#7= IFCFACE((#6));
+#8= IFCCLOSEDSHELL((#7));
+#9= IFCFACETEDBREP(#8);
+#13= IFCSHAPEREPRESENTATION($,'Body','Brep',(#9));
+#14= IFCPRODUCTDEFINITIONSHAPE($,$,(#13));
+#20= IFCWALL('w1',$,'A',$,$,$,#14,$);
+#21= IFCWALL('w2',$,'B',$,$,$,#14,$);
+ENDSEC;
+END-ISO-10303-21;
+"#;
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(ifc_content.as_bytes()).unwrap();
+        temp_file.flush().unwrap();
+        let options = IfcPipelineOptions::default();
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let result = read_ifc_file_with_progress(temp_file.path(), &options, &token);
+        assert!(matches!(result, Err(CstError::Cancelled)));
+
+        let sink = CancelOnResolve(CancellationToken::new());
+        let result = read_ifc_file_with_progress(temp_file.path(), &options, &sink);
+        assert!(matches!(result, Err(CstError::Cancelled)));
+
+        // An untouched token lets the read finish
+        let result = read_ifc_file_with_progress(temp_file.path(), &options, &CancellationToken::new());
+        assert_eq!(result.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_unresolved_brep_reports_path() {
+        let mut entities = HashMap::new();
+        for line in [
+            "#1=IFCFACETEDBREP(#2);",
+            "#2=IFCCLOSEDSHELL((#3,#4));",
+            "#3=IFCFACE((#5));",
+            "#4=IFCFACE((#6));",
+            "#6=IFCFACEOUTERBOUND(#7,.T.);",
+        ] {
+            let entity = parse_entity_line(line).unwrap();
+            entities.insert(entity.entity_id, entity);
+        }
+        let err = resolve_faceted_brep(StepId(1), &entities).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unresolved reference IFCFACETEDBREP #1 → IFCCLOSEDSHELL #2 → IFCFACE #3 → #5: missing"
+        );
+
+        entities.remove(&StepId(2));
+        match resolve_faceted_brep(StepId(1), &entities) {
+            Err(CstError::Unresolved { path, reason }) => {
+                assert_eq!(path, [EntityLink::new(StepId(1), "IFCFACETEDBREP"), EntityLink::missing(StepId(2))]);
+                assert_eq!(reason, "missing");
+            }
+            other => panic!("Expected unresolved reference, got {:?}", other.map(|m| m.name)),
+        }
+
+        // The brep-only fallback skips it and says so
+        let mut diagnostics = Diagnostics::new();
+        let meshes = resolve_meshes(&entities, &IfcPipelineOptions::default(), &NoProgress, &mut diagnostics).unwrap();
+        assert!(meshes.is_empty());
+        let diagnostic = diagnostics.iter().next().unwrap();
+        assert_eq!((diagnostic.code, diagnostic.entity), (UNRESOLVED_GEOMETRY, Some(StepId(1))));
+        assert_eq!(diagnostics.summary()[0].1, "1 geometry items skipped as unresolved");
+    }
+
     #[test]
     fn test_mapped_item_with_placement() {
         // Test the IFCMAPPEDITEM path:
@@ -1348,4 +2171,85 @@ END-ISO-10303-21;
         assert!((p0.y - 60.0).abs() < 1e-6, "y={} expected 60", p0.y);
         assert!((p0.z - 70.0).abs() < 1e-6, "z={} expected 70", p0.z);
     }
+
+    #[test]
+    fn test_mapped_items_group_by_representation_map() {
+        // Two rebars place the same map, the second rotated a quarter turn
+        // about Z, mirrored in Z and scaled by 2
+        let ifc_content = r#"ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION(('ViewDefinition [CoordinationView]'),'2;1');
+FILE_NAME('','2025-03-11T00:00:00',(''),(''),'','','');
+FILE_SCHEMA(('IFC2X3'));
+ENDSEC;
+DATA;
+#1= IFCCARTESIANPOINT((0.,0.,0.));
+#2= IFCCARTESIANPOINT((1.,0.,0.));
+#3= IFCCARTESIANPOINT((1.,1.,0.));
+#4= IFCPOLYLOOP((#1,#2,#3));
+#5= IFCFACEOUTERBOUND(#4,.T.);
+#6= IFCFACE((#5));
+#7= IFCCLOSEDSHELL((#6));
+#8= IFCFACETEDBREP(#7);
+#9= IFCAXIS2PLACEMENT3D(#1,$,$);
+#10= IFCSHAPEREPRESENTATION($,'Body','Brep',(#8));
+#11= IFCREPRESENTATIONMAP(#9,#10);
+#20= IFCCARTESIANPOINT((50.,60.,70.));
+#21= IFCCARTESIANTRANSFORMATIONOPERATOR3D($,$,#20,$,$);
+#22= IFCMAPPEDITEM(#11,#21);
+#23= IFCSHAPEREPRESENTATION($,'Body','MappedRepresentation',(#22));
+#24= IFCPRODUCTDEFINITIONSHAPE($,$,(#23));
+#25= IFCDIRECTION((0.,1.,0.));
+#26= IFCDIRECTION((-1.,0.,0.));
+#27= IFCDIRECTION((0.,0.,-1.));
+#28= IFCCARTESIANTRANSFORMATIONOPERATOR3D(#25,#26,#20,2.,#27);
+#29= IFCMAPPEDITEM(#11,#28);
+#34= IFCSHAPEREPRESENTATION($,'Body','MappedRepresentation',(#29));
+#35= IFCPRODUCTDEFINITIONSHAPE($,$,(#34));
+#30= IFCCARTESIANPOINT((0.,0.,0.));
+#31= IFCAXIS2PLACEMENT3D(#30,$,$);
+#32= IFCLOCALPLACEMENT($,#31);
+#33= IFCREINFORCINGBAR('guid1',#46,'Rebar1',$,$,#32,#24,'tag',$,19.,0.,$,.NOTDEFINED.,$);
+#36= IFCREINFORCINGBAR('guid2',#46,'Rebar2',$,$,#32,#35,'tag',$,19.,0.,$,.NOTDEFINED.,$);
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(ifc_content.as_bytes()).unwrap();
+        temp_file.flush().unwrap();
+
+        let mut result = read_ifc_file(temp_file.path()).unwrap();
+        assert_eq!(result.len(), 2);
+        result.sort_by_key(|m| m.name.clone());
+        let instances: Vec<_> = result.iter().map(|m| m.instance).collect();
+        let groups = instance_groups(&instances);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].representation_map, StepId(11));
+        assert_eq!(groups[0].item, StepId(8));
+        assert_eq!(groups[0].members, vec![0, 1]);
+
+        // The transform carries the rotation, mirror and scale, and maps
+        // the shared geometry onto each copy
+        let second = result[1].instance.unwrap();
+        assert!(has_mirror(second.matrix()));
+        let placed = second.matrix().transform_point3(DVec3::new(1.0, 0.0, 0.0));
+        assert!((placed - DVec3::new(50.0, 62.0, 70.0)).length() < 1e-9, "{placed:?}");
+        assert!((result[1].faces[0].outer[1] - placed).length() < 1e-9);
+
+        // Tessellated copies come back onto the map's own geometry
+        for mesh in &result {
+            let tri = crate::ifc_to_mesh::faces_to_trimesh(&mesh.name, &mesh.faces);
+            let mut shared = TriangleMesh {
+                positions: tri.positions,
+                normals: tri.normals,
+                indices: tri.indices,
+                uvs: Vec::new(),
+            };
+            assert!(mesh.instance.unwrap().to_map_coordinates(&mut shared));
+            let corner = DVec3::new(1.0, 1.0, 0.0);
+            assert!(shared.positions.iter().any(|p| (*p - corner).length() < 1e-9));
+            assert!(shared.normals.iter().all(|n| (*n - DVec3::Z).length() < 1e-9));
+        }
+    }
 }
@@ -1,9 +1,21 @@
 //! IFC spatial hierarchy (Project -> Site -> Building -> Storey).
 
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use cst_core::{ProductId, StepId};
+use serde::{Deserialize, Serialize};
+
+use crate::ifc_assembly::aggregates;
+use crate::ifc_reader::{product_name, split_ifc_args, IfcRawEntity};
+
+/// Entity types the parser has to keep for [`spatial_tree`], besides the
+/// storeys it keeps for containment.
+pub(crate) const SPATIAL_TYPES: &[&str] = &["IFCPROJECT", "IFCSITE", "IFCBUILDING", "IFCSPACE"];
+
 /// A node in the IFC spatial hierarchy tree.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SpatialNode {
-    pub entity_id: u64,
+    pub entity_id: StepId,
     pub kind: SpatialKind,
     pub name: String,
     pub description: Option<String>,
@@ -11,7 +23,7 @@ pub struct SpatialNode {
 }
 
 /// The kind of spatial element.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SpatialKind {
     Project,
     Site,
@@ -20,9 +32,34 @@ pub enum SpatialKind {
     Space,
 }
 
+impl SpatialKind {
+    /// The kind of an upper-case IFC entity type, e.g. `IFCSITE`.
+    pub fn from_type_name(type_name: &str) -> Option<Self> {
+        match type_name {
+            "IFCPROJECT" => Some(SpatialKind::Project),
+            "IFCSITE" => Some(SpatialKind::Site),
+            "IFCBUILDING" => Some(SpatialKind::Building),
+            "IFCBUILDINGSTOREY" => Some(SpatialKind::BuildingStorey),
+            "IFCSPACE" => Some(SpatialKind::Space),
+            _ => None,
+        }
+    }
+
+    /// IFC entity name of the kind, e.g. `IfcBuildingStorey`.
+    pub fn ifc_name(&self) -> &'static str {
+        match self {
+            SpatialKind::Project => "IfcProject",
+            SpatialKind::Site => "IfcSite",
+            SpatialKind::Building => "IfcBuilding",
+            SpatialKind::BuildingStorey => "IfcBuildingStorey",
+            SpatialKind::Space => "IfcSpace",
+        }
+    }
+}
+
 impl SpatialNode {
     /// Create a new spatial node.
-    pub fn new(entity_id: u64, kind: SpatialKind, name: impl Into<String>) -> Self {
+    pub fn new(entity_id: StepId, kind: SpatialKind, name: impl Into<String>) -> Self {
         Self {
             entity_id,
             kind,
@@ -38,7 +75,7 @@ impl SpatialNode {
     }
 
     /// Find a node by entity id (depth-first search).
-    pub fn find_by_id(&self, id: u64) -> Option<&SpatialNode> {
+    pub fn find_by_id(&self, id: StepId) -> Option<&SpatialNode> {
         if self.entity_id == id {
             return Some(self);
         }
@@ -68,6 +105,54 @@ impl SpatialNode {
     }
 }
 
+/// The spatial structure of a parsed model: its IFCPROJECT with the sites,
+/// buildings, storeys and spaces aggregated below it, children in id order.
+/// `None` when the model has no project.
+pub fn spatial_tree(entities: &HashMap<StepId, IfcRawEntity>) -> Option<SpatialNode> {
+    let project = entities
+        .values()
+        .filter(|e| e.type_name == "IFCPROJECT")
+        .min_by_key(|e| e.entity_id)?;
+    let decomposition = aggregates(entities);
+    let mut seen = HashSet::new();
+    Some(spatial_node(project, SpatialKind::Project, entities, &decomposition, &mut seen))
+}
+
+fn spatial_node(
+    entity: &IfcRawEntity,
+    kind: SpatialKind,
+    entities: &HashMap<StepId, IfcRawEntity>,
+    decomposition: &BTreeMap<StepId, Vec<StepId>>,
+    seen: &mut HashSet<StepId>,
+) -> SpatialNode {
+    seen.insert(entity.entity_id);
+    // IFCPROJECT, IFCSITE, ...(GlobalId, OwnerHistory, Name, Description, ...)
+    let name = product_name(ProductId(entity.entity_id), entity);
+    let mut node = SpatialNode::new(entity.entity_id, kind, name);
+    node.description = split_ifc_args(&entity.raw_args)
+        .get(3)
+        .map(|arg| arg.trim().trim_matches('\'').to_string())
+        .filter(|text| text != "$" && !text.is_empty());
+
+    let mut parts = decomposition.get(&entity.entity_id).cloned().unwrap_or_default();
+    parts.sort_unstable();
+    parts.dedup();
+    for part in parts {
+        // A malformed file could aggregate an element into its own subtree
+        if seen.contains(&part) {
+            continue;
+        }
+        let Some(child) = entities.get(&part) else {
+            continue;
+        };
+        if let Some(kind) = SpatialKind::from_type_name(child.type_name.as_str()) {
+            let child = spatial_node(child, kind, entities, decomposition, seen);
+            node.add_child(child);
+        }
+    }
+    node
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -75,13 +160,15 @@ impl SpatialNode {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ifc_progress::NoProgress;
+    use crate::ifc_reader::parse_ifc_entities_from_reader;
 
     fn sample_tree() -> SpatialNode {
-        let mut project = SpatialNode::new(1, SpatialKind::Project, "My Project");
-        let mut site = SpatialNode::new(2, SpatialKind::Site, "Main Site");
-        let mut building = SpatialNode::new(3, SpatialKind::Building, "Building A");
-        let storey1 = SpatialNode::new(4, SpatialKind::BuildingStorey, "Ground Floor");
-        let storey2 = SpatialNode::new(5, SpatialKind::BuildingStorey, "First Floor");
+        let mut project = SpatialNode::new(StepId(1), SpatialKind::Project, "My Project");
+        let mut site = SpatialNode::new(StepId(2), SpatialKind::Site, "Main Site");
+        let mut building = SpatialNode::new(StepId(3), SpatialKind::Building, "Building A");
+        let storey1 = SpatialNode::new(StepId(4), SpatialKind::BuildingStorey, "Ground Floor");
+        let storey2 = SpatialNode::new(StepId(5), SpatialKind::BuildingStorey, "First Floor");
         building.add_child(storey1);
         building.add_child(storey2);
         site.add_child(building);
@@ -98,7 +185,7 @@ mod tests {
     #[test]
     fn test_find_by_id() {
         let tree = sample_tree();
-        let found = tree.find_by_id(3).unwrap();
+        let found = tree.find_by_id(StepId(3)).unwrap();
         assert_eq!(found.name, "Building A");
         assert_eq!(found.kind, SpatialKind::Building);
     }
@@ -106,7 +193,7 @@ mod tests {
     #[test]
     fn test_find_by_id_not_found() {
         let tree = sample_tree();
-        assert!(tree.find_by_id(999).is_none());
+        assert!(tree.find_by_id(StepId(999)).is_none());
     }
 
     #[test]
@@ -124,4 +211,49 @@ mod tests {
         assert_eq!(tree.children[0].children.len(), 1); // one building
         assert_eq!(tree.children[0].children[0].children.len(), 2); // two storeys
     }
+
+    #[test]
+    fn test_spatial_tree_from_aggregates() {
+        let model = "ISO-10303-21;
+HEADER;
+FILE_SCHEMA(('IFC4'));
+ENDSEC;
+DATA;
+#1= IFCPROJECT('p1',$,'Tower',$,$,$,$,$,$);
+#2= IFCSITE('s1',$,'Plot 7','Corner lot',$,$,$,$,.ELEMENT.,$,$,$,$,$);
+#3= IFCBUILDING('b1',$,$,$,$,$,$,$,.ELEMENT.,$,$,$);
+#4= IFCBUILDINGSTOREY('l2',$,'Level 2',$,$,$,$,$,.ELEMENT.,3000.);
+#5= IFCBUILDINGSTOREY('l1',$,'Level 1',$,$,$,$,$,.ELEMENT.,0.);
+#6= IFCSPACE('r1',$,'Lobby',$,$,$,$,$,.ELEMENT.,.INTERNAL.,$);
+#7= IFCWALL('w1',$,'Wall',$,$,$,$,$);
+#10= IFCRELAGGREGATES('a1',$,$,$,#1,(#2));
+#11= IFCRELAGGREGATES('a2',$,$,$,#2,(#3));
+#12= IFCRELAGGREGATES('a3',$,$,$,#3,(#5,#4,#7));
+#13= IFCRELAGGREGATES('a4',$,$,$,#5,(#6));
+#14= IFCRELAGGREGATES('a5',$,$,$,#6,(#1));
+ENDSEC;
+END-ISO-10303-21;
+";
+        let mut entities =
+            parse_ifc_entities_from_reader(model.as_bytes(), 0, &NoProgress).unwrap();
+        let project = spatial_tree(&entities).unwrap();
+        assert_eq!((project.kind.clone(), project.name.as_str()), (SpatialKind::Project, "Tower"));
+        let site = &project.children[0];
+        assert_eq!(site.name, "Plot 7");
+        assert_eq!(site.description.as_deref(), Some("Corner lot"));
+        // Unnamed elements are named after their type and id
+        let building = &site.children[0];
+        assert_eq!(building.name, "IFCBUILDING_3");
+        // Storeys in id order; the wall is not spatial and the space's
+        // loop back to the project is cut
+        let names: Vec<&str> = building.children.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, ["Level 2", "Level 1"]);
+        assert_eq!(building.children[1].children[0].kind, SpatialKind::Space);
+        assert!(building.children[1].children[0].children.is_empty());
+        assert_eq!(project.count(), 6);
+        assert_eq!(project.kind.ifc_name(), "IfcProject");
+
+        entities.remove(&StepId(1));
+        assert!(spatial_tree(&entities).is_none());
+    }
 }
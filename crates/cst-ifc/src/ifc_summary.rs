@@ -0,0 +1,198 @@
+//! Model statistics for reports, dashboards and CI checks.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use cst_core::{ModelUnits, Result};
+use cst_math::Aabb3;
+use serde::Serialize;
+
+use crate::ifc_query::IfcQuery;
+use crate::ifc_reader::{parse_ifc_entities, split_ifc_args};
+
+/// Statistics about an IFC file. Serializes to the JSON written by
+/// [`IfcSummary::to_json`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IfcSummary {
+    /// Schema from the header, e.g. `IFC2X3`
+    pub schema: Option<String>,
+    /// Total number of entity instances
+    pub entity_count: usize,
+    /// Instance count per upper-case entity type
+    pub entity_types: BTreeMap<String, usize>,
+    /// Number of geometry-carrying products
+    pub element_count: usize,
+    pub storeys: Vec<StoreySummary>,
+    /// Shape representation count per representation type (`Brep`,
+    /// `MappedRepresentation`, `SweptSolid`, ...)
+    pub representations: BTreeMap<String, usize>,
+    /// World bounds of the resolved geometry
+    pub bounds: Option<Aabb3>,
+    /// Units of the model's lengths, areas and angles
+    pub units: ModelUnits,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StoreySummary {
+    pub name: String,
+    pub element_count: usize,
+}
+
+impl IfcSummary {
+    /// Pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Collect statistics about the IFC file at `path`.
+pub fn summarize_ifc(path: &Path) -> Result<IfcSummary> {
+    let mut summary = IfcSummary::default();
+    scan_instances(path, &mut summary)?;
+
+    let entities = parse_ifc_entities(path)?;
+    for entity in entities.values() {
+        // IFCSHAPEREPRESENTATION(ContextOfItems, Identifier, Type, Items)
+        if entity.type_name == "IFCSHAPEREPRESENTATION" {
+            let kind = split_ifc_args(&entity.raw_args)
+                .get(2)
+                .map(|arg| arg.trim_matches('\'').to_string())
+                .filter(|kind| kind != "$" && !kind.is_empty())
+                .unwrap_or_else(|| "Unspecified".to_string());
+            *summary.representations.entry(kind).or_default() += 1;
+        }
+    }
+
+    let query = IfcQuery::from_entities(entities);
+    summary.units = query.units();
+    let elements = query.elements();
+    summary.element_count = elements.len();
+    summary.storeys = query
+        .storeys()
+        .into_iter()
+        .map(|name| StoreySummary {
+            name: name.to_string(),
+            element_count: query.elements_in_storey(name).len(),
+        })
+        .collect();
+    summary.bounds = query
+        .meshes(&elements)
+        .iter()
+        .flat_map(|mesh| &mesh.faces)
+        .filter_map(|face| Aabb3::from_points(&face.outer))
+        .reduce(|a, b| a.merge(&b));
+    Ok(summary)
+}
+
+/// Count every entity instance by type and pick up the header schema,
+/// which the geometry parser does not keep.
+fn scan_instances(path: &Path, summary: &mut IfcSummary) -> Result<()> {
+    for_each_statement(path, |statement| {
+        if let Some(rest) = statement.strip_prefix("FILE_SCHEMA") {
+            summary.schema = rest.split('\'').nth(1).map(|schema| schema.to_string());
+            return;
+        }
+        let Some((_, type_name, _)) = split_instance(statement) else {
+            return;
+        };
+        summary.entity_count += 1;
+        *summary
+            .entity_types
+            .entry(type_name.to_string())
+            .or_default() += 1;
+    })
+}
+
+/// Call `visit` with every complete `;`-terminated statement in the file,
+/// header and data alike, with continuation lines joined. Works on files
+/// the entity parser only partially understands.
+pub(crate) fn for_each_statement<F: FnMut(&str)>(path: &Path, mut visit: F) -> Result<()> {
+    let reader = BufReader::with_capacity(1_048_576, File::open(path)?);
+    let mut statement = String::new();
+    for line in reader.lines() {
+        let line = line?;
+        statement.push_str(line.trim());
+        if statement.ends_with(';') {
+            visit(&statement);
+            statement.clear();
+        }
+    }
+    Ok(())
+}
+
+/// `(id, type name, body)` of an instance statement such as
+/// `#12= IFCSIUNIT(...);`, where body starts at the type name.
+pub(crate) fn split_instance(statement: &str) -> Option<(u64, &str, &str)> {
+    let (id, body) = statement.strip_prefix('#')?.split_once('=')?;
+    let id = id.trim().parse().ok()?;
+    let body = body.trim();
+    let type_name = body.split('(').next()?.trim();
+    (!type_name.is_empty()).then_some((id, type_name, body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cst_core::LengthUnit;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    const MODEL: &str = r#"ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION(('ViewDefinition [CoordinationView]'),'2;1');
+FILE_SCHEMA(('IFC2X3'));
+ENDSEC;
+DATA;
+#1= IFCCARTESIANPOINT((0.,0.,0.));
+#2= IFCCARTESIANPOINT((1000.,0.,0.));
+#3= IFCCARTESIANPOINT((1000.,500.,0.));
+#5= IFCPOLYLOOP((#1,#2,#3));
+#6= IFCFACEOUTERBOUND(#5,.T.);
+#7= IFCFACE((#6));
+#8= IFCCLOSEDSHELL((#7));
+#9= IFCFACETEDBREP(#8);
+#10= IFCSHAPEREPRESENTATION($,'Body','Brep',(#9));
+#11= IFCPRODUCTDEFINITIONSHAPE($,$,(#10));
+#12= IFCSIUNIT(*,.LENGTHUNIT.,.MILLI.,.METRE.);
+#13= IFCSIUNIT(*,.PLANEANGLEUNIT.,$,.RADIAN.);
+#20= IFCWALL('w1',$,'Wall',$,$,$,#11,$);
+#21= IFCWALL('w2',$,'Wall',$,$,$,#11,$);
+#30= IFCBUILDINGSTOREY('s1',$,'Level 1',$,$,$,$,$,.ELEMENT.,0.);
+#32= IFCRELCONTAINEDINSPATIALSTRUCTURE('r1',$,$,$,(#20,#21),#30);
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+    #[test]
+    fn test_summarize_ifc() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(MODEL.as_bytes()).unwrap();
+        temp_file.flush().unwrap();
+
+        let summary = summarize_ifc(temp_file.path()).unwrap();
+        assert_eq!(summary.schema.as_deref(), Some("IFC2X3"));
+        assert_eq!(summary.entity_count, 16);
+        assert_eq!(summary.entity_types["IFCCARTESIANPOINT"], 3);
+        assert_eq!(summary.entity_types["IFCWALL"], 2);
+        assert_eq!(summary.element_count, 2);
+        assert_eq!(
+            summary.storeys,
+            vec![StoreySummary {
+                name: "Level 1".to_string(),
+                element_count: 2
+            }]
+        );
+        assert_eq!(summary.representations["Brep"], 1);
+        assert_eq!(summary.units.length, LengthUnit::Millimetre);
+        let bounds = summary.bounds.unwrap();
+        assert_eq!(bounds.max.x, 1000.0);
+        assert_eq!(bounds.max.y, 500.0);
+
+        let json: serde_json::Value = serde_json::from_str(&summary.to_json()).unwrap();
+        assert_eq!(json["entity_types"]["IFCWALL"], 2);
+        assert_eq!(json["storeys"][0]["name"], "Level 1");
+        assert_eq!(json["units"]["length"], "millimetre");
+    }
+}
@@ -0,0 +1,416 @@
+//! Recognition of analytic surfaces in faceted IFC geometry.
+//!
+//! Exporters write flat and curved faces alike as planar polygons. Here
+//! faces are grouped by the plane, cylinder or sphere they approximate, so
+//! adaptive tessellation and exports to exact formats can use the surface
+//! instead of its facets. Curved patches are regions of faces joined by
+//! gently bent edges whose vertices fit a cylinder or a sphere; the
+//! remaining faces are grouped with their coplanar neighbours.
+
+use std::collections::HashMap;
+
+use cst_geometry::surface::{CylindricalSurface, PlanarSurface, SphericalSurface};
+use cst_geometry::Surface;
+use cst_math::fit::{fit_cylinder, fit_sphere, Cylinder, Sphere};
+use cst_math::plane::Plane;
+use cst_math::{DVec3, Point3};
+use serde::{Deserialize, Serialize};
+
+use crate::ifc_reader::IfcFaceData;
+use crate::ifc_to_mesh::compute_face_normal;
+
+/// Largest angle, in radians, between neighbouring facets of a curved patch.
+const MAX_BEND: f64 = 0.53;
+
+/// Neighbouring facets whose normals differ by less than this angle lie in
+/// one plane.
+const MAX_COPLANAR_ANGLE: f64 = 1e-3;
+
+/// Fewest facets that make up a curved patch.
+const MIN_CURVED_FACES: usize = 3;
+
+/// Default tolerance relative to the size of the faces.
+const RELATIVE_TOLERANCE: f64 = 1e-5;
+
+/// An exact surface that a group of faces approximates.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum AnalyticSurface {
+    /// Normal pointing out of the faces
+    Plane(Plane),
+    Cylinder(Cylinder),
+    Sphere(Sphere),
+}
+
+impl AnalyticSurface {
+    /// The surface as a parametric cst-geometry surface, e.g. for adaptive
+    /// tessellation.
+    pub fn to_surface(&self) -> Box<dyn Surface> {
+        match *self {
+            AnalyticSurface::Plane(plane) => {
+                let (u_axis, _) = plane.normal.any_orthonormal_pair();
+                let v_axis = plane.normal.cross(u_axis);
+                Box::new(PlanarSurface::new(plane.origin, u_axis, v_axis))
+            }
+            AnalyticSurface::Cylinder(cylinder) => Box::new(CylindricalSurface::new(
+                cylinder.origin,
+                cylinder.axis,
+                cylinder.radius,
+            )),
+            AnalyticSurface::Sphere(sphere) => {
+                Box::new(SphericalSurface::new(sphere.center, sphere.radius))
+            }
+        }
+    }
+
+    /// Largest distance of any of `points` from the surface.
+    pub fn max_deviation(&self, points: &[Point3]) -> f64 {
+        match self {
+            AnalyticSurface::Plane(plane) => plane.max_deviation(points),
+            AnalyticSurface::Cylinder(cylinder) => cylinder.max_deviation(points),
+            AnalyticSurface::Sphere(sphere) => sphere.max_deviation(points),
+        }
+    }
+}
+
+/// Faces lying on one analytic surface.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SurfacePatch {
+    pub surface: AnalyticSurface,
+    /// Indices of the faces, ascending
+    pub faces: Vec<usize>,
+}
+
+/// Group `faces` into patches of connected faces on one plane, cylinder or
+/// sphere, with every vertex within `tolerance` of the surface. A tolerance
+/// of 0 scales with the size of the faces. Faces that fit no surface, such
+/// as warped quads, belong to no patch.
+pub fn detect_surfaces(faces: &[IfcFaceData], tolerance: f64) -> Vec<SurfacePatch> {
+    let tolerance = if tolerance > 0.0 {
+        tolerance
+    } else {
+        default_tolerance(faces)
+    };
+    let normals: Vec<DVec3> = faces
+        .iter()
+        .map(|f| compute_face_normal(&f.outer))
+        .collect();
+    let edges = shared_edges(faces, tolerance);
+
+    // Curved candidates: regions across edges that bend a little
+    let mut regions = UnionFind::new(faces.len());
+    for &(a, b) in &edges {
+        if normals[a] != DVec3::ZERO
+            && normals[b] != DVec3::ZERO
+            && normals[a].angle_between(normals[b]) <= MAX_BEND
+        {
+            regions.union(a, b);
+        }
+    }
+    let mut patches = Vec::new();
+    let mut claimed = vec![false; faces.len()];
+    for members in regions.groups() {
+        let bends = members
+            .iter()
+            .any(|&i| normals[i].angle_between(normals[members[0]]) > MAX_COPLANAR_ANGLE);
+        if members.len() < MIN_CURVED_FACES || !bends {
+            continue;
+        }
+        let points = face_points(faces, &members);
+        let surface = fit_cylinder(&points)
+            .map(AnalyticSurface::Cylinder)
+            .filter(|s| s.max_deviation(&points) <= tolerance)
+            .or_else(|| {
+                fit_sphere(&points)
+                    .map(AnalyticSurface::Sphere)
+                    .filter(|s| s.max_deviation(&points) <= tolerance)
+            });
+        if let Some(surface) = surface {
+            for &i in &members {
+                claimed[i] = true;
+            }
+            patches.push(SurfacePatch {
+                surface,
+                faces: members,
+            });
+        }
+    }
+
+    // The rest: coplanar neighbours
+    let mut planes = UnionFind::new(faces.len());
+    for &(a, b) in &edges {
+        if !claimed[a] && !claimed[b] && normals[a].angle_between(normals[b]) <= MAX_COPLANAR_ANGLE
+        {
+            planes.union(a, b);
+        }
+    }
+    for members in planes.groups() {
+        if claimed[members[0]] || normals[members[0]] == DVec3::ZERO {
+            continue;
+        }
+        let points = face_points(faces, &members);
+        let Some(mut plane) = Plane::fit(&points) else {
+            continue;
+        };
+        if plane.normal.dot(normals[members[0]]) < 0.0 {
+            plane.normal = -plane.normal;
+        }
+        if plane.max_deviation(&points) <= tolerance {
+            patches.push(SurfacePatch {
+                surface: AnalyticSurface::Plane(plane),
+                faces: members,
+            });
+        }
+    }
+
+    patches.sort_by_key(|patch| patch.faces[0]);
+    patches
+}
+
+/// [`RELATIVE_TOLERANCE`] of the diagonal of the faces' bounding box.
+fn default_tolerance(faces: &[IfcFaceData]) -> f64 {
+    let mut min = DVec3::splat(f64::INFINITY);
+    let mut max = DVec3::splat(f64::NEG_INFINITY);
+    for p in faces.iter().flat_map(|f| f.outer.iter()) {
+        min = min.min(*p);
+        max = max.max(*p);
+    }
+    let diagonal = (max - min).length();
+    if diagonal.is_finite() && diagonal > 0.0 {
+        diagonal * RELATIVE_TOLERANCE
+    } else {
+        RELATIVE_TOLERANCE
+    }
+}
+
+/// Pairs of faces that share an edge, with vertices snapped to `tolerance`.
+fn shared_edges(faces: &[IfcFaceData], tolerance: f64) -> Vec<(usize, usize)> {
+    let key = |p: DVec3| {
+        let q = (p / tolerance).round();
+        (q.x as i64, q.y as i64, q.z as i64)
+    };
+    let mut owners: HashMap<_, Vec<usize>> = HashMap::new();
+    for (index, face) in faces.iter().enumerate() {
+        for ring in std::iter::once(&face.outer).chain(&face.holes) {
+            for (i, &p) in ring.iter().enumerate() {
+                let (a, b) = (key(p), key(ring[(i + 1) % ring.len()]));
+                if a != b {
+                    owners.entry((a.min(b), a.max(b))).or_default().push(index);
+                }
+            }
+        }
+    }
+    let mut pairs: Vec<(usize, usize)> = owners
+        .into_values()
+        .flat_map(|owners| {
+            let pairs: Vec<(usize, usize)> = owners
+                .iter()
+                .enumerate()
+                .flat_map(|(i, &a)| owners[i + 1..].iter().map(move |&b| (a.min(b), a.max(b))))
+                .filter(|(a, b)| a != b)
+                .collect();
+            pairs
+        })
+        .collect();
+    pairs.sort_unstable();
+    pairs.dedup();
+    pairs
+}
+
+/// Every vertex of the faces at `indices`.
+fn face_points(faces: &[IfcFaceData], indices: &[usize]) -> Vec<Point3> {
+    indices
+        .iter()
+        .flat_map(|&i| std::iter::once(&faces[i].outer).chain(&faces[i].holes))
+        .flatten()
+        .copied()
+        .collect()
+}
+
+/// Disjoint sets of face indices.
+struct UnionFind {
+    parent: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(len: usize) -> Self {
+        UnionFind {
+            parent: (0..len).collect(),
+        }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parent[i] != i {
+            self.parent[i] = self.parent[self.parent[i]];
+            i = self.parent[i];
+        }
+        i
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parent[a.max(b)] = a.min(b);
+        }
+    }
+
+    /// The sets in order of their smallest index, members ascending.
+    fn groups(&mut self) -> Vec<Vec<usize>> {
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        let mut slot: HashMap<usize, usize> = HashMap::new();
+        for i in 0..self.parent.len() {
+            let root = self.find(i);
+            let index = *slot.entry(root).or_insert_with(|| {
+                groups.push(Vec::new());
+                groups.len() - 1
+            });
+            groups[index].push(i);
+        }
+        groups
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::{PI, TAU};
+
+    fn face(outer: Vec<DVec3>) -> IfcFaceData {
+        IfcFaceData {
+            outer,
+            holes: Vec::new(),
+        }
+    }
+
+    /// A closed cylinder of `segments` side quads and two polygon caps.
+    fn faceted_cylinder(radius: f64, height: f64, segments: usize) -> Vec<IfcFaceData> {
+        let ring = |z: f64| -> Vec<DVec3> {
+            (0..segments)
+                .map(|i| {
+                    let a = TAU * i as f64 / segments as f64;
+                    DVec3::new(radius * a.cos(), radius * a.sin(), z)
+                })
+                .collect()
+        };
+        let (bottom, top) = (ring(0.0), ring(height));
+        let mut faces: Vec<IfcFaceData> = (0..segments)
+            .map(|i| {
+                let j = (i + 1) % segments;
+                face(vec![bottom[i], bottom[j], top[j], top[i]])
+            })
+            .collect();
+        faces.push(face(bottom.into_iter().rev().collect()));
+        faces.push(face(top));
+        faces
+    }
+
+    #[test]
+    fn test_detects_cylinder_and_caps() {
+        let faces = faceted_cylinder(2.0, 3.0, 16);
+        let patches = detect_surfaces(&faces, 0.0);
+        assert_eq!(patches.len(), 3);
+
+        let AnalyticSurface::Cylinder(cylinder) = patches[0].surface else {
+            panic!("expected a cylinder, got {:?}", patches[0].surface);
+        };
+        assert_eq!(patches[0].faces, (0..16).collect::<Vec<_>>());
+        assert!((cylinder.radius - 2.0).abs() < 1e-6);
+        assert!(cylinder.axis.cross(DVec3::Z).length() < 1e-6);
+
+        let AnalyticSurface::Plane(bottom) = patches[1].surface else {
+            panic!("expected a plane");
+        };
+        assert_eq!(patches[1].faces, vec![16]);
+        assert!((bottom.normal + DVec3::Z).length() < 1e-9);
+        let AnalyticSurface::Plane(top) = patches[2].surface else {
+            panic!("expected a plane");
+        };
+        assert!((top.normal - DVec3::Z).length() < 1e-9);
+        assert!((top.origin.z - 3.0).abs() < 1e-9);
+
+        // The exact surface is the fitted cylinder
+        let surface = patches[0].surface.to_surface();
+        for (u, v) in [(0.3, 0.0), (2.0, 1.5)] {
+            assert!((cylinder.axis_distance(surface.point_at(u, v)) - 2.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_groups_coplanar_triangles() {
+        // A unit box with every side split into two triangles
+        let c = |x: f64, y: f64, z: f64| DVec3::new(x, y, z);
+        let quads = [
+            [c(0., 0., 0.), c(0., 1., 0.), c(1., 1., 0.), c(1., 0., 0.)],
+            [c(0., 0., 1.), c(1., 0., 1.), c(1., 1., 1.), c(0., 1., 1.)],
+            [c(0., 0., 0.), c(1., 0., 0.), c(1., 0., 1.), c(0., 0., 1.)],
+            [c(0., 1., 0.), c(0., 1., 1.), c(1., 1., 1.), c(1., 1., 0.)],
+            [c(0., 0., 0.), c(0., 0., 1.), c(0., 1., 1.), c(0., 1., 0.)],
+            [c(1., 0., 0.), c(1., 1., 0.), c(1., 1., 1.), c(1., 0., 1.)],
+        ];
+        let faces: Vec<IfcFaceData> = quads
+            .iter()
+            .flat_map(|[a, b, c, d]| [face(vec![*a, *b, *c]), face(vec![*a, *c, *d])])
+            .collect();
+        let patches = detect_surfaces(&faces, 1e-9);
+        assert_eq!(patches.len(), 6);
+        for (i, patch) in patches.iter().enumerate() {
+            assert_eq!(patch.faces, vec![2 * i, 2 * i + 1]);
+            assert!(matches!(patch.surface, AnalyticSurface::Plane(_)));
+        }
+    }
+
+    #[test]
+    fn test_detects_sphere() {
+        // A UV sphere of radius 1.5 around (1, 2, 3)
+        let center = DVec3::new(1.0, 2.0, 3.0);
+        let (bands, segments) = (8, 12);
+        let point = |band: usize, segment: usize| {
+            let polar = PI * band as f64 / bands as f64;
+            let azimuth = TAU * segment as f64 / segments as f64;
+            center
+                + 1.5
+                    * DVec3::new(
+                        polar.sin() * azimuth.cos(),
+                        polar.sin() * azimuth.sin(),
+                        polar.cos(),
+                    )
+        };
+        let mut faces = Vec::new();
+        for band in 0..bands {
+            for segment in 0..segments {
+                let next = (segment + 1) % segments;
+                let corners = [
+                    point(band, segment),
+                    point(band + 1, segment),
+                    point(band + 1, next),
+                    point(band, next),
+                ];
+                let mut outer = corners.to_vec();
+                // The poles collapse one side of the quad
+                outer.dedup_by(|a, b| (*a - *b).length() < 1e-12);
+                if (outer[0] - outer[outer.len() - 1]).length() < 1e-12 {
+                    outer.pop();
+                }
+                faces.push(face(outer));
+            }
+        }
+        let patches = detect_surfaces(&faces, 0.0);
+        assert_eq!(patches.len(), 1);
+        let AnalyticSurface::Sphere(sphere) = patches[0].surface else {
+            panic!("expected a sphere, got {:?}", patches[0].surface);
+        };
+        assert_eq!(patches[0].faces.len(), bands * segments);
+        assert!((sphere.center - center).length() < 1e-6);
+        assert!((sphere.radius - 1.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_warped_face_fits_nothing() {
+        let faces = vec![face(vec![
+            DVec3::new(0.0, 0.0, 0.0),
+            DVec3::new(1.0, 0.0, 0.0),
+            DVec3::new(1.0, 1.0, 0.5),
+            DVec3::new(0.0, 1.0, 0.0),
+        ])];
+        assert!(detect_surfaces(&faces, 1e-6).is_empty());
+    }
+}
@@ -3,12 +3,23 @@
 //! Converts IFC polygon face data into indexed triangle meshes with computed normals.
 //! Supports concave polygons and faces with holes via earcutr ear-clipping triangulation.
 
+use cst_core::{DiagnosticCode, Diagnostics};
+use cst_math::plane::Plane;
 use cst_math::{DVec3, Point3, Vector3};
 use crate::ifc_reader::IfcFaceData;
+use serde::{Deserialize, Serialize};
+
+/// A face with fewer than three points or zero area was left out.
+pub const DEGENERATE_FACE: DiagnosticCode =
+    DiagnosticCode::new("degenerate-face", "faces skipped as degenerate");
+
+/// Ear clipping failed and the face was fan-triangulated without its holes.
+pub const FAN_FALLBACK: DiagnosticCode =
+    DiagnosticCode::new("fan-fallback", "faces fan-triangulated after ear clipping failed");
 
 /// Triangle mesh data converted from IFC geometry.
 /// Compatible with cst_mesh::TriangleMesh fields.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct IfcTriMesh {
     pub name: String,
     pub positions: Vec<Point3>,
@@ -45,14 +56,43 @@ impl IfcTriMesh {
 /// # Returns
 /// A triangle mesh with positions, normals, and indices. Degenerate faces are skipped.
 pub fn faces_to_trimesh(name: &str, faces: &[IfcFaceData]) -> IfcTriMesh {
+    faces_to_trimesh_with_tolerance(name, faces, 0.0)
+}
+
+/// Like [`faces_to_trimesh`], but also drops faces with less area than a
+/// `tolerance`-sized square, trading small detail for fewer triangles.
+/// A tolerance of 0 keeps every non-degenerate face.
+pub fn faces_to_trimesh_with_tolerance(name: &str, faces: &[IfcFaceData], tolerance: f64) -> IfcTriMesh {
+    faces_to_trimesh_with_diagnostics(name, faces, tolerance, &mut Diagnostics::new())
+}
+
+/// Like [`faces_to_trimesh_with_tolerance`], recording skipped degenerate
+/// faces and triangulation fallbacks in `diagnostics`.
+pub fn faces_to_trimesh_with_diagnostics(
+    name: &str,
+    faces: &[IfcFaceData],
+    tolerance: f64,
+    diagnostics: &mut Diagnostics,
+) -> IfcTriMesh {
+    let min_area = tolerance * tolerance;
     let mut mesh = IfcTriMesh::new(name.to_string());
     let mut vertex_offset = 0u32;
 
-    for face in faces {
+    for (index, face) in faces.iter().enumerate() {
         let outer = &face.outer;
 
         // Skip degenerate faces
         if outer.len() < 3 {
+            diagnostics.warn(
+                DEGENERATE_FACE,
+                None,
+                format!("Face {} of {} has {} points", index, name, outer.len()),
+            );
+            continue;
+        }
+
+        // Skip detail below the tolerance
+        if min_area > 0.0 && polygon_area(outer) < min_area {
             continue;
         }
 
@@ -61,6 +101,7 @@ pub fn faces_to_trimesh(name: &str, faces: &[IfcFaceData]) -> IfcTriMesh {
 
         // Skip faces with zero-area (degenerate)
         if normal.length_squared() < 1e-10 {
+            diagnostics.warn(DEGENERATE_FACE, None, format!("Face {} of {} has zero area", index, name));
             continue;
         }
 
@@ -92,8 +133,10 @@ pub fn faces_to_trimesh(name: &str, faces: &[IfcFaceData]) -> IfcTriMesh {
                 all_vertices.extend_from_slice(hole);
             }
 
-            // Project 3D vertices to 2D for earcutr
-            let coords_2d = project_to_2d(&all_vertices, &normal);
+            // Project 3D vertices to 2D for earcutr, choosing the axis plane
+            // from the best-fit plane of the outer boundary
+            let projection_normal = projection_normal(outer, normal);
+            let coords_2d = project_to_2d(&all_vertices, &projection_normal);
 
             // Run earcutr
             let tri_result = earcutr::earcut(&coords_2d, &hole_indices, 2);
@@ -102,6 +145,17 @@ pub fn faces_to_trimesh(name: &str, faces: &[IfcFaceData]) -> IfcTriMesh {
                 Ok(indices) if !indices.is_empty() => Some(indices),
                 _ => None,
             };
+            // earcutr winds every polygon the same way in 2D, so faces whose
+            // bound runs the other way (e.g. an IFCFACEOUTERBOUND with .F.)
+            // come out facing away from their normal
+            let tri_indices = tri_indices.map(|mut indices| {
+                if wound_against(&all_vertices, &indices, normal) {
+                    for tri in indices.chunks_exact_mut(3) {
+                        tri.swap(1, 2);
+                    }
+                }
+                indices
+            });
 
             if let Some(tri_indices) = tri_indices {
                 // Add all vertices (outer + holes)
@@ -119,6 +173,11 @@ pub fn faces_to_trimesh(name: &str, faces: &[IfcFaceData]) -> IfcTriMesh {
             } else {
                 // Fallback to fan triangulation on the outer boundary only
                 // (earcutr can fail on degenerate inputs)
+                diagnostics.warn(
+                    FAN_FALLBACK,
+                    None,
+                    format!("Face {} of {} with {} holes", index, name, face.holes.len()),
+                );
                 for vertex in outer {
                     mesh.positions.push(Point3::new(vertex.x, vertex.y, vertex.z));
                     mesh.normals.push(normal);
@@ -139,6 +198,19 @@ pub fn faces_to_trimesh(name: &str, faces: &[IfcFaceData]) -> IfcTriMesh {
     mesh
 }
 
+/// Whether the triangles `indices` into `vertices` face, overall, away from
+/// `normal`.
+fn wound_against(vertices: &[DVec3], indices: &[usize], normal: Vector3) -> bool {
+    let area: Vector3 = indices
+        .chunks_exact(3)
+        .map(|tri| {
+            let (a, b, c) = (vertices[tri[0]], vertices[tri[1]], vertices[tri[2]]);
+            (b - a).cross(c - a)
+        })
+        .sum();
+    area.dot(normal) < 0.0
+}
+
 /// Project 3D points to 2D coordinates for earcutr.
 ///
 /// Uses the face normal to determine the dominant axis, then projects
@@ -175,6 +247,34 @@ fn project_to_2d(vertices: &[DVec3], normal: &Vector3) -> Vec<f64> {
     coords
 }
 
+/// Normal of the least-squares plane through `vertices`, flipped to agree
+/// with the Newell normal. Falls back to the Newell normal when no plane
+/// can be fitted.
+fn projection_normal(vertices: &[DVec3], newell: Vector3) -> Vector3 {
+    match Plane::fit(vertices) {
+        Some(plane) if plane.normal.dot(newell) < 0.0 => -plane.normal,
+        Some(plane) => plane.normal,
+        None => newell,
+    }
+}
+
+/// Indices of faces whose outer boundary deviates from its best-fit plane
+/// by more than `tolerance`.
+///
+/// IFC requires `IfcPolyLoop` boundaries to be planar, but exporters do not
+/// always honour it; warped faces triangulate unpredictably.
+pub fn non_planar_faces(faces: &[IfcFaceData], tolerance: f64) -> Vec<usize> {
+    faces
+        .iter()
+        .enumerate()
+        .filter(|(_, face)| {
+            Plane::fit(&face.outer)
+                .is_some_and(|plane| plane.max_deviation(&face.outer) > tolerance)
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
 /// Triangulate a single convex polygon using fan triangulation from vertex 0.
 ///
 /// For a polygon [v0, v1, v2, v3, ...], generates triangles:
@@ -212,7 +312,17 @@ fn fan_triangulate(vertices: &[DVec3]) -> Vec<[usize; 3]> {
 ///
 /// # Returns
 /// The normalized face normal vector. Returns zero vector for degenerate polygons.
-fn compute_face_normal(vertices: &[DVec3]) -> Vector3 {
+/// Area of a planar polygon (outer boundary only).
+fn polygon_area(vertices: &[DVec3]) -> f64 {
+    let origin = vertices[0];
+    let mut sum = Vector3::ZERO;
+    for pair in vertices[1..].windows(2) {
+        sum += (pair[0] - origin).cross(pair[1] - origin);
+    }
+    sum.length() * 0.5
+}
+
+pub(crate) fn compute_face_normal(vertices: &[DVec3]) -> Vector3 {
     if vertices.len() < 3 {
         return Vector3::ZERO;
     }
@@ -400,6 +510,35 @@ mod tests {
         // Only the valid triangle should be included
         assert_eq!(mesh.positions.len(), 3);
         assert_eq!(mesh.triangle_count(), 1);
+
+        // and the skipped face is reported
+        let mut diagnostics = Diagnostics::new();
+        faces_to_trimesh_with_diagnostics("skip_degen", &faces, 0.0, &mut diagnostics);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics.counts()[&DEGENERATE_FACE], 1);
+        assert_eq!(diagnostics.iter().next().unwrap().message, "Face 0 of skip_degen has 2 points");
+    }
+
+    #[test]
+    fn test_tolerance_drops_small_faces() {
+        let large = simple_face(vec![
+            DVec3::new(0.0, 0.0, 0.0),
+            DVec3::new(10.0, 0.0, 0.0),
+            DVec3::new(10.0, 10.0, 0.0),
+            DVec3::new(0.0, 10.0, 0.0),
+        ]);
+        let small = simple_face(vec![
+            DVec3::new(0.0, 0.0, 5.0),
+            DVec3::new(0.5, 0.0, 5.0),
+            DVec3::new(0.5, 0.5, 5.0),
+            DVec3::new(0.0, 0.5, 5.0),
+        ]);
+        let faces = vec![large, small];
+
+        assert_eq!(faces_to_trimesh("exact", &faces).triangle_count(), 4);
+        assert_eq!(faces_to_trimesh_with_tolerance("coarse", &faces, 1.0).triangle_count(), 2);
+        assert_eq!(faces_to_trimesh_with_tolerance("fine", &faces, 0.1).triangle_count(), 4);
+        assert!((polygon_area(&faces[0].outer) - 100.0).abs() < EPSILON);
     }
 
     #[test]
@@ -563,4 +702,75 @@ mod tests {
         let coords = project_to_2d(&vertices, &normal);
         assert_eq!(coords, vec![1.0, 2.0, 3.0, 4.0]);
     }
+
+    #[test]
+    fn test_non_planar_faces() {
+        let flat = simple_face(vec![
+            DVec3::new(0.0, 0.0, 0.0),
+            DVec3::new(1.0, 0.0, 0.0),
+            DVec3::new(1.0, 1.0, 0.0),
+            DVec3::new(0.0, 1.0, 0.0),
+        ]);
+        let warped = simple_face(vec![
+            DVec3::new(0.0, 0.0, 0.0),
+            DVec3::new(1.0, 0.0, 0.0),
+            DVec3::new(1.0, 1.0, 0.2),
+            DVec3::new(0.0, 1.0, 0.0),
+        ]);
+        let faces = vec![flat, warped];
+
+        assert_eq!(non_planar_faces(&faces, 1e-6), vec![1]);
+        assert!(non_planar_faces(&faces, 0.1).is_empty());
+    }
+
+    #[test]
+    fn test_projection_normal_uses_fitted_plane() {
+        // Steep tilted L-shape with one vertex nudged off-plane: the fitted
+        // normal stays close to the true one and agrees with Newell's sign
+        let tilt = |x: f64, y: f64| DVec3::new(x, y * 0.6, y * 0.8);
+        let mut outline = vec![
+            tilt(0.0, 0.0),
+            tilt(2.0, 0.0),
+            tilt(2.0, 1.0),
+            tilt(1.0, 1.0),
+            tilt(1.0, 2.0),
+            tilt(0.0, 2.0),
+        ];
+        outline[3].x += 1e-4;
+
+        let newell = compute_face_normal(&outline);
+        let normal = projection_normal(&outline, newell);
+        assert!(normal.dot(newell) > 0.0);
+        assert!(normal.dot(Vector3::new(0.0, -0.8, 0.6)) > 1.0 - 1e-6);
+
+        // Collinear input falls back to the Newell normal
+        let line = vec![DVec3::ZERO, DVec3::X, DVec3::X * 2.0];
+        assert_eq!(projection_normal(&line, Vector3::Z), Vector3::Z);
+    }
+
+    #[test]
+    fn test_triangles_follow_face_normal() {
+        // A concave pentagon in both windings, flat and upright
+        let pentagon = vec![
+            DVec3::new(0.0, 0.0, 0.0),
+            DVec3::new(1.0, 0.0, 0.0),
+            DVec3::new(1.5, 0.5, 0.0),
+            DVec3::new(0.5, 1.0, 0.0),
+            DVec3::new(-0.5, 0.5, 0.0),
+        ];
+        let upright: Vec<DVec3> = pentagon.iter().map(|p| DVec3::new(p.x, 0.0, p.y)).collect();
+        for outer in [pentagon, upright] {
+            let mut reversed = outer.clone();
+            reversed.reverse();
+            for face in [simple_face(outer.clone()), simple_face(reversed)] {
+                let mesh = faces_to_trimesh("pentagon", &[face]);
+                assert_eq!(mesh.triangle_count(), 3);
+                for tri in mesh.indices.chunks_exact(3) {
+                    let [a, b, c] = [0, 1, 2].map(|i| mesh.positions[tri[i] as usize]);
+                    let normal = mesh.normals[tri[0] as usize];
+                    assert!((b - a).cross(c - a).dot(normal) > 0.0);
+                }
+            }
+        }
+    }
 }
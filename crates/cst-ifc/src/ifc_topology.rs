@@ -0,0 +1,487 @@
+//! IFC FacetedBrep to Half-Edge Topology
+//!
+//! Builds a `cst_topology::Mesh` per IFCFACETEDBREP instead of triangle soup.
+//! Face loops share vertices by IFCCARTESIANPOINT entity id, so adjacent faces
+//! are stitched through twin half-edges and the result can be validated,
+//! repaired and fed into boolean operations.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use cst_core::{ProductId, Result, StepId};
+use cst_math::{DMat4, DVec3};
+use cst_topology::{FaceId, Mesh, ShellId, VertexId};
+use rayon::prelude::*;
+
+use crate::ifc_reader::{
+    extract_single_ref, parse_entity_refs, parse_ifc_entities, parse_point, product_items,
+    product_name, split_ifc_args, IfcRawEntity, PRODUCT_TYPES,
+};
+
+/// Half-edge topology built from one IFCFACETEDBREP.
+#[derive(Debug, Clone)]
+pub struct IfcTopologyData {
+    pub name: String,
+    /// Entity id of the IFCFACETEDBREP this mesh was built from.
+    pub brep_id: StepId,
+    /// Vertices are already placed in world coordinates.
+    pub mesh: Mesh,
+    /// Shell grouping all faces, or `None` if the faces are not connected.
+    pub shell: Option<ShellId>,
+    /// Faces (or holes) that could not be added, e.g. degenerate loops or
+    /// non-manifold edges.
+    pub skipped_faces: usize,
+}
+
+/// A faceted brep referenced by a product, with its world transform.
+struct BrepInstance {
+    name: String,
+    brep_id: StepId,
+    transform: DMat4,
+}
+
+/// Read an IFC file and build one half-edge mesh per faceted brep instance.
+///
+/// Resolves product placements and IFCMAPPEDITEM instances like
+/// [`read_ifc_file`](crate::ifc_reader::read_ifc_file). Files without product
+/// elements fall back to every IFCFACETEDBREP at its local coordinates.
+pub fn read_ifc_topology(path: &Path) -> Result<Vec<IfcTopologyData>> {
+    let entities = parse_ifc_entities(path)?;
+
+    let mut instances: Vec<BrepInstance> = entities
+        .iter()
+        .filter(|(_, e)| PRODUCT_TYPES.contains(&e.type_name.as_str()))
+        .flat_map(|(&id, product)| product_brep_instances(ProductId(id), product, &entities))
+        .collect();
+
+    if instances.is_empty() {
+        instances = entities
+            .values()
+            .filter(|e| e.type_name == "IFCFACETEDBREP")
+            .map(|e| BrepInstance {
+                name: format!("Brep_{}", e.entity_id.value()),
+                brep_id: e.entity_id,
+                transform: DMat4::IDENTITY,
+            })
+            .collect();
+    }
+    // Stable output order regardless of HashMap iteration.
+    instances.sort_by(|a, b| a.name.cmp(&b.name).then(a.brep_id.cmp(&b.brep_id)));
+
+    Ok(instances
+        .par_iter()
+        .filter_map(|instance| {
+            let mut data = brep_to_topology(instance.brep_id, &entities, &instance.transform)?;
+            data.name = instance.name.clone();
+            Some(data)
+        })
+        .collect())
+}
+
+/// Build a half-edge mesh from an IFCFACETEDBREP, transforming every vertex
+/// by `transform`.
+///
+/// Returns `None` if the entity is not a faceted brep or no face could be
+/// built.
+pub(crate) fn brep_to_topology(
+    brep_id: StepId,
+    entities: &HashMap<StepId, IfcRawEntity>,
+    transform: &DMat4,
+) -> Option<IfcTopologyData> {
+    let brep = entities.get(&brep_id)?;
+    if brep.type_name != "IFCFACETEDBREP" {
+        return None;
+    }
+    let shell_id = *parse_entity_refs(&brep.raw_args).first()?;
+    let shell = entities.get(&shell_id)?;
+
+    let mut mesh = Mesh::new();
+    let mut vertex_by_point: HashMap<StepId, VertexId> = HashMap::new();
+    let mut faces: Vec<FaceId> = Vec::new();
+    let mut skipped_faces = 0;
+
+    for face_ref in parse_entity_refs(&shell.raw_args) {
+        let Some(bounds) = face_bounds(face_ref, entities) else {
+            skipped_faces += 1;
+            continue;
+        };
+
+        let mut loops = Vec::with_capacity(bounds.len());
+        for bound in &bounds {
+            let mut loop_vertices = Vec::with_capacity(bound.points.len());
+            for &point_id in &bound.points {
+                let vertex = match vertex_by_point.get(&point_id) {
+                    Some(&v) => v,
+                    None => {
+                        let Some(p) = parse_point(point_id, entities) else {
+                            continue;
+                        };
+                        let v = mesh.add_vertex(transform.transform_point3(p));
+                        vertex_by_point.insert(point_id, v);
+                        v
+                    }
+                };
+                loop_vertices.push(vertex);
+            }
+            loops.push(loop_vertices);
+        }
+
+        // The outer bound is the one marked IFCFACEOUTERBOUND, else the first.
+        let outer = bounds.iter().position(|bound| bound.outer).unwrap_or(0);
+        let Ok(face_id) = mesh.make_face(&loops[outer]) else {
+            skipped_faces += 1;
+            continue;
+        };
+        // For a .F. bound the loop as written, and so its plane, faces away
+        // from the face
+        mesh.faces[face_id].surface_reversed = bounds[outer].reversed;
+        faces.push(face_id);
+        let outer_normal = loop_normal(&mesh, &loops[outer]);
+        for (i, hole) in loops.iter_mut().enumerate() {
+            if i == outer {
+                continue;
+            }
+            // Exporters often write holes in the same sense as the outer
+            // bound; inner loops must run against it
+            if loop_normal(&mesh, hole).dot(outer_normal) > 0.0 {
+                hole.reverse();
+            }
+            if mesh.add_inner_loop(face_id, hole).is_err() {
+                skipped_faces += 1;
+            }
+        }
+    }
+
+    if faces.is_empty() {
+        return None;
+    }
+    let shell = mesh.add_shell(faces).ok();
+    // A closed shell written inside out would be shaded from within. Shells
+    // with holes in their faces cannot be flipped and stay as written.
+    if let Some(shell) = shell {
+        let inward = mesh.shell_is_closed(shell).unwrap_or(false)
+            && mesh.shell_volume(shell).is_ok_and(|volume| volume < 0.0);
+        if inward {
+            let _ = mesh.flip_shell(shell);
+        }
+    }
+
+    Some(IfcTopologyData {
+        name: format!("Brep_{}", brep_id.value()),
+        brep_id,
+        mesh,
+        shell,
+        skipped_faces,
+    })
+}
+
+/// One bound of an IFCFACE.
+struct FaceBound {
+    /// Point entity ids in the sense of the face.
+    points: Vec<StepId>,
+    /// Whether the bound is an IFCFACEOUTERBOUND.
+    outer: bool,
+    /// Whether the orientation flag is `.F.`, i.e. the loop was written
+    /// against the sense of the face and `points` are reversed.
+    reversed: bool,
+}
+
+/// Newell normal of a loop, not normalized.
+fn loop_normal(mesh: &Mesh, vertices: &[VertexId]) -> DVec3 {
+    let positions: Vec<DVec3> = vertices
+        .iter()
+        .map(|&v| mesh.vertices[v].position)
+        .collect();
+    let mut normal = DVec3::ZERO;
+    for (i, a) in positions.iter().enumerate() {
+        let b = positions[(i + 1) % positions.len()];
+        normal += a.cross(b);
+    }
+    normal
+}
+
+/// Every bound of an IFCFACE, oriented per the bound's orientation flag.
+///
+/// Repeated consecutive points (including a closing point equal to the first)
+/// are dropped.
+fn face_bounds(
+    face_id: StepId,
+    entities: &HashMap<StepId, IfcRawEntity>,
+) -> Option<Vec<FaceBound>> {
+    let face = entities.get(&face_id)?;
+    let mut bounds = Vec::new();
+
+    for bound_id in parse_entity_refs(&face.raw_args) {
+        let Some(bound) = entities.get(&bound_id) else {
+            continue;
+        };
+        let bound_args = split_ifc_args(&bound.raw_args);
+        let Some(poly_loop) = bound_args
+            .first()
+            .and_then(|a| extract_single_ref(a))
+            .and_then(|id| entities.get(&id))
+        else {
+            continue;
+        };
+
+        let mut point_ids = parse_entity_refs(&poly_loop.raw_args);
+        point_ids.dedup();
+        while point_ids.len() > 1 && point_ids.first() == point_ids.last() {
+            point_ids.pop();
+        }
+        let reversed = bound_args.get(1).is_some_and(|o| o.trim() == ".F.");
+        if reversed {
+            point_ids.reverse();
+        }
+        bounds.push(FaceBound {
+            points: point_ids,
+            outer: bound.type_name == "IFCFACEOUTERBOUND",
+            reversed,
+        });
+    }
+
+    if bounds.is_empty() {
+        None
+    } else {
+        Some(bounds)
+    }
+}
+
+/// Faceted breps referenced by a product's shape representations, named
+/// like the meshes of [`read_ifc_file`](crate::ifc_reader::read_ifc_file).
+fn product_brep_instances(
+    product_id: ProductId,
+    product: &IfcRawEntity,
+    entities: &HashMap<StepId, IfcRawEntity>,
+) -> Vec<BrepInstance> {
+    let name = format!(
+        "{}_{}",
+        product_name(product_id, product),
+        product_id.value()
+    );
+    product_items(product, entities)
+        .into_iter()
+        .filter(|placed| {
+            entities
+                .get(&placed.item_id)
+                .is_some_and(|item| item.type_name == "IFCFACETEDBREP")
+        })
+        .map(|placed| BrepInstance {
+            name: name.clone(),
+            brep_id: placed.item_id,
+            transform: placed.transform,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cst_core::traits::Validate;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    /// Unit cube as IFCFACETEDBREP (#20) with outward faces, placed at
+    /// (10, 0, 0) by an IFCBEAM.
+    const CUBE_IFC: &str = r#"ISO-10303-21;
+HEADER;
+FILE_SCHEMA(('IFC2X3'));
+ENDSEC;
+DATA;
+#1= IFCCARTESIANPOINT((0.,0.,0.));
+#2= IFCCARTESIANPOINT((1.,0.,0.));
+#3= IFCCARTESIANPOINT((1.,1.,0.));
+#4= IFCCARTESIANPOINT((0.,1.,0.));
+#5= IFCCARTESIANPOINT((0.,0.,1.));
+#6= IFCCARTESIANPOINT((1.,0.,1.));
+#7= IFCCARTESIANPOINT((1.,1.,1.));
+#8= IFCCARTESIANPOINT((0.,1.,1.));
+#10= IFCPOLYLOOP((#1,#2,#3,#4));
+#11= IFCPOLYLOOP((#5,#6,#7,#8));
+#12= IFCPOLYLOOP((#1,#2,#6,#5));
+#13= IFCPOLYLOOP((#2,#3,#7,#6));
+#14= IFCPOLYLOOP((#3,#4,#8,#7));
+#15= IFCPOLYLOOP((#4,#1,#5,#8));
+#30= IFCFACEOUTERBOUND(#10,.F.);
+#31= IFCFACEOUTERBOUND(#11,.T.);
+#32= IFCFACEOUTERBOUND(#12,.T.);
+#33= IFCFACEOUTERBOUND(#13,.T.);
+#34= IFCFACEOUTERBOUND(#14,.T.);
+#35= IFCFACEOUTERBOUND(#15,.T.);
+#40= IFCFACE((#30));
+#41= IFCFACE((#31));
+#42= IFCFACE((#32));
+#43= IFCFACE((#33));
+#44= IFCFACE((#34));
+#45= IFCFACE((#35));
+#19= IFCCLOSEDSHELL((#40,#41,#42,#43,#44,#45));
+#20= IFCFACETEDBREP(#19);
+#50= IFCCARTESIANPOINT((10.,0.,0.));
+#51= IFCAXIS2PLACEMENT3D(#50,$,$);
+#52= IFCLOCALPLACEMENT($,#51);
+#53= IFCSHAPEREPRESENTATION($,'Body','Brep',(#20));
+#54= IFCPRODUCTDEFINITIONSHAPE($,$,(#53));
+#55= IFCBEAM('guid',$,'Cube',$,$,#52,#54,$);
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+    fn read(content: &str) -> Vec<IfcTopologyData> {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(content.as_bytes()).unwrap();
+        temp_file.flush().unwrap();
+        read_ifc_topology(temp_file.path()).unwrap()
+    }
+
+    #[test]
+    fn test_cube_brep_builds_closed_shell() {
+        let result = read(CUBE_IFC);
+        assert_eq!(result.len(), 1);
+        let data = &result[0];
+        assert_eq!(data.name, "Cube_55");
+        assert_eq!(data.brep_id, StepId(20));
+        assert_eq!(data.skipped_faces, 0);
+
+        // Vertices are shared by point id: 8 vertices, 12 edges, 6 faces.
+        assert_eq!(data.mesh.vertices.len(), 8);
+        assert_eq!(data.mesh.edges.len(), 12);
+        assert_eq!(data.mesh.faces.len(), 6);
+        data.mesh.validate().unwrap();
+
+        let shell = data.shell.expect("faces are connected");
+        assert!(data.mesh.shell_is_closed(shell).unwrap());
+        assert!((data.mesh.shell_volume(shell).unwrap() - 1.0).abs() < 1e-9);
+        // Only the bottom face is bounded against its loop's sense
+        let reversed = data.mesh.faces.values().filter(|f| f.surface_reversed);
+        assert_eq!(reversed.count(), 1);
+    }
+
+    #[test]
+    fn test_inside_out_shell_is_flipped() {
+        // Every bound flag inverted: all faces point into the cube
+        let content = CUBE_IFC
+            .replace(",.T.);", ",.X.);")
+            .replace(",.F.);", ",.T.);")
+            .replace(",.X.);", ",.F.);");
+        let result = read(&content);
+        let data = &result[0];
+        let shell = data.shell.unwrap();
+        assert!((data.mesh.shell_volume(shell).unwrap() - 1.0).abs() < 1e-9);
+        let reversed = data.mesh.faces.values().filter(|f| f.surface_reversed);
+        assert_eq!(reversed.count(), 1);
+        data.mesh.validate().unwrap();
+    }
+
+    #[test]
+    fn test_vertices_are_placed_in_world_coordinates() {
+        let result = read(CUBE_IFC);
+        let min_x = result[0]
+            .mesh
+            .vertices
+            .values()
+            .map(|v| v.position.x)
+            .fold(f64::INFINITY, f64::min);
+        assert!((min_x - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_mapped_brep_is_placed_from_its_mapping_origin() {
+        // The cube's representation mapped about an origin at z = 5 and
+        // moved 2 along y by the item
+        let content = CUBE_IFC.replace(
+            "#54= IFCPRODUCTDEFINITIONSHAPE($,$,(#53));",
+            "#60= IFCCARTESIANPOINT((0.,0.,5.));
+#61= IFCAXIS2PLACEMENT3D(#60,$,$);
+#62= IFCREPRESENTATIONMAP(#61,#53);
+#63= IFCCARTESIANPOINT((0.,2.,0.));
+#64= IFCCARTESIANTRANSFORMATIONOPERATOR3D($,$,#63,$,$);
+#65= IFCMAPPEDITEM(#62,#64);
+#66= IFCSHAPEREPRESENTATION($,'Body','MappedRepresentation',(#65));
+#54= IFCPRODUCTDEFINITIONSHAPE($,$,(#66));",
+        );
+        let result = read(&content);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].name, "Cube_55");
+        let min = result[0]
+            .mesh
+            .vertices
+            .values()
+            .map(|v| v.position)
+            .fold(DVec3::INFINITY, DVec3::min);
+        assert!((min - DVec3::new(10.0, 2.0, 5.0)).length() < 1e-9);
+
+        // Triangle meshes are placed the same way
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(content.as_bytes()).unwrap();
+        let meshes = crate::ifc_reader::read_ifc_file(temp_file.path()).unwrap();
+        let min = meshes[0]
+            .faces
+            .iter()
+            .flat_map(|face| face.outer.iter().copied())
+            .fold(DVec3::INFINITY, DVec3::min);
+        assert!((min - DVec3::new(10.0, 2.0, 5.0)).length() < 1e-9);
+    }
+
+    #[test]
+    fn test_non_manifold_face_is_skipped() {
+        // A second copy of the bottom face reuses the same directed edges.
+        let content = CUBE_IFC.replace(
+            "#19= IFCCLOSEDSHELL((#40,",
+            "#46= IFCFACE((#30));\n#19= IFCCLOSEDSHELL((#40,#46,",
+        );
+        let result = read(&content);
+        assert_eq!(result[0].skipped_faces, 1);
+        assert_eq!(result[0].mesh.faces.len(), 6);
+    }
+
+    #[test]
+    fn test_face_with_hole_gets_inner_loop() {
+        let content = r#"ISO-10303-21;
+DATA;
+#1= IFCCARTESIANPOINT((0.,0.,0.));
+#2= IFCCARTESIANPOINT((4.,0.,0.));
+#3= IFCCARTESIANPOINT((4.,4.,0.));
+#4= IFCCARTESIANPOINT((0.,4.,0.));
+#5= IFCCARTESIANPOINT((1.,1.,0.));
+#6= IFCCARTESIANPOINT((3.,1.,0.));
+#7= IFCCARTESIANPOINT((3.,3.,0.));
+#8= IFCCARTESIANPOINT((1.,3.,0.));
+#10= IFCPOLYLOOP((#1,#2,#3,#4));
+#11= IFCPOLYLOOP((#5,#6,#7,#8));
+#12= IFCFACEOUTERBOUND(#10,.T.);
+#13= IFCFACEBOUND(#11,.F.);
+#14= IFCFACE((#13,#12));
+#15= IFCOPENSHELL((#14));
+#16= IFCFACETEDBREP(#15);
+ENDSEC;
+END-ISO-10303-21;
+"#;
+        // The hole runs against the outer loop whether or not the file
+        // flags it so
+        for content in [
+            content.to_string(),
+            content.replace("#13= IFCFACEBOUND(#11,.F.)", "#13= IFCFACEBOUND(#11,.T.)"),
+        ] {
+            let result = read(&content);
+            assert_eq!(result.len(), 1);
+            let mesh = &result[0].mesh;
+            assert_eq!(result[0].skipped_faces, 0);
+            let face = mesh.faces.values().next().unwrap();
+            assert_eq!(face.inner_loops.len(), 1);
+            assert_eq!(mesh.edges.len(), 8);
+            mesh.validate().unwrap();
+
+            let mut hole = Vec::new();
+            let start = mesh.loops[face.inner_loops[0]].halfedge;
+            let mut he = start;
+            loop {
+                hole.push(mesh.halfedges[he].origin);
+                he = mesh.halfedges[he].next.unwrap();
+                if he == start {
+                    break;
+                }
+            }
+            assert!(loop_normal(mesh, &hole).z < 0.0);
+        }
+    }
+}
@@ -0,0 +1,196 @@
+//! Project units of an IFC model.
+//!
+//! IFC numbers carry no units of their own; the project's
+//! `IFCUNITASSIGNMENT` lists one unit per kind of measure. SI units
+//! (`IFCSIUNIT`) are a prefix and a base unit, while feet, inches and
+//! degrees are `IFCCONVERSIONBASEDUNIT`s defined as a multiple of an SI
+//! unit.
+
+use std::collections::HashMap;
+
+use cst_core::{AngleUnit, AreaUnit, LengthUnit, ModelUnits, StepId};
+
+use crate::ifc_reader::{extract_single_ref, parse_entity_refs, split_ifc_args, IfcRawEntity};
+
+/// Unit entity types the parser has to keep for [`model_units`].
+pub(crate) const UNIT_TYPES: &[&str] = &[
+    "IFCUNITASSIGNMENT",
+    "IFCSIUNIT",
+    "IFCCONVERSIONBASEDUNIT",
+    "IFCMEASUREWITHUNIT",
+];
+
+/// The length, area and angle units of the model. Kinds the file does
+/// not assign keep their SI default.
+pub fn model_units(entities: &HashMap<StepId, IfcRawEntity>) -> ModelUnits {
+    // The project's unit assignment, or every unit when there is none
+    let mut assignments: Vec<&IfcRawEntity> = entities
+        .values()
+        .filter(|e| e.type_name == "IFCUNITASSIGNMENT")
+        .collect();
+    assignments.sort_by_key(|e| e.entity_id);
+    let mut unit_ids: Vec<StepId> = match assignments.first() {
+        Some(assignment) => parse_entity_refs(&assignment.raw_args),
+        None => entities
+            .values()
+            .filter(|e| matches!(e.type_name.as_str(), "IFCSIUNIT" | "IFCCONVERSIONBASEDUNIT"))
+            .map(|e| e.entity_id)
+            .collect(),
+    };
+    if assignments.is_empty() {
+        unit_ids.sort_unstable();
+    }
+
+    let mut units = ModelUnits::default();
+    let (mut length, mut area, mut angle) = (false, false, false);
+    for id in unit_ids {
+        let Some((kind, factor)) = unit_factor(id, entities, 0) else {
+            continue;
+        };
+        // The first unit of each kind wins
+        match kind.as_str() {
+            "LENGTHUNIT" if !length => {
+                units.length = LengthUnit::from_metres(factor);
+                length = true;
+            }
+            "AREAUNIT" if !area => {
+                units.area = AreaUnit::from_square_metres(factor);
+                area = true;
+            }
+            "PLANEANGLEUNIT" if !angle => {
+                units.angle = AngleUnit::from_radians(factor);
+                angle = true;
+            }
+            _ => {}
+        }
+    }
+    units
+}
+
+/// Unit type (e.g. `LENGTHUNIT`) of unit `id` and its size in metres,
+/// square metres or radians.
+fn unit_factor(
+    id: StepId,
+    entities: &HashMap<StepId, IfcRawEntity>,
+    depth: usize,
+) -> Option<(String, f64)> {
+    // Conversion units refer to other units; stop at cycles
+    if depth > 8 {
+        return None;
+    }
+    let unit = entities.get(&id)?;
+    let args = split_ifc_args(&unit.raw_args);
+    let kind = args.get(1)?.trim().trim_matches('.').to_string();
+    match unit.type_name.as_str() {
+        // IFCSIUNIT(Dimensions, UnitType, Prefix, Name)
+        "IFCSIUNIT" => {
+            let prefix = match args.get(2)?.trim().trim_matches('.') {
+                "$" | "" => 1.0,
+                prefix => si_prefix(prefix)?,
+            };
+            let factor = match args.get(3)?.trim().trim_matches('.') {
+                "METRE" | "RADIAN" => prefix,
+                "SQUARE_METRE" => prefix * prefix,
+                _ => return None,
+            };
+            Some((kind, factor))
+        }
+        // IFCCONVERSIONBASEDUNIT(Dimensions, UnitType, Name, ConversionFactor)
+        // IFCMEASUREWITHUNIT(ValueComponent, UnitComponent)
+        "IFCCONVERSIONBASEDUNIT" => {
+            let measure = entities.get(&extract_single_ref(args.get(3)?)?)?;
+            let measure_args = split_ifc_args(&measure.raw_args);
+            let value = typed_number(measure_args.first()?)?;
+            let (_, base) = unit_factor(
+                extract_single_ref(measure_args.get(1)?)?,
+                entities,
+                depth + 1,
+            )?;
+            Some((kind, value * base))
+        }
+        _ => None,
+    }
+}
+
+/// The number in a typed value such as `IFCLENGTHMEASURE(0.3048)`.
+fn typed_number(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let inner = match value.find('(') {
+        Some(open) => value[open + 1..].strip_suffix(')')?,
+        None => value,
+    };
+    inner.trim().parse().ok()
+}
+
+fn si_prefix(prefix: &str) -> Option<f64> {
+    Some(match prefix {
+        "EXA" => 1e18,
+        "PETA" => 1e15,
+        "TERA" => 1e12,
+        "GIGA" => 1e9,
+        "MEGA" => 1e6,
+        "KILO" => 1e3,
+        "HECTO" => 1e2,
+        "DECA" => 1e1,
+        "DECI" => 1e-1,
+        "CENTI" => 1e-2,
+        "MILLI" => 1e-3,
+        "MICRO" => 1e-6,
+        "NANO" => 1e-9,
+        "PICO" => 1e-12,
+        "FEMTO" => 1e-15,
+        "ATTO" => 1e-18,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ifc_query::IfcQuery;
+
+    fn units_of(data: &str) -> ModelUnits {
+        let model = format!(
+            "ISO-10303-21;\nHEADER;\nFILE_SCHEMA(('IFC4'));\nENDSEC;\nDATA;\n{}\nENDSEC;\nEND-ISO-10303-21;\n",
+            data
+        );
+        IfcQuery::from_reader(model.as_bytes()).unwrap().units()
+    }
+
+    #[test]
+    fn test_si_units() {
+        let units = units_of(
+            "#1= IFCSIUNIT(*,.LENGTHUNIT.,.MILLI.,.METRE.);
+#2= IFCSIUNIT(*,.AREAUNIT.,$,.SQUARE_METRE.);
+#3= IFCSIUNIT(*,.PLANEANGLEUNIT.,$,.RADIAN.);
+#4= IFCUNITASSIGNMENT((#1,#2,#3));",
+        );
+        assert_eq!(
+            units,
+            ModelUnits {
+                length: LengthUnit::Millimetre,
+                angle: AngleUnit::Radian,
+                area: AreaUnit::SquareMetre,
+            }
+        );
+        assert_eq!(units_of(""), ModelUnits::default());
+    }
+
+    #[test]
+    fn test_conversion_based_units() {
+        let units = units_of(
+            "#1= IFCSIUNIT(*,.LENGTHUNIT.,$,.METRE.);
+#2= IFCMEASUREWITHUNIT(IFCLENGTHMEASURE(0.3048),#1);
+#3= IFCDIMENSIONALEXPONENTS(1,0,0,0,0,0,0);
+#4= IFCCONVERSIONBASEDUNIT(#3,.LENGTHUNIT.,'FOOT',#2);
+#5= IFCSIUNIT(*,.PLANEANGLEUNIT.,$,.RADIAN.);
+#6= IFCMEASUREWITHUNIT(IFCPLANEANGLEMEASURE(0.0174532925199433),#5);
+#7= IFCCONVERSIONBASEDUNIT(#3,.PLANEANGLEUNIT.,'DEGREE',#6);
+#8= IFCUNITASSIGNMENT((#4,#7));",
+        );
+        assert_eq!(units.length, LengthUnit::Foot);
+        assert_eq!(units.angle, AngleUnit::Degree);
+        // Not assigned, so SI
+        assert_eq!(units.area, AreaUnit::SquareMetre);
+    }
+}
@@ -0,0 +1,477 @@
+//! Geometry validation of IFC files.
+//!
+//! Walks every product's representation down to the polygon loops and
+//! reports what the mesh pipeline would silently skip: dangling
+//! references, degenerate or non-planar faces, open shells and
+//! representation items that are not supported yet.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+
+use cst_core::{Result, StepId};
+use cst_math::plane::Plane;
+use serde::Serialize;
+
+use crate::ifc_geometry::{resolve_swept_solid, SweepTessellation};
+use crate::ifc_options::IfcPipelineOptions;
+use crate::ifc_reader::{
+    extract_single_ref, parse_entity_refs, parse_ifc_entities, parse_point, split_ifc_args,
+    IfcRawEntity, PRODUCT_TYPES,
+};
+use crate::ifc_summary::{for_each_statement, split_instance};
+use crate::ifc_units::model_units;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub enum IssueKind {
+    /// A reference to an entity id that does not exist in the file
+    MissingEntity,
+    /// A face with fewer than three points or zero area
+    DegenerateFace,
+    /// A face whose points stray from their best-fit plane
+    NonPlanarFace,
+    /// A closed shell with edges not shared by exactly two faces
+    OpenShell,
+    /// A representation item the mesh pipeline cannot resolve
+    UnsupportedItem,
+}
+
+impl fmt::Display for IssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            IssueKind::MissingEntity => "missing entity",
+            IssueKind::DegenerateFace => "degenerate face",
+            IssueKind::NonPlanarFace => "non-planar face",
+            IssueKind::OpenShell => "open shell",
+            IssueKind::UnsupportedItem => "unsupported item",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValidationIssue {
+    pub kind: IssueKind,
+    /// Entity the issue was found on
+    pub entity_id: StepId,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ValidationReport {
+    pub issues: Vec<ValidationIssue>,
+    pub products_checked: usize,
+    pub breps_checked: usize,
+}
+
+impl ValidationReport {
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Number of issues of each kind.
+    pub fn counts(&self) -> BTreeMap<IssueKind, usize> {
+        let mut counts = BTreeMap::new();
+        for issue in &self.issues {
+            *counts.entry(issue.kind).or_default() += 1;
+        }
+        counts
+    }
+}
+
+/// Validate the geometry of every product in the IFC file at `path`.
+///
+/// Faces whose points lie further than `planarity_tolerance` from their
+/// best-fit plane are reported as non-planar.
+pub fn validate_ifc(path: &Path, planarity_tolerance: f64) -> Result<ValidationReport> {
+    // The geometry parser keeps only the types it resolves, so collect
+    // every instance's type to tell unsupported items from missing ones
+    let mut all_types: HashMap<StepId, String> = HashMap::new();
+    for_each_statement(path, |statement| {
+        if let Some((id, type_name, _)) = split_instance(statement) {
+            all_types.insert(StepId(id), type_name.to_string());
+        }
+    })?;
+    let entities = parse_ifc_entities(path)?;
+
+    let tessellation = SweepTessellation::new(&model_units(&entities), &IfcPipelineOptions::default());
+    let mut validator = Validator {
+        entities: &entities,
+        all_types: &all_types,
+        tessellation,
+        planarity_tolerance,
+        visited: HashSet::new(),
+        report: ValidationReport::default(),
+    };
+    let mut products: Vec<&IfcRawEntity> = entities
+        .values()
+        .filter(|e| PRODUCT_TYPES.contains(&e.type_name.as_str()))
+        .collect();
+    products.sort_by_key(|e| e.entity_id);
+    for product in products {
+        validator.product(product);
+    }
+    let mut report = validator.report;
+    report
+        .issues
+        .sort_by_key(|issue| (issue.entity_id, issue.kind));
+    Ok(report)
+}
+
+struct Validator<'a> {
+    entities: &'a HashMap<StepId, IfcRawEntity>,
+    all_types: &'a HashMap<StepId, String>,
+    tessellation: SweepTessellation,
+    planarity_tolerance: f64,
+    /// Shape representations, breps and solids already checked (shared
+    /// by mapped items)
+    visited: HashSet<StepId>,
+    report: ValidationReport,
+}
+
+impl Validator<'_> {
+    fn issue(&mut self, kind: IssueKind, entity_id: StepId, message: String) {
+        self.report.issues.push(ValidationIssue {
+            kind,
+            entity_id,
+            message,
+        });
+    }
+
+    /// Look up `id`, referenced from `from`, reporting it when missing.
+    fn get(&mut self, id: StepId, from: StepId) -> Option<&IfcRawEntity> {
+        if !self.all_types.contains_key(&id) {
+            self.issue(
+                IssueKind::MissingEntity,
+                from,
+                format!("references {} which does not exist", id),
+            );
+            return None;
+        }
+        self.entities.get(&id)
+    }
+
+    fn product(&mut self, product: &IfcRawEntity) {
+        self.report.products_checked += 1;
+        // 6=Representation, may be $ for products without geometry
+        let args = split_ifc_args(&product.raw_args);
+        let Some(shape_id) = args.get(6).and_then(|arg| extract_single_ref(arg)) else {
+            return;
+        };
+        let Some(shape) = self.get(shape_id, product.entity_id) else {
+            return;
+        };
+        // IFCPRODUCTDEFINITIONSHAPE(Name, Description, Representations)
+        let reps = split_ifc_args(&shape.raw_args)
+            .get(2)
+            .map(|arg| parse_entity_refs(arg))
+            .unwrap_or_default();
+        for rep_id in reps {
+            self.shape_representation(rep_id, shape_id);
+        }
+    }
+
+    fn shape_representation(&mut self, rep_id: StepId, from: StepId) {
+        if !self.visited.insert(rep_id) {
+            return;
+        }
+        let Some(rep) = self.get(rep_id, from) else {
+            return;
+        };
+        // IFCSHAPEREPRESENTATION(Context, Identifier, Type, Items)
+        let items = split_ifc_args(&rep.raw_args)
+            .get(3)
+            .map(|arg| parse_entity_refs(arg))
+            .unwrap_or_default();
+        for item_id in items {
+            self.item(item_id, rep_id);
+        }
+    }
+
+    fn item(&mut self, item_id: StepId, rep_id: StepId) {
+        let Some(type_name) = self.all_types.get(&item_id) else {
+            self.get(item_id, rep_id);
+            return;
+        };
+        match type_name.as_str() {
+            "IFCFACETEDBREP" => self.brep(item_id),
+            "IFCEXTRUDEDAREASOLID" => self.swept_solid(item_id),
+            "IFCMAPPEDITEM" => {
+                // IFCMAPPEDITEM(MappingSource, MappingTarget) ->
+                // IFCREPRESENTATIONMAP(MappingOrigin, MappedRepresentation)
+                let Some(item) = self.entities.get(&item_id) else {
+                    return;
+                };
+                let Some(map_id) = split_ifc_args(&item.raw_args)
+                    .first()
+                    .and_then(|arg| extract_single_ref(arg))
+                else {
+                    return;
+                };
+                let Some(map) = self.get(map_id, item_id) else {
+                    return;
+                };
+                if let Some(mapped) = split_ifc_args(&map.raw_args)
+                    .get(1)
+                    .and_then(|arg| extract_single_ref(arg))
+                {
+                    self.shape_representation(mapped, map_id);
+                }
+            }
+            other => {
+                let message = format!("{} is not supported", other);
+                self.issue(IssueKind::UnsupportedItem, item_id, message);
+            }
+        }
+    }
+
+    fn swept_solid(&mut self, solid_id: StepId) {
+        if !self.visited.insert(solid_id) {
+            return;
+        }
+        let Some(solid) = self.entities.get(&solid_id) else {
+            return;
+        };
+        // (SweptArea, Position, ExtrudedDirection, Depth)
+        let refs: Vec<StepId> = split_ifc_args(&solid.raw_args)
+            .iter()
+            .take(3)
+            .filter_map(|arg| extract_single_ref(arg))
+            .collect();
+        for id in refs {
+            // Types the reader does not resolve are not parsed
+            if let (Some(type_name), None) = (self.all_types.get(&id), self.entities.get(&id)) {
+                let message = format!("{} is not supported", type_name);
+                self.issue(IssueKind::UnsupportedItem, solid_id, message);
+                return;
+            }
+            if self.get(id, solid_id).is_none() {
+                return;
+            }
+        }
+        if let Err(e) = resolve_swept_solid(solid_id, self.entities, &self.tessellation) {
+            let message = format!("IFCEXTRUDEDAREASOLID cannot be resolved: {}", e);
+            self.issue(IssueKind::UnsupportedItem, solid_id, message);
+        }
+    }
+
+    fn brep(&mut self, brep_id: StepId) {
+        if !self.visited.insert(brep_id) {
+            return;
+        }
+        self.report.breps_checked += 1;
+        let Some(brep) = self.entities.get(&brep_id) else {
+            return;
+        };
+        let Some(shell_id) = parse_entity_refs(&brep.raw_args).first().copied() else {
+            return;
+        };
+        let Some(shell) = self.get(shell_id, brep_id) else {
+            return;
+        };
+        let closed = shell.type_name == "IFCCLOSEDSHELL";
+
+        // Undirected edge use counts, keyed by point entity ids
+        let mut edges: HashMap<(StepId, StepId), usize> = HashMap::new();
+        for face_id in parse_entity_refs(&shell.raw_args) {
+            let Some(face) = self.get(face_id, shell_id) else {
+                continue;
+            };
+            for bound_id in parse_entity_refs(&face.raw_args) {
+                let Some(bound) = self.get(bound_id, face_id) else {
+                    continue;
+                };
+                let Some(loop_id) = split_ifc_args(&bound.raw_args)
+                    .first()
+                    .and_then(|arg| extract_single_ref(arg))
+                else {
+                    continue;
+                };
+                let Some(poly_loop) = self.get(loop_id, bound_id) else {
+                    continue;
+                };
+                let point_ids = parse_entity_refs(&poly_loop.raw_args);
+                self.polygon(face_id, loop_id, &point_ids);
+                for (i, &a) in point_ids.iter().enumerate() {
+                    let b = point_ids[(i + 1) % point_ids.len()];
+                    *edges.entry((a.min(b), a.max(b))).or_default() += 1;
+                }
+            }
+        }
+
+        let unmatched = edges.values().filter(|&&uses| uses != 2).count();
+        if closed && unmatched > 0 {
+            let message = format!("{} edges not shared by exactly two faces", unmatched);
+            self.issue(IssueKind::OpenShell, shell_id, message);
+        }
+    }
+
+    fn polygon(&mut self, face_id: StepId, loop_id: StepId, point_ids: &[StepId]) {
+        let mut points = Vec::with_capacity(point_ids.len());
+        for &point_id in point_ids {
+            if self.get(point_id, loop_id).is_none() {
+                return;
+            }
+            if let Some(point) = parse_point(point_id, self.entities) {
+                points.push(point);
+            }
+        }
+        match Plane::fit(&points) {
+            None => {
+                let message = format!("loop {} has {} usable points", loop_id, points.len());
+                self.issue(IssueKind::DegenerateFace, face_id, message);
+            }
+            Some(plane) => {
+                let deviation = plane.max_deviation(&points);
+                if deviation > self.planarity_tolerance {
+                    let message = format!(
+                        "loop {} deviates {:.3e} from its plane",
+                        loop_id, deviation
+                    );
+                    self.issue(IssueKind::NonPlanarFace, face_id, message);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn validate(data: &str) -> ValidationReport {
+        let content = format!(
+            "ISO-10303-21;\nHEADER;\nFILE_SCHEMA(('IFC2X3'));\nENDSEC;\nDATA;\n{}ENDSEC;\nEND-ISO-10303-21;\n",
+            data
+        );
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(content.as_bytes()).unwrap();
+        temp_file.flush().unwrap();
+        validate_ifc(temp_file.path(), 1e-6).unwrap()
+    }
+
+    /// Closed tetrahedron shell, points #1-#4, faces #21-#24
+    const TETRAHEDRON: &str = "#1= IFCCARTESIANPOINT((0.,0.,0.));
+#2= IFCCARTESIANPOINT((1.,0.,0.));
+#3= IFCCARTESIANPOINT((0.,1.,0.));
+#4= IFCCARTESIANPOINT((0.,0.,1.));
+#11= IFCPOLYLOOP((#1,#3,#2));
+#12= IFCPOLYLOOP((#1,#2,#4));
+#13= IFCPOLYLOOP((#2,#3,#4));
+#14= IFCPOLYLOOP((#3,#1,#4));
+#15= IFCFACEOUTERBOUND(#11,.T.);
+#16= IFCFACEOUTERBOUND(#12,.T.);
+#17= IFCFACEOUTERBOUND(#13,.T.);
+#18= IFCFACEOUTERBOUND(#14,.T.);
+#21= IFCFACE((#15));
+#22= IFCFACE((#16));
+#23= IFCFACE((#17));
+#24= IFCFACE((#18));
+";
+
+    #[test]
+    fn test_valid_model_is_clean() {
+        let report = validate(&format!(
+            "{}#30= IFCCLOSEDSHELL((#21,#22,#23,#24));
+#31= IFCFACETEDBREP(#30);
+#32= IFCSHAPEREPRESENTATION($,'Body','Brep',(#31));
+#33= IFCPRODUCTDEFINITIONSHAPE($,$,(#32));
+#40= IFCCOLUMN('c',$,'Column',$,$,$,#33,$);
+",
+            TETRAHEDRON
+        ));
+        assert!(report.is_clean(), "{:?}", report.issues);
+        assert_eq!(report.products_checked, 1);
+        assert_eq!(report.breps_checked, 1);
+    }
+
+    #[test]
+    fn test_reports_open_shell_and_missing_entity() {
+        // One face dropped from the shell, one item pointing nowhere
+        let report = validate(&format!(
+            "{}#30= IFCCLOSEDSHELL((#21,#22,#23));
+#31= IFCFACETEDBREP(#30);
+#32= IFCSHAPEREPRESENTATION($,'Body','Brep',(#31,#99));
+#33= IFCPRODUCTDEFINITIONSHAPE($,$,(#32));
+#40= IFCCOLUMN('c',$,'Column',$,$,$,#33,$);
+",
+            TETRAHEDRON
+        ));
+        let counts = report.counts();
+        assert_eq!(counts[&IssueKind::OpenShell], 1);
+        assert_eq!(report.issues[0].entity_id, StepId(30));
+        assert_eq!(counts[&IssueKind::MissingEntity], 1);
+        let missing = &report.issues[1];
+        assert_eq!(missing.entity_id, StepId(32));
+        assert!(missing.message.contains("#99"));
+    }
+
+    #[test]
+    fn test_reports_degenerate_nonplanar_and_unsupported() {
+        let report = validate(
+            "#1= IFCCARTESIANPOINT((0.,0.,0.));
+#2= IFCCARTESIANPOINT((1.,0.,0.));
+#3= IFCCARTESIANPOINT((1.,1.,0.5));
+#4= IFCCARTESIANPOINT((0.,1.,0.));
+#5= IFCCARTESIANPOINT((2.,0.,0.));
+#11= IFCPOLYLOOP((#1,#2,#3,#4));
+#12= IFCPOLYLOOP((#1,#2,#5));
+#15= IFCFACEOUTERBOUND(#11,.T.);
+#16= IFCFACEOUTERBOUND(#12,.T.);
+#21= IFCFACE((#15));
+#22= IFCFACE((#16));
+#30= IFCOPENSHELL((#21,#22));
+#31= IFCFACETEDBREP(#30);
+#34= IFCREVOLVEDAREASOLID(#50,$,#51,#52);
+#32= IFCSHAPEREPRESENTATION($,'Body','Brep',(#31,#34));
+#33= IFCPRODUCTDEFINITIONSHAPE($,$,(#32));
+#40= IFCSLAB('s',$,'Slab',$,$,$,#33,$);
+",
+        );
+        let kinds: Vec<(IssueKind, u64)> = report
+            .issues
+            .iter()
+            .map(|issue| (issue.kind, issue.entity_id.value()))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (IssueKind::NonPlanarFace, 21),
+                (IssueKind::DegenerateFace, 22),
+                (IssueKind::UnsupportedItem, 34),
+            ]
+        );
+        assert!(report.issues[2].message.contains("IFCREVOLVEDAREASOLID"));
+    }
+
+    #[test]
+    fn test_checks_extruded_solids() {
+        let report = validate(
+            "#1= IFCCARTESIANPOINT((0.,0.,0.));
+#2= IFCDIRECTION((0.,0.,1.));
+#3= IFCAXIS2PLACEMENT3D(#1,$,$);
+#4= IFCRECTANGLEPROFILEDEF(.AREA.,$,$,200.,100.);
+#5= IFCISHAPEPROFILEDEF(.AREA.,$,$,200.,300.,10.,15.,$);
+#10= IFCEXTRUDEDAREASOLID(#4,#3,#2,3000.);
+#11= IFCEXTRUDEDAREASOLID(#5,#3,#2,3000.);
+#12= IFCEXTRUDEDAREASOLID(#99,#3,#2,3000.);
+#20= IFCSHAPEREPRESENTATION($,'Body','SweptSolid',(#10,#11,#12));
+#21= IFCPRODUCTDEFINITIONSHAPE($,$,(#20));
+#30= IFCBEAM('b',$,'Beam',$,$,$,#21,$);
+",
+        );
+        let kinds: Vec<(IssueKind, u64)> = report
+            .issues
+            .iter()
+            .map(|issue| (issue.kind, issue.entity_id.value()))
+            .collect();
+        // The rectangle resolves; the I-shape profile is not supported yet
+        assert_eq!(
+            kinds,
+            vec![(IssueKind::UnsupportedItem, 11), (IssueKind::MissingEntity, 12)]
+        );
+        assert!(report.issues[0].message.contains("IFCISHAPEPROFILEDEF"));
+    }
+}
@@ -1,7 +1,22 @@
 pub mod step_lexer;
 pub mod step_parser;
+pub mod ifc_arena;
+pub mod ifc_assembly;
+pub mod ifc_curve;
 pub mod ifc_entities;
 pub mod ifc_geometry;
 pub mod ifc_spatial;
+pub mod ifc_openings;
+pub mod ifc_options;
+pub mod ifc_progress;
 pub mod ifc_reader;
+pub mod ifc_query;
+pub mod ifc_json;
+pub mod ifc_csv;
+pub mod ifc_cache;
+pub mod ifc_summary;
+pub mod ifc_surfaces;
+pub mod ifc_units;
+pub mod ifc_validate;
 pub mod ifc_to_mesh;
+pub mod ifc_topology;
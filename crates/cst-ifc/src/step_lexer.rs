@@ -1,8 +1,13 @@
 //! STEP Physical File (SPF) lexer / tokenizer.
 //!
 //! Converts raw IFC text into a flat stream of [`Token`]s that the parser consumes.
+//! Tokens borrow their text from the input; only keywords that are not
+//! upper case and strings with escaped quotes are copied.
 
-use cst_core::{CstError, Result};
+use std::borrow::Cow;
+use std::fmt;
+
+use cst_core::{CstError, Result, SourceLocation, StepId};
 
 // ---------------------------------------------------------------------------
 // Token types
@@ -10,19 +15,19 @@ use cst_core::{CstError, Result};
 
 /// A single lexical token from a STEP Physical File.
 #[derive(Debug, Clone, PartialEq)]
-pub enum Token {
+pub enum Token<'a> {
     /// Entity instance id, e.g. `#123`
     EntityId(u64),
     /// Upper-case keyword, e.g. `IFCWALL`, `FILE_DESCRIPTION`
-    Keyword(String),
+    Keyword(Cow<'a, str>),
     /// Single-quoted string literal, e.g. `'hello'`
-    String(String),
+    String(Cow<'a, str>),
     /// Integer literal, e.g. `42`, `-7`
     Integer(i64),
     /// Real (floating-point) literal, e.g. `3.14`, `1.5E-3`
     Real(f64),
     /// Enumeration value, e.g. `.ELEMENT.`
-    Enum(String),
+    Enum(&'a str),
     /// Boolean `.T.` or `.F.`
     Bool(bool),
     /// Derived attribute `*`
@@ -37,16 +42,56 @@ pub enum Token {
     Equals,
 }
 
+impl fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::EntityId(id) => write!(f, "#{id}"),
+            Token::Keyword(k) => write!(f, "{k}"),
+            Token::String(s) => write!(f, "'{s}'"),
+            Token::Integer(v) => write!(f, "{v}"),
+            Token::Real(v) => write!(f, "{v:?}"),
+            Token::Enum(e) => write!(f, ".{e}."),
+            Token::Bool(b) => f.write_str(if *b { ".T." } else { ".F." }),
+            Token::Derived => f.write_str("'*'"),
+            Token::Null => f.write_str("'$'"),
+            Token::OpenParen => f.write_str("'('"),
+            Token::CloseParen => f.write_str("')'"),
+            Token::Comma => f.write_str("','"),
+            Token::Semicolon => f.write_str("';'"),
+            Token::Equals => f.write_str("'='"),
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Lexer
 // ---------------------------------------------------------------------------
 
 /// Tokenize a STEP Physical File string into a vector of tokens.
-pub fn tokenize(input: &str) -> Result<Vec<Token>> {
+pub fn tokenize(input: &str) -> Result<Vec<Token<'_>>> {
+    Ok(tokenize_with_offsets(input)?.0)
+}
+
+/// Tokenize `input`, also returning the byte offset each token starts at.
+/// Errors are [`CstError::Syntax`] with the line and column of the fault.
+pub fn tokenize_with_offsets(input: &str) -> Result<(Vec<Token<'_>>, Vec<usize>)> {
     let bytes = input.as_bytes();
     let len = bytes.len();
     let mut pos: usize = 0;
     let mut tokens = Vec::new();
+    let mut offsets = Vec::new();
+    // Id of the entity instance being tokenized, for error messages
+    let mut entity: Option<StepId> = None;
+    let error = |at: usize, entity: Option<StepId>, expected: &str, found: String| CstError::Syntax {
+        location: SourceLocation::from_offset(input, at),
+        entity,
+        expected: expected.to_string(),
+        found,
+    };
+    let found_at = |at: usize| match input.get(at..).and_then(|rest| rest.chars().next()) {
+        Some(c) => format!("'{c}'"),
+        None => "end of file".to_string(),
+    };
 
     while pos < len {
         // Skip whitespace
@@ -67,6 +112,7 @@ pub fn tokenize(input: &str) -> Result<Vec<Token>> {
             continue;
         }
 
+        offsets.push(pos);
         match bytes[pos] {
             b'(' => {
                 tokens.push(Token::OpenParen);
@@ -105,39 +151,46 @@ pub fn tokenize(input: &str) -> Result<Vec<Token>> {
                     pos += 1;
                 }
                 if start == pos {
-                    return Err(CstError::Parse(
-                        "Expected digits after '#'".into(),
-                    ));
+                    return Err(error(pos, entity, "digits after '#'", found_at(pos)));
+                }
+                let id: u64 = input[start..pos].parse().map_err(|_| {
+                    error(start - 1, entity, "entity id", format!("#{}", &input[start..pos]))
+                })?;
+                // An id at the start of a statement names the entity
+                if matches!(tokens.last(), None | Some(Token::Semicolon)) {
+                    entity = Some(StepId(id));
                 }
-                let id: u64 = input[start..pos]
-                    .parse()
-                    .map_err(|e| CstError::Parse(format!("Invalid entity id: {e}")))?;
                 tokens.push(Token::EntityId(id));
             }
 
             // String literal: '...'  ('' is escaped single-quote inside)
             b'\'' => {
+                let start = pos;
                 pos += 1;
-                let mut s = std::string::String::new();
+                let mut escaped = false;
                 loop {
                     if pos >= len {
-                        return Err(CstError::Parse("Unterminated string literal".into()));
+                        return Err(error(start, entity, "closing quote of string", found_at(pos)));
                     }
                     if bytes[pos] == b'\'' {
                         // Check for escaped ''
                         if pos + 1 < len && bytes[pos + 1] == b'\'' {
-                            s.push('\'');
+                            escaped = true;
                             pos += 2;
                         } else {
                             pos += 1; // closing quote
                             break;
                         }
                     } else {
-                        s.push(bytes[pos] as char);
                         pos += 1;
                     }
                 }
-                tokens.push(Token::String(s));
+                let text = &input[start + 1..pos - 1];
+                tokens.push(Token::String(if escaped {
+                    Cow::Owned(text.replace("''", "'"))
+                } else {
+                    Cow::Borrowed(text)
+                }));
             }
 
             // Enum or Bool: .XXX.
@@ -148,14 +201,14 @@ pub fn tokenize(input: &str) -> Result<Vec<Token>> {
                     pos += 1;
                 }
                 if pos >= len {
-                    return Err(CstError::Parse("Unterminated enum value".into()));
+                    return Err(error(start - 1, entity, "closing '.' of enum", found_at(pos)));
                 }
                 let val = &input[start..pos];
                 pos += 1; // skip closing '.'
                 match val {
                     "T" => tokens.push(Token::Bool(true)),
                     "F" => tokens.push(Token::Bool(false)),
-                    _ => tokens.push(Token::Enum(val.to_string())),
+                    _ => tokens.push(Token::Enum(val)),
                 }
             }
 
@@ -196,12 +249,12 @@ pub fn tokenize(input: &str) -> Result<Vec<Token>> {
                 if is_real {
                     let v: f64 = text
                         .parse()
-                        .map_err(|e| CstError::Parse(format!("Invalid real: {e}")))?;
+                        .map_err(|_| error(start, entity, "real", format!("'{text}'")))?;
                     tokens.push(Token::Real(v));
                 } else {
                     let v: i64 = text
                         .parse()
-                        .map_err(|e| CstError::Parse(format!("Invalid integer: {e}")))?;
+                        .map_err(|_| error(start, entity, "integer", format!("'{text}'")))?;
                     tokens.push(Token::Integer(v));
                 }
             }
@@ -216,20 +269,21 @@ pub fn tokenize(input: &str) -> Result<Vec<Token>> {
                 {
                     pos += 1;
                 }
-                let word = input[start..pos].to_uppercase();
-                tokens.push(Token::Keyword(word));
+                let word = &input[start..pos];
+                tokens.push(Token::Keyword(if word.bytes().any(|b| b.is_ascii_lowercase()) {
+                    Cow::Owned(word.to_ascii_uppercase())
+                } else {
+                    Cow::Borrowed(word)
+                }));
             }
 
-            other => {
-                return Err(CstError::Parse(format!(
-                    "Unexpected character '{}' at position {pos}",
-                    other as char
-                )));
+            _ => {
+                return Err(error(pos, entity, "a value or delimiter", found_at(pos)));
             }
         }
     }
 
-    Ok(tokens)
+    Ok((tokens, offsets))
 }
 
 // ---------------------------------------------------------------------------
@@ -258,6 +312,15 @@ mod tests {
         assert_eq!(tokens, vec![Token::String("it's".into())]);
     }
 
+    #[test]
+    fn test_tokens_borrow_input() {
+        let tokens = tokenize("IFCWALL('Wand ä',.ELEMENT.) ifcslab").unwrap();
+        assert!(matches!(tokens[0], Token::Keyword(Cow::Borrowed("IFCWALL"))));
+        assert!(matches!(tokens[2], Token::String(Cow::Borrowed("Wand ä"))));
+        assert_eq!(tokens[4], Token::Enum("ELEMENT"));
+        assert!(matches!(&tokens[6], Token::Keyword(Cow::Owned(k)) if k == "IFCSLAB"));
+    }
+
     #[test]
     fn test_integer() {
         let tokens = tokenize("42").unwrap();
@@ -271,6 +334,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_real() {
         let tokens = tokenize("3.14").unwrap();
         assert_eq!(tokens, vec![Token::Real(3.14)]);
@@ -292,7 +356,7 @@ mod tests {
     #[test]
     fn test_enum() {
         let tokens = tokenize(".ELEMENT.").unwrap();
-        assert_eq!(tokens, vec![Token::Enum("ELEMENT".into())]);
+        assert_eq!(tokens, vec![Token::Enum("ELEMENT")]);
     }
 
     #[test]
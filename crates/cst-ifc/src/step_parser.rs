@@ -1,9 +1,13 @@
 //! STEP Physical File parser.
 //!
-//! Consumes [`Token`]s from the lexer and produces a structured [`StepFile`].
+//! Consumes [`Token`]s from the lexer and produces a structured [`StepFile`],
+//! or, with [`parse_step_iter`], one [`StepEntity`] at a time.
 
-use crate::step_lexer::Token;
-use cst_core::{CstError, Result};
+use std::io::BufRead;
+
+use crate::ifc_arena::{TypeName, TypeNames};
+use crate::step_lexer::{tokenize_with_offsets, Token};
+use cst_core::{CstError, DiagnosticCode, Diagnostics, Result, SourceLocation, StepId};
 
 // ---------------------------------------------------------------------------
 // AST types
@@ -17,8 +21,10 @@ pub enum StepAttribute {
     String(String),
     Bool(bool),
     Enum(String),
-    EntityRef(u64),
+    EntityRef(StepId),
     List(Vec<StepAttribute>),
+    /// A value wrapped in its defined type, e.g. `IFCLABEL('Door')`.
+    Typed(TypeName, Box<StepAttribute>),
     Null,
     Derived,
 }
@@ -26,8 +32,8 @@ pub enum StepAttribute {
 /// A parsed STEP entity, e.g. `#1 = IFCPROJECT(...)`.
 #[derive(Debug, Clone)]
 pub struct StepEntity {
-    pub entity_id: u64,
-    pub type_name: String,
+    pub entity_id: StepId,
+    pub type_name: TypeName,
     pub attributes: Vec<StepAttribute>,
 }
 
@@ -39,55 +45,131 @@ pub struct StepHeader {
     pub file_schema: Vec<String>,
 }
 
+impl StepHeader {
+    /// Store the strings of header entry `keyword`; unknown entries are
+    /// ignored.
+    fn set(&mut self, keyword: &str, strings: Vec<String>) {
+        match keyword {
+            "FILE_DESCRIPTION" => self.description = strings,
+            "FILE_NAME" => self.file_name = strings,
+            "FILE_SCHEMA" => self.file_schema = strings,
+            _ => {}
+        }
+    }
+}
+
 /// A complete parsed STEP file.
 #[derive(Debug, Clone)]
 pub struct StepFile {
     pub header: StepHeader,
     pub entities: Vec<StepEntity>,
+    /// Problems that did not stop parsing
+    pub diagnostics: Diagnostics,
 }
 
+/// A token between entities in the data section was ignored.
+pub const SKIPPED_TOKEN: DiagnosticCode =
+    DiagnosticCode::new("skipped-token", "stray tokens skipped in the data section");
+
 // ---------------------------------------------------------------------------
 // Parser
 // ---------------------------------------------------------------------------
 
-struct Parser {
-    tokens: Vec<Token>,
+struct Parser<'a> {
+    input: &'a str,
+    tokens: Vec<Token<'a>>,
+    /// Byte offset of each token in `input`
+    offsets: Vec<usize>,
+    /// Where `input` starts in the file
+    start: SourceLocation,
     pos: usize,
+    /// Id of the entity being parsed, for error messages
+    entity: Option<StepId>,
+    /// Type names of the entities parsed so far
+    names: TypeNames,
+    diagnostics: Diagnostics,
 }
 
-impl Parser {
-    fn new(tokens: Vec<Token>) -> Self {
-        Self { tokens, pos: 0 }
+impl<'a> Parser<'a> {
+    fn new(input: &'a str, tokens: Vec<Token<'a>>, offsets: Vec<usize>) -> Self {
+        Self {
+            input,
+            tokens,
+            offsets,
+            start: SourceLocation::START,
+            pos: 0,
+            entity: None,
+            names: TypeNames::new(),
+            diagnostics: Diagnostics::new(),
+        }
+    }
+
+    /// A syntax error at token `index`, or at the end of the input when
+    /// the tokens ran out.
+    fn syntax_error(&self, index: usize, expected: &str) -> CstError {
+        let (offset, found) = match self.tokens.get(index) {
+            Some(token) => (self.offsets[index], token.to_string()),
+            None => (self.input.len(), "end of file".to_string()),
+        };
+        CstError::Syntax {
+            location: self.location(offset),
+            entity: self.entity,
+            expected: expected.to_string(),
+            found,
+        }
+    }
+
+    /// The file location of byte `offset` of the input.
+    fn location(&self, offset: usize) -> SourceLocation {
+        self.start.advance(SourceLocation::from_offset(self.input, offset))
+    }
+
+    /// Record the next token as skipped and move past it.
+    fn skip_token(&mut self) -> Result<()> {
+        let location = self.location(self.offsets[self.pos]);
+        let message = format!("Skipped {} at {}", self.tokens[self.pos], location);
+        self.diagnostics.warn(SKIPPED_TOKEN, None, message);
+        self.advance()?;
+        Ok(())
     }
 
-    fn peek(&self) -> Option<&Token> {
+    /// A syntax error at the token just consumed.
+    fn unexpected(&self, expected: &str) -> CstError {
+        self.syntax_error(self.pos - 1, expected)
+    }
+
+    fn peek(&self) -> Option<&Token<'a>> {
         self.tokens.get(self.pos)
     }
 
-    fn advance(&mut self) -> Result<&Token> {
+    fn advance(&mut self) -> Result<&Token<'a>> {
         if self.pos >= self.tokens.len() {
-            return Err(CstError::Parse("Unexpected end of tokens".into()));
+            return Err(self.syntax_error(self.pos, "more input"));
         }
         let tok = &self.tokens[self.pos];
         self.pos += 1;
         Ok(tok)
     }
 
+    /// The type name spelled by keyword token `index`.
+    fn type_name(&mut self, index: usize) -> TypeName {
+        match &self.tokens[index] {
+            Token::Keyword(k) => self.names.get(k),
+            _ => unreachable!(),
+        }
+    }
+
     fn expect_keyword(&mut self, kw: &str) -> Result<()> {
         match self.advance()? {
             Token::Keyword(k) if k == kw => Ok(()),
-            other => Err(CstError::Parse(format!(
-                "Expected keyword '{kw}', got {other:?}"
-            ))),
+            _ => Err(self.unexpected(kw)),
         }
     }
 
     fn expect_semicolon(&mut self) -> Result<()> {
         match self.advance()? {
             Token::Semicolon => Ok(()),
-            other => Err(CstError::Parse(format!(
-                "Expected ';', got {other:?}"
-            ))),
+            _ => Err(self.unexpected("';'")),
         }
     }
 
@@ -117,7 +199,7 @@ impl Parser {
                 }
                 _ => {
                     // Skip unrecognized tokens in data section
-                    self.advance()?;
+                    self.skip_token()?;
                 }
             }
         }
@@ -126,7 +208,11 @@ impl Parser {
         self.expect_keyword("END-ISO-10303-21")?;
         self.expect_semicolon()?;
 
-        Ok(StepFile { header, entities })
+        Ok(StepFile {
+            header,
+            entities,
+            diagnostics: std::mem::take(&mut self.diagnostics),
+        })
     }
 
     /// Parse the HEADER section.
@@ -151,12 +237,7 @@ impl Parser {
                     let strings = self.collect_header_strings()?;
                     self.expect_semicolon()?;
 
-                    match kw.as_str() {
-                        "FILE_DESCRIPTION" => header.description = strings,
-                        "FILE_NAME" => header.file_name = strings,
-                        "FILE_SCHEMA" => header.file_schema = strings,
-                        _ => {} // ignore unknown header entries
-                    }
+                    header.set(&kw, strings);
                 }
                 _ => {
                     self.advance()?;
@@ -187,8 +268,8 @@ impl Parser {
                     }
                 }
                 Some(Token::String(_)) => {
-                    if let Token::String(s) = self.advance()?.clone() {
-                        strings.push(s);
+                    if let Token::String(s) = self.advance()? {
+                        strings.push(s.to_string());
                     }
                 }
                 _ => {
@@ -202,42 +283,27 @@ impl Parser {
     /// Parse a single entity: `#id = TYPE_NAME(attr, attr, ...);`
     fn parse_entity(&mut self) -> Result<StepEntity> {
         let entity_id = match self.advance()? {
-            Token::EntityId(id) => *id,
-            other => {
-                return Err(CstError::Parse(format!(
-                    "Expected entity id, got {other:?}"
-                )))
-            }
+            Token::EntityId(id) => StepId(*id),
+            _ => return Err(self.unexpected("entity id")),
         };
+        self.entity = Some(entity_id);
 
         // =
         match self.advance()? {
             Token::Equals => {}
-            other => {
-                return Err(CstError::Parse(format!(
-                    "Expected '=', got {other:?}"
-                )))
-            }
+            _ => return Err(self.unexpected("'='")),
         }
 
         // TYPE_NAME
         let type_name = match self.advance()? {
-            Token::Keyword(k) => k.clone(),
-            other => {
-                return Err(CstError::Parse(format!(
-                    "Expected type keyword, got {other:?}"
-                )))
-            }
+            Token::Keyword(_) => self.type_name(self.pos - 1),
+            _ => return Err(self.unexpected("type keyword")),
         };
 
         // (attributes)
         match self.advance()? {
             Token::OpenParen => {}
-            other => {
-                return Err(CstError::Parse(format!(
-                    "Expected '(' after type name, got {other:?}"
-                )))
-            }
+            _ => return Err(self.unexpected("'(' after type name")),
         }
 
         let attributes = self.parse_attribute_list()?;
@@ -245,15 +311,12 @@ impl Parser {
         // closing )
         match self.advance()? {
             Token::CloseParen => {}
-            other => {
-                return Err(CstError::Parse(format!(
-                    "Expected ')' closing entity, got {other:?}"
-                )))
-            }
+            _ => return Err(self.unexpected("')' closing entity")),
         }
 
         self.expect_semicolon()?;
 
+        self.entity = None;
         Ok(StepEntity {
             entity_id,
             type_name,
@@ -284,43 +347,43 @@ impl Parser {
     fn parse_attribute(&mut self) -> Result<StepAttribute> {
         match self.peek() {
             Some(Token::Integer(_)) => {
-                if let Token::Integer(v) = self.advance()?.clone() {
-                    Ok(StepAttribute::Integer(v))
+                if let Token::Integer(v) = self.advance()? {
+                    Ok(StepAttribute::Integer(*v))
                 } else {
                     unreachable!()
                 }
             }
             Some(Token::Real(_)) => {
-                if let Token::Real(v) = self.advance()?.clone() {
-                    Ok(StepAttribute::Real(v))
+                if let Token::Real(v) = self.advance()? {
+                    Ok(StepAttribute::Real(*v))
                 } else {
                     unreachable!()
                 }
             }
             Some(Token::String(_)) => {
-                if let Token::String(s) = self.advance()?.clone() {
-                    Ok(StepAttribute::String(s))
+                if let Token::String(v) = self.advance()? {
+                    Ok(StepAttribute::String(v.to_string()))
                 } else {
                     unreachable!()
                 }
             }
             Some(Token::Bool(_)) => {
-                if let Token::Bool(b) = self.advance()?.clone() {
-                    Ok(StepAttribute::Bool(b))
+                if let Token::Bool(v) = self.advance()? {
+                    Ok(StepAttribute::Bool(*v))
                 } else {
                     unreachable!()
                 }
             }
             Some(Token::Enum(_)) => {
-                if let Token::Enum(e) = self.advance()?.clone() {
-                    Ok(StepAttribute::Enum(e))
+                if let Token::Enum(v) = self.advance()? {
+                    Ok(StepAttribute::Enum(v.to_string()))
                 } else {
                     unreachable!()
                 }
             }
             Some(Token::EntityId(_)) => {
-                if let Token::EntityId(id) = self.advance()?.clone() {
-                    Ok(StepAttribute::EntityRef(id))
+                if let Token::EntityId(v) = self.advance()? {
+                    Ok(StepAttribute::EntityRef(StepId(*v)))
                 } else {
                     unreachable!()
                 }
@@ -333,33 +396,221 @@ impl Parser {
                 self.advance()?;
                 Ok(StepAttribute::Derived)
             }
+            Some(Token::Keyword(_)) => {
+                self.advance()?;
+                let type_name = self.type_name(self.pos - 1);
+                match self.advance()? {
+                    Token::OpenParen => {}
+                    _ => return Err(self.unexpected(&format!("'(' after {type_name}"))),
+                }
+                let value = self.parse_attribute()?;
+                match self.advance()? {
+                    Token::CloseParen => {}
+                    _ => return Err(self.unexpected(&format!("')' closing {type_name}"))),
+                }
+                Ok(StepAttribute::Typed(type_name, Box::new(value)))
+            }
             Some(Token::OpenParen) => {
                 self.advance()?; // consume '('
                 let items = self.parse_attribute_list()?;
                 match self.advance()? {
                     Token::CloseParen => {}
-                    other => {
-                        return Err(CstError::Parse(format!(
-                            "Expected ')' closing list, got {other:?}"
-                        )))
-                    }
+                    _ => return Err(self.unexpected("')' closing list")),
                 }
                 Ok(StepAttribute::List(items))
             }
-            other => Err(CstError::Parse(format!(
-                "Unexpected token in attribute: {other:?}"
-            ))),
+            _ => Err(self.syntax_error(self.pos, "attribute value")),
         }
     }
 }
 
 /// Parse a STEP Physical File string into a structured [`StepFile`].
 pub fn parse_step(input: &str) -> Result<StepFile> {
-    let tokens = crate::step_lexer::tokenize(input)?;
-    let mut parser = Parser::new(tokens);
+    let (tokens, offsets) = tokenize_with_offsets(input)?;
+    let mut parser = Parser::new(input, tokens, offsets);
     parser.parse_file()
 }
 
+/// Parse a STEP Physical File from `reader` one entity at a time, for
+/// single-pass scans (type counts, GUID indexes, summaries) of files too
+/// large to hold as a [`StepFile`]. Only the current statement is kept in
+/// memory.
+///
+/// The header is available from [`StepEntityIter::header`] once the first
+/// entity was read. The iterator stops after the first error.
+pub fn parse_step_iter<R: BufRead>(reader: R) -> StepEntityIter<R> {
+    StepEntityIter {
+        reader,
+        statement: Vec::new(),
+        start: SourceLocation::START,
+        header: StepHeader::default(),
+        names: TypeNames::new(),
+        diagnostics: Diagnostics::new(),
+        in_data: false,
+        done: false,
+    }
+}
+
+/// Iterator returned by [`parse_step_iter`].
+pub struct StepEntityIter<R> {
+    reader: R,
+    /// Bytes of the statement being read, up to and including its `;`
+    statement: Vec<u8>,
+    /// Where `statement` starts in the file
+    start: SourceLocation,
+    header: StepHeader,
+    /// Type names of the entities read so far
+    names: TypeNames,
+    diagnostics: Diagnostics,
+    in_data: bool,
+    done: bool,
+}
+
+/// What the statement reader is inside of.
+#[derive(Clone, Copy, PartialEq)]
+enum Scan {
+    Code,
+    String,
+    Comment,
+}
+
+impl<R: BufRead> StepEntityIter<R> {
+    /// The header entries read so far.
+    pub fn header(&self) -> &StepHeader {
+        &self.header
+    }
+
+    /// Problems that did not stop parsing, so far.
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
+    /// The file location of byte `offset` of the current statement.
+    fn location(&self, offset: usize) -> SourceLocation {
+        self.start.advance(SourceLocation::from_bytes(&self.statement, offset))
+    }
+
+    /// Read up to and including the next `;` outside strings and comments.
+    /// `false` once only whitespace is left.
+    fn read_statement(&mut self) -> Result<bool> {
+        self.start = self.location(self.statement.len());
+        self.statement.clear();
+        let mut scan = Scan::Code;
+        let mut last = 0u8;
+        loop {
+            let available = self.reader.fill_buf()?;
+            if available.is_empty() {
+                return Ok(!self.statement.iter().all(u8::is_ascii_whitespace));
+            }
+            let mut used = available.len();
+            let mut complete = false;
+            for (i, &b) in available.iter().enumerate() {
+                let previous = std::mem::replace(&mut last, b);
+                match (scan, b) {
+                    (Scan::Code, b'\'') => scan = Scan::String,
+                    (Scan::Code, b'*') if previous == b'/' => {
+                        scan = Scan::Comment;
+                        // The '*' of "/*" does not also start "*/"
+                        last = 0;
+                    }
+                    (Scan::Code, b';') => {
+                        used = i + 1;
+                        complete = true;
+                        break;
+                    }
+                    (Scan::String, b'\'') => scan = Scan::Code,
+                    (Scan::Comment, b'/') if previous == b'*' => {
+                        scan = Scan::Code;
+                        last = 0;
+                    }
+                    _ => {}
+                }
+            }
+            self.statement.extend_from_slice(&available[..used]);
+            self.reader.consume(used);
+            if complete {
+                return Ok(true);
+            }
+        }
+    }
+
+    /// Read statements until the next entity, handling header entries and
+    /// section keywords on the way.
+    fn next_entity(&mut self) -> Result<Option<StepEntity>> {
+        loop {
+            if !self.read_statement()? {
+                return Err(CstError::Syntax {
+                    location: self.location(self.statement.len()),
+                    entity: None,
+                    expected: "END-ISO-10303-21".to_string(),
+                    found: "end of file".to_string(),
+                });
+            }
+            let input = std::str::from_utf8(&self.statement).map_err(|e| CstError::Syntax {
+                location: self.location(e.valid_up_to()),
+                entity: None,
+                expected: "UTF-8 text".to_string(),
+                found: "invalid byte".to_string(),
+            })?;
+            let (tokens, offsets) = tokenize_with_offsets(input).map_err(|e| match e {
+                CstError::Syntax { location, entity, expected, found } => CstError::Syntax {
+                    location: self.start.advance(location),
+                    entity,
+                    expected,
+                    found,
+                },
+                other => other,
+            })?;
+            let mut parser = Parser::new(input, tokens, offsets);
+            parser.start = self.start;
+
+            match parser.peek() {
+                Some(Token::Keyword(k)) if k == "END-ISO-10303-21" => return Ok(None),
+                Some(Token::Keyword(k)) if k == "DATA" => self.in_data = true,
+                Some(Token::Keyword(k)) if k == "ENDSEC" => self.in_data = false,
+                Some(Token::Keyword(k)) if k == "ISO-10303-21" || k == "HEADER" => {}
+                Some(Token::Keyword(k)) if !self.in_data => {
+                    let keyword = k.to_string();
+                    parser.advance()?;
+                    let strings = parser.collect_header_strings()?;
+                    self.header.set(&keyword, strings);
+                }
+                _ => {
+                    // Skip unrecognized tokens in data section
+                    while !matches!(parser.peek(), Some(Token::EntityId(_)) | None) {
+                        parser.skip_token()?;
+                    }
+                    parser.names = std::mem::take(&mut self.names);
+                    let entity = match parser.peek() {
+                        Some(_) => Some(parser.parse_entity()?),
+                        None => None,
+                    };
+                    self.names = parser.names;
+                    self.diagnostics.append(&mut parser.diagnostics);
+                    if entity.is_some() {
+                        return Ok(entity);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for StepEntityIter<R> {
+    type Item = Result<StepEntity>;
+
+    fn next(&mut self) -> Option<Result<StepEntity>> {
+        if self.done {
+            return None;
+        }
+        let next = self.next_entity();
+        if !matches!(next, Ok(Some(_))) {
+            self.done = true;
+        }
+        next.transpose()
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -384,7 +635,7 @@ END-ISO-10303-21;
         let file = parse_step(input).unwrap();
         assert_eq!(file.entities.len(), 1);
         let e = &file.entities[0];
-        assert_eq!(e.entity_id, 100);
+        assert_eq!(e.entity_id, StepId(100));
         assert_eq!(e.type_name, "IFCCARTESIANPOINT");
         // Attribute is a list of 3 reals
         assert_eq!(e.attributes.len(), 1);
@@ -415,6 +666,7 @@ END-ISO-10303-21;
 "#;
         let file = parse_step(input).unwrap();
         assert_eq!(file.entities.len(), 5);
+        assert!(file.diagnostics.is_empty());
 
         // Check header
         assert!(file.header.file_schema.contains(&"IFC4".to_string()));
@@ -422,7 +674,7 @@ END-ISO-10303-21;
         // Check IFCPROJECT
         let proj = &file.entities[0];
         assert_eq!(proj.type_name, "IFCPROJECT");
-        assert_eq!(proj.entity_id, 1);
+        assert_eq!(proj.entity_id, StepId(1));
         assert_eq!(proj.attributes[0], StepAttribute::String("0YvctVUKr0kugbFTf53O9L".into()));
         assert_eq!(proj.attributes[1], StepAttribute::Null);
 
@@ -434,7 +686,7 @@ END-ISO-10303-21;
         // Check IFCEXTRUDEDAREASOLID
         let extrude = &file.entities[4];
         assert_eq!(extrude.type_name, "IFCEXTRUDEDAREASOLID");
-        assert_eq!(extrude.attributes[0], StepAttribute::EntityRef(210));
+        assert_eq!(extrude.attributes[0], StepAttribute::EntityRef(StepId(210)));
         assert_eq!(extrude.attributes[3], StepAttribute::Real(3000.0));
     }
 
@@ -453,8 +705,8 @@ END-ISO-10303-21;
         let proj = &file.entities[0];
         if let StepAttribute::List(refs) = &proj.attributes[7] {
             assert_eq!(refs.len(), 2);
-            assert_eq!(refs[0], StepAttribute::EntityRef(2));
-            assert_eq!(refs[1], StepAttribute::EntityRef(3));
+            assert_eq!(refs[0], StepAttribute::EntityRef(StepId(2)));
+            assert_eq!(refs[1], StepAttribute::EntityRef(StepId(3)));
         } else {
             panic!("Expected list attribute");
         }
@@ -476,4 +728,126 @@ END-ISO-10303-21;
         assert_eq!(e.attributes[0], StepAttribute::Bool(true));
         assert_eq!(e.attributes[1], StepAttribute::Bool(false));
     }
+
+    #[test]
+    fn test_stray_tokens_are_reported() {
+        let input = "ISO-10303-21;\nHEADER;\nENDSEC;\nDATA;\n#1=IFCTEST(.T.);\n ;\nENDSEC;\nEND-ISO-10303-21;\n";
+        let file = parse_step(input).unwrap();
+        assert_eq!(file.entities.len(), 1);
+        let messages: Vec<&str> = file.diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages, ["Skipped ';' at line 6, column 2"]);
+        assert_eq!(file.diagnostics.counts()[&SKIPPED_TOKEN], 1);
+    }
+
+    #[test]
+    fn test_parse_typed_attributes() {
+        let input = r#"ISO-10303-21;
+HEADER;
+FILE_SCHEMA(('IFC4'));
+ENDSEC;
+DATA;
+#1=IFCPROPERTYSINGLEVALUE('FireRating',$,IFCLABEL('REI90'),$);
+#2=IFCPROPERTYLISTVALUE('Widths',$,(IFCLENGTHMEASURE(0.1),IFCLENGTHMEASURE(0.2)),$);
+ENDSEC;
+END-ISO-10303-21;
+"#;
+        let file = parse_step(input).unwrap();
+        assert_eq!(
+            file.entities[0].attributes[2],
+            StepAttribute::Typed("IFCLABEL".into(), Box::new(StepAttribute::String("REI90".into())))
+        );
+        if let StepAttribute::List(values) = &file.entities[1].attributes[2] {
+            assert_eq!(
+                values[1],
+                StepAttribute::Typed("IFCLENGTHMEASURE".into(), Box::new(StepAttribute::Real(0.2)))
+            );
+        } else {
+            panic!("Expected list attribute");
+        }
+    }
+
+    #[test]
+    fn test_type_names_are_shared() {
+        let input = "ISO-10303-21;\nHEADER;\nENDSEC;\nDATA;\n#1=IFCTEST(IFCLABEL('a'));\n\
+#2=IFCTEST(IFCLABEL('b'));\nENDSEC;\nEND-ISO-10303-21;\n";
+        let file = parse_step(input).unwrap();
+        let streamed: Vec<StepEntity> = parse_step_iter(input.as_bytes()).collect::<Result<_>>().unwrap();
+        for entities in [&file.entities, &streamed] {
+            let (first, second) = (&entities[0], &entities[1]);
+            assert!(std::ptr::eq(first.type_name.as_str(), second.type_name.as_str()));
+            match (&first.attributes[0], &second.attributes[0]) {
+                (StepAttribute::Typed(a, _), StepAttribute::Typed(b, _)) => {
+                    assert!(std::ptr::eq(a.as_str(), b.as_str()));
+                }
+                other => panic!("Expected typed attributes, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_syntax_error_location() {
+        let input = "ISO-10303-21;\nHEADER;\nENDSEC;\nDATA;\n#1=IFCWALL('a',$;\n";
+        match parse_step(input) {
+            Err(CstError::Syntax { location, entity, expected, found }) => {
+                assert_eq!((location.line, location.column), (5, 17));
+                assert_eq!(location.offset, 52);
+                assert_eq!(entity, Some(StepId(1)));
+                assert_eq!(expected, "')' closing entity");
+                assert_eq!(found, "';'");
+            }
+            other => panic!("Expected syntax error, got {other:?}"),
+        }
+
+        let err = parse_step("ISO-10303-21;\nHEADER;\nENDSEC;\nDATA;\n#2=IFCWALL('a);\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Syntax error at line 5, column 12 in #2: expected closing quote of string, found end of file"
+        );
+    }
+
+    #[test]
+    fn test_parse_step_iter() {
+        let input = "ISO-10303-21;\nHEADER;\nFILE_SCHEMA(('IFC4'));\nENDSEC;\nDATA;\n\
+/* #9=IFCWALL(';'); */\n#1=IFCWALL('a;b',$,\n  (#2,#3));\n ;\n#2=IFCTEST(.T.,IFCLABEL('it''s'));\n\
+ENDSEC;\nEND-ISO-10303-21;\n";
+        let file = parse_step(input).unwrap();
+        // A small buffer splits statements, strings and comments across reads
+        let mut iter = parse_step_iter(std::io::BufReader::with_capacity(5, input.as_bytes()));
+        let entities: Vec<StepEntity> = iter.by_ref().collect::<Result<_>>().unwrap();
+        assert_eq!(iter.header().file_schema, ["IFC4"]);
+        assert_eq!(entities.len(), 2);
+        for (streamed, parsed) in entities.iter().zip(&file.entities) {
+            assert_eq!(streamed.entity_id, parsed.entity_id);
+            assert_eq!(streamed.type_name, parsed.type_name);
+            assert_eq!(streamed.attributes, parsed.attributes);
+        }
+        assert_eq!(entities[0].attributes[0], StepAttribute::String("a;b".into()));
+        let messages: Vec<&str> = iter.diagnostics().iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages, ["Skipped ';' at line 9, column 2"]);
+        assert_eq!(file.diagnostics.iter().next().unwrap().message, messages[0]);
+    }
+
+    #[test]
+    fn test_parse_step_iter_errors() {
+        let input = "ISO-10303-21;\nHEADER;\nENDSEC;\nDATA;\n#1=IFCWALL('a',$;\n";
+        let mut iter = parse_step_iter(input.as_bytes());
+        match iter.next() {
+            Some(Err(CstError::Syntax { location, entity, .. })) => {
+                assert_eq!((location.line, location.column, location.offset), (5, 17, 52));
+                assert_eq!(entity, Some(StepId(1)));
+            }
+            other => panic!("Expected syntax error, got {other:?}"),
+        }
+        assert!(iter.next().is_none());
+
+        // Entities before a missing end are still read
+        let input = "ISO-10303-21;\nHEADER;\nENDSEC;\nDATA;\n#1=IFCTEST(.T.);\n";
+        let results: Vec<Result<StepEntity>> = parse_step_iter(input.as_bytes()).collect();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].as_ref().unwrap().entity_id, StepId(1));
+        assert_eq!(
+            results[1].as_ref().unwrap_err().to_string(),
+            "Syntax error at line 6, column 1: expected END-ISO-10303-21, found end of file"
+        );
+    }
 }
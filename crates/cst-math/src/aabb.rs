@@ -1,8 +1,8 @@
-use crate::{Point3, Vector3};
+use crate::{Point2, Point3, Vector2, Vector3};
 use serde::{Deserialize, Serialize};
 
 /// Axis-Aligned Bounding Box in 3D space.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Aabb3 {
     pub min: Point3,
     pub max: Point3,
@@ -66,12 +66,111 @@ impl Aabb3 {
             max: self.max + offset,
         }
     }
+
+    /// Distance from `p` to the box; zero inside.
+    pub fn distance_to_point(&self, p: Point3) -> f64 {
+        (self.min - p).max(p - self.max).max(Vector3::ZERO).length()
+    }
+
+    /// Smallest distance between points of the two boxes; zero when they
+    /// intersect.
+    pub fn distance(&self, other: &Self) -> f64 {
+        (self.min - other.max)
+            .max(other.min - self.max)
+            .max(Vector3::ZERO)
+            .length()
+    }
+}
+
+/// Axis-Aligned Bounding Box in 2D space (UV domain, plan view).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Aabb2 {
+    pub min: Point2,
+    pub max: Point2,
+}
+
+impl Aabb2 {
+    pub fn new(min: Point2, max: Point2) -> Self {
+        Self { min, max }
+    }
+
+    pub fn from_points(points: &[Point2]) -> Option<Self> {
+        if points.is_empty() {
+            return None;
+        }
+        let mut min = points[0];
+        let mut max = points[0];
+        for &p in &points[1..] {
+            min = min.min(p);
+            max = max.max(p);
+        }
+        Some(Self { min, max })
+    }
+
+    pub fn center(&self) -> Point2 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn extents(&self) -> Vector2 {
+        self.max - self.min
+    }
+
+    pub fn area(&self) -> f64 {
+        let e = self.extents();
+        e.x * e.y
+    }
+
+    pub fn contains_point(&self, p: Point2) -> bool {
+        p.x >= self.min.x && p.x <= self.max.x && p.y >= self.min.y && p.y <= self.max.y
+    }
+
+    /// Whether the boxes touch or overlap (closed intervals).
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.min.x <= other.max.x
+            && self.max.x >= other.min.x
+            && self.min.y <= other.max.y
+            && self.max.y >= other.min.y
+    }
+
+    /// Whether the boxes share interior area; touching edges do not count.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.min.x < other.max.x
+            && self.max.x > other.min.x
+            && self.min.y < other.max.y
+            && self.max.y > other.min.y
+    }
+
+    /// The common box, or `None` if the boxes are disjoint.
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        if !self.intersects(other) {
+            return None;
+        }
+        Some(Self {
+            min: self.min.max(other.min),
+            max: self.max.min(other.max),
+        })
+    }
+
+    pub fn merge(&self, other: &Self) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    pub fn expand(&self, amount: f64) -> Self {
+        let offset = Vector2::splat(amount);
+        Self {
+            min: self.min - offset,
+            max: self.max + offset,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use glam::dvec3;
+    use glam::{dvec2, dvec3};
 
     #[test]
     fn test_from_points() {
@@ -96,4 +195,51 @@ mod tests {
         assert!(a.intersects(&b));
         assert!(!a.intersects(&c));
     }
+
+    #[test]
+    fn test_distances() {
+        let a = Aabb3::new(dvec3(0.0, 0.0, 0.0), dvec3(2.0, 2.0, 2.0));
+        assert_eq!(a.distance_to_point(dvec3(1.0, 1.0, 1.0)), 0.0);
+        assert_eq!(a.distance_to_point(dvec3(5.0, 1.0, 6.0)), 5.0);
+
+        let b = Aabb3::new(dvec3(1.0, 1.0, 1.0), dvec3(3.0, 3.0, 3.0));
+        let c = Aabb3::new(dvec3(5.0, 6.0, 0.0), dvec3(6.0, 7.0, 1.0));
+        assert_eq!(a.distance(&b), 0.0);
+        assert_eq!(a.distance(&c), 5.0);
+        assert_eq!(c.distance(&a), 5.0);
+    }
+
+    #[test]
+    fn test_aabb2_from_points() {
+        let pts = [dvec2(1.0, 2.0), dvec2(-1.0, 5.0), dvec2(3.0, -1.0)];
+        let aabb = Aabb2::from_points(&pts).unwrap();
+        assert_eq!(aabb.min, dvec2(-1.0, -1.0));
+        assert_eq!(aabb.max, dvec2(3.0, 5.0));
+        assert_eq!(aabb.area(), 24.0);
+        assert!(aabb.contains_point(dvec2(0.0, 0.0)));
+        assert!(!aabb.contains_point(dvec2(0.0, 6.0)));
+        assert!(Aabb2::from_points(&[]).is_none());
+    }
+
+    #[test]
+    fn test_aabb2_intersection_and_overlap() {
+        let a = Aabb2::new(dvec2(0.0, 0.0), dvec2(2.0, 2.0));
+        let b = Aabb2::new(dvec2(1.0, 1.0), dvec2(3.0, 3.0));
+        let edge = Aabb2::new(dvec2(2.0, 0.0), dvec2(4.0, 2.0));
+        let far = Aabb2::new(dvec2(5.0, 5.0), dvec2(6.0, 6.0));
+
+        let common = a.intersection(&b).unwrap();
+        assert_eq!(common.min, dvec2(1.0, 1.0));
+        assert_eq!(common.max, dvec2(2.0, 2.0));
+        assert!(a.overlaps(&b));
+
+        // Touching edges intersect but do not overlap
+        assert!(a.intersects(&edge));
+        assert!(!a.overlaps(&edge));
+        assert_eq!(a.intersection(&edge).unwrap().area(), 0.0);
+
+        assert!(!a.intersects(&far));
+        assert!(a.intersection(&far).is_none());
+        assert_eq!(a.merge(&far).max, dvec2(6.0, 6.0));
+    }
 }
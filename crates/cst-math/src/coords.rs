@@ -0,0 +1,162 @@
+//! Spherical and cylindrical coordinates, and angle wrapping.
+//!
+//! Both coordinate systems are taken around the +Z axis with azimuth
+//! measured from +X towards +Y. Callers with a different up axis (the
+//! camera is Y-up) swizzle or map through their own frame.
+
+use std::f64::consts::{PI, TAU};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Vector3;
+
+/// Spherical coordinates: radius, polar angle from +Z in `[0, π]` and
+/// azimuth in `(-π, π]`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Spherical {
+    pub radius: f64,
+    pub polar: f64,
+    pub azimuth: f64,
+}
+
+impl Spherical {
+    pub fn new(radius: f64, polar: f64, azimuth: f64) -> Self {
+        Self {
+            radius,
+            polar,
+            azimuth,
+        }
+    }
+
+    /// Coordinates of `v`. The zero vector maps to all zeros.
+    pub fn from_cartesian(v: Vector3) -> Self {
+        let radius = v.length();
+        if radius == 0.0 {
+            return Self::new(0.0, 0.0, 0.0);
+        }
+        Self {
+            radius,
+            polar: (v.z / radius).clamp(-1.0, 1.0).acos(),
+            azimuth: v.y.atan2(v.x),
+        }
+    }
+
+    pub fn to_cartesian(&self) -> Vector3 {
+        let (sin_polar, cos_polar) = self.polar.sin_cos();
+        let (sin_azimuth, cos_azimuth) = self.azimuth.sin_cos();
+        self.radius * Vector3::new(sin_polar * cos_azimuth, sin_polar * sin_azimuth, cos_polar)
+    }
+
+    /// Angle above the XY plane, `π/2 - polar`.
+    pub fn latitude(&self) -> f64 {
+        PI / 2.0 - self.polar
+    }
+}
+
+/// Cylindrical coordinates: distance from the Z axis, azimuth in
+/// `(-π, π]` and height along Z.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Cylindrical {
+    pub radius: f64,
+    pub azimuth: f64,
+    pub height: f64,
+}
+
+impl Cylindrical {
+    pub fn new(radius: f64, azimuth: f64, height: f64) -> Self {
+        Self {
+            radius,
+            azimuth,
+            height,
+        }
+    }
+
+    pub fn from_cartesian(v: Vector3) -> Self {
+        Self {
+            radius: v.x.hypot(v.y),
+            azimuth: v.y.atan2(v.x),
+            height: v.z,
+        }
+    }
+
+    pub fn to_cartesian(&self) -> Vector3 {
+        let (sin, cos) = self.azimuth.sin_cos();
+        Vector3::new(self.radius * cos, self.radius * sin, self.height)
+    }
+}
+
+/// Wrap an angle into `(-π, π]`.
+pub fn wrap_angle(angle: f64) -> f64 {
+    let wrapped = PI - (PI - angle).rem_euclid(TAU);
+    // rem_euclid can round up to TAU for tiny negative inputs
+    if wrapped <= -PI {
+        wrapped + TAU
+    } else {
+        wrapped
+    }
+}
+
+/// Wrap an angle into `[0, 2π)`.
+pub fn wrap_angle_positive(angle: f64) -> f64 {
+    let wrapped = angle.rem_euclid(TAU);
+    if wrapped >= TAU {
+        0.0
+    } else {
+        wrapped
+    }
+}
+
+/// Signed shortest rotation from `from` to `to`, in `(-π, π]`.
+pub fn angle_difference(from: f64, to: f64) -> f64 {
+    wrap_angle(to - from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::dvec3;
+
+    #[test]
+    fn test_spherical_round_trip() {
+        for v in [
+            dvec3(1.0, 2.0, 3.0),
+            dvec3(-4.0, 0.5, -1.0),
+            dvec3(0.0, -2.0, 0.0),
+            dvec3(0.0, 0.0, 7.0),
+        ] {
+            let s = Spherical::from_cartesian(v);
+            assert!((s.to_cartesian() - v).length() < 1e-12);
+            assert!((0.0..=PI).contains(&s.polar));
+            assert!(s.azimuth > -PI && s.azimuth <= PI);
+        }
+        let up = Spherical::from_cartesian(dvec3(0.0, 0.0, 2.0));
+        assert_eq!((up.radius, up.polar, up.latitude()), (2.0, 0.0, PI / 2.0));
+        assert_eq!(Spherical::from_cartesian(Vector3::ZERO).radius, 0.0);
+    }
+
+    #[test]
+    fn test_cylindrical_round_trip() {
+        let v = dvec3(-3.0, 4.0, 1.5);
+        let c = Cylindrical::from_cartesian(v);
+        assert_eq!(c.radius, 5.0);
+        assert_eq!(c.height, 1.5);
+        assert!((c.to_cartesian() - v).length() < 1e-12);
+    }
+
+    #[test]
+    fn test_angle_wrapping() {
+        assert!((wrap_angle(3.0 * PI / 2.0) - -PI / 2.0).abs() < 1e-12);
+        assert_eq!(wrap_angle(PI), PI);
+        assert!((wrap_angle(-PI) - PI).abs() < 1e-12);
+        assert!((wrap_angle(-7.0 * TAU + 0.25) - 0.25).abs() < 1e-9);
+        assert!(wrap_angle(-1e-300) <= PI && wrap_angle(-1e-300) > -PI);
+
+        assert!((wrap_angle_positive(-PI / 2.0) - 3.0 * PI / 2.0).abs() < 1e-12);
+        assert_eq!(wrap_angle_positive(TAU), 0.0);
+        assert!(wrap_angle_positive(-1e-300) < TAU);
+
+        // Crossing the ±π seam takes the short way round
+        assert!((angle_difference(3.0, -3.0) - (TAU - 6.0)).abs() < 1e-12);
+        assert!((angle_difference(0.5, 0.25) - -0.25).abs() < 1e-12);
+    }
+}
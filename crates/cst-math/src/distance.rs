@@ -0,0 +1,297 @@
+//! Closest points between points, segments and triangles.
+//!
+//! The closest-point routines follow Ericson, *Real-Time Collision
+//! Detection*, chapter 5.
+
+use crate::ray::Ray;
+use crate::Point3;
+
+/// Squared lengths below this count as degenerate segments.
+const DEGENERATE_EPSILON: f64 = 1e-24;
+
+/// Closest point to `p` on the segment `a`-`b`.
+pub fn closest_point_on_segment(p: Point3, a: Point3, b: Point3) -> Point3 {
+    let ab = b - a;
+    let len_sq = ab.length_squared();
+    if len_sq <= DEGENERATE_EPSILON {
+        return a;
+    }
+    let t = ((p - a).dot(ab) / len_sq).clamp(0.0, 1.0);
+    a + ab * t
+}
+
+/// Closest point to `p` on the triangle `(a, b, c)`, interior included.
+pub fn closest_point_on_triangle(p: Point3, a: Point3, b: Point3, c: Point3) -> Point3 {
+    let ab = b - a;
+    let ac = c - a;
+    let ap = p - a;
+    let d1 = ab.dot(ap);
+    let d2 = ac.dot(ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return a;
+    }
+
+    let bp = p - b;
+    let d3 = ab.dot(bp);
+    let d4 = ac.dot(bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return b;
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+
+    let cp = p - c;
+    let d5 = ab.dot(cp);
+    let d6 = ac.dot(cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return c;
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+
+    // Inside the face region. Degenerate triangles end up here with a zero
+    // denominator; fall back to the closest edge.
+    let denom = va + vb + vc;
+    if denom.abs() <= DEGENERATE_EPSILON {
+        return [(a, b), (b, c), (c, a)]
+            .into_iter()
+            .map(|(s, e)| closest_point_on_segment(p, s, e))
+            .min_by(|x, y| x.distance_squared(p).total_cmp(&y.distance_squared(p)))
+            .unwrap_or(a);
+    }
+    a + ab * (vb / denom) + ac * (vc / denom)
+}
+
+/// Closest points between the segments `p1`-`q1` and `p2`-`q2`, as
+/// `(on_first, on_second)`.
+pub fn closest_points_on_segments(
+    p1: Point3,
+    q1: Point3,
+    p2: Point3,
+    q2: Point3,
+) -> (Point3, Point3) {
+    let d1 = q1 - p1;
+    let d2 = q2 - p2;
+    let r = p1 - p2;
+    let a = d1.length_squared();
+    let e = d2.length_squared();
+    let f = d2.dot(r);
+
+    if a <= DEGENERATE_EPSILON && e <= DEGENERATE_EPSILON {
+        return (p1, p2);
+    }
+    let (s, t) = if a <= DEGENERATE_EPSILON {
+        (0.0, (f / e).clamp(0.0, 1.0))
+    } else {
+        let c = d1.dot(r);
+        if e <= DEGENERATE_EPSILON {
+            ((-c / a).clamp(0.0, 1.0), 0.0)
+        } else {
+            let b = d1.dot(d2);
+            let denom = a * e - b * b;
+            // Parallel segments: any s works, start from p1
+            let mut s = if denom > DEGENERATE_EPSILON {
+                ((b * f - c * e) / denom).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let mut t = (b * s + f) / e;
+            if t < 0.0 {
+                t = 0.0;
+                s = (-c / a).clamp(0.0, 1.0);
+            } else if t > 1.0 {
+                t = 1.0;
+                s = ((b - c) / a).clamp(0.0, 1.0);
+            }
+            (s, t)
+        }
+    };
+    (p1 + d1 * s, p2 + d2 * t)
+}
+
+/// Where the segment `p`-`q` crosses the triangle `(a, b, c)`, if it does.
+pub fn segment_triangle_intersection(
+    p: Point3,
+    q: Point3,
+    a: Point3,
+    b: Point3,
+    c: Point3,
+) -> Option<Point3> {
+    // Unnormalized direction, so the hit parameter runs from 0 at p to 1 at q
+    let ray = Ray {
+        origin: p,
+        direction: q - p,
+    };
+    let hit = ray.intersect_triangle(a, b, c)?;
+    (hit.t <= 1.0).then(|| ray.at(hit.t))
+}
+
+/// Closest points between two triangles, as `(on_first, on_second)`.
+/// Both points coincide when the triangles intersect.
+pub fn closest_points_on_triangles(first: [Point3; 3], second: [Point3; 3]) -> (Point3, Point3) {
+    for (edges, triangle) in [(first, second), (second, first)] {
+        for i in 0..3 {
+            let [a, b, c] = triangle;
+            if let Some(x) = segment_triangle_intersection(edges[i], edges[(i + 1) % 3], a, b, c) {
+                return (x, x);
+            }
+        }
+    }
+
+    let mut best = (first[0], second[0]);
+    let mut best_distance = f64::INFINITY;
+    let mut consider = |pair: (Point3, Point3)| {
+        let d = pair.0.distance_squared(pair.1);
+        if d < best_distance {
+            best_distance = d;
+            best = pair;
+        }
+    };
+    for i in 0..3 {
+        let (p1, q1) = (first[i], first[(i + 1) % 3]);
+        for j in 0..3 {
+            consider(closest_points_on_segments(
+                p1,
+                q1,
+                second[j],
+                second[(j + 1) % 3],
+            ));
+        }
+        let [a, b, c] = second;
+        consider((first[i], closest_point_on_triangle(first[i], a, b, c)));
+        let [a, b, c] = first;
+        consider((closest_point_on_triangle(second[i], a, b, c), second[i]));
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::dvec3;
+
+    const TRIANGLE: [Point3; 3] = [
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(2.0, 0.0, 0.0),
+        Point3::new(0.0, 2.0, 0.0),
+    ];
+
+    #[test]
+    fn test_closest_point_on_triangle_regions() {
+        let [a, b, c] = TRIANGLE;
+        // Above the interior
+        assert_eq!(
+            closest_point_on_triangle(dvec3(0.5, 0.5, 3.0), a, b, c),
+            dvec3(0.5, 0.5, 0.0)
+        );
+        // Vertex regions
+        assert_eq!(
+            closest_point_on_triangle(dvec3(-1.0, -1.0, 1.0), a, b, c),
+            a
+        );
+        assert_eq!(closest_point_on_triangle(dvec3(3.0, -1.0, 0.0), a, b, c), b);
+        assert_eq!(closest_point_on_triangle(dvec3(-1.0, 4.0, 0.0), a, b, c), c);
+        // Edge regions
+        assert_eq!(
+            closest_point_on_triangle(dvec3(1.0, -2.0, 0.0), a, b, c),
+            dvec3(1.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            closest_point_on_triangle(dvec3(2.0, 2.0, 0.0), a, b, c),
+            dvec3(1.0, 1.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_closest_point_on_degenerate_triangle() {
+        let a = dvec3(0.0, 0.0, 0.0);
+        let b = dvec3(1.0, 0.0, 0.0);
+        let c = dvec3(2.0, 0.0, 0.0);
+        let closest = closest_point_on_triangle(dvec3(1.5, 1.0, 0.0), a, b, c);
+        assert!(closest.distance(dvec3(1.5, 0.0, 0.0)) < 1e-12);
+    }
+
+    #[test]
+    fn test_closest_points_on_segments() {
+        // Skew segments crossing at x = 1, y = 0 one unit apart
+        let (p, q) = closest_points_on_segments(
+            dvec3(0.0, 0.0, 0.0),
+            dvec3(2.0, 0.0, 0.0),
+            dvec3(1.0, -1.0, 1.0),
+            dvec3(1.0, 1.0, 1.0),
+        );
+        assert_eq!(p, dvec3(1.0, 0.0, 0.0));
+        assert_eq!(q, dvec3(1.0, 0.0, 1.0));
+
+        // Parallel segments
+        let (p, q) = closest_points_on_segments(
+            dvec3(0.0, 0.0, 0.0),
+            dvec3(1.0, 0.0, 0.0),
+            dvec3(3.0, 1.0, 0.0),
+            dvec3(5.0, 1.0, 0.0),
+        );
+        assert_eq!(p, dvec3(1.0, 0.0, 0.0));
+        assert_eq!(q, dvec3(3.0, 1.0, 0.0));
+
+        // Point against segment
+        let p0 = dvec3(0.5, 2.0, 0.0);
+        let (p, q) = closest_points_on_segments(p0, p0, dvec3(0.0, 0.0, 0.0), dvec3(1.0, 0.0, 0.0));
+        assert_eq!((p, q), (p0, dvec3(0.5, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_closest_points_on_triangles() {
+        // Parallel, one unit above
+        let lifted = TRIANGLE.map(|p| p + dvec3(0.5, 0.5, 1.0));
+        let (p, q) = closest_points_on_triangles(TRIANGLE, lifted);
+        assert!((p.distance(q) - 1.0).abs() < 1e-12);
+
+        // Edge against edge, side by side
+        let beside = [
+            dvec3(3.0, 0.0, 0.0),
+            dvec3(3.0, 2.0, 0.0),
+            dvec3(5.0, 0.0, 0.0),
+        ];
+        let (p, q) = closest_points_on_triangles(TRIANGLE, beside);
+        assert_eq!(p, dvec3(2.0, 0.0, 0.0));
+        assert_eq!(q, dvec3(3.0, 0.0, 0.0));
+
+        // Piercing triangles touch
+        let piercing = [
+            dvec3(0.5, 0.5, -1.0),
+            dvec3(0.5, 0.5, 1.0),
+            dvec3(0.5, -1.0, 0.0),
+        ];
+        let (p, q) = closest_points_on_triangles(TRIANGLE, piercing);
+        assert_eq!(p, q);
+        assert!(p.z.abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_segment_triangle_intersection() {
+        let [a, b, c] = TRIANGLE;
+        let hit =
+            segment_triangle_intersection(dvec3(0.5, 0.5, -1.0), dvec3(0.5, 0.5, 1.0), a, b, c);
+        assert_eq!(hit, Some(dvec3(0.5, 0.5, 0.0)));
+        // Stops short of the plane
+        assert!(segment_triangle_intersection(
+            dvec3(0.5, 0.5, -2.0),
+            dvec3(0.5, 0.5, -1.0),
+            a,
+            b,
+            c
+        )
+        .is_none());
+    }
+}
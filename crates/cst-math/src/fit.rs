@@ -0,0 +1,314 @@
+//! Least-squares fitting of analytic primitives to point sets.
+//!
+//! Used to recognise spheres and cylinders in triangulated face sets, so
+//! imported meshes can be turned back into analytic surfaces. Planes are
+//! fitted by [`Plane::fit`](crate::plane::Plane::fit).
+
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use serde::{Deserialize, Serialize};
+
+use crate::linalg::{solve_least_squares, Matrix};
+use crate::{Point2, Point3, Vector3};
+
+/// Gauss-Newton iterations used to refine an algebraic sphere fit.
+const SPHERE_REFINE_ITERATIONS: usize = 20;
+
+/// Resolution of the coarse cylinder axis search over the hemisphere.
+const AXIS_SEARCH_AZIMUTH_STEPS: usize = 64;
+const AXIS_SEARCH_POLAR_STEPS: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Sphere {
+    pub center: Point3,
+    pub radius: f64,
+}
+
+impl Sphere {
+    /// Distance from the surface, positive outside.
+    pub fn signed_distance(&self, p: Point3) -> f64 {
+        p.distance(self.center) - self.radius
+    }
+
+    /// Largest distance of any point from the surface (0 for no points).
+    pub fn max_deviation(&self, points: &[Point3]) -> f64 {
+        points
+            .iter()
+            .map(|&p| self.signed_distance(p).abs())
+            .fold(0.0, f64::max)
+    }
+}
+
+/// Infinite circular cylinder.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Cylinder {
+    /// Point on the axis
+    pub origin: Point3,
+    /// Unit axis direction
+    pub axis: Vector3,
+    pub radius: f64,
+}
+
+impl Cylinder {
+    /// Distance from the axis line.
+    pub fn axis_distance(&self, p: Point3) -> f64 {
+        let d = p - self.origin;
+        (d - self.axis * d.dot(self.axis)).length()
+    }
+
+    /// Distance from the surface, positive outside.
+    pub fn signed_distance(&self, p: Point3) -> f64 {
+        self.axis_distance(p) - self.radius
+    }
+
+    /// Largest distance of any point from the surface (0 for no points).
+    pub fn max_deviation(&self, points: &[Point3]) -> f64 {
+        points
+            .iter()
+            .map(|&p| self.signed_distance(p).abs())
+            .fold(0.0, f64::max)
+    }
+}
+
+/// Algebraic least-squares circle through 2D points, as `(center, radius)`.
+///
+/// Returns `None` for fewer than three points or collinear points.
+pub fn fit_circle(points: &[Point2]) -> Option<(Point2, f64)> {
+    if points.len() < 3 {
+        return None;
+    }
+    // Work relative to the centroid for conditioning
+    let centroid = points.iter().sum::<Point2>() / points.len() as f64;
+    let mut a = Matrix::zeros(points.len(), 3);
+    let mut b = Vec::with_capacity(points.len());
+    for (i, p) in points.iter().enumerate() {
+        let q = *p - centroid;
+        a[(i, 0)] = 2.0 * q.x;
+        a[(i, 1)] = 2.0 * q.y;
+        a[(i, 2)] = 1.0;
+        b.push(q.length_squared());
+    }
+    let x = solve_least_squares(&a, &b)?;
+    let center = Point2::new(x[0], x[1]);
+    let radius_sq = x[2] + center.length_squared();
+    (radius_sq > 0.0).then(|| (center + centroid, radius_sq.sqrt()))
+}
+
+/// Least-squares sphere through a point set.
+///
+/// An algebraic fit gives the starting point for a few Gauss-Newton steps
+/// that minimise the geometric (point to surface) distances. Returns `None`
+/// for fewer than four points or coplanar points.
+pub fn fit_sphere(points: &[Point3]) -> Option<Sphere> {
+    if points.len() < 4 {
+        return None;
+    }
+    let centroid = points.iter().sum::<Point3>() / points.len() as f64;
+    let mut a = Matrix::zeros(points.len(), 4);
+    let mut b = Vec::with_capacity(points.len());
+    for (i, p) in points.iter().enumerate() {
+        let q = *p - centroid;
+        a[(i, 0)] = 2.0 * q.x;
+        a[(i, 1)] = 2.0 * q.y;
+        a[(i, 2)] = 2.0 * q.z;
+        a[(i, 3)] = 1.0;
+        b.push(q.length_squared());
+    }
+    let x = solve_least_squares(&a, &b)?;
+    let offset = Vector3::new(x[0], x[1], x[2]);
+    let radius_sq = x[3] + offset.length_squared();
+    if radius_sq <= 0.0 {
+        return None;
+    }
+    let mut sphere = Sphere {
+        center: centroid + offset,
+        radius: radius_sq.sqrt(),
+    };
+
+    // Geometric refinement: residual |p - c| - r, unknowns (c, r)
+    for _ in 0..SPHERE_REFINE_ITERATIONS {
+        let mut jacobian = Matrix::zeros(points.len(), 4);
+        let mut residual = Vec::with_capacity(points.len());
+        for (i, p) in points.iter().enumerate() {
+            let d = *p - sphere.center;
+            let length = d.length();
+            if length == 0.0 {
+                return Some(sphere);
+            }
+            let u = d / length;
+            jacobian[(i, 0)] = u.x;
+            jacobian[(i, 1)] = u.y;
+            jacobian[(i, 2)] = u.z;
+            jacobian[(i, 3)] = 1.0;
+            residual.push(length - sphere.radius);
+        }
+        let Some(step) = solve_least_squares(&jacobian, &residual) else {
+            break;
+        };
+        sphere.center += Vector3::new(step[0], step[1], step[2]);
+        sphere.radius += step[3];
+        if step.iter().map(|s| s * s).sum::<f64>() <= (sphere.radius * 1e-14).powi(2) {
+            break;
+        }
+    }
+    Some(sphere)
+}
+
+/// Least-squares cylinder through a point set.
+///
+/// The axis direction is found by a search over the hemisphere of
+/// directions, refined locally; for each candidate the points are projected
+/// onto the perpendicular plane and fitted with a circle. Works for partial
+/// cylinders (arcs well below 180°) as long as the points span some length
+/// along the axis or around it. Returns `None` for fewer than five points
+/// or degenerate input.
+pub fn fit_cylinder(points: &[Point3]) -> Option<Cylinder> {
+    if points.len() < 5 {
+        return None;
+    }
+    let centroid = points.iter().sum::<Point3>() / points.len() as f64;
+    let local: Vec<Vector3> = points.iter().map(|p| *p - centroid).collect();
+
+    // Coarse search: polar angle in [0, π/2], azimuth in [0, 2π)
+    let polar_step = FRAC_PI_2 / AXIS_SEARCH_POLAR_STEPS as f64;
+    let azimuth_step = 2.0 * PI / AXIS_SEARCH_AZIMUTH_STEPS as f64;
+    let mut best: Option<(f64, f64, f64)> = None;
+    for i in 0..=AXIS_SEARCH_POLAR_STEPS {
+        let theta = i as f64 * polar_step;
+        let azimuths = if i == 0 { 1 } else { AXIS_SEARCH_AZIMUTH_STEPS };
+        for j in 0..azimuths {
+            let phi = j as f64 * azimuth_step;
+            if let Some(error) = axis_error(&local, direction(theta, phi)) {
+                if best.map_or(true, |(e, _, _)| error < e) {
+                    best = Some((error, theta, phi));
+                }
+            }
+        }
+    }
+    let (mut error, mut theta, mut phi) = best?;
+
+    // Local pattern search with a shrinking step
+    let mut step = polar_step;
+    while step > 1e-10 {
+        let mut improved = false;
+        for (dt, dp) in [(step, 0.0), (-step, 0.0), (0.0, step), (0.0, -step)] {
+            let candidate = axis_error(&local, direction(theta + dt, phi + dp));
+            if let Some(e) = candidate.filter(|&e| e < error) {
+                (error, theta, phi) = (e, theta + dt, phi + dp);
+                improved = true;
+            }
+        }
+        if !improved {
+            step *= 0.5;
+        }
+    }
+
+    let axis = direction(theta, phi);
+    let (u, v) = axis.any_orthonormal_pair();
+    let projected: Vec<Point2> = local
+        .iter()
+        .map(|q| Point2::new(q.dot(u), q.dot(v)))
+        .collect();
+    let (center, radius) = fit_circle(&projected)?;
+    Some(Cylinder {
+        origin: centroid + u * center.x + v * center.y,
+        axis,
+        radius,
+    })
+}
+
+fn direction(theta: f64, phi: f64) -> Vector3 {
+    Vector3::new(
+        theta.sin() * phi.cos(),
+        theta.sin() * phi.sin(),
+        theta.cos(),
+    )
+}
+
+/// Sum of squared circle residuals after projecting along `axis`.
+fn axis_error(local: &[Vector3], axis: Vector3) -> Option<f64> {
+    let (u, v) = axis.any_orthonormal_pair();
+    let projected: Vec<Point2> = local
+        .iter()
+        .map(|q| Point2::new(q.dot(u), q.dot(v)))
+        .collect();
+    let (center, radius) = fit_circle(&projected)?;
+    Some(
+        projected
+            .iter()
+            .map(|q| (q.distance(center) - radius).powi(2))
+            .sum(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::{dvec2, dvec3};
+
+    #[test]
+    fn test_fit_circle() {
+        let points: Vec<Point2> = (0..12)
+            .map(|i| {
+                let a = i as f64 * 0.2;
+                dvec2(3.0 + 2.0 * a.cos(), -1.0 + 2.0 * a.sin())
+            })
+            .collect();
+        let (center, radius) = fit_circle(&points).unwrap();
+        assert!((center - dvec2(3.0, -1.0)).length() < 1e-9);
+        assert!((radius - 2.0).abs() < 1e-9);
+
+        let line = [dvec2(0.0, 0.0), dvec2(1.0, 1.0), dvec2(2.0, 2.0)];
+        assert!(fit_circle(&line).is_none());
+    }
+
+    #[test]
+    fn test_fit_sphere() {
+        let center = dvec3(1.0, 2.0, -3.0);
+        // Only one octant of the sphere, with alternating radial noise
+        let points: Vec<Point3> = (0..60)
+            .map(|i| {
+                let (theta, phi) = (0.1 + (i % 6) as f64 * 0.25, (i / 6) as f64 * 0.15);
+                let noise = if i % 2 == 0 { 1e-3 } else { -1e-3 };
+                center + direction(theta, phi) * (5.0 + noise)
+            })
+            .collect();
+        let sphere = fit_sphere(&points).unwrap();
+        assert!((sphere.center - center).length() < 1e-2);
+        assert!((sphere.radius - 5.0).abs() < 1e-2);
+        assert!(sphere.max_deviation(&points) < 2e-3);
+
+        let flat: Vec<Point3> = (0..10)
+            .map(|i| dvec3(i as f64, (i * i) as f64, 0.0))
+            .collect();
+        assert!(fit_sphere(&flat).is_none());
+    }
+
+    #[test]
+    fn test_fit_cylinder() {
+        let axis = dvec3(1.0, 2.0, 0.5).normalize();
+        let (u, v) = axis.any_orthonormal_pair();
+        let origin = dvec3(4.0, -2.0, 1.0);
+        // A 120° strip of a radius 1.5 cylinder, 3 units long
+        let points: Vec<Point3> = (0..80)
+            .map(|i| {
+                let angle = (i % 10) as f64 * 0.23;
+                let along = (i / 10) as f64 * 0.4;
+                origin + axis * along + (u * angle.cos() + v * angle.sin()) * 1.5
+            })
+            .collect();
+
+        let cylinder = fit_cylinder(&points).unwrap();
+        assert!(cylinder.axis.dot(axis).abs() > 1.0 - 1e-9);
+        assert!((cylinder.radius - 1.5).abs() < 1e-6);
+        assert!(cylinder.axis_distance(origin) < 1e-6);
+        assert!(cylinder.max_deviation(&points) < 1e-6);
+
+        // A sphere is not a cylinder
+        let sphere: Vec<Point3> = (0..60)
+            .map(|i| direction(0.2 + (i % 6) as f64 * 0.25, (i / 6) as f64 * 0.6) * 2.0)
+            .collect();
+        let best = fit_cylinder(&sphere).unwrap();
+        assert!(best.max_deviation(&sphere) > 0.05);
+    }
+}
@@ -1,10 +1,16 @@
 pub mod aabb;
+pub mod coords;
+pub mod distance;
+pub mod fit;
+pub mod linalg;
 pub mod plane;
 pub mod ray;
+pub mod rtree;
+pub mod spatial;
 pub mod transform;
 
-pub use glam::{DVec2, DVec3, DVec4, DMat3, DMat4, DAffine3};
-pub use aabb::Aabb3;
+pub use glam::{DVec2, DVec3, DVec4, DMat3, DMat4, DAffine3, DQuat};
+pub use aabb::{Aabb2, Aabb3};
 
 pub type Point2 = DVec2;
 pub type Point3 = DVec3;
@@ -0,0 +1,418 @@
+//! Small dense and banded linear solvers.
+//!
+//! Sized for geometry fitting: NURBS interpolation (banded collocation
+//! matrices) and least-squares primitive fits (a handful of unknowns).
+//! Systems of a few hundred unknowns are fine; anything larger belongs in
+//! a real linear algebra library.
+
+use std::ops::{Index, IndexMut};
+
+/// Pivots smaller than this (relative to the largest matrix entry) make a
+/// system singular.
+pub const SINGULAR_EPSILON: f64 = 1e-12;
+
+/// Row-major dense matrix.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    data: Vec<f64>,
+}
+
+impl Matrix {
+    pub fn zeros(rows: usize, cols: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            data: vec![0.0; rows * cols],
+        }
+    }
+
+    pub fn identity(n: usize) -> Self {
+        let mut m = Self::zeros(n, n);
+        for i in 0..n {
+            m[(i, i)] = 1.0;
+        }
+        m
+    }
+
+    /// Build from row slices, which must all have the same length.
+    pub fn from_rows(rows: &[&[f64]]) -> Self {
+        let cols = rows.first().map_or(0, |r| r.len());
+        assert!(rows.iter().all(|r| r.len() == cols), "ragged matrix rows");
+        Self {
+            rows: rows.len(),
+            cols,
+            data: rows.concat(),
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn row(&self, i: usize) -> &[f64] {
+        &self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut t = Self::zeros(self.cols, self.rows);
+        for i in 0..self.rows {
+            for j in 0..self.cols {
+                t[(j, i)] = self[(i, j)];
+            }
+        }
+        t
+    }
+
+    pub fn mul_vec(&self, x: &[f64]) -> Vec<f64> {
+        assert_eq!(x.len(), self.cols);
+        (0..self.rows)
+            .map(|i| self.row(i).iter().zip(x).map(|(a, b)| a * b).sum())
+            .collect()
+    }
+
+    fn max_abs(&self) -> f64 {
+        self.data.iter().fold(0.0, |m, v| m.max(v.abs()))
+    }
+}
+
+impl Index<(usize, usize)> for Matrix {
+    type Output = f64;
+
+    fn index(&self, (i, j): (usize, usize)) -> &f64 {
+        &self.data[i * self.cols + j]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Matrix {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut f64 {
+        &mut self.data[i * self.cols + j]
+    }
+}
+
+/// LU factorization with partial pivoting of a square matrix.
+#[derive(Debug, Clone)]
+pub struct Lu {
+    lu: Matrix,
+    /// Row of the original matrix that ended up in each row
+    permutation: Vec<usize>,
+    /// +1 or -1 depending on the number of row swaps
+    sign: f64,
+}
+
+impl Lu {
+    /// Factorize `a`. Returns `None` if `a` is not square or is singular.
+    pub fn new(a: &Matrix) -> Option<Self> {
+        let n = a.rows;
+        if a.cols != n {
+            return None;
+        }
+        let tolerance = a.max_abs() * SINGULAR_EPSILON;
+        let mut lu = a.clone();
+        let mut permutation: Vec<usize> = (0..n).collect();
+        let mut sign = 1.0;
+
+        for k in 0..n {
+            let pivot = (k..n).max_by(|&i, &j| lu[(i, k)].abs().total_cmp(&lu[(j, k)].abs()))?;
+            if lu[(pivot, k)].abs() <= tolerance {
+                return None;
+            }
+            if pivot != k {
+                for j in 0..n {
+                    lu.data.swap(k * n + j, pivot * n + j);
+                }
+                permutation.swap(k, pivot);
+                sign = -sign;
+            }
+            for i in k + 1..n {
+                let factor = lu[(i, k)] / lu[(k, k)];
+                lu[(i, k)] = factor;
+                for j in k + 1..n {
+                    lu[(i, j)] -= factor * lu[(k, j)];
+                }
+            }
+        }
+        Some(Self {
+            lu,
+            permutation,
+            sign,
+        })
+    }
+
+    /// Solve `a x = b`.
+    pub fn solve(&self, b: &[f64]) -> Vec<f64> {
+        let n = self.lu.rows;
+        assert_eq!(b.len(), n);
+        let mut x: Vec<f64> = self.permutation.iter().map(|&p| b[p]).collect();
+        for i in 0..n {
+            for j in 0..i {
+                x[i] -= self.lu[(i, j)] * x[j];
+            }
+        }
+        for i in (0..n).rev() {
+            for j in i + 1..n {
+                x[i] -= self.lu[(i, j)] * x[j];
+            }
+            x[i] /= self.lu[(i, i)];
+        }
+        x
+    }
+
+    pub fn determinant(&self) -> f64 {
+        (0..self.lu.rows).fold(self.sign, |d, i| d * self.lu[(i, i)])
+    }
+}
+
+/// Householder QR factorization of a matrix with at least as many rows as
+/// columns, for least-squares problems.
+#[derive(Debug, Clone)]
+pub struct Qr {
+    /// R above the diagonal, Householder vectors below (with `diag` and
+    /// `betas` completing them)
+    qr: Matrix,
+    diag: Vec<f64>,
+    betas: Vec<f64>,
+}
+
+impl Qr {
+    /// Factorize `a`. Returns `None` if it has fewer rows than columns or
+    /// is rank deficient.
+    pub fn new(a: &Matrix) -> Option<Self> {
+        let (m, n) = (a.rows, a.cols);
+        if m < n {
+            return None;
+        }
+        let tolerance = a.max_abs() * SINGULAR_EPSILON;
+        let mut qr = a.clone();
+        let mut diag = vec![0.0; n];
+        let mut betas = vec![0.0; n];
+
+        for k in 0..n {
+            let norm = (k..m).map(|i| qr[(i, k)] * qr[(i, k)]).sum::<f64>().sqrt();
+            if norm <= tolerance {
+                return None;
+            }
+            // Reflect column k onto -sign(a_kk) |a_k| e_k to avoid cancellation
+            let alpha = if qr[(k, k)] > 0.0 { -norm } else { norm };
+            qr[(k, k)] -= alpha;
+            let v_norm_sq: f64 = (k..m).map(|i| qr[(i, k)] * qr[(i, k)]).sum();
+            let beta = 2.0 / v_norm_sq;
+            for j in k + 1..n {
+                let s: f64 = (k..m).map(|i| qr[(i, k)] * qr[(i, j)]).sum();
+                for i in k..m {
+                    qr[(i, j)] -= beta * s * qr[(i, k)];
+                }
+            }
+            diag[k] = alpha;
+            betas[k] = beta;
+        }
+        Some(Self { qr, diag, betas })
+    }
+
+    /// Solve `a x ≈ b` in the least-squares sense (exactly for square `a`).
+    pub fn solve_least_squares(&self, b: &[f64]) -> Vec<f64> {
+        let (m, n) = (self.qr.rows, self.qr.cols);
+        assert_eq!(b.len(), m);
+        // y = Qᵀ b
+        let mut y = b.to_vec();
+        for k in 0..n {
+            let s: f64 = (k..m).map(|i| self.qr[(i, k)] * y[i]).sum();
+            for (i, yi) in y.iter_mut().enumerate().skip(k) {
+                *yi -= self.betas[k] * s * self.qr[(i, k)];
+            }
+        }
+        // Back substitution with R
+        let mut x = vec![0.0; n];
+        for i in (0..n).rev() {
+            let s: f64 = (i + 1..n).map(|j| self.qr[(i, j)] * x[j]).sum();
+            x[i] = (y[i] - s) / self.diag[i];
+        }
+        x
+    }
+}
+
+/// Solve `a x = b` for square `a` (LU with partial pivoting).
+pub fn solve(a: &Matrix, b: &[f64]) -> Option<Vec<f64>> {
+    Lu::new(a).map(|lu| lu.solve(b))
+}
+
+/// Least-squares solution of the overdetermined system `a x ≈ b`.
+pub fn solve_least_squares(a: &Matrix, b: &[f64]) -> Option<Vec<f64>> {
+    Qr::new(a).map(|qr| qr.solve_least_squares(b))
+}
+
+/// Square band matrix with `lower` sub- and `upper` super-diagonals, as
+/// produced by B-spline collocation (bandwidth = degree).
+#[derive(Debug, Clone, PartialEq)]
+pub struct BandedMatrix {
+    n: usize,
+    lower: usize,
+    upper: usize,
+    /// Row `i` stores columns `i - lower ..= i + upper`
+    data: Vec<f64>,
+}
+
+impl BandedMatrix {
+    pub fn zeros(n: usize, lower: usize, upper: usize) -> Self {
+        Self {
+            n,
+            lower,
+            upper,
+            data: vec![0.0; n * (lower + upper + 1)],
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    /// Entry `(i, j)`; zero outside the band.
+    pub fn get(&self, i: usize, j: usize) -> f64 {
+        self.offset(i, j).map_or(0.0, |k| self.data[k])
+    }
+
+    /// Set entry `(i, j)`, which must lie inside the band.
+    pub fn set(&mut self, i: usize, j: usize, value: f64) {
+        let k = self.offset(i, j).expect("entry outside matrix band");
+        self.data[k] = value;
+    }
+
+    fn offset(&self, i: usize, j: usize) -> Option<usize> {
+        let inside = i < self.n && j < self.n && j + self.lower >= i && j <= i + self.upper;
+        inside.then(|| i * (self.lower + self.upper + 1) + j + self.lower - i)
+    }
+
+    /// Solve `a x = b` by Gaussian elimination without pivoting, which
+    /// keeps the band structure. Stable for the diagonally dominant and
+    /// totally positive matrices of spline interpolation; returns `None`
+    /// on a vanishing pivot.
+    pub fn solve(&self, b: &[f64]) -> Option<Vec<f64>> {
+        let n = self.n;
+        assert_eq!(b.len(), n);
+        let tolerance = self.data.iter().fold(0.0f64, |m, v| m.max(v.abs())) * SINGULAR_EPSILON;
+        let mut a = self.clone();
+        let mut x = b.to_vec();
+
+        for k in 0..n {
+            let pivot = a.get(k, k);
+            if pivot.abs() <= tolerance {
+                return None;
+            }
+            let last_col = (k + a.upper).min(n - 1);
+            for i in k + 1..=(k + a.lower).min(n - 1) {
+                let factor = a.get(i, k) / pivot;
+                if factor == 0.0 {
+                    continue;
+                }
+                for j in k..=last_col {
+                    let value = a.get(i, j) - factor * a.get(k, j);
+                    a.set(i, j, value);
+                }
+                x[i] -= factor * x[k];
+            }
+        }
+        for i in (0..n).rev() {
+            let last_col = (i + a.upper).min(n - 1);
+            let s: f64 = (i + 1..=last_col).map(|j| a.get(i, j) * x[j]).sum();
+            x[i] = (x[i] - s) / a.get(i, i);
+        }
+        Some(x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: &[f64], b: &[f64]) {
+        assert_eq!(a.len(), b.len());
+        for (x, y) in a.iter().zip(b) {
+            assert!((x - y).abs() < 1e-9, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_lu_solve_needs_pivoting() {
+        // Zero in the top-left corner forces a row swap
+        let a = Matrix::from_rows(&[&[0.0, 2.0, 1.0], &[1.0, 1.0, 0.0], &[3.0, 0.0, 1.0]]);
+        let x = [1.0, -2.0, 3.0];
+        let b = a.mul_vec(&x);
+        let lu = Lu::new(&a).unwrap();
+        assert_close(&lu.solve(&b), &x);
+        assert!((lu.determinant() - -5.0).abs() < 1e-12);
+
+        let singular = Matrix::from_rows(&[&[1.0, 2.0], &[2.0, 4.0]]);
+        assert!(solve(&singular, &[1.0, 2.0]).is_none());
+        assert!(Lu::new(&Matrix::zeros(2, 3)).is_none());
+    }
+
+    #[test]
+    fn test_qr_least_squares_line_fit() {
+        // y = 2x + 1 sampled with symmetric noise: the fit recovers it
+        let xs = [0.0, 1.0, 2.0, 3.0, 4.0];
+        let noise = [0.1, -0.1, 0.0, 0.1, -0.1];
+        let rows: Vec<[f64; 2]> = xs.iter().map(|&x| [x, 1.0]).collect();
+        let row_refs: Vec<&[f64]> = rows.iter().map(|r| &r[..]).collect();
+        let a = Matrix::from_rows(&row_refs);
+        let b: Vec<f64> = xs
+            .iter()
+            .zip(noise)
+            .map(|(x, n)| 2.0 * x + 1.0 + n)
+            .collect();
+
+        let fit = solve_least_squares(&a, &b).unwrap();
+        assert!((fit[0] - 1.98).abs() < 1e-9 && (fit[1] - 1.04).abs() < 1e-9);
+
+        // Square systems are solved exactly
+        let square = Matrix::from_rows(&[&[4.0, 1.0], &[2.0, 3.0]]);
+        assert_close(
+            &solve_least_squares(&square, &[6.0, 8.0]).unwrap(),
+            &[1.0, 2.0],
+        );
+
+        let rank_deficient = Matrix::from_rows(&[&[1.0, 2.0], &[2.0, 4.0], &[3.0, 6.0]]);
+        assert!(Qr::new(&rank_deficient).is_none());
+    }
+
+    #[test]
+    fn test_banded_solve_matches_dense() {
+        // Tridiagonal plus one extra super-diagonal
+        let n = 8;
+        let mut band = BandedMatrix::zeros(n, 1, 2);
+        let mut dense = Matrix::zeros(n, n);
+        for i in 0..n {
+            for j in i.saturating_sub(1)..=(i + 2).min(n - 1) {
+                let value = if i == j {
+                    4.0
+                } else {
+                    1.0 / (1 + i + j) as f64
+                };
+                band.set(i, j, value);
+                dense[(i, j)] = value;
+            }
+        }
+        assert_eq!(band.get(0, 5), 0.0);
+
+        let b: Vec<f64> = (0..n).map(|i| i as f64 - 3.0).collect();
+        assert_close(&band.solve(&b).unwrap(), &solve(&dense, &b).unwrap());
+        assert!(BandedMatrix::zeros(3, 1, 1)
+            .solve(&[1.0, 2.0, 3.0])
+            .is_none());
+    }
+
+    #[test]
+    fn test_matrix_helpers() {
+        let a = Matrix::from_rows(&[&[1.0, 2.0, 3.0], &[4.0, 5.0, 6.0]]);
+        assert_eq!(a.transpose()[(2, 1)], 6.0);
+        assert_eq!(a.mul_vec(&[1.0, 0.0, -1.0]), vec![-2.0, -2.0]);
+        assert_eq!(
+            Matrix::identity(3).mul_vec(&[7.0, 8.0, 9.0]),
+            vec![7.0, 8.0, 9.0]
+        );
+    }
+}
@@ -2,7 +2,7 @@ use crate::{Point3, Vector3};
 use serde::{Deserialize, Serialize};
 
 /// A plane in 3D space defined by a point and normal.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Plane {
     pub origin: Point3,
     pub normal: Vector3,
@@ -37,6 +37,104 @@ impl Plane {
     pub fn project_point(&self, point: Point3) -> Point3 {
         point - self.normal * self.signed_distance(point)
     }
+
+    /// Least-squares plane through a point set.
+    ///
+    /// The plane passes through the centroid, with the normal along the
+    /// direction of least variance (smallest eigenvector of the covariance
+    /// matrix). The normal sign is arbitrary. Returns `None` for fewer than
+    /// three points or (nearly) collinear points.
+    pub fn fit(points: &[Point3]) -> Option<Self> {
+        if points.len() < 3 {
+            return None;
+        }
+        let centroid = points.iter().sum::<Point3>() / points.len() as f64;
+        let mut covariance = [[0.0; 3]; 3];
+        for p in points {
+            let d = *p - centroid;
+            for (i, row) in covariance.iter_mut().enumerate() {
+                for (j, value) in row.iter_mut().enumerate() {
+                    *value += d[i] * d[j];
+                }
+            }
+        }
+
+        let (values, vectors) = symmetric_eigen(covariance);
+        let mut order = [0, 1, 2];
+        order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+        // The two in-plane directions must both have spread
+        if values[order[1]] <= values[order[2]] * 1e-12 {
+            return None;
+        }
+        let k = order[0];
+        let normal = Vector3::new(vectors[0][k], vectors[1][k], vectors[2][k]);
+        Some(Self::new(centroid, normal))
+    }
+
+    /// Largest distance of any point from the plane (0 for no points).
+    pub fn max_deviation(&self, points: &[Point3]) -> f64 {
+        points
+            .iter()
+            .map(|&p| self.signed_distance(p).abs())
+            .fold(0.0, f64::max)
+    }
+}
+
+/// How far a point set is from planar: the largest distance from its
+/// best-fit plane. `None` when no plane can be fitted.
+pub fn planarity_deviation(points: &[Point3]) -> Option<f64> {
+    Plane::fit(points).map(|plane| plane.max_deviation(points))
+}
+
+/// Normal of a polygon by Newell's method, which stays robust for
+/// non-planar and non-convex outlines. Not normalized: its length is twice
+/// the polygon area, zero for degenerate polygons.
+pub fn newell_normal(points: &[Point3]) -> Vector3 {
+    let mut normal = Vector3::ZERO;
+    for (i, a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+        normal.x += (a.y - b.y) * (a.z + b.z);
+        normal.y += (a.z - b.z) * (a.x + b.x);
+        normal.z += (a.x - b.x) * (a.y + b.y);
+    }
+    normal
+}
+
+/// Eigenvalues and eigenvectors (as matrix columns) of a symmetric 3x3
+/// matrix, by cyclic Jacobi rotations.
+fn symmetric_eigen(mut a: [[f64; 3]; 3]) -> ([f64; 3], [[f64; 3]; 3]) {
+    let mut v = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    let scale: f64 = a.iter().flatten().map(|x| x * x).sum();
+    for _sweep in 0..32 {
+        let off = a[0][1] * a[0][1] + a[0][2] * a[0][2] + a[1][2] * a[1][2];
+        if off <= scale * 1e-30 {
+            break;
+        }
+        for (p, q) in [(0, 1), (0, 2), (1, 2)] {
+            if a[p][q] == 0.0 {
+                continue;
+            }
+            // Rotation angle that zeroes a[p][q]
+            let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+            let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+            let c = 1.0 / (t * t + 1.0).sqrt();
+            let s = t * c;
+            for row in a.iter_mut() {
+                let (kp, kq) = (row[p], row[q]);
+                row[p] = c * kp - s * kq;
+                row[q] = s * kp + c * kq;
+            }
+            let (row_p, row_q) = (a[p], a[q]);
+            a[p] = std::array::from_fn(|k| c * row_p[k] - s * row_q[k]);
+            a[q] = std::array::from_fn(|k| s * row_p[k] + c * row_q[k]);
+            for row in v.iter_mut() {
+                let (kp, kq) = (row[p], row[q]);
+                row[p] = c * kp - s * kq;
+                row[q] = s * kp + c * kq;
+            }
+        }
+    }
+    ([a[0][0], a[1][1], a[2][2]], v)
 }
 
 #[cfg(test)]
@@ -51,10 +149,71 @@ mod tests {
         assert!((plane.signed_distance(dvec3(0.0, 0.0, -3.0)) + 3.0).abs() < 1e-10);
     }
 
+    #[test]
+    fn test_newell_normal() {
+        // A non-convex L shape in the plane z = 2, counter-clockwise
+        let points = [
+            dvec3(0.0, 0.0, 2.0),
+            dvec3(2.0, 0.0, 2.0),
+            dvec3(2.0, 1.0, 2.0),
+            dvec3(1.0, 1.0, 2.0),
+            dvec3(1.0, 2.0, 2.0),
+            dvec3(0.0, 2.0, 2.0),
+        ];
+        assert!((newell_normal(&points) - dvec3(0.0, 0.0, 6.0)).length() < 1e-12);
+        let reversed: Vec<Point3> = points.iter().rev().copied().collect();
+        assert!((newell_normal(&reversed) + dvec3(0.0, 0.0, 6.0)).length() < 1e-12);
+        assert_eq!(newell_normal(&points[..2]), Vector3::ZERO);
+    }
+
     #[test]
     fn test_project_point() {
         let plane = Plane::xy();
         let projected = plane.project_point(dvec3(1.0, 2.0, 5.0));
         assert!((projected - dvec3(1.0, 2.0, 0.0)).length() < 1e-10);
     }
+
+    #[test]
+    fn test_fit_tilted_plane() {
+        let normal = dvec3(1.0, -2.0, 3.0).normalize();
+        let (u, v) = normal.any_orthonormal_pair();
+        let origin = dvec3(4.0, 5.0, 6.0);
+        let points: Vec<Point3> = (0..20)
+            .map(|i| {
+                let (a, b) = ((i % 5) as f64, (i / 5) as f64 * 1.5);
+                origin + u * a + v * b
+            })
+            .collect();
+
+        let plane = Plane::fit(&points).unwrap();
+        assert!(plane.normal.dot(normal).abs() > 1.0 - 1e-12);
+        assert!(plane.max_deviation(&points) < 1e-9);
+        assert!(planarity_deviation(&points).unwrap() < 1e-9);
+    }
+
+    #[test]
+    fn test_planarity_deviation() {
+        // Unit square with one corner lifted: a warped quad
+        let warped = [
+            dvec3(0.0, 0.0, 0.0),
+            dvec3(1.0, 0.0, 0.0),
+            dvec3(1.0, 1.0, 0.2),
+            dvec3(0.0, 1.0, 0.0),
+        ];
+        let deviation = planarity_deviation(&warped).unwrap();
+        assert!((deviation - 0.05).abs() < 1e-3, "{}", deviation);
+
+        let plane = Plane::fit(&warped).unwrap();
+        assert!(plane.normal.z.abs() > 0.95);
+    }
+
+    #[test]
+    fn test_fit_degenerate() {
+        assert!(Plane::fit(&[dvec3(0.0, 0.0, 0.0), dvec3(1.0, 0.0, 0.0)]).is_none());
+        let collinear: Vec<Point3> = (0..5)
+            .map(|i| dvec3(i as f64, 2.0 * i as f64, 0.0))
+            .collect();
+        assert!(Plane::fit(&collinear).is_none());
+        assert_eq!(Plane::xy().max_deviation(&[]), 0.0);
+    }
 }
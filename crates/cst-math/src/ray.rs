@@ -1,6 +1,21 @@
-use crate::{Point3, Vector3};
+use crate::plane::Plane;
+use crate::{Aabb3, Point3, Vector3};
 use serde::{Deserialize, Serialize};
 
+/// Determinant below which a ray counts as parallel to a triangle.
+const PARALLEL_EPSILON: f64 = 1e-12;
+
+/// Where a ray hits a triangle `(a, b, c)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TriangleHit {
+    /// Ray parameter of the hit point.
+    pub t: f64,
+    /// Barycentric weight of `b`.
+    pub u: f64,
+    /// Barycentric weight of `c`; `a` gets `1 - u - v`.
+    pub v: f64,
+}
+
 /// A ray in 3D space defined by origin and direction.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Ray {
@@ -31,6 +46,131 @@ impl Ray {
     pub fn distance_to_point(&self, point: Point3) -> f64 {
         (point - self.closest_point(point)).length()
     }
+
+    /// Ray/triangle intersection (Möller–Trumbore). Both triangle sides are
+    /// hit; hits behind the origin are rejected.
+    ///
+    /// Fast, but a ray through an edge shared by two triangles can slip
+    /// between them. Use [`Ray::intersect_triangle_watertight`] when that
+    /// matters.
+    pub fn intersect_triangle(&self, a: Point3, b: Point3, c: Point3) -> Option<TriangleHit> {
+        let e1 = b - a;
+        let e2 = c - a;
+        let p = self.direction.cross(e2);
+        let det = e1.dot(p);
+        if det.abs() < PARALLEL_EPSILON {
+            return None;
+        }
+        let inv_det = 1.0 / det;
+        let s = self.origin - a;
+        let u = s.dot(p) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = s.cross(e1);
+        let v = self.direction.dot(q) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let t = e2.dot(q) * inv_det;
+        (t >= 0.0).then_some(TriangleHit { t, u, v })
+    }
+
+    /// Watertight ray/triangle intersection (Woop, Benthin and Wald 2013).
+    ///
+    /// Works in a ray-aligned frame where the edge tests of neighbouring
+    /// triangles are evaluated identically, so a ray through a shared edge
+    /// or vertex always hits at least one of them. Both sides are hit.
+    pub fn intersect_triangle_watertight(
+        &self,
+        a: Point3,
+        b: Point3,
+        c: Point3,
+    ) -> Option<TriangleHit> {
+        let d = self.direction;
+        // Dominant axis becomes z; swapping x and y keeps the winding
+        let m = d.abs();
+        let kz = if m.x >= m.y && m.x >= m.z {
+            0
+        } else if m.y >= m.z {
+            1
+        } else {
+            2
+        };
+        let mut kx = (kz + 1) % 3;
+        let mut ky = (kx + 1) % 3;
+        if d[kz] < 0.0 {
+            std::mem::swap(&mut kx, &mut ky);
+        }
+        let sx = d[kx] / d[kz];
+        let sy = d[ky] / d[kz];
+        let sz = 1.0 / d[kz];
+
+        // Shear the vertices so the ray runs along +z through the origin
+        let [a, b, c] = [a, b, c].map(|p| p - self.origin);
+        let shear = |p: Vector3| (p[kx] - sx * p[kz], p[ky] - sy * p[kz], sz * p[kz]);
+        let (ax, ay, az) = shear(a);
+        let (bx, by, bz) = shear(b);
+        let (cx, cy, cz) = shear(c);
+
+        // Scaled barycentrics from 2D edge functions
+        let e0 = cx * by - cy * bx;
+        let e1 = ax * cy - ay * cx;
+        let e2 = bx * ay - by * ax;
+        if (e0 < 0.0 || e1 < 0.0 || e2 < 0.0) && (e0 > 0.0 || e1 > 0.0 || e2 > 0.0) {
+            return None;
+        }
+        let det = e0 + e1 + e2;
+        if det == 0.0 {
+            return None;
+        }
+
+        let t = (e0 * az + e1 * bz + e2 * cz) / det;
+        (t >= 0.0).then_some(TriangleHit {
+            t,
+            u: e1 / det,
+            v: e2 / det,
+        })
+    }
+
+    /// Ray/box slab test, returning the entry and exit parameters.
+    ///
+    /// The entry is clamped to 0 when the origin is inside the box. Rays
+    /// parallel to a face and lying in its plane count as hits.
+    pub fn intersect_aabb(&self, aabb: &Aabb3) -> Option<(f64, f64)> {
+        let mut t_min = 0.0_f64;
+        let mut t_max = f64::INFINITY;
+        for axis in 0..3 {
+            let origin = self.origin[axis];
+            if self.direction[axis] == 0.0 {
+                // Parallel to the slab: inside it everywhere or nowhere
+                if origin < aabb.min[axis] || origin > aabb.max[axis] {
+                    return None;
+                }
+                continue;
+            }
+            let inv = 1.0 / self.direction[axis];
+            let t1 = (aabb.min[axis] - origin) * inv;
+            let t2 = (aabb.max[axis] - origin) * inv;
+            t_min = t_min.max(t1.min(t2));
+            t_max = t_max.min(t1.max(t2));
+            if t_min > t_max {
+                return None;
+            }
+        }
+        Some((t_min, t_max))
+    }
+
+    /// Ray/plane intersection parameter. `None` when the ray is parallel to
+    /// the plane or the plane is behind the origin.
+    pub fn intersect_plane(&self, plane: &Plane) -> Option<f64> {
+        let denom = self.direction.dot(plane.normal);
+        if denom.abs() < PARALLEL_EPSILON {
+            return None;
+        }
+        let t = (plane.origin - self.origin).dot(plane.normal) / denom;
+        (t >= 0.0).then_some(t)
+    }
 }
 
 #[cfg(test)]
@@ -51,4 +191,87 @@ mod tests {
         let dist = ray.distance_to_point(dvec3(5.0, 3.0, 0.0));
         assert!((dist - 3.0).abs() < 1e-10);
     }
+
+    #[test]
+    fn test_intersect_triangle() {
+        let (a, b, c) = (
+            dvec3(0.0, 0.0, 0.0),
+            dvec3(1.0, 0.0, 0.0),
+            dvec3(0.0, 1.0, 0.0),
+        );
+        let ray = Ray::new(dvec3(0.25, 0.5, 2.0), dvec3(0.0, 0.0, -1.0));
+        for hit in [
+            ray.intersect_triangle(a, b, c).unwrap(),
+            ray.intersect_triangle_watertight(a, b, c).unwrap(),
+        ] {
+            assert!((hit.t - 2.0).abs() < 1e-12);
+            assert!((hit.u - 0.25).abs() < 1e-12);
+            assert!((hit.v - 0.5).abs() < 1e-12);
+        }
+
+        // Back side, miss, and triangle behind the origin
+        let below = Ray::new(dvec3(0.25, 0.25, -1.0), dvec3(0.0, 0.0, 1.0));
+        assert!(below.intersect_triangle(a, b, c).is_some());
+        assert!(below.intersect_triangle_watertight(a, b, c).is_some());
+        let outside = Ray::new(dvec3(0.8, 0.8, 1.0), dvec3(0.0, 0.0, -1.0));
+        assert!(outside.intersect_triangle(a, b, c).is_none());
+        assert!(outside.intersect_triangle_watertight(a, b, c).is_none());
+        let away = Ray::new(dvec3(0.25, 0.25, 1.0), dvec3(0.0, 0.0, 1.0));
+        assert!(away.intersect_triangle(a, b, c).is_none());
+        assert!(away.intersect_triangle_watertight(a, b, c).is_none());
+    }
+
+    #[test]
+    fn test_watertight_shared_edge() {
+        // Quad split along its diagonal; rays through the diagonal must hit
+        let p = [
+            dvec3(0.0, 0.0, 0.0),
+            dvec3(1.0, 0.0, 0.0),
+            dvec3(1.0, 1.0, 0.0),
+            dvec3(0.0, 1.0, 0.0),
+        ];
+        let direction = dvec3(0.3, -0.2, -1.0);
+        for i in 1..100 {
+            let s = i as f64 / 100.0;
+            let ray = Ray::new(dvec3(s, s, 0.0) - direction, direction);
+            let hits = [[0, 1, 2], [0, 2, 3]]
+                .iter()
+                .filter(|t| {
+                    ray.intersect_triangle_watertight(p[t[0]], p[t[1]], p[t[2]])
+                        .is_some()
+                })
+                .count();
+            assert!(hits >= 1, "ray {} slipped through", i);
+        }
+    }
+
+    #[test]
+    fn test_intersect_aabb() {
+        let aabb = Aabb3::new(dvec3(-1.0, -1.0, -1.0), dvec3(1.0, 1.0, 1.0));
+        let ray = Ray::new(dvec3(-5.0, 0.0, 0.0), dvec3(1.0, 0.0, 0.0));
+        assert_eq!(ray.intersect_aabb(&aabb), Some((4.0, 6.0)));
+
+        // Inside: entry clamped to the origin
+        let inside = Ray::new(dvec3(0.0, 0.0, 0.0), dvec3(0.0, 1.0, 0.0));
+        assert_eq!(inside.intersect_aabb(&aabb), Some((0.0, 1.0)));
+
+        // Parallel outside, parallel on a face, and pointing away
+        let parallel = Ray::new(dvec3(-5.0, 2.0, 0.0), dvec3(1.0, 0.0, 0.0));
+        assert!(parallel.intersect_aabb(&aabb).is_none());
+        let grazing = Ray::new(dvec3(-5.0, 1.0, 0.0), dvec3(1.0, 0.0, 0.0));
+        assert_eq!(grazing.intersect_aabb(&aabb), Some((4.0, 6.0)));
+        let away = Ray::new(dvec3(-5.0, 0.0, 0.0), dvec3(-1.0, 0.0, 0.0));
+        assert!(away.intersect_aabb(&aabb).is_none());
+    }
+
+    #[test]
+    fn test_intersect_plane() {
+        let plane = Plane::new(dvec3(0.0, 0.0, 3.0), dvec3(0.0, 0.0, 1.0));
+        let ray = Ray::new(dvec3(1.0, 1.0, 0.0), dvec3(0.0, 0.0, 2.0));
+        assert_eq!(ray.intersect_plane(&plane), Some(3.0));
+        let away = Ray::new(dvec3(1.0, 1.0, 0.0), dvec3(0.0, 0.0, -1.0));
+        assert!(away.intersect_plane(&plane).is_none());
+        let parallel = Ray::new(dvec3(1.0, 1.0, 0.0), dvec3(1.0, 0.0, 0.0));
+        assert!(parallel.intersect_plane(&plane).is_none());
+    }
 }
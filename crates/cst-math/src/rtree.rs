@@ -0,0 +1,209 @@
+//! Packed R-tree over 3D boxes.
+//!
+//! A static bounding volume hierarchy built in one pass: items are sorted
+//! along a Morton curve through their centres, then grouped bottom-up into
+//! nodes of [`NODE_SIZE`] children. The whole tree lives in two flat
+//! arrays, so it serializes compactly and a box query only visits the
+//! nodes whose bounds it touches. Used for element selection and clipping,
+//! where the set of boxes is fixed once a model is loaded.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Aabb3, Point3};
+
+/// Children per internal node.
+pub const NODE_SIZE: usize = 16;
+
+/// Bits per axis of the Morton code the items are sorted by.
+const MORTON_BITS: u32 = 10;
+
+/// Immutable box index for intersection queries.
+///
+/// Items are identified by their position in the slice the tree was built
+/// from, or by the ids given to [`from_entries`](Self::from_entries).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PackedRTree {
+    /// Item boxes in curve order, then the node boxes of each level up to
+    /// the root
+    boxes: Vec<Aabb3>,
+    /// Item id for a leaf, position of the first child for a node
+    indices: Vec<usize>,
+    /// End of each level in `boxes`, leaves first
+    level_ends: Vec<usize>,
+}
+
+impl PackedRTree {
+    /// Index `items`, numbered in slice order.
+    pub fn new(items: &[Aabb3]) -> Self {
+        let entries: Vec<(usize, Aabb3)> = items.iter().copied().enumerate().collect();
+        Self::from_entries(&entries)
+    }
+
+    /// Index boxes under their own ids, e.g. when only some of a list of
+    /// elements have bounds.
+    pub fn from_entries(entries: &[(usize, Aabb3)]) -> Self {
+        let Some(extent) = entries.iter().map(|e| e.1).reduce(|a, b| a.merge(&b)) else {
+            return Self::default();
+        };
+        let mut sorted: Vec<(u32, usize, Aabb3)> = entries
+            .iter()
+            .map(|&(id, b)| (morton_code(b.center(), &extent), id, b))
+            .collect();
+        sorted.sort_by_key(|&(code, id, _)| (code, id));
+
+        let mut boxes: Vec<Aabb3> = sorted.iter().map(|e| e.2).collect();
+        let mut indices: Vec<usize> = sorted.iter().map(|e| e.1).collect();
+        let mut level_ends = vec![boxes.len()];
+        let mut start = 0;
+        while boxes.len() - start > 1 {
+            let end = boxes.len();
+            for first in (start..end).step_by(NODE_SIZE) {
+                let last = (first + NODE_SIZE).min(end);
+                let bounds = boxes[first + 1..last]
+                    .iter()
+                    .fold(boxes[first], |a, b| a.merge(b));
+                boxes.push(bounds);
+                indices.push(first);
+            }
+            start = end;
+            level_ends.push(boxes.len());
+        }
+        Self {
+            boxes,
+            indices,
+            level_ends,
+        }
+    }
+
+    /// Number of indexed items.
+    pub fn len(&self) -> usize {
+        self.level_ends.first().copied().unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Boxes of the items in curve order, followed by the boxes of each
+    /// level of nodes up to the root, which is last.
+    pub fn boxes(&self) -> &[Aabb3] {
+        &self.boxes
+    }
+
+    /// Parallel to [`boxes`](Self::boxes): the item id for a leaf, the
+    /// position in `boxes` of the first of up to [`NODE_SIZE`] children for
+    /// a node.
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+
+    /// End of each level in [`boxes`](Self::boxes), leaves first. A node's
+    /// children stop at the end of the level below it.
+    pub fn level_ends(&self) -> &[usize] {
+        &self.level_ends
+    }
+
+    /// Box around every item; `None` when the tree is empty.
+    pub fn bounds(&self) -> Option<Aabb3> {
+        self.boxes.last().copied()
+    }
+
+    /// Ids of the items whose boxes intersect `query` (touching counts), in
+    /// ascending order.
+    pub fn search(&self, query: &Aabb3) -> Vec<usize> {
+        let mut found = Vec::new();
+        let Some(root) = self.boxes.len().checked_sub(1) else {
+            return found;
+        };
+        let mut stack = vec![(root, self.level_ends.len() - 1)];
+        while let Some((node, level)) = stack.pop() {
+            if !self.boxes[node].intersects(query) {
+                continue;
+            }
+            if level == 0 {
+                found.push(self.indices[node]);
+                continue;
+            }
+            let first = self.indices[node];
+            let last = (first + NODE_SIZE).min(self.level_ends[level - 1]);
+            stack.extend((first..last).map(|child| (child, level - 1)));
+        }
+        found.sort_unstable();
+        found
+    }
+}
+
+/// Position of `p` along the Morton curve through `extent`.
+fn morton_code(p: Point3, extent: &Aabb3) -> u32 {
+    let cells = ((1u32 << MORTON_BITS) - 1) as f64;
+    let size = extent.extents().max(Point3::splat(1e-12));
+    let cell = ((p - extent.min) / size * cells).clamp(Point3::ZERO, Point3::splat(cells));
+    spread(cell.x as u32) | spread(cell.y as u32) << 1 | spread(cell.z as u32) << 2
+}
+
+/// The low [`MORTON_BITS`] bits of `v`, two zero bits after each.
+fn spread(v: u32) -> u32 {
+    let mut v = v & 0x3ff;
+    v = (v | v << 16) & 0x0300_00ff;
+    v = (v | v << 8) & 0x0300_f00f;
+    v = (v | v << 4) & 0x030c_30c3;
+    (v | v << 2) & 0x0924_9249
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::dvec3;
+
+    /// Unit boxes on a `n` × `n` grid in the xy plane, 2 apart.
+    fn grid(n: usize) -> Vec<Aabb3> {
+        (0..n * n)
+            .map(|i| {
+                let min = dvec3((i % n) as f64 * 2.0, (i / n) as f64 * 2.0, 0.0);
+                Aabb3::new(min, min + 1.0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_search_matches_brute_force() {
+        let items = grid(20);
+        let tree = PackedRTree::new(&items);
+        assert_eq!(tree.len(), 400);
+        let bounds = tree.bounds().unwrap();
+        assert_eq!(bounds.min, dvec3(0.0, 0.0, 0.0));
+        assert_eq!(bounds.max, dvec3(39.0, 39.0, 1.0));
+
+        let queries = [
+            Aabb3::new(dvec3(3.5, 3.5, 0.5), dvec3(9.0, 5.5, 0.5)),
+            Aabb3::new(dvec3(-5.0, -5.0, -5.0), dvec3(50.0, 50.0, 5.0)),
+            Aabb3::new(dvec3(1.0, 1.0, 1.0), dvec3(1.0, 1.0, 1.0)),
+            Aabb3::new(dvec3(1.2, 0.0, 0.0), dvec3(1.8, 40.0, 1.0)),
+            Aabb3::new(dvec3(0.0, 0.0, 2.0), dvec3(40.0, 40.0, 3.0)),
+        ];
+        for query in &queries {
+            let expected: Vec<usize> = (0..items.len())
+                .filter(|&i| items[i].intersects(query))
+                .collect();
+            assert_eq!(tree.search(query), expected);
+        }
+        assert_eq!(tree.search(&queries[1]).len(), 400);
+        assert!(tree.search(&queries[3]).is_empty());
+    }
+
+    #[test]
+    fn test_empty_and_single() {
+        let empty = PackedRTree::new(&[]);
+        assert!(empty.is_empty());
+        assert!(empty.bounds().is_none());
+        let everything = Aabb3::new(Point3::splat(-1e9), Point3::splat(1e9));
+        assert!(empty.search(&everything).is_empty());
+
+        let single = PackedRTree::new(&grid(1));
+        assert_eq!(single.len(), 1);
+        assert_eq!(single.search(&everything), vec![0]);
+
+        let sparse = PackedRTree::from_entries(&[(7, grid(1)[0]), (3, grid(2)[3])]);
+        assert_eq!(sparse.search(&everything), vec![3, 7]);
+    }
+}
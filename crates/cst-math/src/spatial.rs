@@ -0,0 +1,229 @@
+//! Spatial hash over 3D points.
+//!
+//! Points are bucketed into a uniform grid of cubic cells, so radius and
+//! nearest-neighbour queries only visit the cells around the query point.
+//! Used for vertex welding, sewing and duplicate detection, where points
+//! arrive incrementally and queries use a radius close to the cell size.
+
+use std::collections::HashMap;
+
+use crate::Point3;
+
+type Cell = (i64, i64, i64);
+
+/// Incremental point index for radius and nearest-neighbour queries.
+///
+/// Points are identified by their insertion order. Queries are fastest
+/// when the radius is about the cell size.
+#[derive(Debug, Clone)]
+pub struct PointIndex {
+    cell_size: f64,
+    points: Vec<Point3>,
+    cells: HashMap<Cell, Vec<usize>>,
+}
+
+impl PointIndex {
+    /// Empty index with the given cell size (clamped to at least 1e-12).
+    pub fn new(cell_size: f64) -> Self {
+        Self {
+            cell_size: cell_size.max(1e-12),
+            points: Vec::new(),
+            cells: HashMap::new(),
+        }
+    }
+
+    /// Index all `points`, numbered in slice order.
+    pub fn from_points(points: &[Point3], cell_size: f64) -> Self {
+        let mut index = Self::new(cell_size);
+        for &p in points {
+            index.insert(p);
+        }
+        index
+    }
+
+    /// Add a point and return its id.
+    pub fn insert(&mut self, p: Point3) -> usize {
+        let id = self.points.len();
+        self.points.push(p);
+        self.cells.entry(self.cell(p)).or_default().push(id);
+        id
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Position of point `id`.
+    pub fn point(&self, id: usize) -> Point3 {
+        self.points[id]
+    }
+
+    pub fn points(&self) -> &[Point3] {
+        &self.points
+    }
+
+    /// Ids of all points within `radius` of `p` (inclusive), in ascending
+    /// order.
+    pub fn within_radius(&self, p: Point3, radius: f64) -> Vec<usize> {
+        let mut found = Vec::new();
+        self.visit_radius(p, radius, |id| found.push(id));
+        found.sort_unstable();
+        found
+    }
+
+    /// Lowest id within `radius` of `p` that also satisfies `accept`.
+    ///
+    /// This is the welding query: with points inserted in input order, the
+    /// lowest id is the first occurrence of a cluster.
+    pub fn find_within<F>(&self, p: Point3, radius: f64, mut accept: F) -> Option<usize>
+    where
+        F: FnMut(usize) -> bool,
+    {
+        let mut best: Option<usize> = None;
+        self.visit_radius(p, radius, |id| {
+            if best.map_or(true, |b| id < b) && accept(id) {
+                best = Some(id);
+            }
+        });
+        best
+    }
+
+    /// Closest point to `p` as `(id, distance)`; ties go to the lower id.
+    pub fn nearest(&self, p: Point3) -> Option<(usize, f64)> {
+        if self.points.is_empty() {
+            return None;
+        }
+        // Search growing shells of cells until the best hit is closer than
+        // anything an unvisited shell could hold.
+        let center = self.cell(p);
+        let max_ring = self.max_ring(center);
+        let mut best: Option<(usize, f64)> = None;
+        for ring in 0..=max_ring {
+            for cell in shell(center, ring) {
+                for &id in self.cells.get(&cell).into_iter().flatten() {
+                    let d = self.points[id].distance_squared(p);
+                    if best.map_or(true, |(b, bd)| d < bd || (d == bd && id < b)) {
+                        best = Some((id, d));
+                    }
+                }
+            }
+            if let Some((_, d)) = best {
+                let reach = ring as f64 * self.cell_size;
+                if d <= reach * reach {
+                    break;
+                }
+            }
+        }
+        best.map(|(id, d)| (id, d.sqrt()))
+    }
+
+    fn cell(&self, p: Point3) -> Cell {
+        let c = p / self.cell_size;
+        (c.x.floor() as i64, c.y.floor() as i64, c.z.floor() as i64)
+    }
+
+    fn visit_radius<F: FnMut(usize)>(&self, p: Point3, radius: f64, mut visit: F) {
+        let radius = radius.max(0.0);
+        let (lo, hi) = (self.cell(p - radius), self.cell(p + radius));
+        let r_sq = radius * radius;
+        for x in lo.0..=hi.0 {
+            for y in lo.1..=hi.1 {
+                for z in lo.2..=hi.2 {
+                    for &id in self.cells.get(&(x, y, z)).into_iter().flatten() {
+                        if self.points[id].distance_squared(p) <= r_sq {
+                            visit(id);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Shell index beyond which no occupied cell exists.
+    fn max_ring(&self, center: Cell) -> i64 {
+        self.cells
+            .keys()
+            .map(|c| {
+                (c.0 - center.0)
+                    .abs()
+                    .max((c.1 - center.1).abs())
+                    .max((c.2 - center.2).abs())
+            })
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Cells at Chebyshev distance exactly `ring` from `center`.
+fn shell(center: Cell, ring: i64) -> impl Iterator<Item = Cell> {
+    (-ring..=ring).flat_map(move |dx| {
+        (-ring..=ring).flat_map(move |dy| {
+            (-ring..=ring).filter_map(move |dz| {
+                let on_shell = dx.abs() == ring || dy.abs() == ring || dz.abs() == ring;
+                on_shell.then_some((center.0 + dx, center.1 + dy, center.2 + dz))
+            })
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::dvec3;
+
+    fn grid_points() -> Vec<Point3> {
+        (0..125)
+            .map(|i| dvec3((i % 5) as f64, ((i / 5) % 5) as f64, (i / 25) as f64) * 0.7)
+            .collect()
+    }
+
+    #[test]
+    fn test_within_radius_matches_brute_force() {
+        let points = grid_points();
+        let index = PointIndex::from_points(&points, 0.5);
+        let query = dvec3(1.3, 1.1, 1.6);
+        for radius in [0.0, 0.4, 0.9, 2.5] {
+            let expected: Vec<usize> = (0..points.len())
+                .filter(|&i| points[i].distance(query) <= radius)
+                .collect();
+            assert_eq!(index.within_radius(query, radius), expected);
+        }
+    }
+
+    #[test]
+    fn test_find_within_prefers_lowest_id() {
+        let mut index = PointIndex::new(1e-6);
+        let a = index.insert(dvec3(1e-9, 0.0, 0.0));
+        let b = index.insert(dvec3(-1e-9, 0.0, 0.0));
+        assert_eq!(index.find_within(Point3::ZERO, 1e-6, |_| true), Some(a));
+        assert_eq!(index.find_within(Point3::ZERO, 1e-6, |id| id != a), Some(b));
+        assert_eq!(
+            index.find_within(dvec3(1.0, 0.0, 0.0), 1e-6, |_| true),
+            None
+        );
+    }
+
+    #[test]
+    fn test_nearest() {
+        let points = grid_points();
+        let index = PointIndex::from_points(&points, 0.25);
+        for query in [
+            dvec3(1.3, 1.1, 1.6),
+            dvec3(-5.0, 9.0, 2.0),
+            dvec3(0.0, 0.0, 0.0),
+        ] {
+            let (id, distance) = index.nearest(query).unwrap();
+            let best = points
+                .iter()
+                .map(|p| p.distance(query))
+                .fold(f64::INFINITY, f64::min);
+            assert!((distance - best).abs() < 1e-12);
+            assert!((points[id].distance(query) - best).abs() < 1e-12);
+        }
+        assert!(PointIndex::new(1.0).nearest(Point3::ZERO).is_none());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{DMat4, Point3, Vector3};
+use crate::{DMat3, DMat4, DQuat, Point3, Vector3};
 use serde::{Deserialize, Serialize};
 
 /// Rigid body transform (rotation + translation, no shear/scale).
@@ -50,6 +50,210 @@ impl Transform {
     }
 }
 
+/// Translation-rotation-scale transform, applied as scale, then rotation,
+/// then translation.
+///
+/// Unlike a matrix, the rotation stays a unit quaternion through repeated
+/// composition and interpolation, so poses do not accumulate shear.
+/// Composition and inversion are exact for uniform scale; with non-uniform
+/// scale under rotation they drop the shear a matrix product would carry.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Trs {
+    pub translation: Vector3,
+    pub rotation: DQuat,
+    pub scale: Vector3,
+}
+
+impl Trs {
+    pub const IDENTITY: Self = Self {
+        translation: Vector3::ZERO,
+        rotation: DQuat::IDENTITY,
+        scale: Vector3::ONE,
+    };
+
+    pub fn new(translation: Vector3, rotation: DQuat, scale: Vector3) -> Self {
+        Self {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    pub fn from_translation(translation: Vector3) -> Self {
+        Self {
+            translation,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn from_rotation(rotation: DQuat) -> Self {
+        Self {
+            rotation,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// Convert an affine matrix. Returns `None` if it is singular or has
+    /// shear beyond [`RIGID_TOLERANCE`]; mirroring becomes a negative z
+    /// scale.
+    pub fn from_mat4(m: DMat4) -> Option<Self> {
+        let d = decompose(m)?;
+        if d.shear().abs().max_element() > RIGID_TOLERANCE {
+            return None;
+        }
+        Some(Self::new(d.translation, d.rotation, d.scale()))
+    }
+
+    pub fn to_mat4(&self) -> DMat4 {
+        DMat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation)
+    }
+
+    pub fn transform_point(&self, p: Point3) -> Point3 {
+        self.rotation * (p * self.scale) + self.translation
+    }
+
+    pub fn transform_vector(&self, v: Vector3) -> Vector3 {
+        self.rotation * (v * self.scale)
+    }
+
+    /// Apply `self`, then `other` (same order as [`Transform::then`]).
+    pub fn then(&self, other: &Trs) -> Trs {
+        Trs {
+            translation: other.transform_point(self.translation),
+            rotation: (other.rotation * self.rotation).normalize(),
+            scale: other.scale * self.scale,
+        }
+    }
+
+    /// Inverse transform, or `None` if a scale component is zero.
+    pub fn inverse(&self) -> Option<Trs> {
+        if self.scale.cmpeq(Vector3::ZERO).any() {
+            return None;
+        }
+        let rotation = self.rotation.inverse();
+        let scale = self.scale.recip();
+        Some(Trs {
+            translation: -(rotation * self.translation) * scale,
+            rotation,
+            scale,
+        })
+    }
+
+    /// Interpolate towards `other`: linear for translation and scale,
+    /// spherical (shortest arc) for rotation.
+    pub fn interpolate(&self, other: &Trs, t: f64) -> Trs {
+        Trs {
+            translation: self.translation.lerp(other.translation, t),
+            rotation: self.rotation.slerp(other.rotation, t),
+            scale: self.scale.lerp(other.scale, t),
+        }
+    }
+}
+
+impl Default for Trs {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// Tolerance used by [`is_rigid`] on the orthonormality of the axes.
+pub const RIGID_TOLERANCE: f64 = 1e-9;
+
+/// An affine matrix split as `translation * rotation * scale_shear`.
+///
+/// `scale_shear` is upper triangular: its diagonal holds the axis scales
+/// (a negative z scale for mirrored matrices) and its upper entries the
+/// shear. For matrices without shear it is diagonal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Decomposition {
+    pub translation: Vector3,
+    pub rotation: DQuat,
+    pub scale_shear: DMat3,
+}
+
+impl Decomposition {
+    /// Axis scale factors (diagonal of `scale_shear`).
+    pub fn scale(&self) -> Vector3 {
+        Vector3::new(
+            self.scale_shear.x_axis.x,
+            self.scale_shear.y_axis.y,
+            self.scale_shear.z_axis.z,
+        )
+    }
+
+    /// Shear terms `(xy, xz, yz)`, relative to the scale of the sheared axis.
+    pub fn shear(&self) -> Vector3 {
+        let m = &self.scale_shear;
+        let scale = self.scale();
+        Vector3::new(
+            m.y_axis.x / scale.y,
+            m.z_axis.x / scale.z,
+            m.z_axis.y / scale.z,
+        )
+    }
+
+    /// Reassemble the matrix.
+    pub fn to_mat4(&self) -> DMat4 {
+        DMat4::from_translation(self.translation)
+            * DMat4::from_mat3(DMat3::from_quat(self.rotation) * self.scale_shear)
+    }
+}
+
+/// Decompose the affine part of `m` into translation, rotation and
+/// scale/shear (Gram-Schmidt / QR on the linear columns).
+///
+/// Mirroring is folded into a negative z scale so the rotation is always
+/// proper. Returns `None` if the linear part is singular.
+pub fn decompose(m: DMat4) -> Option<Decomposition> {
+    let linear = DMat3::from_mat4(m);
+    if linear.determinant().abs() < 1e-15 {
+        return None;
+    }
+
+    let (c0, c1, c2) = (linear.x_axis, linear.y_axis, linear.z_axis);
+    let sx = c0.length();
+    let q0 = c0 / sx;
+
+    let xy = q0.dot(c1);
+    let c1 = c1 - q0 * xy;
+    let sy = c1.length();
+    let q1 = c1 / sy;
+
+    let xz = q0.dot(c2);
+    let yz = q1.dot(c2);
+    let c2 = c2 - q0 * xz - q1 * yz;
+    let mut sz = c2.length();
+    let mut q2 = c2 / sz;
+    if linear.determinant() < 0.0 {
+        sz = -sz;
+        q2 = -q2;
+    }
+
+    Some(Decomposition {
+        translation: m.w_axis.truncate(),
+        rotation: DQuat::from_mat3(&DMat3::from_cols(q0, q1, q2)),
+        scale_shear: DMat3::from_cols(
+            Vector3::new(sx, 0.0, 0.0),
+            Vector3::new(xy, sy, 0.0),
+            Vector3::new(xz, yz, sz),
+        ),
+    })
+}
+
+/// Whether `m` is a rotation plus translation: orthonormal axes, no
+/// scale, shear or mirroring (within [`RIGID_TOLERANCE`]).
+pub fn is_rigid(m: DMat4) -> bool {
+    let linear = DMat3::from_mat4(m);
+    let gram = linear.transpose() * linear;
+    gram.abs_diff_eq(DMat3::IDENTITY, RIGID_TOLERANCE) && linear.determinant() > 0.0
+}
+
+/// Whether `m` flips handedness (negative determinant of the linear part),
+/// which reverses the winding of transformed faces.
+pub fn has_mirror(m: DMat4) -> bool {
+    DMat3::from_mat4(m).determinant() < 0.0
+}
+
 impl Default for Transform {
     fn default() -> Self {
         Self::identity()
@@ -85,4 +289,125 @@ mod tests {
         let result = inv.transform_point(t.transform_point(p));
         assert!((result - p).length() < 1e-10);
     }
+
+    #[test]
+    fn test_decompose_round_trip() {
+        let m = DMat4::from_translation(dvec3(1.0, 2.0, 3.0))
+            * DMat4::from_rotation_z(0.7)
+            * DMat4::from_scale(dvec3(2.0, 3.0, 0.5));
+        let d = decompose(m).unwrap();
+        assert!((d.translation - dvec3(1.0, 2.0, 3.0)).length() < 1e-10);
+        assert!((d.scale() - dvec3(2.0, 3.0, 0.5)).length() < 1e-10);
+        assert!(d.shear().length() < 1e-10);
+        assert!(d.rotation.angle_between(DQuat::from_rotation_z(0.7)) < 1e-10);
+        assert!(d.to_mat4().abs_diff_eq(m, 1e-10));
+    }
+
+    #[test]
+    fn test_decompose_shear_and_mirror() {
+        let shear = DMat4::from_cols_array(&[
+            1.0, 0.0, 0.0, 0.0, //
+            0.5, 1.0, 0.0, 0.0, //
+            0.0, 0.0, 1.0, 0.0, //
+            0.0, 0.0, 0.0, 1.0,
+        ]);
+        let d = decompose(shear).unwrap();
+        assert!((d.shear() - dvec3(0.5, 0.0, 0.0)).length() < 1e-10);
+        assert!(d.to_mat4().abs_diff_eq(shear, 1e-10));
+
+        let mirror = DMat4::from_rotation_x(0.3) * DMat4::from_scale(dvec3(-1.0, 1.0, 1.0));
+        let d = decompose(mirror).unwrap();
+        assert!(d.scale().z < 0.0);
+        assert!(d.rotation.is_normalized());
+        assert!(d.to_mat4().abs_diff_eq(mirror, 1e-10));
+
+        assert!(decompose(DMat4::from_scale(dvec3(1.0, 0.0, 1.0))).is_none());
+    }
+
+    #[test]
+    fn test_rigid_and_mirror_checks() {
+        let rigid = DMat4::from_translation(dvec3(5.0, 0.0, 0.0)) * DMat4::from_rotation_y(1.2);
+        assert!(is_rigid(rigid));
+        assert!(!has_mirror(rigid));
+
+        assert!(!is_rigid(DMat4::from_scale(dvec3(2.0, 2.0, 2.0))));
+        let mirror = DMat4::from_scale(dvec3(1.0, 1.0, -1.0));
+        assert!(!is_rigid(mirror));
+        assert!(has_mirror(mirror));
+    }
+
+    #[test]
+    fn test_trs_matches_matrix() {
+        let trs = Trs::new(
+            dvec3(1.0, 2.0, 3.0),
+            DQuat::from_rotation_y(0.4),
+            dvec3(2.0, 2.0, 2.0),
+        );
+        let m = trs.to_mat4();
+        let p = dvec3(0.5, -1.0, 4.0);
+        assert!((trs.transform_point(p) - m.transform_point3(p)).length() < 1e-10);
+        assert!((trs.transform_vector(p) - m.transform_vector3(p)).length() < 1e-10);
+
+        let back = Trs::from_mat4(m).unwrap();
+        assert!(back.to_mat4().abs_diff_eq(m, 1e-10));
+
+        let shear = DMat4::from_cols_array(&[
+            1.0, 0.0, 0.0, 0.0, 0.5, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0,
+        ]);
+        assert!(Trs::from_mat4(shear).is_none());
+    }
+
+    #[test]
+    fn test_trs_compose_and_inverse() {
+        let a = Trs::new(
+            dvec3(1.0, 0.0, 0.0),
+            DQuat::from_rotation_z(0.3),
+            dvec3(3.0, 3.0, 3.0),
+        );
+        let b = Trs::new(
+            dvec3(0.0, 5.0, 0.0),
+            DQuat::from_rotation_x(1.1),
+            Vector3::ONE,
+        );
+        let composed = a.then(&b);
+        assert!(composed
+            .to_mat4()
+            .abs_diff_eq(b.to_mat4() * a.to_mat4(), 1e-10));
+
+        let round_trip = a.then(&a.inverse().unwrap());
+        assert!(round_trip.to_mat4().abs_diff_eq(DMat4::IDENTITY, 1e-10));
+        assert!(
+            Trs::new(Vector3::ZERO, DQuat::IDENTITY, dvec3(1.0, 0.0, 1.0))
+                .inverse()
+                .is_none()
+        );
+
+        // Many small steps keep the rotation normalized
+        let step = Trs::from_rotation(DQuat::from_rotation_y(0.001));
+        let mut pose = Trs::IDENTITY;
+        for _ in 0..10_000 {
+            pose = pose.then(&step);
+        }
+        assert!(pose.rotation.is_normalized());
+        assert!(pose.rotation.angle_between(DQuat::from_rotation_y(10.0)) < 1e-9);
+    }
+
+    #[test]
+    fn test_trs_interpolate() {
+        let a = Trs::IDENTITY;
+        let b = Trs::new(
+            dvec3(10.0, 0.0, 0.0),
+            DQuat::from_rotation_z(std::f64::consts::FRAC_PI_2),
+            dvec3(3.0, 3.0, 3.0),
+        );
+        let mid = a.interpolate(&b, 0.5);
+        assert!((mid.translation - dvec3(5.0, 0.0, 0.0)).length() < 1e-10);
+        assert!((mid.scale - dvec3(2.0, 2.0, 2.0)).length() < 1e-10);
+        assert!(
+            mid.rotation
+                .angle_between(DQuat::from_rotation_z(std::f64::consts::FRAC_PI_4))
+                < 1e-10
+        );
+        assert_eq!(a.interpolate(&b, 1.0).translation, b.translation);
+    }
 }
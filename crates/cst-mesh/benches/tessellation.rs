@@ -0,0 +1,94 @@
+//! Planar triangulation and adaptive surface tessellation.
+//!
+//! `cargo bench -p cst-mesh -- --save-baseline main` on the base branch, then
+//! `cargo bench -p cst-mesh -- --baseline main` to compare a change against it.
+
+use std::f64::consts::TAU;
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use cst_geometry::surface::{NurbsSurface, SphericalSurface, ToroidalSurface};
+use cst_geometry::Surface;
+use cst_math::DVec3;
+use cst_mesh::{adaptive_tessellate_surface, tessellate_planar_face, tessellate_surface};
+
+fn triangulation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("planar_face");
+    for sides in [8usize, 64, 1024] {
+        let polygon: Vec<DVec3> = (0..sides)
+            .map(|i| {
+                let angle = TAU * i as f64 / sides as f64;
+                DVec3::new(angle.cos(), angle.sin(), 0.0)
+            })
+            .collect();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(sides),
+            &polygon,
+            |b, polygon| b.iter(|| tessellate_planar_face(black_box(polygon))),
+        );
+    }
+    group.finish();
+}
+
+/// A bicubic patch with a raised, heavier-weighted centre.
+fn nurbs_patch() -> NurbsSurface {
+    let knots = vec![0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0];
+    let control_points = (0..4)
+        .map(|i| {
+            (0..4)
+                .map(|j| {
+                    let z = if (1..3).contains(&i) && (1..3).contains(&j) {
+                        1.0
+                    } else {
+                        0.0
+                    };
+                    DVec3::new(i as f64, j as f64, z)
+                })
+                .collect()
+        })
+        .collect();
+    let weights = (0..4)
+        .map(|i| {
+            (0..4)
+                .map(|j| if i == 1 && j == 2 { 3.0 } else { 1.0 })
+                .collect()
+        })
+        .collect();
+    NurbsSurface::new(3, 3, knots.clone(), knots, control_points, weights)
+}
+
+fn adaptive_tessellation(c: &mut Criterion) {
+    let surfaces: [(&str, Box<dyn Surface>); 3] = [
+        ("sphere", Box::new(SphericalSurface::new(DVec3::ZERO, 1.0))),
+        (
+            "torus",
+            Box::new(ToroidalSurface::new(DVec3::ZERO, DVec3::Z, 2.0, 0.5)),
+        ),
+        ("nurbs", Box::new(nurbs_patch())),
+    ];
+
+    let mut group = c.benchmark_group("adaptive");
+    for (name, surface) in &surfaces {
+        for tolerance in [1e-2, 1e-3] {
+            group.bench_with_input(
+                BenchmarkId::new(*name, tolerance),
+                surface.as_ref(),
+                |b, surface| b.iter(|| adaptive_tessellate_surface(surface, black_box(tolerance))),
+            );
+        }
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("uniform");
+    for (name, surface) in &surfaces {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(name),
+            surface.as_ref(),
+            |b, surface| b.iter(|| tessellate_surface(surface, black_box(64), black_box(64))),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, triangulation, adaptive_tessellation);
+criterion_main!(benches);
@@ -2,6 +2,9 @@
 //!
 //! Recursively subdivides UV patches where the surface curvature exceeds a tolerance,
 //! producing finer triangles in high-curvature regions and coarser triangles in flat areas.
+//! The tolerance is either a fixed distance or, for a viewer, measured in pixels on
+//! screen ([`ScreenSpaceError`]), so geometry near the camera gets more triangles than
+//! geometry far away.
 
 use cst_geometry::Surface;
 use cst_math::{Point2, Point3, Vector3};
@@ -11,10 +14,73 @@ use crate::TriangleMesh;
 /// Maximum recursion depth to prevent infinite subdivision.
 const MAX_DEPTH: u32 = 8;
 
+/// Longest projected patch edge, in pixels, that
+/// [`ScreenSpaceError::new`] allows.
+pub const DEFAULT_MAX_EDGE_PIXELS: f64 = 64.0;
+
+/// Tessellation error measured on screen, for
+/// [`screen_space_tessellate_surface`].
+///
+/// Perspective sizes shrink with the distance from the eye rather than the
+/// depth along the view direction, so a tessellation stays valid while the
+/// camera turns in place.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScreenSpaceError {
+    /// Eye position; unused for orthographic views
+    pub eye: Point3,
+    /// Pixels covered by one world unit at unit distance from a perspective
+    /// eye, or anywhere in an orthographic view
+    pub pixels_per_unit: f64,
+    pub orthographic: bool,
+    /// Largest deviation of a patch from the true surface, in pixels
+    pub max_deviation: f64,
+    /// Longest projected patch edge, in pixels, so shading stays smooth on
+    /// large patches close to the eye
+    pub max_edge_length: f64,
+}
+
+impl ScreenSpaceError {
+    /// Perspective error from an eye, allowing `max_deviation` pixels and
+    /// edges of up to [`DEFAULT_MAX_EDGE_PIXELS`].
+    pub fn new(eye: Point3, pixels_per_unit: f64, max_deviation: f64) -> Self {
+        Self {
+            eye,
+            pixels_per_unit,
+            orthographic: false,
+            max_deviation,
+            max_edge_length: DEFAULT_MAX_EDGE_PIXELS,
+        }
+    }
+
+    /// Size in pixels of a world-space length at `at`.
+    pub fn pixels(&self, length: f64, at: Point3) -> f64 {
+        if self.orthographic {
+            return length * self.pixels_per_unit;
+        }
+        length * self.pixels_per_unit / (at - self.eye).length().max(1e-9)
+    }
+
+    /// World-space length covering `pixels` pixels at `at`; the inverse of
+    /// [`pixels`](Self::pixels).
+    pub fn length(&self, pixels: f64, at: Point3) -> f64 {
+        if self.orthographic {
+            return pixels / self.pixels_per_unit;
+        }
+        pixels * (at - self.eye).length().max(1e-9) / self.pixels_per_unit
+    }
+}
+
+/// When a patch is fine enough.
+enum Tolerance {
+    /// Deviation in world units
+    Distance(f64),
+    Screen(ScreenSpaceError),
+}
+
 /// Collects vertex/index data during recursive subdivision.
 struct MeshBuilder<'a> {
     surface: &'a dyn Surface,
-    tolerance: f64,
+    tolerance: Tolerance,
     u_domain: (f64, f64),
     v_domain: (f64, f64),
     positions: Vec<Point3>,
@@ -24,7 +90,7 @@ struct MeshBuilder<'a> {
 }
 
 impl<'a> MeshBuilder<'a> {
-    fn new(surface: &'a dyn Surface, tolerance: f64) -> Self {
+    fn new(surface: &'a dyn Surface, tolerance: Tolerance) -> Self {
         Self {
             surface,
             tolerance,
@@ -50,7 +116,18 @@ impl<'a> MeshBuilder<'a> {
         let p_mid_approx = (p00 + p10 + p01 + p11) * 0.25;
         let deviation = (p_mid_true - p_mid_approx).length();
 
-        if deviation > self.tolerance && depth < MAX_DEPTH {
+        let too_coarse = match &self.tolerance {
+            Tolerance::Distance(tolerance) => deviation > *tolerance,
+            Tolerance::Screen(view) => {
+                let longest_edge = [(p00, p10), (p01, p11), (p00, p01), (p10, p11)]
+                    .iter()
+                    .map(|&(a, b)| view.pixels((b - a).length(), (a + b) * 0.5))
+                    .fold(0.0, f64::max);
+                view.pixels(deviation, p_mid_true) > view.max_deviation
+                    || longest_edge > view.max_edge_length
+            }
+        };
+        if too_coarse && depth < MAX_DEPTH {
             self.subdivide(u0, u_mid, v0, v_mid, depth + 1);
             self.subdivide(u_mid, u1, v0, v_mid, depth + 1);
             self.subdivide(u0, u_mid, v_mid, v1, depth + 1);
@@ -107,6 +184,23 @@ impl<'a> MeshBuilder<'a> {
 /// # Returns
 /// A `TriangleMesh` with positions, normals, and UV coordinates.
 pub fn adaptive_tessellate_surface(surface: &dyn Surface, tolerance: f64) -> TriangleMesh {
+    tessellate(surface, Tolerance::Distance(tolerance))
+}
+
+/// Adaptively tessellate a parametric surface for a viewpoint.
+///
+/// Like [`adaptive_tessellate_surface`], but a quad is subdivided while its
+/// deviation from the surface or its longest edge, projected to the screen,
+/// exceeds the limits in `view`. Parts of the surface near the eye end up
+/// with more triangles than parts far away.
+pub fn screen_space_tessellate_surface(
+    surface: &dyn Surface,
+    view: &ScreenSpaceError,
+) -> TriangleMesh {
+    tessellate(surface, Tolerance::Screen(*view))
+}
+
+fn tessellate(surface: &dyn Surface, tolerance: Tolerance) -> TriangleMesh {
     let (u_min, u_max) = surface.domain_u();
     let (v_min, v_max) = surface.domain_v();
 
@@ -161,6 +255,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_screen_space_near_finer_than_far() {
+        let view = ScreenSpaceError::new(DVec3::ZERO, 1000.0, 0.5);
+        let near = SphericalSurface::new(DVec3::new(0.0, 0.0, -5.0), 1.0);
+        let far = SphericalSurface::new(DVec3::new(0.0, 0.0, -5000.0), 1.0);
+        let near_mesh = screen_space_tessellate_surface(&near, &view);
+        let far_mesh = screen_space_tessellate_surface(&far, &view);
+        assert!(
+            near_mesh.triangle_count() > far_mesh.triangle_count(),
+            "Near ({}) should get more triangles than far ({})",
+            near_mesh.triangle_count(),
+            far_mesh.triangle_count()
+        );
+        assert_eq!(far_mesh.triangle_count(), 32); // initial grid, under a pixel across
+
+        // Every emitted quad is within the on-screen limits
+        for quad in near_mesh.positions.chunks(4) {
+            for (i, &a) in quad.iter().enumerate() {
+                let b = quad[(i + 1) % 4];
+                assert!(view.pixels((b - a).length(), (a + b) * 0.5) <= view.max_edge_length);
+            }
+        }
+
+        // An orthographic view treats both alike
+        let ortho = ScreenSpaceError {
+            orthographic: true,
+            pixels_per_unit: 100.0,
+            ..view
+        };
+        assert_eq!(
+            screen_space_tessellate_surface(&near, &ortho).triangle_count(),
+            screen_space_tessellate_surface(&far, &ortho).triangle_count()
+        );
+    }
+
     #[test]
     fn test_adaptive_sphere_points_on_surface() {
         let radius = 3.0;
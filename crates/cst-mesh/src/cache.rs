@@ -0,0 +1,130 @@
+//! Reuse of surface tessellations.
+//!
+//! Building models repeat the same surfaces many times over: every mullion
+//! of a curtain wall or every instance of a mapped item. A
+//! [`TessellationCache`] remembers the mesh of each surface by its
+//! [`SurfaceKey`] and tolerance, so identical surfaces are tessellated once.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use cst_geometry::{Surface, SurfaceKey};
+
+use crate::adaptive::adaptive_tessellate_surface;
+use crate::TriangleMesh;
+
+/// Meshes from [`adaptive_tessellate_surface`], keyed by surface and
+/// tolerance.
+///
+/// Safe to share between threads. Surfaces without a
+/// [`cache_key`](Surface::cache_key) are tessellated on every call.
+#[derive(Debug, Default)]
+pub struct TessellationCache {
+    meshes: Mutex<HashMap<(SurfaceKey, u64), Arc<TriangleMesh>>>,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl TessellationCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The tessellation of `surface` at `tolerance`, from the cache when
+    /// an identical surface was tessellated at the same tolerance before.
+    pub fn tessellate(&self, surface: &dyn Surface, tolerance: f64) -> Arc<TriangleMesh> {
+        let Some(key) = surface.cache_key() else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return Arc::new(adaptive_tessellate_surface(surface, tolerance));
+        };
+        let key = (key, tolerance.to_bits());
+        if let Some(mesh) = self.lock().get(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Arc::clone(mesh);
+        }
+
+        // Tessellate without holding the lock; threads racing on the same
+        // surface at worst do the work twice
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let mesh = Arc::new(adaptive_tessellate_surface(surface, tolerance));
+        Arc::clone(self.lock().entry(key).or_insert(mesh))
+    }
+
+    /// Number of cached meshes.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Calls of [`tessellate`](Self::tessellate) answered from the cache.
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Calls of [`tessellate`](Self::tessellate) that had to tessellate.
+    pub fn misses(&self) -> usize {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Drop every cached mesh and reset the counters.
+    pub fn clear(&self) {
+        self.lock().clear();
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<(SurfaceKey, u64), Arc<TriangleMesh>>> {
+        // A panic while holding the lock cannot leave the map half-updated
+        self.meshes.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cst_geometry::surface::{CylindricalSurface, SphericalSurface};
+    use cst_math::DVec3;
+    use rayon::prelude::*;
+
+    #[test]
+    fn test_identical_surfaces_tessellate_once() {
+        let cache = TessellationCache::new();
+        let mullion = || CylindricalSurface::new(DVec3::ZERO, DVec3::Z, 0.05);
+
+        let first = cache.tessellate(&mullion(), 0.001);
+        let second = cache.tessellate(&mullion(), 0.001);
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(
+            first.positions,
+            adaptive_tessellate_surface(&mullion(), 0.001).positions
+        );
+        assert_eq!((cache.hits(), cache.misses(), cache.len()), (1, 1, 1));
+
+        // A different tolerance or surface is a separate entry
+        cache.tessellate(&mullion(), 0.01);
+        let moved = CylindricalSurface::new(DVec3::X, DVec3::Z, 0.05);
+        assert!(!Arc::ptr_eq(&first, &cache.tessellate(&moved, 0.001)));
+        assert_eq!((cache.hits(), cache.misses(), cache.len()), (1, 3, 3));
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(cache.hits(), 0);
+    }
+
+    #[test]
+    fn test_shared_between_threads() {
+        let cache = TessellationCache::new();
+        let sphere = SphericalSurface::new(DVec3::ZERO, 1.0);
+        let counts: Vec<usize> = (0..64)
+            .into_par_iter()
+            .map(|_| cache.tessellate(&sphere, 0.01).triangle_count())
+            .collect();
+        assert!(counts.iter().all(|&n| n == counts[0]));
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.hits() + cache.misses(), 64);
+    }
+}
@@ -0,0 +1,191 @@
+//! Feature edge extraction for edge overlays.
+//!
+//! BIM viewers draw crisp outlines on top of shaded geometry: open
+//! boundaries, creases between faces meeting at an angle, and the view
+//! dependent silhouette. Triangulation diagonals inside flat faces are left
+//! out. Coincident vertices are welded first so per-face vertex duplicates
+//! do not turn every triangle edge into a boundary.
+
+use std::collections::HashMap;
+
+use cst_math::{Point3, Vector3};
+
+use crate::weld::weld_positions;
+use crate::TriangleMesh;
+
+/// Line segments as a shared position array plus index pairs.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LineList {
+    pub positions: Vec<Point3>,
+    /// Two indices per segment.
+    pub indices: Vec<u32>,
+}
+
+impl LineList {
+    /// Number of line segments.
+    pub fn segment_count(&self) -> usize {
+        self.indices.len() / 2
+    }
+
+    /// Whether the list holds no segments.
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+}
+
+/// Boundary, non-manifold and crease edges of a mesh.
+///
+/// An edge is a crease when the normals of its two triangles differ by more
+/// than `crease_angle` (radians).
+pub fn feature_edges(mesh: &TriangleMesh, crease_angle: f64) -> LineList {
+    let cos_crease = crease_angle.cos();
+    let adjacency = EdgeAdjacency::new(mesh);
+    adjacency.select(|faces| match faces {
+        [a, b] => adjacency.normals[*a].dot(adjacency.normals[*b]) < cos_crease,
+        _ => true,
+    })
+}
+
+/// Silhouette edges seen from `eye`: edges between a triangle facing the
+/// eye and one facing away, plus open boundaries.
+pub fn silhouette_edges(mesh: &TriangleMesh, eye: Point3) -> LineList {
+    let adjacency = EdgeAdjacency::new(mesh);
+    let facing = |face: usize| adjacency.normals[face].dot(eye - adjacency.centers[face]) > 0.0;
+    adjacency.select(|faces| match faces {
+        [a, b] => facing(*a) != facing(*b),
+        [_] => true,
+        _ => false,
+    })
+}
+
+/// Every distinct triangle edge, for wireframe display.
+pub fn wireframe_edges(mesh: &TriangleMesh) -> LineList {
+    EdgeAdjacency::new(mesh).select(|_| true)
+}
+
+/// Welded edges of a mesh and the triangles using them.
+struct EdgeAdjacency {
+    positions: Vec<Point3>,
+    /// Welded edge (low, high) -> triangle indices, in first-seen order.
+    edges: Vec<((u32, u32), Vec<usize>)>,
+    normals: Vec<Vector3>,
+    centers: Vec<Point3>,
+}
+
+impl EdgeAdjacency {
+    fn new(mesh: &TriangleMesh) -> Self {
+        let weld = weld_positions(&mesh.positions, mesh.tolerance_context().linear);
+        let triangles = weld.triangles(&mesh.indices);
+
+        let mut lookup: HashMap<(u32, u32), usize> = HashMap::new();
+        let mut edges: Vec<((u32, u32), Vec<usize>)> = Vec::new();
+        let mut normals = Vec::with_capacity(triangles.len());
+        let mut centers = Vec::with_capacity(triangles.len());
+        for (face, tri) in triangles.iter().enumerate() {
+            let [a, b, c] = tri.map(|i| weld.unique[i as usize]);
+            normals.push((b - a).cross(c - a).normalize_or_zero());
+            centers.push((a + b + c) / 3.0);
+            for k in 0..3 {
+                let (u, v) = (tri[k], tri[(k + 1) % 3]);
+                if u == v {
+                    continue;
+                }
+                let key = (u.min(v), u.max(v));
+                let slot = *lookup.entry(key).or_insert_with(|| {
+                    edges.push((key, Vec::new()));
+                    edges.len() - 1
+                });
+                edges[slot].1.push(face);
+            }
+        }
+
+        Self {
+            positions: weld.unique,
+            edges,
+            normals,
+            centers,
+        }
+    }
+
+    /// Line list of the edges whose adjacent triangles pass `keep`, using
+    /// only the welded positions that appear in it.
+    fn select<F>(&self, keep: F) -> LineList
+    where
+        F: Fn(&[usize]) -> bool,
+    {
+        let mut remap: HashMap<u32, u32> = HashMap::new();
+        let mut lines = LineList::default();
+        for ((a, b), faces) in &self.edges {
+            if !keep(faces) {
+                continue;
+            }
+            for v in [*a, *b] {
+                let index = *remap.entry(v).or_insert_with(|| {
+                    lines.positions.push(self.positions[v as usize]);
+                    lines.positions.len() as u32 - 1
+                });
+                lines.indices.push(index);
+            }
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::unit_cube;
+    use cst_math::DVec3;
+
+    #[test]
+    fn test_cube_feature_edges() {
+        // 12 cube edges; the face diagonals are coplanar and dropped.
+        let cube = unit_cube();
+        let edges = feature_edges(&cube, 30f64.to_radians());
+        assert_eq!(edges.segment_count(), 12);
+        assert_eq!(edges.positions.len(), 8);
+
+        // A crease angle above 90° keeps nothing on a closed cube.
+        assert!(feature_edges(&cube, 100f64.to_radians()).is_empty());
+    }
+
+    #[test]
+    fn test_wireframe_includes_diagonals() {
+        assert_eq!(wireframe_edges(&unit_cube()).segment_count(), 18);
+    }
+
+    #[test]
+    fn test_open_boundary_edges() {
+        let quad = TriangleMesh {
+            positions: vec![
+                DVec3::new(0.0, 0.0, 0.0),
+                DVec3::new(1.0, 0.0, 0.0),
+                DVec3::new(1.0, 1.0, 0.0),
+                DVec3::new(0.0, 1.0, 0.0),
+            ],
+            normals: vec![],
+            indices: vec![0, 1, 2, 0, 2, 3],
+            uvs: vec![],
+        };
+        // Outline only, no diagonal
+        assert_eq!(feature_edges(&quad, 0.5).segment_count(), 4);
+        assert_eq!(
+            silhouette_edges(&quad, DVec3::new(0.5, 0.5, 5.0)).segment_count(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_cube_silhouette() {
+        let cube = unit_cube();
+        let center = cube.centroid().unwrap();
+
+        // Looking straight at one face: its four outline edges
+        let front = silhouette_edges(&cube, center + DVec3::new(0.0, 0.0, 10.0));
+        assert_eq!(front.segment_count(), 4);
+
+        // Looking at a corner: a hexagon
+        let corner = silhouette_edges(&cube, center + DVec3::splat(10.0));
+        assert_eq!(corner.segment_count(), 6);
+    }
+}
@@ -55,15 +55,18 @@ pub fn tessellate_surface(
     let v_count = v_divs + 1;
     let total_verts = u_count * v_count;
 
-    let mut positions = Vec::with_capacity(total_verts);
+    let us: Vec<f64> = (0..u_count)
+        .map(|i| u_min + (u_max - u_min) * i as f64 / u_divs as f64)
+        .collect();
+    let vs: Vec<f64> = (0..v_count)
+        .map(|j| v_min + (v_max - v_min) * j as f64 / v_divs as f64)
+        .collect();
+    let positions = surface.points_at_grid(&us, &vs);
     let mut normals = Vec::with_capacity(total_verts);
     let mut uvs = Vec::with_capacity(total_verts);
 
-    for i in 0..u_count {
-        let u = u_min + (u_max - u_min) * i as f64 / u_divs as f64;
-        for j in 0..v_count {
-            let v = v_min + (v_max - v_min) * j as f64 / v_divs as f64;
-            positions.push(surface.point_at(u, v));
+    for (i, &u) in us.iter().enumerate() {
+        for (j, &v) in vs.iter().enumerate() {
             normals.push(surface.normal_at(u, v));
             uvs.push(Point2::new(
                 i as f64 / u_divs as f64,
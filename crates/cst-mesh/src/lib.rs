@@ -1,9 +1,29 @@
 pub mod adaptive;
+pub mod cache;
+pub mod edges;
 pub mod face_tessellator;
+pub mod offset;
+pub mod section;
+pub mod smooth;
 pub mod topology_to_mesh;
 pub mod triangulate;
+mod trim;
+mod weld;
 
-pub use adaptive::adaptive_tessellate_surface;
+#[cfg(test)]
+mod test_util;
+
+pub use adaptive::{
+    adaptive_tessellate_surface, screen_space_tessellate_surface, ScreenSpaceError,
+};
+pub use cache::TessellationCache;
+pub use edges::{feature_edges, silhouette_edges, wireframe_edges, LineList};
 pub use face_tessellator::{tessellate_planar_face, tessellate_surface};
-pub use topology_to_mesh::topology_mesh_to_triangles;
+pub use offset::offset_mesh;
+pub use section::{section_mesh, SectionPolyline};
+pub use smooth::{smooth_mesh, SmoothMethod, SmoothOptions};
+pub use topology_to_mesh::{
+    edge_to_polyline, topology_mesh_to_triangles, topology_mesh_to_triangles_cached,
+    topology_mesh_to_triangles_for_view, topology_mesh_to_triangles_with_geometry,
+};
 pub use triangulate::TriangleMesh;
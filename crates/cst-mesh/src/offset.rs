@@ -0,0 +1,177 @@
+//! Normal-based mesh offsetting.
+//!
+//! Moves every vertex along its angle-weighted pseudo-normal so that the
+//! faces around it end up `distance` away from their original planes.
+//! Positive distances inflate a closed mesh, negative distances deflate it.
+//! Typical use is generating clearance zones around pipes and members.
+
+use cst_math::{Point3, Vector3};
+
+use crate::weld::weld_positions;
+use crate::TriangleMesh;
+
+/// Upper bound for the miter scale at sharp corners, so that near-degenerate
+/// spikes do not shoot vertices arbitrarily far away.
+const MAX_MITER_SCALE: f64 = 4.0;
+
+/// Offset a triangle mesh by `distance` along its outward normals.
+///
+/// Coincident vertices are welded before computing normals, so meshes with
+/// per-face vertices (flat shading) stay closed after offsetting. At edges
+/// and corners the displacement is scaled by a miter factor so that planar
+/// faces move exactly `distance` (e.g. a box stays a box).
+///
+/// The returned mesh keeps the input topology, normals and UVs; only
+/// positions change. For negative distances larger than the local feature
+/// size the result self-intersects, as with any vertex-based offset.
+pub fn offset_mesh(mesh: &TriangleMesh, distance: f64) -> TriangleMesh {
+    let mut result = mesh.clone();
+    if distance == 0.0 || mesh.indices.is_empty() {
+        return result;
+    }
+
+    let weld = weld_positions(&mesh.positions, mesh.tolerance_context().linear);
+    let triangles = weld.triangles(&mesh.indices);
+    let n = weld.unique.len();
+
+    // Angle-weighted pseudo-normals, plus the face normals around each vertex
+    // for the miter correction.
+    let mut normal_sum = vec![Vector3::ZERO; n];
+    let mut face_normals: Vec<Vec<Vector3>> = vec![Vec::new(); n];
+
+    for tri in &triangles {
+        let p = [
+            weld.unique[tri[0] as usize],
+            weld.unique[tri[1] as usize],
+            weld.unique[tri[2] as usize],
+        ];
+        let normal = (p[1] - p[0]).cross(p[2] - p[0]);
+        let len = normal.length();
+        if len < 1e-14 {
+            continue;
+        }
+        let normal = normal / len;
+
+        for k in 0..3 {
+            let angle = corner_angle(p[k], p[(k + 1) % 3], p[(k + 2) % 3]);
+            let v = tri[k] as usize;
+            normal_sum[v] += normal * angle;
+            face_normals[v].push(normal);
+        }
+    }
+
+    let displacement: Vec<Vector3> = (0..n)
+        .map(|v| {
+            let len = normal_sum[v].length();
+            if len < 1e-14 {
+                return Vector3::ZERO;
+            }
+            let dir = normal_sum[v] / len;
+            let min_dot = face_normals[v]
+                .iter()
+                .map(|fn_| dir.dot(*fn_))
+                .fold(1.0_f64, f64::min);
+            let scale = if min_dot > 1.0 / MAX_MITER_SCALE {
+                1.0 / min_dot
+            } else {
+                MAX_MITER_SCALE
+            };
+            dir * (distance * scale)
+        })
+        .collect();
+
+    for (pos, &w) in result.positions.iter_mut().zip(&weld.remap) {
+        *pos += displacement[w as usize];
+    }
+    result
+}
+
+/// Interior angle at `a` in the triangle `(a, b, c)`.
+fn corner_angle(a: Point3, b: Point3, c: Point3) -> f64 {
+    let u = b - a;
+    let v = c - a;
+    let denom = u.length() * v.length();
+    if denom < 1e-14 {
+        return 0.0;
+    }
+    (u.dot(v) / denom).clamp(-1.0, 1.0).acos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::unit_cube;
+    use cst_math::DVec3;
+
+    #[test]
+    fn test_offset_cube_inflates_by_distance() {
+        let cube = unit_cube();
+        let grown = offset_mesh(&cube, 0.25);
+        let bb = grown.bounding_box();
+        assert!(
+            (bb.min - DVec3::splat(-0.25)).length() < 1e-9,
+            "min = {:?}",
+            bb.min
+        );
+        assert!(
+            (bb.max - DVec3::splat(1.25)).length() < 1e-9,
+            "max = {:?}",
+            bb.max
+        );
+    }
+
+    #[test]
+    fn test_offset_cube_deflates_with_negative_distance() {
+        let cube = unit_cube();
+        let shrunk = offset_mesh(&cube, -0.1);
+        let bb = shrunk.bounding_box();
+        assert!((bb.min - DVec3::splat(0.1)).length() < 1e-9);
+        assert!((bb.max - DVec3::splat(0.9)).length() < 1e-9);
+    }
+
+    #[test]
+    fn test_offset_keeps_faces_planar() {
+        let cube = unit_cube();
+        let grown = offset_mesh(&cube, 0.5);
+        // Every vertex of the top face (+Z) must sit exactly at z = 1.5.
+        for (p, n) in grown.positions.iter().zip(&cube.normals) {
+            if n.z > 0.9 {
+                assert!((p.z - 1.5).abs() < 1e-9, "top vertex at z = {}", p.z);
+            }
+        }
+    }
+
+    #[test]
+    fn test_offset_preserves_topology() {
+        let cube = unit_cube();
+        let grown = offset_mesh(&cube, 1.0);
+        assert_eq!(grown.indices, cube.indices);
+        assert_eq!(grown.vertex_count(), cube.vertex_count());
+        assert_eq!(grown.normals, cube.normals);
+    }
+
+    #[test]
+    fn test_offset_zero_is_identity() {
+        let cube = unit_cube();
+        let same = offset_mesh(&cube, 0.0);
+        assert_eq!(same.positions, cube.positions);
+    }
+
+    #[test]
+    fn test_offset_open_triangle_moves_along_normal() {
+        let mesh = TriangleMesh {
+            positions: vec![
+                DVec3::new(0.0, 0.0, 0.0),
+                DVec3::new(1.0, 0.0, 0.0),
+                DVec3::new(0.0, 1.0, 0.0),
+            ],
+            normals: vec![],
+            indices: vec![0, 1, 2],
+            uvs: vec![],
+        };
+        let moved = offset_mesh(&mesh, 2.0);
+        for p in &moved.positions {
+            assert!((p.z - 2.0).abs() < 1e-12);
+        }
+    }
+}
@@ -0,0 +1,268 @@
+//! Plane sections of triangle meshes.
+//!
+//! Cutting a mesh with a plane gives the outline drawn in floor plans and
+//! section views. Coincident vertices are welded first so the crossing
+//! points of neighbouring triangles match and chain into polylines.
+
+use std::collections::HashMap;
+
+use cst_math::plane::Plane;
+use cst_math::Point3;
+
+use crate::weld::weld_positions;
+use crate::TriangleMesh;
+
+/// Where a plane cuts a mesh.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SectionPolyline {
+    pub points: Vec<Point3>,
+    /// Whether the last point connects back to the first.
+    pub closed: bool,
+}
+
+/// A directed crossing segment between two welded edges.
+struct Segment {
+    start: (u32, u32),
+    end: (u32, u32),
+}
+
+/// Cut `mesh` with `plane`.
+///
+/// A closed, outward-facing mesh gives closed polylines that run
+/// counter-clockwise seen from the plane normal around material and
+/// clockwise around holes. Open meshes may give open polylines. Vertices
+/// on the plane count as lying on its positive side, and collinear points
+/// are dropped.
+pub fn section_mesh(mesh: &TriangleMesh, plane: &Plane) -> Vec<SectionPolyline> {
+    let tolerance = mesh.tolerance_context().linear;
+    let weld = weld_positions(&mesh.positions, tolerance);
+    let side: Vec<f64> = weld
+        .unique
+        .iter()
+        .map(|&p| plane.signed_distance(p))
+        .collect();
+
+    // Crossing point of each welded edge, computed from its ordered key so
+    // that both triangles sharing the edge get identical points
+    let mut crossings: HashMap<(u32, u32), Point3> = HashMap::new();
+    let mut crossing = |u: u32, v: u32| {
+        let key = (u.min(v), u.max(v));
+        crossings.entry(key).or_insert_with(|| {
+            let (a, b) = (key.0 as usize, key.1 as usize);
+            let t = side[a] / (side[a] - side[b]);
+            weld.unique[a].lerp(weld.unique[b], t)
+        });
+        key
+    };
+
+    let mut segments = Vec::new();
+    for tri in weld.triangles(&mesh.indices) {
+        let positive = tri.map(|v| side[v as usize] >= 0.0);
+        if positive.iter().all(|&p| p) || positive.iter().all(|&p| !p) {
+            continue;
+        }
+        // The plane crosses the two edges leaving the lone vertex
+        let lone = (0..3)
+            .find(|&k| positive[k] != positive[(k + 1) % 3] && positive[k] != positive[(k + 2) % 3])
+            .expect("a crossed triangle has one vertex on its own side");
+        let (v, next, prev) = (tri[lone], tri[(lone + 1) % 3], tri[(lone + 2) % 3]);
+        let (a, b) = (crossing(v, next), crossing(prev, v));
+        // Orient the segment so that, seen from the plane normal, the
+        // triangle's outward side is on its right and material on its left
+        let segment = if positive[lone] {
+            Segment { start: a, end: b }
+        } else {
+            Segment { start: b, end: a }
+        };
+        if segment.start != segment.end {
+            segments.push(segment);
+        }
+    }
+
+    chain_segments(&segments)
+        .into_iter()
+        .map(|(keys, closed)| {
+            let points = keys.iter().map(|key| crossings[key]).collect();
+            SectionPolyline {
+                points: remove_collinear(points, closed, tolerance),
+                closed,
+            }
+        })
+        .filter(|polyline| polyline.points.len() >= 2)
+        .collect()
+}
+
+/// Join segments that share crossing points into chains. Chains start at
+/// points only one segment touches, so open chains come out whole; the
+/// rest are loops. Triangles with flipped winding give segments against
+/// the flow, so each chain takes the direction most of its segments have.
+fn chain_segments(segments: &[Segment]) -> Vec<(Vec<(u32, u32)>, bool)> {
+    let mut touching: HashMap<(u32, u32), Vec<usize>> = HashMap::new();
+    for (i, segment) in segments.iter().enumerate() {
+        touching.entry(segment.start).or_default().push(i);
+        touching.entry(segment.end).or_default().push(i);
+    }
+    let other_end = |i: usize, key: (u32, u32)| {
+        let segment = &segments[i];
+        if segment.start == key {
+            segment.end
+        } else {
+            segment.start
+        }
+    };
+    let open_ends = segments
+        .iter()
+        .enumerate()
+        .flat_map(|(i, s)| [(i, s.start), (i, s.end)])
+        .filter(|(_, key)| touching[key].len() == 1);
+    let all = segments.iter().enumerate().map(|(i, s)| (i, s.start));
+
+    let mut used = vec![false; segments.len()];
+    let mut chains = Vec::new();
+    for (first, origin) in open_ends.chain(all) {
+        if used[first] {
+            continue;
+        }
+        let mut keys = vec![origin];
+        let mut along = 0i64;
+        let (mut current, mut key) = (first, origin);
+        let closed = loop {
+            used[current] = true;
+            along += if segments[current].start == key {
+                1
+            } else {
+                -1
+            };
+            key = other_end(current, key);
+            if key == origin {
+                break true;
+            }
+            keys.push(key);
+            match touching[&key].iter().find(|&&i| !used[i]) {
+                Some(&next) => current = next,
+                None => break false,
+            }
+        };
+        if along < 0 {
+            keys.reverse();
+        }
+        chains.push((keys, closed));
+    }
+    chains
+}
+
+/// Drop points that lie on the line through their neighbours.
+fn remove_collinear(points: Vec<Point3>, closed: bool, tolerance: f64) -> Vec<Point3> {
+    let n = points.len();
+    if n < 3 {
+        return points;
+    }
+    let redundant = |i: usize| {
+        if !closed && (i == 0 || i == n - 1) {
+            return false;
+        }
+        let (prev, next) = (points[(i + n - 1) % n], points[(i + 1) % n]);
+        let chord = next - prev;
+        let length = chord.length();
+        length > 0.0 && chord.cross(points[i] - prev).length() / length <= tolerance
+    };
+    let kept: Vec<Point3> = (0..n)
+        .filter(|&i| !redundant(i))
+        .map(|i| points[i])
+        .collect();
+    // A degenerate loop collapses entirely; keep it as it was
+    if kept.len() < 2 {
+        points
+    } else {
+        kept
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{box_mesh, unit_cube};
+    use cst_math::{DVec3, Vector3};
+
+    /// Twice the signed area of the polygon's projection onto the XY plane.
+    fn signed_area_xy(points: &[Point3]) -> f64 {
+        (0..points.len())
+            .map(|i| {
+                let (a, b) = (points[i], points[(i + 1) % points.len()]);
+                a.x * b.y - b.x * a.y
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_cube_section() {
+        let plane = Plane::new(DVec3::new(0.0, 0.0, 0.5), Vector3::Z);
+        let section = section_mesh(&unit_cube(), &plane);
+        assert_eq!(section.len(), 1);
+        let square = &section[0];
+        assert!(square.closed);
+        // Diagonal crossings are collinear and dropped
+        assert_eq!(square.points.len(), 4);
+        assert!(square.points.iter().all(|p| (p.z - 0.5).abs() < 1e-12));
+        assert!((signed_area_xy(&square.points) - 2.0).abs() < 1e-9);
+
+        // A flipped side triangle does not break or turn the loop
+        let mut flipped = unit_cube();
+        let side = (0..flipped.indices.len() / 3)
+            .find(|&t| {
+                let z: Vec<f64> = flipped.indices[t * 3..t * 3 + 3]
+                    .iter()
+                    .map(|&i| flipped.positions[i as usize].z)
+                    .collect();
+                z.iter().any(|&z| z < 0.5) && z.iter().any(|&z| z > 0.5)
+            })
+            .unwrap();
+        flipped.indices.swap(side * 3, side * 3 + 1);
+        let section = section_mesh(&flipped, &plane);
+        assert_eq!(section.len(), 1);
+        assert!(section[0].closed);
+        assert!((signed_area_xy(&section[0].points) - 2.0).abs() < 1e-9);
+
+        let above = Plane::new(DVec3::new(0.0, 0.0, 2.0), Vector3::Z);
+        assert!(section_mesh(&unit_cube(), &above).is_empty());
+    }
+
+    #[test]
+    fn test_section_with_hole_runs_clockwise() {
+        // A box with an inward-facing cavity
+        let mut hollow = box_mesh(DVec3::ZERO, DVec3::new(3.0, 3.0, 1.0));
+        let mut cavity = box_mesh(DVec3::new(1.0, 1.0, 0.1), DVec3::new(2.0, 2.0, 0.9));
+        cavity.indices.reverse();
+        hollow.merge(&cavity);
+        let plane = Plane::new(DVec3::new(0.0, 0.0, 0.25), Vector3::Z);
+        let section = section_mesh(&hollow, &plane);
+
+        assert_eq!(section.len(), 2);
+        assert!(section.iter().all(|s| s.closed));
+        let mut areas: Vec<f64> = section.iter().map(|s| signed_area_xy(&s.points)).collect();
+        areas.sort_by(f64::total_cmp);
+        assert!((areas[0] + 2.0).abs() < 1e-9);
+        assert!((areas[1] - 18.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_open_mesh_gives_open_polyline() {
+        // Two faces of a cube's side, open at both ends
+        let mut cube = unit_cube();
+        cube.indices.truncate(0);
+        let wall = box_mesh(DVec3::ZERO, DVec3::ONE);
+        // Keep the y = 0 and x = 1 sides (the third and sixth quads)
+        for quad in [2, 5] {
+            cube.indices
+                .extend_from_slice(&wall.indices[quad * 6..quad * 6 + 6]);
+        }
+        cube.positions = wall.positions;
+        let plane = Plane::new(DVec3::new(0.0, 0.0, 0.5), Vector3::Z);
+        let section = section_mesh(&cube, &plane);
+        assert_eq!(section.len(), 1);
+        assert!(!section[0].closed);
+        assert_eq!(section[0].points.len(), 3);
+        assert!(section[0].points[0].distance(DVec3::new(0.0, 0.0, 0.5)) < 1e-12);
+        assert!(section[0].points[2].distance(DVec3::new(1.0, 1.0, 0.5)) < 1e-12);
+    }
+}
@@ -0,0 +1,344 @@
+//! Laplacian and Taubin smoothing for triangle meshes.
+//!
+//! Intended for cleaning noisy triangulated face sets (scanned surfaces,
+//! exported terrain) before display. Boundary and sharp feature edges can be
+//! preserved: vertices on a single crease or boundary only slide along it,
+//! and corner vertices where several creases meet stay fixed.
+
+use std::collections::HashMap;
+
+use cst_core::ToleranceContext;
+use cst_math::{Point3, Vector3};
+
+use crate::weld::weld_positions;
+use crate::TriangleMesh;
+
+/// Turning angle (radians) at which a boundary polyline vertex counts as a
+/// corner when feature detection is disabled.
+const DEFAULT_CORNER_ANGLE: f64 = std::f64::consts::FRAC_PI_4;
+
+/// Smoothing kernel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SmoothMethod {
+    /// Plain umbrella-operator smoothing. Shrinks closed meshes over time.
+    Laplacian,
+    /// Taubin λ|μ smoothing: every pass is followed by an inflating step with
+    /// the (negative) factor `mu`, which largely cancels the shrinkage.
+    Taubin { mu: f64 },
+}
+
+/// Parameters for [`smooth_mesh`].
+#[derive(Debug, Clone)]
+pub struct SmoothOptions {
+    pub method: SmoothMethod,
+    /// Number of smoothing passes.
+    pub iterations: usize,
+    /// Step factor toward the neighbour average, in `(0, 1]`.
+    pub lambda: f64,
+    /// Keep open-boundary vertices on the boundary polyline.
+    pub preserve_boundary: bool,
+    /// Dihedral angle (radians) above which an edge counts as a feature
+    /// edge. `None` disables feature detection.
+    pub feature_angle: Option<f64>,
+    /// Welding tolerance for coincident vertices. `None` derives it from
+    /// the mesh extent.
+    pub tolerance: Option<ToleranceContext>,
+}
+
+impl Default for SmoothOptions {
+    fn default() -> Self {
+        Self {
+            method: SmoothMethod::Taubin { mu: -0.53 },
+            iterations: 10,
+            lambda: 0.5,
+            preserve_boundary: true,
+            feature_angle: Some(45f64.to_radians()),
+            tolerance: None,
+        }
+    }
+}
+
+/// How a welded vertex may move during smoothing.
+enum Constraint {
+    /// Average over all neighbours.
+    Free(Vec<u32>),
+    /// Average over the two neighbours along a boundary or crease.
+    Crease([u32; 2]),
+    /// Corner or endpoint of a feature; never moves.
+    Fixed,
+}
+
+/// Smooth a triangle mesh, returning a new mesh with the same topology.
+///
+/// Coincident vertices are welded first so per-face vertex duplicates move
+/// together and the mesh does not crack open. Normals are recomputed when
+/// the input carries normals.
+pub fn smooth_mesh(mesh: &TriangleMesh, options: &SmoothOptions) -> TriangleMesh {
+    let mut result = mesh.clone();
+    if options.iterations == 0 || mesh.indices.is_empty() {
+        return result;
+    }
+
+    let weld = weld_positions(
+        &mesh.positions,
+        options
+            .tolerance
+            .unwrap_or_else(|| mesh.tolerance_context())
+            .linear,
+    );
+    let triangles = weld.triangles(&mesh.indices);
+    let constraints = classify_vertices(&weld.unique, &triangles, options);
+
+    let mut positions = weld.unique;
+    for _ in 0..options.iterations {
+        positions = relax(&positions, &constraints, options.lambda);
+        if let SmoothMethod::Taubin { mu } = options.method {
+            positions = relax(&positions, &constraints, mu);
+        }
+    }
+
+    for (pos, &w) in result.positions.iter_mut().zip(&weld.remap) {
+        *pos = positions[w as usize];
+    }
+    if !result.normals.is_empty() {
+        result.compute_normals();
+    }
+    result
+}
+
+/// One umbrella-operator pass: `p += factor * (avg(neighbours) - p)`.
+fn relax(positions: &[Point3], constraints: &[Constraint], factor: f64) -> Vec<Point3> {
+    positions
+        .iter()
+        .zip(constraints)
+        .map(|(&p, constraint)| {
+            let neighbours: &[u32] = match constraint {
+                Constraint::Free(n) => n,
+                Constraint::Crease(n) => n,
+                Constraint::Fixed => return p,
+            };
+            if neighbours.is_empty() {
+                return p;
+            }
+            let sum: Vector3 = neighbours.iter().map(|&n| positions[n as usize]).sum();
+            let avg = sum / neighbours.len() as f64;
+            p + (avg - p) * factor
+        })
+        .collect()
+}
+
+/// Classify every welded vertex as free, crease-constrained or fixed.
+fn classify_vertices(
+    positions: &[Point3],
+    triangles: &[[u32; 3]],
+    options: &SmoothOptions,
+) -> Vec<Constraint> {
+    let n = positions.len();
+
+    // Undirected edge -> normals of the adjacent triangles.
+    let mut edge_faces: HashMap<(u32, u32), Vec<Vector3>> = HashMap::new();
+    for tri in triangles {
+        if tri[0] == tri[1] || tri[1] == tri[2] || tri[2] == tri[0] {
+            continue;
+        }
+        let p0 = positions[tri[0] as usize];
+        let normal = (positions[tri[1] as usize] - p0)
+            .cross(positions[tri[2] as usize] - p0)
+            .normalize_or_zero();
+        for k in 0..3 {
+            let (a, b) = (tri[k], tri[(k + 1) % 3]);
+            edge_faces
+                .entry((a.min(b), a.max(b)))
+                .or_default()
+                .push(normal);
+        }
+    }
+
+    let mut neighbours: Vec<Vec<u32>> = vec![Vec::new(); n];
+    let mut constrained: Vec<Vec<u32>> = vec![Vec::new(); n];
+    let cos_feature = options.feature_angle.map(f64::cos);
+
+    for (&(a, b), normals) in &edge_faces {
+        neighbours[a as usize].push(b);
+        neighbours[b as usize].push(a);
+
+        let is_constrained = match normals.len() {
+            1 => options.preserve_boundary,
+            2 => cos_feature.is_some_and(|c| normals[0].dot(normals[1]) < c),
+            // Non-manifold edges are always kept in place.
+            _ => true,
+        };
+        if is_constrained {
+            constrained[a as usize].push(b);
+            constrained[b as usize].push(a);
+        }
+    }
+
+    // A boundary/crease polyline that turns sharply at a vertex makes that
+    // vertex a corner, which must stay put.
+    let cos_corner = cos_feature.unwrap_or(DEFAULT_CORNER_ANGLE.cos());
+
+    neighbours
+        .into_iter()
+        .zip(constrained)
+        .enumerate()
+        .map(|(v, (mut all, along))| match along.len() {
+            0 => {
+                // Deterministic order keeps the floating-point sums stable.
+                all.sort_unstable();
+                Constraint::Free(all)
+            }
+            2 => {
+                let p = positions[v];
+                let incoming = (p - positions[along[0] as usize]).normalize_or_zero();
+                let outgoing = (positions[along[1] as usize] - p).normalize_or_zero();
+                if incoming.dot(outgoing) < cos_corner {
+                    Constraint::Fixed
+                } else {
+                    Constraint::Crease([along[0], along[1]])
+                }
+            }
+            _ => Constraint::Fixed,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adaptive_tessellate_surface;
+    use crate::test_util::unit_cube;
+    use cst_geometry::surface::SphericalSurface;
+    use cst_math::DVec3;
+
+    /// `size` x `size` grid on the XY plane with a deterministic z "noise".
+    fn noisy_grid(size: usize) -> TriangleMesh {
+        let mut mesh = TriangleMesh::default();
+        for j in 0..=size {
+            for i in 0..=size {
+                let interior = i > 0 && j > 0 && i < size && j < size;
+                let noise = if interior && (i + j) % 2 == 0 {
+                    0.1
+                } else {
+                    0.0
+                };
+                mesh.positions.push(DVec3::new(i as f64, j as f64, noise));
+            }
+        }
+        let row = (size + 1) as u32;
+        for j in 0..size as u32 {
+            for i in 0..size as u32 {
+                let a = j * row + i;
+                mesh.indices
+                    .extend_from_slice(&[a, a + 1, a + row + 1, a, a + row + 1, a + row]);
+            }
+        }
+        mesh
+    }
+
+    fn max_abs_z(mesh: &TriangleMesh) -> f64 {
+        mesh.positions.iter().map(|p| p.z.abs()).fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn test_laplacian_reduces_noise() {
+        let mesh = noisy_grid(8);
+        let opts = SmoothOptions {
+            method: SmoothMethod::Laplacian,
+            feature_angle: None,
+            ..Default::default()
+        };
+        let smoothed = smooth_mesh(&mesh, &opts);
+        assert!(max_abs_z(&smoothed) < 0.5 * max_abs_z(&mesh));
+    }
+
+    #[test]
+    fn test_boundary_is_preserved() {
+        let mesh = noisy_grid(6);
+        let opts = SmoothOptions {
+            method: SmoothMethod::Laplacian,
+            iterations: 20,
+            feature_angle: None,
+            ..Default::default()
+        };
+        let smoothed = smooth_mesh(&mesh, &opts);
+        for (before, after) in mesh.positions.iter().zip(&smoothed.positions) {
+            let on_boundary =
+                before.x == 0.0 || before.y == 0.0 || before.x == 6.0 || before.y == 6.0;
+            if on_boundary {
+                // Straight boundary rows can only slide along themselves.
+                assert!(after.z.abs() < 1e-12);
+                if before.x == 0.0 || before.x == 6.0 {
+                    assert_eq!(after.x, before.x);
+                }
+                if before.y == 0.0 || before.y == 6.0 {
+                    assert_eq!(after.y, before.y);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_unconstrained_boundary_moves() {
+        let mesh = noisy_grid(4);
+        let opts = SmoothOptions {
+            method: SmoothMethod::Laplacian,
+            preserve_boundary: false,
+            feature_angle: None,
+            ..Default::default()
+        };
+        let smoothed = smooth_mesh(&mesh, &opts);
+        // The corner (0, 0) is pulled inward once boundaries are free.
+        assert!(smoothed.positions[0].x > 0.0);
+    }
+
+    #[test]
+    fn test_feature_edges_keep_cube_sharp() {
+        let cube = unit_cube();
+        let smoothed = smooth_mesh(&cube, &SmoothOptions::default());
+        for (a, b) in cube.positions.iter().zip(&smoothed.positions) {
+            assert!((*a - *b).length() < 1e-12, "{:?} moved to {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_taubin_shrinks_less_than_laplacian() {
+        let sphere = SphericalSurface::new(DVec3::ZERO, 1.0);
+        let mesh = adaptive_tessellate_surface(&sphere, 0.05);
+        let mean_radius = |m: &TriangleMesh| {
+            m.positions.iter().map(|p| p.length()).sum::<f64>() / m.positions.len() as f64
+        };
+
+        let base = SmoothOptions {
+            iterations: 20,
+            preserve_boundary: false,
+            feature_angle: None,
+            ..Default::default()
+        };
+        let laplacian = smooth_mesh(
+            &mesh,
+            &SmoothOptions {
+                method: SmoothMethod::Laplacian,
+                ..base.clone()
+            },
+        );
+        let taubin = smooth_mesh(&mesh, &base);
+
+        let shrink_laplacian = 1.0 - mean_radius(&laplacian);
+        let shrink_taubin = 1.0 - mean_radius(&taubin);
+        assert!(shrink_laplacian > 0.0);
+        assert!(
+            shrink_taubin.abs() < shrink_laplacian,
+            "taubin {shrink_taubin} vs laplacian {shrink_laplacian}"
+        );
+    }
+
+    #[test]
+    fn test_normals_recomputed_when_present() {
+        let mut mesh = noisy_grid(4);
+        mesh.compute_normals();
+        let smoothed = smooth_mesh(&mesh, &SmoothOptions::default());
+        assert_eq!(smoothed.normals.len(), smoothed.vertex_count());
+        assert!(smoothed.normals.iter().all(|n| n.z > 0.9));
+    }
+}
@@ -0,0 +1,71 @@
+//! Shared mesh fixtures for unit tests.
+
+use cst_math::DVec3;
+
+use crate::TriangleMesh;
+
+/// Axis-aligned box `[min, max]` with outward-facing triangles and
+/// per-face (unshared) vertices, the way tessellated IFC faces arrive.
+pub(crate) fn box_mesh(min: DVec3, max: DVec3) -> TriangleMesh {
+    let c = |x: bool, y: bool, z: bool| {
+        DVec3::new(
+            if x { max.x } else { min.x },
+            if y { max.y } else { min.y },
+            if z { max.z } else { min.z },
+        )
+    };
+    // Each quad is listed counter-clockwise when viewed from outside.
+    let quads = [
+        [
+            c(false, false, false),
+            c(false, true, false),
+            c(true, true, false),
+            c(true, false, false),
+        ],
+        [
+            c(false, false, true),
+            c(true, false, true),
+            c(true, true, true),
+            c(false, true, true),
+        ],
+        [
+            c(false, false, false),
+            c(true, false, false),
+            c(true, false, true),
+            c(false, false, true),
+        ],
+        [
+            c(false, true, false),
+            c(false, true, true),
+            c(true, true, true),
+            c(true, true, false),
+        ],
+        [
+            c(false, false, false),
+            c(false, false, true),
+            c(false, true, true),
+            c(false, true, false),
+        ],
+        [
+            c(true, false, false),
+            c(true, true, false),
+            c(true, true, true),
+            c(true, false, true),
+        ],
+    ];
+
+    let mut mesh = TriangleMesh::default();
+    for quad in &quads {
+        let base = mesh.positions.len() as u32;
+        mesh.positions.extend_from_slice(quad);
+        mesh.indices
+            .extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+    }
+    mesh.compute_normals();
+    mesh
+}
+
+/// Unit cube `[0, 1]^3`.
+pub(crate) fn unit_cube() -> TriangleMesh {
+    box_mesh(DVec3::ZERO, DVec3::ONE)
+}
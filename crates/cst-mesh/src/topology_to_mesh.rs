@@ -1,9 +1,18 @@
 //! Convert a half-edge topology Mesh to a TriangleMesh.
 
+use std::sync::Arc;
+
+use cst_geometry::tessellate::curve_to_polyline;
+use cst_geometry::{GeometryPool, Surface};
 use cst_math::Point3;
-use cst_topology::Mesh;
+use cst_topology::{EdgeId, FaceHalfEdgeIter, FaceId, Mesh};
 
+use crate::adaptive::{
+    adaptive_tessellate_surface, screen_space_tessellate_surface, ScreenSpaceError,
+};
+use crate::cache::TessellationCache;
 use crate::face_tessellator::tessellate_planar_face;
+use crate::trim::trim_patch;
 use crate::TriangleMesh;
 
 /// Convert a `cst_topology::Mesh` to a `TriangleMesh`.
@@ -13,31 +22,181 @@ use crate::TriangleMesh;
 pub fn topology_mesh_to_triangles(mesh: &Mesh) -> TriangleMesh {
     let mut result = TriangleMesh::default();
 
-    for (face_id, _face) in &mesh.faces {
-        let vertex_iter = match mesh.face_vertices(face_id) {
-            Some(iter) => iter,
-            None => continue,
-        };
+    for face_id in mesh.faces.keys() {
+        if let Some(face_mesh) = planar_face_mesh(mesh, face_id) {
+            result.merge(&face_mesh);
+        }
+    }
 
-        let positions: Vec<Point3> = vertex_iter
-            .map(|vid| mesh.vertices[vid].position)
-            .collect();
+    result
+}
 
-        if positions.len() < 3 {
-            continue;
-        }
+/// Convert a `cst_topology::Mesh` to a `TriangleMesh`, using the true surface
+/// of faces that reference one in `pool`.
+///
+/// Faces with a surface are tessellated adaptively, trimmed to their outer
+/// and inner loops in the parameter space of the surface and flipped when
+/// `surface_reversed` is set. Edges are sampled to `tolerance` along their
+/// curves. Faces without a surface, whose surface index is missing from the
+/// pool or whose loops stray from the surface fall back to fan
+/// triangulation of the outer loop.
+pub fn topology_mesh_to_triangles_with_geometry(
+    mesh: &Mesh,
+    pool: &GeometryPool,
+    tolerance: f64,
+) -> TriangleMesh {
+    surface_faces_to_triangles(
+        mesh,
+        pool,
+        |surface| Arc::new(adaptive_tessellate_surface(surface, tolerance)),
+        |_| tolerance,
+    )
+}
+
+/// Like [`topology_mesh_to_triangles_with_geometry`], but identical surfaces
+/// are tessellated once and then taken from `cache`, which can be shared
+/// across meshes and threads.
+pub fn topology_mesh_to_triangles_cached(
+    mesh: &Mesh,
+    pool: &GeometryPool,
+    tolerance: f64,
+    cache: &TessellationCache,
+) -> TriangleMesh {
+    surface_faces_to_triangles(
+        mesh,
+        pool,
+        |surface| cache.tessellate(surface, tolerance),
+        |_| tolerance,
+    )
+}
 
-        let face_mesh = tessellate_planar_face(&positions);
+/// Like [`topology_mesh_to_triangles_with_geometry`], but surfaces are
+/// tessellated for a viewpoint with [`screen_space_tessellate_surface`], so
+/// faces near the eye get more triangles than faces far away. Edges are
+/// sampled to the allowed deviation in pixels at the first vertex of their
+/// face.
+pub fn topology_mesh_to_triangles_for_view(
+    mesh: &Mesh,
+    pool: &GeometryPool,
+    view: &ScreenSpaceError,
+) -> TriangleMesh {
+    surface_faces_to_triangles(
+        mesh,
+        pool,
+        |surface| Arc::new(screen_space_tessellate_surface(surface, view)),
+        |at| view.length(view.max_deviation, at),
+    )
+}
+
+/// Tessellate the surface of each face with `tessellate` and trim it to the
+/// face's loops, sampled to the tolerance `tolerance_at` the face.
+fn surface_faces_to_triangles(
+    mesh: &Mesh,
+    pool: &GeometryPool,
+    tessellate: impl Fn(&dyn Surface) -> Arc<TriangleMesh>,
+    tolerance_at: impl Fn(Point3) -> f64,
+) -> TriangleMesh {
+    let mut result = TriangleMesh::default();
+
+    for (face_id, face) in &mesh.faces {
+        let surface = face.surface.and_then(|s| pool.surface(s.0));
+        let trimmed = surface.and_then(|surface| {
+            let first = mesh.face_vertices(face_id)?.next()?;
+            let tolerance = tolerance_at(mesh.vertices.get(first)?.position);
+            let rings = face_rings(mesh, face_id, pool, tolerance)?;
+            trim_patch(surface, &tessellate(surface), &rings, tolerance)
+        });
+        let face_mesh = match trimmed {
+            Some(mut trimmed) => {
+                if face.surface_reversed {
+                    trimmed.flip_winding();
+                }
+                trimmed
+            }
+            None => match planar_face_mesh(mesh, face_id) {
+                Some(m) => m,
+                None => continue,
+            },
+        };
         result.merge(&face_mesh);
     }
 
     result
 }
 
+/// Discretize an edge into a polyline running from the origin of its
+/// `halfedge_a` to the target.
+///
+/// Edges referencing a curve in `pool` are sampled with
+/// [`curve_to_polyline`]; other edges yield their two end points.
+pub fn edge_to_polyline(
+    mesh: &Mesh,
+    edge_id: EdgeId,
+    pool: &GeometryPool,
+    tolerance: f64,
+) -> Option<Vec<Point3>> {
+    let edge = mesh.edges.get(edge_id)?;
+    if let Some(curve) = edge.curve.and_then(|c| pool.curve(c.0)) {
+        return Some(curve_to_polyline(curve, tolerance));
+    }
+    let start = mesh.halfedges.get(edge.halfedge_a)?.origin;
+    let end = mesh.halfedge_target(edge.halfedge_a)?;
+    Some(vec![
+        mesh.vertices.get(start)?.position,
+        mesh.vertices.get(end)?.position,
+    ])
+}
+
+/// The outer loop of a face followed by its inner loops, each a closed
+/// polyline along the edges without the closing point.
+fn face_rings(
+    mesh: &Mesh,
+    face_id: FaceId,
+    pool: &GeometryPool,
+    tolerance: f64,
+) -> Option<Vec<Vec<Point3>>> {
+    let face = mesh.faces.get(face_id)?;
+    std::iter::once(face.outer_loop)
+        .chain(face.inner_loops.iter().copied())
+        .map(|loop_id| {
+            let start = mesh.loops.get(loop_id)?.halfedge;
+            let mut ring = Vec::new();
+            for he_id in FaceHalfEdgeIter::new(mesh, start) {
+                let edge_id = mesh.halfedges.get(he_id)?.edge?;
+                let mut points = edge_to_polyline(mesh, edge_id, pool, tolerance)?;
+                if mesh.edges[edge_id].halfedge_a != he_id {
+                    points.reverse();
+                }
+                points.pop();
+                ring.extend(points);
+            }
+            Some(ring)
+        })
+        .collect()
+}
+
+/// Fan-triangulate a face from its vertex positions.
+fn planar_face_mesh(mesh: &Mesh, face_id: FaceId) -> Option<TriangleMesh> {
+    let positions: Vec<Point3> = mesh
+        .face_vertices(face_id)?
+        .map(|vid| mesh.vertices[vid].position)
+        .collect();
+
+    if positions.len() < 3 {
+        return None;
+    }
+
+    Some(tessellate_planar_face(&positions))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use cst_geometry::curve::Circle;
+    use cst_geometry::surface::{CylindricalSurface, PlanarSurface, SphericalSurface};
     use cst_math::DVec3;
+    use cst_topology::{CurveRef, SurfaceRef};
+    use std::f64::consts::{FRAC_PI_2, PI, TAU};
 
     #[test]
     fn test_single_triangle_topology_to_mesh() {
@@ -82,6 +241,194 @@ mod tests {
         assert_eq!(mesh.triangle_count(), 2);
     }
 
+    /// A face through points of `surface` at the parameters `uvs`.
+    fn face_on(topo: &mut Mesh, surface: &dyn Surface, uvs: &[(f64, f64)]) -> FaceId {
+        let vertices: Vec<_> = uvs
+            .iter()
+            .map(|&(u, v)| topo.add_vertex(surface.point_at(u, v)))
+            .collect();
+        topo.make_face(&vertices).unwrap()
+    }
+
+    const PATCH: [(f64, f64); 4] = [(0.2, -0.3), (1.0, -0.3), (1.0, 0.4), (0.2, 0.4)];
+
+    #[test]
+    fn test_face_with_surface_uses_true_surface() {
+        let mut pool = GeometryPool::new();
+        let surface = SphericalSurface::new(DVec3::ZERO, 2.0);
+        let sphere = pool.add_surface(surface.clone());
+
+        let mut topo = Mesh::new();
+        let curved = face_on(&mut topo, &surface, &PATCH);
+        topo.set_face_surface(curved, Some(SurfaceRef(sphere)))
+            .unwrap();
+
+        let mesh = topology_mesh_to_triangles_with_geometry(&topo, &pool, 0.01);
+        assert!(mesh.triangle_count() > 2);
+        for p in &mesh.positions {
+            assert!((p.length() - 2.0).abs() < 1e-9);
+        }
+        // Only the patch between the face's corners is covered
+        for uv in &mesh.uvs {
+            let (u, v) = (uv.x * TAU, uv.y * PI - FRAC_PI_2);
+            assert!((0.2 - 1e-9..=1.0 + 1e-9).contains(&u));
+            assert!((-0.3 - 1e-9..=0.4 + 1e-9).contains(&v));
+        }
+        // The spherical rectangle has area r^2 (sin(0.4) + sin(0.3)) (1.0 - 0.2)
+        let area = 4.0 * (0.4f64.sin() + 0.3f64.sin()) * 0.8;
+        assert!((mesh.surface_area() - area).abs() < 0.01 * area);
+
+        let view = ScreenSpaceError::new(DVec3::new(5.0, 0.0, 0.0), 1000.0, 0.5);
+        let mesh = topology_mesh_to_triangles_for_view(&topo, &pool, &view);
+        assert!((mesh.surface_area() - area).abs() < 0.01 * area);
+
+        // Without geometry the same face is two flat triangles.
+        assert_eq!(topology_mesh_to_triangles(&topo).triangle_count(), 2);
+    }
+
+    #[test]
+    fn test_faces_sharing_a_surface_do_not_overlap() {
+        let mut pool = GeometryPool::new();
+        let surface = CylindricalSurface::new(DVec3::ZERO, DVec3::Z, 1.0);
+        let cylinder = pool.add_surface(surface.clone());
+
+        // Three strips side by side around the axis, one across the seam
+        let mut topo = Mesh::new();
+        for (a, b) in [(-0.3, 0.2), (0.2, 1.4), (1.4, 2.6)] {
+            let face = face_on(
+                &mut topo,
+                &surface,
+                &[(a, 0.0), (b, 0.0), (b, 1.0), (a, 1.0)],
+            );
+            topo.set_face_surface(face, Some(SurfaceRef(cylinder)))
+                .unwrap();
+        }
+
+        let cache = TessellationCache::new();
+        for mesh in [
+            topology_mesh_to_triangles_with_geometry(&topo, &pool, 0.001),
+            topology_mesh_to_triangles_cached(&topo, &pool, 0.001, &cache),
+        ] {
+            assert!((mesh.surface_area() - 2.9).abs() < 0.01);
+            for p in &mesh.positions {
+                assert!((p.truncate().length() - 1.0).abs() < 1e-9);
+                assert!((-1e-9..=1.0 + 1e-9).contains(&p.z));
+            }
+        }
+    }
+
+    #[test]
+    fn test_inner_loops_are_cut_out() {
+        let mut pool = GeometryPool::new();
+        let plane = pool.add_surface(PlanarSurface::new(DVec3::ZERO, DVec3::X, DVec3::Y));
+
+        let mut topo = Mesh::new();
+        let corners = |topo: &mut Mesh, min: f64, max: f64| {
+            [(min, min), (max, min), (max, max), (min, max)]
+                .map(|(x, y)| topo.add_vertex(DVec3::new(x, y, 0.0)))
+        };
+        let outer = corners(&mut topo, 0.0, 2.0);
+        let mut inner = corners(&mut topo, 0.5, 1.5);
+        inner.reverse();
+        let face = topo.make_face(&outer).unwrap();
+        topo.add_inner_loop(face, &inner).unwrap();
+        topo.set_face_surface(face, Some(SurfaceRef(plane)))
+            .unwrap();
+
+        let mesh = topology_mesh_to_triangles_with_geometry(&topo, &pool, 0.01);
+        assert!((mesh.surface_area() - 3.0).abs() < 1e-9);
+        for n in &mesh.normals {
+            assert_eq!(*n, DVec3::Z);
+        }
+    }
+
+    #[test]
+    fn test_face_off_its_surface_falls_back_to_loop_polygon() {
+        let mut pool = GeometryPool::new();
+        let sphere = pool.add_surface(SphericalSurface::new(DVec3::ZERO, 2.0));
+
+        let mut topo = Mesh::new();
+        let v0 = topo.add_vertex(DVec3::new(0.0, 0.0, 0.0));
+        let v1 = topo.add_vertex(DVec3::new(1.0, 0.0, 0.0));
+        let v2 = topo.add_vertex(DVec3::new(0.0, 1.0, 0.0));
+        let face = topo.make_face(&[v0, v1, v2]).unwrap();
+        topo.set_face_surface(face, Some(SurfaceRef(sphere)))
+            .unwrap();
+
+        let mesh = topology_mesh_to_triangles_with_geometry(&topo, &pool, 0.05);
+        assert_eq!(mesh.triangle_count(), 1);
+        assert!((mesh.surface_area() - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_cached_matches_uncached() {
+        let mut pool = GeometryPool::new();
+        let surface = SphericalSurface::new(DVec3::ZERO, 1.0);
+        let first = pool.add_surface(surface.clone());
+        let second = pool.add_surface(surface.clone());
+
+        let mut topo = Mesh::new();
+        let a = face_on(&mut topo, &surface, &PATCH);
+        let b = face_on(&mut topo, &surface, &[(2.0, 0.1), (2.5, 0.1), (2.5, 0.6)]);
+        topo.set_face_surface(a, Some(SurfaceRef(first))).unwrap();
+        topo.set_face_surface(b, Some(SurfaceRef(second))).unwrap();
+        topo.faces[b].surface_reversed = true;
+
+        let cache = TessellationCache::new();
+        let cached = topology_mesh_to_triangles_cached(&topo, &pool, 0.05, &cache);
+        let uncached = topology_mesh_to_triangles_with_geometry(&topo, &pool, 0.05);
+        assert!(cached.triangle_count() > 3);
+        assert_eq!(cached.positions, uncached.positions);
+        assert_eq!(cached.indices, uncached.indices);
+        // Both faces share one sphere; the reversed one is flipped anyway
+        assert_eq!((cache.len(), cache.hits()), (1, 1));
+    }
+
+    #[test]
+    fn test_reversed_surface_flips_normals() {
+        let mut pool = GeometryPool::new();
+        let surface = SphericalSurface::new(DVec3::ZERO, 1.0);
+        let sphere = pool.add_surface(surface.clone());
+
+        let mut topo = Mesh::new();
+        let face = face_on(&mut topo, &surface, &PATCH);
+        topo.set_face_surface(face, Some(SurfaceRef(sphere)))
+            .unwrap();
+
+        let outward = topology_mesh_to_triangles_with_geometry(&topo, &pool, 0.1);
+        topo.faces[face].surface_reversed = true;
+        let inward = topology_mesh_to_triangles_with_geometry(&topo, &pool, 0.1);
+
+        assert_eq!(outward.triangle_count(), inward.triangle_count());
+        assert_eq!(outward.indices[1], inward.indices[2]);
+        assert!((outward.normals[0] + inward.normals[0]).length() < 1e-12);
+        assert!(outward.normals[0].dot(outward.positions[0]) > 0.0);
+    }
+
+    #[test]
+    fn test_edge_to_polyline() {
+        let mut pool = GeometryPool::new();
+        let arc = pool.add_curve(Circle::new(DVec3::ZERO, DVec3::Z, 1.0));
+
+        let mut topo = Mesh::new();
+        let v0 = topo.add_vertex(DVec3::new(1.0, 0.0, 0.0));
+        let v1 = topo.add_vertex(DVec3::new(-1.0, 0.0, 0.0));
+        let edge = topo.make_edge(v0, v1).unwrap();
+
+        let straight = edge_to_polyline(&topo, edge, &pool, 0.01).unwrap();
+        assert_eq!(
+            straight,
+            vec![DVec3::new(1.0, 0.0, 0.0), DVec3::new(-1.0, 0.0, 0.0)]
+        );
+
+        topo.set_edge_curve(edge, Some(CurveRef(arc))).unwrap();
+        let curved = edge_to_polyline(&topo, edge, &pool, 0.01).unwrap();
+        assert!(curved.len() > 2);
+        for p in &curved {
+            assert!((p.length() - 1.0).abs() < 1e-9);
+        }
+    }
+
     #[test]
     fn test_empty_topology() {
         let topo = Mesh::new();
@@ -1,8 +1,16 @@
+use std::collections::HashMap;
+
+use cst_core::ToleranceContext;
 use cst_math::aabb::Aabb3;
-use cst_math::{Point2, Point3, Vector3};
+use cst_math::transform::has_mirror;
+use cst_math::{DMat3, DMat4, Point2, Point3, Vector3};
+
+use serde::{Deserialize, Serialize};
+
+use crate::weld::weld_positions;
 
 /// GPU-ready triangle mesh with interleaved vertex data.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TriangleMesh {
     pub positions: Vec<Point3>,
     pub normals: Vec<Vector3>,
@@ -31,6 +39,56 @@ impl TriangleMesh {
             .extend(other.indices.iter().map(|&i| i + offset));
     }
 
+    /// Apply `matrix` to the positions, and its inverse transpose to the
+    /// normals. A mirroring matrix also flips the winding so triangles keep
+    /// facing the way their normals point; a singular one recomputes the
+    /// normals from the flattened triangles.
+    pub fn transform(&mut self, matrix: &DMat4) {
+        for p in &mut self.positions {
+            *p = matrix.transform_point3(*p);
+        }
+        let linear = DMat3::from_mat4(*matrix);
+        if linear.determinant().abs() < 1e-15 {
+            self.compute_normals();
+            return;
+        }
+        let normal_matrix = linear.inverse().transpose();
+        for n in &mut self.normals {
+            *n = (normal_matrix * *n).normalize_or_zero();
+        }
+        if has_mirror(*matrix) {
+            for tri in self.indices.chunks_exact_mut(3) {
+                tri.swap(1, 2);
+            }
+        }
+    }
+
+    /// The mesh moved by `offset`.
+    pub fn translated(mut self, offset: Vector3) -> Self {
+        for p in &mut self.positions {
+            *p += offset;
+        }
+        self
+    }
+
+    /// The mesh scaled about the origin by `factors` per axis; see
+    /// [`TriangleMesh::transform`] for negative factors.
+    pub fn scaled(mut self, factors: Vector3) -> Self {
+        self.transform(&DMat4::from_scale(factors));
+        self
+    }
+
+    /// Turn every triangle around: reverse the winding and negate the
+    /// normals.
+    pub fn flip_winding(&mut self) {
+        for tri in self.indices.chunks_exact_mut(3) {
+            tri.swap(1, 2);
+        }
+        for n in &mut self.normals {
+            *n = -*n;
+        }
+    }
+
     /// Compute flat (face) normals from triangle indices and assign to each vertex.
     ///
     /// For shared vertices this accumulates normals from all adjacent faces
@@ -63,11 +121,112 @@ impl TriangleMesh {
     pub fn bounding_box(&self) -> Aabb3 {
         Aabb3::from_points(&self.positions).unwrap_or(Aabb3::new(Point3::ZERO, Point3::ZERO))
     }
+
+    /// Iterate over the corner positions of every triangle.
+    fn triangles(&self) -> impl Iterator<Item = [Point3; 3]> + '_ {
+        self.indices.chunks_exact(3).map(|tri| {
+            [
+                self.positions[tri[0] as usize],
+                self.positions[tri[1] as usize],
+                self.positions[tri[2] as usize],
+            ]
+        })
+    }
+
+    /// Total area of all triangles.
+    pub fn surface_area(&self) -> f64 {
+        self.triangles()
+            .map(|[p0, p1, p2]| 0.5 * (p1 - p0).cross(p2 - p0).length())
+            .sum()
+    }
+
+    /// Signed enclosed volume (divergence theorem over the triangles).
+    ///
+    /// Positive for closed meshes with outward-facing (counter-clockwise)
+    /// triangles, negative when the winding is inverted. Only meaningful for
+    /// watertight meshes; see [`TriangleMesh::is_watertight`].
+    pub fn signed_volume(&self) -> f64 {
+        self.triangles()
+            .map(|[p0, p1, p2]| p0.dot(p1.cross(p2)) / 6.0)
+            .sum()
+    }
+
+    /// Centroid of the mesh.
+    ///
+    /// For a closed mesh with non-zero volume this is the centroid of the
+    /// enclosed solid; otherwise it falls back to the area-weighted centroid
+    /// of the surface. Returns `None` for meshes without area.
+    pub fn centroid(&self) -> Option<Point3> {
+        let volume = self.signed_volume();
+        if volume.abs() > 1e-12 && self.is_watertight() {
+            // Each triangle spans a tetrahedron with the origin whose centroid
+            // is (p0 + p1 + p2) / 4, weighted by its signed volume.
+            let weighted: Vector3 = self
+                .triangles()
+                .map(|[p0, p1, p2]| (p0 + p1 + p2) * (p0.dot(p1.cross(p2)) / 24.0))
+                .sum();
+            return Some(weighted / volume);
+        }
+
+        let mut area = 0.0;
+        let mut weighted = Vector3::ZERO;
+        for [p0, p1, p2] in self.triangles() {
+            let a = 0.5 * (p1 - p0).cross(p2 - p0).length();
+            area += a;
+            weighted += (p0 + p1 + p2) * (a / 3.0);
+        }
+        (area > 1e-12).then(|| weighted / area)
+    }
+
+    /// Tolerances scaled to the size of this mesh, used when welding
+    /// coincident positions (see [`ToleranceContext::from_extent`]).
+    pub fn tolerance_context(&self) -> ToleranceContext {
+        if self.positions.is_empty() {
+            return ToleranceContext::default();
+        }
+        ToleranceContext::from_extent(self.bounding_box().extents().length())
+    }
+
+    /// Whether the mesh is closed and consistently oriented.
+    ///
+    /// Coincident positions are welded first, so per-face vertex duplicates
+    /// do not count as open edges. The mesh is watertight when every edge is
+    /// shared by exactly two triangles that traverse it in opposite
+    /// directions.
+    pub fn is_watertight(&self) -> bool {
+        self.is_watertight_within(&self.tolerance_context())
+    }
+
+    /// [`Self::is_watertight`] with an explicit welding tolerance
+    /// (`tolerance.linear`).
+    pub fn is_watertight_within(&self, tolerance: &ToleranceContext) -> bool {
+        if self.indices.is_empty() {
+            return false;
+        }
+        let weld = weld_positions(&self.positions, tolerance.linear);
+
+        // Directed edge -> number of uses. A closed, oriented mesh uses every
+        // directed edge once and its reverse once.
+        let mut directed: HashMap<(u32, u32), u32> = HashMap::new();
+        for tri in weld.triangles(&self.indices) {
+            for k in 0..3 {
+                let (a, b) = (tri[k], tri[(k + 1) % 3]);
+                if a == b {
+                    continue;
+                }
+                *directed.entry((a, b)).or_insert(0) += 1;
+            }
+        }
+        directed
+            .iter()
+            .all(|(&(a, b), &count)| count == 1 && directed.get(&(b, a)) == Some(&1))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::{box_mesh, unit_cube};
     use cst_math::DVec3;
 
     fn single_triangle() -> TriangleMesh {
@@ -131,6 +290,115 @@ mod tests {
         assert_eq!(bb.max, DVec3::new(1.0, 1.0, 0.0));
     }
 
+    #[test]
+    fn test_surface_area() {
+        let mesh = single_triangle();
+        assert!((mesh.surface_area() - 0.5).abs() < 1e-12);
+        assert!((unit_cube().surface_area() - 6.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_transform_keeps_normals_outward() {
+        let mut cube = unit_cube();
+        cube.compute_normals();
+        let sheared = DMat4::from_cols_array(&[
+            1.0, 0.0, 0.0, 0.0, //
+            2.0, 1.0, 0.0, 0.0, //
+            0.0, 0.0, 1.0, 0.0, //
+            5.0, 0.0, 0.0, 1.0,
+        ]);
+        let mirrored = DMat4::from_scale(DVec3::new(-1.0, 1.0, 1.0));
+        for matrix in [sheared, mirrored, mirrored * sheared] {
+            let mut mesh = cube.clone();
+            mesh.transform(&matrix);
+            assert!(mesh.signed_volume() > 0.0);
+            let mut expected = mesh.clone();
+            expected.compute_normals();
+            for (n, e) in mesh.normals.iter().zip(&expected.normals) {
+                assert!(n.dot(*e) > 0.0, "{:?} against {:?}", n, e);
+            }
+        }
+
+        let flat = cube.clone().scaled(DVec3::new(1.0, 1.0, 0.0));
+        assert_eq!(flat.normals.len(), cube.normals.len());
+        assert!(flat.normals.iter().all(|n| n.is_finite()));
+    }
+
+    #[test]
+    fn test_translated_scaled_and_flipped() {
+        let mesh = unit_cube()
+            .scaled(DVec3::new(2.0, 3.0, 4.0))
+            .translated(DVec3::new(1.0, 0.0, 0.0));
+        let bounds = mesh.bounding_box();
+        assert_eq!(bounds.min, DVec3::new(1.0, 0.0, 0.0));
+        assert_eq!(bounds.max, DVec3::new(3.0, 3.0, 4.0));
+        assert!((mesh.signed_volume() - 24.0).abs() < 1e-9);
+
+        let mut mirrored = unit_cube().scaled(DVec3::new(-1.0, 1.0, 1.0));
+        assert!((mirrored.signed_volume() - 1.0).abs() < 1e-12);
+        mirrored.flip_winding();
+        assert!((mirrored.signed_volume() + 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_signed_volume_of_box() {
+        let mesh = box_mesh(DVec3::new(1.0, 2.0, 3.0), DVec3::new(3.0, 5.0, 7.0));
+        assert!((mesh.signed_volume() - 24.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_signed_volume_inverted_winding_is_negative() {
+        let mut mesh = unit_cube();
+        mesh.flip_winding();
+        assert!((mesh.signed_volume() + 1.0).abs() < 1e-12);
+        // Inverted but still consistent: watertight.
+        assert!(mesh.is_watertight());
+    }
+
+    #[test]
+    fn test_centroid_of_closed_box() {
+        let mesh = box_mesh(DVec3::new(1.0, 2.0, 3.0), DVec3::new(3.0, 5.0, 7.0));
+        let c = mesh.centroid().unwrap();
+        assert!((c - DVec3::new(2.0, 3.5, 5.0)).length() < 1e-9, "{:?}", c);
+    }
+
+    #[test]
+    fn test_centroid_of_open_surface() {
+        let mesh = single_triangle();
+        let c = mesh.centroid().unwrap();
+        assert!((c - DVec3::new(1.0 / 3.0, 1.0 / 3.0, 0.0)).length() < 1e-12);
+        assert!(TriangleMesh::default().centroid().is_none());
+    }
+
+    #[test]
+    fn test_watertight_detection() {
+        let cube = unit_cube();
+        assert!(cube.is_watertight());
+        assert!(!single_triangle().is_watertight());
+
+        // Dropping one triangle opens the mesh.
+        let mut open = cube.clone();
+        open.indices.truncate(open.indices.len() - 3);
+        assert!(!open.is_watertight());
+
+        // Flipping a single triangle breaks the orientation.
+        let mut flipped = cube;
+        flipped.indices.swap(1, 2);
+        assert!(!flipped.is_watertight());
+    }
+
+    #[test]
+    fn test_watertight_tolerance_scales_with_extent() {
+        // A millimetre-scale box whose duplicated corners drifted by 1e-6
+        let mut cube = box_mesh(DVec3::ZERO, DVec3::splat(5000.0));
+        for (i, p) in cube.positions.iter_mut().enumerate() {
+            p.x += (i % 2) as f64 * 1e-6;
+        }
+        assert!(cube.tolerance_context().linear > 1e-6);
+        assert!(cube.is_watertight());
+        assert!(!cube.is_watertight_within(&ToleranceContext::default().with_linear(1e-9)));
+    }
+
     #[test]
     fn test_empty_mesh() {
         let mesh = TriangleMesh::default();
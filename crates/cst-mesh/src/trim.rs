@@ -0,0 +1,316 @@
+//! Trimming of surface patches to the loops of a face.
+//!
+//! A face covers only the part of its surface inside its outer loop and
+//! outside its inner loops. The loops are mapped into the normalized
+//! parameter space of the surface, triangulated there and cut along the
+//! triangles of the untrimmed patch, so the trimmed face follows the surface
+//! as closely as the patch does.
+
+use cst_geometry::Surface;
+use cst_math::{Point2, Point3, Vector3};
+
+use crate::TriangleMesh;
+
+/// Newton steps when mapping a point onto the surface.
+const MAX_INVERSION_STEPS: usize = 20;
+
+/// Step of the finite differences for the surface derivatives, in
+/// normalized parameters.
+const DERIVATIVE_STEP: f64 = 1e-7;
+
+/// A point in normalized parameters, with its exact position when it is a
+/// point of a loop.
+#[derive(Debug, Clone, Copy)]
+struct UvPoint {
+    uv: Point2,
+    position: Option<Point3>,
+}
+
+/// The part of `patch`, a tessellation of `surface` with normalized uvs,
+/// inside `rings`: the outer loop of a face followed by its inner loops, as
+/// closed polylines without the closing point.
+///
+/// `None` when a loop strays more than `tolerance` from the surface or
+/// cannot be laid out in parameter space, e.g. when it runs once around a
+/// cylinder.
+pub(crate) fn trim_patch(
+    surface: &dyn Surface,
+    patch: &TriangleMesh,
+    rings: &[Vec<Point3>],
+    tolerance: f64,
+) -> Option<TriangleMesh> {
+    if patch.uvs.len() != patch.positions.len() {
+        return None;
+    }
+    let param = Parameterization::new(surface, tolerance);
+
+    let mut points: Vec<UvPoint> = Vec::new();
+    let mut holes = Vec::new();
+    for (i, ring) in rings.iter().enumerate() {
+        if ring.len() < 3 {
+            return None;
+        }
+        if i > 0 {
+            holes.push(points.len());
+        }
+        let start = points.len();
+        for &position in ring {
+            let mut uv = param.invert(position, nearest_uv(patch, position)?)?;
+            if let Some(previous) = points[start..].last() {
+                uv = param.unwrap(previous.uv, uv);
+            }
+            points.push(UvPoint {
+                uv,
+                position: Some(position),
+            });
+        }
+        // A loop that does not close in parameter space winds around the
+        // surface
+        let (first, last) = (points[start].uv, points[points.len() - 1].uv);
+        if param.unwrap(last, first) != first {
+            return None;
+        }
+    }
+
+    let coords: Vec<f64> = points.iter().flat_map(|p| [p.uv.x, p.uv.y]).collect();
+    let regions = earcutr::earcut(&coords, &holes, 2).ok()?;
+    if regions.is_empty() {
+        return None;
+    }
+
+    let shifts = param.shifts();
+    let cells: Vec<[Point2; 3]> = patch
+        .indices
+        .chunks_exact(3)
+        .map(|tri| ccw([0, 1, 2].map(|k| patch.uvs[tri[k] as usize])))
+        .collect();
+
+    let mut trimmed = TriangleMesh::default();
+    for tri in regions.chunks_exact(3) {
+        let mut region = [points[tri[0]], points[tri[1]], points[tri[2]]];
+        if signed_area(&region.map(|p| p.uv)) < 0.0 {
+            region.swap(1, 2);
+        }
+        let (low, high) = bounds(&region.map(|p| p.uv));
+        for &shift in &shifts {
+            for cell in &cells {
+                let cell = cell.map(|uv| uv + shift);
+                let (cell_low, cell_high) = bounds(&cell);
+                if cell_low.cmpgt(high).any() || cell_high.cmplt(low).any() {
+                    continue;
+                }
+                let piece = clip(&region, &cell);
+                let uvs: Vec<Point2> = piece.iter().map(|p| p.uv).collect();
+                if piece.len() >= 3 && signed_area(&uvs) > 0.0 {
+                    param.emit(&mut trimmed, &piece);
+                }
+            }
+        }
+    }
+    Some(trimmed)
+}
+
+/// A surface evaluated over its domain mapped onto `[0, 1]` in u and v.
+struct Parameterization<'a> {
+    surface: &'a dyn Surface,
+    u_domain: (f64, f64),
+    v_domain: (f64, f64),
+    /// Whether the surface closes on itself in u and in v
+    periodic: [bool; 2],
+    tolerance: f64,
+}
+
+impl<'a> Parameterization<'a> {
+    fn new(surface: &'a dyn Surface, tolerance: f64) -> Self {
+        let mut param = Self {
+            surface,
+            u_domain: surface.domain_u(),
+            v_domain: surface.domain_v(),
+            periodic: [false; 2],
+            tolerance,
+        };
+        let closes = |start: fn(f64) -> Point2, end: fn(f64) -> Point2| {
+            [0.25, 0.5, 0.75]
+                .iter()
+                .all(|&t| (param.point(start(t)) - param.point(end(t))).length() <= tolerance)
+        };
+        let periodic = [
+            closes(|t| Point2::new(0.0, t), |t| Point2::new(1.0, t)),
+            closes(|t| Point2::new(t, 0.0), |t| Point2::new(t, 1.0)),
+        ];
+        param.periodic = periodic;
+        param
+    }
+
+    /// Surface parameters at normalized `uv`, wrapped into the domain in
+    /// periodic directions.
+    fn parameters(&self, uv: Point2) -> (f64, f64) {
+        let wrap = |t: f64, periodic: bool| if periodic { t.rem_euclid(1.0) } else { t };
+        let (u0, u1) = self.u_domain;
+        let (v0, v1) = self.v_domain;
+        (
+            u0 + wrap(uv.x, self.periodic[0]) * (u1 - u0),
+            v0 + wrap(uv.y, self.periodic[1]) * (v1 - v0),
+        )
+    }
+
+    fn point(&self, uv: Point2) -> Point3 {
+        let (u, v) = self.parameters(uv);
+        self.surface.point_at(u, v)
+    }
+
+    fn normal(&self, uv: Point2) -> Vector3 {
+        let (u, v) = self.parameters(uv);
+        self.surface.normal_at(u, v)
+    }
+
+    /// `uv` kept inside the domain in directions that are not periodic.
+    fn clamp(&self, uv: Point2) -> Point2 {
+        let clamp = |t: f64, periodic: bool| if periodic { t } else { t.clamp(0.0, 1.0) };
+        Point2::new(clamp(uv.x, self.periodic[0]), clamp(uv.y, self.periodic[1]))
+    }
+
+    /// Parameters of the point of the surface closest to `position`, by
+    /// Newton iteration from `seed`. `None` when that point is more than the
+    /// tolerance away.
+    fn invert(&self, position: Point3, seed: Point2) -> Option<Point2> {
+        let mut uv = seed;
+        for _ in 0..MAX_INVERSION_STEPS {
+            let (du, dv) = self.derivatives(uv);
+            let (a, b, c) = (du.dot(du), du.dot(dv), dv.dot(dv));
+            let det = a * c - b * b;
+            if det <= f64::EPSILON * a * c {
+                break;
+            }
+            let r = position - self.point(uv);
+            let (ru, rv) = (du.dot(r), dv.dot(r));
+            let step = Point2::new(c * ru - b * rv, a * rv - b * ru) / det;
+            uv = self.clamp(uv + step);
+            if step.length() <= f64::EPSILON {
+                break;
+            }
+        }
+        ((position - self.point(uv)).length() <= self.tolerance).then_some(uv)
+    }
+
+    /// Partial derivatives with respect to the normalized parameters, by
+    /// central differences kept inside the domain.
+    fn derivatives(&self, uv: Point2) -> (Vector3, Vector3) {
+        let h = DERIVATIVE_STEP;
+        let along = |offset: Point2| {
+            let (low, high) = (self.clamp(uv - offset), self.clamp(uv + offset));
+            (self.point(high) - self.point(low)) / (high - low).length()
+        };
+        (along(Point2::new(h, 0.0)), along(Point2::new(0.0, h)))
+    }
+
+    /// `uv` moved by whole periods in periodic directions to lie next to
+    /// `previous`.
+    fn unwrap(&self, previous: Point2, uv: Point2) -> Point2 {
+        let shift = |t: f64, previous: f64, periodic: bool| {
+            if periodic {
+                t - (t - previous).round()
+            } else {
+                t
+            }
+        };
+        Point2::new(
+            shift(uv.x, previous.x, self.periodic[0]),
+            shift(uv.y, previous.y, self.periodic[1]),
+        )
+    }
+
+    /// Offsets of the copies of the patch that unwrapped loops can reach.
+    fn shifts(&self) -> Vec<Point2> {
+        let range = |periodic: bool| if periodic { -1..=1 } else { 0..=0 };
+        range(self.periodic[0])
+            .flat_map(|i| range(self.periodic[1]).map(move |j| Point2::new(i as f64, j as f64)))
+            .collect()
+    }
+
+    /// Append the convex polygon `piece` to `mesh` as a fan.
+    fn emit(&self, mesh: &mut TriangleMesh, piece: &[UvPoint]) {
+        let base = mesh.positions.len() as u32;
+        for p in piece {
+            mesh.positions
+                .push(p.position.unwrap_or_else(|| self.point(p.uv)));
+            mesh.normals.push(self.normal(p.uv));
+            mesh.uvs.push(p.uv);
+        }
+        for i in 1..piece.len() as u32 - 1 {
+            mesh.indices.extend([base, base + i, base + i + 1]);
+        }
+    }
+}
+
+/// Normalized parameters of the patch vertex closest to `position`.
+fn nearest_uv(patch: &TriangleMesh, position: Point3) -> Option<Point2> {
+    patch
+        .positions
+        .iter()
+        .zip(&patch.uvs)
+        .min_by(|(a, _), (b, _)| {
+            let (a, b) = ((**a - position).length(), (**b - position).length());
+            a.total_cmp(&b)
+        })
+        .map(|(_, &uv)| uv)
+}
+
+/// The part of the counter-clockwise triangle `region` inside the
+/// counter-clockwise triangle `cell` (Sutherland-Hodgman).
+fn clip(region: &[UvPoint; 3], cell: &[Point2; 3]) -> Vec<UvPoint> {
+    let mut output = region.to_vec();
+    for i in 0..3 {
+        let (a, b) = (cell[i], cell[(i + 1) % 3]);
+        let side = |p: Point2| (b - a).perp_dot(p - a);
+        let input = std::mem::take(&mut output);
+        for (j, &current) in input.iter().enumerate() {
+            let previous = input[(j + input.len() - 1) % input.len()];
+            let (sc, sp) = (side(current.uv), side(previous.uv));
+            let crossing = || UvPoint {
+                uv: previous.uv + (current.uv - previous.uv) * (sp / (sp - sc)),
+                position: None,
+            };
+            if sc >= 0.0 {
+                if sp < 0.0 {
+                    output.push(crossing());
+                }
+                output.push(current);
+            } else if sp >= 0.0 {
+                output.push(crossing());
+            }
+        }
+        if output.is_empty() {
+            break;
+        }
+    }
+    output.dedup_by(|a, b| a.uv == b.uv);
+    if output.len() > 1 && output[0].uv == output[output.len() - 1].uv {
+        output.pop();
+    }
+    output
+}
+
+/// Twice the signed area of a polygon, positive when counter-clockwise.
+fn signed_area(polygon: &[Point2]) -> f64 {
+    (0..polygon.len())
+        .map(|i| polygon[i].perp_dot(polygon[(i + 1) % polygon.len()]))
+        .sum()
+}
+
+fn ccw(mut triangle: [Point2; 3]) -> [Point2; 3] {
+    if signed_area(&triangle) < 0.0 {
+        triangle.swap(1, 2);
+    }
+    triangle
+}
+
+fn bounds(points: &[Point2]) -> (Point2, Point2) {
+    points.iter().fold(
+        (
+            Point2::splat(f64::INFINITY),
+            Point2::splat(f64::NEG_INFINITY),
+        ),
+        |(low, high), &p| (low.min(p), high.max(p)),
+    )
+}
@@ -0,0 +1,82 @@
+//! Position welding for triangle soups.
+//!
+//! Tessellated IFC geometry duplicates vertices per face so that flat shading
+//! works. Operations that need connectivity (offsetting, smoothing, topology
+//! checks) first weld coincident positions into shared vertex ids.
+
+use cst_math::spatial::PointIndex;
+use cst_math::Point3;
+
+/// Mapping from the vertices of a mesh to a set of welded (unique) positions.
+pub(crate) struct WeldMap {
+    /// For every input vertex, the index of its welded vertex.
+    pub remap: Vec<u32>,
+    /// Welded vertex positions (first occurrence wins).
+    pub unique: Vec<Point3>,
+}
+
+impl WeldMap {
+    /// Welded triangle indices (same order as the input index buffer).
+    pub fn triangles(&self, indices: &[u32]) -> Vec<[u32; 3]> {
+        indices
+            .chunks_exact(3)
+            .map(|t| {
+                [
+                    self.remap[t[0] as usize],
+                    self.remap[t[1] as usize],
+                    self.remap[t[2] as usize],
+                ]
+            })
+            .collect()
+    }
+}
+
+/// Weld positions that lie within `tolerance` of each other.
+///
+/// Each position merges into the first earlier unique position within the
+/// tolerance, found through a [`PointIndex`] with cell size `tolerance`.
+pub(crate) fn weld_positions(positions: &[Point3], tolerance: f64) -> WeldMap {
+    let tol = tolerance.max(1e-12);
+    let mut index = PointIndex::new(tol);
+    let mut remap = Vec::with_capacity(positions.len());
+
+    for &p in positions {
+        let idx = match index.find_within(p, tol, |_| true) {
+            Some(idx) => idx,
+            None => index.insert(p),
+        };
+        remap.push(idx as u32);
+    }
+
+    WeldMap {
+        remap,
+        unique: index.points().to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cst_math::DVec3;
+
+    #[test]
+    fn test_weld_merges_duplicates() {
+        let positions = vec![
+            DVec3::new(0.0, 0.0, 0.0),
+            DVec3::new(1.0, 0.0, 0.0),
+            DVec3::new(1.0 + 1e-9, 0.0, 0.0),
+            DVec3::new(0.0, 0.0, 0.0),
+        ];
+        let weld = weld_positions(&positions, 1e-6);
+        assert_eq!(weld.unique.len(), 2);
+        assert_eq!(weld.remap, vec![0, 1, 1, 0]);
+    }
+
+    #[test]
+    fn test_weld_across_cell_boundary() {
+        // Points on either side of a grid line must still merge.
+        let positions = vec![DVec3::new(-1e-9, 0.0, 0.0), DVec3::new(1e-9, 0.0, 0.0)];
+        let weld = weld_positions(&positions, 1e-6);
+        assert_eq!(weld.unique.len(), 1);
+    }
+}
@@ -0,0 +1,75 @@
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import { createRequire } from 'node:module';
+import { mkdtempSync, writeFileSync } from 'node:fs';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+
+const { ifcToMeshes, exportScene } = createRequire(import.meta.url)('../cst-node.node');
+
+const MODEL = `ISO-10303-21;
+HEADER;
+FILE_SCHEMA(('IFC2X3'));
+ENDSEC;
+DATA;
+#1= IFCCARTESIANPOINT((0.,0.,0.));
+#2= IFCCARTESIANPOINT((1000.,0.,0.));
+#3= IFCCARTESIANPOINT((1000.,1000.,0.));
+#4= IFCCARTESIANPOINT((0.,1000.,0.));
+#5= IFCPOLYLOOP((#1,#2,#3,#4));
+#6= IFCFACEOUTERBOUND(#5,.T.);
+#7= IFCFACE((#6));
+#8= IFCCLOSEDSHELL((#7));
+#9= IFCFACETEDBREP(#8);
+#10= IFCSHAPEREPRESENTATION($,'Body','Brep',(#9));
+#11= IFCPRODUCTDEFINITIONSHAPE($,$,(#10));
+#20= IFCWALL('2O2Fr$t4X7Zf8NOew3FLOH',$,'Wall A',$,$,$,#11,$);
+ENDSEC;
+END-ISO-10303-21;
+`;
+
+test('ifcToMeshes returns transferable typed arrays', async () => {
+  const meshes = await ifcToMeshes(Buffer.from(MODEL), { unitScale: 0.001 });
+  assert.equal(meshes.length, 1);
+  const [wall] = meshes;
+  assert.equal(wall.globalId, '2O2Fr$t4X7Zf8NOew3FLOH');
+  assert.equal(wall.ifcType, 'IFCWALL');
+  assert.ok(wall.positions instanceof Float32Array);
+  assert.equal(wall.positions.length, 12);
+  assert.ok(wall.positions.every((c) => c === 0 || Math.abs(c - 1) < 1e-6));
+  assert.equal(wall.normals.length, 12);
+  assert.ok(wall.indices instanceof Uint32Array);
+  assert.equal(wall.indices.length, 6);
+  assert.deepEqual(wall.bounds.map((c) => Math.round(c * 1e6) / 1e6), [0, 0, 0, 1, 1, 0]);
+  assert.equal(meshes.index.nodeSize, 16);
+  assert.deepEqual(Array.from(meshes.index.indices), [0]);
+  assert.deepEqual(meshes.index.levelEnds, [1]);
+  assert.equal(meshes.index.boxes.length, 6);
+
+  const copy = structuredClone(wall.positions, { transfer: [wall.positions.buffer] });
+  assert.equal(copy.length, 12);
+  assert.equal(wall.positions.length, 0);
+});
+
+test('ifcToMeshes reads from a path', async () => {
+  const path = join(mkdtempSync(join(tmpdir(), 'cst-node-')), 'wall.ifc');
+  writeFileSync(path, MODEL);
+  const meshes = await ifcToMeshes(path);
+  assert.equal(meshes.length, 1);
+  assert.equal(meshes[0].positions[3], 1000);
+});
+
+test('exportScene writes glb and chunked binary meshes', async () => {
+  const glb = await exportScene(Buffer.from(MODEL), 'glb');
+  assert.ok(glb instanceof ArrayBuffer);
+  assert.equal(Buffer.from(glb, 0, 4).toString(), 'glTF');
+
+  const bin = await exportScene(Buffer.from(MODEL), 'bin', { unitScale: 0.001 });
+  assert.ok(bin instanceof ArrayBuffer);
+  assert.ok(bin.byteLength > 0);
+});
+
+test('errors reject or throw', async () => {
+  assert.throws(() => exportScene(Buffer.from(MODEL), 'obj'), /unknown export format/);
+  await assert.rejects(ifcToMeshes('/nonexistent/model.ifc'), /failed to read IFC/);
+});
@@ -0,0 +1,299 @@
+//! Node.js addon for the web viewer workflow.
+//!
+//! Conversion runs on the libuv thread pool, so the event loop stays free
+//! while large models are parsed. Results are typed arrays over ordinary
+//! (non-external) `ArrayBuffer`s, which can be listed in the transfer list
+//! of `postMessage` to hand them to a worker without copying.
+//!
+//! ```js
+//! const { ifcToMeshes, exportScene } = require('./cst-node.node');
+//!
+//! const meshes = await ifcToMeshes('building.ifc', { unitScale: 0.001 });
+//! worker.postMessage(meshes, meshes.flatMap(m => [m.positions.buffer, m.indices.buffer]));
+//!
+//! // Box selection without touching the triangles
+//! const picked = meshes.filter(m => m.bounds && overlaps(m.bounds, selection));
+//!
+//! // Same bytes as `cst_viewer web` writes to mesh.bin
+//! const bin = await exportScene(fs.readFileSync('building.ifc'), 'bin');
+//! ```
+//!
+//! Build with `cargo build -p cst-node --release` and copy
+//! `target/release/libcst_node.so` (`.dylib`, `cst_node.dll`) to
+//! `cst-node.node`.
+
+use std::path::PathBuf;
+
+use cst_ifc::ifc_cache::CachedModel;
+use cst_ifc::ifc_options::IfcPipelineOptions;
+use cst_ifc::ifc_progress::NoProgress;
+use cst_math::rtree::{PackedRTree, NODE_SIZE};
+use cst_mesh::TriangleMesh;
+use cst_render::{BinaryMeshOptions, ElementMetadata, NormalEncoding, Scene};
+use napi::bindgen_prelude::*;
+use napi::{Env, JsArrayBuffer, JsObject, JsTypedArray, Task, TypedArrayType};
+use napi_derive::napi;
+
+/// Conversion settings; every field is optional.
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct ConvertOptions {
+    /// Factor applied to every coordinate, e.g. 0.001 for millimetres
+    pub unit_scale: Option<f64>,
+    /// Drop faces smaller than a square of this size, in model units
+    pub tolerance: Option<f64>,
+    /// Only convert these product types, e.g. `["IfcWall", "IfcSlab"]`
+    pub include_types: Option<Vec<String>>,
+    /// Skip these product types
+    pub exclude_types: Option<Vec<String>>,
+    /// Only convert elements contained in the named storey
+    pub storey: Option<String>,
+}
+
+impl ConvertOptions {
+    fn pipeline(self) -> IfcPipelineOptions {
+        IfcPipelineOptions {
+            unit_scale: self.unit_scale,
+            tessellation_tolerance: self.tolerance.unwrap_or(0.0),
+            type_filter: self.include_types,
+            exclude_types: self.exclude_types.unwrap_or_default(),
+            storey: self.storey,
+            ..Default::default()
+        }
+    }
+}
+
+/// An IFC file path or the file contents.
+enum Input {
+    Path(PathBuf),
+    Bytes(Vec<u8>),
+}
+
+impl Input {
+    fn new(input: Either<String, Buffer>) -> Self {
+        match input {
+            Either::A(path) => Input::Path(path.into()),
+            // Buffers belong to the JS heap; copy before leaving the thread
+            Either::B(bytes) => Input::Bytes(bytes.to_vec()),
+        }
+    }
+
+    fn load(&self, options: &IfcPipelineOptions) -> Result<CachedModel> {
+        let model = match self {
+            Input::Path(path) => CachedModel::build(path, options, &NoProgress),
+            Input::Bytes(bytes) => CachedModel::from_reader(bytes.as_slice(), options, &NoProgress),
+        };
+        model.map_err(|e| Error::from_reason(format!("failed to read IFC: {}", e)))
+    }
+}
+
+pub struct MeshesTask {
+    input: Input,
+    options: IfcPipelineOptions,
+}
+
+impl Task for MeshesTask {
+    type Output = CachedModel;
+    type JsValue = JsObject;
+
+    fn compute(&mut self) -> Result<CachedModel> {
+        self.input.load(&self.options)
+    }
+
+    fn resolve(&mut self, env: Env, model: CachedModel) -> Result<JsObject> {
+        let mut meshes = env.create_array_with_length(model.meshes.len())?;
+        for (i, cached) in model.meshes.iter().enumerate() {
+            let product = cached.product.and_then(|id| model.products.get(&id));
+            let mut mesh = env.create_object()?;
+            mesh.set("name", cached.mesh.name.as_str())?;
+            mesh.set("globalId", product.and_then(|p| p.global_id.as_deref()))?;
+            mesh.set("ifcType", product.map(|p| p.ifc_type.as_str()))?;
+            mesh.set("color", cached.color.map(|c| c.map(f64::from).to_vec()))?;
+            mesh.set("bounds", cached.bounds.map(|b| flatten_f64(&[b.min, b.max])))?;
+            let positions = flatten(&cached.mesh.positions);
+            let normals = flatten(&cached.mesh.normals);
+            mesh.set("positions", float32_array(&env, &positions)?)?;
+            mesh.set("normals", float32_array(&env, &normals)?)?;
+            mesh.set("indices", uint32_array(&env, &cached.mesh.indices)?)?;
+            meshes.set_element(i as u32, mesh)?;
+        }
+        meshes.set("index", packed_index(&env, &model.index)?)?;
+        Ok(meshes)
+    }
+}
+
+/// Parse and tessellate an IFC file (path or contents) into one entry per
+/// element mesh: `{ name, globalId, ifcType, color, bounds, positions,
+/// normals, indices }` with `Float32Array` and `Uint32Array` buffers.
+/// `bounds` is the world box `[minX, minY, minZ, maxX, maxY, maxZ]`, or
+/// `null` for a mesh without vertices.
+///
+/// The array also carries `index`, a packed R-tree over the bounds:
+/// `boxes` holds six numbers per entry, the meshes in curve order followed
+/// by each level of nodes up to the root; `indices` holds the mesh
+/// position for a leaf and the entry of the first of up to `nodeSize`
+/// children for a node; `levelEnds` ends each level, leaves first.
+#[napi(
+    ts_args_type = "input: string | Buffer, options?: ConvertOptions",
+    ts_return_type = "Promise<Array<{ name: string, globalId: string | null, ifcType: string | null, color: number[] | null, bounds: number[] | null, positions: Float32Array, normals: Float32Array, indices: Uint32Array }> & { index: { nodeSize: number, boxes: Float64Array, indices: Uint32Array, levelEnds: number[] } }>"
+)]
+pub fn ifc_to_meshes(
+    input: Either<String, Buffer>,
+    options: Option<ConvertOptions>,
+) -> AsyncTask<MeshesTask> {
+    AsyncTask::new(MeshesTask {
+        input: Input::new(input),
+        options: options.unwrap_or_default().pipeline(),
+    })
+}
+
+/// Output formats of [`export_scene`].
+#[derive(Debug, Clone, Copy)]
+enum ExportFormat {
+    Glb,
+    /// Chunked binary mesh read by the web viewer
+    Bin,
+}
+
+pub struct ExportTask {
+    input: Input,
+    options: IfcPipelineOptions,
+    format: ExportFormat,
+}
+
+impl Task for ExportTask {
+    type Output = Vec<u8>;
+    type JsValue = JsArrayBuffer;
+
+    fn compute(&mut self) -> Result<Vec<u8>> {
+        let model = self.input.load(&self.options)?;
+        let scene = build_scene(&model, &self.options);
+        Ok(match self.format {
+            ExportFormat::Glb => scene.to_glb(),
+            ExportFormat::Bin => scene.to_chunked_binary_mesh(&BinaryMeshOptions {
+                chunked: true,
+                normals: Some(NormalEncoding::Octahedral),
+                eye: None,
+            }),
+        })
+    }
+
+    fn resolve(&mut self, env: Env, bytes: Vec<u8>) -> Result<JsArrayBuffer> {
+        Ok(array_buffer(&env, &bytes)?.into_raw())
+    }
+}
+
+/// Convert an IFC file (path or contents) and export the scene as an
+/// `ArrayBuffer`: `"glb"` for binary glTF, `"bin"` for the web viewer's
+/// chunked mesh file.
+#[napi(
+    ts_args_type = "input: string | Buffer, format: 'glb' | 'bin', options?: ConvertOptions",
+    ts_return_type = "Promise<ArrayBuffer>"
+)]
+pub fn export_scene(
+    input: Either<String, Buffer>,
+    format: String,
+    options: Option<ConvertOptions>,
+) -> Result<AsyncTask<ExportTask>> {
+    let format = match format.as_str() {
+        "glb" => ExportFormat::Glb,
+        "bin" => ExportFormat::Bin,
+        other => {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!("unknown export format '{}', expected 'glb' or 'bin'", other),
+            ))
+        }
+    };
+    Ok(AsyncTask::new(ExportTask {
+        input: Input::new(input),
+        options: options.unwrap_or_default().pipeline(),
+        format,
+    }))
+}
+
+/// One scene mesh per element, carrying its IFC metadata.
+fn build_scene(model: &CachedModel, options: &IfcPipelineOptions) -> Scene {
+    let mut scene = Scene::new();
+    for cached in &model.meshes {
+        if cached.mesh.indices.is_empty() {
+            continue;
+        }
+        let metadata = cached
+            .product
+            .and_then(|id| model.products.get(&id))
+            .map(|product| ElementMetadata {
+                ifc_type: Some(product.ifc_type.clone()),
+                storey: product.storey.clone(),
+                global_id: product.global_id.clone(),
+                properties: product.properties.clone(),
+            })
+            .unwrap_or_default();
+        let mesh = TriangleMesh {
+            positions: cached.mesh.positions.clone(),
+            normals: cached.mesh.normals.clone(),
+            indices: cached.mesh.indices.clone(),
+            uvs: vec![],
+        };
+        let color = options.color_or_default(cached.color);
+        scene.add_element(&cached.mesh.name, mesh, color, metadata);
+    }
+    scene
+}
+
+/// The packed R-tree of [`CachedModel::index`] as typed arrays.
+fn packed_index(env: &Env, index: &PackedRTree) -> Result<JsObject> {
+    let mut object = env.create_object()?;
+    let boxes: Vec<f64> = index
+        .boxes()
+        .iter()
+        .flat_map(|b| flatten_f64(&[b.min, b.max]))
+        .collect();
+    let indices: Vec<u32> = index.indices().iter().map(|&i| i as u32).collect();
+    let level_ends: Vec<u32> = index.level_ends().iter().map(|&i| i as u32).collect();
+    object.set("nodeSize", NODE_SIZE as u32)?;
+    object.set("boxes", float64_array(env, &boxes)?)?;
+    object.set("indices", uint32_array(env, &indices)?)?;
+    object.set("levelEnds", level_ends)?;
+    Ok(object)
+}
+
+fn flatten_f64(points: &[cst_math::DVec3]) -> Vec<f64> {
+    points.iter().flat_map(|p| [p.x, p.y, p.z]).collect()
+}
+
+fn flatten(points: &[cst_math::DVec3]) -> Vec<f32> {
+    points
+        .iter()
+        .flat_map(|p| [p.x as f32, p.y as f32, p.z as f32])
+        .collect()
+}
+
+/// Copy `bytes` into a V8-owned `ArrayBuffer`. External buffers cannot be
+/// transferred between threads, so results are never handed out that way.
+fn array_buffer(env: &Env, bytes: &[u8]) -> Result<napi::JsArrayBufferValue> {
+    let mut buffer = env.create_arraybuffer(bytes.len())?;
+    buffer.copy_from_slice(bytes);
+    Ok(buffer)
+}
+
+fn float32_array(env: &Env, values: &[f32]) -> Result<JsTypedArray> {
+    let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_ne_bytes()).collect();
+    array_buffer(env, &bytes)?
+        .into_raw()
+        .into_typedarray(TypedArrayType::Float32, values.len(), 0)
+}
+
+fn float64_array(env: &Env, values: &[f64]) -> Result<JsTypedArray> {
+    let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_ne_bytes()).collect();
+    array_buffer(env, &bytes)?
+        .into_raw()
+        .into_typedarray(TypedArrayType::Float64, values.len(), 0)
+}
+
+fn uint32_array(env: &Env, values: &[u32]) -> Result<JsTypedArray> {
+    let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_ne_bytes()).collect();
+    array_buffer(env, &bytes)?
+        .into_raw()
+        .into_typedarray(TypedArrayType::Uint32, values.len(), 0)
+}
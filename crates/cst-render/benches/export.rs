@@ -0,0 +1,66 @@
+//! Scene export formats, measured on the `samples/office.ifc` model.
+//!
+//! `cargo bench -p cst-render -- --save-baseline main` on the base branch,
+//! then `cargo bench -p cst-render -- --baseline main` to compare a change.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use cst_ifc::ifc_options::IfcPipelineOptions;
+use cst_ifc::ifc_reader::read_ifc;
+use cst_ifc::ifc_to_mesh::faces_to_trimesh;
+use cst_mesh::TriangleMesh;
+use cst_render::{BinaryMeshOptions, NormalEncoding, Scene};
+
+const SAMPLE: &str = include_str!("../../../samples/office.ifc");
+
+fn sample_scene() -> Scene {
+    let options = IfcPipelineOptions {
+        unit_scale: Some(0.001),
+        ..Default::default()
+    };
+    let mut scene = Scene::new();
+    for data in read_ifc(SAMPLE.as_bytes(), &options).unwrap() {
+        let trimesh = faces_to_trimesh(&data.name, &data.faces);
+        let mesh = TriangleMesh {
+            positions: trimesh.positions,
+            normals: trimesh.normals,
+            indices: trimesh.indices,
+            uvs: vec![],
+        };
+        scene.add_mesh(&data.name, mesh, data.color.unwrap_or([0.7, 0.7, 0.7]));
+    }
+    scene
+}
+
+fn binary_export(c: &mut Criterion) {
+    let scene = sample_scene();
+    let triangles: usize = scene.meshes.iter().map(|m| m.mesh.triangle_count()).sum();
+    let path = std::env::temp_dir().join(format!("cst-bench-{}.bin", std::process::id()));
+
+    let mut group = c.benchmark_group("export");
+    group.throughput(Throughput::Elements(triangles as u64));
+    group.bench_function("binary_v3", |b| {
+        b.iter(|| scene.export_binary_mesh(black_box(&path)).unwrap())
+    });
+    for (name, normals) in [
+        ("chunked_v4", None),
+        ("chunked_v4_octahedral", Some(NormalEncoding::Octahedral)),
+    ] {
+        let options = BinaryMeshOptions {
+            chunked: true,
+            normals,
+            eye: None,
+        };
+        group.bench_function(name, |b| {
+            b.iter(|| scene.to_chunked_binary_mesh(black_box(&options)))
+        });
+    }
+    group.bench_function("glb", |b| b.iter(|| black_box(&scene).to_glb()));
+    group.finish();
+
+    let _ = std::fs::remove_file(path);
+}
+
+criterion_group!(benches, binary_export);
+criterion_main!(benches);
@@ -0,0 +1,496 @@
+//! BCF viewpoints (`.bcfv` visualization info).
+//!
+//! BIM Collaboration Format issues carry a viewpoint: the camera, section
+//! planes and which components were visible or selected, identified by IFC
+//! `GlobalId`. Reading one into a [`Camera`] and [`Scene`] opens an issue
+//! raised in another tool at the same spot; writing one shares ours. The
+//! BCF 2.1 layout is written; 2.1 and 3.0 files are read.
+
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use cst_core::error::{CstError, Result};
+use cst_math::plane::Plane;
+use cst_math::{Point3, Vector3};
+
+use crate::camera::{Camera, Projection};
+use crate::scene::Scene;
+
+/// Camera of a BCF viewpoint
+#[derive(Debug, Clone, PartialEq)]
+pub struct BcfCamera {
+    pub view_point: Point3,
+    pub direction: Vector3,
+    pub up: Vector3,
+    pub projection: BcfProjection,
+}
+
+/// Projection of a BCF camera
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BcfProjection {
+    /// Vertical field of view in degrees
+    Perspective { field_of_view: f64 },
+    /// Visible view height in world units
+    Orthographic { view_to_world_scale: f64 },
+}
+
+impl BcfCamera {
+    /// Camera pose and projection of `camera`
+    pub fn from_camera(camera: &Camera) -> Self {
+        let projection = match camera.projection {
+            Projection::Perspective => BcfProjection::Perspective {
+                field_of_view: camera.fov_y.to_degrees(),
+            },
+            Projection::Orthographic { height } => BcfProjection::Orthographic {
+                view_to_world_scale: height,
+            },
+        };
+        Self {
+            view_point: camera.eye,
+            direction: (camera.target - camera.eye).normalize_or_zero(),
+            up: camera.up,
+            projection,
+        }
+    }
+
+    /// Move `camera` to this pose. BCF has no look-at point, so the target
+    /// is put `focus_distance` along the view direction.
+    pub fn apply(&self, camera: &mut Camera, focus_distance: f64) {
+        camera.eye = self.view_point;
+        camera.target = self.view_point + self.direction.normalize_or_zero() * focus_distance;
+        camera.up = self.up;
+        match self.projection {
+            BcfProjection::Perspective { field_of_view } => {
+                camera.fov_y = field_of_view.to_radians();
+                camera.projection = Projection::Perspective;
+            }
+            BcfProjection::Orthographic {
+                view_to_world_scale,
+            } => {
+                camera.projection = Projection::Orthographic {
+                    height: view_to_world_scale,
+                };
+            }
+        }
+    }
+}
+
+/// A BCF viewpoint: camera, clipping planes and component visibility
+#[derive(Debug, Clone, PartialEq)]
+pub struct BcfViewpoint {
+    pub guid: String,
+    pub camera: Option<BcfCamera>,
+    /// Section planes in [`Scene::section_planes`] convention: geometry on
+    /// the side the normal points to is kept. (BCF clipping plane
+    /// directions point at the removed side.)
+    pub clipping_planes: Vec<Plane>,
+    /// Visibility of components not listed in `exceptions`
+    pub default_visibility: bool,
+    /// GlobalIds whose visibility is the opposite of the default
+    pub exceptions: Vec<String>,
+    /// GlobalIds of selected components
+    pub selection: Vec<String>,
+}
+
+impl BcfViewpoint {
+    /// Capture the camera, section planes and mesh visibility of a scene.
+    ///
+    /// Whichever of the visible or hidden elements is smaller becomes the
+    /// exception list. Meshes without a GlobalId cannot be referenced and
+    /// are left out.
+    pub fn from_scene(guid: &str, camera: &Camera, scene: &Scene) -> Self {
+        let (mut visible, mut hidden) = (Vec::new(), Vec::new());
+        for scene_mesh in &scene.meshes {
+            if let Some(id) = &scene_mesh.metadata.global_id {
+                let list = if scene_mesh.visible {
+                    &mut visible
+                } else {
+                    &mut hidden
+                };
+                if !list.contains(id) {
+                    list.push(id.clone());
+                }
+            }
+        }
+        let default_visibility = hidden.len() <= visible.len();
+        Self {
+            guid: guid.to_string(),
+            camera: Some(BcfCamera::from_camera(camera)),
+            clipping_planes: scene.section_planes.clone(),
+            default_visibility,
+            exceptions: if default_visibility { hidden } else { visible },
+            selection: Vec::new(),
+        }
+    }
+
+    /// Apply the viewpoint: move the camera, replace the scene's section
+    /// planes and set mesh visibility by GlobalId.
+    ///
+    /// The camera target is placed level with the scene center along the
+    /// view direction, so orbiting afterwards turns around the model.
+    pub fn apply(&self, camera: &mut Camera, scene: &mut Scene) {
+        if let Some(bcf_camera) = &self.camera {
+            let focus_distance = scene
+                .bounds()
+                .map(|bounds| {
+                    (bounds.center() - bcf_camera.view_point)
+                        .dot(bcf_camera.direction.normalize_or_zero())
+                })
+                .filter(|&distance| distance > 0.0)
+                .unwrap_or(1.0);
+            bcf_camera.apply(camera, focus_distance);
+        }
+
+        scene.section_planes = self.clipping_planes.clone();
+
+        let exceptions: HashSet<&str> = self.exceptions.iter().map(String::as_str).collect();
+        for scene_mesh in &mut scene.meshes {
+            let listed = scene_mesh
+                .metadata
+                .global_id
+                .as_deref()
+                .is_some_and(|id| exceptions.contains(id));
+            scene_mesh.visible = self.default_visibility != listed;
+        }
+    }
+
+    /// Read a `.bcfv` file
+    pub fn read(path: &Path) -> Result<Self> {
+        Self::from_xml(&std::fs::read_to_string(path)?)
+    }
+
+    /// Write a `.bcfv` file
+    pub fn write(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, self.to_xml())?;
+        Ok(())
+    }
+
+    /// Parse visualization info XML
+    pub fn from_xml(xml: &str) -> Result<Self> {
+        let doc = roxmltree::Document::parse(xml).map_err(|e| bcf_error(&e.to_string()))?;
+        let root = doc.root_element();
+        if root.tag_name().name() != "VisualizationInfo" {
+            return Err(bcf_error("missing VisualizationInfo element"));
+        }
+
+        let components = child(root, "Components");
+        let component_ids = |list: Option<roxmltree::Node>| -> Vec<String> {
+            list.into_iter()
+                .flat_map(|node| node.children())
+                .filter(|node| node.has_tag_name("Component"))
+                .filter_map(|node| node.attribute("IfcGuid"))
+                .map(str::to_string)
+                .collect()
+        };
+        let selection = component_ids(components.and_then(|c| child(c, "Selection")));
+        let visibility = components.and_then(|c| child(c, "Visibility"));
+        // Without a Visibility element everything is shown; with one the
+        // attribute defaults to false
+        let default_visibility = match visibility {
+            Some(node) => node.attribute("DefaultVisibility") == Some("true"),
+            None => true,
+        };
+        let exceptions = component_ids(visibility.and_then(|v| child(v, "Exceptions")));
+
+        let camera = if let Some(node) = child(root, "PerspectiveCamera") {
+            Some(read_camera(node, "FieldOfView", |field_of_view| {
+                BcfProjection::Perspective { field_of_view }
+            })?)
+        } else if let Some(node) = child(root, "OrthogonalCamera") {
+            Some(read_camera(
+                node,
+                "ViewToWorldScale",
+                |view_to_world_scale| BcfProjection::Orthographic {
+                    view_to_world_scale,
+                },
+            )?)
+        } else {
+            None
+        };
+
+        let mut clipping_planes = Vec::new();
+        if let Some(planes) = child(root, "ClippingPlanes") {
+            for plane in planes
+                .children()
+                .filter(|n| n.has_tag_name("ClippingPlane"))
+            {
+                let location = read_vector(plane, "Location")?;
+                let direction = read_vector(plane, "Direction")?;
+                clipping_planes.push(Plane::new(location, -direction));
+            }
+        }
+
+        Ok(Self {
+            guid: root.attribute("Guid").unwrap_or_default().to_string(),
+            camera,
+            clipping_planes,
+            default_visibility,
+            exceptions,
+            selection,
+        })
+    }
+
+    /// Visualization info XML in the BCF 2.1 layout
+    pub fn to_xml(&self) -> String {
+        let mut xml = String::new();
+        writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+        writeln!(
+            xml,
+            r#"<VisualizationInfo Guid="{}">"#,
+            xml_escape(&self.guid)
+        )
+        .unwrap();
+
+        writeln!(xml, "  <Components>").unwrap();
+        if !self.selection.is_empty() {
+            writeln!(xml, "    <Selection>").unwrap();
+            write_components(&mut xml, &self.selection, "      ");
+            writeln!(xml, "    </Selection>").unwrap();
+        }
+        writeln!(
+            xml,
+            r#"    <Visibility DefaultVisibility="{}">"#,
+            self.default_visibility
+        )
+        .unwrap();
+        if !self.exceptions.is_empty() {
+            writeln!(xml, "      <Exceptions>").unwrap();
+            write_components(&mut xml, &self.exceptions, "        ");
+            writeln!(xml, "      </Exceptions>").unwrap();
+        }
+        writeln!(xml, "    </Visibility>").unwrap();
+        writeln!(xml, "  </Components>").unwrap();
+
+        if let Some(camera) = &self.camera {
+            let (tag, field, value) = match camera.projection {
+                BcfProjection::Perspective { field_of_view } => {
+                    ("PerspectiveCamera", "FieldOfView", field_of_view)
+                }
+                BcfProjection::Orthographic {
+                    view_to_world_scale,
+                } => ("OrthogonalCamera", "ViewToWorldScale", view_to_world_scale),
+            };
+            writeln!(xml, "  <{}>", tag).unwrap();
+            write_vector(&mut xml, "    ", "CameraViewPoint", camera.view_point);
+            write_vector(&mut xml, "    ", "CameraDirection", camera.direction);
+            write_vector(&mut xml, "    ", "CameraUpVector", camera.up);
+            writeln!(xml, "    <{0}>{1}</{0}>", field, value).unwrap();
+            writeln!(xml, "  </{}>", tag).unwrap();
+        }
+
+        if !self.clipping_planes.is_empty() {
+            writeln!(xml, "  <ClippingPlanes>").unwrap();
+            for plane in &self.clipping_planes {
+                writeln!(xml, "    <ClippingPlane>").unwrap();
+                write_vector(&mut xml, "      ", "Location", plane.origin);
+                write_vector(&mut xml, "      ", "Direction", -plane.normal);
+                writeln!(xml, "    </ClippingPlane>").unwrap();
+            }
+            writeln!(xml, "  </ClippingPlanes>").unwrap();
+        }
+
+        writeln!(xml, "</VisualizationInfo>").unwrap();
+        xml
+    }
+}
+
+fn bcf_error(message: &str) -> CstError {
+    CstError::Parse(format!("BCF viewpoint: {}", message))
+}
+
+fn child<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+    name: &str,
+) -> Option<roxmltree::Node<'a, 'input>> {
+    node.children().find(|n| n.has_tag_name(name))
+}
+
+fn read_number(node: roxmltree::Node, name: &str) -> Result<f64> {
+    child(node, name)
+        .and_then(|n| n.text())
+        .and_then(|text| text.trim().parse().ok())
+        .ok_or_else(|| bcf_error(&format!("missing or invalid {}", name)))
+}
+
+fn read_vector(node: roxmltree::Node, name: &str) -> Result<Vector3> {
+    let vector = child(node, name).ok_or_else(|| bcf_error(&format!("missing {}", name)))?;
+    Ok(Vector3::new(
+        read_number(vector, "X")?,
+        read_number(vector, "Y")?,
+        read_number(vector, "Z")?,
+    ))
+}
+
+fn read_camera(
+    node: roxmltree::Node,
+    field: &str,
+    projection: impl FnOnce(f64) -> BcfProjection,
+) -> Result<BcfCamera> {
+    Ok(BcfCamera {
+        view_point: read_vector(node, "CameraViewPoint")?,
+        direction: read_vector(node, "CameraDirection")?,
+        up: read_vector(node, "CameraUpVector")?,
+        projection: projection(read_number(node, field)?),
+    })
+}
+
+fn write_vector(xml: &mut String, indent: &str, name: &str, v: Vector3) {
+    writeln!(xml, "{}<{}>", indent, name).unwrap();
+    for (axis, value) in [("X", v.x), ("Y", v.y), ("Z", v.z)] {
+        writeln!(xml, "{}  <{1}>{2}</{1}>", indent, axis, value).unwrap();
+    }
+    writeln!(xml, "{}</{}>", indent, name).unwrap();
+}
+
+fn write_components(xml: &mut String, ids: &[String], indent: &str) {
+    for id in ids {
+        writeln!(
+            xml,
+            r#"{}<Component IfcGuid="{}" />"#,
+            indent,
+            xml_escape(id)
+        )
+        .unwrap();
+    }
+}
+
+pub(crate) fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::ElementMetadata;
+    use cst_math::DVec3;
+    use cst_mesh::TriangleMesh;
+
+    const SAMPLE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<VisualizationInfo Guid="8dc86298-9737-40b4-a448-98a9e953293a">
+  <Components>
+    <Selection>
+      <Component IfcGuid="2MF28NhmDBiRVyFakgdbCT" />
+    </Selection>
+    <Visibility DefaultVisibility="false">
+      <Exceptions>
+        <Component IfcGuid="2MF28NhmDBiRVyFakgdbCT" />
+        <Component IfcGuid="0fdpeZZEX3FwJ7x0ox5kzF" />
+      </Exceptions>
+    </Visibility>
+  </Components>
+  <OrthogonalCamera>
+    <CameraViewPoint><X>10</X><Y>-5</Y><Z>3.5</Z></CameraViewPoint>
+    <CameraDirection><X>-1</X><Y>0</Y><Z>0</Z></CameraDirection>
+    <CameraUpVector><X>0</X><Y>0</Y><Z>1</Z></CameraUpVector>
+    <ViewToWorldScale>12.5</ViewToWorldScale>
+    <AspectRatio>1.6</AspectRatio>
+  </OrthogonalCamera>
+  <ClippingPlanes>
+    <ClippingPlane>
+      <Location><X>0</X><Y>0</Y><Z>2</Z></Location>
+      <Direction><X>0</X><Y>0</Y><Z>1</Z></Direction>
+    </ClippingPlane>
+  </ClippingPlanes>
+</VisualizationInfo>
+"#;
+
+    fn element(scene: &mut Scene, global_id: &str, x: f64) {
+        let mesh = TriangleMesh {
+            positions: vec![
+                DVec3::new(x, 0.0, 0.0),
+                DVec3::new(x + 1.0, 0.0, 0.0),
+                DVec3::new(x, 1.0, 0.0),
+            ],
+            normals: vec![],
+            indices: vec![0, 1, 2],
+            uvs: vec![],
+        };
+        let metadata = ElementMetadata {
+            global_id: Some(global_id.to_string()),
+            ..Default::default()
+        };
+        scene.add_element(global_id, mesh, [0.5, 0.5, 0.5], metadata);
+    }
+
+    #[test]
+    fn test_read_viewpoint() {
+        let viewpoint = BcfViewpoint::from_xml(SAMPLE).unwrap();
+        assert_eq!(viewpoint.guid, "8dc86298-9737-40b4-a448-98a9e953293a");
+        assert_eq!(viewpoint.selection, vec!["2MF28NhmDBiRVyFakgdbCT"]);
+        assert!(!viewpoint.default_visibility);
+        assert_eq!(viewpoint.exceptions.len(), 2);
+
+        let camera = viewpoint.camera.as_ref().unwrap();
+        assert_eq!(camera.view_point, DVec3::new(10.0, -5.0, 3.5));
+        assert_eq!(
+            camera.projection,
+            BcfProjection::Orthographic {
+                view_to_world_scale: 12.5
+            }
+        );
+
+        // Geometry above z = 2 is removed, so the kept side faces down
+        let plane = viewpoint.clipping_planes[0];
+        assert_eq!(plane.normal, -DVec3::Z);
+        assert!(plane.signed_distance(DVec3::new(0.0, 0.0, 1.0)) > 0.0);
+    }
+
+    #[test]
+    fn test_apply_to_camera_and_scene() {
+        let mut scene = Scene::new();
+        element(&mut scene, "2MF28NhmDBiRVyFakgdbCT", 0.0);
+        element(&mut scene, "0fdpeZZEX3FwJ7x0ox5kzF", 2.0);
+        element(&mut scene, "3cUkl32yn9qRSPvBJVyWYp", 4.0);
+        scene.add_mesh("No id", TriangleMesh::default(), [0.5, 0.5, 0.5]);
+
+        let mut camera = Camera::default();
+        BcfViewpoint::from_xml(SAMPLE)
+            .unwrap()
+            .apply(&mut camera, &mut scene);
+
+        let visible: Vec<bool> = scene.meshes.iter().map(|m| m.visible).collect();
+        assert_eq!(visible, vec![true, true, false, false]);
+        assert_eq!(scene.section_planes.len(), 1);
+        assert_eq!(camera.eye, DVec3::new(10.0, -5.0, 3.5));
+        assert_eq!(camera.up, DVec3::Z);
+        assert!(camera.is_orthographic());
+        // Target level with the scene center (x = 2.5)
+        assert!((camera.target.x - 2.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_write_round_trip() {
+        let mut scene = Scene::new();
+        element(&mut scene, "A&B", 0.0);
+        element(&mut scene, "C", 2.0);
+        element(&mut scene, "D", 4.0);
+        scene.meshes[1].visible = false;
+        scene.add_section_plane(Plane::new(DVec3::new(0.0, 0.0, 3.0), -DVec3::Z));
+        let camera = Camera {
+            eye: DVec3::new(1.0, 2.0, 10.0),
+            target: DVec3::new(1.0, 2.0, 0.0),
+            ..Default::default()
+        };
+
+        let mut viewpoint = BcfViewpoint::from_scene("view-1", &camera, &scene);
+        assert!(viewpoint.default_visibility);
+        assert_eq!(viewpoint.exceptions, vec!["C"]);
+        viewpoint.selection.push("A&B".into());
+
+        let xml = viewpoint.to_xml();
+        assert!(xml.contains(r#"<Component IfcGuid="A&amp;B" />"#));
+        assert!(xml.contains("<FieldOfView>45</FieldOfView>"));
+        assert_eq!(BcfViewpoint::from_xml(&xml).unwrap(), viewpoint);
+
+        let path = std::env::temp_dir().join("test_viewpoint.bcfv");
+        viewpoint.write(&path).unwrap();
+        assert_eq!(BcfViewpoint::read(&path).unwrap(), viewpoint);
+        let _ = std::fs::remove_file(path);
+
+        assert!(BcfViewpoint::from_xml("<Markup />").is_err());
+    }
+}
@@ -0,0 +1,537 @@
+//! Bounding volume hierarchy over axis-aligned boxes.
+//!
+//! The tree only stores item indices, so the same structure serves for the
+//! triangles of a mesh and for whole meshes of a scene.
+
+use cst_math::plane::Plane;
+use cst_math::ray::Ray;
+use cst_math::{Aabb3, Point3};
+use cst_mesh::TriangleMesh;
+
+use crate::camera::aabb_in_frustum;
+
+/// Maximum number of items stored in a leaf.
+const LEAF_SIZE: usize = 4;
+
+#[derive(Debug, Clone)]
+enum NodeKind {
+    /// Items `items[start..end]`.
+    Leaf { start: usize, end: usize },
+    /// Child node indices.
+    Inner { left: usize, right: usize },
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    bounds: Aabb3,
+    kind: NodeKind,
+}
+
+/// Bounding volume hierarchy built by median splits along the longest axis.
+#[derive(Debug, Clone)]
+pub struct Bvh {
+    nodes: Vec<Node>,
+    items: Vec<usize>,
+    item_bounds: Vec<Aabb3>,
+}
+
+impl Bvh {
+    /// Build a hierarchy over items given by their bounding boxes.
+    pub fn build(bounds: &[Aabb3]) -> Self {
+        let mut bvh = Self {
+            nodes: Vec::new(),
+            items: (0..bounds.len()).collect(),
+            item_bounds: bounds.to_vec(),
+        };
+        if !bounds.is_empty() {
+            bvh.build_node(bounds, 0, bounds.len());
+        }
+        bvh
+    }
+
+    /// Build a hierarchy over the triangles of a mesh.
+    pub fn from_triangles(mesh: &TriangleMesh) -> Self {
+        let bounds: Vec<Aabb3> = mesh
+            .indices
+            .chunks_exact(3)
+            .map(|tri| {
+                let [a, b, c] = triangle_points(mesh, tri);
+                Aabb3::new(a.min(b).min(c), a.max(b).max(c))
+            })
+            .collect();
+        Self::build(&bounds)
+    }
+
+    /// Whether the hierarchy holds no items.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Bounds of all items.
+    pub fn bounds(&self) -> Option<Aabb3> {
+        self.nodes.first().map(|n| n.bounds)
+    }
+
+    /// Bounds of each item, as passed to [`Bvh::build`].
+    pub fn item_bounds(&self) -> &[Aabb3] {
+        &self.item_bounds
+    }
+
+    /// Find the closest item hit by a ray.
+    ///
+    /// `hit(item)` returns the ray parameter of the item's intersection, if
+    /// any. Subtrees whose boxes are entered beyond the best hit so far are
+    /// skipped.
+    pub fn closest_hit<F>(&self, ray: &Ray, mut hit: F) -> Option<(usize, f64)>
+    where
+        F: FnMut(usize) -> Option<f64>,
+    {
+        let mut best: Option<(usize, f64)> = None;
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            let Some(entry) = ray.intersect_aabb(&node.bounds).map(|(entry, _)| entry) else {
+                continue;
+            };
+            if best.is_some_and(|(_, t)| entry > t) {
+                continue;
+            }
+            match node.kind {
+                NodeKind::Leaf { start, end } => {
+                    for &item in &self.items[start..end] {
+                        if let Some(t) = hit(item) {
+                            if best.map_or(true, |(_, best_t)| t < best_t) {
+                                best = Some((item, t));
+                            }
+                        }
+                    }
+                }
+                NodeKind::Inner { left, right } => {
+                    stack.push(right);
+                    stack.push(left);
+                }
+            }
+        }
+        best
+    }
+
+    /// Find the item closest to a point.
+    ///
+    /// `distance(item)` returns the exact distance from the point to the
+    /// item. Subtrees whose boxes are farther away than the best item so far
+    /// are skipped.
+    pub fn nearest<F>(&self, point: Point3, mut distance: F) -> Option<(usize, f64)>
+    where
+        F: FnMut(usize) -> f64,
+    {
+        let mut best: Option<(usize, f64)> = None;
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if best.is_some_and(|(_, d)| node.bounds.distance_to_point(point) >= d) {
+                continue;
+            }
+            match node.kind {
+                NodeKind::Leaf { start, end } => {
+                    for &item in &self.items[start..end] {
+                        let d = distance(item);
+                        if best.map_or(true, |(_, best_d)| d < best_d) {
+                            best = Some((item, d));
+                        }
+                    }
+                }
+                NodeKind::Inner { left, right } => {
+                    // Visit the nearer child first
+                    let left_d = self.nodes[left].bounds.distance_to_point(point);
+                    let right_d = self.nodes[right].bounds.distance_to_point(point);
+                    if left_d <= right_d {
+                        stack.push(right);
+                        stack.push(left);
+                    } else {
+                        stack.push(left);
+                        stack.push(right);
+                    }
+                }
+            }
+        }
+        best
+    }
+
+    /// Find the closest pair of items, one from each hierarchy.
+    ///
+    /// `distance(item, other_item)` returns the exact distance between two
+    /// items. Node pairs whose boxes are farther apart than the best pair so
+    /// far are skipped, and the search stops at the first touching pair.
+    pub fn closest_pair<F>(&self, other: &Bvh, mut distance: F) -> Option<(usize, usize, f64)>
+    where
+        F: FnMut(usize, usize) -> f64,
+    {
+        let mut best: Option<(usize, usize, f64)> = None;
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() && !other.nodes.is_empty() {
+            stack.push((0, 0));
+        }
+        while let Some((index, other_index)) = stack.pop() {
+            let bounds = &self.nodes[index].bounds;
+            let other_bounds = &other.nodes[other_index].bounds;
+            if best.is_some_and(|(_, _, d)| bounds.distance(other_bounds) >= d) {
+                continue;
+            }
+            let children = self.children(index);
+            let other_children = other.children(other_index);
+            // Descend into the larger node, or the only inner one
+            let split_self = match (children, other_children) {
+                (Some(_), Some(_)) => {
+                    bounds.extents().length_squared() >= other_bounds.extents().length_squared()
+                }
+                (children, _) => children.is_some(),
+            };
+            let pairs = match (children, other_children) {
+                (Some((left, right)), _) if split_self => {
+                    [(left, other_index), (right, other_index)]
+                }
+                (_, Some((left, right))) => [(index, left), (index, right)],
+                _ => {
+                    for &item in self.leaf_items(index) {
+                        for &other_item in other.leaf_items(other_index) {
+                            let gap =
+                                self.item_bounds[item].distance(&other.item_bounds[other_item]);
+                            if best.is_some_and(|(_, _, d)| gap >= d) {
+                                continue;
+                            }
+                            let d = distance(item, other_item);
+                            if best.map_or(true, |(_, _, best_d)| d < best_d) {
+                                best = Some((item, other_item, d));
+                            }
+                        }
+                    }
+                    if best.is_some_and(|(_, _, d)| d <= 0.0) {
+                        break;
+                    }
+                    continue;
+                }
+            };
+            // Visit the closer pair first
+            let gap = |(a, b): (usize, usize)| {
+                self.nodes[a].bounds.distance(&other.nodes[b].bounds)
+            };
+            let [near, far] = if gap(pairs[0]) <= gap(pairs[1]) {
+                pairs
+            } else {
+                [pairs[1], pairs[0]]
+            };
+            stack.push(far);
+            stack.push(near);
+        }
+        best
+    }
+
+    fn children(&self, index: usize) -> Option<(usize, usize)> {
+        match self.nodes[index].kind {
+            NodeKind::Inner { left, right } => Some((left, right)),
+            NodeKind::Leaf { .. } => None,
+        }
+    }
+
+    fn leaf_items(&self, index: usize) -> &[usize] {
+        match self.nodes[index].kind {
+            NodeKind::Leaf { start, end } => &self.items[start..end],
+            NodeKind::Inner { .. } => &[],
+        }
+    }
+
+    /// Items whose boxes are hit by a ray, with the entry parameter, sorted
+    /// from near to far.
+    pub fn query_ray(&self, ray: &Ray) -> Vec<(usize, f64)> {
+        let mut hits = Vec::new();
+        self.visit(
+            |bounds| ray.intersect_aabb(bounds).is_some(),
+            |item| {
+                if let Some((t, _)) = ray.intersect_aabb(&self.item_bounds[item]) {
+                    hits.push((item, t));
+                }
+            },
+        );
+        hits.sort_by(|a, b| a.1.total_cmp(&b.1));
+        hits
+    }
+
+    /// Items whose boxes intersect `aabb`.
+    pub fn query_aabb(&self, aabb: &Aabb3) -> Vec<usize> {
+        let mut found = Vec::new();
+        self.visit(
+            |bounds| bounds.intersects(aabb),
+            |item| {
+                if self.item_bounds[item].intersects(aabb) {
+                    found.push(item);
+                }
+            },
+        );
+        found.sort_unstable();
+        found
+    }
+
+    /// Items whose boxes are at least partly inside a frustum given by
+    /// inward-facing planes (see `Camera::frustum_planes`).
+    pub fn query_frustum(&self, planes: &[Plane; 6]) -> Vec<usize> {
+        let mut found = Vec::new();
+        self.visit(
+            |bounds| aabb_in_frustum(planes, bounds),
+            |item| {
+                if aabb_in_frustum(planes, &self.item_bounds[item]) {
+                    found.push(item);
+                }
+            },
+        );
+        found.sort_unstable();
+        found
+    }
+
+    /// Call `found` for every item in leaves reached through nodes accepted
+    /// by `enter`.
+    fn visit<E, F>(&self, enter: E, mut found: F)
+    where
+        E: Fn(&Aabb3) -> bool,
+        F: FnMut(usize),
+    {
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !enter(&node.bounds) {
+                continue;
+            }
+            match node.kind {
+                NodeKind::Leaf { start, end } => {
+                    self.items[start..end].iter().for_each(|&item| found(item));
+                }
+                NodeKind::Inner { left, right } => {
+                    stack.push(right);
+                    stack.push(left);
+                }
+            }
+        }
+    }
+
+    /// Find the closest triangle of `mesh` hit by a ray, for a hierarchy
+    /// built with [`Bvh::from_triangles`] on that mesh.
+    ///
+    /// Hits for which `accept(t)` is false are ignored, so callers can skip
+    /// clipped geometry.
+    pub fn intersect_triangles<F>(
+        &self,
+        mesh: &TriangleMesh,
+        ray: &Ray,
+        accept: F,
+    ) -> Option<(usize, f64)>
+    where
+        F: Fn(f64) -> bool,
+    {
+        self.closest_hit(ray, |tri| {
+            let [a, b, c] = triangle_points(mesh, &mesh.indices[tri * 3..tri * 3 + 3]);
+            ray.intersect_triangle(a, b, c)
+                .map(|hit| hit.t)
+                .filter(|&t| accept(t))
+        })
+    }
+
+    fn build_node(&mut self, bounds: &[Aabb3], start: usize, end: usize) -> usize {
+        let items = &mut self.items[start..end];
+        let node_bounds = items
+            .iter()
+            .map(|&i| bounds[i])
+            .reduce(|a, b| a.merge(&b))
+            .unwrap_or_else(|| Aabb3::new(Point3::ZERO, Point3::ZERO));
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            bounds: node_bounds,
+            kind: NodeKind::Leaf { start, end },
+        });
+        if items.len() <= LEAF_SIZE {
+            return index;
+        }
+
+        // Split at the median centroid along the longest axis.
+        let extents = node_bounds.extents();
+        let axis = if extents.x >= extents.y && extents.x >= extents.z {
+            0
+        } else if extents.y >= extents.z {
+            1
+        } else {
+            2
+        };
+        let mid = items.len() / 2;
+        items.select_nth_unstable_by(mid, |&a, &b| {
+            bounds[a].center()[axis].total_cmp(&bounds[b].center()[axis])
+        });
+
+        let left = self.build_node(bounds, start, start + mid);
+        let right = self.build_node(bounds, start + mid, end);
+        self.nodes[index].kind = NodeKind::Inner { left, right };
+        index
+    }
+}
+
+fn triangle_points(mesh: &TriangleMesh, tri: &[u32]) -> [Point3; 3] {
+    [
+        mesh.positions[tri[0] as usize],
+        mesh.positions[tri[1] as usize],
+        mesh.positions[tri[2] as usize],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cst_math::Vector3;
+
+    fn grid_mesh(n: usize) -> TriangleMesh {
+        // n x n quads in the z = 0 plane
+        let mut positions = Vec::new();
+        for j in 0..=n {
+            for i in 0..=n {
+                positions.push(Point3::new(i as f64, j as f64, 0.0));
+            }
+        }
+        let mut indices = Vec::new();
+        let row = (n + 1) as u32;
+        for j in 0..n as u32 {
+            for i in 0..n as u32 {
+                let v = j * row + i;
+                indices.extend_from_slice(&[v, v + 1, v + row + 1, v, v + row + 1, v + row]);
+            }
+        }
+        TriangleMesh {
+            positions,
+            normals: vec![],
+            indices,
+            uvs: vec![],
+        }
+    }
+
+    #[test]
+    fn test_empty_bvh() {
+        let bvh = Bvh::build(&[]);
+        assert!(bvh.is_empty());
+        assert!(bvh.bounds().is_none());
+        let ray = Ray::new(Point3::ZERO, Vector3::Z);
+        assert!(bvh.closest_hit(&ray, |_| Some(0.0)).is_none());
+    }
+
+    #[test]
+    fn test_triangle_hit_matches_brute_force() {
+        let mesh = grid_mesh(8);
+        let bvh = Bvh::from_triangles(&mesh);
+        let ray = Ray::new(Point3::new(3.25, 5.75, 10.0), -Vector3::Z);
+
+        let (tri, t) = bvh.intersect_triangles(&mesh, &ray, |_| true).unwrap();
+        assert!((t - 10.0).abs() < 1e-10);
+
+        let hits: Vec<usize> = (0..mesh.indices.len() / 3)
+            .filter(|&i| {
+                let [a, b, c] = triangle_points(&mesh, &mesh.indices[i * 3..i * 3 + 3]);
+                ray.intersect_triangle(a, b, c).is_some()
+            })
+            .collect();
+        assert_eq!(hits, vec![tri]);
+    }
+
+    #[test]
+    fn test_miss_and_rejected_hits() {
+        let mesh = grid_mesh(4);
+        let bvh = Bvh::from_triangles(&mesh);
+
+        let outside = Ray::new(Point3::new(10.0, 10.0, 1.0), -Vector3::Z);
+        assert!(bvh.intersect_triangles(&mesh, &outside, |_| true).is_none());
+
+        let inside = Ray::new(Point3::new(1.5, 1.5, 1.0), -Vector3::Z);
+        assert!(bvh.intersect_triangles(&mesh, &inside, |_| false).is_none());
+    }
+
+    #[test]
+    fn test_closest_of_stacked_items() {
+        // Unit boxes stacked along z; the ray comes from above.
+        let bounds: Vec<Aabb3> = (0..20)
+            .map(|k| {
+                let z = k as f64 * 2.0;
+                Aabb3::new(Point3::new(0.0, 0.0, z), Point3::new(1.0, 1.0, z + 1.0))
+            })
+            .collect();
+        let bvh = Bvh::build(&bounds);
+        let ray = Ray::new(Point3::new(0.5, 0.5, 100.0), -Vector3::Z);
+
+        let hit = bvh.closest_hit(&ray, |i| ray.intersect_aabb(&bounds[i]).map(|(t, _)| t));
+        assert_eq!(hit.map(|(i, _)| i), Some(19));
+
+        let order: Vec<usize> = bvh.query_ray(&ray).into_iter().map(|(i, _)| i).collect();
+        assert_eq!(order, (0..20).rev().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_query_aabb() {
+        let bounds: Vec<Aabb3> = (0..50)
+            .map(|k| {
+                let x = k as f64;
+                Aabb3::new(Point3::new(x, 0.0, 0.0), Point3::new(x + 0.5, 1.0, 1.0))
+            })
+            .collect();
+        let bvh = Bvh::build(&bounds);
+
+        let query = Aabb3::new(Point3::new(9.8, 0.5, 0.5), Point3::new(12.2, 2.0, 2.0));
+        assert_eq!(bvh.query_aabb(&query), vec![10, 11, 12]);
+
+        let far = Aabb3::new(Point3::new(0.0, 5.0, 0.0), Point3::new(100.0, 6.0, 1.0));
+        assert!(bvh.query_aabb(&far).is_empty());
+    }
+
+    #[test]
+    fn test_nearest_and_closest_pair_match_brute_force() {
+        // Scattered unit boxes, deterministic
+        let scatter = |seed: u64, count: usize| -> Vec<Aabb3> {
+            let mut state = seed;
+            let mut next = || {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                (state >> 33) as f64 / (1u64 << 31) as f64 * 50.0
+            };
+            (0..count)
+                .map(|_| {
+                    let min = Point3::new(next(), next(), next());
+                    Aabb3::new(min, min + Vector3::ONE)
+                })
+                .collect()
+        };
+        let a = scatter(1, 40);
+        let b: Vec<Aabb3> = scatter(2, 30)
+            .iter()
+            .map(|bb| Aabb3::new(bb.min + Vector3::X * 60.0, bb.max + Vector3::X * 60.0))
+            .collect();
+        let (a_bvh, b_bvh) = (Bvh::build(&a), Bvh::build(&b));
+
+        let point = Point3::new(25.0, 70.0, -5.0);
+        let (item, d) = a_bvh.nearest(point, |i| a[i].distance_to_point(point)).unwrap();
+        let expected = a
+            .iter()
+            .map(|bb| bb.distance_to_point(point))
+            .fold(f64::INFINITY, f64::min);
+        assert_eq!(d, expected);
+        assert_eq!(a[item].distance_to_point(point), expected);
+
+        let (i, j, d) = a_bvh.closest_pair(&b_bvh, |i, j| a[i].distance(&b[j])).unwrap();
+        let expected = a
+            .iter()
+            .flat_map(|x| b.iter().map(move |y| x.distance(y)))
+            .fold(f64::INFINITY, f64::min);
+        assert_eq!(d, expected);
+        assert_eq!(a[i].distance(&b[j]), expected);
+
+        assert!(a_bvh.closest_pair(&Bvh::build(&[]), |_, _| 0.0).is_none());
+    }
+}
@@ -1,6 +1,128 @@
-use cst_math::{Aabb3, Point3, Vector3, DVec3};
+use cst_math::{Aabb3, Point3, Vector3, DVec3, DMat3, DQuat};
+use cst_math::coords::Spherical;
+use cst_math::plane::Plane;
+use cst_math::ray::Ray;
+use cst_math::transform::Trs;
+use cst_core::error::{CstError, Result};
+use cst_mesh::ScreenSpaceError;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
 
-/// A 3D perspective camera with look-at controls.
+use crate::scene::{PickTarget, Scene};
+
+/// Margin [`Camera::fit_to_aabb`] leaves around the box, as a fraction of
+/// its size on screen.
+pub const DEFAULT_FIT_PADDING: f64 = 0.1;
+
+/// How a camera maps view space onto the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Projection {
+    /// Perspective projection using the camera's `fov_y`.
+    Perspective,
+    /// Parallel projection showing `height` world units vertically.
+    Orthographic { height: f64 },
+}
+
+/// Preset viewing directions, relative to the camera's Y-up world: north
+/// is -Z and east +X, so the front elevation looks north from the south.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StandardView {
+    Top,
+    Bottom,
+    Front,
+    Back,
+    Left,
+    Right,
+    /// Isometric from above the north-east corner
+    IsoNorthEast,
+    IsoNorthWest,
+    IsoSouthEast,
+    IsoSouthWest,
+}
+
+impl StandardView {
+    /// Every view, in the order of the viewer's number-key hotkeys.
+    pub const ALL: [StandardView; 10] = [
+        StandardView::Top,
+        StandardView::Front,
+        StandardView::Left,
+        StandardView::Right,
+        StandardView::Back,
+        StandardView::Bottom,
+        StandardView::IsoNorthEast,
+        StandardView::IsoNorthWest,
+        StandardView::IsoSouthEast,
+        StandardView::IsoSouthWest,
+    ];
+
+    /// Display name, e.g. `Isometric NE`.
+    pub fn name(self) -> &'static str {
+        match self {
+            StandardView::Top => "Top",
+            StandardView::Bottom => "Bottom",
+            StandardView::Front => "Front",
+            StandardView::Back => "Back",
+            StandardView::Left => "Left",
+            StandardView::Right => "Right",
+            StandardView::IsoNorthEast => "Isometric NE",
+            StandardView::IsoNorthWest => "Isometric NW",
+            StandardView::IsoSouthEast => "Isometric SE",
+            StandardView::IsoSouthWest => "Isometric SW",
+        }
+    }
+
+    /// Short identifier for command lines, e.g. `iso-ne`.
+    pub fn key(self) -> &'static str {
+        match self {
+            StandardView::Top => "top",
+            StandardView::Bottom => "bottom",
+            StandardView::Front => "front",
+            StandardView::Back => "back",
+            StandardView::Left => "left",
+            StandardView::Right => "right",
+            StandardView::IsoNorthEast => "iso-ne",
+            StandardView::IsoNorthWest => "iso-nw",
+            StandardView::IsoSouthEast => "iso-se",
+            StandardView::IsoSouthWest => "iso-sw",
+        }
+    }
+
+    /// The view with the given [`key`](Self::key), ignoring case.
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|view| view.key().eq_ignore_ascii_case(key))
+    }
+
+    /// Unit vector from the target towards the eye.
+    pub fn direction(self) -> Vector3 {
+        let v = match self {
+            StandardView::Top => Vector3::Y,
+            StandardView::Bottom => Vector3::NEG_Y,
+            StandardView::Front => Vector3::Z,
+            StandardView::Back => Vector3::NEG_Z,
+            StandardView::Left => Vector3::NEG_X,
+            StandardView::Right => Vector3::X,
+            StandardView::IsoNorthEast => Vector3::new(1.0, 1.0, -1.0),
+            StandardView::IsoNorthWest => Vector3::new(-1.0, 1.0, -1.0),
+            StandardView::IsoSouthEast => Vector3::new(1.0, 1.0, 1.0),
+            StandardView::IsoSouthWest => Vector3::new(-1.0, 1.0, 1.0),
+        };
+        v.normalize()
+    }
+
+    /// Screen up: world up, except for plans, which put north (-Z) at the
+    /// top from above and south at the top from below.
+    pub fn up(self) -> Vector3 {
+        match self {
+            StandardView::Top => Vector3::NEG_Z,
+            StandardView::Bottom => Vector3::Z,
+            _ => Vector3::Y,
+        }
+    }
+}
+
+/// A 3D camera with look-at controls.
 #[derive(Debug, Clone)]
 pub struct Camera {
     pub eye: Point3,       // camera position
@@ -10,6 +132,24 @@ pub struct Camera {
     pub aspect: f64,       // width/height
     pub near: f64,         // near clip plane
     pub far: f64,          // far clip plane
+    pub projection: Projection,
+}
+
+impl Default for Camera {
+    /// Create a camera with sensible defaults.
+    /// Eye at (0, 0, 5), looking at origin, 45° FOV, 16:9 aspect.
+    fn default() -> Self {
+        Self {
+            eye: Point3::new(0.0, 0.0, 5.0),
+            target: Point3::ZERO,
+            up: Vector3::Y,
+            fov_y: std::f64::consts::FRAC_PI_4, // 45 degrees
+            aspect: 16.0 / 9.0,
+            near: 0.1,
+            far: 100.0,
+            projection: Projection::Perspective,
+        }
+    }
 }
 
 impl Camera {
@@ -31,23 +171,50 @@ impl Camera {
             aspect,
             near,
             far,
+            projection: Projection::Perspective,
         }
     }
 
-    /// Create a camera with sensible defaults.
-    /// Eye at (0, 0, 5), looking at origin, 45° FOV, 16:9 aspect.
-    pub fn default() -> Self {
-        Self {
-            eye: Point3::new(0.0, 0.0, 5.0),
-            target: Point3::ZERO,
-            up: Vector3::Y,
-            fov_y: std::f64::consts::FRAC_PI_4, // 45 degrees
-            aspect: 16.0 / 9.0,
-            near: 0.1,
-            far: 100.0,
+    /// A perspective camera looking at `bounds` from a standard direction,
+    /// framed with [`fit_to_aabb`](Self::fit_to_aabb).
+    pub fn standard_view(view: StandardView, bounds: &Aabb3, aspect: f64) -> Self {
+        let mut camera = Self {
+            aspect,
+            ..Self::default()
+        };
+        camera.set_standard_view(view, bounds);
+        camera
+    }
+
+    /// Turn to a standard direction and frame `bounds`, keeping the
+    /// projection, so orthographic cameras give true plans and elevations.
+    pub fn set_standard_view(&mut self, view: StandardView, bounds: &Aabb3) {
+        self.target = bounds.center();
+        self.eye = self.target + view.direction();
+        self.up = view.up();
+        self.fit_to_aabb(bounds);
+    }
+
+    /// Whether the camera uses an orthographic projection.
+    pub fn is_orthographic(&self) -> bool {
+        matches!(self.projection, Projection::Orthographic { .. })
+    }
+
+    /// Switch to an orthographic projection that keeps the size of objects
+    /// at the target distance unchanged.
+    pub fn set_orthographic(&mut self) {
+        if let Projection::Perspective = self.projection {
+            let distance = (self.target - self.eye).length();
+            let height = 2.0 * distance * (self.fov_y / 2.0).tan();
+            self.projection = Projection::Orthographic { height };
         }
     }
 
+    /// Switch back to a perspective projection.
+    pub fn set_perspective(&mut self) {
+        self.projection = Projection::Perspective;
+    }
+
     /// Compute the view matrix (look-at matrix) in row-major format.
     pub fn view_matrix(&self) -> [[f64; 4]; 4] {
         let forward = (self.target - self.eye).normalize();
@@ -73,9 +240,22 @@ impl Camera {
         mat
     }
 
-    /// Compute the perspective projection matrix in row-major format.
+    /// Compute the projection matrix in row-major format.
     /// Uses OpenGL-style NDC (-1 to 1 for Z).
     pub fn projection_matrix(&self) -> [[f64; 4]; 4] {
+        if let Projection::Orthographic { height } = self.projection {
+            let half_h = height / 2.0;
+            let half_w = half_h * self.aspect;
+
+            let mut mat = [[0.0; 4]; 4];
+            mat[0][0] = 1.0 / half_w;
+            mat[1][1] = 1.0 / half_h;
+            mat[2][2] = -2.0 / (self.far - self.near);
+            mat[2][3] = -(self.far + self.near) / (self.far - self.near);
+            mat[3][3] = 1.0;
+            return mat;
+        }
+
         let tan_half_fov = (self.fov_y / 2.0).tan();
         let f = 1.0 / tan_half_fov;
 
@@ -96,33 +276,109 @@ impl Camera {
         multiply_matrices(&proj, &view)
     }
 
+    /// The six frustum planes (left, right, bottom, top, near, far) with
+    /// normals pointing into the visible volume.
+    ///
+    /// Extracted from the view-projection matrix, so both projections work.
+    pub fn frustum_planes(&self) -> [Plane; 6] {
+        let m = self.view_projection();
+        let combine = |row: usize, sign: f64| {
+            let a = m[3][0] + sign * m[row][0];
+            let b = m[3][1] + sign * m[row][1];
+            let c = m[3][2] + sign * m[row][2];
+            let d = m[3][3] + sign * m[row][3];
+            // a*x + b*y + c*z + d = 0 as origin + normal
+            let normal = Vector3::new(a, b, c);
+            Plane::new(-normal * (d / normal.length_squared()), normal)
+        };
+        [
+            combine(0, 1.0),
+            combine(0, -1.0),
+            combine(1, 1.0),
+            combine(1, -1.0),
+            combine(2, 1.0),
+            combine(2, -1.0),
+        ]
+    }
+
+    /// Build the world-space ray through a pixel.
+    ///
+    /// `x` and `y` are measured from the top-left corner of a viewport of
+    /// `viewport = (width, height)` pixels. Perspective rays start at the eye;
+    /// orthographic rays start on the eye plane and share the view direction.
+    pub fn ray_from_screen(&self, x: f64, y: f64, viewport: (f64, f64)) -> Ray {
+        let ndc_x = 2.0 * x / viewport.0 - 1.0;
+        let ndc_y = 1.0 - 2.0 * y / viewport.1;
+
+        let forward = (self.target - self.eye).normalize();
+        let right = forward.cross(self.up).normalize();
+        let up = right.cross(forward);
+
+        match self.projection {
+            Projection::Perspective => {
+                let tan_half_fov = (self.fov_y / 2.0).tan();
+                let direction = forward
+                    + right * (ndc_x * tan_half_fov * self.aspect)
+                    + up * (ndc_y * tan_half_fov);
+                Ray::new(self.eye, direction)
+            }
+            Projection::Orthographic { height } => {
+                let half_h = height / 2.0;
+                let origin =
+                    self.eye + right * (ndc_x * half_h * self.aspect) + up * (ndc_y * half_h);
+                Ray::new(origin, forward)
+            }
+        }
+    }
+
+    /// Screen-space tessellation limits for this camera on a viewport
+    /// `viewport_height` pixels tall, allowing surfaces to deviate by
+    /// `max_pixels` (see [`cst_mesh::screen_space_tessellate_surface`]).
+    ///
+    /// The result depends on the eye position and zoom, not the view
+    /// direction; tessellate again after moving or zooming a lot.
+    pub fn screen_space_error(&self, viewport_height: f64, max_pixels: f64) -> ScreenSpaceError {
+        match self.projection {
+            Projection::Perspective => {
+                let pixels_per_unit = viewport_height / (2.0 * (self.fov_y / 2.0).tan());
+                ScreenSpaceError::new(self.eye, pixels_per_unit, max_pixels)
+            }
+            Projection::Orthographic { height } => ScreenSpaceError {
+                orthographic: true,
+                ..ScreenSpaceError::new(self.eye, viewport_height / height, max_pixels)
+            },
+        }
+    }
+
     /// Orbit the camera around the target.
     /// delta_x and delta_y are in radians.
     pub fn orbit(&mut self, delta_x: f64, delta_y: f64) {
         let offset = self.eye - self.target;
-        let radius = offset.length();
 
-        // Convert to spherical coordinates
-        let theta = offset.z.atan2(offset.x); // azimuth
-        let phi = (offset.y / radius).acos(); // polar angle
+        // Spherical coordinates around the Y (up) axis: swap Y and Z
+        let mut spherical = Spherical::from_cartesian(DVec3::new(offset.x, offset.z, offset.y));
+        spherical.azimuth += delta_x;
+        spherical.polar = (spherical.polar + delta_y).clamp(0.01, std::f64::consts::PI - 0.01);
 
-        // Apply deltas
-        let new_theta = theta + delta_x;
-        let new_phi = (phi + delta_y).clamp(0.01, std::f64::consts::PI - 0.01);
-
-        // Convert back to Cartesian
-        let new_offset = DVec3::new(
-            radius * new_phi.sin() * new_theta.cos(),
-            radius * new_phi.cos(),
-            radius * new_phi.sin() * new_theta.sin(),
-        );
-
-        self.eye = self.target + new_offset;
+        let new_offset = spherical.to_cartesian();
+        self.eye = self.target + DVec3::new(new_offset.x, new_offset.z, new_offset.y);
     }
 
     /// Zoom by moving the camera closer or farther from the target.
     /// Positive delta moves closer, negative moves farther.
+    ///
+    /// Orthographic cameras shrink or grow the visible height by the same
+    /// proportion instead, since moving the eye would not change the image.
     pub fn zoom(&mut self, delta: f64) {
+        if let Projection::Orthographic { height } = &mut self.projection {
+            let distance = (self.target - self.eye).length();
+            let new_distance = distance - delta;
+            if new_distance > 0.1 {
+                *height *= new_distance / distance;
+            }
+            return;
+        }
+
         let direction = (self.target - self.eye).normalize();
         let new_eye = self.eye + direction * delta;
 
@@ -144,23 +400,199 @@ impl Camera {
         self.target += offset;
     }
 
-    /// Adjust camera to fit an AABB in view.
-    /// Positions camera to see entire bounding box.
+    /// Adjust camera to fit an AABB in view, keeping the view direction.
+    ///
+    /// Uses [`DEFAULT_FIT_PADDING`]; see
+    /// [`fit_to_aabb_with_padding`](Self::fit_to_aabb_with_padding).
     pub fn fit_to_aabb(&mut self, aabb: &Aabb3) {
+        self.fit_to_aabb_with_padding(aabb, DEFAULT_FIT_PADDING);
+    }
+
+    /// Frame an AABB so all eight corners are on screen, leaving `padding`
+    /// (a fraction of the box's screen size, e.g. 0.1 for 10%) around it.
+    ///
+    /// The view direction and up vector are kept; the target moves to the
+    /// box centre. Both screen axes are checked against the aspect ratio,
+    /// so tall boxes fit vertically and wide ones horizontally.
+    ///
+    /// Perspective cameras move the eye to the closest distance at which
+    /// every corner is inside the frustum. Orthographic cameras set the
+    /// visible height instead and back the eye off far enough that the box
+    /// lies between the clip planes. The far plane grows if needed.
+    pub fn fit_to_aabb_with_padding(&mut self, aabb: &Aabb3, padding: f64) {
         let center = aabb.center();
-        let size = aabb.extents();
-        let max_dim = size.x.max(size.y).max(size.z);
+        let forward = (self.target - self.eye).normalize();
+        let right = forward.cross(self.up).normalize();
+        let up = right.cross(forward);
+        let scale = 1.0 + padding.max(0.0);
 
-        // Calculate distance needed to fit the object
-        let distance = max_dim / (2.0 * (self.fov_y / 2.0).tan());
+        // Box corners in the camera frame centred on the box
+        let corners: Vec<(f64, f64, f64)> = (0..8)
+            .map(|i| {
+                let corner = Point3::new(
+                    if i & 1 == 0 { aabb.min.x } else { aabb.max.x },
+                    if i & 2 == 0 { aabb.min.y } else { aabb.max.y },
+                    if i & 4 == 0 { aabb.min.z } else { aabb.max.z },
+                );
+                let offset = corner - center;
+                (offset.dot(right), offset.dot(up), offset.dot(forward))
+            })
+            .collect();
 
-        // Position camera along view direction
-        let view_dir = (self.target - self.eye).normalize();
         self.target = center;
-        self.eye = center - view_dir * distance * 1.5; // 1.5x for padding
+        if self.is_orthographic() {
+            let half_height = corners
+                .iter()
+                .map(|&(x, y, _)| y.abs().max(x.abs() / self.aspect))
+                .fold(0.0, f64::max);
+            let height = (2.0 * half_height * scale).max(1e-6);
+            self.projection = Projection::Orthographic { height };
+            // The bounding sphere stays between the clip planes whatever
+            // the view direction, so orbiting does not clip the model.
+            let diameter = aabb.extents().length().max(1e-6);
+            self.eye = center - forward * (diameter + self.near);
+            self.far = self.far.max(2.0 * diameter + self.near);
+            return;
+        }
+
+        // A corner at depth `distance + z` is on screen when its offsets
+        // are within the half-extents of the frustum at that depth.
+        let tan_y = (self.fov_y / 2.0).tan() / scale;
+        let tan_x = tan_y * self.aspect;
+        let mut distance = self.near;
+        let mut depth = 0.0_f64;
+        for &(x, y, z) in &corners {
+            let needed = (x.abs() / tan_x).max(y.abs() / tan_y) - z;
+            distance = distance.max(needed).max(self.near - z);
+            depth = depth.max(z);
+        }
+        self.eye = center - forward * distance;
+        self.far = self.far.max(distance + depth + self.near);
+    }
+
+    /// Frame the meshes and instances in `selection`, see
+    /// [`fit_to_aabb`](Self::fit_to_aabb).
+    ///
+    /// Returns `false`, leaving the camera unchanged, when none of them
+    /// has geometry.
+    pub fn fit_to_selection(&mut self, scene: &Scene, selection: &[PickTarget]) -> bool {
+        match scene.selection_bounds(selection) {
+            Some(bounds) => {
+                self.fit_to_aabb(&bounds);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// A named, serializable viewpoint (camera bookmark).
+///
+/// Only the pose and projection are stored; aspect ratio and clip planes
+/// belong to the viewer that applies the view.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CameraView {
+    pub name: String,
+    pub eye: Point3,
+    pub target: Point3,
+    pub up: Vector3,
+    /// Vertical FOV in radians, used for perspective projection
+    pub fov_y: f64,
+    pub projection: Projection,
+}
+
+impl CameraView {
+    /// Capture the current pose of a camera.
+    pub fn from_camera(name: &str, camera: &Camera) -> Self {
+        Self {
+            name: name.to_string(),
+            eye: camera.eye,
+            target: camera.target,
+            up: camera.up,
+            fov_y: camera.fov_y,
+            projection: camera.projection,
+        }
+    }
+
+    /// Move a camera to this view, keeping its aspect ratio and clip planes.
+    pub fn apply(&self, camera: &mut Camera) {
+        camera.eye = self.eye;
+        camera.target = self.target;
+        camera.up = self.up;
+        camera.fov_y = self.fov_y;
+        camera.projection = self.projection;
+    }
+
+    /// Blend towards `other` for camera animation (`t` in `[0, 1]`).
+    ///
+    /// The orbit around the target is interpolated as a rotation (slerp),
+    /// so the eye swings around the model instead of cutting through it,
+    /// and the target distance changes linearly. Projection and name are
+    /// taken from whichever view is nearer.
+    pub fn interpolate(&self, other: &CameraView, t: f64) -> CameraView {
+        let pose = self.orbit_pose().interpolate(&other.orbit_pose(), t);
+        let nearer = if t < 0.5 { self } else { other };
+        let projection = match (self.projection, other.projection) {
+            (Projection::Orthographic { height: a }, Projection::Orthographic { height: b }) => {
+                Projection::Orthographic { height: a + (b - a) * t }
+            }
+            _ => nearer.projection,
+        };
+        CameraView {
+            name: nearer.name.clone(),
+            eye: pose.transform_point(Vector3::Z),
+            target: pose.translation,
+            up: pose.rotation * Vector3::Y,
+            fov_y: self.fov_y + (other.fov_y - self.fov_y) * t,
+            projection,
+        }
+    }
+
+    /// Camera frame as a TRS centred on the target: the rotation maps view
+    /// axes to world axes and the scale is the eye distance, so the eye
+    /// sits at local +Z.
+    fn orbit_pose(&self) -> Trs {
+        let offset = self.eye - self.target;
+        let distance = offset.length();
+        let back = offset.normalize_or_zero();
+        let right = self.up.cross(back).normalize_or_zero();
+        let up = back.cross(right);
+        let rotation = DQuat::from_mat3(&DMat3::from_cols(right, up, back));
+        Trs::new(self.target, rotation, Vector3::splat(distance))
     }
 }
 
+/// Write named views to a JSON file.
+pub fn save_views(path: &Path, views: &[CameraView]) -> Result<()> {
+    let json = serde_json::to_string_pretty(views)
+        .map_err(|e| CstError::Parse(format!("camera views: {}", e)))?;
+    std::fs::write(path, json)?;
+    Ok(())
+}
+
+/// Read named views written by [`save_views`].
+pub fn load_views(path: &Path) -> Result<Vec<CameraView>> {
+    let json = std::fs::read_to_string(path)?;
+    serde_json::from_str(&json).map_err(|e| CstError::Parse(format!("camera views: {}", e)))
+}
+
+/// Whether an AABB is at least partly inside a frustum given by inward
+/// facing planes.
+///
+/// Conservative: boxes near frustum corners may be reported visible.
+pub fn aabb_in_frustum(planes: &[Plane; 6], aabb: &Aabb3) -> bool {
+    planes.iter().all(|plane| {
+        // Corner furthest along the plane normal
+        let n = plane.normal;
+        let corner = Point3::new(
+            if n.x >= 0.0 { aabb.max.x } else { aabb.min.x },
+            if n.y >= 0.0 { aabb.max.y } else { aabb.min.y },
+            if n.z >= 0.0 { aabb.max.z } else { aabb.min.z },
+        );
+        plane.signed_distance(corner) >= 0.0
+    })
+}
+
 /// Multiply two 4x4 matrices (row-major).
 fn multiply_matrices(a: &[[f64; 4]; 4], b: &[[f64; 4]; 4]) -> [[f64; 4]; 4] {
     let mut result = [[0.0; 4]; 4];
@@ -283,4 +715,328 @@ mod tests {
         let distance = (cam.eye - cam.target).length();
         assert!(distance > 4.0); // Should be farther than box radius
     }
+
+    #[test]
+    fn test_orthographic_projection_matrix() {
+        let cam = Camera {
+            aspect: 2.0,
+            projection: Projection::Orthographic { height: 10.0 },
+            ..Default::default()
+        };
+        let proj = cam.projection_matrix();
+
+        assert!((proj[0][0] - 0.1).abs() < 1e-10); // half width = 10
+        assert!((proj[1][1] - 0.2).abs() < 1e-10); // half height = 5
+        assert!(proj[3][2].abs() < 1e-10); // no perspective divide
+        assert!((proj[3][3] - 1.0).abs() < 1e-10);
+
+        // Near and far planes map to -1 and 1
+        let depth = |z: f64| proj[2][2] * z + proj[2][3];
+        assert!((depth(-cam.near) + 1.0).abs() < 1e-10);
+        assert!((depth(-cam.far) - 1.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_set_orthographic_keeps_target_scale() {
+        let mut cam = Camera::default();
+        cam.set_orthographic();
+        assert!(cam.is_orthographic());
+
+        let expected = 2.0 * 5.0 * (cam.fov_y / 2.0).tan();
+        match cam.projection {
+            Projection::Orthographic { height } => assert!((height - expected).abs() < 1e-10),
+            Projection::Perspective => unreachable!(),
+        }
+
+        cam.set_perspective();
+        assert!(!cam.is_orthographic());
+    }
+
+    #[test]
+    fn test_orthographic_zoom_scales_height() {
+        let mut cam = Camera {
+            projection: Projection::Orthographic { height: 10.0 },
+            ..Default::default()
+        };
+        let eye = cam.eye;
+
+        cam.zoom(2.5); // half the target distance
+
+        assert_eq!(cam.eye, eye);
+        assert_eq!(cam.projection, Projection::Orthographic { height: 5.0 });
+    }
+
+    #[test]
+    fn test_ray_from_screen_center() {
+        let cam = Camera::default();
+        let ray = cam.ray_from_screen(960.0, 540.0, (1920.0, 1080.0));
+        assert_eq!(ray.origin, cam.eye);
+        assert!((ray.direction - Vector3::new(0.0, 0.0, -1.0)).length() < 1e-10);
+    }
+
+    #[test]
+    fn test_ray_from_screen_corner_projects_back() {
+        let cam = Camera::default();
+        let ray = cam.ray_from_screen(0.0, 0.0, (1920.0, 1080.0));
+
+        // A point along the ray lands on the top-left NDC corner
+        let p = ray.at(3.0);
+        let vp = cam.view_projection();
+        let clip: Vec<f64> = (0..4)
+            .map(|i| vp[i][0] * p.x + vp[i][1] * p.y + vp[i][2] * p.z + vp[i][3])
+            .collect();
+        assert!((clip[0] / clip[3] + 1.0).abs() < 1e-9);
+        assert!((clip[1] / clip[3] - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_ray_from_screen_orthographic() {
+        let cam = Camera {
+            aspect: 2.0,
+            projection: Projection::Orthographic { height: 4.0 },
+            ..Default::default()
+        };
+        let ray = cam.ray_from_screen(800.0, 100.0, (800.0, 400.0));
+        assert!((ray.direction - Vector3::new(0.0, 0.0, -1.0)).length() < 1e-10);
+        assert!((ray.origin - Point3::new(4.0, 1.0, 5.0)).length() < 1e-10);
+    }
+
+    #[test]
+    fn test_frustum_planes_perspective() {
+        let cam = Camera::default();
+        let planes = cam.frustum_planes();
+
+        // Target is inside, eye and points beyond the far plane are not
+        let inside = |p: Point3| planes.iter().all(|pl| pl.signed_distance(p) >= 0.0);
+        assert!(inside(cam.target));
+        assert!(!inside(cam.eye));
+        assert!(!inside(Point3::new(0.0, 0.0, 5.0 - 150.0)));
+        assert!(!inside(Point3::new(50.0, 0.0, 0.0)));
+
+        // Near plane sits at distance `near` in front of the eye
+        assert!((planes[4].signed_distance(cam.eye) + cam.near).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_aabb_in_frustum() {
+        let cam = Camera {
+            projection: Projection::Orthographic { height: 2.0 },
+            aspect: 1.0,
+            ..Default::default()
+        };
+        let planes = cam.frustum_planes();
+        let unit = |c: Point3| Aabb3::new(c - DVec3::splat(0.5), c + DVec3::splat(0.5));
+
+        assert!(aabb_in_frustum(&planes, &unit(Point3::ZERO)));
+        assert!(aabb_in_frustum(&planes, &unit(Point3::new(1.2, 0.0, 0.0)))); // straddles
+        assert!(!aabb_in_frustum(&planes, &unit(Point3::new(2.0, 0.0, 0.0))));
+        assert!(!aabb_in_frustum(&planes, &unit(Point3::new(0.0, 0.0, 10.0))));
+    }
+
+    #[test]
+    fn test_orthographic_fit_to_aabb() {
+        let mut cam = Camera {
+            aspect: 1.0,
+            projection: Projection::Orthographic { height: 1.0 },
+            ..Default::default()
+        };
+        let aabb = Aabb3::new(
+            Point3::new(-1.0, -1.0, -1.0),
+            Point3::new(1.0, 1.0, 1.0),
+        );
+
+        cam.fit_to_aabb(&aabb);
+
+        assert_eq!(cam.target, Point3::ZERO);
+        let Projection::Orthographic { height } = cam.projection else {
+            unreachable!()
+        };
+        // Looking straight down -Z the cube's face fills the height, padded
+        assert!((height - 2.0 * (1.0 + DEFAULT_FIT_PADDING)).abs() < 1e-10);
+
+        // Whole box lies between the clip planes
+        let distance = (cam.eye - cam.target).length();
+        let radius = aabb.extents().length() / 2.0;
+        assert!(distance - radius > cam.near);
+        assert!(distance + radius < cam.far);
+    }
+
+    /// NDC x and y of a world point.
+    fn to_ndc(cam: &Camera, p: Point3) -> (f64, f64) {
+        let vp = cam.view_projection();
+        let clip: Vec<f64> = (0..4)
+            .map(|i| vp[i][0] * p.x + vp[i][1] * p.y + vp[i][2] * p.z + vp[i][3])
+            .collect();
+        (clip[0] / clip[3], clip[1] / clip[3])
+    }
+
+    /// Largest |NDC x| and |NDC y| over the corners of a box.
+    fn screen_extent(cam: &Camera, aabb: &Aabb3) -> (f64, f64) {
+        (0..8)
+            .map(|i| {
+                let corner = Point3::new(
+                    if i & 1 == 0 { aabb.min.x } else { aabb.max.x },
+                    if i & 2 == 0 { aabb.min.y } else { aabb.max.y },
+                    if i & 4 == 0 { aabb.min.z } else { aabb.max.z },
+                );
+                to_ndc(cam, corner)
+            })
+            .fold((0.0, 0.0), |(mx, my), (x, y)| (mx.max(x.abs()), my.max(y.abs())))
+    }
+
+    #[test]
+    fn test_fit_to_aabb_respects_both_axes() {
+        // A tower on a wide screen is limited by its height, a long slab
+        // on a narrow one by its width.
+        let tower = Aabb3::new(Point3::new(-1.0, 0.0, -1.0), Point3::new(1.0, 40.0, 1.0));
+        let slab = Aabb3::new(Point3::new(-30.0, 0.0, -1.0), Point3::new(30.0, 1.0, 1.0));
+        for projection in [Projection::Perspective, Projection::Orthographic { height: 1.0 }] {
+            for (aabb, aspect) in [(&tower, 16.0 / 9.0), (&slab, 0.5)] {
+                let mut cam = Camera {
+                    eye: Point3::new(3.0, 2.0, 5.0),
+                    aspect,
+                    projection,
+                    ..Default::default()
+                };
+                let view_dir = (cam.target - cam.eye).normalize();
+                cam.fit_to_aabb(aabb);
+
+                assert_eq!(cam.target, aabb.center());
+                assert!(((cam.target - cam.eye).normalize() - view_dir).length() < 1e-10);
+                let (x, y) = screen_extent(&cam, aabb);
+                assert!(x <= 1.0 + 1e-9 && y <= 1.0 + 1e-9);
+                // Tight along the limiting axis
+                let fill = x.max(y);
+                assert!((fill - 1.0 / (1.0 + DEFAULT_FIT_PADDING)).abs() < 1e-6, "{fill}");
+                assert!((cam.eye - cam.target).length() < cam.far);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fit_to_selection() {
+        let mut scene = Scene::new();
+        scene.add_mesh("Near", crate::test_util::create_test_triangle(), [0.5; 3]);
+        let mut far = crate::test_util::create_test_triangle();
+        for p in &mut far.positions {
+            p.x += 100.0;
+        }
+        scene.add_mesh("Far", far, [0.5; 3]);
+
+        let mut cam = Camera::default();
+        assert!(cam.fit_to_selection(&scene, &[PickTarget::Mesh(1)]));
+        assert_eq!(cam.target, Point3::new(100.5, 0.5, 0.0));
+        assert!((cam.eye - cam.target).length() < 5.0);
+
+        let before = cam.eye;
+        assert!(!cam.fit_to_selection(&scene, &[PickTarget::Mesh(7)]));
+        assert_eq!(cam.eye, before);
+    }
+
+    #[test]
+    fn test_standard_views() {
+        let bounds = Aabb3::new(Point3::new(0.0, 0.0, 0.0), Point3::new(20.0, 6.0, 10.0));
+        for view in StandardView::ALL {
+            assert_eq!(StandardView::from_key(view.key()), Some(view));
+            let cam = Camera::standard_view(view, &bounds, 1.5);
+            assert_eq!(cam.target, bounds.center());
+            let back = (cam.eye - cam.target).normalize();
+            assert!((back - view.direction()).length() < 1e-10, "{}", view.name());
+            let (x, y) = screen_extent(&cam, &bounds);
+            assert!(x <= 1.0 + 1e-9 && y <= 1.0 + 1e-9, "{}", view.name());
+        }
+        assert_eq!(StandardView::from_key("ISO-NE"), Some(StandardView::IsoNorthEast));
+        assert_eq!(StandardView::from_key("diagonal"), None);
+
+        // The plan shows north (-Z) at the top and east (+X) on the right
+        let top = Camera::standard_view(StandardView::Top, &bounds, 1.0);
+        let (_, north) = to_ndc(&top, Point3::new(10.0, 3.0, 0.0));
+        let (east, _) = to_ndc(&top, Point3::new(20.0, 3.0, 5.0));
+        assert!(north > 0.0 && east > 0.0);
+
+        // An orthographic camera stays orthographic; on a wide screen the
+        // elevation is limited by its height
+        let mut cam = Camera {
+            aspect: 4.0,
+            projection: Projection::Orthographic { height: 1.0 },
+            ..Default::default()
+        };
+        cam.set_standard_view(StandardView::Front, &bounds);
+        let Projection::Orthographic { height } = cam.projection else {
+            unreachable!()
+        };
+        assert!((height - 6.0 * (1.0 + DEFAULT_FIT_PADDING)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_screen_space_error() {
+        let mut cam = Camera::default();
+        let error = cam.screen_space_error(1080.0, 0.5);
+        assert_eq!(error.eye, cam.eye);
+        assert!(!error.orthographic);
+
+        // A point at the target projects to NDC 0; one a unit above it lands
+        // as many pixels up as the error predicts
+        let (_, y) = to_ndc(&cam, Point3::new(0.0, 1.0, 0.0));
+        let pixels = y * 1080.0 / 2.0;
+        assert!((error.pixels(1.0, cam.target) - pixels).abs() < 1e-9);
+
+        cam.set_orthographic();
+        let error = cam.screen_space_error(1080.0, 0.5);
+        assert!(error.orthographic);
+        let (_, y) = to_ndc(&cam, Point3::new(0.0, 1.0, -50.0));
+        assert!((error.pixels(1.0, Point3::new(0.0, 1.0, -50.0)) - y * 540.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_camera_view_round_trip() {
+        let mut cam = Camera {
+            eye: Point3::new(10.0, 5.0, -3.0),
+            target: Point3::new(1.0, 2.0, 3.0),
+            aspect: 1.5,
+            ..Default::default()
+        };
+        cam.set_orthographic();
+        let view = CameraView::from_camera("North elevation", &cam);
+
+        let path = std::env::temp_dir().join("test_camera_views.json");
+        save_views(&path, std::slice::from_ref(&view)).unwrap();
+        let loaded = load_views(&path).unwrap();
+        assert_eq!(loaded, vec![view]);
+
+        let mut other = Camera::default();
+        loaded[0].apply(&mut other);
+        assert_eq!(other.eye, cam.eye);
+        assert_eq!(other.target, cam.target);
+        assert_eq!(other.projection, cam.projection);
+        assert_eq!(other.aspect, Camera::default().aspect);
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(matches!(load_views(&path), Err(CstError::Parse(_))));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_camera_view_interpolate() {
+        let front = CameraView::from_camera("front", &Camera::default());
+        let side = CameraView {
+            name: "side".to_string(),
+            eye: Point3::new(10.0, 0.0, 0.0),
+            ..front.clone()
+        };
+
+        let start = front.interpolate(&side, 0.0);
+        assert!((start.eye - front.eye).length() < 1e-10);
+        assert!((start.up - Vector3::Y).length() < 1e-10);
+
+        // Halfway: on the orbit between the two, not on the straight line
+        let mid = front.interpolate(&side, 0.5);
+        assert!((mid.target - Point3::ZERO).length() < 1e-10);
+        assert!((mid.eye.length() - 7.5).abs() < 1e-10);
+        assert!((mid.eye.x - mid.eye.z).abs() < 1e-10);
+        assert_eq!(mid.name, "side");
+
+        let end = front.interpolate(&side, 1.0);
+        assert!((end.eye - side.eye).length() < 1e-10);
+    }
 }
@@ -0,0 +1,814 @@
+//! glTF 2.0 export of a [`Scene`], as JSON with an embedded buffer or as
+//! a binary GLB container.
+//!
+//! Meshes keep their node hierarchy and materials; the vertex buffers can
+//! be compressed with `EXT_meshopt_compression`. Each instanced group is
+//! written once as a glTF mesh shared by one node per instance, whose
+//! matrix is the instance transform.
+
+use std::path::Path;
+
+use cst_math::{Aabb3, DMat3, DMat4, Point3};
+
+use crate::material::Material;
+use crate::meshopt;
+use crate::scene::{js_string, Scene, SceneMesh};
+
+/// Options for [`Scene::export_gltf_json_with_options`] and
+/// [`Scene::export_glb_with_options`]
+#[derive(Debug, Clone, Default)]
+pub struct GltfExportOptions {
+    /// Compress vertex and index data with `EXT_meshopt_compression`.
+    /// The extension is then required, so loaders must support it
+    /// (three.js needs `setMeshoptDecoder`).
+    pub meshopt_compression: bool,
+}
+
+/// A buffer view over the uncompressed glTF binary data
+struct GltfView {
+    offset: usize,
+    length: usize,
+    /// Element size, used as the meshopt vertex size
+    stride: usize,
+    target: u32,
+}
+
+/// Binary data of a glTF export
+struct GltfPayload {
+    /// Contents of buffer 0
+    data: Vec<u8>,
+    views: Vec<GltfView>,
+    /// Location (offset, length) of each view's meshopt stream in `data`.
+    /// When set, the views themselves refer to an empty fallback buffer.
+    compressed: Option<Vec<(usize, usize)>>,
+}
+
+impl Scene {
+    /// Transform taking a mesh's world coordinates into the frame of its
+    /// node, or None when no change is needed
+    fn mesh_export_transform(&self, mesh: usize) -> Option<DMat4> {
+        let world = self.node_world_transform(self.mesh_node(mesh)?);
+        (world != DMat4::IDENTITY).then(|| world.inverse())
+    }
+
+    /// Export scene as glTF JSON file
+    pub fn export_gltf_json(&self) -> String {
+        self.export_gltf_json_with_options(&GltfExportOptions::default())
+    }
+
+    /// Export scene as glTF JSON using the given options
+    pub fn export_gltf_json_with_options(&self, options: &GltfExportOptions) -> String {
+        self.gltf_json(&self.gltf_payload(options), true)
+    }
+
+    /// Export scene as a binary glTF (GLB) container
+    pub fn export_glb(&self, path: &Path) -> std::io::Result<()> {
+        self.export_glb_with_options(path, &GltfExportOptions::default())
+    }
+
+    /// Export scene as GLB using the given options
+    pub fn export_glb_with_options(&self, path: &Path, options: &GltfExportOptions) -> std::io::Result<()> {
+        std::fs::write(path, self.to_glb_with_options(options))
+    }
+
+    /// Encode the scene as GLB: a 12-byte header, then a JSON chunk and a
+    /// binary chunk, each padded to 4 bytes
+    pub fn to_glb(&self) -> Vec<u8> {
+        self.to_glb_with_options(&GltfExportOptions::default())
+    }
+
+    /// Encode the scene as GLB using the given options
+    pub fn to_glb_with_options(&self, options: &GltfExportOptions) -> Vec<u8> {
+        const MAGIC: u32 = 0x4654_6C67; // "glTF"
+        const CHUNK_JSON: u32 = 0x4E4F_534A; // "JSON"
+        const CHUNK_BIN: u32 = 0x004E_4942; // "BIN\0"
+
+        let payload = self.gltf_payload(options);
+        let mut json = self.gltf_json(&payload, false).into_bytes();
+        while json.len() % 4 != 0 {
+            json.push(b' ');
+        }
+        let mut bin = payload.data;
+        while bin.len() % 4 != 0 {
+            bin.push(0);
+        }
+
+        let total = 12 + 8 + json.len() + 8 + bin.len();
+        let mut glb = Vec::with_capacity(total);
+        glb.extend_from_slice(&MAGIC.to_le_bytes());
+        glb.extend_from_slice(&2u32.to_le_bytes());
+        glb.extend_from_slice(&(total as u32).to_le_bytes());
+        glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        glb.extend_from_slice(&CHUNK_JSON.to_le_bytes());
+        glb.extend_from_slice(&json);
+        glb.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+        glb.extend_from_slice(&CHUNK_BIN.to_le_bytes());
+        glb.extend_from_slice(&bin);
+        glb
+    }
+
+    /// Buffer views of the binary data: position, normal and index views
+    /// per mesh, then the UV views of textured meshes, then position,
+    /// normal and index views per instanced group
+    fn gltf_views(&self) -> Vec<GltfView> {
+        let mut views = Vec::with_capacity(self.meshes.len() * 3);
+        let mut offset = 0;
+        let mut push = |length: usize, stride: usize, target: u32| {
+            views.push(GltfView { offset, length, stride, target });
+            offset += length;
+        };
+        for scene_mesh in &self.meshes {
+            push(scene_mesh.mesh.positions.len() * 12, 12, 34962);
+            push(scene_mesh.mesh.normals.len() * 12, 12, 34962);
+            push(scene_mesh.mesh.indices.len() * 4, 4, 34963);
+        }
+        for i in self.gltf_textured_meshes() {
+            push(self.meshes[i].mesh.uvs.len() * 8, 8, 34962);
+        }
+        for ig in &self.instanced_groups {
+            push(ig.mesh.positions.len() * 12, 12, 34962);
+            push(ig.mesh.normals.len() * 12, 12, 34962);
+            push(ig.mesh.indices.len() * 4, 4, 34963);
+        }
+        views
+    }
+
+    /// Binary data for a glTF export, compressed per view when requested.
+    ///
+    /// Every view, index data included, uses the meshopt `ATTRIBUTES` mode.
+    fn gltf_payload(&self, options: &GltfExportOptions) -> GltfPayload {
+        let raw = self.generate_gltf_binary_buffer();
+        let views = self.gltf_views();
+        if !options.meshopt_compression {
+            return GltfPayload { data: raw, views, compressed: None };
+        }
+
+        let mut data = Vec::new();
+        let mut compressed = Vec::with_capacity(views.len());
+        for view in &views {
+            let stream = meshopt::encode_vertex_buffer(
+                &raw[view.offset..view.offset + view.length],
+                view.stride,
+            );
+            compressed.push((data.len(), stream.len()));
+            data.extend_from_slice(&stream);
+            while data.len() % 4 != 0 {
+                data.push(0);
+            }
+        }
+        GltfPayload { data, views, compressed: Some(compressed) }
+    }
+
+    /// glTF JSON document. With `embed` the binary data is written as a
+    /// base64 data URI; without, it refers to the GLB binary chunk.
+    fn gltf_json(&self, payload: &GltfPayload, embed: bool) -> String {
+        use std::fmt::Write as FmtWrite;
+
+        let mut json = String::new();
+
+        // Textured meshes get an extra TEXCOORD_0 accessor after the
+        // position/normal/index accessors of all meshes
+        let textured = self.gltf_textured_meshes();
+        let texcoord_accessor = |i: usize| {
+            textured
+                .iter()
+                .position(|&t| t == i)
+                .map(|k| self.meshes.len() * 3 + k)
+        };
+        // Instanced groups come last: glTF mesh and material `meshes + g`,
+        // accessors from `group_accessor(g)` on
+        let groups = &self.instanced_groups;
+        let group_accessor = |g: usize| self.meshes.len() * 3 + textured.len() + g * 3;
+
+        // Start JSON
+        writeln!(json, "{{").unwrap();
+        writeln!(json, "  \"asset\": {{").unwrap();
+        writeln!(json, "    \"version\": \"2.0\",").unwrap();
+        writeln!(json, "    \"generator\": \"CSTEngine\"").unwrap();
+        writeln!(json, "  }},").unwrap();
+        if payload.compressed.is_some() {
+            writeln!(json, "  \"extensionsUsed\": [\"EXT_meshopt_compression\"],").unwrap();
+            writeln!(json, "  \"extensionsRequired\": [\"EXT_meshopt_compression\"],").unwrap();
+        }
+
+        // Scene: root graph nodes plus meshes outside the graph. glTF node
+        // `i` holds mesh `i`; graph node `j` becomes glTF node `meshes + j`;
+        // the instances of all groups follow as further roots.
+        let graph_node = |j: usize| self.meshes.len() + j;
+        let instances: Vec<(usize, usize)> = groups
+            .iter()
+            .enumerate()
+            .flat_map(|(g, ig)| (0..ig.transforms.len()).map(move |k| (g, k)))
+            .collect();
+        let mut roots: Vec<usize> = (0..self.meshes.len())
+            .filter(|&i| self.mesh_node(i).is_none())
+            .collect();
+        roots.extend(self.root_nodes().into_iter().map(graph_node));
+        let first_instance = graph_node(self.nodes.len());
+        roots.extend(first_instance..first_instance + instances.len());
+        writeln!(json, "  \"scene\": 0,").unwrap();
+        writeln!(json, "  \"scenes\": [{{").unwrap();
+        write!(json, "    \"nodes\": [").unwrap();
+        for (k, node) in roots.iter().enumerate() {
+            if k > 0 { write!(json, ", ").unwrap(); }
+            write!(json, "{}", node).unwrap();
+        }
+        writeln!(json, "]").unwrap();
+        writeln!(json, "  }}],").unwrap();
+
+        // Nodes
+        writeln!(json, "  \"nodes\": [").unwrap();
+        for (i, scene_mesh) in self.meshes.iter().enumerate() {
+            writeln!(json, "    {{").unwrap();
+            writeln!(json, "      \"name\": \"{}\",", scene_mesh.name).unwrap();
+            writeln!(json, "      \"mesh\": {}", i).unwrap();
+            write!(json, "    }}").unwrap();
+            if i < self.meshes.len() - 1 || !self.nodes.is_empty() || !instances.is_empty() {
+                writeln!(json, ",").unwrap();
+            } else {
+                writeln!(json).unwrap();
+            }
+        }
+        for (j, node) in self.nodes.iter().enumerate() {
+            writeln!(json, "    {{").unwrap();
+            write!(json, "      \"name\": {}", js_string(&node.name)).unwrap();
+            if node.transform != DMat4::IDENTITY {
+                write!(json, ",\n      \"matrix\": {:?}", node.transform.to_cols_array()).unwrap();
+            }
+            let children: Vec<usize> = node
+                .children
+                .iter()
+                .map(|&c| graph_node(c))
+                .chain(node.meshes.iter().copied())
+                .collect();
+            if !children.is_empty() {
+                write!(json, ",\n      \"children\": {:?}", children).unwrap();
+            }
+            writeln!(json).unwrap();
+            write!(json, "    }}").unwrap();
+            if j < self.nodes.len() - 1 || !instances.is_empty() {
+                writeln!(json, ",").unwrap();
+            } else {
+                writeln!(json).unwrap();
+            }
+        }
+        for (k, &(g, instance)) in instances.iter().enumerate() {
+            let matrix = groups[g].transforms[instance].map(f64::from);
+            writeln!(json, "    {{").unwrap();
+            writeln!(json, "      \"name\": {},", js_string(&format!("{}_{}", groups[g].name, instance))).unwrap();
+            writeln!(json, "      \"mesh\": {},", self.meshes.len() + g).unwrap();
+            writeln!(json, "      \"matrix\": {:?}", matrix).unwrap();
+            write!(json, "    }}").unwrap();
+            if k + 1 < instances.len() {
+                writeln!(json, ",").unwrap();
+            } else {
+                writeln!(json).unwrap();
+            }
+        }
+        writeln!(json, "  ],").unwrap();
+
+        // Meshes
+        writeln!(json, "  \"meshes\": [").unwrap();
+        for (i, scene_mesh) in self.meshes.iter().enumerate() {
+            writeln!(json, "    {{").unwrap();
+            writeln!(json, "      \"name\": \"{}\",", scene_mesh.name).unwrap();
+            writeln!(json, "      \"primitives\": [{{").unwrap();
+            writeln!(json, "        \"attributes\": {{").unwrap();
+            writeln!(json, "          \"POSITION\": {},", i * 3).unwrap();
+            write!(json, "          \"NORMAL\": {}", i * 3 + 1).unwrap();
+            if let Some(accessor) = texcoord_accessor(i) {
+                write!(json, ",\n          \"TEXCOORD_0\": {}", accessor).unwrap();
+            }
+            writeln!(json).unwrap();
+            writeln!(json, "        }},").unwrap();
+            writeln!(json, "        \"indices\": {},", i * 3 + 2).unwrap();
+            writeln!(json, "        \"material\": {}", i).unwrap();
+            writeln!(json, "      }}]").unwrap();
+            write!(json, "    }}").unwrap();
+            if i < self.meshes.len() - 1 || !groups.is_empty() {
+                writeln!(json, ",").unwrap();
+            } else {
+                writeln!(json).unwrap();
+            }
+        }
+        for (g, ig) in groups.iter().enumerate() {
+            let accessor = group_accessor(g);
+            writeln!(json, "    {{").unwrap();
+            writeln!(json, "      \"name\": {},", js_string(&ig.name)).unwrap();
+            writeln!(json, "      \"primitives\": [{{").unwrap();
+            writeln!(json, "        \"attributes\": {{").unwrap();
+            writeln!(json, "          \"POSITION\": {},", accessor).unwrap();
+            writeln!(json, "          \"NORMAL\": {}", accessor + 1).unwrap();
+            writeln!(json, "        }},").unwrap();
+            writeln!(json, "        \"indices\": {},", accessor + 2).unwrap();
+            writeln!(json, "        \"material\": {}", self.meshes.len() + g).unwrap();
+            writeln!(json, "      }}]").unwrap();
+            write!(json, "    }}").unwrap();
+            if g + 1 < groups.len() {
+                writeln!(json, ",").unwrap();
+            } else {
+                writeln!(json).unwrap();
+            }
+        }
+        writeln!(json, "  ],").unwrap();
+
+        // Materials, of the meshes then of the instanced groups
+        writeln!(json, "  \"materials\": [").unwrap();
+        let materials: Vec<(&str, &Material)> = self
+            .meshes
+            .iter()
+            .map(|sm| (sm.name.as_str(), &sm.material))
+            .chain(groups.iter().map(|ig| (ig.name.as_str(), &ig.material)))
+            .collect();
+        for (i, &(name, material)) in materials.iter().enumerate() {
+            writeln!(json, "    {{").unwrap();
+            writeln!(json, "      \"name\": \"{}_Material\",", name).unwrap();
+            let [r, g, b, a] = material.base_color_rgba();
+            writeln!(json, "      \"pbrMetallicRoughness\": {{").unwrap();
+            writeln!(json, "        \"baseColorFactor\": [{}, {}, {}, {}],", r, g, b, a).unwrap();
+            if let Some(k) = textured.iter().position(|&t| t == i) {
+                writeln!(json, "        \"baseColorTexture\": {{ \"index\": {} }},", k).unwrap();
+            }
+            writeln!(json, "        \"metallicFactor\": {},", material.metallic).unwrap();
+            writeln!(json, "        \"roughnessFactor\": {}", material.roughness).unwrap();
+            writeln!(json, "      }},").unwrap();
+            if material.is_transparent() {
+                writeln!(json, "      \"alphaMode\": \"BLEND\",").unwrap();
+            }
+            writeln!(json, "      \"doubleSided\": {}", material.double_sided).unwrap();
+            write!(json, "    }}").unwrap();
+            if i + 1 < materials.len() {
+                writeln!(json, ",").unwrap();
+            } else {
+                writeln!(json).unwrap();
+            }
+        }
+        writeln!(json, "  ],").unwrap();
+
+        // Accessors
+        writeln!(json, "  \"accessors\": [").unwrap();
+        let mut accessor_idx = 0;
+        for (i, scene_mesh) in self.meshes.iter().enumerate() {
+            let vertex_count = scene_mesh.mesh.positions.len();
+            let index_count = scene_mesh.mesh.indices.len();
+
+            // Position accessor
+            let bounds = match self.mesh_export_transform(i) {
+                Some(transform) => {
+                    let local: Vec<Point3> = scene_mesh
+                        .mesh
+                        .positions
+                        .iter()
+                        .map(|&p| transform.transform_point3(p))
+                        .collect();
+                    Aabb3::from_points(&local).unwrap_or_else(|| self.compute_mesh_bounds(scene_mesh))
+                }
+                None => self.compute_mesh_bounds(scene_mesh),
+            };
+            writeln!(json, "    {{").unwrap();
+            writeln!(json, "      \"bufferView\": {},", accessor_idx).unwrap();
+            writeln!(json, "      \"componentType\": 5126,").unwrap();
+            writeln!(json, "      \"count\": {},", vertex_count).unwrap();
+            writeln!(json, "      \"type\": \"VEC3\",").unwrap();
+            writeln!(json, "      \"max\": [{}, {}, {}],", bounds.max.x, bounds.max.y, bounds.max.z).unwrap();
+            writeln!(json, "      \"min\": [{}, {}, {}]", bounds.min.x, bounds.min.y, bounds.min.z).unwrap();
+            writeln!(json, "    }},").unwrap();
+
+            // Normal accessor
+            writeln!(json, "    {{").unwrap();
+            writeln!(json, "      \"bufferView\": {},", accessor_idx + 1).unwrap();
+            writeln!(json, "      \"componentType\": 5126,").unwrap();
+            writeln!(json, "      \"count\": {},", vertex_count).unwrap();
+            writeln!(json, "      \"type\": \"VEC3\"").unwrap();
+            writeln!(json, "    }},").unwrap();
+
+            // Index accessor
+            writeln!(json, "    {{").unwrap();
+            writeln!(json, "      \"bufferView\": {},", accessor_idx + 2).unwrap();
+            writeln!(json, "      \"componentType\": 5125,").unwrap();
+            writeln!(json, "      \"count\": {},", index_count).unwrap();
+            writeln!(json, "      \"type\": \"SCALAR\"").unwrap();
+            write!(json, "    }}").unwrap();
+
+            accessor_idx += 3;
+            if accessor_idx < self.meshes.len() * 3 || !textured.is_empty() || !groups.is_empty() {
+                writeln!(json, ",").unwrap();
+            } else {
+                writeln!(json).unwrap();
+            }
+        }
+        for (k, &i) in textured.iter().enumerate() {
+            writeln!(json, "    {{").unwrap();
+            writeln!(json, "      \"bufferView\": {},", self.meshes.len() * 3 + k).unwrap();
+            writeln!(json, "      \"componentType\": 5126,").unwrap();
+            writeln!(json, "      \"count\": {},", self.meshes[i].mesh.uvs.len()).unwrap();
+            writeln!(json, "      \"type\": \"VEC2\"").unwrap();
+            write!(json, "    }}").unwrap();
+            if k + 1 < textured.len() || !groups.is_empty() {
+                writeln!(json, ",").unwrap();
+            } else {
+                writeln!(json).unwrap();
+            }
+        }
+        for (g, ig) in groups.iter().enumerate() {
+            let accessor = group_accessor(g);
+            let vertex_count = ig.mesh.positions.len();
+            let bounds = ig.bounds().unwrap_or_else(|| Aabb3::new(Point3::ZERO, Point3::ONE));
+            writeln!(json, "    {{").unwrap();
+            writeln!(json, "      \"bufferView\": {},", accessor).unwrap();
+            writeln!(json, "      \"componentType\": 5126,").unwrap();
+            writeln!(json, "      \"count\": {},", vertex_count).unwrap();
+            writeln!(json, "      \"type\": \"VEC3\",").unwrap();
+            writeln!(json, "      \"max\": [{}, {}, {}],", bounds.max.x, bounds.max.y, bounds.max.z).unwrap();
+            writeln!(json, "      \"min\": [{}, {}, {}]", bounds.min.x, bounds.min.y, bounds.min.z).unwrap();
+            writeln!(json, "    }},").unwrap();
+            writeln!(json, "    {{").unwrap();
+            writeln!(json, "      \"bufferView\": {},", accessor + 1).unwrap();
+            writeln!(json, "      \"componentType\": 5126,").unwrap();
+            writeln!(json, "      \"count\": {},", vertex_count).unwrap();
+            writeln!(json, "      \"type\": \"VEC3\"").unwrap();
+            writeln!(json, "    }},").unwrap();
+            writeln!(json, "    {{").unwrap();
+            writeln!(json, "      \"bufferView\": {},", accessor + 2).unwrap();
+            writeln!(json, "      \"componentType\": 5125,").unwrap();
+            writeln!(json, "      \"count\": {},", ig.mesh.indices.len()).unwrap();
+            writeln!(json, "      \"type\": \"SCALAR\"").unwrap();
+            write!(json, "    }}").unwrap();
+            if g + 1 < groups.len() {
+                writeln!(json, ",").unwrap();
+            } else {
+                writeln!(json).unwrap();
+            }
+        }
+        writeln!(json, "  ],").unwrap();
+
+        // BufferViews. Compressed views keep their uncompressed layout in
+        // the fallback buffer 1 and locate the meshopt stream in buffer 0.
+        writeln!(json, "  \"bufferViews\": [").unwrap();
+        for (v, view) in payload.views.iter().enumerate() {
+            writeln!(json, "    {{").unwrap();
+            writeln!(json, "      \"buffer\": {},", payload.compressed.is_some() as usize).unwrap();
+            writeln!(json, "      \"byteOffset\": {},", view.offset).unwrap();
+            writeln!(json, "      \"byteLength\": {},", view.length).unwrap();
+            if let Some(compressed) = &payload.compressed {
+                let (offset, length) = compressed[v];
+                if view.target == 34962 {
+                    writeln!(json, "      \"byteStride\": {},", view.stride).unwrap();
+                }
+                writeln!(json, "      \"extensions\": {{ \"EXT_meshopt_compression\": {{").unwrap();
+                writeln!(json, "        \"buffer\": 0, \"byteOffset\": {}, \"byteLength\": {},", offset, length).unwrap();
+                writeln!(json, "        \"byteStride\": {}, \"count\": {}, \"mode\": \"ATTRIBUTES\"",
+                    view.stride, view.length / view.stride).unwrap();
+                writeln!(json, "      }} }},").unwrap();
+            }
+            writeln!(json, "      \"target\": {}", view.target).unwrap();
+            write!(json, "    }}").unwrap();
+            if v + 1 < payload.views.len() {
+                writeln!(json, ",").unwrap();
+            } else {
+                writeln!(json).unwrap();
+            }
+        }
+        writeln!(json, "  ],").unwrap();
+
+        // One texture per textured material, referencing its image by URI
+        if !textured.is_empty() {
+            writeln!(json, "  \"samplers\": [{{}}],").unwrap();
+            write!(json, "  \"textures\": [").unwrap();
+            for k in 0..textured.len() {
+                if k > 0 { write!(json, ", ").unwrap(); }
+                write!(json, "{{ \"sampler\": 0, \"source\": {} }}", k).unwrap();
+            }
+            writeln!(json, "],").unwrap();
+            write!(json, "  \"images\": [").unwrap();
+            for (k, &i) in textured.iter().enumerate() {
+                if k > 0 { write!(json, ", ").unwrap(); }
+                let uri = self.meshes[i].material.texture.as_deref().unwrap_or_default();
+                write!(json, "{{ \"uri\": {} }}", js_string(uri)).unwrap();
+            }
+            writeln!(json, "],").unwrap();
+        }
+
+        // Buffer (base64 encoded binary data, or the GLB binary chunk)
+        writeln!(json, "  \"buffers\": [{{").unwrap();
+        if embed {
+            writeln!(json, "    \"byteLength\": {},", payload.data.len()).unwrap();
+            write!(json, "    \"uri\": \"data:application/octet-stream;base64,").unwrap();
+            write!(json, "{}\"", base64_encode(&payload.data)).unwrap();
+            writeln!(json).unwrap();
+        } else {
+            writeln!(json, "    \"byteLength\": {}", payload.data.len()).unwrap();
+        }
+        if payload.compressed.is_some() {
+            // Fallback buffer without data: the extension is required
+            let raw_length = payload.views.last().map_or(0, |view| view.offset + view.length);
+            writeln!(json, "  }}, {{").unwrap();
+            writeln!(json, "    \"byteLength\": {},", raw_length).unwrap();
+            writeln!(json, "    \"extensions\": {{ \"EXT_meshopt_compression\": {{ \"fallback\": true }} }}").unwrap();
+        }
+        writeln!(json, "  }}]").unwrap();
+
+        writeln!(json, "}}").unwrap();
+
+        json
+    }
+
+    /// Meshes exported with a base color texture: a texture URI and one UV
+    /// per vertex are both required
+    fn gltf_textured_meshes(&self) -> Vec<usize> {
+        self.meshes
+            .iter()
+            .enumerate()
+            .filter(|(_, sm)| {
+                sm.material.texture.is_some() && sm.mesh.uvs.len() == sm.mesh.positions.len()
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    fn compute_mesh_bounds(&self, scene_mesh: &SceneMesh) -> Aabb3 {
+        scene_mesh.bounds().unwrap_or_else(|| {
+            use cst_math::{Point3, DVec3};
+            Aabb3::new(Point3::ZERO, DVec3::splat(1.0))
+        })
+    }
+
+    fn generate_gltf_binary_buffer(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+
+        for (i, scene_mesh) in self.meshes.iter().enumerate() {
+            // Meshes under a graph node are written in that node's frame
+            let transform = self.mesh_export_transform(i).unwrap_or(DMat4::IDENTITY);
+            let normal_matrix = DMat3::from_mat4(transform).inverse().transpose();
+
+            // Write positions
+            for &p in &scene_mesh.mesh.positions {
+                let pos = transform.transform_point3(p);
+                buffer.extend_from_slice(&(pos.x as f32).to_le_bytes());
+                buffer.extend_from_slice(&(pos.y as f32).to_le_bytes());
+                buffer.extend_from_slice(&(pos.z as f32).to_le_bytes());
+            }
+
+            // Write normals
+            for &n in &scene_mesh.mesh.normals {
+                let norm = (normal_matrix * n).normalize_or_zero();
+                buffer.extend_from_slice(&(norm.x as f32).to_le_bytes());
+                buffer.extend_from_slice(&(norm.y as f32).to_le_bytes());
+                buffer.extend_from_slice(&(norm.z as f32).to_le_bytes());
+            }
+
+            // Write indices
+            for idx in &scene_mesh.mesh.indices {
+                buffer.extend_from_slice(&idx.to_le_bytes());
+            }
+        }
+
+        // Texture coordinates of textured meshes, after the meshes
+        for i in self.gltf_textured_meshes() {
+            for uv in &self.meshes[i].mesh.uvs {
+                buffer.extend_from_slice(&(uv.x as f32).to_le_bytes());
+                buffer.extend_from_slice(&(uv.y as f32).to_le_bytes());
+            }
+        }
+
+        // Base geometry of instanced groups, in its own frame
+        for ig in &self.instanced_groups {
+            for p in &ig.mesh.positions {
+                buffer.extend_from_slice(&(p.x as f32).to_le_bytes());
+                buffer.extend_from_slice(&(p.y as f32).to_le_bytes());
+                buffer.extend_from_slice(&(p.z as f32).to_le_bytes());
+            }
+            for n in &ig.mesh.normals {
+                let norm = n.normalize_or_zero();
+                buffer.extend_from_slice(&(norm.x as f32).to_le_bytes());
+                buffer.extend_from_slice(&(norm.y as f32).to_le_bytes());
+                buffer.extend_from_slice(&(norm.z as f32).to_le_bytes());
+            }
+            for idx in &ig.mesh.indices {
+                buffer.extend_from_slice(&idx.to_le_bytes());
+            }
+        }
+
+        buffer
+    }
+}
+
+// Simple base64 encoder
+fn base64_encode(data: &[u8]) -> String {
+    const CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut result = String::new();
+
+    let mut i = 0;
+    while i < data.len() {
+        let b1 = data[i];
+        let b2 = if i + 1 < data.len() { data[i + 1] } else { 0 };
+        let b3 = if i + 2 < data.len() { data[i + 2] } else { 0 };
+
+        result.push(CHARS[(b1 >> 2) as usize] as char);
+        result.push(CHARS[(((b1 & 0x03) << 4) | (b2 >> 4)) as usize] as char);
+
+        if i + 1 < data.len() {
+            result.push(CHARS[(((b2 & 0x0F) << 2) | (b3 >> 6)) as usize] as char);
+        } else {
+            result.push('=');
+        }
+
+        if i + 2 < data.len() {
+            result.push(CHARS[(b3 & 0x3F) as usize] as char);
+        } else {
+            result.push('=');
+        }
+
+        i += 3;
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::create_test_triangle;
+    use cst_math::DVec3;
+
+    #[test]
+    fn test_gltf_json_valid() {
+        let mut scene = Scene::new();
+        let mesh = create_test_triangle();
+        scene.add_mesh("TestMesh", mesh, [0.8, 0.2, 0.3]);
+
+        let json = scene.export_gltf_json();
+
+        // Check JSON is valid by parsing it
+        let parsed: Result<serde_json::Value, _> = serde_json::from_str(&json);
+        assert!(parsed.is_ok(), "Generated glTF JSON should be valid");
+
+        let gltf = parsed.unwrap();
+
+        // Check key fields
+        assert!(gltf["asset"]["version"].as_str().unwrap() == "2.0");
+        assert!(gltf["scenes"].is_array());
+        assert!(gltf["nodes"].is_array());
+        assert!(gltf["meshes"].is_array());
+        assert!(gltf["materials"].is_array());
+        assert!(gltf["accessors"].is_array());
+        assert!(gltf["bufferViews"].is_array());
+        assert!(gltf["buffers"].is_array());
+    }
+
+    #[test]
+    fn test_gltf_materials() {
+        let mut scene = Scene::new();
+        scene.add_mesh("Plain", create_test_triangle(), [0.8, 0.2, 0.3]);
+        let mut textured = create_test_triangle();
+        textured.uvs = vec![
+            cst_math::Point2::new(0.0, 0.0),
+            cst_math::Point2::new(1.0, 0.0),
+            cst_math::Point2::new(0.0, 1.0),
+        ];
+        let glass = Material {
+            texture: Some("brick.png".into()),
+            double_sided: false,
+            ..Material::from_color([0.6, 0.8, 0.9]).with_alpha(0.5)
+        };
+        scene.add_mesh("Textured", textured, glass);
+
+        let gltf: serde_json::Value = serde_json::from_str(&scene.export_gltf_json()).unwrap();
+        let materials = gltf["materials"].as_array().unwrap();
+        assert_eq!(materials[0]["alphaMode"], serde_json::Value::Null);
+        assert_eq!(materials[1]["alphaMode"], "BLEND");
+        assert_eq!(materials[1]["doubleSided"], false);
+        assert_eq!(materials[1]["pbrMetallicRoughness"]["baseColorFactor"][3], 0.5);
+        assert_eq!(materials[1]["pbrMetallicRoughness"]["baseColorTexture"]["index"], 0);
+        assert_eq!(gltf["images"][0]["uri"], "brick.png");
+
+        // TEXCOORD_0 follows the three accessors per mesh
+        let attributes = &gltf["meshes"][1]["primitives"][0]["attributes"];
+        assert_eq!(attributes["TEXCOORD_0"], 6);
+        assert!(gltf["meshes"][0]["primitives"][0]["attributes"]["TEXCOORD_0"].is_null());
+        assert_eq!(gltf["accessors"][6]["type"], "VEC2");
+        assert_eq!(gltf["bufferViews"].as_array().unwrap().len(), 7);
+        let buffer_len = gltf["buffers"][0]["byteLength"].as_u64().unwrap() as usize;
+        assert_eq!(buffer_len, scene.generate_gltf_binary_buffer().len());
+    }
+
+    #[test]
+    fn test_gltf_node_hierarchy() {
+        let mut scene = Scene::new();
+        scene.add_mesh("Loose", create_test_triangle(), [0.8, 0.2, 0.3]);
+        scene.add_mesh("Slab", create_test_triangle(), [0.8, 0.2, 0.3]);
+
+        let building = scene.add_node("Building", None, DMat4::IDENTITY);
+        let lift = DMat4::from_translation(DVec3::new(0.0, 0.0, 3.0));
+        let storey = scene.add_node("Level 1", Some(building), lift);
+        scene.attach_mesh(storey, 1);
+        assert_eq!(scene.root_nodes(), vec![building]);
+        assert_eq!(scene.mesh_node(1), Some(storey));
+        assert_eq!(scene.node_world_transform(storey), lift);
+
+        let gltf: serde_json::Value = serde_json::from_str(&scene.export_gltf_json()).unwrap();
+        // Mesh nodes first, then graph nodes
+        assert_eq!(gltf["scenes"][0]["nodes"], serde_json::json!([0, 2]));
+        assert_eq!(gltf["nodes"][2]["name"], "Building");
+        assert_eq!(gltf["nodes"][2]["children"], serde_json::json!([3]));
+        assert!(gltf["nodes"][2]["matrix"].is_null());
+        assert_eq!(gltf["nodes"][3]["children"], serde_json::json!([1]));
+        assert_eq!(gltf["nodes"][3]["matrix"][14], 3.0);
+
+        // The slab keeps its world position once the storey lift is applied
+        assert_eq!(gltf["accessors"][3]["min"][2].as_f64(), Some(-3.0));
+        assert_eq!(gltf["accessors"][0]["min"][2].as_f64(), Some(0.0));
+    }
+
+    #[test]
+    fn test_gltf_instances_share_one_mesh() {
+        let mut scene = Scene::new();
+        scene.add_mesh("Slab", create_test_triangle(), [0.8, 0.2, 0.3]);
+        let transforms: Vec<[f32; 16]> = [0.0, 4.0, 8.0]
+            .iter()
+            .map(|&x| DMat4::from_translation(DVec3::new(x, 0.0, 0.0)).to_cols_array())
+            .map(|m| m.map(|v| v as f32))
+            .collect();
+        scene.add_instanced_group("Column", create_test_triangle(), [0.5, 0.5, 0.5], transforms);
+
+        let gltf: serde_json::Value = serde_json::from_str(&scene.export_gltf_json()).unwrap();
+        assert_eq!(gltf["scenes"][0]["nodes"], serde_json::json!([0, 1, 2, 3]));
+        assert_eq!(gltf["meshes"].as_array().unwrap().len(), 2);
+        for (k, x) in [0.0, 4.0, 8.0].into_iter().enumerate() {
+            let node = &gltf["nodes"][1 + k];
+            assert_eq!(node["name"], format!("Column_{}", k));
+            assert_eq!(node["mesh"], 1);
+            assert_eq!(node["matrix"][12].as_f64(), Some(x));
+        }
+
+        // The group's accessors follow those of the meshes, in its own frame
+        let attributes = &gltf["meshes"][1]["primitives"][0]["attributes"];
+        assert_eq!(attributes["POSITION"], 3);
+        assert_eq!(gltf["meshes"][1]["primitives"][0]["indices"], 5);
+        assert_eq!(gltf["meshes"][1]["primitives"][0]["material"], 1);
+        assert_eq!(gltf["materials"][1]["name"], "Column_Material");
+        assert_eq!(gltf["accessors"][3]["max"][0].as_f64(), Some(1.0));
+        assert_eq!(gltf["bufferViews"].as_array().unwrap().len(), 6);
+        let buffer_len = gltf["buffers"][0]["byteLength"].as_u64().unwrap() as usize;
+        assert_eq!(buffer_len, scene.generate_gltf_binary_buffer().len());
+    }
+
+    #[test]
+    fn test_glb_container() {
+        let mut scene = Scene::new();
+        scene.add_mesh("TestMesh", create_test_triangle(), [0.8, 0.2, 0.3]);
+
+        let glb = scene.to_glb();
+        let word = |at: usize| u32::from_le_bytes(glb[at..at + 4].try_into().unwrap()) as usize;
+        assert_eq!(&glb[0..4], b"glTF");
+        assert_eq!(word(4), 2);
+        assert_eq!(word(8), glb.len());
+
+        let json_len = word(12);
+        assert_eq!(&glb[16..20], b"JSON");
+        assert_eq!(json_len % 4, 0);
+        let gltf: serde_json::Value = serde_json::from_slice(&glb[20..20 + json_len]).unwrap();
+        assert!(gltf["buffers"][0]["uri"].is_null());
+
+        let bin_at = 20 + json_len;
+        assert_eq!(&glb[bin_at + 4..bin_at + 8], b"BIN\0");
+        let byte_length = gltf["buffers"][0]["byteLength"].as_u64().unwrap() as usize;
+        assert!(word(bin_at) >= byte_length);
+        assert_eq!(bin_at + 8 + word(bin_at), glb.len());
+        // Smaller than the base64 JSON
+        assert!(glb.len() < scene.export_gltf_json().len());
+    }
+
+    #[test]
+    fn test_gltf_meshopt_compression() {
+        let mut scene = Scene::new();
+        scene.add_mesh("A", create_test_triangle(), [0.8, 0.2, 0.3]);
+        scene.add_mesh("B", create_test_triangle(), [0.2, 0.8, 0.3]);
+
+        let options = GltfExportOptions { meshopt_compression: true };
+        let gltf: serde_json::Value =
+            serde_json::from_str(&scene.export_gltf_json_with_options(&options)).unwrap();
+        assert_eq!(gltf["extensionsRequired"][0], "EXT_meshopt_compression");
+        assert_eq!(gltf["buffers"][1]["extensions"]["EXT_meshopt_compression"]["fallback"], true);
+
+        // Every view decodes back to the uncompressed bytes it stands for
+        let raw = scene.generate_gltf_binary_buffer();
+        let payload = scene.gltf_payload(&options);
+        assert_eq!(gltf["buffers"][1]["byteLength"].as_u64(), Some(raw.len() as u64));
+        for view in gltf["bufferViews"].as_array().unwrap() {
+            assert_eq!(view["buffer"], 1);
+            let ext = &view["extensions"]["EXT_meshopt_compression"];
+            let at = |key: &str| ext[key].as_u64().unwrap() as usize;
+            let stream = &payload.data[at("byteOffset")..at("byteOffset") + at("byteLength")];
+            let decoded = meshopt::decode_vertex_buffer(stream, at("count"), at("byteStride")).unwrap();
+            let offset = view["byteOffset"].as_u64().unwrap() as usize;
+            assert_eq!(decoded, &raw[offset..offset + decoded.len()]);
+        }
+
+        // GLB carries the compressed data in its binary chunk
+        let glb = scene.to_glb_with_options(&options);
+        let json_len = u32::from_le_bytes(glb[12..16].try_into().unwrap()) as usize;
+        let bin_len = u32::from_le_bytes(glb[20 + json_len..24 + json_len].try_into().unwrap());
+        assert_eq!(bin_len as usize, payload.data.len());
+    }
+}
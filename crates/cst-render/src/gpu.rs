@@ -0,0 +1,558 @@
+//! Headless rendering of a [`Scene`] on the GPU into an RGBA image.
+//!
+//! Draws into a wgpu offscreen color and depth texture and reads the pixels
+//! back, with the buffers and uniforms of [`crate::pipeline`]. The shading
+//! matches the CPU rasterizer in [`crate::offscreen`], which stays the
+//! fallback on machines without any adapter.
+
+use cst_core::{CstError, Result};
+use wgpu::util::DeviceExt;
+
+use crate::camera::Camera;
+use crate::offscreen::{RgbaImage, BACKGROUND};
+use crate::pipeline::{
+    prepare_mesh_with_layout, CameraUniforms, ClipPlaneUniforms, MaterialUniforms, VertexLayout,
+    INSTANCE_ATTRIBUTES,
+};
+use crate::scene::Scene;
+
+const COLOR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+/// Model matrix of meshes drawn without instances, column-major.
+const IDENTITY: [f32; 16] = [
+    1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0,
+];
+
+const SHADER: &str = r#"
+struct Camera {
+    view: mat4x4<f32>,
+    projection: mat4x4<f32>,
+    view_projection: mat4x4<f32>,
+    eye_position: vec4<f32>,
+    projection_params: vec4<f32>,
+};
+
+struct ClipPlanes {
+    planes: array<vec4<f32>, 6>,
+    count: vec4<u32>,
+};
+
+struct Material {
+    base_color: vec4<f32>,
+    params: vec4<f32>,
+};
+
+@group(0) @binding(0) var<uniform> camera: Camera;
+@group(0) @binding(1) var<uniform> clip_planes: ClipPlanes;
+@group(1) @binding(0) var<uniform> material: Material;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(5) model_0: vec4<f32>,
+    @location(6) model_1: vec4<f32>,
+    @location(7) model_2: vec4<f32>,
+    @location(8) model_3: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip: vec4<f32>,
+    @location(0) world: vec3<f32>,
+};
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    let model = mat4x4<f32>(in.model_0, in.model_1, in.model_2, in.model_3);
+    let world = model * vec4<f32>(in.position, 1.0);
+    // The camera matrices are uploaded row by row
+    var clip = world * camera.view_projection;
+    // From the camera's [-w, w] depth range to wgpu's [0, w]
+    clip.z = (clip.z + clip.w) * 0.5;
+    var out: VertexOutput;
+    out.clip = clip;
+    out.world = world.xyz;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Flat shading: the face normal from the screen-space derivatives
+    var n = normalize(cross(dpdx(in.world), dpdy(in.world)));
+    for (var i = 0u; i < clip_planes.count.x; i++) {
+        let plane = clip_planes.planes[i];
+        if dot(plane.xyz, in.world) + plane.w < 0.0 {
+            discard;
+        }
+    }
+    // Lit from whichever side faces the camera; the third row of the view
+    // matrix points backwards from the view direction
+    if dot(n, camera.view[2].xyz) < 0.0 {
+        n = -n;
+    }
+    let key = max(dot(n, normalize(vec3<f32>(1.0, 1.0, 1.0))), 0.0);
+    let fill = max(dot(n, normalize(vec3<f32>(-1.0, -1.0, -1.0))), 0.0);
+    let shade = 0.5 + 0.6 * key + 0.3 * fill;
+    return vec4<f32>(clamp(material.base_color.rgb * shade, vec3<f32>(0.0), vec3<f32>(1.0)), 1.0);
+}
+"#;
+
+/// A GPU device with the pipeline for offscreen renders of scenes.
+///
+/// Creating one is expensive; keep it around to render many images.
+pub struct OffscreenRenderer {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    adapter: wgpu::AdapterInfo,
+    pipeline: wgpu::RenderPipeline,
+    frame_layout: wgpu::BindGroupLayout,
+    material_layout: wgpu::BindGroupLayout,
+}
+
+/// One draw: geometry, material and the model matrices to draw it with.
+struct Draw {
+    vertices: wgpu::Buffer,
+    indices: wgpu::Buffer,
+    index_count: u32,
+    instances: wgpu::Buffer,
+    instance_count: u32,
+    material: wgpu::BindGroup,
+}
+
+impl OffscreenRenderer {
+    /// Open the default adapter, or a software one when there is no GPU.
+    ///
+    /// [`CstError::NotFound`] when the machine has no adapter at all.
+    pub fn new() -> Result<Self> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+        let request = |force_fallback_adapter| {
+            pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+                force_fallback_adapter,
+                ..Default::default()
+            }))
+        };
+        let adapter = request(false)
+            .or_else(|| request(true))
+            .ok_or_else(|| CstError::NotFound("GPU adapter".to_string()))?;
+        let limits = wgpu::Limits::downlevel_webgl2_defaults().using_resolution(adapter.limits());
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("cst offscreen"),
+                required_features: wgpu::Features::empty(),
+                required_limits: limits,
+            },
+            None,
+        ))
+        .map_err(|e| CstError::InvalidOperation(format!("GPU device: {}", e)))?;
+
+        let uniform = |binding, visibility| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let frame_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("frame"),
+            entries: &[
+                uniform(0, wgpu::ShaderStages::VERTEX_FRAGMENT),
+                uniform(1, wgpu::ShaderStages::FRAGMENT),
+            ],
+        });
+        let material_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("material"),
+            entries: &[uniform(0, wgpu::ShaderStages::FRAGMENT)],
+        });
+        let pipeline = create_pipeline(&device, &frame_layout, &material_layout);
+
+        Ok(Self {
+            device,
+            queue,
+            adapter: adapter.get_info(),
+            pipeline,
+            frame_layout,
+            material_layout,
+        })
+    }
+
+    /// Name of the adapter, e.g. `llvmpipe` for Mesa's software renderer.
+    pub fn adapter_name(&self) -> &str {
+        &self.adapter.name
+    }
+
+    /// Render `scene` from `camera` into a `width` x `height` image, like
+    /// [`Scene::render_to_image`] does on the CPU.
+    pub fn render(&self, scene: &Scene, camera: &Camera, width: u32, height: u32) -> RgbaImage {
+        if width == 0 || height == 0 {
+            return RgbaImage::new(width, height, BACKGROUND);
+        }
+        let mut camera = camera.clone();
+        camera.aspect = width as f64 / height as f64;
+
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let target = |format, usage| {
+            self.device.create_texture(&wgpu::TextureDescriptor {
+                label: None,
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage,
+                view_formats: &[],
+            })
+        };
+        let color = target(
+            COLOR_FORMAT,
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        );
+        let depth = target(DEPTH_FORMAT, wgpu::TextureUsages::RENDER_ATTACHMENT);
+        let color_view = color.create_view(&Default::default());
+        let depth_view = depth.create_view(&Default::default());
+
+        let frame = self.frame_bind_group(scene, &camera);
+        let draws = self.draws(scene, &camera);
+
+        let mut encoder = self.device.create_command_encoder(&Default::default());
+        {
+            let background = BACKGROUND.map(|c| c as f64 / 255.0);
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("scene"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &color_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: background[0],
+                            g: background[1],
+                            b: background[2],
+                            a: background[3],
+                        }),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Discard,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &frame, &[]);
+            for draw in &draws {
+                pass.set_bind_group(1, &draw.material, &[]);
+                pass.set_vertex_buffer(0, draw.vertices.slice(..));
+                pass.set_vertex_buffer(1, draw.instances.slice(..));
+                pass.set_index_buffer(draw.indices.slice(..), wgpu::IndexFormat::Uint32);
+                pass.draw_indexed(0..draw.index_count, 0, 0..draw.instance_count);
+            }
+        }
+
+        // Rows of a texture copy are padded to 256 bytes
+        let row = width as usize * 4;
+        let padded_row = row.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as usize);
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback"),
+            size: (padded_row * height as usize) as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        encoder.copy_texture_to_buffer(
+            color.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &readback,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row as u32),
+                    rows_per_image: None,
+                },
+            },
+            size,
+        );
+        self.queue.submit([encoder.finish()]);
+
+        let slice = readback.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| {});
+        self.device.poll(wgpu::Maintain::Wait);
+        let mut image = RgbaImage::new(width, height, BACKGROUND);
+        {
+            let data = slice.get_mapped_range();
+            for (pixels, padded) in image
+                .pixels
+                .chunks_exact_mut(row)
+                .zip(data.chunks_exact(padded_row))
+            {
+                pixels.copy_from_slice(&padded[..row]);
+            }
+        }
+        readback.unmap();
+        image
+    }
+
+    /// Camera and section plane uniforms.
+    fn frame_bind_group(&self, scene: &Scene, camera: &Camera) -> wgpu::BindGroup {
+        let buffer = |contents: &[u8]| {
+            self.device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: None,
+                    contents,
+                    usage: wgpu::BufferUsages::UNIFORM,
+                })
+        };
+        let camera = buffer(CameraUniforms::from_camera(camera).as_bytes());
+        let clip_planes = buffer(ClipPlaneUniforms::from_planes(&scene.section_planes).as_bytes());
+        self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("frame"),
+            layout: &self.frame_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: camera.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: clip_planes.as_entire_binding(),
+                },
+            ],
+        })
+    }
+
+    /// Buffers of the meshes and instances in the camera frustum.
+    fn draws(&self, scene: &Scene, camera: &Camera) -> Vec<Draw> {
+        let mut draws = Vec::new();
+        for i in scene.visible_meshes(camera) {
+            let scene_mesh = &scene.meshes[i];
+            draws.extend(self.draw(&scene_mesh.mesh, &scene_mesh.material, &[IDENTITY]));
+        }
+        let mut instances = vec![Vec::new(); scene.instanced_groups.len()];
+        for (group, instance) in scene.visible_instances(camera) {
+            instances[group].push(scene.instanced_groups[group].transforms[instance]);
+        }
+        for (group, transforms) in scene.instanced_groups.iter().zip(&instances) {
+            if !transforms.is_empty() {
+                draws.extend(self.draw(&group.mesh, &group.material, transforms));
+            }
+        }
+        draws
+    }
+
+    fn draw(
+        &self,
+        mesh: &cst_mesh::TriangleMesh,
+        material: &crate::material::Material,
+        transforms: &[[f32; 16]],
+    ) -> Option<Draw> {
+        if mesh.indices.is_empty() {
+            return None;
+        }
+        let prepared = prepare_mesh_with_layout(mesh, material, &VertexLayout::POSITION_NORMAL);
+        let buffer = |contents: &[u8], usage| {
+            self.device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: None,
+                    contents,
+                    usage,
+                })
+        };
+        let uniforms = MaterialUniforms::from_material(material);
+        let uniforms = buffer(uniforms.as_bytes(), wgpu::BufferUsages::UNIFORM);
+        Some(Draw {
+            vertices: buffer(&prepared.vertex_buffer_bytes, wgpu::BufferUsages::VERTEX),
+            indices: buffer(&prepared.index_buffer_bytes, wgpu::BufferUsages::INDEX),
+            index_count: prepared.indices.len() as u32,
+            instances: buffer(bytemuck::cast_slice(transforms), wgpu::BufferUsages::VERTEX),
+            instance_count: transforms.len() as u32,
+            material: self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("material"),
+                layout: &self.material_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniforms.as_entire_binding(),
+                }],
+            }),
+        })
+    }
+}
+
+/// Depth-tested triangles of position-normal vertices, stepped by instance
+/// model matrices, into an RGBA color target.
+fn create_pipeline(
+    device: &wgpu::Device,
+    frame_layout: &wgpu::BindGroupLayout,
+    material_layout: &wgpu::BindGroupLayout,
+) -> wgpu::RenderPipeline {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("offscreen"),
+        source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+    });
+    let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("offscreen"),
+        bind_group_layouts: &[frame_layout, material_layout],
+        push_constant_ranges: &[],
+    });
+
+    let format = |format| match format {
+        crate::pipeline::VertexFormat::Float32x2 => wgpu::VertexFormat::Float32x2,
+        crate::pipeline::VertexFormat::Float32x3 => wgpu::VertexFormat::Float32x3,
+        crate::pipeline::VertexFormat::Float32x4 => wgpu::VertexFormat::Float32x4,
+    };
+    let attributes = |attributes: &[crate::pipeline::VertexAttribute]| -> Vec<_> {
+        attributes
+            .iter()
+            .map(|a| wgpu::VertexAttribute {
+                format: format(a.format),
+                offset: a.offset,
+                shader_location: a.location,
+            })
+            .collect()
+    };
+    let layout_attributes = VertexLayout::POSITION_NORMAL.attributes();
+    let vertex_attributes = attributes(&layout_attributes);
+    let instance_attributes = attributes(&INSTANCE_ATTRIBUTES);
+    let buffers = [
+        wgpu::VertexBufferLayout {
+            array_stride: VertexLayout::POSITION_NORMAL.stride(),
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &vertex_attributes,
+        },
+        wgpu::VertexBufferLayout {
+            array_stride: 64,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &instance_attributes,
+        },
+    ];
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("offscreen"),
+        layout: Some(&layout),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: "vs_main",
+            buffers: &buffers,
+        },
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            // IFC winding is unreliable; both sides are drawn
+            cull_mode: None,
+            ..Default::default()
+        },
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: DEPTH_FORMAT,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: Default::default(),
+            bias: Default::default(),
+        }),
+        multisample: Default::default(),
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: "fs_main",
+            targets: &[Some(COLOR_FORMAT.into())],
+        }),
+        multiview: None,
+    })
+}
+
+impl Scene {
+    /// Render the scene from `camera` on the GPU into a `width` x `height`
+    /// image, falling back to [`Scene::render_to_image`] on the CPU when
+    /// the machine has no adapter.
+    ///
+    /// Opens a device on every call; use an [`OffscreenRenderer`] to render
+    /// many images.
+    pub fn render_to_image_gpu(&self, camera: &Camera, width: u32, height: u32) -> RgbaImage {
+        match OffscreenRenderer::new() {
+            Ok(renderer) => renderer.render(self, camera, width, height),
+            Err(_) => self.render_to_image(camera, width, height),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cst_math::plane::Plane;
+    use cst_math::{DMat4, DVec3, Point3, Vector3};
+    use cst_mesh::TriangleMesh;
+
+    fn quad(z: f64, size: f64) -> TriangleMesh {
+        TriangleMesh {
+            positions: vec![
+                Point3::new(-size, -size, z),
+                Point3::new(size, -size, z),
+                Point3::new(size, size, z),
+                Point3::new(-size, size, z),
+            ],
+            normals: vec![],
+            indices: vec![0, 1, 2, 0, 2, 3],
+            uvs: vec![],
+        }
+    }
+
+    /// Pixels where the two images differ by more than rounding, as a
+    /// fraction of the image; edges may rasterize a pixel apart.
+    fn mismatch(a: &RgbaImage, b: &RgbaImage) -> f64 {
+        assert_eq!((a.width, a.height), (b.width, b.height));
+        let differing = a
+            .pixels
+            .chunks_exact(4)
+            .zip(b.pixels.chunks_exact(4))
+            .filter(|(p, q)| p.iter().zip(*q).any(|(x, y)| x.abs_diff(*y) > 2))
+            .count();
+        differing as f64 / (a.width * a.height) as f64
+    }
+
+    #[test]
+    fn test_gpu_render_matches_cpu_render() {
+        let Ok(renderer) = OffscreenRenderer::new() else {
+            eprintln!("No GPU adapter; skipping");
+            return;
+        };
+
+        // Overlapping quads, a section plane between them and an instanced
+        // pair off to the side
+        let mut scene = Scene::new();
+        scene.add_mesh("Back", quad(-1.0, 1.0), [1.0, 0.0, 0.0]);
+        scene.add_mesh("Front", quad(0.0, 0.5), [0.0, 1.0, 0.0]);
+        scene.add_section_plane(Plane::new(Point3::new(0.0, 0.0, -0.5), -Vector3::Z));
+        let transforms = [DVec3::new(-1.5, 1.0, -2.0), DVec3::new(1.5, -1.0, -2.0)]
+            .map(|t| DMat4::from_translation(t).to_cols_array().map(|v| v as f32));
+        scene.add_instanced_group(
+            "Pair",
+            quad(0.0, 0.25),
+            [0.2, 0.4, 1.0],
+            transforms.to_vec(),
+        );
+
+        let camera = Camera::default();
+        let cpu = scene.render_to_image(&camera, 96, 64);
+        let gpu = renderer.render(&scene, &camera, 96, 64);
+        assert!(
+            mismatch(&cpu, &gpu) < 0.03,
+            "{}: {:.3}",
+            renderer.adapter_name(),
+            mismatch(&cpu, &gpu)
+        );
+
+        // The plane cuts the front quad away, showing the back one
+        let center = gpu.pixel(48, 32);
+        assert!(center[0] > 0 && center[1] == 0, "back quad: {:?}", center);
+
+        // Empty images and scenes
+        assert_eq!(renderer.render(&scene, &camera, 0, 5).pixels.len(), 0);
+        let empty = renderer.render(&Scene::new(), &camera, 300, 2);
+        assert!(empty.pixels.chunks(4).all(|p| p == BACKGROUND));
+    }
+}
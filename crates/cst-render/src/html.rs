@@ -0,0 +1,1295 @@
+//! Standalone HTML export of a [`Scene`].
+//!
+//! Writes one self-contained page with the meshes inlined as JavaScript
+//! arrays and a small Three.js viewer: orbit camera, picking, element
+//! properties, spatial tree, section planes and measuring.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use cst_math::Aabb3;
+use cst_mesh::{feature_edges, TriangleMesh};
+
+use crate::camera::{Camera, CameraView, Projection, StandardView};
+use crate::material::Material;
+use crate::scene::{js_string, ElementMetadata, Scene, SpatialTreeNode};
+
+/// Options for [`Scene::export_html_with_options`]
+#[derive(Debug, Clone, Default)]
+pub struct HtmlExportOptions {
+    /// Start the viewer with an orthographic camera (plan/elevation views).
+    /// The projection can still be toggled with the `O` key.
+    pub orthographic: bool,
+    /// Draw feature edges (boundaries and creases sharper than this angle,
+    /// in radians) over the shaded meshes. Toggled with the `E` key.
+    pub edge_overlay: Option<f64>,
+    /// Local copy of `three.min.js` (r128) to embed inline instead of
+    /// loading it from the CDN, so the file also opens without network access
+    pub three_js: Option<PathBuf>,
+}
+
+impl SpatialTreeNode {
+    /// Write the subtree as JSON
+    fn write_json(&self, out: &mut String) {
+        use std::fmt::Write as FmtWrite;
+
+        write!(
+            out,
+            "{{\"name\":{},\"kind\":{},\"meshes\":{:?},\"children\":[",
+            js_string(&self.name),
+            js_string(&self.kind),
+            self.meshes
+        )
+        .unwrap();
+        for (i, child) in self.children.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            child.write_json(out);
+        }
+        out.push_str("]}");
+    }
+}
+
+impl Scene {
+    /// Export scene as a standalone HTML file with embedded Three.js viewer
+    pub fn export_html(&self, path: &Path) -> std::io::Result<()> {
+        self.export_html_with_options(path, &HtmlExportOptions::default())
+    }
+
+    /// Export scene as a standalone HTML file using the given viewer options
+    pub fn export_html_with_options(
+        &self,
+        path: &Path,
+        options: &HtmlExportOptions,
+    ) -> std::io::Result<()> {
+        let bounds = self.bounds().unwrap_or_else(|| {
+            use cst_math::{Point3, DVec3};
+            Aabb3::new(Point3::ZERO, DVec3::splat(1.0))
+        });
+        let center = bounds.center();
+        let size = bounds.extents();
+        let camera_distance = size.length() * 1.5;
+
+        // Read the embedded library up front so a bad path leaves no file behind
+        let three_js = match &options.three_js {
+            Some(library) => Some(std::fs::read_to_string(library)?),
+            None => None,
+        };
+
+        let mut file = std::fs::File::create(path)?;
+
+        write!(file, r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>CSTEngine Scene Viewer</title>
+    <style>
+        body {{
+            margin: 0;
+            overflow: hidden;
+            font-family: 'Segoe UI', Tahoma, Geneva, Verdana, sans-serif;
+            background: #1a1a1a;
+        }}
+        #container {{
+            width: 100vw;
+            height: 100vh;
+        }}
+        #info {{
+            position: absolute;
+            top: 10px;
+            left: 10px;
+            background: rgba(0, 0, 0, 0.7);
+            color: white;
+            padding: 15px;
+            border-radius: 5px;
+            font-size: 14px;
+            max-width: 300px;
+            max-height: calc(100vh - 40px);
+            overflow-y: auto;
+        }}
+        #info h3 {{
+            margin: 0 0 10px 0;
+            font-size: 16px;
+            border-bottom: 1px solid #666;
+            padding-bottom: 5px;
+        }}
+        #info .mesh-item {{
+            margin: 5px 0;
+            padding: 5px;
+            background: rgba(255, 255, 255, 0.1);
+            border-radius: 3px;
+        }}
+        #info .mesh-name {{
+            font-weight: bold;
+            color: #4fc3f7;
+        }}
+        #info .mesh-stats {{
+            font-size: 12px;
+            color: #aaa;
+        }}
+        #info .mesh-item.hidden .mesh-name {{
+            color: #777;
+        }}
+        #info label {{
+            display: flex;
+            align-items: center;
+            gap: 6px;
+            cursor: pointer;
+        }}
+        #info select {{
+            margin-top: 6px;
+            width: 100%;
+            background: #333;
+            color: white;
+            border: 1px solid #666;
+        }}
+        #info button {{
+            margin-top: 4px;
+            font-size: 11px;
+            background: #333;
+            color: white;
+            border: 1px solid #666;
+            border-radius: 3px;
+            cursor: pointer;
+        }}
+        #info button.active {{
+            background: #8a7a00;
+        }}
+        #tree {{
+            position: absolute;
+            top: 10px;
+            right: 10px;
+            background: rgba(0, 0, 0, 0.7);
+            color: white;
+            padding: 15px;
+            border-radius: 5px;
+            font-size: 13px;
+            max-width: 300px;
+            max-height: calc(100vh - 40px);
+            overflow-y: auto;
+        }}
+        #tree details {{
+            margin-left: 12px;
+        }}
+        #tree summary {{
+            cursor: pointer;
+        }}
+        #tree .kind {{
+            font-size: 11px;
+            color: #aaa;
+        }}
+        #properties {{
+            position: absolute;
+            bottom: 10px;
+            right: 10px;
+            background: rgba(0, 0, 0, 0.8);
+            color: white;
+            padding: 15px;
+            border-radius: 5px;
+            font-size: 13px;
+            max-width: 360px;
+            max-height: 45vh;
+            overflow-y: auto;
+        }}
+        #properties h3 {{
+            margin: 0 0 10px 0;
+            font-size: 16px;
+            word-break: break-all;
+        }}
+        #properties td {{
+            padding: 2px 6px 2px 0;
+            vertical-align: top;
+            word-break: break-all;
+        }}
+        #properties td:first-child {{
+            color: #aaa;
+            white-space: nowrap;
+        }}
+        #measure-result {{
+            position: absolute;
+            bottom: 10px;
+            left: 10px;
+            background: rgba(0, 0, 0, 0.8);
+            color: #ffeb3b;
+            padding: 10px 15px;
+            border-radius: 5px;
+            font-size: 13px;
+            font-family: monospace;
+            white-space: pre;
+        }}
+        #error {{
+            position: absolute;
+            top: 50%;
+            left: 50%;
+            transform: translate(-50%, -50%);
+            background: rgba(200, 0, 0, 0.9);
+            color: white;
+            padding: 20px;
+            border-radius: 5px;
+            display: none;
+        }}
+    </style>
+</head>
+<body>
+    <div id="container"></div>
+    <div id="info">
+        <h3>CSTEngine Scene</h3>
+        <div>Meshes: {}</div>
+        <div>Triangles: {}</div>
+        <button id="show-all">Show all</button>
+        <button id="measure" title="Distance between two picked points">Measure (M)</button>
+"#, self.meshes.len(), self.total_triangles())?;
+
+        // Saved views dropdown
+        if !self.views.is_empty() {
+            writeln!(file, r#"        <select id="views"><option value="">Saved views…</option>"#)?;
+            for (i, view) in self.views.iter().enumerate() {
+                writeln!(file, r#"            <option value="{}">{}</option>"#, i, html_escape(&view.name))?;
+            }
+            writeln!(file, "        </select>")?;
+        }
+        writeln!(file, r#"        <hr style="border: 1px solid #666; margin: 10px 0;">"#)?;
+
+        // Write mesh list with visibility controls
+        for (i, scene_mesh) in self.meshes.iter().enumerate() {
+            let tri_count = scene_mesh.mesh.indices.len() / 3;
+            let details: Vec<&str> = [&scene_mesh.metadata.ifc_type, &scene_mesh.metadata.storey]
+                .into_iter()
+                .flatten()
+                .map(String::as_str)
+                .collect();
+            write!(file, r#"        <div class="mesh-item" data-index="{}">
+            <label><input type="checkbox" checked><span class="mesh-name">{}</span></label>
+            <div class="mesh-stats">{}{} triangles</div>
+            <button class="isolate">Isolate</button>
+        </div>
+"#, i, html_escape(&scene_mesh.name),
+                if details.is_empty() { String::new() } else { format!("{} · ", html_escape(&details.join(" · "))) },
+                tri_count)?;
+        }
+
+        write!(file, r#"    </div>
+    <div id="tree" style="display: none;"><h3>Spatial Structure</h3></div>
+    <div id="properties" style="display: none;"><h3></h3><table></table></div>
+    <div id="measure-result" style="display: none;"></div>
+"#)?;
+
+        match &three_js {
+            Some(source) => {
+                writeln!(file, r#"    <div id="error">Failed to load the embedded Three.js library.</div>"#)?;
+                writeln!(file)?;
+                // A literal `</script` would end the element early
+                writeln!(file, "    <script>{}</script>", source.replace("</script", "<\\/script"))?;
+            }
+            None => {
+                writeln!(file, r#"    <div id="error">Failed to load Three.js from CDN. Please check your internet connection.</div>"#)?;
+                writeln!(file)?;
+                writeln!(file, r#"    <script src="https://cdnjs.cloudflare.com/ajax/libs/three.js/r128/three.min.js"></script>"#)?;
+            }
+        }
+
+        write!(file, r#"    <script>
+        if (typeof THREE === 'undefined') document.getElementById('error').style.display='block';
+"#)?;
+
+        // Embed mesh data
+        writeln!(file, "        const meshData = [")?;
+        for (i, scene_mesh) in self.meshes.iter().enumerate() {
+            writeln!(file, "            {{")?;
+            writeln!(file, "                name: {},", js_string(&scene_mesh.name))?;
+            writeln!(file, "                visible: {},", scene_mesh.visible)?;
+            write_metadata_js(&mut file, &scene_mesh.metadata)?;
+            writeln!(file, "                area: {:.3},", scene_mesh.mesh.surface_area())?;
+            let material = &scene_mesh.material;
+            write_material_js(&mut file, material)?;
+            write_geometry_js(&mut file, &scene_mesh.mesh)?;
+
+            // Texture coordinates, only needed for textured materials
+            write!(file, "                uvs: [")?;
+            if material.texture.is_some() && scene_mesh.mesh.uvs.len() == scene_mesh.mesh.positions.len() {
+                for (j, uv) in scene_mesh.mesh.uvs.iter().enumerate() {
+                    if j > 0 { write!(file, ",")?; }
+                    write!(file, "{:.4},{:.4}", uv.x as f32, uv.y as f32)?;
+                }
+            }
+            writeln!(file, "],")?;
+            write_edges_js(&mut file, &scene_mesh.mesh, options.edge_overlay)?;
+
+            write!(file, "            }}")?;
+            if i < self.meshes.len() - 1 {
+                write!(file, ",")?;
+            }
+            writeln!(file)?;
+        }
+        write!(file, "        ];\n\n")?;
+
+        // Instanced groups: the shared geometry in its own coordinates,
+        // a column-major matrix and the element metadata per instance
+        writeln!(file, "        const instanceData = [")?;
+        for (i, group) in self.instanced_groups.iter().enumerate() {
+            writeln!(file, "            {{")?;
+            writeln!(file, "                name: {},", js_string(&group.name))?;
+            writeln!(file, "                area: {:.3},", group.mesh.surface_area())?;
+            write_material_js(&mut file, &group.material)?;
+            write!(file, "                transforms: [")?;
+            for (j, value) in group.transforms.iter().flatten().enumerate() {
+                if j > 0 { write!(file, ",")?; }
+                write!(file, "{}", value)?;
+            }
+            writeln!(file, "],")?;
+            writeln!(file, "                instances: [")?;
+            for metadata in &group.metadata {
+                writeln!(file, "                {{")?;
+                write_metadata_js(&mut file, metadata)?;
+                writeln!(file, "                }},")?;
+            }
+            writeln!(file, "                ],")?;
+            write_geometry_js(&mut file, &group.mesh)?;
+            write_edges_js(&mut file, &group.mesh, options.edge_overlay)?;
+            write!(file, "            }}")?;
+            if i < self.instanced_groups.len() - 1 {
+                write!(file, ",")?;
+            }
+            writeln!(file)?;
+        }
+        write!(file, "        ];\n\n")?;
+
+        // Section planes as three.js (normal, constant) pairs
+        write!(file, "        const clipPlaneData = [")?;
+        for (i, plane) in self.section_planes.iter().enumerate() {
+            if i > 0 { write!(file, ",")?; }
+            let n = plane.normal;
+            write!(file, "[{},{},{},{}]", n.x, n.y, n.z, -n.dot(plane.origin))?;
+        }
+        write!(file, "];\n\n")?;
+
+        // Spatial hierarchy for the tree panel
+        let mut tree_json = String::from("null");
+        if let Some(tree) = &self.spatial_tree {
+            tree_json.clear();
+            tree.write_json(&mut tree_json);
+        }
+        write!(file, "        const spatialTree = {};\n\n", tree_json)?;
+
+        // Camera bookmarks; orthoHeight is null for perspective views
+        writeln!(file, "        const cameraViews = [")?;
+        for view in &self.views {
+            write_view_js(&mut file, view)?;
+        }
+        write!(file, "        ];\n\n")?;
+
+        // Standard views for the number keys, framed for a square window;
+        // orthoHeight is used when the orthographic camera is active
+        writeln!(file, "        const standardViews = [")?;
+        for standard in StandardView::ALL {
+            let mut camera = Camera::standard_view(standard, &bounds, 1.0);
+            let mut plan = camera.clone();
+            plan.set_orthographic();
+            plan.set_standard_view(standard, &bounds);
+            camera.projection = plan.projection;
+            write_view_js(&mut file, &CameraView::from_camera(standard.name(), &camera))?;
+        }
+        write!(file, "        ];\n\n")?;
+
+        // Three.js scene setup
+        write!(file, r#"        function initScene() {{
+            const scene = new THREE.Scene();
+            scene.background = new THREE.Color(0x1a1a1a);
+
+            const perspectiveCamera = new THREE.PerspectiveCamera(
+                60,
+                window.innerWidth / window.innerHeight,
+                0.1,
+                10000
+            );
+
+            // Orthographic frustum sized to the scene extents
+            const orthoHalfHeight = {:.2};
+            const orthographicCamera = new THREE.OrthographicCamera(-1, 1, 1, -1, 0.1, 10000);
+            function updateOrthoFrustum() {{
+                const aspect = window.innerWidth / window.innerHeight;
+                orthographicCamera.left = -orthoHalfHeight * aspect;
+                orthographicCamera.right = orthoHalfHeight * aspect;
+                orthographicCamera.top = orthoHalfHeight;
+                orthographicCamera.bottom = -orthoHalfHeight;
+                orthographicCamera.updateProjectionMatrix();
+            }}
+            updateOrthoFrustum();
+
+            let camera = {} ? orthographicCamera : perspectiveCamera;
+
+            const renderer = new THREE.WebGLRenderer({{ antialias: true }});
+            renderer.setSize(window.innerWidth, window.innerHeight);
+            renderer.localClippingEnabled = true;
+            document.getElementById('container').appendChild(renderer.domElement);
+
+            const clipPlanes = clipPlaneData.map(p =>
+                new THREE.Plane(new THREE.Vector3(p[0], p[1], p[2]), p[3]));
+
+            // Add lighting
+            const ambientLight = new THREE.AmbientLight(0x404040, 2);
+            scene.add(ambientLight);
+
+            const dirLight1 = new THREE.DirectionalLight(0xffffff, 1);
+            dirLight1.position.set(1, 1, 1);
+            scene.add(dirLight1);
+
+            const dirLight2 = new THREE.DirectionalLight(0xffffff, 0.5);
+            dirLight2.position.set(-1, -1, -1);
+            scene.add(dirLight2);
+
+            // Edge overlay lines share one material
+            const edgeMaterial = new THREE.LineBasicMaterial({{
+                color: 0x111111,
+                clippingPlanes: clipPlanes
+            }});
+            const textureLoader = new THREE.TextureLoader();
+            const meshObjects = [];
+            const edgeObjects = [];
+            let showEdges = true;
+
+            function makeGeometry(data) {{
+                const geometry = new THREE.BufferGeometry();
+                geometry.setAttribute('position', new THREE.Float32BufferAttribute(data.positions, 3));
+                geometry.setAttribute('normal', new THREE.Float32BufferAttribute(data.normals, 3));
+                geometry.setIndex(data.indices);
+                return geometry;
+            }}
+
+            function makeMaterial(look, textured) {{
+                return new THREE.MeshStandardMaterial({{
+                    color: new THREE.Color(look.color[0], look.color[1], look.color[2]),
+                    metalness: look.metalness,
+                    roughness: look.roughness,
+                    transparent: look.opacity < 1,
+                    opacity: look.opacity,
+                    depthWrite: look.opacity >= 1,
+                    map: look.map && textured ? textureLoader.load(look.map) : null,
+                    side: look.doubleSided ? THREE.DoubleSide : THREE.FrontSide,
+                    clippingPlanes: clipPlanes,
+                    // Push faces back so edge lines are not hidden
+                    polygonOffset: true,
+                    polygonOffsetFactor: 1,
+                    polygonOffsetUnits: 1
+                }});
+            }}
+
+            // Add meshes
+            meshData.forEach(data => {{
+                const geometry = makeGeometry(data);
+                if (data.uvs.length > 0) {{
+                    geometry.setAttribute('uv', new THREE.Float32BufferAttribute(data.uvs, 2));
+                }}
+                const material = makeMaterial(data.material, data.uvs.length > 0);
+
+                const mesh = new THREE.Mesh(geometry, material);
+                mesh.userData = {{
+                    name: data.name,
+                    ifcType: data.ifcType,
+                    storey: data.storey,
+                    globalId: data.globalId,
+                    properties: data.properties,
+                    area: data.area,
+                    triangles: data.indices.length / 3
+                }};
+                scene.add(mesh);
+                meshObjects.push(mesh);
+
+                let edgeLines = null;
+                if (data.edges.length > 0) {{
+                    const edgeGeometry = new THREE.BufferGeometry();
+                    edgeGeometry.setAttribute('position', new THREE.Float32BufferAttribute(data.edges, 3));
+                    edgeLines = new THREE.LineSegments(edgeGeometry, edgeMaterial);
+                    scene.add(edgeLines);
+                }}
+                edgeObjects.push(edgeLines);
+            }});
+
+            // Add instanced groups, one draw call each. Instance colors
+            // multiply the material color; white leaves it unchanged.
+            const instanceObjects = [];
+            const instanceEdgeObjects = [];
+            const instanceWhite = new THREE.Color(1, 1, 1);
+            instanceData.forEach(data => {{
+                const count = data.transforms.length / 16;
+                const mesh = new THREE.InstancedMesh(makeGeometry(data), makeMaterial(data.material, false), count);
+                // Culling would test the shared geometry where it sits, not the instances
+                mesh.frustumCulled = false;
+                let edgeGeometry = null;
+                if (data.edges.length > 0) {{
+                    edgeGeometry = new THREE.BufferGeometry();
+                    edgeGeometry.setAttribute('position', new THREE.Float32BufferAttribute(data.edges, 3));
+                }}
+                const matrix = new THREE.Matrix4();
+                for (let i = 0; i < count; i++) {{
+                    matrix.fromArray(data.transforms, i * 16);
+                    mesh.setMatrixAt(i, matrix);
+                    mesh.setColorAt(i, instanceWhite);
+                    if (edgeGeometry) {{
+                        const lines = new THREE.LineSegments(edgeGeometry, edgeMaterial);
+                        lines.matrixAutoUpdate = false;
+                        lines.matrix.copy(matrix);
+                        scene.add(lines);
+                        instanceEdgeObjects.push(lines);
+                    }}
+                }}
+                mesh.userData = {{
+                    name: data.name,
+                    area: data.area,
+                    triangles: data.indices.length / 3,
+                    instances: data.instances
+                }};
+                scene.add(mesh);
+                instanceObjects.push(mesh);
+            }});
+
+            // Per-element visibility, driven by the mesh list
+            const meshItems = document.querySelectorAll('#info .mesh-item');
+            function setVisible(i, visible) {{
+                if (!visible && selected === meshObjects[i]) select(null);
+                meshObjects[i].visible = visible;
+                if (edgeObjects[i]) edgeObjects[i].visible = visible && showEdges;
+                meshItems[i].querySelector('input').checked = visible;
+                meshItems[i].classList.toggle('hidden', !visible);
+            }}
+            meshItems.forEach((item, i) => {{
+                item.querySelector('input').addEventListener('change', (e) => setVisible(i, e.target.checked));
+                item.querySelector('.isolate').addEventListener('click', () => {{
+                    meshObjects.forEach((_, j) => setVisible(j, j === i));
+                }});
+            }});
+            document.getElementById('show-all').addEventListener('click', () => {{
+                meshObjects.forEach((_, j) => setVisible(j, true));
+                document.querySelectorAll('#tree input').forEach(box => {{ box.checked = true; }});
+            }});
+
+            // Collapsible spatial tree; a checkbox toggles every mesh below it
+            function subtreeMeshes(node) {{
+                return node.children.reduce((all, child) => all.concat(subtreeMeshes(child)), node.meshes);
+            }}
+            function buildTreeNode(node) {{
+                const details = document.createElement('details');
+                details.open = node.kind !== 'IfcBuildingStorey';
+                const summary = document.createElement('summary');
+                const box = document.createElement('input');
+                box.type = 'checkbox';
+                box.checked = true;
+                box.addEventListener('click', (e) => e.stopPropagation());
+                box.addEventListener('change', () => {{
+                    subtreeMeshes(node).forEach(i => {{ if (meshObjects[i]) setVisible(i, box.checked); }});
+                    details.querySelectorAll('input').forEach(inner => {{ inner.checked = box.checked; }});
+                }});
+                const kind = document.createElement('span');
+                kind.className = 'kind';
+                kind.textContent = ' ' + node.kind + ' (' + subtreeMeshes(node).length + ')';
+                summary.append(box, ' ' + node.name, kind);
+                details.appendChild(summary);
+                node.children.forEach(child => details.appendChild(buildTreeNode(child)));
+                return details;
+            }}
+            if (spatialTree) {{
+                const panel = document.getElementById('tree');
+                panel.appendChild(buildTreeNode(spatialTree));
+                panel.style.display = 'block';
+            }}
+
+            // Add grid and axes
+            const gridSize = {:.2};
+            const grid = new THREE.GridHelper(gridSize * 2, 20, 0x444444, 0x222222);
+            grid.position.y = {:.2};
+            scene.add(grid);
+
+            const axes = new THREE.AxesHelper(gridSize * 0.5);
+            scene.add(axes);
+
+            // Position camera
+            const center = new THREE.Vector3({:.2}, {:.2}, {:.2});
+            const distance = {:.2};
+            [perspectiveCamera, orthographicCamera].forEach(cam => {{
+                cam.position.set(
+                    center.x + distance * 0.7,
+                    center.y + distance * 0.7,
+                    center.z + distance * 0.7
+                );
+                cam.lookAt(center);
+            }});
+
+            // Orbit controls: left drag rotates, right or shift drag pans,
+            // wheel and pinch zoom. Pointer events cover mouse, pen and touch.
+            const target = center.clone();
+            const spherical = new THREE.Spherical().setFromVector3(
+                perspectiveCamera.position.clone().sub(target));
+            const canvas = renderer.domElement;
+            const pointers = new Map();
+            let panning = false;
+
+            function updateCameraPosition() {{
+                spherical.makeSafe();
+                const offset = new THREE.Vector3().setFromSpherical(spherical);
+                [perspectiveCamera, orthographicCamera].forEach(cam => {{
+                    cam.position.copy(target).add(offset);
+                    cam.lookAt(target);
+                }});
+            }}
+
+            function pan(dx, dy) {{
+                // World units per screen pixel at the target depth
+                const perPixel = camera === orthographicCamera
+                    ? (orthographicCamera.top - orthographicCamera.bottom) / orthographicCamera.zoom / canvas.clientHeight
+                    : 2 * spherical.radius * Math.tan(perspectiveCamera.fov * Math.PI / 360) / canvas.clientHeight;
+                camera.updateMatrix();
+                const right = new THREE.Vector3().setFromMatrixColumn(camera.matrix, 0);
+                const up = new THREE.Vector3().setFromMatrixColumn(camera.matrix, 1);
+                target.addScaledVector(right, -dx * perPixel).addScaledVector(up, dy * perPixel);
+                updateCameraPosition();
+            }}
+
+            function zoom(factor) {{
+                if (camera === orthographicCamera) {{
+                    // Moving an orthographic camera does not change the image
+                    orthographicCamera.zoom = Math.max(0.01, orthographicCamera.zoom / factor);
+                    orthographicCamera.updateProjectionMatrix();
+                }} else {{
+                    spherical.radius = Math.max(0.01, spherical.radius * factor);
+                    updateCameraPosition();
+                }}
+            }}
+
+            canvas.style.touchAction = 'none';
+            canvas.addEventListener('contextmenu', (e) => e.preventDefault());
+
+            canvas.addEventListener('pointerdown', (e) => {{
+                canvas.setPointerCapture(e.pointerId);
+                pointers.set(e.pointerId, {{ x: e.clientX, y: e.clientY }});
+                panning = e.button === 2 || e.shiftKey;
+            }});
+
+            canvas.addEventListener('pointermove', (e) => {{
+                const previous = pointers.get(e.pointerId);
+                if (!previous) return;
+                const dx = e.clientX - previous.x;
+                const dy = e.clientY - previous.y;
+
+                if (pointers.size === 2) {{
+                    // Two fingers: pinch zoom and pan
+                    const other = [...pointers].find(([id]) => id !== e.pointerId)[1];
+                    const before = Math.hypot(previous.x - other.x, previous.y - other.y);
+                    const after = Math.hypot(e.clientX - other.x, e.clientY - other.y);
+                    if (before > 0 && after > 0) zoom(before / after);
+                    pan(dx / 2, dy / 2);
+                }} else if (panning) {{
+                    pan(dx, dy);
+                }} else {{
+                    spherical.theta -= dx * 0.01;
+                    spherical.phi -= dy * 0.01;
+                    updateCameraPosition();
+                }}
+
+                pointers.set(e.pointerId, {{ x: e.clientX, y: e.clientY }});
+            }});
+
+            const releasePointer = (e) => {{
+                pointers.delete(e.pointerId);
+            }};
+            canvas.addEventListener('pointerup', releasePointer);
+            canvas.addEventListener('pointercancel', releasePointer);
+
+            canvas.addEventListener('wheel', (e) => {{
+                e.preventDefault();
+                zoom(Math.exp(e.deltaY * 0.001));
+            }}, {{ passive: false }});
+
+            // Saved views; a #view=<name> URL hash opens one directly, so a
+            // link shares the exact viewpoint
+            function applyView(view) {{
+                target.set(view.target[0], view.target[1], view.target[2]);
+                [perspectiveCamera, orthographicCamera].forEach(cam => cam.up.set(view.up[0], view.up[1], view.up[2]));
+                perspectiveCamera.fov = view.fov;
+                perspectiveCamera.updateProjectionMatrix();
+                spherical.setFromVector3(new THREE.Vector3(view.eye[0], view.eye[1], view.eye[2]).sub(target));
+                if (view.orthoHeight !== null) {{
+                    orthographicCamera.zoom = 2 * orthoHalfHeight / view.orthoHeight;
+                    orthographicCamera.updateProjectionMatrix();
+                    camera = orthographicCamera;
+                }} else {{
+                    camera = perspectiveCamera;
+                }}
+                updateCameraPosition();
+            }}
+
+            const viewSelect = document.getElementById('views');
+            if (viewSelect) {{
+                viewSelect.addEventListener('change', () => {{
+                    const view = cameraViews[viewSelect.value];
+                    if (!view) return;
+                    applyView(view);
+                    history.replaceState(null, '', '#view=' + encodeURIComponent(view.name));
+                }});
+            }}
+            if (location.hash.startsWith('#view=')) {{
+                const name = decodeURIComponent(location.hash.slice('#view='.length));
+                const initial = cameraViews.findIndex(view => view.name === name);
+                if (initial >= 0) {{
+                    applyView(cameraViews[initial]);
+                    if (viewSelect) viewSelect.value = initial;
+                }}
+            }}
+
+            // Click to select: highlight the element and list its properties.
+            // A pointer that moved more than a few pixels was a drag, not a click.
+            const raycaster = new THREE.Raycaster();
+            const propertiesPanel = document.getElementById('properties');
+            let selected = null;
+            let selectedInstance = null;
+            let clickStart = null;
+            const instanceHighlight = new THREE.Color(1, 1, 0.4);
+
+            function highlight(on) {{
+                if (!selected) return;
+                if (selected.isInstancedMesh) {{
+                    selected.setColorAt(selectedInstance, on ? instanceHighlight : instanceWhite);
+                    selected.instanceColor.needsUpdate = true;
+                }} else {{
+                    selected.material.emissive.setHex(on ? 0x555500 : 0x000000);
+                }}
+            }}
+
+            // An instanced mesh is selected one instance at a time
+            function select(object, instance) {{
+                highlight(false);
+                selected = object;
+                selectedInstance = object && object.isInstancedMesh ? instance : null;
+                if (!object) {{
+                    propertiesPanel.style.display = 'none';
+                    return;
+                }}
+                highlight(true);
+
+                const info = selectedInstance === null
+                    ? object.userData
+                    : {{ ...object.userData, ...object.userData.instances[selectedInstance] }};
+                propertiesPanel.querySelector('h3').textContent = info.name;
+                // Instances of a group that are not elements have no metadata
+                const rows = [
+                    ['Type', info.ifcType],
+                    ['GlobalId', info.globalId],
+                    ['Storey', info.storey],
+                    ['Triangles', String(info.triangles)],
+                    ['Area', info.area.toFixed(3)]
+                ].filter(row => row[1] != null).concat(info.properties || []);
+                const table = propertiesPanel.querySelector('table');
+                table.replaceChildren(...rows.map(([key, value]) => {{
+                    const row = document.createElement('tr');
+                    [key, value].forEach(text => {{
+                        const cell = document.createElement('td');
+                        cell.textContent = text;
+                        row.appendChild(cell);
+                    }});
+                    return row;
+                }}));
+                propertiesPanel.style.display = 'block';
+            }}
+
+            // Measure mode: two clicks on the model read the distance between
+            // the picked points; a third click starts a new measurement
+            const measureButton = document.getElementById('measure');
+            const measurePanel = document.getElementById('measure-result');
+            const measureColor = 0xffeb3b;
+            const markerMaterial = new THREE.PointsMaterial({{
+                color: measureColor, size: 8, sizeAttenuation: false, depthTest: false
+            }});
+            const measureLineMaterial = new THREE.LineBasicMaterial({{ color: measureColor, depthTest: false }});
+            let measuring = false;
+            let measurePoints = [];
+            let measureObjects = [];
+
+            function clearMeasurement() {{
+                measureObjects.forEach(object => {{
+                    scene.remove(object);
+                    object.geometry.dispose();
+                }});
+                measureObjects = [];
+                measurePoints = [];
+                measurePanel.style.display = 'none';
+            }}
+
+            function setMeasuring(on) {{
+                measuring = on;
+                measureButton.classList.toggle('active', on);
+                canvas.style.cursor = on ? 'crosshair' : '';
+                if (!on) clearMeasurement();
+            }}
+
+            function addMeasurePoint(point) {{
+                if (measurePoints.length === 2) clearMeasurement();
+                measurePoints.push(point);
+                measureObjects.forEach(object => {{
+                    scene.remove(object);
+                    object.geometry.dispose();
+                }});
+                const geometry = () => new THREE.BufferGeometry().setFromPoints(measurePoints);
+                measureObjects = [new THREE.Points(geometry(), markerMaterial)];
+                if (measurePoints.length === 2) measureObjects.push(new THREE.Line(geometry(), measureLineMaterial));
+                measureObjects.forEach(object => {{
+                    object.renderOrder = 1;
+                    scene.add(object);
+                }});
+
+                measurePanel.style.display = 'block';
+                if (measurePoints.length < 2) {{
+                    measurePanel.textContent = 'Pick the second point';
+                    return;
+                }}
+                const [a, b] = measurePoints;
+                const delta = b.clone().sub(a);
+                measurePanel.textContent = 'Distance ' + a.distanceTo(b).toFixed(3) + '\n' +
+                    'dX ' + delta.x.toFixed(3) + '  dY ' + delta.y.toFixed(3) + '  dZ ' + delta.z.toFixed(3);
+            }}
+
+            measureButton.addEventListener('click', () => setMeasuring(!measuring));
+
+            canvas.addEventListener('pointerdown', (e) => {{
+                clickStart = e.button === 0 && pointers.size === 1 ? {{ x: e.clientX, y: e.clientY }} : null;
+            }});
+
+            canvas.addEventListener('pointerup', (e) => {{
+                if (!clickStart || Math.hypot(e.clientX - clickStart.x, e.clientY - clickStart.y) > 4) return;
+                clickStart = null;
+
+                const rect = canvas.getBoundingClientRect();
+                const ndc = new THREE.Vector2(
+                    ((e.clientX - rect.left) / rect.width) * 2 - 1,
+                    -((e.clientY - rect.top) / rect.height) * 2 + 1
+                );
+                raycaster.setFromCamera(ndc, camera);
+                // The raycaster ignores clipping, so skip hits in removed regions
+                const hit = raycaster.intersectObjects(meshObjects.filter(m => m.visible).concat(instanceObjects))
+                    .find(h => clipPlanes.every(plane => plane.distanceToPoint(h.point) >= 0));
+                if (measuring) {{
+                    if (hit) addMeasurePoint(hit.point.clone());
+                    return;
+                }}
+                select(hit ? hit.object : null, hit ? hit.instanceId : null);
+            }});
+
+            // Meshes hidden in the scene start unchecked
+            meshData.forEach((data, i) => {{ if (!data.visible) setVisible(i, false); }});
+
+            // Keyboard: Escape clears, O toggles projection, E edges, M measure,
+            // 1-9 and 0 jump to the standard views keeping the projection
+            window.addEventListener('keydown', (e) => {{
+                if (e.key >= '0' && e.key <= '9') {{
+                    const view = standardViews[(Number(e.key) + 9) % 10];
+                    applyView(camera === orthographicCamera ? view : {{ ...view, orthoHeight: null }});
+                }}
+                if (e.key === 'Escape') {{
+                    select(null);
+                    clearMeasurement();
+                }}
+                if (e.key === 'm' || e.key === 'M') {{
+                    setMeasuring(!measuring);
+                }}
+                if (e.key === 'o' || e.key === 'O') {{
+                    camera = camera === orthographicCamera ? perspectiveCamera : orthographicCamera;
+                }}
+                if (e.key === 'e' || e.key === 'E') {{
+                    showEdges = !showEdges;
+                    edgeObjects.forEach((lines, i) => {{
+                        if (lines) lines.visible = showEdges && meshObjects[i].visible;
+                    }});
+                    instanceEdgeObjects.forEach(lines => {{ lines.visible = showEdges; }});
+                }}
+            }});
+
+            // Handle window resize
+            window.addEventListener('resize', () => {{
+                perspectiveCamera.aspect = window.innerWidth / window.innerHeight;
+                perspectiveCamera.updateProjectionMatrix();
+                updateOrthoFrustum();
+                renderer.setSize(window.innerWidth, window.innerHeight);
+            }});
+
+            // Animation loop
+            function animate() {{
+                requestAnimationFrame(animate);
+                renderer.render(scene, camera);
+            }}
+            animate();
+        }}
+
+        if (typeof THREE !== 'undefined') initScene();
+    </script>
+</body>
+</html>
+"#,
+            (size.length() * 0.55).max(0.5),
+            options.orthographic,
+            size.length().max(10.0),
+            bounds.min.y,
+            center.x, center.y, center.z,
+            camera_distance
+        )?;
+
+        Ok(())
+    }
+}
+
+/// The `ifcType`, `storey`, `globalId` and `properties` fields of a
+/// JavaScript element object
+fn write_metadata_js(file: &mut impl Write, metadata: &ElementMetadata) -> std::io::Result<()> {
+    let or_null = |value: &Option<String>| value.as_deref().map_or("null".to_string(), js_string);
+    writeln!(file, "                ifcType: {},", or_null(&metadata.ifc_type))?;
+    writeln!(file, "                storey: {},", or_null(&metadata.storey))?;
+    writeln!(file, "                globalId: {},", or_null(&metadata.global_id))?;
+    write!(file, "                properties: [")?;
+    for (j, (key, value)) in metadata.properties.iter().enumerate() {
+        if j > 0 { write!(file, ",")?; }
+        write!(file, "[{},{}]", js_string(key), js_string(value))?;
+    }
+    writeln!(file, "],")
+}
+
+/// The `material` field of a JavaScript mesh object
+fn write_material_js(file: &mut impl Write, material: &Material) -> std::io::Result<()> {
+    writeln!(file, "                material: {{ color: [{}, {}, {}], opacity: {}, metalness: {}, roughness: {}, doubleSided: {}, map: {} }},",
+        material.base_color[0], material.base_color[1], material.base_color[2],
+        material.alpha, material.metallic, material.roughness, material.double_sided,
+        material.texture.as_deref().map_or("null".to_string(), js_string))
+}
+
+/// The `positions`, `normals` and `indices` fields of a JavaScript mesh
+/// object
+fn write_geometry_js(file: &mut impl Write, mesh: &TriangleMesh) -> std::io::Result<()> {
+    // Positions and normals as f32 truncated to 2 decimals
+    write!(file, "                positions: [")?;
+    for (j, pos) in mesh.positions.iter().enumerate() {
+        if j > 0 { write!(file, ",")?; }
+        write!(file, "{:.2},{:.2},{:.2}", pos.x as f32, pos.y as f32, pos.z as f32)?;
+    }
+    writeln!(file, "],")?;
+
+    write!(file, "                normals: [")?;
+    for (j, norm) in mesh.normals.iter().enumerate() {
+        if j > 0 { write!(file, ",")?; }
+        write!(file, "{:.2},{:.2},{:.2}", norm.x as f32, norm.y as f32, norm.z as f32)?;
+    }
+    writeln!(file, "],")?;
+
+    write!(file, "                indices: [")?;
+    for (j, idx) in mesh.indices.iter().enumerate() {
+        if j > 0 { write!(file, ",")?; }
+        write!(file, "{}", idx)?;
+    }
+    writeln!(file, "],")
+}
+
+/// The closing `edges` field of a JavaScript mesh object: feature edges
+/// sharper than `crease_angle` as segment endpoint pairs, empty without one
+fn write_edges_js(
+    file: &mut impl Write,
+    mesh: &TriangleMesh,
+    crease_angle: Option<f64>,
+) -> std::io::Result<()> {
+    write!(file, "                edges: [")?;
+    if let Some(crease_angle) = crease_angle {
+        let lines = feature_edges(mesh, crease_angle);
+        for (j, &idx) in lines.indices.iter().enumerate() {
+            if j > 0 { write!(file, ",")?; }
+            let pos = lines.positions[idx as usize];
+            write!(file, "{:.2},{:.2},{:.2}", pos.x as f32, pos.y as f32, pos.z as f32)?;
+        }
+    }
+    writeln!(file, "]")
+}
+
+/// One entry of a JavaScript view array
+fn write_view_js(file: &mut impl Write, view: &CameraView) -> std::io::Result<()> {
+    let ortho_height = match view.projection {
+        Projection::Orthographic { height } => height.to_string(),
+        Projection::Perspective => "null".to_string(),
+    };
+    writeln!(file, "            {{ name: {}, eye: [{}, {}, {}], target: [{}, {}, {}], up: [{}, {}, {}], fov: {}, orthoHeight: {} }},",
+        js_string(&view.name),
+        view.eye.x, view.eye.y, view.eye.z,
+        view.target.x, view.target.y, view.target.z,
+        view.up.x, view.up.y, view.up.z,
+        view.fov_y.to_degrees(), ortho_height)
+}
+
+/// Escape text for use inside HTML element content
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::ElementMetadata;
+    use crate::test_util::create_test_triangle;
+    use cst_math::DVec3;
+
+    #[test]
+    fn test_html_export() {
+        let mut scene = Scene::new();
+        let mesh = create_test_triangle();
+        scene.add_mesh("TestTriangle", mesh, [0.5, 0.6, 0.7]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let html_path = dir.path().join("test_scene.html");
+
+        let result = scene.export_html(&html_path);
+        assert!(result.is_ok());
+
+        // Check file was created and has content
+        let metadata = std::fs::metadata(&html_path);
+        assert!(metadata.is_ok());
+        assert!(metadata.unwrap().len() > 0);
+
+        // Read file and check for key elements
+        let content = std::fs::read_to_string(&html_path).unwrap();
+        assert!(content.contains("<!DOCTYPE html>"));
+        assert!(content.contains("three.min.js"));
+        assert!(content.contains("TestTriangle"));
+        assert!(content.contains("meshData"));
+    }
+
+    #[test]
+    fn test_html_export_edge_overlay() {
+        let mut scene = Scene::new();
+        scene.add_mesh("TestTriangle", create_test_triangle(), [0.5, 0.6, 0.7]);
+        let dir = tempfile::tempdir().unwrap();
+        let html_path = dir.path().join("test_scene_edges.html");
+
+        scene.export_html(&html_path).unwrap();
+        let content = std::fs::read_to_string(&html_path).unwrap();
+        assert!(content.contains("edges: []"));
+
+        let options = HtmlExportOptions {
+            edge_overlay: Some(30f64.to_radians()),
+            ..Default::default()
+        };
+        scene.export_html_with_options(&html_path, &options).unwrap();
+        let content = std::fs::read_to_string(&html_path).unwrap();
+        // Three boundary segments of the open triangle
+        let edges_line = content.lines().find(|l| l.contains("edges: [")).unwrap();
+        assert_eq!(edges_line.matches(',').count(), 3 * 2 * 3 - 1);
+        assert!(content.contains("THREE.LineSegments"));
+    }
+
+    #[test]
+    fn test_html_export_element_controls() {
+        let mut scene = Scene::new();
+        scene.add_element(
+            "Wall <A>",
+            create_test_triangle(),
+            [0.5, 0.6, 0.7],
+            ElementMetadata {
+                ifc_type: Some("IfcWall".into()),
+                storey: Some("Level 1".into()),
+                global_id: Some("2O2Fr$t4X7Zf8NOew3FLOH".into()),
+                properties: vec![("Pset_WallCommon.IsExternal".into(), "TRUE".into())],
+            },
+        );
+        scene.add_mesh("Plain", create_test_triangle(), [0.5, 0.6, 0.7]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let html_path = dir.path().join("test_scene_elements.html");
+        scene.export_html(&html_path).unwrap();
+        let content = std::fs::read_to_string(&html_path).unwrap();
+
+        assert!(content.contains(r#"<div class="mesh-item" data-index="1">"#));
+        assert!(content.contains("Wall &lt;A&gt;"));
+        assert!(content.contains("IfcWall · Level 1 · 1 triangles"));
+        assert!(content.contains(r#"name: "Wall \u003cA>","#));
+        assert!(content.contains(r#"ifcType: "IfcWall","#));
+        assert!(content.contains("storey: null,"));
+        assert!(content.contains(r#"globalId: "2O2Fr$t4X7Zf8NOew3FLOH","#));
+        assert!(content.contains(r#"properties: [["Pset_WallCommon.IsExternal","TRUE"]],"#));
+        assert!(content.contains("properties: [],"));
+        assert!(content.contains(r#"<div id="properties""#));
+        assert!(content.contains("raycaster.intersectObjects"));
+        assert!(content.contains("id=\"show-all\""));
+        assert!(content.contains("function setVisible"));
+        assert!(content.contains("area: 0.500,"));
+        assert!(content.contains(r#"<button id="measure""#));
+        assert!(content.contains("function addMeasurePoint"));
+    }
+
+    #[test]
+    fn test_js_string_escaping() {
+        assert_eq!(js_string("plain"), "\"plain\"");
+        assert_eq!(js_string("a\"b\\c\nd"), r#""a\"b\\c\nd""#);
+        assert_eq!(js_string("</script>"), r#""\u003c/script>""#);
+    }
+
+    #[test]
+    fn test_html_export_spatial_tree() {
+        let mut scene = Scene::new();
+        scene.add_mesh("Slab", create_test_triangle(), [0.5, 0.6, 0.7]);
+        scene.add_mesh("Wall", create_test_triangle(), [0.5, 0.6, 0.7]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let html_path = dir.path().join("test_scene_tree.html");
+        scene.export_html(&html_path).unwrap();
+        let content = std::fs::read_to_string(&html_path).unwrap();
+        assert!(content.contains("const spatialTree = null;"));
+
+        let mut storey = SpatialTreeNode::new("Level \"1\"", "IfcBuildingStorey");
+        storey.meshes = vec![0, 1];
+        let mut building = SpatialTreeNode::new("Building", "IfcBuilding");
+        building.children.push(storey);
+        scene.spatial_tree = Some(building);
+
+        scene.export_html(&html_path).unwrap();
+        let content = std::fs::read_to_string(&html_path).unwrap();
+        assert!(content.contains(concat!(
+            r#"const spatialTree = {"name":"Building","kind":"IfcBuilding","meshes":[],"children":["#,
+            r#"{"name":"Level \"1\"","kind":"IfcBuildingStorey","meshes":[0, 1],"children":[]}]};"#
+        )));
+        assert!(content.contains("function buildTreeNode"));
+    }
+
+    #[test]
+    fn test_html_export_instanced_groups() {
+        let mut scene = Scene::new();
+        let mut window = ElementMetadata {
+            ifc_type: Some("IFCWINDOW".to_string()),
+            global_id: Some("2O2Fr$t4X7Zf8NOew3FLOH".to_string()),
+            ..Default::default()
+        };
+        let identity = [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0];
+        let mut moved = identity;
+        moved[12] = 5.0;
+        let mut metadata = vec![window.clone()];
+        window.global_id = Some("1kTvXnbbzCWw8lcMd1dR4o".to_string());
+        metadata.push(window);
+        scene.add_instanced_elements(
+            "Window",
+            create_test_triangle(),
+            [0.2, 0.4, 0.8],
+            vec![identity, moved],
+            metadata,
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let html_path = dir.path().join("instanced.html");
+        scene.export_html(&html_path).unwrap();
+        let content = std::fs::read_to_string(&html_path).unwrap();
+        assert!(content.contains("const instanceData = ["));
+        assert!(content.contains("0,0,0,1,1,0,0,0,0,1,0,0,0,0,1,0,5,0,0,1]"));
+        assert!(content.contains(r#"globalId: "2O2Fr$t4X7Zf8NOew3FLOH","#));
+        assert!(content.contains(r#"globalId: "1kTvXnbbzCWw8lcMd1dR4o","#));
+        assert!(content.contains("new THREE.InstancedMesh("));
+        assert!(content.contains("hit.instanceId"));
+    }
+
+    #[test]
+    fn test_html_export_camera_views() {
+        let mut scene = Scene::new();
+        scene.add_mesh("Slab", create_test_triangle(), [0.5, 0.5, 0.5]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let html_path = dir.path().join("test_scene_views.html");
+        scene.export_html(&html_path).unwrap();
+        let content = std::fs::read_to_string(&html_path).unwrap();
+        assert!(!content.contains(r#"<select id="views">"#));
+        assert!(content.contains("const cameraViews = [\n        ];"));
+        // Standard views are always there for the number keys
+        let standard = &content[content.find("const standardViews = [").unwrap()..];
+        assert!(standard.starts_with("const standardViews = [\n            { name: \"Top\","));
+        assert!(standard.contains(r#"{ name: "Isometric SW", "#));
+        assert!(!standard[..standard.find("];").unwrap()].contains("orthoHeight: null"));
+
+        let mut camera = Camera {
+            eye: DVec3::new(0.0, 10.0, 0.0),
+            target: DVec3::ZERO,
+            up: DVec3::Z,
+            ..Default::default()
+        };
+        scene.views.push(CameraView::from_camera("Entrance <west>", &camera));
+        camera.set_orthographic();
+        scene.views.push(CameraView::from_camera("Plan", &camera));
+        scene.export_html(&html_path).unwrap();
+        let content = std::fs::read_to_string(&html_path).unwrap();
+
+        assert!(content.contains(r#"<option value="0">Entrance &lt;west&gt;</option>"#));
+        assert!(content.contains(r#"<option value="1">Plan</option>"#));
+        assert!(content.contains(
+            r#"{ name: "Entrance \u003cwest>", eye: [0, 10, 0], target: [0, 0, 0], up: [0, 0, 1], fov: 45, orthoHeight: null },"#
+        ));
+        assert!(content.contains(r#"name: "Plan""#));
+        assert!(!content.contains("orthoHeight: null },\n        ];"));
+        assert!(content.contains("function applyView"));
+    }
+
+    #[test]
+    fn test_html_export_embedded_three_js() {
+        let mut scene = Scene::new();
+        scene.add_mesh("Triangle", create_test_triangle(), [0.5, 0.6, 0.7]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let library = dir.path().join("test_three_stub.js");
+        std::fs::write(&library, "var THREE = {}; var tag = '</script>';").unwrap();
+        let html_path = dir.path().join("test_scene_offline.html");
+
+        let options = HtmlExportOptions {
+            three_js: Some(library.clone()),
+            ..Default::default()
+        };
+        scene.export_html_with_options(&html_path, &options).unwrap();
+        let content = std::fs::read_to_string(&html_path).unwrap();
+        assert!(!content.contains("cdnjs.cloudflare.com"));
+        assert!(content.contains(r"<script>var THREE = {}; var tag = '<\/script>';</script>"));
+        assert!(content.contains("new THREE.Spherical()"));
+
+        // A missing library is an error rather than a silently broken page
+        let missing = HtmlExportOptions {
+            three_js: Some(dir.path().join("no_such_three.min.js")),
+            ..Default::default()
+        };
+        assert!(scene.export_html_with_options(&html_path, &missing).is_err());
+    }
+
+    #[test]
+    fn test_html_export_orthographic() {
+        let mut scene = Scene::new();
+        scene.add_mesh("TestTriangle", create_test_triangle(), [0.5, 0.6, 0.7]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let html_path = dir.path().join("test_scene_ortho.html");
+        let options = HtmlExportOptions {
+            orthographic: true,
+            ..Default::default()
+        };
+        scene.export_html_with_options(&html_path, &options).unwrap();
+
+        let content = std::fs::read_to_string(&html_path).unwrap();
+        assert!(content.contains("THREE.OrthographicCamera"));
+        assert!(content.contains("let camera = true ? orthographicCamera"));
+
+        scene.export_html(&html_path).unwrap();
+        let content = std::fs::read_to_string(&html_path).unwrap();
+        assert!(content.contains("let camera = false ? orthographicCamera"));
+    }
+    #[test]
+    fn test_html_export_section_planes() {
+        use cst_math::plane::Plane;
+        use cst_math::{Point3, Vector3};
+
+        let mut scene = Scene::new();
+        scene.add_mesh("TestTriangle", create_test_triangle(), [0.5, 0.6, 0.7]);
+        scene.add_section_plane(Plane::new(Point3::new(0.0, 0.5, 0.0), Vector3::new(0.0, -1.0, 0.0)));
+
+        let dir = tempfile::tempdir().unwrap();
+        let html_path = dir.path().join("test_scene_section.html");
+        scene.export_html(&html_path).unwrap();
+        let content = std::fs::read_to_string(&html_path).unwrap();
+        assert!(content.contains("const clipPlaneData = [[0,-1,0,0.5]];"));
+        assert!(content.contains("clippingPlanes: clipPlanes"));
+    }
+}
@@ -0,0 +1,485 @@
+//! One-call conversion of IFC files to scenes and exchange formats.
+//!
+//! Parses and tessellates an IFC file with [`cst_ifc`], builds a [`Scene`]
+//! with one mesh per element under the file's spatial structure, and
+//! writes it with the scene exporters. Through the cache next to the IFC file when
+//! [`IfcPipelineOptions::cache`] is set.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+use cst_core::{ProductId, Result};
+use cst_ifc::ifc_assembly::IfcAssembly;
+use cst_ifc::ifc_cache::{self, CachedMesh, CachedModel};
+use cst_ifc::ifc_options::IfcPipelineOptions;
+use cst_ifc::ifc_progress::{NoProgress, ProgressSink};
+use cst_ifc::ifc_reader::instance_groups;
+use cst_ifc::ifc_spatial::{SpatialKind, SpatialNode};
+use cst_mesh::TriangleMesh;
+
+use crate::scene::{ElementMetadata, Scene, SpatialTreeNode};
+
+/// Read and tessellate the IFC file at `ifc_path` into a scene.
+pub fn ifc_to_scene(ifc_path: &Path, options: &IfcPipelineOptions) -> Result<Scene> {
+    ifc_to_scene_with_progress(ifc_path, options, &NoProgress)
+}
+
+/// Like [`ifc_to_scene`], reporting progress to and polling cancellation
+/// from `progress`.
+pub fn ifc_to_scene_with_progress(
+    ifc_path: &Path,
+    options: &IfcPipelineOptions,
+    progress: &dyn ProgressSink,
+) -> Result<Scene> {
+    let model = if options.cache {
+        ifc_cache::load_or_build(ifc_path, options, progress)?
+    } else {
+        CachedModel::build(ifc_path, options, progress)?
+    };
+    Ok(model_scene(&model, options))
+}
+
+/// Convert the IFC file at `ifc_path` to Wavefront OBJ at `obj_path`, with
+/// its material library next to it.
+pub fn ifc_to_obj(ifc_path: &Path, obj_path: &Path, options: &IfcPipelineOptions) -> Result<()> {
+    ifc_to_scene(ifc_path, options)?.export_obj(obj_path)?;
+    Ok(())
+}
+
+/// Convert the IFC file at `ifc_path` to binary STL at `stl_path`.
+pub fn ifc_to_stl(ifc_path: &Path, stl_path: &Path, options: &IfcPipelineOptions) -> Result<()> {
+    ifc_to_scene(ifc_path, options)?.export_stl(stl_path)?;
+    Ok(())
+}
+
+/// Convert the IFC file at `ifc_path` to binary glTF at `glb_path`.
+#[cfg(feature = "gltf")]
+pub fn ifc_to_glb(ifc_path: &Path, glb_path: &Path, options: &IfcPipelineOptions) -> Result<()> {
+    ifc_to_scene(ifc_path, options)?.export_glb(glb_path)?;
+    Ok(())
+}
+
+/// One mesh per element of `model` with its IFC metadata, under the
+/// file's project -> site -> building -> storey tree with the storey
+/// elevations. Stairs group their parts under their storey.
+///
+/// Elements are kept in file order until the next one would exceed
+/// `options.triangle_budget`; elements without a color get
+/// `options.default_color`. With `options.instancing`, elements placed
+/// from the same representation map item in the same color become one
+/// instanced group instead, charged to the budget once and left out of
+/// the tree.
+pub fn model_scene(model: &CachedModel, options: &IfcPipelineOptions) -> Scene {
+    let mut scene = Scene::new();
+    let mut budget = options.triangle_budget.unwrap_or(usize::MAX);
+    let mut storeys: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    // Meshes of each assembly, grouped under one node instead of the storey
+    let mut assemblies: Vec<Vec<usize>> = vec![Vec::new(); model.assemblies.len()];
+    let mut assembly_of: HashMap<ProductId, usize> = HashMap::new();
+    for (index, assembly) in model.assemblies.iter().enumerate() {
+        for &id in std::iter::once(&assembly.id).chain(&assembly.parts) {
+            assembly_of.entry(id).or_insert(index);
+        }
+    }
+    let mut groups = if options.instancing {
+        instanced_groups(model, options)
+    } else {
+        HashMap::new()
+    };
+    let instanced: HashSet<usize> = groups.values().flat_map(|(_, members)| members).copied().collect();
+    for (index, cached) in model.meshes.iter().enumerate() {
+        let triangles = cached.mesh.indices.len() / 3;
+        if triangles == 0 {
+            continue;
+        }
+        let group = groups.remove(&index);
+        // Later members were added with the group's first one
+        if group.is_none() && instanced.contains(&index) {
+            continue;
+        }
+        if triangles > budget {
+            break;
+        }
+        budget -= triangles;
+
+        let color = options.color_or_default(cached.color);
+        if let Some((mesh, members)) = group {
+            let members = members.iter().map(|&member| &model.meshes[member]);
+            let transforms = members
+                .clone()
+                .filter_map(|member| member.instance.map(|instance| instance.matrix_f32()))
+                .collect();
+            let metadata = members.map(|member| element_metadata(model, member)).collect();
+            scene.add_instanced_elements(&cached.mesh.name, mesh, color, transforms, metadata);
+            continue;
+        }
+
+        let product = cached.product.and_then(|id| model.products.get(&id));
+        let assembly = cached.product.and_then(|id| assembly_of.get(&id).copied());
+        if let Some(index) = assembly {
+            assemblies[index].push(scene.meshes.len());
+        } else if let Some(storey) = product.and_then(|p| p.storey.as_deref()) {
+            storeys.entry(storey).or_default().push(scene.meshes.len());
+        }
+        let metadata = element_metadata(model, cached);
+        scene.add_element(&cached.mesh.name, triangle_mesh(cached), color, metadata);
+    }
+
+    // The file's spatial structure, with each storey's meshes and stairs
+    let mut root = match &model.spatial {
+        Some(project) => tree_node(project, &model.elevations),
+        // Only for files without the IfcProject that IFC requires
+        None => SpatialTreeNode::new("Project", SpatialKind::Project.ifc_name()),
+    };
+    // A stair's parts share its storey; the stair itself often has no mesh
+    let assembly_storey = |assembly: &IfcAssembly| {
+        std::iter::once(&assembly.id)
+            .chain(&assembly.parts)
+            .find_map(|id| model.products.get(id)?.storey.as_deref())
+    };
+    for name in model.assemblies.iter().filter_map(assembly_storey) {
+        storeys.entry(name).or_default();
+    }
+    for (name, meshes) in storeys {
+        match storey_mut(&mut root, name) {
+            Some(storey) => storey.meshes = meshes,
+            // Storeys the spatial structure leaves out go under its root
+            None => {
+                let mut storey = SpatialTreeNode::new(name, STOREY_KIND);
+                storey.meshes = meshes;
+                storey.elevation = model.elevations.get(name).copied();
+                root.children.push(storey);
+            }
+        }
+    }
+    for (assembly, meshes) in model.assemblies.iter().zip(assemblies) {
+        if meshes.is_empty() {
+            continue;
+        }
+        let mut node = SpatialTreeNode::new(&assembly.name, "IfcStair");
+        node.meshes = meshes;
+        // Under the stair's storey, or the root when it has none
+        match assembly_storey(assembly).and_then(|name| storey_mut(&mut root, name)) {
+            Some(storey) => storey.children.push(node),
+            None => root.children.push(node),
+        }
+    }
+    scene.spatial_tree = Some(root);
+    scene
+}
+
+/// The IFC metadata of the element `cached` was built from.
+fn element_metadata(model: &CachedModel, cached: &CachedMesh) -> ElementMetadata {
+    cached
+        .product
+        .and_then(|id| model.products.get(&id))
+        .map(|product| ElementMetadata {
+            ifc_type: Some(product.ifc_type.clone()),
+            storey: product.storey.clone(),
+            global_id: product.global_id.clone(),
+            properties: product.properties.clone(),
+        })
+        .unwrap_or_default()
+}
+
+fn triangle_mesh(cached: &CachedMesh) -> TriangleMesh {
+    TriangleMesh {
+        positions: cached.mesh.positions.clone(),
+        normals: cached.mesh.normals.clone(),
+        indices: cached.mesh.indices.clone(),
+        uvs: vec![],
+    }
+}
+
+/// Meshes placed from one representation map item in one color, two or
+/// more to a group, keyed by their first member: the shared geometry in
+/// map coordinates and the members in file order. Placements with a
+/// singular transform stay separate meshes.
+fn instanced_groups(
+    model: &CachedModel,
+    options: &IfcPipelineOptions,
+) -> HashMap<usize, (TriangleMesh, Vec<usize>)> {
+    let instances: Vec<_> = model
+        .meshes
+        .iter()
+        .map(|cached| cached.instance.filter(|_| !cached.mesh.indices.is_empty()))
+        .collect();
+    let mut groups = HashMap::new();
+    for group in instance_groups(&instances) {
+        // Copies of one map can still be colored differently
+        let mut by_color: BTreeMap<[u32; 3], Vec<usize>> = BTreeMap::new();
+        for &index in &group.members {
+            let color = options.color_or_default(model.meshes[index].color);
+            by_color.entry(color.map(f32::to_bits)).or_default().push(index);
+        }
+        for members in by_color.into_values().filter(|members| members.len() > 1) {
+            // Any member brought back into map coordinates serves as
+            // the shared geometry
+            let first = &model.meshes[members[0]];
+            let mut mesh = triangle_mesh(first);
+            if first.instance.is_some_and(|instance| instance.to_map_coordinates(&mut mesh)) {
+                groups.insert(members[0], (mesh, members));
+            }
+        }
+    }
+    groups
+}
+
+const STOREY_KIND: &str = "IfcBuildingStorey";
+
+/// `node` and the spatial elements below it, without meshes; storeys get
+/// their elevation.
+fn tree_node(node: &SpatialNode, elevations: &BTreeMap<String, f64>) -> SpatialTreeNode {
+    let mut tree = SpatialTreeNode::new(&node.name, node.kind.ifc_name());
+    if node.kind == SpatialKind::BuildingStorey {
+        tree.elevation = elevations.get(&node.name).copied();
+    }
+    tree.children = node
+        .children
+        .iter()
+        .map(|child| tree_node(child, elevations))
+        .collect();
+    tree
+}
+
+/// The first storey named `name` in the subtree of `node`.
+fn storey_mut<'a>(node: &'a mut SpatialTreeNode, name: &str) -> Option<&'a mut SpatialTreeNode> {
+    if node.kind == STOREY_KIND && node.name == name {
+        return Some(node);
+    }
+    node.children
+        .iter_mut()
+        .find_map(|child| storey_mut(child, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two unstyled walls on one storey
+    const MODEL: &str = "ISO-10303-21;
+HEADER;
+FILE_SCHEMA(('IFC2X3'));
+ENDSEC;
+DATA;
+#1= IFCCARTESIANPOINT((0.,0.,0.));
+#2= IFCCARTESIANPOINT((1.,0.,0.));
+#3= IFCCARTESIANPOINT((1.,1.,0.));
+#4= IFCCARTESIANPOINT((0.,1.,0.));
+#5= IFCPOLYLOOP((#1,#2,#3,#4));
+#6= IFCFACEOUTERBOUND(#5,.T.);
+#7= IFCFACE((#6));
+#8= IFCCLOSEDSHELL((#7));
+#9= IFCFACETEDBREP(#8);
+#10= IFCSHAPEREPRESENTATION($,'Body','Brep',(#9));
+#11= IFCPRODUCTDEFINITIONSHAPE($,$,(#10));
+#20= IFCWALL('2O2Fr$t4X7Zf8NOew3FLOH',$,'Wall A',$,$,$,#11,$);
+#21= IFCWALL('1kTvXnbbzCWw8lcMd1dR4o',$,'Wall B',$,$,$,#11,$);
+#30= IFCBUILDINGSTOREY('st1',$,'Level 1',$,$,$,$,$,.ELEMENT.,0.);
+#31= IFCRELCONTAINEDINSPATIALSTRUCTURE('r1',$,$,$,(#20,#21),#30);
+ENDSEC;
+END-ISO-10303-21;
+";
+
+    fn sample() -> &'static Path {
+        Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../samples/office.ifc"
+        ))
+    }
+
+    /// One mesh per element
+    fn options() -> IfcPipelineOptions {
+        IfcPipelineOptions {
+            unit_scale: Some(0.001),
+            instancing: false,
+            ..Default::default()
+        }
+    }
+
+    fn triangle_count(scene: &Scene) -> usize {
+        scene.meshes.iter().map(|m| m.mesh.triangle_count()).sum()
+    }
+
+    #[test]
+    fn test_ifc_round_trips_through_stl_obj_and_glb() {
+        let scene = ifc_to_scene(sample(), &options()).unwrap();
+        let triangles = triangle_count(&scene);
+        assert!(triangles > 0);
+        let dir = tempfile::tempdir().unwrap();
+
+        // STL: a triangle count after the header, then 50 bytes a triangle
+        let stl = dir.path().join("office.stl");
+        ifc_to_stl(sample(), &stl, &options()).unwrap();
+        let bytes = std::fs::read(&stl).unwrap();
+        let count = u32::from_le_bytes(bytes[80..84].try_into().unwrap()) as usize;
+        assert_eq!(count, triangles);
+        assert_eq!(bytes.len(), 84 + 50 * triangles);
+
+        // OBJ: one object per mesh, every triangle as a face, and the
+        // material library beside it
+        let obj = dir.path().join("office.obj");
+        ifc_to_obj(sample(), &obj, &options()).unwrap();
+        let text = std::fs::read_to_string(&obj).unwrap();
+        let lines = |prefix: &str| text.lines().filter(|l| l.starts_with(prefix)).count();
+        assert_eq!(lines("o "), scene.meshes.len());
+        assert_eq!(lines("f "), triangles);
+        let vertices: usize = scene.meshes.iter().map(|m| m.mesh.positions.len()).sum();
+        assert_eq!(lines("v "), vertices);
+        assert!(dir.path().join("office.mtl").exists());
+
+        // GLB: the header and the same bytes as exporting the scene
+        #[cfg(feature = "gltf")]
+        {
+            let glb = dir.path().join("office.glb");
+            ifc_to_glb(sample(), &glb, &options()).unwrap();
+            let bytes = std::fs::read(&glb).unwrap();
+            assert_eq!(&bytes[..4], b"glTF");
+            let length = u32::from_le_bytes(bytes[8..12].try_into().unwrap()) as usize;
+            assert_eq!(length, bytes.len());
+            assert_eq!(bytes, scene.to_glb());
+        }
+    }
+
+    #[test]
+    fn test_ifc_to_scene_respects_the_triangle_budget() {
+        let full = ifc_to_scene(sample(), &options()).unwrap();
+        let all = triangle_count(&full);
+        let options = IfcPipelineOptions {
+            triangle_budget: Some(all / 2),
+            ..options()
+        };
+        let scene = ifc_to_scene(sample(), &options).unwrap();
+        let triangles = triangle_count(&scene);
+        assert!(triangles > 0 && triangles <= all / 2);
+        // The elements in file order up to the first one over the budget
+        let next = &full.meshes[scene.meshes.len()];
+        assert!(triangles + next.mesh.triangle_count() > all / 2);
+        for (kept, original) in scene.meshes.iter().zip(&full.meshes) {
+            assert_eq!(kept.name, original.name);
+        }
+    }
+
+    #[test]
+    fn test_model_scene_instances_mapped_elements() {
+        let flat = ifc_to_scene(sample(), &options()).unwrap();
+        let options = IfcPipelineOptions {
+            instancing: true,
+            ..options()
+        };
+        let scene = ifc_to_scene(sample(), &options).unwrap();
+        // The windows are placed from representation maps
+        assert!(!scene.instanced_groups.is_empty());
+        let instances: usize = scene.instanced_groups.iter().map(|g| g.transforms.len()).sum();
+        assert_eq!(scene.meshes.len() + instances, flat.meshes.len());
+        for group in &scene.instanced_groups {
+            assert!(group.transforms.len() > 1);
+            assert_eq!(group.metadata.len(), group.transforms.len());
+            assert!(group.metadata.iter().all(|m| m.ifc_type.as_deref() == Some("IFCWINDOW")));
+            assert!(group.metadata.iter().all(|m| m.global_id.is_some()));
+        }
+        // Each instance lands where the element's own mesh is
+        let group = &scene.instanced_groups[0];
+        let element = flat
+            .meshes
+            .iter()
+            .find(|m| m.metadata.global_id == group.metadata[0].global_id)
+            .unwrap();
+        let expected = element.bounds().unwrap();
+        let placed = group.instance_bounds(0).unwrap();
+        assert!((placed.min - expected.min).length() < 1e-3);
+        assert!((placed.max - expected.max).length() < 1e-3);
+
+        // A budget charges the shared geometry once
+        let base: usize = scene.instanced_groups.iter().map(|g| g.mesh.triangle_count()).sum();
+        let budget = IfcPipelineOptions {
+            triangle_budget: Some(triangle_count(&scene) + base),
+            ..options
+        };
+        let limited = ifc_to_scene(sample(), &budget).unwrap();
+        assert_eq!(limited.meshes.len(), scene.meshes.len());
+        assert_eq!(limited.instanced_groups.len(), scene.instanced_groups.len());
+    }
+
+    #[test]
+    fn test_model_scene_follows_the_spatial_structure() {
+        let scene = ifc_to_scene(sample(), &options()).unwrap();
+        let project = scene.spatial_tree.as_ref().unwrap();
+        assert_eq!((project.name.as_str(), project.kind.as_str()), ("Sample Office", "IfcProject"));
+        let site = &project.children[0];
+        assert_eq!(site.kind, "IfcSite");
+        let building = &site.children[0];
+        assert_eq!((building.name.as_str(), building.kind.as_str()), ("Office Block", "IfcBuilding"));
+
+        let storeys: Vec<(&str, Option<f64>)> = building
+            .children
+            .iter()
+            .map(|s| (s.name.as_str(), s.elevation))
+            .collect();
+        assert_eq!(
+            storeys,
+            [
+                ("Level 1", Some(0.0)),
+                ("Level 2", Some(3.5)),
+                ("Level 3", Some(7.0)),
+                ("Level 4", Some(10.5))
+            ]
+        );
+        // Every mesh sits in exactly one storey
+        let mut meshes: Vec<usize> = building.children.iter().flat_map(|s| s.meshes.clone()).collect();
+        meshes.sort_unstable();
+        assert_eq!(meshes, (0..scene.meshes.len()).collect::<Vec<_>>());
+        for storey in &building.children {
+            for &i in &storey.meshes {
+                assert_eq!(scene.meshes[i].metadata.storey.as_deref(), Some(storey.name.as_str()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_model_scene_without_a_project_keeps_the_storeys() {
+        let options = IfcPipelineOptions::default();
+        let model = CachedModel::from_reader(MODEL.as_bytes(), &options, &NoProgress).unwrap();
+        assert!(model.spatial.is_none());
+        let tree = model_scene(&model, &options).spatial_tree.unwrap();
+        assert_eq!(tree.kind, "IfcProject");
+        assert_eq!(tree.children.len(), 1);
+        assert_eq!(tree.children[0].name, "Level 1");
+        assert_eq!(tree.children[0].meshes, [0, 1]);
+        assert_eq!(tree.children[0].elevation, Some(0.0));
+    }
+
+    #[test]
+    fn test_model_scene_draws_unstyled_elements_in_the_default_color() {
+        let gray = [0.25, 0.5, 0.75];
+        let options = IfcPipelineOptions {
+            type_colors: Vec::new(),
+            default_color: gray,
+            ..Default::default()
+        };
+        let model = CachedModel::from_reader(MODEL.as_bytes(), &options, &NoProgress).unwrap();
+        assert!(model.meshes.iter().all(|m| m.color.is_none()));
+        let scene = model_scene(&model, &options);
+        assert_eq!(scene.meshes.len(), 2);
+        assert!(scene.meshes.iter().all(|m| m.material.base_color == gray));
+
+        // Type colors still apply to unstyled elements unless turned off
+        let options = IfcPipelineOptions {
+            default_color: gray,
+            ..Default::default()
+        };
+        let model = CachedModel::from_reader(MODEL.as_bytes(), &options, &NoProgress).unwrap();
+        let scene = model_scene(&model, &options);
+        let wall = options.type_color("IFCWALL").unwrap();
+        assert!(scene.meshes.iter().all(|m| m.material.base_color == wall));
+    }
+
+    #[test]
+    fn test_ifc_to_obj_reports_a_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing.ifc");
+        let result = ifc_to_obj(&missing, &dir.path().join("out.obj"), &options());
+        assert!(result.is_err());
+        assert!(!dir.path().join("out.obj").exists());
+    }
+}
@@ -1,8 +1,62 @@
-pub mod pipeline;
+pub mod bcf;
+pub mod bvh;
 pub mod camera;
+#[cfg(feature = "gltf")]
+pub mod gltf;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+#[cfg(feature = "html")]
+pub mod html;
+#[cfg(feature = "ifc")]
+pub mod ifc;
+pub mod material;
+pub mod measure;
+#[cfg(feature = "gltf")]
+pub mod meshopt;
+pub mod obj;
+#[cfg(feature = "render")]
+pub mod offscreen;
+#[cfg(feature = "render")]
+pub mod pipeline;
+pub mod plan;
+pub mod point_cloud;
 pub mod scene;
+pub mod stl;
+pub mod streaming;
+#[cfg(test)]
+mod test_util;
 
 // Re-export main types
-pub use camera::Camera;
-pub use pipeline::{GpuVertex, RenderMesh, CameraUniforms, prepare_mesh};
-pub use scene::{Scene, SceneMesh};
+pub use bcf::{BcfCamera, BcfProjection, BcfViewpoint};
+pub use bvh::Bvh;
+pub use camera::{
+    aabb_in_frustum, load_views, save_views, Camera, CameraView, Projection, StandardView,
+    DEFAULT_FIT_PADDING,
+};
+#[cfg(feature = "gltf")]
+pub use gltf::GltfExportOptions;
+#[cfg(feature = "gpu")]
+pub use gpu::OffscreenRenderer;
+#[cfg(feature = "html")]
+pub use html::HtmlExportOptions;
+#[cfg(all(feature = "ifc", feature = "gltf"))]
+pub use ifc::ifc_to_glb;
+#[cfg(feature = "ifc")]
+pub use ifc::{ifc_to_obj, ifc_to_scene, ifc_to_scene_with_progress, ifc_to_stl, model_scene};
+pub use material::Material;
+pub use measure::{Distance, Segment};
+#[cfg(feature = "render")]
+pub use offscreen::RgbaImage;
+#[cfg(feature = "render")]
+pub use pipeline::{
+    prepare_instanced, prepare_instanced_with_layout, prepare_lines, prepare_mesh,
+    prepare_mesh_with_layout, prepare_mesh_with_material, CameraUniforms, ClipPlaneUniforms,
+    GpuVertex, MaterialUniforms, RenderInstances, RenderLines, RenderMesh, VertexAttribute,
+    VertexFormat, VertexLayout, INSTANCE_ATTRIBUTES,
+};
+pub use plan::{FloorPlan, FloorPlanOptions, PlanElement, PlanOutline};
+pub use point_cloud::{PointCloud, PointCloudOptions};
+pub use scene::{
+    ElementMetadata, PickHit, PickTarget, Scene, SceneIndex, SceneMesh, SceneNode, SpatialTreeNode,
+};
+pub use streaming::{BinaryMeshOptions, NormalEncoding, MESH_READER_JS, WEB_VIEWER_HTML};
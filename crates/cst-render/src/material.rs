@@ -0,0 +1,88 @@
+//! Surface appearance of scene meshes.
+//!
+//! A metallic-roughness model matching glTF 2.0, so the same description
+//! feeds the GPU uniforms, the glTF and binary exporters and the HTML viewer.
+
+use serde::{Deserialize, Serialize};
+
+/// PBR material of a mesh or instanced group.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Material {
+    /// Linear RGB base color
+    pub base_color: [f32; 3],
+    /// Opacity; below 1.0 the mesh is drawn blended
+    pub alpha: f32,
+    pub metallic: f32,
+    pub roughness: f32,
+    /// Render back faces too (IFC geometry often has inconsistent winding)
+    pub double_sided: bool,
+    /// URI of a base color texture, sampled with the mesh UVs
+    pub texture: Option<String>,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self {
+            base_color: [0.8, 0.8, 0.8],
+            alpha: 1.0,
+            metallic: 0.0,
+            roughness: 0.5,
+            double_sided: true,
+            texture: None,
+        }
+    }
+}
+
+impl Material {
+    /// Opaque, non-metallic material of the given color
+    pub fn from_color(base_color: [f32; 3]) -> Self {
+        Self {
+            base_color,
+            ..Default::default()
+        }
+    }
+
+    /// Set the opacity
+    pub fn with_alpha(mut self, alpha: f32) -> Self {
+        self.alpha = alpha.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Whether the material needs alpha blending
+    pub fn is_transparent(&self) -> bool {
+        self.alpha < 1.0
+    }
+
+    /// Base color and alpha as RGBA
+    pub fn base_color_rgba(&self) -> [f32; 4] {
+        let [r, g, b] = self.base_color;
+        [r, g, b, self.alpha]
+    }
+}
+
+impl From<[f32; 3]> for Material {
+    fn from(base_color: [f32; 3]) -> Self {
+        Self::from_color(base_color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_color() {
+        let material: Material = [0.1, 0.2, 0.3].into();
+        assert_eq!(material.base_color_rgba(), [0.1, 0.2, 0.3, 1.0]);
+        assert!(!material.is_transparent());
+        assert!(material.double_sided);
+        assert!(material.texture.is_none());
+    }
+
+    #[test]
+    fn test_alpha() {
+        let glass = Material::from_color([0.6, 0.8, 0.9]).with_alpha(0.3);
+        assert!(glass.is_transparent());
+        assert_eq!(Material::default().with_alpha(1.5).alpha, 1.0);
+    }
+}
@@ -0,0 +1,431 @@
+//! Distances, edge lengths and areas for measuring elements.
+//!
+//! The free functions work on plain meshes, for headless QA scripts. The
+//! [`Scene`] methods take pick targets and hits like the viewer's measure
+//! tool does, and apply instance transforms so results are in world space.
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+use cst_math::distance::{
+    closest_point_on_segment, closest_point_on_triangle, closest_points_on_triangles,
+};
+use cst_math::Point3;
+use cst_mesh::TriangleMesh;
+use serde::Serialize;
+
+use crate::bvh::Bvh;
+use crate::scene::{PickHit, PickTarget, Scene};
+
+/// Cosine of the largest angle between the normals of triangles that still
+/// count as one planar face.
+const COPLANAR_COS: f64 = 1.0 - 1e-9;
+
+/// Closest points between two pieces of geometry.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Distance {
+    /// Length of `from`-`to`; zero when the geometry touches
+    pub distance: f64,
+    /// Closest point on the first argument
+    pub from: Point3,
+    /// Closest point on the second argument
+    pub to: Point3,
+}
+
+impl Distance {
+    fn between(from: Point3, to: Point3) -> Self {
+        Self {
+            distance: from.distance(to),
+            from,
+            to,
+        }
+    }
+}
+
+/// A straight edge.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Segment {
+    pub start: Point3,
+    pub end: Point3,
+}
+
+impl Segment {
+    pub fn length(&self) -> f64 {
+        self.start.distance(self.end)
+    }
+}
+
+/// Minimum distance between two meshes, with the closest points.
+///
+/// `None` when either mesh has no triangles.
+pub fn mesh_distance(a: &TriangleMesh, b: &TriangleMesh) -> Option<Distance> {
+    mesh_distance_with(a, &Bvh::from_triangles(a), b, &Bvh::from_triangles(b))
+}
+
+/// Distance from a point to the closest point on a mesh.
+pub fn point_mesh_distance(point: Point3, mesh: &TriangleMesh) -> Option<Distance> {
+    point_mesh_distance_with(point, mesh, &Bvh::from_triangles(mesh))
+}
+
+/// Area of the planar face containing `triangle`: the triangles connected
+/// to it through shared edges that lie in the same plane.
+pub fn face_area(mesh: &TriangleMesh, triangle: usize) -> f64 {
+    planar_face(mesh, triangle)
+        .iter()
+        .map(|&t| triangle_area(&triangle_points(mesh, t)))
+        .sum()
+}
+
+/// The outline edge of the planar face containing `triangle` that is
+/// closest to `point`. Collinear pieces of the outline are joined, so an
+/// edge split by neighbouring faces still reads its full length.
+pub fn nearest_face_edge(mesh: &TriangleMesh, triangle: usize, point: Point3) -> Option<Segment> {
+    let face = planar_face(mesh, triangle);
+    let outline = face_outline(mesh, &face);
+    let distance = |s: &Segment| point.distance(closest_point_on_segment(point, s.start, s.end));
+    let mut edge = *outline
+        .iter()
+        .min_by(|a, b| distance(a).total_cmp(&distance(b)))?;
+
+    let direction = (edge.end - edge.start).normalize_or_zero();
+    let collinear =
+        |s: &Segment| (s.end - s.start).normalize_or_zero().dot(direction).abs() >= COPLANAR_COS;
+    // Each pass extends at most one end; the outline is finite
+    for _ in 0..outline.len() {
+        let next = outline.iter().find(|s| collinear(s) && s.start == edge.end);
+        let previous = outline.iter().find(|s| collinear(s) && s.end == edge.start);
+        match (next, previous) {
+            (Some(next), _) if next.end != edge.start => edge.end = next.end,
+            (_, Some(previous)) if previous.start != edge.end => edge.start = previous.start,
+            _ => break,
+        }
+    }
+    Some(edge)
+}
+
+pub(crate) fn mesh_distance_with(
+    a: &TriangleMesh,
+    a_bvh: &Bvh,
+    b: &TriangleMesh,
+    b_bvh: &Bvh,
+) -> Option<Distance> {
+    let mut closest = None;
+    a_bvh.closest_pair(b_bvh, |ta, tb| {
+        let (from, to) =
+            closest_points_on_triangles(triangle_points(a, ta), triangle_points(b, tb));
+        let distance = Distance::between(from, to);
+        if closest.map_or(true, |c: Distance| distance.distance < c.distance) {
+            closest = Some(distance);
+        }
+        distance.distance
+    })?;
+    closest
+}
+
+pub(crate) fn point_mesh_distance_with(
+    point: Point3,
+    mesh: &TriangleMesh,
+    bvh: &Bvh,
+) -> Option<Distance> {
+    let (triangle, _) = bvh.nearest(point, |t| {
+        let [a, b, c] = triangle_points(mesh, t);
+        point.distance(closest_point_on_triangle(point, a, b, c))
+    })?;
+    let [a, b, c] = triangle_points(mesh, triangle);
+    Some(Distance::between(
+        point,
+        closest_point_on_triangle(point, a, b, c),
+    ))
+}
+
+fn triangle_points(mesh: &TriangleMesh, triangle: usize) -> [Point3; 3] {
+    let tri = &mesh.indices[triangle * 3..triangle * 3 + 3];
+    [
+        mesh.positions[tri[0] as usize],
+        mesh.positions[tri[1] as usize],
+        mesh.positions[tri[2] as usize],
+    ]
+}
+
+fn triangle_area([a, b, c]: &[Point3; 3]) -> f64 {
+    (*b - *a).cross(*c - *a).length() * 0.5
+}
+
+/// Vertices are matched by position, so faces that were triangulated
+/// separately but share corners still connect.
+type PositionKey = [u64; 3];
+
+fn position_key(p: Point3) -> PositionKey {
+    // Adding 0.0 folds -0.0 into 0.0
+    [p.x + 0.0, p.y + 0.0, p.z + 0.0].map(f64::to_bits)
+}
+
+fn edge_key(a: Point3, b: Point3) -> (PositionKey, PositionKey) {
+    let (a, b) = (position_key(a), position_key(b));
+    if a <= b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+/// Triangles of the planar face containing `triangle`, found by flooding
+/// across shared edges to neighbours with the same normal.
+fn planar_face(mesh: &TriangleMesh, triangle: usize) -> Vec<usize> {
+    let count = mesh.indices.len() / 3;
+    if triangle >= count {
+        return Vec::new();
+    }
+    let normal = |t: usize| {
+        let [a, b, c] = triangle_points(mesh, t);
+        (b - a).cross(c - a).normalize_or_zero()
+    };
+    let reference = normal(triangle);
+    if reference == Point3::ZERO {
+        return vec![triangle];
+    }
+
+    let mut by_edge: HashMap<(PositionKey, PositionKey), Vec<usize>> = HashMap::new();
+    for t in 0..count {
+        let [a, b, c] = triangle_points(mesh, t);
+        for (s, e) in [(a, b), (b, c), (c, a)] {
+            by_edge.entry(edge_key(s, e)).or_default().push(t);
+        }
+    }
+
+    let mut face = vec![triangle];
+    let mut seen = HashSet::from([triangle]);
+    let mut next = 0;
+    while let Some(&t) = face.get(next) {
+        next += 1;
+        let [a, b, c] = triangle_points(mesh, t);
+        for (s, e) in [(a, b), (b, c), (c, a)] {
+            for &neighbour in &by_edge[&edge_key(s, e)] {
+                if !seen.contains(&neighbour) && normal(neighbour).dot(reference) >= COPLANAR_COS {
+                    seen.insert(neighbour);
+                    face.push(neighbour);
+                }
+            }
+        }
+    }
+    face
+}
+
+/// Edges of `face` used by exactly one of its triangles, in winding order.
+fn face_outline(mesh: &TriangleMesh, face: &[usize]) -> Vec<Segment> {
+    let mut uses: HashMap<(PositionKey, PositionKey), usize> = HashMap::new();
+    let mut edges = Vec::with_capacity(face.len() * 3);
+    for &t in face {
+        let [a, b, c] = triangle_points(mesh, t);
+        for (start, end) in [(a, b), (b, c), (c, a)] {
+            *uses.entry(edge_key(start, end)).or_default() += 1;
+            edges.push(Segment { start, end });
+        }
+    }
+    edges.retain(|s| uses[&edge_key(s.start, s.end)] == 1);
+    edges
+}
+
+impl Scene {
+    /// Minimum distance between two meshes or instances, in world space.
+    pub fn distance(&self, a: PickTarget, b: PickTarget) -> Option<Distance> {
+        let (a_mesh, a_bvh) = self.world_geometry(a)?;
+        let (b_mesh, b_bvh) = self.world_geometry(b)?;
+        mesh_distance_with(&a_mesh, &a_bvh, &b_mesh, &b_bvh)
+    }
+
+    /// Distance from a world-space point to a mesh or instance; `from` is
+    /// the point itself.
+    pub fn distance_to_point(&self, target: PickTarget, point: Point3) -> Option<Distance> {
+        let (mesh, bvh) = self.world_geometry(target)?;
+        point_mesh_distance_with(point, &mesh, &bvh)
+    }
+
+    /// The face edge nearest to a pick hit, for edge length readouts.
+    pub fn picked_edge(&self, hit: &PickHit) -> Option<Segment> {
+        let (mesh, _) = self.world_geometry(hit.target)?;
+        nearest_face_edge(&mesh, hit.triangle, hit.point)
+    }
+
+    /// Area of the planar face under a pick hit.
+    pub fn picked_face_area(&self, hit: &PickHit) -> Option<f64> {
+        let (mesh, _) = self.world_geometry(hit.target)?;
+        (hit.triangle < mesh.triangle_count()).then(|| face_area(&mesh, hit.triangle))
+    }
+
+    /// Total surface area of a mesh or instance.
+    pub fn surface_area(&self, target: PickTarget) -> Option<f64> {
+        let (mesh, _) = self.world_geometry(target)?;
+        Some(mesh.surface_area())
+    }
+
+    /// The triangles of a pick target in world space, with their BVH.
+    /// Meshes use their cached hierarchy; instances are transformed.
+    fn world_geometry(&self, target: PickTarget) -> Option<(Cow<'_, TriangleMesh>, Cow<'_, Bvh>)> {
+        match target {
+            PickTarget::Mesh(i) => {
+                let scene_mesh = self.meshes.get(i)?;
+                Some((
+                    Cow::Borrowed(&scene_mesh.mesh),
+                    Cow::Borrowed(scene_mesh.bvh()),
+                ))
+            }
+            PickTarget::Instance { group, instance } => {
+                let ig = self.instanced_groups.get(group)?;
+                if instance >= ig.transforms.len() {
+                    return None;
+                }
+                let transform = ig.transform_matrix(instance);
+                let mesh = TriangleMesh {
+                    positions: ig
+                        .mesh
+                        .positions
+                        .iter()
+                        .map(|&p| transform.transform_point3(p))
+                        .collect(),
+                    normals: Vec::new(),
+                    indices: ig.mesh.indices.clone(),
+                    uvs: Vec::new(),
+                };
+                let bvh = Bvh::from_triangles(&mesh);
+                Some((Cow::Owned(mesh), Cow::Owned(bvh)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cst_math::ray::Ray;
+    use cst_math::{DMat4, DVec3, Vector3};
+
+    /// Axis-aligned box with shared corners, outward winding.
+    fn box_mesh(min: Point3, max: Point3) -> TriangleMesh {
+        let positions = (0..8)
+            .map(|i| {
+                Point3::new(
+                    if i & 1 == 0 { min.x } else { max.x },
+                    if i & 2 == 0 { min.y } else { max.y },
+                    if i & 4 == 0 { min.z } else { max.z },
+                )
+            })
+            .collect();
+        let quads = [
+            [0, 2, 3, 1],
+            [4, 5, 7, 6],
+            [0, 1, 5, 4],
+            [2, 6, 7, 3],
+            [0, 4, 6, 2],
+            [1, 3, 7, 5],
+        ];
+        let indices = quads
+            .iter()
+            .flat_map(|[a, b, c, d]| [*a, *b, *c, *a, *c, *d])
+            .collect();
+        TriangleMesh {
+            positions,
+            normals: vec![],
+            indices,
+            uvs: vec![],
+        }
+    }
+
+    fn unit_box_at(x: f64) -> TriangleMesh {
+        box_mesh(Point3::new(x, 0.0, 0.0), Point3::new(x + 1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn test_mesh_distance() {
+        let d = mesh_distance(&unit_box_at(0.0), &unit_box_at(3.5)).unwrap();
+        assert!((d.distance - 2.5).abs() < 1e-12);
+        assert_eq!(d.from.x, 1.0);
+        assert_eq!(d.to.x, 3.5);
+
+        // Overlapping boxes touch
+        let d = mesh_distance(&unit_box_at(0.0), &unit_box_at(0.5)).unwrap();
+        assert_eq!(d.distance, 0.0);
+
+        assert!(mesh_distance(&unit_box_at(0.0), &TriangleMesh::default()).is_none());
+    }
+
+    #[test]
+    fn test_point_mesh_distance() {
+        let mesh = unit_box_at(0.0);
+        let d = point_mesh_distance(Point3::new(0.5, 0.5, 4.0), &mesh).unwrap();
+        assert_eq!(d.distance, 3.0);
+        assert_eq!(d.to, Point3::new(0.5, 0.5, 1.0));
+
+        // Inside: distance to the nearest face
+        let d = point_mesh_distance(Point3::new(0.5, 0.5, 0.9), &mesh).unwrap();
+        assert!((d.distance - 0.1).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_face_area_and_edges() {
+        let mesh = box_mesh(Point3::ZERO, Point3::new(4.0, 2.0, 1.0));
+        // Triangle 0 is on the bottom face (z = 0), 4 x 2
+        assert_eq!(face_area(&mesh, 0), 8.0);
+        assert_eq!(face_outline(&mesh, &planar_face(&mesh, 0)).len(), 4);
+
+        let edge = nearest_face_edge(&mesh, 0, Point3::new(1.0, 0.1, 0.0)).unwrap();
+        assert_eq!(edge.length(), 4.0);
+        let edge = nearest_face_edge(&mesh, 1, Point3::new(3.9, 1.0, 0.0)).unwrap();
+        assert_eq!(edge.length(), 2.0);
+    }
+
+    #[test]
+    fn test_split_edge_reads_full_length() {
+        // A 2 x 1 rectangle triangulated as two squares
+        let mesh = TriangleMesh {
+            positions: vec![
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(1.0, 0.0, 0.0),
+                Point3::new(2.0, 0.0, 0.0),
+                Point3::new(0.0, 1.0, 0.0),
+                Point3::new(1.0, 1.0, 0.0),
+                Point3::new(2.0, 1.0, 0.0),
+            ],
+            normals: vec![],
+            indices: vec![0, 1, 4, 0, 4, 3, 1, 2, 5, 1, 5, 4],
+            uvs: vec![],
+        };
+        assert_eq!(face_area(&mesh, 0), 2.0);
+        let edge = nearest_face_edge(&mesh, 0, Point3::new(0.2, 0.0, 0.0)).unwrap();
+        assert_eq!(edge.length(), 2.0);
+        let edge = nearest_face_edge(&mesh, 3, Point3::new(2.0, 0.5, 0.0)).unwrap();
+        assert_eq!(edge.length(), 1.0);
+    }
+
+    #[test]
+    fn test_scene_measurements() {
+        let mut scene = Scene::new();
+        scene.add_mesh("a", unit_box_at(0.0), [0.5, 0.5, 0.5]);
+        let translation = DMat4::from_translation(DVec3::new(5.0, 0.0, 0.0));
+        let transform = translation.to_cols_array().map(|v| v as f32);
+        scene.add_instanced_group("b", unit_box_at(0.0), [0.5, 0.5, 0.5], vec![transform]);
+
+        let a = PickTarget::Mesh(0);
+        let b = PickTarget::Instance {
+            group: 0,
+            instance: 0,
+        };
+        let d = scene.distance(a, b).unwrap();
+        assert!((d.distance - 4.0).abs() < 1e-6);
+        assert!((d.to.x - 5.0).abs() < 1e-6);
+
+        let d = scene
+            .distance_to_point(b, Point3::new(7.0, 0.5, 0.5))
+            .unwrap();
+        assert!((d.distance - 1.0).abs() < 1e-6);
+        assert!((scene.surface_area(b).unwrap() - 6.0).abs() < 1e-6);
+        assert!(scene.distance(a, PickTarget::Mesh(5)).is_none());
+
+        let hit = scene
+            .pick(&Ray::new(Point3::new(5.5, 0.5, 3.0), -Vector3::Z))
+            .unwrap();
+        assert_eq!(hit.target, b);
+        assert!((scene.picked_face_area(&hit).unwrap() - 1.0).abs() < 1e-6);
+        assert!((scene.picked_edge(&hit).unwrap().length() - 1.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,178 @@
+// Streaming reader for CSTEngine chunked mesh files (mesh.bin format v4).
+//
+// Usage:
+//   import { readChunkedMesh } from './mesh_reader.js';
+//   for await (const chunk of readChunkedMesh('mesh.bin')) {
+//     addToScene(chunk);
+//   }
+//
+// Chunks are yielded in file order (largest / nearest first) as soon as
+// their bytes have arrived. Each chunk is
+//   { kind: 'mesh' | 'instanced', index, bounds: { min, max }, name,
+//     color: [r, g, b], alpha, metallic, roughness, doubleSided, texture,
+//     positions: Float32Array, normals: Float32Array | null,
+//     indices: Uint32Array,
+//     transforms: Float32Array (16 floats per instance, column-major) }
+// Typed arrays are views into the download buffer, not copies, except
+// octahedral-encoded normals which are decoded to unit vectors.
+
+const HEADER_SIZE = 16;
+const ENTRY_SIZE = 40;
+
+export async function* readChunkedMesh(url, options = {}) {
+  const response = await fetch(url, options);
+  if (!response.ok) {
+    throw new Error(`mesh.bin: HTTP ${response.status}`);
+  }
+  const reader = response.body.getReader();
+
+  // Bytes received before the total size is known
+  let pending = new Uint8Array(0);
+  let buffer = null;
+  let received = 0;
+  let manifest = null;
+  let normalEncoding = 0;
+  let next = 0;
+
+  const append = (bytes) => {
+    if (buffer) {
+      buffer.set(bytes, received);
+    } else {
+      const grown = new Uint8Array(received + bytes.length);
+      grown.set(pending);
+      grown.set(bytes, received);
+      pending = grown;
+    }
+    received += bytes.length;
+  };
+
+  const tryReadManifest = () => {
+    if (received < HEADER_SIZE) return;
+    const view = new DataView(pending.buffer);
+    if (view.getUint8(0) !== 4) {
+      throw new Error(`mesh.bin: expected format v4, got v${view.getUint8(0)}`);
+    }
+    normalEncoding = view.getUint8(1);
+    const chunkCount = view.getUint32(4, true);
+    const manifestEnd = HEADER_SIZE + chunkCount * ENTRY_SIZE;
+    if (received < manifestEnd) return;
+
+    manifest = [];
+    let total = manifestEnd;
+    for (let c = 0; c < chunkCount; c++) {
+      const at = HEADER_SIZE + c * ENTRY_SIZE;
+      const f = (k) => view.getFloat32(at + 16 + k * 4, true);
+      const entry = {
+        kind: view.getUint32(at, true) === 0 ? 'mesh' : 'instanced',
+        index: view.getUint32(at + 4, true),
+        offset: view.getUint32(at + 8, true),
+        length: view.getUint32(at + 12, true),
+        bounds: { min: [f(0), f(1), f(2)], max: [f(3), f(4), f(5)] },
+      };
+      total = Math.max(total, entry.offset + entry.length);
+      manifest.push(entry);
+    }
+
+    // Size is known now: move into one buffer the chunk views can share
+    buffer = new Uint8Array(total);
+    buffer.set(pending);
+    pending = null;
+  };
+
+  while (true) {
+    const { done, value } = await reader.read();
+    if (value) {
+      append(value);
+      if (!manifest) tryReadManifest();
+    }
+    while (manifest && next < manifest.length) {
+      const entry = manifest[next];
+      if (received < entry.offset + entry.length) break;
+      yield parseChunk(buffer.buffer, entry, normalEncoding);
+      next++;
+    }
+    if (done) break;
+  }
+
+  if (!manifest || next < manifest.length) {
+    throw new Error('mesh.bin: truncated');
+  }
+}
+
+function parseChunk(arrayBuffer, entry, normalEncoding) {
+  const view = new DataView(arrayBuffer, entry.offset, entry.length);
+  const vertexCount = view.getUint32(0, true);
+  const indexCount = view.getUint32(4, true);
+  const instanceCount = view.getUint32(8, true);
+
+  // Chunks are 4-byte aligned, so the arrays can be viewed in place
+  let at = entry.offset + 12;
+  const positions = new Float32Array(arrayBuffer, at, vertexCount * 3);
+  at += positions.byteLength;
+  let normals = null;
+  if (normalEncoding === 1) {
+    normals = new Float32Array(arrayBuffer, at, vertexCount * 3);
+    at += normals.byteLength;
+  } else if (normalEncoding === 2) {
+    normals = decodeOctahedral(new Int16Array(arrayBuffer, at, vertexCount * 2));
+    at += vertexCount * 4;
+  }
+  const indices = new Uint32Array(arrayBuffer, at, indexCount);
+  at += indices.byteLength;
+  const transforms = new Float32Array(arrayBuffer, at, instanceCount * 16);
+  at += transforms.byteLength;
+
+  let rel = at - entry.offset;
+  const f = () => { const v = view.getFloat32(rel, true); rel += 4; return v; };
+  const color = [f(), f(), f()];
+  const alpha = f();
+  const metallic = f();
+  const roughness = f();
+  const flags = view.getUint32(rel, true);
+  rel += 4;
+
+  const decoder = new TextDecoder();
+  const text = () => {
+    const length = view.getUint32(rel, true);
+    const bytes = new Uint8Array(arrayBuffer, entry.offset + rel + 4, length);
+    rel += 4 + length;
+    return decoder.decode(bytes);
+  };
+  const name = text();
+  const texture = text() || null;
+
+  return {
+    kind: entry.kind,
+    index: entry.index,
+    bounds: entry.bounds,
+    name,
+    color,
+    alpha,
+    metallic,
+    roughness,
+    doubleSided: (flags & 1) !== 0,
+    texture,
+    positions,
+    normals,
+    indices,
+    transforms,
+  };
+}
+
+// Octahedral [i16 x, i16 y] snorm pairs to unit xyz
+function decodeOctahedral(encoded) {
+  const normals = new Float32Array((encoded.length / 2) * 3);
+  for (let i = 0; i < encoded.length / 2; i++) {
+    let x = Math.max(encoded[2 * i] / 32767, -1);
+    let y = Math.max(encoded[2 * i + 1] / 32767, -1);
+    const z = 1 - Math.abs(x) - Math.abs(y);
+    const t = Math.max(-z, 0);
+    x -= x >= 0 ? t : -t;
+    y -= y >= 0 ? t : -t;
+    const length = Math.hypot(x, y, z) || 1;
+    normals[3 * i] = x / length;
+    normals[3 * i + 1] = y / length;
+    normals[3 * i + 2] = z / length;
+  }
+  return normals;
+}
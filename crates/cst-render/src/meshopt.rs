@@ -0,0 +1,283 @@
+//! meshoptimizer vertex codec, as used by `EXT_meshopt_compression`.
+//!
+//! Implements version 0 of the vertex buffer bitstream: vertices are split
+//! into blocks, each byte lane is delta-encoded against the previous vertex,
+//! zigzagged, and packed in groups of 16 with 0, 2, 4 or 8 bits per value.
+//! The output is meant to be gzip/brotli compressed by the web server on
+//! top, which is where most of the size reduction comes from.
+
+use cst_core::error::{CstError, Result};
+
+const VERTEX_HEADER: u8 = 0xa0;
+const VERTEX_BLOCK_SIZE_BYTES: usize = 8192;
+const VERTEX_BLOCK_MAX_SIZE: usize = 256;
+const BYTE_GROUP_SIZE: usize = 16;
+const TAIL_MAX_SIZE: usize = 32;
+
+/// Vertices per block: the block must fit the scratch size and be a
+/// multiple of the byte group size.
+fn vertex_block_size(vertex_size: usize) -> usize {
+    let size = (VERTEX_BLOCK_SIZE_BYTES / vertex_size) & !(BYTE_GROUP_SIZE - 1);
+    size.min(VERTEX_BLOCK_MAX_SIZE)
+}
+
+fn zigzag8(v: u8) -> u8 {
+    (((v as i8) >> 7) as u8) ^ (v << 1)
+}
+
+fn unzigzag8(v: u8) -> u8 {
+    0u8.wrapping_sub(v & 1) ^ (v >> 1)
+}
+
+/// Encode `data`, a packed array of `vertex_size`-byte vertices.
+///
+/// `vertex_size` must be a multiple of 4 and at most 256.
+pub fn encode_vertex_buffer(data: &[u8], vertex_size: usize) -> Vec<u8> {
+    assert!(vertex_size > 0 && vertex_size <= 256 && vertex_size % 4 == 0);
+    assert_eq!(data.len() % vertex_size, 0);
+
+    let first = data
+        .get(..vertex_size)
+        .map_or_else(|| vec![0; vertex_size], <[u8]>::to_vec);
+    let mut last_vertex = first.clone();
+    let mut out = vec![VERTEX_HEADER];
+
+    for block in data.chunks(vertex_block_size(vertex_size) * vertex_size) {
+        encode_vertex_block(&mut out, block, vertex_size, &mut last_vertex);
+    }
+
+    // The first vertex goes at the end, padded to the tail size, so decoders
+    // can skip bounds checks
+    out.resize(out.len() + TAIL_MAX_SIZE.saturating_sub(vertex_size), 0);
+    out.extend_from_slice(&first);
+    out
+}
+
+fn encode_vertex_block(
+    out: &mut Vec<u8>,
+    block: &[u8],
+    vertex_size: usize,
+    last_vertex: &mut [u8],
+) {
+    let count = block.len() / vertex_size;
+    let aligned = (count + BYTE_GROUP_SIZE - 1) & !(BYTE_GROUP_SIZE - 1);
+    let mut buffer = [0u8; VERTEX_BLOCK_MAX_SIZE];
+
+    for k in 0..vertex_size {
+        let mut previous = last_vertex[k];
+        for i in 0..count {
+            let value = block[i * vertex_size + k];
+            buffer[i] = zigzag8(value.wrapping_sub(previous));
+            previous = value;
+        }
+        encode_bytes(out, &buffer[..aligned]);
+    }
+
+    last_vertex.copy_from_slice(&block[(count - 1) * vertex_size..]);
+}
+
+/// Encoded size of a byte group at the given bit width
+fn measure_group(group: &[u8], bits: usize) -> usize {
+    match bits {
+        1 => {
+            if group.iter().all(|&b| b == 0) {
+                0
+            } else {
+                usize::MAX
+            }
+        }
+        8 => BYTE_GROUP_SIZE,
+        _ => {
+            let sentinel = (1u8 << bits) - 1;
+            BYTE_GROUP_SIZE * bits / 8 + group.iter().filter(|&&b| b >= sentinel).count()
+        }
+    }
+}
+
+fn encode_bytes(out: &mut Vec<u8>, buffer: &[u8]) {
+    // Two header bits per group select its bit width
+    let header = out.len();
+    out.resize(header + (buffer.len() / BYTE_GROUP_SIZE).div_ceil(4), 0);
+
+    for (g, group) in buffer.chunks_exact(BYTE_GROUP_SIZE).enumerate() {
+        let mut best_bits = 8;
+        let mut best_size = measure_group(group, 8);
+        for bits in [1, 2, 4] {
+            let size = measure_group(group, bits);
+            if size < best_size {
+                best_bits = bits;
+                best_size = size;
+            }
+        }
+
+        let bits_log2 = best_bits.trailing_zeros() as u8;
+        out[header + g / 4] |= bits_log2 << ((g % 4) * 2);
+
+        match best_bits {
+            1 => {}
+            8 => out.extend_from_slice(group),
+            bits => {
+                // Fixed part packed MSB first, then a full byte for every
+                // value that does not fit (marked with the all-ones sentinel)
+                let sentinel = (1u8 << bits) - 1;
+                for chunk in group.chunks_exact(8 / bits) {
+                    let mut byte = 0u8;
+                    for &value in chunk {
+                        byte = (byte << bits) | value.min(sentinel);
+                    }
+                    out.push(byte);
+                }
+                out.extend(group.iter().filter(|&&b| b >= sentinel));
+            }
+        }
+    }
+}
+
+/// Decode `count` vertices of `vertex_size` bytes.
+pub fn decode_vertex_buffer(data: &[u8], count: usize, vertex_size: usize) -> Result<Vec<u8>> {
+    let error = |message: &str| CstError::Parse(format!("meshopt vertex buffer: {}", message));
+    if vertex_size == 0 || vertex_size > 256 || vertex_size % 4 != 0 {
+        return Err(error("invalid vertex size"));
+    }
+    let tail_size = vertex_size.max(TAIL_MAX_SIZE);
+    if data.len() < 1 + tail_size {
+        return Err(error("truncated"));
+    }
+    if data[0] != VERTEX_HEADER {
+        return Err(error("unsupported header"));
+    }
+
+    let body_end = data.len() - tail_size;
+    let mut last_vertex = data[data.len() - vertex_size..].to_vec();
+    let mut out = vec![0u8; count * vertex_size];
+    let mut pos = 1;
+    let block_size = vertex_block_size(vertex_size);
+    let mut buffer = [0u8; VERTEX_BLOCK_MAX_SIZE];
+
+    for start in (0..count).step_by(block_size) {
+        let block_count = block_size.min(count - start);
+        let aligned = (block_count + BYTE_GROUP_SIZE - 1) & !(BYTE_GROUP_SIZE - 1);
+        for k in 0..vertex_size {
+            pos = decode_bytes(data, body_end, pos, &mut buffer[..aligned])
+                .ok_or_else(|| error("truncated"))?;
+            let mut previous = last_vertex[k];
+            for (i, &encoded) in buffer[..block_count].iter().enumerate() {
+                previous = previous.wrapping_add(unzigzag8(encoded));
+                out[(start + i) * vertex_size + k] = previous;
+            }
+            last_vertex[k] = previous;
+        }
+    }
+
+    if pos != body_end {
+        return Err(error("trailing data"));
+    }
+    Ok(out)
+}
+
+/// Decode byte groups into `buffer`, returning the position after them
+fn decode_bytes(data: &[u8], end: usize, mut pos: usize, buffer: &mut [u8]) -> Option<usize> {
+    let groups = buffer.len() / BYTE_GROUP_SIZE;
+    let header = data.get(pos..pos + groups.div_ceil(4))?;
+    pos += header.len();
+
+    for (g, group) in buffer.chunks_exact_mut(BYTE_GROUP_SIZE).enumerate() {
+        match (header[g / 4] >> ((g % 4) * 2)) & 3 {
+            0 => group.fill(0),
+            3 => {
+                group.copy_from_slice(data.get(pos..pos + BYTE_GROUP_SIZE)?);
+                pos += BYTE_GROUP_SIZE;
+            }
+            bits_log2 => {
+                let bits = 1usize << bits_log2;
+                let sentinel = (1u8 << bits) - 1;
+                let fixed = data.get(pos..pos + BYTE_GROUP_SIZE * bits / 8)?;
+                let mut extra = pos + fixed.len();
+                for (i, value) in group.iter_mut().enumerate() {
+                    let shift = 8 - bits * (i % (8 / bits) + 1);
+                    let encoded = (fixed[i / (8 / bits)] >> shift) & sentinel;
+                    *value = if encoded == sentinel {
+                        extra += 1;
+                        *data.get(extra - 1)?
+                    } else {
+                        encoded
+                    };
+                }
+                pos = extra;
+            }
+        }
+        if pos > end {
+            return None;
+        }
+    }
+    Some(pos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(data: &[u8], vertex_size: usize) -> Vec<u8> {
+        let encoded = encode_vertex_buffer(data, vertex_size);
+        decode_vertex_buffer(&encoded, data.len() / vertex_size, vertex_size).unwrap()
+    }
+
+    #[test]
+    fn test_zigzag() {
+        for v in 0..=255u8 {
+            assert_eq!(unzigzag8(zigzag8(v)), v);
+        }
+        assert_eq!(zigzag8(1), 2);
+        assert_eq!(zigzag8(0xff), 1);
+    }
+
+    #[test]
+    fn test_round_trip_positions() {
+        // Smooth data (a grid of f32 positions) spanning several blocks
+        let data: Vec<u8> = (0..1000)
+            .flat_map(|i| [(i % 37) as f32 * 0.25, (i / 37) as f32 * 0.5, 3.0f32])
+            .flat_map(f32::to_le_bytes)
+            .collect();
+        assert_eq!(round_trip(&data, 12), data);
+
+        // Correlated data compresses
+        assert!(encode_vertex_buffer(&data, 12).len() < data.len() / 2);
+    }
+
+    #[test]
+    fn test_round_trip_noise() {
+        let mut state = 0x1234_5678u32;
+        let data: Vec<u8> = (0..4 * 333)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect();
+        assert_eq!(round_trip(&data, 4), data);
+        assert_eq!(round_trip(&data[..16], 16), &data[..16]);
+    }
+
+    #[test]
+    fn test_empty_and_layout() {
+        let encoded = encode_vertex_buffer(&[], 12);
+        assert_eq!(encoded[0], VERTEX_HEADER);
+        assert_eq!(encoded.len(), 1 + TAIL_MAX_SIZE);
+        assert!(decode_vertex_buffer(&encoded, 0, 12).unwrap().is_empty());
+
+        // Two identical vertices: every lane is a zero group (header only)
+        let vertex = 7u32.to_le_bytes();
+        let encoded = encode_vertex_buffer(&[vertex, vertex].concat(), 4);
+        assert_eq!(encoded.len(), 1 + 4 + TAIL_MAX_SIZE);
+        assert_eq!(&encoded[encoded.len() - 4..], &vertex);
+    }
+
+    #[test]
+    fn test_decode_rejects_bad_input() {
+        let encoded = encode_vertex_buffer(&[1, 2, 3, 4, 5, 6, 7, 8], 4);
+        assert!(decode_vertex_buffer(&encoded[1..], 2, 4).is_err());
+        assert!(decode_vertex_buffer(&encoded, 100, 4).is_err());
+        assert!(decode_vertex_buffer(&encoded, 2, 6).is_err());
+    }
+}
@@ -0,0 +1,264 @@
+//! Wavefront OBJ + MTL export of a [`Scene`].
+//!
+//! Each mesh becomes an `o`/`g` group with its own material; instanced
+//! groups are expanded into one group per instance. Many AEC tools that
+//! predate glTF still only import OBJ.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use cst_math::{DMat3, DMat4};
+use cst_mesh::TriangleMesh;
+
+use crate::material::Material;
+use crate::scene::Scene;
+
+impl Scene {
+    /// Write the scene as `path` (OBJ) plus a material library next to it
+    /// with the same stem and a `.mtl` extension.
+    pub fn export_obj(&self, path: &Path) -> std::io::Result<()> {
+        let mtl_path = path.with_extension("mtl");
+        let mtl_name = mtl_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let mut obj = BufWriter::new(File::create(path)?);
+        let mut mtl = BufWriter::new(File::create(&mtl_path)?);
+        self.write_obj(&mut obj, &mut mtl, &mtl_name)?;
+        obj.flush()?;
+        mtl.flush()
+    }
+
+    /// Write OBJ and MTL data to separate writers; the OBJ refers to the
+    /// library as `mtl_name`.
+    pub fn write_obj<O: Write, M: Write>(
+        &self,
+        obj: &mut O,
+        mtl: &mut M,
+        mtl_name: &str,
+    ) -> std::io::Result<()> {
+        writeln!(obj, "# CSTEngine OBJ export")?;
+        writeln!(mtl, "# CSTEngine MTL export")?;
+        if !mtl_name.is_empty() {
+            writeln!(obj, "mtllib {}", mtl_name)?;
+        }
+
+        let mut group = ObjGroupWriter::default();
+        for (i, scene_mesh) in self.meshes.iter().enumerate() {
+            let material = format!("mat_{}", i);
+            write_material(mtl, &material, &scene_mesh.material)?;
+            let name = obj_name(&scene_mesh.name, i);
+            group.write(obj, &name, &material, &scene_mesh.mesh, DMat4::IDENTITY)?;
+        }
+        for (g, ig) in self.instanced_groups.iter().enumerate() {
+            let material = format!("inst_mat_{}", g);
+            write_material(mtl, &material, &ig.material)?;
+            for instance in 0..ig.transforms.len() {
+                let name = format!("{}_{}", obj_name(&ig.name, g), instance);
+                let transform = ig.transform_matrix(instance);
+                group.write(obj, &name, &material, &ig.mesh, transform)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Running vertex counts; OBJ indices are global and 1-based
+#[derive(Default)]
+struct ObjGroupWriter {
+    positions: usize,
+    normals: usize,
+    uvs: usize,
+}
+
+impl ObjGroupWriter {
+    fn write<W: Write>(
+        &mut self,
+        out: &mut W,
+        name: &str,
+        material: &str,
+        mesh: &TriangleMesh,
+        transform: DMat4,
+    ) -> std::io::Result<()> {
+        let has_normals = mesh.normals.len() == mesh.positions.len();
+        let has_uvs = mesh.uvs.len() == mesh.positions.len();
+        let normal_matrix = DMat3::from_mat4(transform).inverse().transpose();
+
+        writeln!(out, "o {}", name)?;
+        writeln!(out, "g {}", name)?;
+        writeln!(out, "usemtl {}", material)?;
+        for &p in &mesh.positions {
+            let p = transform.transform_point3(p);
+            writeln!(out, "v {} {} {}", p.x, p.y, p.z)?;
+        }
+        if has_normals {
+            for &n in &mesh.normals {
+                let n = (normal_matrix * n).normalize_or_zero();
+                writeln!(out, "vn {} {} {}", n.x, n.y, n.z)?;
+            }
+        }
+        if has_uvs {
+            for uv in &mesh.uvs {
+                writeln!(out, "vt {} {}", uv.x, uv.y)?;
+            }
+        }
+
+        for tri in mesh.indices.chunks_exact(3) {
+            write!(out, "f")?;
+            for &index in tri {
+                let index = index as usize;
+                write!(out, " {}", self.positions + index + 1)?;
+                match (has_uvs, has_normals) {
+                    (true, true) => write!(
+                        out,
+                        "/{}/{}",
+                        self.uvs + index + 1,
+                        self.normals + index + 1
+                    )?,
+                    (true, false) => write!(out, "/{}", self.uvs + index + 1)?,
+                    (false, true) => write!(out, "//{}", self.normals + index + 1)?,
+                    (false, false) => {}
+                }
+            }
+            writeln!(out)?;
+        }
+
+        self.positions += mesh.positions.len();
+        if has_normals {
+            self.normals += mesh.normals.len();
+        }
+        if has_uvs {
+            self.uvs += mesh.uvs.len();
+        }
+        Ok(())
+    }
+}
+
+fn write_material<W: Write>(out: &mut W, name: &str, material: &Material) -> std::io::Result<()> {
+    let [r, g, b] = material.base_color;
+    writeln!(out)?;
+    writeln!(out, "newmtl {}", name)?;
+    writeln!(out, "Kd {} {} {}", r, g, b)?;
+    writeln!(out, "Ka {} {} {}", r * 0.2, g * 0.2, b * 0.2)?;
+    writeln!(out, "Ks 0.1 0.1 0.1")?;
+    // Phong exponent from roughness: smooth surfaces get tight highlights
+    writeln!(
+        out,
+        "Ns {}",
+        (1.0 - material.roughness).clamp(0.0, 1.0) * 250.0
+    )?;
+    writeln!(out, "d {}", material.alpha)?;
+    writeln!(out, "illum 2")?;
+    if let Some(texture) = &material.texture {
+        writeln!(out, "map_Kd {}", texture)?;
+    }
+    Ok(())
+}
+
+/// Group name without whitespace, which OBJ readers split on
+fn obj_name(name: &str, index: usize) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| {
+            if c.is_whitespace() || c.is_control() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    if cleaned.is_empty() {
+        format!("mesh_{}", index)
+    } else {
+        cleaned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cst_math::{DVec3, Point2};
+
+    fn triangle() -> TriangleMesh {
+        TriangleMesh {
+            positions: vec![
+                DVec3::new(0.0, 0.0, 0.0),
+                DVec3::new(1.0, 0.0, 0.0),
+                DVec3::new(0.0, 1.0, 0.0),
+            ],
+            normals: vec![DVec3::Z; 3],
+            indices: vec![0, 1, 2],
+            uvs: vec![],
+        }
+    }
+
+    fn export(scene: &Scene) -> (String, String) {
+        let (mut obj, mut mtl) = (Vec::new(), Vec::new());
+        scene.write_obj(&mut obj, &mut mtl, "model.mtl").unwrap();
+        (
+            String::from_utf8(obj).unwrap(),
+            String::from_utf8(mtl).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_groups_and_global_indices() {
+        let mut scene = Scene::new();
+        scene.add_mesh("Wall A", triangle(), [0.5, 0.25, 1.0]);
+        let mut textured = triangle();
+        textured.normals.clear();
+        textured.uvs = vec![Point2::ZERO, Point2::X, Point2::Y];
+        scene.add_mesh(
+            "",
+            textured,
+            Material::from_color([1.0, 1.0, 1.0]).with_alpha(0.5),
+        );
+
+        let (obj, mtl) = export(&scene);
+        assert!(obj.starts_with("# CSTEngine OBJ export\nmtllib model.mtl\n"));
+        assert!(obj.contains("o Wall_A\ng Wall_A\nusemtl mat_0\n"));
+        assert!(obj.contains("f 1//1 2//2 3//3\n"));
+        // Second mesh continues the position count, has UVs but no normals
+        assert!(obj.contains("o mesh_1\n"));
+        assert!(obj.contains("f 4/1 5/2 6/3\n"));
+        assert_eq!(obj.lines().filter(|l| l.starts_with("v ")).count(), 6);
+
+        assert!(mtl.contains("newmtl mat_0\nKd 0.5 0.25 1\n"));
+        assert!(mtl.contains("newmtl mat_1\n"));
+        assert!(mtl.contains("d 0.5\n"));
+    }
+
+    #[test]
+    fn test_instances_are_expanded() {
+        let mut scene = Scene::new();
+        let shift = DMat4::from_translation(DVec3::new(10.0, 0.0, 0.0));
+        let transforms = [DMat4::IDENTITY, shift]
+            .iter()
+            .map(|m| m.to_cols_array().map(|v| v as f32))
+            .collect();
+        scene.add_instanced_group("Column", triangle(), [0.7, 0.7, 0.7], transforms);
+
+        let (obj, mtl) = export(&scene);
+        assert!(obj.contains("o Column_0\n"));
+        assert!(obj.contains("o Column_1\n"));
+        assert!(obj.contains("v 11 0 0\n"));
+        assert_eq!(mtl.matches("newmtl inst_mat_0").count(), 1);
+    }
+
+    #[test]
+    fn test_export_obj_files() {
+        let mut scene = Scene::new();
+        scene.add_mesh("Slab", triangle(), [0.8, 0.8, 0.8]);
+
+        let path = std::env::temp_dir().join("test_scene_export.obj");
+        scene.export_obj(&path).unwrap();
+        let obj = std::fs::read_to_string(&path).unwrap();
+        assert!(obj.contains("mtllib test_scene_export.mtl"));
+        assert!(path.with_extension("mtl").exists());
+
+        let _ = std::fs::remove_file(path.with_extension("mtl"));
+        let _ = std::fs::remove_file(path);
+    }
+}
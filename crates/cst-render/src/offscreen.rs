@@ -0,0 +1,393 @@
+//! Headless rendering of a [`Scene`] into an RGBA image.
+//!
+//! Triangles are rasterized on the CPU with a depth buffer and simple
+//! two-light shading matching the HTML viewer, so preview images can be
+//! produced on machines without a GPU (CI, asset pipelines). Frustum culling
+//! and section planes are honoured like in the interactive viewers: the
+//! planes are packed into [`ClipPlaneUniforms`] and every triangle is cut
+//! along their clip distances, as the GPU does with the same uniforms.
+//!
+//! With the `gpu` feature, the `gpu` module renders the same images with a
+//! wgpu offscreen target and falls back to this rasterizer.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use cst_math::{DMat4, DVec4, Point3, Vector3};
+use cst_mesh::TriangleMesh;
+
+use crate::camera::Camera;
+use crate::pipeline::ClipPlaneUniforms;
+use crate::scene::Scene;
+
+/// Background color, as in the HTML viewer.
+pub(crate) const BACKGROUND: [u8; 4] = [0x1a, 0x1a, 0x1a, 0xff];
+
+/// An 8-bit RGBA image stored row by row from the top.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    /// Create an image filled with one color.
+    pub fn new(width: u32, height: u32, fill: [u8; 4]) -> Self {
+        Self {
+            width,
+            height,
+            pixels: fill.repeat(width as usize * height as usize),
+        }
+    }
+
+    /// Color of the pixel at column `x`, row `y`.
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        [
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ]
+    }
+
+    /// Encode the image as PNG.
+    pub fn write_png<W: Write>(&self, writer: W) -> std::io::Result<()> {
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut png_writer = encoder.write_header().map_err(std::io::Error::other)?;
+        png_writer
+            .write_image_data(&self.pixels)
+            .map_err(std::io::Error::other)?;
+        png_writer.finish().map_err(std::io::Error::other)
+    }
+
+    /// Save the image as a PNG file.
+    pub fn save_png(&self, path: &Path) -> std::io::Result<()> {
+        self.write_png(BufWriter::new(File::create(path)?))
+    }
+}
+
+/// A vertex after projection: clip-space position plus world position for
+/// the section plane clip distances.
+#[derive(Debug, Clone, Copy)]
+struct ClipVertex {
+    clip: DVec4,
+    world: Point3,
+}
+
+/// Color and depth targets for one render.
+struct Target {
+    image: RgbaImage,
+    depth: Vec<f32>,
+    clip_planes: ClipPlaneUniforms,
+}
+
+impl Scene {
+    /// Render the scene from `camera` into a `width` x `height` image.
+    ///
+    /// The camera's aspect ratio is replaced by `width / height`. Like on
+    /// the GPU, section planes beyond
+    /// [`MAX_SECTION_PLANES`](crate::pipeline::MAX_SECTION_PLANES) are
+    /// ignored.
+    pub fn render_to_image(&self, camera: &Camera, width: u32, height: u32) -> RgbaImage {
+        let mut camera = camera.clone();
+        camera.aspect = width.max(1) as f64 / height.max(1) as f64;
+        let view_projection = DMat4::from_cols_array_2d(&camera.view_projection()).transpose();
+        let view_dir = (camera.target - camera.eye).normalize_or_zero();
+
+        let mut target = Target {
+            image: RgbaImage::new(width, height, BACKGROUND),
+            depth: vec![f32::INFINITY; width as usize * height as usize],
+            clip_planes: ClipPlaneUniforms::from_planes(&self.section_planes),
+        };
+
+        for i in self.visible_meshes(&camera) {
+            let scene_mesh = &self.meshes[i];
+            target.draw_mesh(
+                &scene_mesh.mesh,
+                DMat4::IDENTITY,
+                &view_projection,
+                view_dir,
+                scene_mesh.material.base_color,
+            );
+        }
+        for (group, instance) in self.visible_instances(&camera) {
+            let ig = &self.instanced_groups[group];
+            target.draw_mesh(
+                &ig.mesh,
+                ig.transform_matrix(instance),
+                &view_projection,
+                view_dir,
+                ig.material.base_color,
+            );
+        }
+        target.image
+    }
+}
+
+impl Target {
+    fn draw_mesh(
+        &mut self,
+        mesh: &TriangleMesh,
+        model: DMat4,
+        view_projection: &DMat4,
+        view_dir: Vector3,
+        color: [f32; 3],
+    ) {
+        for tri in mesh.indices.chunks_exact(3) {
+            let world = [tri[0], tri[1], tri[2]]
+                .map(|i| model.transform_point3(mesh.positions[i as usize]));
+            let normal = (world[1] - world[0]).cross(world[2] - world[0]);
+            if normal.length_squared() == 0.0 {
+                continue;
+            }
+            let shade = shade(normal.normalize(), view_dir);
+            let rgba = [
+                (color[0] * shade * 255.0).clamp(0.0, 255.0) as u8,
+                (color[1] * shade * 255.0).clamp(0.0, 255.0) as u8,
+                (color[2] * shade * 255.0).clamp(0.0, 255.0) as u8,
+                0xff,
+            ];
+
+            let vertices = world.map(|p| ClipVertex {
+                clip: *view_projection * p.extend(1.0),
+                world: p,
+            });
+            let mut polygon = clip_polygon(vertices.to_vec(), |v| v.clip.z + v.clip.w);
+            for plane in 0..self.clip_planes.active() {
+                polygon = clip_polygon(polygon, |v| self.clip_planes.distance(plane, v.world));
+            }
+            for k in 1..polygon.len().saturating_sub(1) {
+                self.raster_triangle([polygon[0], polygon[k], polygon[k + 1]], rgba);
+            }
+        }
+    }
+
+    fn raster_triangle(&mut self, vertices: [ClipVertex; 3], rgba: [u8; 4]) {
+        let (w, h) = (self.image.width as f64, self.image.height as f64);
+        let screen = vertices.map(|v| {
+            let ndc = v.clip.truncate() / v.clip.w;
+            [(ndc.x + 1.0) * 0.5 * w, (1.0 - ndc.y) * 0.5 * h, ndc.z]
+        });
+        let area = edge(screen[0], screen[1], screen[2]);
+        if area.abs() < 1e-12 {
+            return;
+        }
+
+        let min_x = screen
+            .iter()
+            .map(|s| s[0])
+            .fold(f64::INFINITY, f64::min)
+            .floor()
+            .max(0.0);
+        let max_x = screen
+            .iter()
+            .map(|s| s[0])
+            .fold(f64::NEG_INFINITY, f64::max)
+            .ceil()
+            .min(w);
+        let min_y = screen
+            .iter()
+            .map(|s| s[1])
+            .fold(f64::INFINITY, f64::min)
+            .floor()
+            .max(0.0);
+        let max_y = screen
+            .iter()
+            .map(|s| s[1])
+            .fold(f64::NEG_INFINITY, f64::max)
+            .ceil()
+            .min(h);
+
+        for y in min_y as u32..max_y as u32 {
+            for x in min_x as u32..max_x as u32 {
+                let p = [x as f64 + 0.5, y as f64 + 0.5, 0.0];
+                let b = [
+                    edge(screen[1], screen[2], p) / area,
+                    edge(screen[2], screen[0], p) / area,
+                    edge(screen[0], screen[1], p) / area,
+                ];
+                if b.iter().any(|&v| v < 0.0) {
+                    continue;
+                }
+                let depth = b[0] * screen[0][2] + b[1] * screen[1][2] + b[2] * screen[2][2];
+                if !(-1.0..=1.0).contains(&depth) {
+                    continue;
+                }
+                let index = y as usize * self.image.width as usize + x as usize;
+                if depth as f32 >= self.depth[index] {
+                    continue;
+                }
+                self.depth[index] = depth as f32;
+                self.image.pixels[index * 4..index * 4 + 4].copy_from_slice(&rgba);
+            }
+        }
+    }
+}
+
+/// Brightness of a face: ambient plus the two directional lights of the
+/// HTML viewer, lit from whichever side faces the camera.
+fn shade(normal: Vector3, view_dir: Vector3) -> f32 {
+    let n = if normal.dot(view_dir) > 0.0 {
+        -normal
+    } else {
+        normal
+    };
+    let key = n.dot(Vector3::new(1.0, 1.0, 1.0).normalize()).max(0.0);
+    let fill = n.dot(Vector3::new(-1.0, -1.0, -1.0).normalize()).max(0.0);
+    (0.5 + 0.6 * key + 0.3 * fill) as f32
+}
+
+/// Signed area term of `p` relative to the edge `a -> b`.
+fn edge(a: [f64; 3], b: [f64; 3], p: [f64; 3]) -> f64 {
+    (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0])
+}
+
+/// The part of a convex polygon where `distance` is not negative, e.g. in
+/// front of the near plane (`z >= -w` in clip space).
+fn clip_polygon(
+    polygon: Vec<ClipVertex>,
+    distance: impl Fn(&ClipVertex) -> f64,
+) -> Vec<ClipVertex> {
+    let mut out = Vec::with_capacity(polygon.len() + 1);
+    for i in 0..polygon.len() {
+        let a = polygon[i];
+        let b = polygon[(i + 1) % polygon.len()];
+        let (da, db) = (distance(&a), distance(&b));
+        if da >= 0.0 {
+            out.push(a);
+        }
+        if (da >= 0.0) != (db >= 0.0) {
+            let t = da / (da - db);
+            out.push(ClipVertex {
+                clip: a.clip.lerp(b.clip, t),
+                world: a.world.lerp(b.world, t),
+            });
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cst_math::plane::Plane;
+
+    fn quad(z: f64, size: f64) -> TriangleMesh {
+        TriangleMesh {
+            positions: vec![
+                Point3::new(-size, -size, z),
+                Point3::new(size, -size, z),
+                Point3::new(size, size, z),
+                Point3::new(-size, size, z),
+            ],
+            normals: vec![],
+            indices: vec![0, 1, 2, 0, 2, 3],
+            uvs: vec![],
+        }
+    }
+
+    #[test]
+    fn test_empty_scene_renders_background() {
+        let image = Scene::new().render_to_image(&Camera::default(), 8, 4);
+        assert_eq!(image.pixels.len(), 8 * 4 * 4);
+        assert!(image.pixels.chunks(4).all(|p| p == BACKGROUND));
+    }
+
+    #[test]
+    fn test_depth_order() {
+        let mut scene = Scene::new();
+        scene.add_mesh("Back", quad(-1.0, 1.0), [1.0, 0.0, 0.0]);
+        scene.add_mesh("Front", quad(0.0, 0.5), [0.0, 1.0, 0.0]);
+
+        let image = scene.render_to_image(&Camera::default(), 64, 64);
+        let center = image.pixel(32, 32);
+        assert!(
+            center[1] > 0 && center[0] == 0,
+            "front quad wins: {:?}",
+            center
+        );
+        let edge = image.pixel(20, 32);
+        assert!(
+            edge[0] > 0 && edge[1] == 0,
+            "back quad around it: {:?}",
+            edge
+        );
+        assert_eq!(image.pixel(0, 0), BACKGROUND);
+    }
+
+    #[test]
+    fn test_section_plane_hides_geometry() {
+        let mut scene = Scene::new();
+        scene.add_mesh("Quad", quad(0.0, 1.0), [1.0, 1.0, 1.0]);
+        // Keep only x < 0
+        scene.add_section_plane(Plane::new(Point3::ZERO, -Vector3::X));
+
+        let image = scene.render_to_image(&Camera::default(), 64, 64);
+        assert_ne!(image.pixel(28, 32), BACKGROUND);
+        assert_eq!(image.pixel(36, 32), BACKGROUND);
+    }
+
+    #[test]
+    fn test_section_plane_cuts_triangles_away_from_depth() {
+        // Cutting away the front quad shows the one behind it
+        let mut scene = Scene::new();
+        scene.add_mesh("Back", quad(-1.0, 1.0), [1.0, 0.0, 0.0]);
+        scene.add_mesh("Front", quad(0.0, 1.0), [0.0, 1.0, 0.0]);
+        scene.add_section_plane(Plane::new(Point3::new(0.0, 0.0, -0.5), -Vector3::Z));
+
+        let image = scene.render_to_image(&Camera::default(), 64, 64);
+        let center = image.pixel(32, 32);
+        assert!(center[0] > 0 && center[1] == 0, "back quad: {:?}", center);
+
+        // Planes the GPU uniforms have no room for are ignored
+        let mut scene = Scene::new();
+        scene.add_mesh("Quad", quad(0.0, 1.0), [1.0, 1.0, 1.0]);
+        for _ in 0..crate::pipeline::MAX_SECTION_PLANES {
+            scene.add_section_plane(Plane::new(Point3::new(0.0, 0.0, -1.0), Vector3::Z));
+        }
+        scene.add_section_plane(Plane::new(Point3::ZERO, -Vector3::X));
+        let image = scene.render_to_image(&Camera::default(), 64, 64);
+        assert_ne!(image.pixel(36, 32), BACKGROUND);
+    }
+
+    #[test]
+    fn test_near_plane_clipping() {
+        // A floor running from behind the camera to far in front of it
+        let mut scene = Scene::new();
+        scene.add_mesh(
+            "Floor",
+            TriangleMesh {
+                positions: vec![
+                    Point3::new(-5.0, -1.0, 10.0),
+                    Point3::new(5.0, -1.0, 10.0),
+                    Point3::new(5.0, -1.0, -50.0),
+                    Point3::new(-5.0, -1.0, -50.0),
+                ],
+                normals: vec![],
+                indices: vec![0, 1, 2, 0, 2, 3],
+                uvs: vec![],
+            },
+            [1.0, 1.0, 1.0],
+        );
+
+        let image = scene.render_to_image(&Camera::default(), 64, 64);
+        assert_ne!(image.pixel(32, 63), BACKGROUND); // floor below the camera
+        assert_eq!(image.pixel(32, 0), BACKGROUND); // sky above
+    }
+
+    #[test]
+    fn test_png_encoding() {
+        let image = RgbaImage::new(3, 2, [10, 20, 30, 255]);
+        let mut bytes = Vec::new();
+        image.write_png(&mut bytes).unwrap();
+        assert_eq!(&bytes[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&bytes[12..16], b"IHDR");
+        assert_eq!(u32::from_be_bytes(bytes[16..20].try_into().unwrap()), 3);
+        assert_eq!(u32::from_be_bytes(bytes[20..24].try_into().unwrap()), 2);
+    }
+}
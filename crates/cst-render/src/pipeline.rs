@@ -1,9 +1,13 @@
-use cst_mesh::TriangleMesh;
+use bytemuck::{Pod, Zeroable};
+use cst_mesh::{LineList, TriangleMesh};
 use cst_math::{Point2, Point3, Vector3};
 
-/// Vertex with f32 data packed for GPU.
+use crate::material::Material;
+use crate::scene::InstancedGroup;
+
+/// Vertex with f32 data packed for GPU, in the default [`VertexLayout`].
 #[repr(C)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
 pub struct GpuVertex {
     pub position: [f32; 3],
     pub normal: [f32; 3],
@@ -22,13 +26,99 @@ impl GpuVertex {
 
     /// Convert vertex array to raw bytes for GPU upload.
     pub fn as_bytes(vertices: &[GpuVertex]) -> Vec<u8> {
-        let size = std::mem::size_of::<GpuVertex>() * vertices.len();
-        let mut bytes = Vec::with_capacity(size);
-        unsafe {
-            let ptr = vertices.as_ptr() as *const u8;
-            bytes.extend_from_slice(std::slice::from_raw_parts(ptr, size));
+        bytemuck::cast_slice(vertices).to_vec()
+    }
+}
+
+/// Data type of a vertex attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VertexFormat {
+    Float32x2,
+    Float32x3,
+    Float32x4,
+}
+
+impl VertexFormat {
+    /// Size in bytes.
+    pub fn size(self) -> u64 {
+        match self {
+            VertexFormat::Float32x2 => 8,
+            VertexFormat::Float32x3 => 12,
+            VertexFormat::Float32x4 => 16,
         }
-        bytes
+    }
+}
+
+/// One attribute of an interleaved vertex, as a pipeline's vertex buffer
+/// layout declares it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VertexAttribute {
+    /// Shader location: 0 position, 1 normal, 2 uv, 3 tangent, 4 color
+    pub location: u32,
+    /// Byte offset within the vertex
+    pub offset: u64,
+    pub format: VertexFormat,
+}
+
+/// Attributes of the vertex buffer a pipeline reads.
+///
+/// Position and normal are always present; the others are chosen per
+/// pipeline, e.g. tangents only where materials are normal-mapped, or
+/// colours for batches that merge meshes of different materials. The
+/// default matches [`GpuVertex`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VertexLayout {
+    pub uv: bool,
+    /// Tangent `xyz` along increasing u and handedness `w` (±1)
+    pub tangent: bool,
+    /// Linear RGBA of the mesh material
+    pub color: bool,
+}
+
+impl Default for VertexLayout {
+    fn default() -> Self {
+        Self {
+            uv: true,
+            tangent: false,
+            color: false,
+        }
+    }
+}
+
+impl VertexLayout {
+    /// Position and normal only.
+    pub const POSITION_NORMAL: Self = Self {
+        uv: false,
+        tangent: false,
+        color: false,
+    };
+
+    /// Attributes in buffer order, tightly packed.
+    pub fn attributes(&self) -> Vec<VertexAttribute> {
+        let mut formats = vec![(0, VertexFormat::Float32x3), (1, VertexFormat::Float32x3)];
+        if self.uv {
+            formats.push((2, VertexFormat::Float32x2));
+        }
+        if self.tangent {
+            formats.push((3, VertexFormat::Float32x4));
+        }
+        if self.color {
+            formats.push((4, VertexFormat::Float32x4));
+        }
+        let mut offset = 0;
+        formats
+            .into_iter()
+            .map(|(location, format)| {
+                let attribute = VertexAttribute { location, offset, format };
+                offset += format.size();
+                attribute
+            })
+            .collect()
+    }
+
+    /// Bytes per vertex.
+    pub fn stride(&self) -> u64 {
+        self.attributes().iter().map(|a| a.format.size()).sum()
     }
 }
 
@@ -37,55 +127,251 @@ impl GpuVertex {
 pub struct RenderMesh {
     pub vertices: Vec<GpuVertex>,
     pub indices: Vec<u32>,
+    /// `vertices` with the attributes of `layout`, interleaved
     pub vertex_buffer_bytes: Vec<u8>,
     pub index_buffer_bytes: Vec<u8>,
+    pub material: MaterialUniforms,
+    pub layout: VertexLayout,
 }
 
-/// Convert a TriangleMesh to GPU-ready buffers.
+/// Convert a TriangleMesh to GPU-ready buffers with the default material.
 pub fn prepare_mesh(mesh: &TriangleMesh) -> RenderMesh {
+    prepare_mesh_with_material(mesh, &Material::default())
+}
+
+/// Convert a TriangleMesh and its material to GPU-ready buffers.
+pub fn prepare_mesh_with_material(mesh: &TriangleMesh, material: &Material) -> RenderMesh {
+    prepare_mesh_with_layout(mesh, material, &VertexLayout::default())
+}
+
+/// Convert a TriangleMesh and its material to GPU-ready buffers whose
+/// vertex buffer holds the attributes of `layout`.
+pub fn prepare_mesh_with_layout(
+    mesh: &TriangleMesh,
+    material: &Material,
+    layout: &VertexLayout,
+) -> RenderMesh {
     let vertex_count = mesh.positions.len();
-    let mut vertices = Vec::with_capacity(vertex_count);
+    let normals: Vec<Vector3> = (0..vertex_count)
+        .map(|i| mesh.normals.get(i).copied().unwrap_or(Vector3::Y))
+        .collect();
 
     // Convert each vertex to GPU format
-    for i in 0..vertex_count {
-        let pos = mesh.positions[i];
-        let normal = mesh.normals.get(i).copied().unwrap_or(Vector3::Y);
-        let uv = mesh.uvs.get(i).copied().unwrap_or(Point2::ZERO);
+    let vertices: Vec<GpuVertex> = (0..vertex_count)
+        .map(|i| {
+            let uv = mesh.uvs.get(i).copied().unwrap_or(Point2::ZERO);
+            GpuVertex::from_mesh_vertex(mesh.positions[i], normals[i], uv)
+        })
+        .collect();
 
-        vertices.push(GpuVertex::from_mesh_vertex(pos, normal, uv));
+    let tangents = if layout.tangent {
+        vertex_tangents(mesh, &normals)
+    } else {
+        Vec::new()
+    };
+    let color = material.base_color_rgba();
+    let components = layout.stride() as usize / 4;
+    let mut data: Vec<f32> = Vec::with_capacity(vertex_count * components);
+    for (i, vertex) in vertices.iter().enumerate() {
+        data.extend_from_slice(&vertex.position);
+        data.extend_from_slice(&vertex.normal);
+        if layout.uv {
+            data.extend_from_slice(&vertex.uv);
+        }
+        if layout.tangent {
+            data.extend_from_slice(&tangents[i]);
+        }
+        if layout.color {
+            data.extend_from_slice(&color);
+        }
     }
 
-    // Convert to byte buffers
-    let vertex_buffer_bytes = GpuVertex::as_bytes(&vertices);
-    let index_buffer_bytes = indices_to_bytes(&mesh.indices);
-
     RenderMesh {
         vertices,
         indices: mesh.indices.clone(),
+        vertex_buffer_bytes: bytemuck::cast_slice(&data).to_vec(),
+        index_buffer_bytes: indices_to_bytes(&mesh.indices),
+        material: MaterialUniforms::from_material(material),
+        layout: *layout,
+    }
+}
+
+/// Per-instance vertex buffer attributes: the four columns of the model
+/// matrix, after the five locations a [`VertexLayout`] can use.
+///
+/// Bind the transform buffer of [`RenderInstances`] as a second vertex
+/// buffer with a stride of 64 bytes, stepped per instance. In wgpu:
+///
+/// ```text
+/// wgpu::VertexBufferLayout {
+///     array_stride: 64,
+///     step_mode: wgpu::VertexStepMode::Instance,
+///     attributes: &wgpu::vertex_attr_array![5 => Float32x4, 6 => Float32x4,
+///                                           7 => Float32x4, 8 => Float32x4],
+/// }
+/// ```
+///
+/// and rebuild `mat4x4<f32>(c0, c1, c2, c3)` in the vertex shader.
+pub const INSTANCE_ATTRIBUTES: [VertexAttribute; 4] = [
+    VertexAttribute { location: 5, offset: 0, format: VertexFormat::Float32x4 },
+    VertexAttribute { location: 6, offset: 16, format: VertexFormat::Float32x4 },
+    VertexAttribute { location: 7, offset: 32, format: VertexFormat::Float32x4 },
+    VertexAttribute { location: 8, offset: 48, format: VertexFormat::Float32x4 },
+];
+
+/// Prepared instanced draw: the base geometry once, and one model matrix
+/// per instance.
+#[derive(Debug, Clone)]
+pub struct RenderInstances {
+    pub mesh: RenderMesh,
+    /// Column-major model matrices, as in [`InstancedGroup::transforms`]
+    pub transforms: Vec<[f32; 16]>,
+    /// `transforms` for the instance buffer, see [`INSTANCE_ATTRIBUTES`]
+    pub instance_buffer_bytes: Vec<u8>,
+}
+
+impl RenderInstances {
+    /// Number of instances to draw.
+    pub fn instance_count(&self) -> u32 {
+        self.transforms.len() as u32
+    }
+}
+
+/// Convert an instanced group to GPU-ready buffers with the default
+/// vertex layout.
+pub fn prepare_instanced(group: &InstancedGroup) -> RenderInstances {
+    prepare_instanced_with_layout(group, &VertexLayout::default())
+}
+
+/// Convert an instanced group to GPU-ready buffers whose vertex buffer
+/// holds the attributes of `layout`. Normals stay in the base geometry's
+/// frame; shaders transform them with the instance matrix.
+pub fn prepare_instanced_with_layout(
+    group: &InstancedGroup,
+    layout: &VertexLayout,
+) -> RenderInstances {
+    RenderInstances {
+        mesh: prepare_mesh_with_layout(&group.mesh, &group.material, layout),
+        transforms: group.transforms.clone(),
+        instance_buffer_bytes: bytemuck::cast_slice(&group.transforms).to_vec(),
+    }
+}
+
+/// Per-vertex tangents from the UVs, made perpendicular to `normals`.
+/// Vertices without usable UVs get an arbitrary tangent in their plane.
+fn vertex_tangents(mesh: &TriangleMesh, normals: &[Vector3]) -> Vec<[f32; 4]> {
+    let vertex_count = mesh.positions.len();
+    let mut tangents = vec![Vector3::ZERO; vertex_count];
+    let mut bitangents = vec![Vector3::ZERO; vertex_count];
+    if mesh.uvs.len() == vertex_count {
+        for tri in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [tri[0] as usize, tri[1] as usize, tri[2] as usize];
+            let (p, uv) = (&mesh.positions, &mesh.uvs);
+            let (e1, e2) = (p[b] - p[a], p[c] - p[a]);
+            let (d1, d2) = (uv[b] - uv[a], uv[c] - uv[a]);
+            let det = d1.x * d2.y - d2.x * d1.y;
+            if det.abs() < 1e-12 {
+                continue;
+            }
+            let tangent = (e1 * d2.y - e2 * d1.y) / det;
+            let bitangent = (e2 * d1.x - e1 * d2.x) / det;
+            for i in [a, b, c] {
+                tangents[i] += tangent;
+                bitangents[i] += bitangent;
+            }
+        }
+    }
+
+    (0..vertex_count)
+        .map(|i| {
+            let normal = normals[i];
+            let mut tangent = (tangents[i] - normal * normal.dot(tangents[i])).normalize_or_zero();
+            if tangent == Vector3::ZERO {
+                tangent = normal.any_orthonormal_vector();
+            }
+            // Mirrored UVs run the bitangent against normal × tangent
+            let mirrored = normal.cross(tangent).dot(bitangents[i]) < 0.0;
+            let handedness = if mirrored { -1.0 } else { 1.0 };
+            [tangent.x as f32, tangent.y as f32, tangent.z as f32, handedness]
+        })
+        .collect()
+}
+
+/// Uniform buffer for a mesh material.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+pub struct MaterialUniforms {
+    /// Linear RGB plus alpha.
+    pub base_color: [f32; 4],
+    /// `[metallic, roughness, double_sided (0 or 1), has_texture (0 or 1)]`.
+    pub params: [f32; 4],
+}
+
+impl MaterialUniforms {
+    /// Pack a material. The texture itself is bound separately.
+    pub fn from_material(material: &Material) -> Self {
+        Self {
+            base_color: material.base_color_rgba(),
+            params: [
+                material.metallic,
+                material.roughness,
+                if material.double_sided { 1.0 } else { 0.0 },
+                if material.texture.is_some() { 1.0 } else { 0.0 },
+            ],
+        }
+    }
+
+    /// Raw bytes for GPU upload.
+    pub fn as_bytes(&self) -> &[u8] {
+        bytemuck::bytes_of(self)
+    }
+}
+
+/// Prepared line-list data (edge overlays) ready for GPU upload.
+///
+/// Drawn as a second primitive set with a line-list topology on top of the
+/// shaded triangles.
+#[derive(Debug, Clone)]
+pub struct RenderLines {
+    pub positions: Vec<[f32; 3]>,
+    pub indices: Vec<u32>,
+    pub vertex_buffer_bytes: Vec<u8>,
+    pub index_buffer_bytes: Vec<u8>,
+}
+
+/// Convert a LineList to GPU-ready buffers.
+pub fn prepare_lines(lines: &LineList) -> RenderLines {
+    let positions: Vec<[f32; 3]> = lines
+        .positions
+        .iter()
+        .map(|p| [p.x as f32, p.y as f32, p.z as f32])
+        .collect();
+    let vertex_buffer_bytes = bytemuck::cast_slice(&positions).to_vec();
+
+    RenderLines {
+        positions,
+        indices: lines.indices.clone(),
         vertex_buffer_bytes,
-        index_buffer_bytes,
+        index_buffer_bytes: indices_to_bytes(&lines.indices),
     }
 }
 
 /// Convert index array to raw bytes.
 fn indices_to_bytes(indices: &[u32]) -> Vec<u8> {
-    let size = std::mem::size_of::<u32>() * indices.len();
-    let mut bytes = Vec::with_capacity(size);
-    unsafe {
-        let ptr = indices.as_ptr() as *const u8;
-        bytes.extend_from_slice(std::slice::from_raw_parts(ptr, size));
-    }
-    bytes
+    bytemuck::cast_slice(indices).to_vec()
 }
 
 /// Uniform buffer for camera matrices.
 #[repr(C)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
 pub struct CameraUniforms {
     pub view: [[f32; 4]; 4],
     pub projection: [[f32; 4]; 4],
     pub view_projection: [[f32; 4]; 4],
     pub eye_position: [f32; 4],
+    /// `[is_orthographic (0 or 1), orthographic height, near, far]`.
+    /// Shaders need the flag to use a constant view direction for lighting.
+    pub projection_params: [f32; 4],
 }
 
 impl CameraUniforms {
@@ -100,14 +386,75 @@ impl CameraUniforms {
             camera.eye.z as f32,
             1.0,
         ];
+        let (ortho_flag, ortho_height) = match camera.projection {
+            crate::camera::Projection::Perspective => (0.0, 0.0),
+            crate::camera::Projection::Orthographic { height } => (1.0, height as f32),
+        };
+        let projection_params = [ortho_flag, ortho_height, camera.near as f32, camera.far as f32];
 
         Self {
             view,
             projection,
             view_projection,
             eye_position,
+            projection_params,
         }
     }
+
+    /// Raw bytes for GPU upload.
+    pub fn as_bytes(&self) -> &[u8] {
+        bytemuck::bytes_of(self)
+    }
+}
+
+/// Maximum number of section planes passed to the GPU.
+pub const MAX_SECTION_PLANES: usize = 6;
+
+/// Uniform buffer for section planes.
+///
+/// Each plane is `[nx, ny, nz, d]`; the vertex shader writes
+/// `dot(n, p) + d` to a clip distance (or the fragment shader discards when
+/// it is negative), keeping geometry on the side the normal points to.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct ClipPlaneUniforms {
+    pub planes: [[f32; 4]; MAX_SECTION_PLANES],
+    /// `[active plane count, 0, 0, 0]`, padded to 16 bytes.
+    pub count: [u32; 4],
+}
+
+impl ClipPlaneUniforms {
+    /// Pack the scene's section planes. Planes beyond
+    /// [`MAX_SECTION_PLANES`] are ignored.
+    pub fn from_planes(planes: &[cst_math::plane::Plane]) -> Self {
+        let mut packed = [[0.0; 4]; MAX_SECTION_PLANES];
+        let count = planes.len().min(MAX_SECTION_PLANES);
+        for (slot, plane) in packed.iter_mut().zip(planes) {
+            let n = plane.normal;
+            *slot = [n.x as f32, n.y as f32, n.z as f32, -n.dot(plane.origin) as f32];
+        }
+        Self {
+            planes: packed,
+            count: [count as u32, 0, 0, 0],
+        }
+    }
+
+    /// Number of planes in use.
+    pub fn active(&self) -> usize {
+        (self.count[0] as usize).min(MAX_SECTION_PLANES)
+    }
+
+    /// Clip distance `dot(n, p) + d` of `point` to plane `index`, as the
+    /// vertex shader writes it: negative where geometry is cut away.
+    pub fn distance(&self, index: usize, point: Point3) -> f64 {
+        let [nx, ny, nz, d] = self.planes[index].map(f64::from);
+        nx * point.x + ny * point.y + nz * point.z + d
+    }
+
+    /// Raw bytes for GPU upload.
+    pub fn as_bytes(&self) -> &[u8] {
+        bytemuck::bytes_of(self)
+    }
 }
 
 /// Convert f64 matrix to f32 matrix.
@@ -123,6 +470,7 @@ fn convert_matrix_to_f32(mat: [[f64; 4]; 4]) -> [[f32; 4]; 4] {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use cst_math::DMat4;
 
     fn create_test_mesh() -> TriangleMesh {
         TriangleMesh {
@@ -177,6 +525,97 @@ mod tests {
         assert_eq!(render_mesh.index_buffer_bytes.len(), 3 * 4);
     }
 
+    #[test]
+    fn test_vertex_layouts() {
+        let mesh = create_test_mesh();
+        let material = Material::from_color([0.2, 0.4, 0.6]);
+
+        assert_eq!(VertexLayout::default().stride(), 32);
+        assert_eq!(VertexLayout::POSITION_NORMAL.stride(), 24);
+        let full = VertexLayout { uv: true, tangent: true, color: true };
+        let offsets: Vec<(u32, u64)> =
+            full.attributes().iter().map(|a| (a.location, a.offset)).collect();
+        assert_eq!(offsets, vec![(0, 0), (1, 12), (2, 24), (3, 32), (4, 48)]);
+        assert_eq!(full.stride(), 64);
+
+        let plain = prepare_mesh_with_layout(&mesh, &material, &VertexLayout::POSITION_NORMAL);
+        assert_eq!(plain.vertex_buffer_bytes.len(), 3 * 24);
+        let default = prepare_mesh_with_material(&mesh, &material);
+        assert_eq!(default.vertex_buffer_bytes, GpuVertex::as_bytes(&default.vertices));
+
+        let render_mesh = prepare_mesh_with_layout(&mesh, &material, &full);
+        assert_eq!(render_mesh.layout, full);
+        let floats: &[f32] = bytemuck::cast_slice(&render_mesh.vertex_buffer_bytes);
+        assert_eq!(floats.len(), 3 * 16);
+        // u runs along x and v along y, so the tangent is +x, right-handed
+        assert_eq!(&floats[8..12], &[1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(&floats[12..16], &[0.2, 0.4, 0.6, 1.0]);
+    }
+
+    #[test]
+    fn test_tangents_follow_mirrored_uvs() {
+        let mut mesh = create_test_mesh();
+        for uv in &mut mesh.uvs {
+            uv.x = -uv.x;
+        }
+        let tangents = vertex_tangents(&mesh, &mesh.normals);
+        assert_eq!(tangents[0], [-1.0, 0.0, 0.0, -1.0]);
+
+        mesh.uvs.clear();
+        let tangent = vertex_tangents(&mesh, &mesh.normals)[0];
+        assert_eq!(tangent[2], 0.0);
+        assert!((tangent[0].hypot(tangent[1]) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_prepare_instanced() {
+        let mut scene = crate::scene::Scene::new();
+        let mut shifted = DMat4::IDENTITY.to_cols_array().map(|v| v as f32);
+        shifted[12] = 4.0;
+        let transforms = vec![DMat4::IDENTITY.to_cols_array().map(|v| v as f32), shifted];
+        scene.add_instanced_group("Column", create_test_mesh(), [0.5, 0.5, 0.5], transforms);
+
+        let instances = prepare_instanced(&scene.instanced_groups[0]);
+        assert_eq!(instances.instance_count(), 2);
+        assert_eq!(instances.mesh.vertices.len(), 3);
+        assert_eq!(instances.mesh.material.base_color, [0.5, 0.5, 0.5, 1.0]);
+        assert_eq!(instances.instance_buffer_bytes.len(), 2 * 64);
+        let floats: &[f32] = bytemuck::cast_slice(&instances.instance_buffer_bytes);
+        assert_eq!(floats[16 + 12], 4.0);
+
+        let stride: u64 = INSTANCE_ATTRIBUTES.iter().map(|a| a.format.size()).sum();
+        assert_eq!(stride, 64);
+        let full = VertexLayout { uv: true, tangent: true, color: true };
+        let last = full.attributes().last().unwrap().location;
+        assert!(INSTANCE_ATTRIBUTES.iter().all(|a| a.location > last));
+    }
+
+    #[test]
+    fn test_uniform_bytes() {
+        let camera = CameraUniforms::from_camera(&crate::camera::Camera::default());
+        assert_eq!(camera.as_bytes().len(), std::mem::size_of::<CameraUniforms>());
+        assert_eq!(&camera.as_bytes()[192..196], &camera.eye_position[0].to_ne_bytes());
+        assert_eq!(ClipPlaneUniforms::from_planes(&[]).as_bytes().len(), 112);
+        let material = MaterialUniforms::from_material(&Material::default());
+        assert_eq!(material.as_bytes().len(), 32);
+    }
+
+    #[test]
+    fn test_prepare_mesh_material() {
+        let mesh = create_test_mesh();
+        assert_eq!(prepare_mesh(&mesh).material.base_color, [0.8, 0.8, 0.8, 1.0]);
+
+        let material = Material {
+            metallic: 1.0,
+            double_sided: false,
+            ..Material::from_color([0.2, 0.4, 0.6]).with_alpha(0.5)
+        };
+        let uniforms = prepare_mesh_with_material(&mesh, &material).material;
+        assert_eq!(uniforms.base_color, [0.2, 0.4, 0.6, 0.5]);
+        assert_eq!(uniforms.params, [1.0, 0.5, 0.0, 0.0]);
+        assert_eq!(std::mem::size_of::<MaterialUniforms>(), 32);
+    }
+
     #[test]
     fn test_camera_uniforms_from_camera() {
         let camera = crate::camera::Camera::default();
@@ -195,6 +634,49 @@ mod tests {
         assert!(proj_sum.abs() > 0.1);
     }
 
+    #[test]
+    fn test_camera_uniforms_orthographic() {
+        let mut camera = crate::camera::Camera::default();
+        let perspective = CameraUniforms::from_camera(&camera);
+        assert_eq!(perspective.projection_params, [0.0, 0.0, 0.1, 100.0]);
+
+        camera.projection = crate::camera::Projection::Orthographic { height: 8.0 };
+        let ortho = CameraUniforms::from_camera(&camera);
+        assert_eq!(ortho.projection_params, [1.0, 8.0, 0.1, 100.0]);
+        assert!((ortho.projection[3][3] - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_clip_plane_uniforms() {
+        use cst_math::plane::Plane;
+
+        let planes = vec![Plane::new(Point3::new(0.0, 0.0, 3.0), Vector3::new(0.0, 0.0, -2.0)); 8];
+        let uniforms = ClipPlaneUniforms::from_planes(&planes);
+
+        // 6 planes * 16 bytes + 16 bytes count
+        assert_eq!(std::mem::size_of::<ClipPlaneUniforms>(), 112);
+        assert_eq!(uniforms.count[0], MAX_SECTION_PLANES as u32);
+        assert_eq!(uniforms.planes[0], [0.0, 0.0, -1.0, 3.0]);
+        assert_eq!(uniforms.active(), MAX_SECTION_PLANES);
+        assert_eq!(uniforms.distance(0, Point3::new(5.0, 1.0, 1.0)), 2.0);
+        assert_eq!(uniforms.distance(0, Point3::new(0.0, 0.0, 4.0)), -1.0);
+
+        let empty = ClipPlaneUniforms::from_planes(&[]);
+        assert_eq!(empty.count[0], 0);
+        assert_eq!(empty.active(), 0);
+    }
+
+    #[test]
+    fn test_prepare_lines() {
+        let lines = cst_mesh::feature_edges(&create_test_mesh(), 0.5);
+        let render_lines = prepare_lines(&lines);
+
+        // Open triangle: three boundary segments over three vertices
+        assert_eq!(render_lines.indices.len(), 6);
+        assert_eq!(render_lines.vertex_buffer_bytes.len(), 3 * 12);
+        assert_eq!(render_lines.index_buffer_bytes.len(), 6 * 4);
+    }
+
     #[test]
     fn test_gpu_vertex_from_mesh_vertex() {
         let pos = Point3::new(1.0, 2.0, 3.0);
@@ -0,0 +1,430 @@
+//! Floor plans: storeys cut with a horizontal plane and drawn from above.
+//!
+//! [`Scene::floor_plans`] walks the storeys of the spatial tree and cuts
+//! their meshes `cut_height` above each storey elevation. Walls come out as
+//! filled outlines; IFC wall geometry has its openings voided, so doors and
+//! windows show as gaps in them. Plans export as SVG, or as DXF polylines
+//! with one layer per IFC type.
+
+use std::fmt::Write as _;
+
+use cst_math::plane::Plane;
+use cst_math::{Aabb2, Point2, Point3, Vector3};
+use cst_mesh::section_mesh;
+
+use crate::bcf::xml_escape;
+use crate::scene::{Scene, SpatialTreeNode};
+
+/// Spatial tree kind of the nodes that get a plan.
+const STOREY_KIND: &str = "IfcBuildingStorey";
+
+/// Options for [`Scene::floor_plans`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FloorPlanOptions {
+    /// Height of the cut plane above the storey elevation, in scene units
+    pub cut_height: f64,
+}
+
+impl Default for FloorPlanOptions {
+    /// Cut at 1.2 m, for scenes in metres.
+    fn default() -> Self {
+        Self { cut_height: 1.2 }
+    }
+}
+
+/// The section of one storey.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FloorPlan {
+    /// Storey name
+    pub name: String,
+    pub elevation: f64,
+    /// Height of the cut plane
+    pub cut_elevation: f64,
+    /// Elements the cut plane passes through
+    pub elements: Vec<PlanElement>,
+}
+
+/// An element cut by a floor plan.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanElement {
+    pub name: String,
+    pub ifc_type: Option<String>,
+    pub global_id: Option<String>,
+    pub outlines: Vec<PlanOutline>,
+}
+
+/// A section outline in plan coordinates. Closed outlines run
+/// counter-clockwise around material and clockwise around holes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanOutline {
+    pub points: Vec<Point2>,
+    pub closed: bool,
+}
+
+impl PlanElement {
+    /// Whether the element is a wall, including IFC2x3 `IfcWallStandardCase`.
+    pub fn is_wall(&self) -> bool {
+        self.ifc_type
+            .as_deref()
+            .is_some_and(|t| t.to_ascii_uppercase().starts_with("IFCWALL"))
+    }
+}
+
+impl FloorPlan {
+    /// Extent of all outlines.
+    pub fn bounds(&self) -> Option<Aabb2> {
+        let points: Vec<Point2> = self
+            .elements
+            .iter()
+            .flat_map(|e| &e.outlines)
+            .flat_map(|o| o.points.iter().copied())
+            .collect();
+        Aabb2::from_points(&points)
+    }
+
+    /// SVG drawing with plan y pointing up the page. Walls are filled,
+    /// everything else is outlined; each path carries the element name as
+    /// its title and the IFC type and GlobalId as data attributes.
+    pub fn to_svg(&self) -> String {
+        let bounds = self
+            .bounds()
+            .unwrap_or_else(|| Aabb2::new(Point2::ZERO, Point2::ONE));
+        let margin = bounds.extents().max_element() * 0.02;
+        let origin = Point2::new(bounds.min.x - margin, bounds.max.y + margin);
+        let size = bounds.extents() + Point2::splat(2.0 * margin);
+        let to_page = |p: Point2| Point2::new(p.x - origin.x, origin.y - p.y);
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {} {}">"#,
+            coord(size.x),
+            coord(size.y)
+        );
+        let _ = writeln!(svg, "<title>{}</title>", xml_escape(&self.name));
+        svg.push_str(
+            "<style>path { stroke: #000; stroke-width: 1; vector-effect: non-scaling-stroke; } \
+             .wall { fill: #404040; fill-rule: evenodd; } .element { fill: none; }</style>\n",
+        );
+
+        // Walls last, so their fill covers outlines of what they enclose
+        let (walls, others): (Vec<&PlanElement>, Vec<&PlanElement>) =
+            self.elements.iter().partition(|e| e.is_wall());
+        for element in others.into_iter().chain(walls) {
+            let mut filled = String::new();
+            let mut open = String::new();
+            for outline in &element.outlines {
+                let target = if outline.closed && element.is_wall() {
+                    &mut filled
+                } else {
+                    &mut open
+                };
+                for (i, &p) in outline.points.iter().enumerate() {
+                    let p = to_page(p);
+                    let command = if i == 0 { 'M' } else { 'L' };
+                    let _ = write!(target, "{}{} {} ", command, coord(p.x), coord(p.y));
+                }
+                if outline.closed {
+                    target.push_str("Z ");
+                }
+            }
+            for (class, path) in [("wall", filled), ("element", open)] {
+                if path.is_empty() {
+                    continue;
+                }
+                let _ = write!(svg, r#"<path class="{}" d="{}""#, class, path.trim_end());
+                if let Some(ifc_type) = &element.ifc_type {
+                    let _ = write!(svg, r#" data-ifc-type="{}""#, xml_escape(ifc_type));
+                }
+                if let Some(global_id) = &element.global_id {
+                    let _ = write!(svg, r#" data-global-id="{}""#, xml_escape(global_id));
+                }
+                let _ = writeln!(svg, "><title>{}</title></path>", xml_escape(&element.name));
+            }
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// ASCII DXF (R12) with every outline as a 2D polyline on a layer
+    /// named after the element's IFC type.
+    pub fn to_dxf(&self) -> String {
+        let mut dxf = String::new();
+        let mut group = |code: u32, value: &str| {
+            let _ = writeln!(dxf, "{}\n{}", code, value);
+        };
+        group(0, "SECTION");
+        group(2, "HEADER");
+        group(9, "$ACADVER");
+        group(1, "AC1009");
+        group(0, "ENDSEC");
+        group(0, "SECTION");
+        group(2, "ENTITIES");
+        for element in &self.elements {
+            let layer = dxf_layer(element.ifc_type.as_deref().unwrap_or("0"));
+            for outline in &element.outlines {
+                group(0, "POLYLINE");
+                group(8, &layer);
+                group(66, "1");
+                group(70, if outline.closed { "1" } else { "0" });
+                for p in &outline.points {
+                    group(0, "VERTEX");
+                    group(8, &layer);
+                    group(10, &coord(p.x));
+                    group(20, &coord(p.y));
+                }
+                group(0, "SEQEND");
+                group(8, &layer);
+            }
+        }
+        group(0, "ENDSEC");
+        group(0, "EOF");
+        dxf
+    }
+}
+
+impl Scene {
+    /// One plan per storey of the spatial tree, bottom to top.
+    ///
+    /// Each storey is cut `cut_height` above its elevation, or above the
+    /// lowest point of its meshes when the model gives no elevation. Hidden
+    /// meshes and storeys without meshes are left out.
+    pub fn floor_plans(&self, options: &FloorPlanOptions) -> Vec<FloorPlan> {
+        let mut storeys = Vec::new();
+        if let Some(tree) = &self.spatial_tree {
+            collect_storeys(tree, &mut storeys);
+        }
+        let mut plans: Vec<FloorPlan> = storeys
+            .into_iter()
+            .filter_map(|storey| {
+                let mut meshes = Vec::new();
+                collect_meshes(storey, &mut meshes);
+                if meshes.is_empty() {
+                    return None;
+                }
+                let elevation = storey.elevation.or_else(|| {
+                    meshes
+                        .iter()
+                        .filter_map(|&i| self.meshes.get(i)?.bounds())
+                        .map(|b| b.min.z)
+                        .reduce(f64::min)
+                })?;
+                Some(self.floor_plan(&storey.name, elevation, &meshes, options))
+            })
+            .collect();
+        plans.sort_by(|a, b| a.elevation.total_cmp(&b.elevation));
+        plans
+    }
+
+    /// Cut the given meshes `cut_height` above `elevation`.
+    pub fn floor_plan(
+        &self,
+        name: &str,
+        elevation: f64,
+        meshes: &[usize],
+        options: &FloorPlanOptions,
+    ) -> FloorPlan {
+        let cut_elevation = elevation + options.cut_height;
+        let plane = Plane::new(Point3::new(0.0, 0.0, cut_elevation), Vector3::Z);
+        let elements = meshes
+            .iter()
+            .filter_map(|&i| self.meshes.get(i))
+            .filter(|m| m.visible)
+            .filter(|m| {
+                m.bounds()
+                    .is_some_and(|b| b.min.z <= cut_elevation && b.max.z >= cut_elevation)
+            })
+            .filter_map(|m| {
+                let outlines: Vec<PlanOutline> = section_mesh(&m.mesh, &plane)
+                    .into_iter()
+                    .map(|section| PlanOutline {
+                        points: section.points.iter().map(|p| p.truncate()).collect(),
+                        closed: section.closed,
+                    })
+                    .collect();
+                (!outlines.is_empty()).then(|| PlanElement {
+                    name: m.name.clone(),
+                    ifc_type: m.metadata.ifc_type.clone(),
+                    global_id: m.metadata.global_id.clone(),
+                    outlines,
+                })
+            })
+            .collect();
+        FloorPlan {
+            name: name.to_string(),
+            elevation,
+            cut_elevation,
+            elements,
+        }
+    }
+}
+
+fn collect_storeys<'a>(node: &'a SpatialTreeNode, storeys: &mut Vec<&'a SpatialTreeNode>) {
+    if node.kind == STOREY_KIND {
+        storeys.push(node);
+        return;
+    }
+    for child in &node.children {
+        collect_storeys(child, storeys);
+    }
+}
+
+/// Meshes of a node and everything below it, e.g. spaces in a storey.
+fn collect_meshes(node: &SpatialTreeNode, meshes: &mut Vec<usize>) {
+    meshes.extend_from_slice(&node.meshes);
+    for child in &node.children {
+        collect_meshes(child, meshes);
+    }
+}
+
+/// Coordinate rounded to 1/1000 of a unit, without trailing zeros.
+fn coord(value: f64) -> String {
+    // Adding 0.0 turns -0 into 0
+    ((value * 1000.0).round() / 1000.0 + 0.0).to_string()
+}
+
+/// DXF layer names may not contain `<>/\":;?*|=,` or backquotes.
+fn dxf_layer(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if "<>/\\\":;?*|=,`".contains(c) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::ElementMetadata;
+    use cst_mesh::TriangleMesh;
+
+    /// Axis-aligned box with shared corners, outward winding.
+    fn box_mesh(min: Point3, max: Point3) -> TriangleMesh {
+        let positions = (0..8)
+            .map(|i| {
+                Point3::new(
+                    if i & 1 == 0 { min.x } else { max.x },
+                    if i & 2 == 0 { min.y } else { max.y },
+                    if i & 4 == 0 { min.z } else { max.z },
+                )
+            })
+            .collect();
+        let quads = [
+            [0, 2, 3, 1],
+            [4, 5, 7, 6],
+            [0, 1, 5, 4],
+            [2, 6, 7, 3],
+            [0, 4, 6, 2],
+            [1, 3, 7, 5],
+        ];
+        let indices = quads
+            .iter()
+            .flat_map(|[a, b, c, d]| [*a, *b, *c, *a, *c, *d])
+            .collect();
+        TriangleMesh {
+            positions,
+            normals: vec![],
+            indices,
+            uvs: vec![],
+        }
+    }
+
+    fn element(ifc_type: &str) -> ElementMetadata {
+        ElementMetadata {
+            ifc_type: Some(ifc_type.into()),
+            global_id: Some(format!("{}-guid", ifc_type)),
+            ..Default::default()
+        }
+    }
+
+    /// Two storeys 3 apart, each with a wall broken by a door opening and
+    /// a slab; the upper storey has no elevation set.
+    fn two_storey_scene() -> Scene {
+        let mut scene = Scene::new();
+        let mut building = SpatialTreeNode::new("Building", "IfcBuilding");
+        for (level, z) in [(0, 0.0), (1, 3.0)] {
+            let mut storey = SpatialTreeNode::new(&format!("Level {}", level), STOREY_KIND);
+            storey.elevation = (level == 0).then_some(z);
+            let first = scene.meshes.len();
+            let slab = box_mesh(Point3::new(0.0, 0.0, z), Point3::new(6.0, 4.0, z + 0.2));
+            scene.add_element("Slab", slab, [0.5, 0.5, 0.5], element("IfcSlab"));
+            // A wall from x = 0 to 6 with a door gap from 2 to 3
+            for (x0, x1) in [(0.0, 2.0), (3.0, 6.0)] {
+                let wall = box_mesh(Point3::new(x0, 0.0, z), Point3::new(x1, 0.2, z + 3.0));
+                scene.add_element("Wall <A>", wall, [0.8, 0.8, 0.8], element("IfcWall"));
+            }
+            storey.meshes = (first..scene.meshes.len()).collect();
+            building.children.push(storey);
+        }
+        // A roof level without geometry gets no plan
+        let mut roof = SpatialTreeNode::new("Roof", STOREY_KIND);
+        roof.elevation = Some(6.0);
+        building.children.push(roof);
+        scene.spatial_tree = Some(building);
+        scene
+    }
+
+    #[test]
+    fn test_floor_plans_per_storey() {
+        let scene = two_storey_scene();
+        let plans = scene.floor_plans(&FloorPlanOptions::default());
+        assert_eq!(plans.len(), 2);
+        assert_eq!(plans[0].name, "Level 0");
+        assert_eq!(plans[1].elevation, 3.0);
+        assert_eq!(plans[1].cut_elevation, 4.2);
+
+        // The slab lies below the cut; the wall shows as two pieces
+        let plan = &plans[0];
+        assert_eq!(plan.elements.len(), 2);
+        assert!(plan.elements.iter().all(PlanElement::is_wall));
+        let outline = &plan.elements[1].outlines[0];
+        assert!(outline.closed);
+        assert_eq!(outline.points.len(), 4);
+        let bounds = plan.bounds().unwrap();
+        assert_eq!(bounds.min, Point2::new(0.0, 0.0));
+        assert_eq!(bounds.max, Point2::new(6.0, 0.2));
+
+        // Hidden meshes are not drawn
+        let mut scene = scene;
+        scene.meshes[1].visible = false;
+        assert_eq!(
+            scene.floor_plans(&FloorPlanOptions::default())[0]
+                .elements
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_floor_plan_svg() {
+        let scene = two_storey_scene();
+        let plan = &scene.floor_plans(&FloorPlanOptions::default())[0];
+        let svg = plan.to_svg();
+        assert!(
+            svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 6.24 0.44\">")
+        );
+        assert!(svg.contains("<title>Level 0</title>"));
+        assert_eq!(svg.matches("<path class=\"wall\"").count(), 2);
+        assert!(svg.contains("data-ifc-type=\"IfcWall\" data-global-id=\"IfcWall-guid\""));
+        assert!(svg.contains("<title>Wall &lt;A&gt;</title>"));
+        // Page y runs down: the wall face at plan y = 0 is at the bottom
+        assert!(svg.contains(r#"d="M2.12 0.32 L2.12 0.12 L0.12 0.12 L0.12 0.32 Z""#));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn test_floor_plan_dxf() {
+        let scene = two_storey_scene();
+        let plan = &scene.floor_plans(&FloorPlanOptions::default())[0];
+        let dxf = plan.to_dxf();
+        assert_eq!(dxf.matches("\nPOLYLINE\n").count(), 2);
+        assert_eq!(dxf.matches("\nVERTEX\n").count(), 8);
+        assert!(dxf.contains("\n8\nIfcWall\n"));
+        assert!(dxf.ends_with("0\nEOF\n"));
+        assert_eq!(dxf_layer("A:B/C"), "A_B_C");
+    }
+}
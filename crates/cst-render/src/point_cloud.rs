@@ -0,0 +1,323 @@
+//! Point cloud sampling of a [`Scene`].
+//!
+//! Construction verification compares the as-built laser scan against the
+//! design model. Scans are point clouds, so the model is sampled into one:
+//! points spread uniformly over every visible surface, each carrying the
+//! normal of its face and the id of the element it lies on. Sampling is
+//! seeded, so a scene and its options always give the same cloud.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use cst_math::{DMat4, Point3, Vector3};
+use cst_mesh::TriangleMesh;
+
+use crate::scene::Scene;
+
+/// How densely to sample a scene.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointCloudOptions {
+    /// Average distance between neighbouring points, in scene units. Each
+    /// face gets about `area / spacing²` points.
+    pub spacing: f64,
+    /// Seed of the sampling sequence.
+    pub seed: u64,
+}
+
+impl Default for PointCloudOptions {
+    fn default() -> Self {
+        Self {
+            spacing: 0.01,
+            seed: 0,
+        }
+    }
+}
+
+/// Points sampled from the surfaces of a scene.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PointCloud {
+    pub points: Vec<Point3>,
+    /// Unit normal of the face each point lies on.
+    pub normals: Vec<Vector3>,
+    /// Index into `elements` for each point.
+    pub element_ids: Vec<u32>,
+    /// Element labels: the GlobalId when known, otherwise the mesh name.
+    /// Instances are labelled `NAME[i]`. Meshes with the same label share
+    /// an id.
+    pub elements: Vec<String>,
+}
+
+impl PointCloud {
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Write the cloud as a binary PLY file.
+    pub fn export_ply(&self, path: &Path) -> std::io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write_ply(&mut out)?;
+        out.flush()
+    }
+
+    /// Write binary little-endian PLY data: double coordinates, float
+    /// normals and a uint `element_id` per vertex. The element labels are
+    /// listed in `comment element ID LABEL` header lines.
+    pub fn write_ply<W: Write>(&self, out: &mut W) -> std::io::Result<()> {
+        writeln!(out, "ply")?;
+        writeln!(out, "format binary_little_endian 1.0")?;
+        writeln!(out, "comment CSTEngine point cloud")?;
+        for (id, label) in self.elements.iter().enumerate() {
+            writeln!(out, "comment element {} {}", id, header_safe(label))?;
+        }
+        writeln!(out, "element vertex {}", self.points.len())?;
+        for axis in ["x", "y", "z"] {
+            writeln!(out, "property double {}", axis)?;
+        }
+        for axis in ["nx", "ny", "nz"] {
+            writeln!(out, "property float {}", axis)?;
+        }
+        writeln!(out, "property uint element_id")?;
+        writeln!(out, "end_header")?;
+
+        for ((p, n), id) in self.points.iter().zip(&self.normals).zip(&self.element_ids) {
+            for coord in [p.x, p.y, p.z] {
+                out.write_all(&coord.to_le_bytes())?;
+            }
+            for coord in [n.x, n.y, n.z] {
+                out.write_all(&(coord as f32).to_le_bytes())?;
+            }
+            out.write_all(&id.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Write the cloud as an XYZ text file.
+    pub fn export_xyz(&self, path: &Path) -> std::io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write_xyz(&mut out)?;
+        out.flush()
+    }
+
+    /// Write one `x y z nx ny nz element_id` line per point. XYZ has no
+    /// header, so the element labels are not included.
+    pub fn write_xyz<W: Write>(&self, out: &mut W) -> std::io::Result<()> {
+        for ((p, n), id) in self.points.iter().zip(&self.normals).zip(&self.element_ids) {
+            writeln!(
+                out,
+                "{} {} {} {:.6} {:.6} {:.6} {}",
+                p.x, p.y, p.z, n.x, n.y, n.z, id
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// PLY header lines end at the first newline.
+fn header_safe(label: &str) -> String {
+    label
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect()
+}
+
+impl Scene {
+    /// Sample points uniformly over the visible meshes and every instance.
+    /// A spacing that is not positive gives an empty cloud.
+    pub fn sample_points(&self, options: &PointCloudOptions) -> PointCloud {
+        let mut cloud = PointCloud::default();
+        if options.spacing.is_nan() || options.spacing <= 0.0 {
+            return cloud;
+        }
+        let density = 1.0 / (options.spacing * options.spacing);
+        let mut rng = SplitMix64(options.seed);
+
+        let mut parts: Vec<(String, &TriangleMesh, DMat4)> = self
+            .meshes
+            .iter()
+            .filter(|m| m.visible)
+            .map(|m| {
+                let label = m
+                    .metadata
+                    .global_id
+                    .clone()
+                    .unwrap_or_else(|| m.name.clone());
+                (label, &m.mesh, DMat4::IDENTITY)
+            })
+            .collect();
+        for ig in &self.instanced_groups {
+            for instance in 0..ig.transforms.len() {
+                let label = format!("{}[{}]", ig.name, instance);
+                parts.push((label, &ig.mesh, ig.transform_matrix(instance)));
+            }
+        }
+
+        let mut ids: HashMap<String, u32> = HashMap::new();
+        for (label, mesh, transform) in parts {
+            // Elements split over several meshes share one id
+            let id = *ids.entry(label).or_insert_with_key(|label| {
+                cloud.elements.push(label.clone());
+                cloud.elements.len() as u32 - 1
+            });
+            for tri in mesh.indices.chunks_exact(3) {
+                let [a, b, c] = [tri[0], tri[1], tri[2]]
+                    .map(|i| transform.transform_point3(mesh.positions[i as usize]));
+                let cross = (b - a).cross(c - a);
+                let expected = 0.5 * cross.length() * density;
+                // Round the fractional part at random so small faces still
+                // get their share on average
+                let count =
+                    expected.floor() as usize + usize::from(rng.next_f64() < expected.fract());
+                let normal = cross.normalize_or_zero();
+                for _ in 0..count {
+                    // Uniform over the triangle
+                    let r1 = rng.next_f64().sqrt();
+                    let r2 = rng.next_f64();
+                    let p = a * (1.0 - r1) + b * (r1 * (1.0 - r2)) + c * (r1 * r2);
+                    cloud.points.push(p);
+                    cloud.normals.push(normal);
+                    cloud.element_ids.push(id);
+                }
+            }
+        }
+        cloud
+    }
+}
+
+/// The SplitMix64 generator: small, fast and good enough for sampling.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::ElementMetadata;
+    use cst_math::DVec3;
+
+    /// A unit square in the XY plane facing +Z.
+    fn square() -> TriangleMesh {
+        TriangleMesh {
+            positions: vec![
+                DVec3::new(0.0, 0.0, 0.0),
+                DVec3::new(1.0, 0.0, 0.0),
+                DVec3::new(1.0, 1.0, 0.0),
+                DVec3::new(0.0, 1.0, 0.0),
+            ],
+            indices: vec![0, 1, 2, 0, 2, 3],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_sample_points_covers_surfaces() {
+        let mut scene = Scene::new();
+        let metadata = ElementMetadata {
+            global_id: Some("0abc".to_string()),
+            ..Default::default()
+        };
+        scene.add_element("Slab", square(), [0.8, 0.8, 0.8], metadata.clone());
+        scene.add_mesh("Hidden", square(), [1.0, 0.0, 0.0]);
+        scene.meshes[1].visible = false;
+        // A second mesh of the same element, e.g. in another material
+        let mut raised = square();
+        raised.positions.iter_mut().for_each(|p| p.z = 1.0);
+        scene.add_element("Slab", raised, [0.8, 0.8, 0.8], metadata);
+        let offsets = [[
+            1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 5.0, 1.0,
+        ]];
+        scene.add_instanced_group("Panel", square(), [0.8, 0.8, 0.8], offsets.to_vec());
+
+        let options = PointCloudOptions {
+            spacing: 0.05,
+            seed: 7,
+        };
+        let cloud = scene.sample_points(&options);
+        assert_eq!(cloud.elements, ["0abc", "Panel[0]"]);
+        assert_eq!(cloud.points.len(), cloud.normals.len());
+        assert_eq!(cloud.points.len(), cloud.element_ids.len());
+
+        // About 400 points per unit square
+        let count = |id: u32, z: f64| {
+            cloud
+                .points
+                .iter()
+                .zip(&cloud.element_ids)
+                .filter(|(p, &e)| e == id && (p.z - z).abs() < 1e-12)
+                .count()
+        };
+        for (id, z) in [(0, 0.0), (0, 1.0), (1, 5.0)] {
+            assert!(
+                (380..=420).contains(&count(id, z)),
+                "{} points",
+                count(id, z)
+            );
+        }
+        assert_eq!(count(0, 0.0) + count(0, 1.0) + count(1, 5.0), cloud.len());
+        for (p, n) in cloud.points.iter().zip(&cloud.normals) {
+            assert_eq!(*n, DVec3::Z);
+            assert!((0.0..=1.0).contains(&p.x) && (0.0..=1.0).contains(&p.y));
+        }
+
+        // Seeded: the same options give the same cloud
+        assert_eq!(scene.sample_points(&options), cloud);
+        let zero = PointCloudOptions {
+            spacing: 0.0,
+            seed: 7,
+        };
+        assert!(scene.sample_points(&zero).is_empty());
+    }
+
+    #[test]
+    fn test_write_ply_and_xyz() {
+        let cloud = PointCloud {
+            points: vec![DVec3::new(1.0, 2.0, 3.0)],
+            normals: vec![DVec3::Z],
+            element_ids: vec![0],
+            elements: vec!["Wall\nA".to_string()],
+        };
+
+        let mut ply = Vec::new();
+        cloud.write_ply(&mut ply).unwrap();
+        let header_end = b"end_header\n";
+        let split = ply
+            .windows(header_end.len())
+            .position(|w| w == header_end)
+            .unwrap()
+            + header_end.len();
+        let header = std::str::from_utf8(&ply[..split]).unwrap();
+        assert!(header.starts_with("ply\nformat binary_little_endian 1.0\n"));
+        assert!(header.contains("comment element 0 Wall A\n"));
+        assert!(header.contains("element vertex 1\n"));
+        // Three doubles, three floats and a uint
+        let body = &ply[split..];
+        assert_eq!(body.len(), 3 * 8 + 3 * 4 + 4);
+        assert_eq!(body[16..24], 3.0f64.to_le_bytes());
+        assert_eq!(body[32..36], 1.0f32.to_le_bytes());
+
+        let mut xyz = Vec::new();
+        cloud.write_xyz(&mut xyz).unwrap();
+        assert_eq!(
+            String::from_utf8(xyz).unwrap(),
+            "1 2 3 0.000000 0.000000 1.000000 0\n"
+        );
+    }
+}
@@ -1,30 +1,221 @@
 use cst_mesh::TriangleMesh;
-use cst_math::Aabb3;
+use cst_math::{Aabb3, DMat4, Point3};
+use cst_math::plane::Plane;
+use cst_math::ray::Ray;
 use std::path::Path;
-use std::io::Write;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::bvh::Bvh;
+use crate::camera::{Camera, CameraView};
+use crate::material::Material;
+use crate::streaming::{write_normals, NormalEncoding};
+
+/// Descriptive data about the element a mesh was built from
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ElementMetadata {
+    /// IFC entity type, e.g. `IfcWall`
+    pub ifc_type: Option<String>,
+    /// Name of the containing building storey
+    pub storey: Option<String>,
+    /// IFC `GlobalId` of the entity
+    pub global_id: Option<String>,
+    /// Property values as (`Pset.Property`, value) pairs, in display order
+    pub properties: Vec<(String, String)>,
+}
+
+/// A node of the spatial hierarchy (Project / Site / Building / Storey)
+/// shown as a tree panel in the HTML viewer
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpatialTreeNode {
+    pub name: String,
+    /// Kind label, e.g. `IfcBuildingStorey`
+    pub kind: String,
+    /// Indices into `Scene::meshes` contained directly in this node
+    pub meshes: Vec<usize>,
+    pub children: Vec<SpatialTreeNode>,
+    /// Storey elevation in scene units, when the model sets one
+    #[serde(default)]
+    pub elevation: Option<f64>,
+}
+
+impl SpatialTreeNode {
+    /// Create a node without meshes or children
+    pub fn new(name: &str, kind: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            kind: kind.to_string(),
+            ..Default::default()
+        }
+    }
+
+}
+
+/// A node of the scene graph (site, building, storey, element assembly...)
+///
+/// Nodes only carry structure: meshes keep their world coordinates, and
+/// exporters that nest geometry under nodes (glTF) express each mesh in the
+/// frame of the node that holds it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneNode {
+    pub name: String,
+    /// Transform relative to the parent node
+    pub transform: DMat4,
+    pub parent: Option<usize>,
+    /// Child indices into `Scene::nodes`
+    pub children: Vec<usize>,
+    /// Indices into `Scene::meshes` held by this node
+    pub meshes: Vec<usize>,
+}
 
-/// A named mesh in the scene
-#[derive(Clone)]
+/// A named mesh in the scene. Serialization leaves out the cached bounds
+/// and BVH; they are rebuilt on first use.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct SceneMesh {
     pub name: String,
     pub mesh: TriangleMesh,
-    pub color: [f32; 3],
+    pub material: Material,
+    pub metadata: ElementMetadata,
+    /// Hidden meshes are skipped by picking and rendering and start
+    /// unchecked in the HTML viewer
+    pub visible: bool,
+    /// Bounding box, computed on first use
+    #[serde(skip)]
+    bounds: OnceLock<Option<Aabb3>>,
+    /// Triangle BVH, built on first use
+    #[serde(skip)]
+    bvh: OnceLock<Bvh>,
+}
+
+impl SceneMesh {
+    /// Bounding box of the mesh (None when it has no vertices), cached
+    pub fn bounds(&self) -> Option<Aabb3> {
+        *self.bounds.get_or_init(|| Aabb3::from_points(&self.mesh.positions))
+    }
+
+    /// Triangle BVH of the mesh, built on first use
+    pub fn bvh(&self) -> &Bvh {
+        self.bvh.get_or_init(|| Bvh::from_triangles(&self.mesh))
+    }
+
+    /// Drop cached data; call after modifying `mesh` in place
+    pub fn invalidate_cache(&mut self) {
+        self.bounds = OnceLock::new();
+        self.bvh = OnceLock::new();
+    }
 }
 
 /// An instanced mesh group - one base geometry with multiple transform matrices
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct InstancedGroup {
     pub name: String,
     pub mesh: TriangleMesh,
-    pub color: [f32; 3],
+    pub material: Material,
     /// Each transform is a 4x4 matrix stored as [f32; 16] in column-major order
     pub transforms: Vec<[f32; 16]>,
+    /// The element each instance was built from, parallel to `transforms`;
+    /// empty when the instances are not elements
+    #[serde(default)]
+    pub metadata: Vec<ElementMetadata>,
+    /// Bounding box of the base geometry, computed on first use
+    #[serde(skip)]
+    bounds: OnceLock<Option<Aabb3>>,
+    /// Triangle BVH of the base geometry, built on first use
+    #[serde(skip)]
+    bvh: OnceLock<Bvh>,
+}
+
+impl InstancedGroup {
+    /// Bounding box of the base geometry (untransformed), cached
+    pub fn bounds(&self) -> Option<Aabb3> {
+        *self.bounds.get_or_init(|| Aabb3::from_points(&self.mesh.positions))
+    }
+
+    /// World-space bounding box of one instance
+    pub fn instance_bounds(&self, instance: usize) -> Option<Aabb3> {
+        let local = self.bounds()?;
+        let transform = self.transform_matrix(instance);
+        let corners: Vec<Point3> = (0..8)
+            .map(|i| {
+                let corner = Point3::new(
+                    if i & 1 == 0 { local.min.x } else { local.max.x },
+                    if i & 2 == 0 { local.min.y } else { local.max.y },
+                    if i & 4 == 0 { local.min.z } else { local.max.z },
+                );
+                transform.transform_point3(corner)
+            })
+            .collect();
+        Aabb3::from_points(&corners)
+    }
+
+    /// Triangle BVH of the base geometry, built on first use
+    pub fn bvh(&self) -> &Bvh {
+        self.bvh.get_or_init(|| Bvh::from_triangles(&self.mesh))
+    }
+
+    /// Drop cached data; call after modifying `mesh` in place
+    pub fn invalidate_cache(&mut self) {
+        self.bounds = OnceLock::new();
+        self.bvh = OnceLock::new();
+    }
+
+    /// Transform of one instance as a matrix
+    pub fn transform_matrix(&self, instance: usize) -> DMat4 {
+        DMat4::from_cols_array(&self.transforms[instance].map(f64::from))
+    }
+}
+
+/// What a pick ray hit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickTarget {
+    /// Index into `Scene::meshes`
+    Mesh(usize),
+    /// Instance of an entry in `Scene::instanced_groups`
+    Instance { group: usize, instance: usize },
+}
+
+/// Result of [`Scene::pick`]
+#[derive(Debug, Clone, Copy)]
+pub struct PickHit {
+    pub target: PickTarget,
+    /// Triangle index (into `indices` / 3) of the hit mesh
+    pub triangle: usize,
+    /// Hit point in world space
+    pub point: Point3,
+    /// Distance from the ray origin
+    pub distance: f64,
+}
+
+/// Scene BVH returned by [`Scene::spatial_index`]
+pub struct SceneIndex {
+    pub bvh: Bvh,
+    /// What each BVH item refers to
+    pub items: Vec<PickTarget>,
+}
+
+impl SceneIndex {
+    /// World-space bounds of each item
+    pub fn item_bounds(&self) -> &[Aabb3] {
+        self.bvh.item_bounds()
+    }
 }
 
 /// A 3D scene for visualization
 pub struct Scene {
     pub meshes: Vec<SceneMesh>,
     pub instanced_groups: Vec<InstancedGroup>,
+    /// Section planes cutting the whole scene. Geometry on the side the
+    /// normal points to is kept; the rest is clipped away.
+    pub section_planes: Vec<Plane>,
+    /// Spatial hierarchy for the HTML tree panel, when known
+    pub spatial_tree: Option<SpatialTreeNode>,
+    /// Scene graph; empty for a flat scene
+    pub nodes: Vec<SceneNode>,
+    /// Named viewpoints offered in the HTML viewer's view list
+    pub views: Vec<CameraView>,
+    /// Scene BVH, built on first spatial query
+    index: OnceLock<SceneIndex>,
 }
 
 impl Scene {
@@ -33,16 +224,37 @@ impl Scene {
         Self {
             meshes: Vec::new(),
             instanced_groups: Vec::new(),
+            section_planes: Vec::new(),
+            spatial_tree: None,
+            nodes: Vec::new(),
+            views: Vec::new(),
+            index: OnceLock::new(),
         }
     }
 
-    /// Add a mesh with a name and color
-    pub fn add_mesh(&mut self, name: &str, mesh: TriangleMesh, color: [f32; 3]) {
+    /// Add a mesh with a name and material (or plain RGB color)
+    pub fn add_mesh(&mut self, name: &str, mesh: TriangleMesh, material: impl Into<Material>) {
+        self.add_element(name, mesh, material, ElementMetadata::default());
+    }
+
+    /// Add a mesh together with metadata about its element
+    pub fn add_element(
+        &mut self,
+        name: &str,
+        mesh: TriangleMesh,
+        material: impl Into<Material>,
+        metadata: ElementMetadata,
+    ) {
         self.meshes.push(SceneMesh {
             name: name.to_string(),
             mesh,
-            color,
+            material: material.into(),
+            metadata,
+            visible: true,
+            bounds: OnceLock::new(),
+            bvh: OnceLock::new(),
         });
+        self.invalidate_index();
     }
 
     /// Add a mesh with auto-assigned color
@@ -65,506 +277,283 @@ impl Scene {
     }
 
     /// Add an instanced group (one base geometry with multiple placements)
-    pub fn add_instanced_group(&mut self, name: &str, mesh: TriangleMesh, color: [f32; 3], transforms: Vec<[f32; 16]>) {
+    pub fn add_instanced_group(
+        &mut self,
+        name: &str,
+        mesh: TriangleMesh,
+        material: impl Into<Material>,
+        transforms: Vec<[f32; 16]>,
+    ) {
+        self.add_instanced_elements(name, mesh, material, transforms, Vec::new());
+    }
+
+    /// Add an instanced group whose instances are elements, with the
+    /// metadata of each instance
+    pub fn add_instanced_elements(
+        &mut self,
+        name: &str,
+        mesh: TriangleMesh,
+        material: impl Into<Material>,
+        transforms: Vec<[f32; 16]>,
+        metadata: Vec<ElementMetadata>,
+    ) {
         self.instanced_groups.push(InstancedGroup {
             name: name.to_string(),
             mesh,
-            color,
+            material: material.into(),
             transforms,
+            metadata,
+            bounds: OnceLock::new(),
+            bvh: OnceLock::new(),
         });
+        self.invalidate_index();
     }
 
-    /// Compute scene bounding box
-    pub fn bounds(&self) -> Option<Aabb3> {
-        if self.meshes.is_empty() && self.instanced_groups.is_empty() {
-            return None;
-        }
-
-        let mut all_points = Vec::new();
-        for scene_mesh in &self.meshes {
-            all_points.extend_from_slice(&scene_mesh.mesh.positions);
-        }
-        for ig in &self.instanced_groups {
-            for p in &ig.mesh.positions {
-                all_points.push(*p);
-            }
-        }
-        Aabb3::from_points(&all_points)
+    /// Add a section plane; geometry behind it (against the normal) is hidden
+    pub fn add_section_plane(&mut self, plane: Plane) {
+        self.section_planes.push(plane);
     }
 
-    /// Total triangle count across all meshes
-    pub fn total_triangles(&self) -> usize {
-        self.meshes.iter().map(|m| m.mesh.indices.len() / 3).sum()
+    /// Whether a point is hidden by any of the section planes
+    pub fn is_clipped(&self, point: cst_math::Point3) -> bool {
+        self.section_planes
+            .iter()
+            .any(|plane| plane.signed_distance(point) < 0.0)
     }
 
-    /// Export scene as a standalone HTML file with embedded Three.js viewer
-    pub fn export_html(&self, path: &Path) -> std::io::Result<()> {
-        let bounds = self.bounds().unwrap_or_else(|| {
-            use cst_math::{Point3, DVec3};
-            Aabb3::new(Point3::ZERO, DVec3::splat(1.0))
+    /// Add a scene graph node below `parent` (or as a root) and return its index
+    pub fn add_node(&mut self, name: &str, parent: Option<usize>, transform: DMat4) -> usize {
+        let index = self.nodes.len();
+        self.nodes.push(SceneNode {
+            name: name.to_string(),
+            transform,
+            parent,
+            children: Vec::new(),
+            meshes: Vec::new(),
         });
-        let center = bounds.center();
-        let size = bounds.extents();
-        let camera_distance = size.length() * 1.5;
-
-        let mut file = std::fs::File::create(path)?;
-
-        write!(file, r#"<!DOCTYPE html>
-<html lang="en">
-<head>
-    <meta charset="UTF-8">
-    <meta name="viewport" content="width=device-width, initial-scale=1.0">
-    <title>CSTEngine Scene Viewer</title>
-    <style>
-        body {{
-            margin: 0;
-            overflow: hidden;
-            font-family: 'Segoe UI', Tahoma, Geneva, Verdana, sans-serif;
-            background: #1a1a1a;
-        }}
-        #container {{
-            width: 100vw;
-            height: 100vh;
-        }}
-        #info {{
-            position: absolute;
-            top: 10px;
-            left: 10px;
-            background: rgba(0, 0, 0, 0.7);
-            color: white;
-            padding: 15px;
-            border-radius: 5px;
-            font-size: 14px;
-            max-width: 300px;
-            max-height: calc(100vh - 40px);
-            overflow-y: auto;
-        }}
-        #info h3 {{
-            margin: 0 0 10px 0;
-            font-size: 16px;
-            border-bottom: 1px solid #666;
-            padding-bottom: 5px;
-        }}
-        #info .mesh-item {{
-            margin: 5px 0;
-            padding: 5px;
-            background: rgba(255, 255, 255, 0.1);
-            border-radius: 3px;
-        }}
-        #info .mesh-name {{
-            font-weight: bold;
-            color: #4fc3f7;
-        }}
-        #info .mesh-stats {{
-            font-size: 12px;
-            color: #aaa;
-        }}
-        #error {{
-            position: absolute;
-            top: 50%;
-            left: 50%;
-            transform: translate(-50%, -50%);
-            background: rgba(200, 0, 0, 0.9);
-            color: white;
-            padding: 20px;
-            border-radius: 5px;
-            display: none;
-        }}
-    </style>
-</head>
-<body>
-    <div id="container"></div>
-    <div id="info">
-        <h3>CSTEngine Scene</h3>
-        <div>Meshes: {}</div>
-        <div>Triangles: {}</div>
-        <hr style="border: 1px solid #666; margin: 10px 0;">
-"#, self.meshes.len(), self.total_triangles())?;
-
-        // Write mesh list
-        for scene_mesh in &self.meshes {
-            let tri_count = scene_mesh.mesh.indices.len() / 3;
-            write!(file, r#"        <div class="mesh-item">
-            <div class="mesh-name">{}</div>
-            <div class="mesh-stats">{} triangles</div>
-        </div>
-"#, scene_mesh.name, tri_count)?;
+        if let Some(parent) = parent {
+            self.nodes[parent].children.push(index);
         }
+        index
+    }
 
-        write!(file, r#"    </div>
-    <div id="error">Failed to load Three.js from CDN. Please check your internet connection.</div>
-
-    <script src="https://cdnjs.cloudflare.com/ajax/libs/three.js/r128/three.min.js"></script>
-    <script>
-        if (typeof THREE === 'undefined') document.getElementById('error').style.display='block';
-"#)?;
-
-        // Embed mesh data
-        write!(file, "        const meshData = [\n")?;
-        for (i, scene_mesh) in self.meshes.iter().enumerate() {
-            write!(file, "            {{\n")?;
-            write!(file, "                name: \"{}\",\n", scene_mesh.name)?;
-            write!(file, "                color: [{}, {}, {}],\n",
-                scene_mesh.color[0], scene_mesh.color[1], scene_mesh.color[2])?;
-
-            // Write positions (convert to f32 and truncate to 2 decimals)
-            write!(file, "                positions: [")?;
-            for (j, pos) in scene_mesh.mesh.positions.iter().enumerate() {
-                if j > 0 { write!(file, ",")?; }
-                write!(file, "{:.2},{:.2},{:.2}", pos.x as f32, pos.y as f32, pos.z as f32)?;
-            }
-            write!(file, "],\n")?;
+    /// Place a mesh under a node. A mesh belongs to at most one node;
+    /// attaching it again moves it.
+    pub fn attach_mesh(&mut self, node: usize, mesh: usize) {
+        for other in &mut self.nodes {
+            other.meshes.retain(|&m| m != mesh);
+        }
+        self.nodes[node].meshes.push(mesh);
+    }
 
-            // Write normals
-            write!(file, "                normals: [")?;
-            for (j, norm) in scene_mesh.mesh.normals.iter().enumerate() {
-                if j > 0 { write!(file, ",")?; }
-                write!(file, "{:.2},{:.2},{:.2}", norm.x as f32, norm.y as f32, norm.z as f32)?;
-            }
-            write!(file, "],\n")?;
+    /// Nodes without a parent
+    pub fn root_nodes(&self) -> Vec<usize> {
+        (0..self.nodes.len())
+            .filter(|&i| self.nodes[i].parent.is_none())
+            .collect()
+    }
 
-            // Write indices
-            write!(file, "                indices: [")?;
-            for (j, idx) in scene_mesh.mesh.indices.iter().enumerate() {
-                if j > 0 { write!(file, ",")?; }
-                write!(file, "{}", idx)?;
-            }
-            write!(file, "]\n")?;
+    /// The node holding a mesh, if any
+    pub fn mesh_node(&self, mesh: usize) -> Option<usize> {
+        self.nodes.iter().position(|node| node.meshes.contains(&mesh))
+    }
 
-            write!(file, "            }}")?;
-            if i < self.meshes.len() - 1 {
-                write!(file, ",")?;
-            }
-            write!(file, "\n")?;
-        }
-        write!(file, "        ];\n\n")?;
-
-        // Three.js scene setup
-        write!(file, r#"        function initScene() {{
-            const scene = new THREE.Scene();
-            scene.background = new THREE.Color(0x1a1a1a);
-
-            const camera = new THREE.PerspectiveCamera(
-                60,
-                window.innerWidth / window.innerHeight,
-                0.1,
-                10000
-            );
-
-            const renderer = new THREE.WebGLRenderer({{ antialias: true }});
-            renderer.setSize(window.innerWidth, window.innerHeight);
-            document.getElementById('container').appendChild(renderer.domElement);
-
-            // Add lighting
-            const ambientLight = new THREE.AmbientLight(0x404040, 2);
-            scene.add(ambientLight);
-
-            const dirLight1 = new THREE.DirectionalLight(0xffffff, 1);
-            dirLight1.position.set(1, 1, 1);
-            scene.add(dirLight1);
-
-            const dirLight2 = new THREE.DirectionalLight(0xffffff, 0.5);
-            dirLight2.position.set(-1, -1, -1);
-            scene.add(dirLight2);
-
-            // Add meshes
-            meshData.forEach(data => {{
-                const geometry = new THREE.BufferGeometry();
-                geometry.setAttribute('position', new THREE.Float32BufferAttribute(data.positions, 3));
-                geometry.setAttribute('normal', new THREE.Float32BufferAttribute(data.normals, 3));
-                geometry.setIndex(data.indices);
-
-                const material = new THREE.MeshPhongMaterial({{
-                    color: new THREE.Color(data.color[0], data.color[1], data.color[2]),
-                    shininess: 30,
-                    side: THREE.DoubleSide
-                }});
-
-                const mesh = new THREE.Mesh(geometry, material);
-                scene.add(mesh);
-            }});
-
-            // Add grid and axes
-            const gridSize = {:.2};
-            const grid = new THREE.GridHelper(gridSize * 2, 20, 0x444444, 0x222222);
-            grid.position.y = {:.2};
-            scene.add(grid);
-
-            const axes = new THREE.AxesHelper(gridSize * 0.5);
-            scene.add(axes);
-
-            // Position camera
-            const center = new THREE.Vector3({:.2}, {:.2}, {:.2});
-            const distance = {:.2};
-            camera.position.set(
-                center.x + distance * 0.7,
-                center.y + distance * 0.7,
-                center.z + distance * 0.7
-            );
-            camera.lookAt(center);
-
-            // Simple orbit controls (mouse drag)
-            let isDragging = false;
-            let previousMousePosition = {{ x: 0, y: 0 }};
-            let theta = Math.PI / 4;
-            let phi = Math.PI / 4;
-            let radius = distance;
-
-            renderer.domElement.addEventListener('mousedown', (e) => {{
-                isDragging = true;
-                previousMousePosition = {{ x: e.clientX, y: e.clientY }};
-            }});
-
-            renderer.domElement.addEventListener('mousemove', (e) => {{
-                if (isDragging) {{
-                    const deltaX = e.clientX - previousMousePosition.x;
-                    const deltaY = e.clientY - previousMousePosition.y;
-
-                    theta -= deltaX * 0.01;
-                    phi = Math.max(0.1, Math.min(Math.PI - 0.1, phi + deltaY * 0.01));
-
-                    previousMousePosition = {{ x: e.clientX, y: e.clientY }};
-                    updateCameraPosition();
-                }}
-            }});
-
-            renderer.domElement.addEventListener('mouseup', () => {{
-                isDragging = false;
-            }});
-
-            renderer.domElement.addEventListener('wheel', (e) => {{
-                e.preventDefault();
-                radius = Math.max(1, radius + e.deltaY * 0.01);
-                updateCameraPosition();
-            }});
-
-            function updateCameraPosition() {{
-                camera.position.x = center.x + radius * Math.sin(phi) * Math.cos(theta);
-                camera.position.y = center.y + radius * Math.cos(phi);
-                camera.position.z = center.z + radius * Math.sin(phi) * Math.sin(theta);
-                camera.lookAt(center);
-            }}
-
-            // Handle window resize
-            window.addEventListener('resize', () => {{
-                camera.aspect = window.innerWidth / window.innerHeight;
-                camera.updateProjectionMatrix();
-                renderer.setSize(window.innerWidth, window.innerHeight);
-            }});
-
-            // Animation loop
-            function animate() {{
-                requestAnimationFrame(animate);
-                renderer.render(scene, camera);
-            }}
-            animate();
-        }}
-
-        if (typeof THREE !== 'undefined') initScene();
-    </script>
-</body>
-</html>
-"#,
-            size.length().max(10.0),
-            bounds.min.y,
-            center.x, center.y, center.z,
-            camera_distance
-        )?;
-
-        Ok(())
-    }
-
-    /// Export scene as glTF JSON file
-    pub fn export_gltf_json(&self) -> String {
-        use std::fmt::Write as FmtWrite;
-
-        let mut json = String::new();
-
-        // Start JSON
-        writeln!(json, "{{").unwrap();
-        writeln!(json, "  \"asset\": {{").unwrap();
-        writeln!(json, "    \"version\": \"2.0\",").unwrap();
-        writeln!(json, "    \"generator\": \"CSTEngine\"").unwrap();
-        writeln!(json, "  }},").unwrap();
-
-        // Scene
-        writeln!(json, "  \"scene\": 0,").unwrap();
-        writeln!(json, "  \"scenes\": [{{").unwrap();
-        write!(json, "    \"nodes\": [").unwrap();
-        for i in 0..self.meshes.len() {
-            if i > 0 { write!(json, ", ").unwrap(); }
-            write!(json, "{}", i).unwrap();
+    /// Transform from a node's frame to world coordinates
+    pub fn node_world_transform(&self, node: usize) -> DMat4 {
+        let mut transform = self.nodes[node].transform;
+        let mut parent = self.nodes[node].parent;
+        while let Some(p) = parent {
+            transform = self.nodes[p].transform * transform;
+            parent = self.nodes[p].parent;
         }
-        writeln!(json, "]").unwrap();
-        writeln!(json, "  }}],").unwrap();
-
-        // Nodes
-        writeln!(json, "  \"nodes\": [").unwrap();
-        for (i, scene_mesh) in self.meshes.iter().enumerate() {
-            writeln!(json, "    {{").unwrap();
-            writeln!(json, "      \"name\": \"{}\",", scene_mesh.name).unwrap();
-            writeln!(json, "      \"mesh\": {}", i).unwrap();
-            write!(json, "    }}").unwrap();
-            if i < self.meshes.len() - 1 {
-                writeln!(json, ",").unwrap();
-            } else {
-                writeln!(json).unwrap();
+        transform
+    }
+
+    /// Find the closest mesh or instance hit by a ray.
+    ///
+    /// Hits removed by section planes are skipped, so picking selects what
+    /// the viewer shows. Candidates come from the scene BVH, nearest first.
+    pub fn pick(&self, ray: &Ray) -> Option<PickHit> {
+        let visible = |t: f64| !self.is_clipped(ray.at(t));
+        let index = self.spatial_index();
+        let mut best: Option<PickHit> = None;
+        index.bvh.closest_hit(ray, |item| {
+            let target = index.items[item];
+            let (triangle, t) = match target {
+                PickTarget::Mesh(i) => {
+                    let scene_mesh = &self.meshes[i];
+                    if !scene_mesh.visible {
+                        return None;
+                    }
+                    scene_mesh.bvh().intersect_triangles(&scene_mesh.mesh, ray, visible)?
+                }
+                PickTarget::Instance { group, instance } => {
+                    let ig = &self.instanced_groups[group];
+                    let inverse = ig.transform_matrix(instance).inverse();
+                    // Direction is left unnormalized so local and world ray
+                    // parameters coincide.
+                    let local = Ray {
+                        origin: inverse.transform_point3(ray.origin),
+                        direction: inverse.transform_vector3(ray.direction),
+                    };
+                    ig.bvh().intersect_triangles(&ig.mesh, &local, visible)?
+                }
+            };
+            if best.map_or(true, |b| t < b.distance) {
+                best = Some(PickHit {
+                    target,
+                    triangle,
+                    point: ray.at(t),
+                    distance: t,
+                });
             }
-        }
-        writeln!(json, "  ],").unwrap();
-
-        // Meshes
-        writeln!(json, "  \"meshes\": [").unwrap();
-        for (i, scene_mesh) in self.meshes.iter().enumerate() {
-            writeln!(json, "    {{").unwrap();
-            writeln!(json, "      \"name\": \"{}\",", scene_mesh.name).unwrap();
-            writeln!(json, "      \"primitives\": [{{").unwrap();
-            writeln!(json, "        \"attributes\": {{").unwrap();
-            writeln!(json, "          \"POSITION\": {},", i * 3).unwrap();
-            writeln!(json, "          \"NORMAL\": {}", i * 3 + 1).unwrap();
-            writeln!(json, "        }},").unwrap();
-            writeln!(json, "        \"indices\": {},", i * 3 + 2).unwrap();
-            writeln!(json, "        \"material\": {}", i).unwrap();
-            writeln!(json, "      }}]").unwrap();
-            write!(json, "    }}").unwrap();
-            if i < self.meshes.len() - 1 {
-                writeln!(json, ",").unwrap();
-            } else {
-                writeln!(json).unwrap();
+            Some(t)
+        });
+        best
+    }
+
+    /// Scene BVH over the bounds of every mesh and instance, built on
+    /// first use and reset by the `add_*` methods.
+    ///
+    /// Call [`Scene::invalidate_index`] after editing `meshes` or
+    /// `instanced_groups` directly.
+    pub fn spatial_index(&self) -> &SceneIndex {
+        self.index.get_or_init(|| {
+            let mut items = Vec::new();
+            let mut bounds = Vec::new();
+            for (i, scene_mesh) in self.meshes.iter().enumerate() {
+                if let Some(b) = scene_mesh.bounds() {
+                    items.push(PickTarget::Mesh(i));
+                    bounds.push(b);
+                }
             }
-        }
-        writeln!(json, "  ],").unwrap();
-
-        // Materials
-        writeln!(json, "  \"materials\": [").unwrap();
-        for (i, scene_mesh) in self.meshes.iter().enumerate() {
-            writeln!(json, "    {{").unwrap();
-            writeln!(json, "      \"name\": \"{}_Material\",", scene_mesh.name).unwrap();
-            writeln!(json, "      \"pbrMetallicRoughness\": {{").unwrap();
-            writeln!(json, "        \"baseColorFactor\": [{}, {}, {}, 1.0],",
-                scene_mesh.color[0], scene_mesh.color[1], scene_mesh.color[2]).unwrap();
-            writeln!(json, "        \"metallicFactor\": 0.0,").unwrap();
-            writeln!(json, "        \"roughnessFactor\": 0.5").unwrap();
-            writeln!(json, "      }},").unwrap();
-            writeln!(json, "      \"doubleSided\": true").unwrap();
-            write!(json, "    }}").unwrap();
-            if i < self.meshes.len() - 1 {
-                writeln!(json, ",").unwrap();
-            } else {
-                writeln!(json).unwrap();
+            for (group, ig) in self.instanced_groups.iter().enumerate() {
+                for instance in 0..ig.transforms.len() {
+                    if let Some(b) = ig.instance_bounds(instance) {
+                        items.push(PickTarget::Instance { group, instance });
+                        bounds.push(b);
+                    }
+                }
             }
-        }
-        writeln!(json, "  ],").unwrap();
-
-        // Accessors
-        writeln!(json, "  \"accessors\": [").unwrap();
-        let mut accessor_idx = 0;
-        for scene_mesh in &self.meshes {
-            let vertex_count = scene_mesh.mesh.positions.len();
-            let index_count = scene_mesh.mesh.indices.len();
-
-            // Position accessor
-            let bounds = self.compute_mesh_bounds(scene_mesh);
-            writeln!(json, "    {{").unwrap();
-            writeln!(json, "      \"bufferView\": {},", accessor_idx).unwrap();
-            writeln!(json, "      \"componentType\": 5126,").unwrap();
-            writeln!(json, "      \"count\": {},", vertex_count).unwrap();
-            writeln!(json, "      \"type\": \"VEC3\",").unwrap();
-            writeln!(json, "      \"max\": [{}, {}, {}],", bounds.max.x, bounds.max.y, bounds.max.z).unwrap();
-            writeln!(json, "      \"min\": [{}, {}, {}]", bounds.min.x, bounds.min.y, bounds.min.z).unwrap();
-            writeln!(json, "    }},").unwrap();
-
-            // Normal accessor
-            writeln!(json, "    {{").unwrap();
-            writeln!(json, "      \"bufferView\": {},", accessor_idx + 1).unwrap();
-            writeln!(json, "      \"componentType\": 5126,").unwrap();
-            writeln!(json, "      \"count\": {},", vertex_count).unwrap();
-            writeln!(json, "      \"type\": \"VEC3\"").unwrap();
-            writeln!(json, "    }},").unwrap();
-
-            // Index accessor
-            writeln!(json, "    {{").unwrap();
-            writeln!(json, "      \"bufferView\": {},", accessor_idx + 2).unwrap();
-            writeln!(json, "      \"componentType\": 5125,").unwrap();
-            writeln!(json, "      \"count\": {},", index_count).unwrap();
-            writeln!(json, "      \"type\": \"SCALAR\"").unwrap();
-            write!(json, "    }}").unwrap();
-
-            accessor_idx += 3;
-            if accessor_idx < self.meshes.len() * 3 {
-                writeln!(json, ",").unwrap();
-            } else {
-                writeln!(json).unwrap();
+            SceneIndex {
+                bvh: Bvh::build(&bounds),
+                items,
             }
-        }
-        writeln!(json, "  ],").unwrap();
-
-        // BufferViews
-        writeln!(json, "  \"bufferViews\": [").unwrap();
-        let mut offset = 0;
-        let mut view_idx = 0;
-        for scene_mesh in &self.meshes {
-            let pos_bytes = scene_mesh.mesh.positions.len() * 12;
-            let norm_bytes = scene_mesh.mesh.normals.len() * 12;
-            let idx_bytes = scene_mesh.mesh.indices.len() * 4;
-
-            // Position buffer view
-            writeln!(json, "    {{").unwrap();
-            writeln!(json, "      \"buffer\": 0,").unwrap();
-            writeln!(json, "      \"byteOffset\": {},", offset).unwrap();
-            writeln!(json, "      \"byteLength\": {},", pos_bytes).unwrap();
-            writeln!(json, "      \"target\": 34962").unwrap();
-            writeln!(json, "    }},").unwrap();
-            offset += pos_bytes;
-
-            // Normal buffer view
-            writeln!(json, "    {{").unwrap();
-            writeln!(json, "      \"buffer\": 0,").unwrap();
-            writeln!(json, "      \"byteOffset\": {},", offset).unwrap();
-            writeln!(json, "      \"byteLength\": {},", norm_bytes).unwrap();
-            writeln!(json, "      \"target\": 34962").unwrap();
-            writeln!(json, "    }},").unwrap();
-            offset += norm_bytes;
-
-            // Index buffer view
-            writeln!(json, "    {{").unwrap();
-            writeln!(json, "      \"buffer\": 0,").unwrap();
-            writeln!(json, "      \"byteOffset\": {},", offset).unwrap();
-            writeln!(json, "      \"byteLength\": {},", idx_bytes).unwrap();
-            writeln!(json, "      \"target\": 34963").unwrap();
-            write!(json, "    }}").unwrap();
-            offset += idx_bytes;
-
-            view_idx += 3;
-            if view_idx < self.meshes.len() * 3 {
-                writeln!(json, ",").unwrap();
-            } else {
-                writeln!(json).unwrap();
+        })
+    }
+
+    /// Drop the cached scene BVH
+    pub fn invalidate_index(&mut self) {
+        self.index = OnceLock::new();
+    }
+
+    /// Meshes and instances whose bounds are hit by a ray, nearest first
+    pub fn query_ray(&self, ray: &Ray) -> Vec<PickTarget> {
+        let index = self.spatial_index();
+        index
+            .bvh
+            .query_ray(ray)
+            .into_iter()
+            .map(|(item, _)| index.items[item])
+            .collect()
+    }
+
+    /// Meshes and instances whose bounds intersect `aabb`
+    pub fn query_aabb(&self, aabb: &Aabb3) -> Vec<PickTarget> {
+        let index = self.spatial_index();
+        index
+            .bvh
+            .query_aabb(aabb)
+            .into_iter()
+            .map(|item| index.items[item])
+            .collect()
+    }
+
+    /// Meshes and instances whose bounds intersect a frustum given by
+    /// inward-facing planes
+    pub fn query_frustum(&self, planes: &[Plane; 6]) -> Vec<PickTarget> {
+        let index = self.spatial_index();
+        index
+            .bvh
+            .query_frustum(planes)
+            .into_iter()
+            .map(|item| index.items[item])
+            .collect()
+    }
+
+    /// Pairs of meshes/instances whose bounds, grown by `tolerance`,
+    /// overlap: the broad phase of clash detection
+    pub fn clash_candidates(&self, tolerance: f64) -> Vec<(PickTarget, PickTarget)> {
+        let index = self.spatial_index();
+        let mut pairs = Vec::new();
+        for (item, bounds) in index.item_bounds().iter().enumerate() {
+            for other in index.bvh.query_aabb(&bounds.expand(tolerance)) {
+                if other > item {
+                    pairs.push((index.items[item], index.items[other]));
+                }
             }
         }
-        writeln!(json, "  ],").unwrap();
+        pairs
+    }
 
-        // Buffer (base64 encoded binary data)
-        writeln!(json, "  \"buffers\": [{{").unwrap();
-        writeln!(json, "    \"byteLength\": {},", offset).unwrap();
-        write!(json, "    \"uri\": \"data:application/octet-stream;base64,").unwrap();
+    /// Compute scene bounding box
+    pub fn bounds(&self) -> Option<Aabb3> {
+        if self.meshes.is_empty() && self.instanced_groups.is_empty() {
+            return None;
+        }
 
-        // Generate binary data and encode to base64
-        let binary_data = self.generate_gltf_binary_buffer();
-        write!(json, "{}\"", base64_encode(&binary_data)).unwrap();
-        writeln!(json).unwrap();
-        writeln!(json, "  }}]").unwrap();
+        self.meshes
+            .iter()
+            .map(SceneMesh::bounds)
+            .chain(self.instanced_groups.iter().map(InstancedGroup::bounds))
+            .flatten()
+            .reduce(|a, b| a.merge(&b))
+    }
 
-        writeln!(json, "}}").unwrap();
+    /// Bounding box of the picked meshes and instances, e.g. to zoom to a
+    /// selection. Targets without geometry or out of range are skipped;
+    /// `None` when nothing is left.
+    pub fn selection_bounds(&self, selection: &[PickTarget]) -> Option<Aabb3> {
+        selection
+            .iter()
+            .filter_map(|target| match *target {
+                PickTarget::Mesh(i) => self.meshes.get(i)?.bounds(),
+                PickTarget::Instance { group, instance } => {
+                    let group = self.instanced_groups.get(group)?;
+                    (instance < group.transforms.len())
+                        .then(|| group.instance_bounds(instance))
+                        .flatten()
+                }
+            })
+            .reduce(|a, b| a.merge(&b))
+    }
 
-        json
+    /// Indices of visible meshes whose cached bounds intersect the camera
+    /// frustum
+    pub fn visible_meshes(&self, camera: &Camera) -> Vec<usize> {
+        self.query_frustum(&camera.frustum_planes())
+            .into_iter()
+            .filter_map(|target| match target {
+                PickTarget::Mesh(i) => self.meshes[i].visible.then_some(i),
+                PickTarget::Instance { .. } => None,
+            })
+            .collect()
     }
 
-    fn compute_mesh_bounds(&self, scene_mesh: &SceneMesh) -> Aabb3 {
-        Aabb3::from_points(&scene_mesh.mesh.positions).unwrap_or_else(|| {
-            use cst_math::{Point3, DVec3};
-            Aabb3::new(Point3::ZERO, DVec3::splat(1.0))
-        })
+    /// `(group, instance)` pairs whose bounds intersect the camera frustum
+    pub fn visible_instances(&self, camera: &Camera) -> Vec<(usize, usize)> {
+        self.query_frustum(&camera.frustum_planes())
+            .into_iter()
+            .filter_map(|target| match target {
+                PickTarget::Instance { group, instance } => Some((group, instance)),
+                PickTarget::Mesh(_) => None,
+            })
+            .collect()
+    }
+
+    /// Total triangle count across all meshes
+    pub fn total_triangles(&self) -> usize {
+        self.meshes.iter().map(|m| m.mesh.indices.len() / 3).sum()
     }
 
     /// Export scene mesh data as a compact binary file for web streaming.
@@ -581,19 +570,42 @@ impl Scene {
     ///   [vertex_count * 3 * f32 positions]
     ///   [index_count * u32 indices]
     ///   [instance_count * 16 * f32 transform_matrices]
+    /// Then a material table, one entry per regular mesh followed by one per
+    /// instanced group (readers that stop after the geometry can ignore it):
+    ///   [f32 alpha][f32 metallic][f32 roughness][u8 flags: bit 0 = double-sided]
+    ///   [u32 texture_uri_len][texture_uri_utf8]
+    ///
+    /// Format v5 (normals) is v3 with a normal encoding byte after the
+    /// version ([`NormalEncoding`] as u8) and per-vertex normals after the
+    /// positions of every mesh and group.
+    ///
+    /// For progressive loading or normals use
+    /// [`Scene::export_binary_mesh_with_options`].
     pub fn export_binary_mesh(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::write(path, self.sequential_binary_mesh(None))
+    }
+
+    /// The v2/v3 layout, or v5 when `normals` is set
+    pub(crate) fn sequential_binary_mesh(&self, normals: Option<NormalEncoding>) -> Vec<u8> {
         let mut buf = Vec::new();
 
-        let version: u8 = if self.instanced_groups.is_empty() { 2 } else { 3 };
+        let version: u8 = match normals {
+            Some(_) => 5,
+            None if self.instanced_groups.is_empty() => 2,
+            None => 3,
+        };
         buf.push(version);
+        if let Some(encoding) = normals {
+            buf.push(encoding as u8);
+        }
 
-        if version == 3 {
-            // v3: two counts
+        if version == 2 {
+            // v2: single count
             buf.extend_from_slice(&(self.meshes.len() as u32).to_le_bytes());
-            buf.extend_from_slice(&(self.instanced_groups.len() as u32).to_le_bytes());
         } else {
-            // v2: single count
+            // v3/v5: two counts
             buf.extend_from_slice(&(self.meshes.len() as u32).to_le_bytes());
+            buf.extend_from_slice(&(self.instanced_groups.len() as u32).to_le_bytes());
         }
 
         // Regular meshes (same as v2)
@@ -601,9 +613,9 @@ impl Scene {
             let name_bytes = sm.name.as_bytes();
             buf.extend_from_slice(&(name_bytes.len() as u32).to_le_bytes());
             buf.extend_from_slice(name_bytes);
-            buf.extend_from_slice(&sm.color[0].to_le_bytes());
-            buf.extend_from_slice(&sm.color[1].to_le_bytes());
-            buf.extend_from_slice(&sm.color[2].to_le_bytes());
+            for channel in sm.material.base_color {
+                buf.extend_from_slice(&channel.to_le_bytes());
+            }
             let vc = sm.mesh.positions.len() as u32;
             let ic = sm.mesh.indices.len() as u32;
             buf.extend_from_slice(&vc.to_le_bytes());
@@ -613,19 +625,22 @@ impl Scene {
                 buf.extend_from_slice(&(p.y as f32).to_le_bytes());
                 buf.extend_from_slice(&(p.z as f32).to_le_bytes());
             }
+            if let Some(encoding) = normals {
+                write_normals(&mut buf, &sm.mesh, encoding);
+            }
             for &i in &sm.mesh.indices {
                 buf.extend_from_slice(&i.to_le_bytes());
             }
         }
 
-        // Instanced groups (v3 only)
+        // Instanced groups (v3/v5 only)
         for ig in &self.instanced_groups {
             let name_bytes = ig.name.as_bytes();
             buf.extend_from_slice(&(name_bytes.len() as u32).to_le_bytes());
             buf.extend_from_slice(name_bytes);
-            buf.extend_from_slice(&ig.color[0].to_le_bytes());
-            buf.extend_from_slice(&ig.color[1].to_le_bytes());
-            buf.extend_from_slice(&ig.color[2].to_le_bytes());
+            for channel in ig.material.base_color {
+                buf.extend_from_slice(&channel.to_le_bytes());
+            }
             let vc = ig.mesh.positions.len() as u32;
             let ic = ig.mesh.indices.len() as u32;
             let inst_count = ig.transforms.len() as u32;
@@ -637,6 +652,9 @@ impl Scene {
                 buf.extend_from_slice(&(p.y as f32).to_le_bytes());
                 buf.extend_from_slice(&(p.z as f32).to_le_bytes());
             }
+            if let Some(encoding) = normals {
+                write_normals(&mut buf, &ig.mesh, encoding);
+            }
             for &i in &ig.mesh.indices {
                 buf.extend_from_slice(&i.to_le_bytes());
             }
@@ -647,35 +665,22 @@ impl Scene {
             }
         }
 
-        std::fs::write(path, &buf)
-    }
-
-    fn generate_gltf_binary_buffer(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-
-        for scene_mesh in &self.meshes {
-            // Write positions
-            for pos in &scene_mesh.mesh.positions {
-                buffer.extend_from_slice(&(pos.x as f32).to_le_bytes());
-                buffer.extend_from_slice(&(pos.y as f32).to_le_bytes());
-                buffer.extend_from_slice(&(pos.z as f32).to_le_bytes());
-            }
-
-            // Write normals
-            for norm in &scene_mesh.mesh.normals {
-                buffer.extend_from_slice(&(norm.x as f32).to_le_bytes());
-                buffer.extend_from_slice(&(norm.y as f32).to_le_bytes());
-                buffer.extend_from_slice(&(norm.z as f32).to_le_bytes());
-            }
-
-            // Write indices
-            for idx in &scene_mesh.mesh.indices {
-                buffer.extend_from_slice(&idx.to_le_bytes());
-            }
+        // Material table
+        let materials = self.meshes.iter().map(|sm| &sm.material)
+            .chain(self.instanced_groups.iter().map(|ig| &ig.material));
+        for material in materials {
+            buf.extend_from_slice(&material.alpha.to_le_bytes());
+            buf.extend_from_slice(&material.metallic.to_le_bytes());
+            buf.extend_from_slice(&material.roughness.to_le_bytes());
+            buf.push(material.double_sided as u8);
+            let uri = material.texture.as_deref().unwrap_or_default().as_bytes();
+            buf.extend_from_slice(&(uri.len() as u32).to_le_bytes());
+            buf.extend_from_slice(uri);
         }
 
-        buffer
+        buf
     }
+
 }
 
 impl Default for Scene {
@@ -684,59 +689,32 @@ impl Default for Scene {
     }
 }
 
-// Simple base64 encoder
-fn base64_encode(data: &[u8]) -> String {
-    const CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
-    let mut result = String::new();
-
-    let mut i = 0;
-    while i < data.len() {
-        let b1 = data[i];
-        let b2 = if i + 1 < data.len() { data[i + 1] } else { 0 };
-        let b3 = if i + 2 < data.len() { data[i + 2] } else { 0 };
-
-        result.push(CHARS[(b1 >> 2) as usize] as char);
-        result.push(CHARS[(((b1 & 0x03) << 4) | (b2 >> 4)) as usize] as char);
-
-        if i + 1 < data.len() {
-            result.push(CHARS[(((b2 & 0x0F) << 2) | (b3 >> 6)) as usize] as char);
-        } else {
-            result.push('=');
-        }
-
-        if i + 2 < data.len() {
-            result.push(CHARS[(b3 & 0x3F) as usize] as char);
-        } else {
-            result.push('=');
+/// Quote text as a JavaScript string literal
+#[cfg(any(feature = "gltf", feature = "html"))]
+pub(crate) fn js_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            // Keep `</script>` in names from closing the script element
+            '<' => quoted.push_str("\\u003c"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
         }
-
-        i += 3;
     }
-
-    result
+    quoted.push('"');
+    quoted
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use cst_math::DVec3;
-
-    fn create_test_triangle() -> TriangleMesh {
-        TriangleMesh {
-            positions: vec![
-                DVec3::new(0.0, 0.0, 0.0),
-                DVec3::new(1.0, 0.0, 0.0),
-                DVec3::new(0.0, 1.0, 0.0),
-            ],
-            normals: vec![
-                DVec3::new(0.0, 0.0, 1.0),
-                DVec3::new(0.0, 0.0, 1.0),
-                DVec3::new(0.0, 0.0, 1.0),
-            ],
-            indices: vec![0, 1, 2],
-            uvs: vec![],
-        }
-    }
+    use crate::test_util::create_test_triangle;
 
     #[test]
     fn test_empty_scene() {
@@ -785,61 +763,236 @@ mod tests {
         assert_eq!(scene.meshes.len(), 12);
 
         // Check that colors cycle through palette
-        assert_eq!(scene.meshes[0].color, scene.meshes[10].color);
-        assert_ne!(scene.meshes[0].color, scene.meshes[1].color);
+        assert_eq!(scene.meshes[0].material, scene.meshes[10].material);
+        assert_ne!(scene.meshes[0].material, scene.meshes[1].material);
     }
 
     #[test]
-    fn test_html_export() {
+    fn test_section_planes() {
+        use cst_math::{Point3, Vector3};
+
         let mut scene = Scene::new();
-        let mesh = create_test_triangle();
-        scene.add_mesh("TestTriangle", mesh, [0.5, 0.6, 0.7]);
+        scene.add_mesh("TestTriangle", create_test_triangle(), [0.5, 0.6, 0.7]);
+        // Keep everything below y = 0.5
+        scene.add_section_plane(Plane::new(Point3::new(0.0, 0.5, 0.0), Vector3::new(0.0, -1.0, 0.0)));
 
-        let temp_dir = std::env::temp_dir();
-        let html_path = temp_dir.join("test_scene.html");
+        assert!(!scene.is_clipped(Point3::new(0.0, 0.0, 0.0)));
+        assert!(scene.is_clipped(Point3::new(0.0, 1.0, 0.0)));
+    }
 
-        let result = scene.export_html(&html_path);
-        assert!(result.is_ok());
+    #[test]
+    fn test_pick_closest_mesh() {
+        use cst_math::Vector3;
 
-        // Check file was created and has content
-        let metadata = std::fs::metadata(&html_path);
-        assert!(metadata.is_ok());
-        assert!(metadata.unwrap().len() > 0);
+        let mut scene = Scene::new();
+        let near = create_test_triangle();
+        let mut far = create_test_triangle();
+        for p in &mut far.positions {
+            p.z = -2.0;
+        }
+        scene.add_mesh("Far", far, [0.5, 0.5, 0.5]);
+        scene.add_mesh("Near", near, [0.5, 0.5, 0.5]);
+
+        let ray = Ray::new(Point3::new(0.25, 0.25, 5.0), -Vector3::Z);
+        let hit = scene.pick(&ray).unwrap();
+        assert_eq!(hit.target, PickTarget::Mesh(1));
+        assert_eq!(hit.triangle, 0);
+        assert!((hit.distance - 5.0).abs() < 1e-10);
+        assert!((hit.point - Point3::new(0.25, 0.25, 0.0)).length() < 1e-10);
+
+        // Cutting away z > -1 exposes the far triangle
+        scene.add_section_plane(Plane::new(Point3::new(0.0, 0.0, -1.0), -Vector3::Z));
+        let hit = scene.pick(&ray).unwrap();
+        assert_eq!(hit.target, PickTarget::Mesh(0));
+
+        let miss = Ray::new(Point3::new(5.0, 5.0, 5.0), -Vector3::Z);
+        assert!(scene.pick(&miss).is_none());
+    }
 
-        // Read file and check for key elements
-        let content = std::fs::read_to_string(&html_path).unwrap();
-        assert!(content.contains("<!DOCTYPE html>"));
-        assert!(content.contains("three.min.js"));
-        assert!(content.contains("TestTriangle"));
-        assert!(content.contains("meshData"));
+    #[test]
+    fn test_pick_instance() {
+        use cst_math::{DVec3, Vector3};
 
-        // Cleanup
-        let _ = std::fs::remove_file(html_path);
+        let mut scene = Scene::new();
+        let shifted = |x: f64| {
+            DMat4::from_translation(DVec3::new(x, 0.0, 0.0))
+                .to_cols_array()
+                .map(|v| v as f32)
+        };
+        scene.add_instanced_group(
+            "Tri",
+            create_test_triangle(),
+            [0.5, 0.5, 0.5],
+            vec![shifted(0.0), shifted(10.0)],
+        );
+
+        let ray = Ray::new(Point3::new(10.25, 0.25, 3.0), -Vector3::Z);
+        let hit = scene.pick(&ray).unwrap();
+        assert_eq!(hit.target, PickTarget::Instance { group: 0, instance: 1 });
+        assert!((hit.point - Point3::new(10.25, 0.25, 0.0)).length() < 1e-6);
+        assert!((hit.distance - 3.0).abs() < 1e-6);
     }
 
     #[test]
-    fn test_gltf_json_valid() {
+    fn test_selection_bounds() {
         let mut scene = Scene::new();
-        let mesh = create_test_triangle();
-        scene.add_mesh("TestMesh", mesh, [0.8, 0.2, 0.3]);
+        let mut lifted = create_test_triangle();
+        for p in &mut lifted.positions {
+            p.z = 4.0;
+        }
+        scene.add_mesh("Ground", create_test_triangle(), [0.5, 0.5, 0.5]);
+        scene.add_mesh("Lifted", lifted, [0.5, 0.5, 0.5]);
+        let shifted = DMat4::from_translation(DVec3::new(10.0, 0.0, 0.0))
+            .to_cols_array()
+            .map(|v| v as f32);
+        scene.add_instanced_group("Tri", create_test_triangle(), [0.5, 0.5, 0.5], vec![shifted]);
+
+        let bounds = scene.selection_bounds(&[PickTarget::Mesh(1)]).unwrap();
+        assert_eq!(bounds.min, DVec3::new(0.0, 0.0, 4.0));
+
+        let both = [PickTarget::Mesh(0), PickTarget::Instance { group: 0, instance: 0 }];
+        let bounds = scene.selection_bounds(&both).unwrap();
+        assert_eq!(bounds.min, DVec3::new(0.0, 0.0, 0.0));
+        assert_eq!(bounds.max, DVec3::new(11.0, 1.0, 0.0));
+
+        let missing = [PickTarget::Mesh(5), PickTarget::Instance { group: 0, instance: 3 }];
+        assert!(scene.selection_bounds(&missing).is_none());
+        assert!(scene.selection_bounds(&[]).is_none());
+    }
 
-        let json = scene.export_gltf_json();
+    #[test]
+    fn test_cached_bounds_and_invalidation() {
+        let mut scene = Scene::new();
+        scene.add_mesh("TestTriangle", create_test_triangle(), [0.5, 0.6, 0.7]);
+        assert_eq!(scene.meshes[0].bounds().unwrap().max, DVec3::new(1.0, 1.0, 0.0));
+
+        scene.meshes[0].mesh.positions[1].x = 3.0;
+        assert_eq!(scene.meshes[0].bounds().unwrap().max.x, 1.0); // still cached
+        scene.meshes[0].invalidate_cache();
+        assert_eq!(scene.meshes[0].bounds().unwrap().max.x, 3.0);
+        assert_eq!(scene.bounds().unwrap().max.x, 3.0);
+    }
 
-        // Check JSON is valid by parsing it
-        let parsed: Result<serde_json::Value, _> = serde_json::from_str(&json);
-        assert!(parsed.is_ok(), "Generated glTF JSON should be valid");
+    #[test]
+    fn test_frustum_culling() {
+        let mut scene = Scene::new();
+        let mut behind = create_test_triangle();
+        for p in &mut behind.positions {
+            p.z += 10.0; // behind the default camera at z = 5
+        }
+        scene.add_mesh("InView", create_test_triangle(), [0.5, 0.5, 0.5]);
+        scene.add_mesh("Behind", behind, [0.5, 0.5, 0.5]);
+
+        let shifted = |x: f32| {
+            let mut m = [0.0f32; 16];
+            m[0] = 1.0;
+            m[5] = 1.0;
+            m[10] = 1.0;
+            m[15] = 1.0;
+            m[12] = x;
+            m
+        };
+        scene.add_instanced_group(
+            "Tri",
+            create_test_triangle(),
+            [0.5, 0.5, 0.5],
+            vec![shifted(0.0), shifted(500.0)],
+        );
+
+        let camera = Camera::default();
+        assert_eq!(scene.visible_meshes(&camera), vec![0]);
+        assert_eq!(scene.visible_instances(&camera), vec![(0, 0)]);
+    }
 
-        let gltf = parsed.unwrap();
+    #[test]
+    fn test_spatial_queries() {
+        use cst_math::Vector3;
 
-        // Check key fields
-        assert!(gltf["asset"]["version"].as_str().unwrap() == "2.0");
-        assert!(gltf["scenes"].is_array());
-        assert!(gltf["nodes"].is_array());
-        assert!(gltf["meshes"].is_array());
-        assert!(gltf["materials"].is_array());
-        assert!(gltf["accessors"].is_array());
-        assert!(gltf["bufferViews"].is_array());
-        assert!(gltf["buffers"].is_array());
+        let mut scene = Scene::new();
+        for k in 0..10 {
+            let mut tri = create_test_triangle();
+            for p in &mut tri.positions {
+                p.x += k as f64 * 2.0;
+            }
+            scene.add_mesh(&format!("Tri{}", k), tri, [0.5, 0.5, 0.5]);
+        }
+        let translation = |x: f64| {
+            DMat4::from_translation(cst_math::DVec3::new(x, 0.0, 0.0))
+                .to_cols_array()
+                .map(|v| v as f32)
+        };
+        scene.add_instanced_group("Inst", create_test_triangle(), [0.5, 0.5, 0.5], vec![translation(4.5)]);
+
+        let query = Aabb3::new(DVec3::new(3.9, 0.0, -1.0), DVec3::new(4.6, 1.0, 1.0));
+        let found = scene.query_aabb(&query);
+        assert_eq!(found.len(), 2);
+        assert!(found.contains(&PickTarget::Mesh(2)));
+        assert!(found.contains(&PickTarget::Instance { group: 0, instance: 0 }));
+
+        // Along +x through y = z = 0 every box is hit, in order of distance
+        let ray = Ray::new(DVec3::new(-1.0, 0.0, 0.0), Vector3::X);
+        let hits = scene.query_ray(&ray);
+        assert_eq!(hits.len(), 11);
+        assert_eq!(hits[0], PickTarget::Mesh(0));
+
+        // Mesh 2 and the instance overlap; neighbours are 1 unit apart
+        let clashes = scene.clash_candidates(0.0);
+        assert_eq!(clashes, vec![(PickTarget::Mesh(2), PickTarget::Instance { group: 0, instance: 0 })]);
+        assert_eq!(scene.clash_candidates(1.1).len(), 9 + 2);
+
+        let camera = Camera {
+            eye: DVec3::new(0.0, 0.0, 5.0),
+            target: DVec3::ZERO,
+            ..Default::default()
+        };
+        let planes = camera.frustum_planes();
+        assert!(scene.query_frustum(&planes).contains(&PickTarget::Mesh(0)));
+        assert!(!scene.query_frustum(&planes).contains(&PickTarget::Mesh(9)));
+    }
+
+    #[test]
+    fn test_binary_material_table() {
+        let mut scene = Scene::new();
+        scene.add_mesh("A", create_test_triangle(), Material::from_color([1.0, 0.0, 0.0]).with_alpha(0.25));
+
+        let path = std::env::temp_dir().join("test_scene_materials.bin");
+        scene.export_binary_mesh(&path).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+
+        // Header, name, color, counts, positions, indices, then the table
+        let geometry_len = 1 + 4 + (4 + 1) + 12 + 8 + 3 * 12 + 3 * 4;
+        assert_eq!(&bytes[1 + 4 + 5..1 + 4 + 5 + 4], &1.0f32.to_le_bytes());
+        let table = &bytes[geometry_len..];
+        assert_eq!(table.len(), 4 * 3 + 1 + 4);
+        assert_eq!(&table[0..4], &0.25f32.to_le_bytes());
+        assert_eq!(table[12], 1);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_scene_mesh_serde_round_trip() {
+        let mut scene = Scene::new();
+        let metadata = ElementMetadata {
+            ifc_type: Some("IfcWall".into()),
+            global_id: Some("2O2Fr$t4X7Zf8NOew3FLOH".into()),
+            properties: vec![("Pset_WallCommon.FireRating".into(), "REI120".into())],
+            ..Default::default()
+        };
+        scene.add_element("Wall", create_test_triangle(), [1.0, 0.0, 0.0], metadata.clone());
+        let original = &scene.meshes[0];
+        assert!(original.bounds().is_some());
+
+        let json = serde_json::to_string(original).unwrap();
+        assert!(!json.contains("bvh"));
+        let restored: SceneMesh = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.name, "Wall");
+        assert_eq!(restored.metadata, metadata);
+        assert_eq!(restored.material, original.material);
+        assert_eq!(restored.mesh.positions, original.mesh.positions);
+        assert_eq!(restored.mesh.indices, original.mesh.indices);
+        // Cached data is rebuilt rather than stored
+        assert_eq!(restored.bounds().map(|b| b.max), original.bounds().map(|b| b.max));
     }
 
     #[test]
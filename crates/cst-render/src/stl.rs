@@ -0,0 +1,124 @@
+//! Binary STL export of a [`Scene`].
+//!
+//! STL has no materials, names or shared vertices: every visible mesh and
+//! every instance is flattened into one triangle soup in world space.
+//! Enough for 3D printing, clash checks and tools that only read STL.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use cst_math::DMat4;
+use cst_mesh::TriangleMesh;
+
+use crate::scene::Scene;
+
+impl Scene {
+    /// Write the scene as a binary STL file.
+    pub fn export_stl(&self, path: &Path) -> std::io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write_stl(&mut out)?;
+        out.flush()
+    }
+
+    /// Write binary STL data. Hidden meshes are skipped.
+    pub fn write_stl<W: Write>(&self, out: &mut W) -> std::io::Result<()> {
+        let mut parts: Vec<(&TriangleMesh, DMat4)> = self
+            .meshes
+            .iter()
+            .filter(|m| m.visible)
+            .map(|m| (&m.mesh, DMat4::IDENTITY))
+            .collect();
+        for ig in &self.instanced_groups {
+            for instance in 0..ig.transforms.len() {
+                parts.push((&ig.mesh, ig.transform_matrix(instance)));
+            }
+        }
+        let count: usize = parts.iter().map(|(mesh, _)| mesh.triangle_count()).sum();
+        let count = u32::try_from(count).map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "too many triangles for STL",
+            )
+        })?;
+
+        let mut header = [0u8; 80];
+        let label = b"CSTEngine STL export";
+        header[..label.len()].copy_from_slice(label);
+        out.write_all(&header)?;
+        out.write_all(&count.to_le_bytes())?;
+
+        for (mesh, transform) in parts {
+            for tri in mesh.indices.chunks_exact(3) {
+                let [a, b, c] = [tri[0], tri[1], tri[2]]
+                    .map(|i| transform.transform_point3(mesh.positions[i as usize]));
+                let normal = (b - a).cross(c - a).normalize_or_zero();
+                for v in [normal, a, b, c] {
+                    for coord in [v.x, v.y, v.z] {
+                        out.write_all(&(coord as f32).to_le_bytes())?;
+                    }
+                }
+                // Attribute byte count, unused
+                out.write_all(&[0, 0])?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cst_math::DVec3;
+
+    fn triangle() -> TriangleMesh {
+        TriangleMesh {
+            positions: vec![
+                DVec3::new(0.0, 0.0, 0.0),
+                DVec3::new(1.0, 0.0, 0.0),
+                DVec3::new(0.0, 1.0, 0.0),
+            ],
+            normals: vec![DVec3::Z; 3],
+            indices: vec![0, 1, 2],
+            uvs: vec![],
+        }
+    }
+
+    fn read_f32(bytes: &[u8], offset: usize) -> f32 {
+        f32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_stl_layout() {
+        let mut scene = Scene::new();
+        scene.add_mesh("a", triangle(), [1.0, 0.0, 0.0]);
+        scene.add_mesh("hidden", triangle(), [0.0, 1.0, 0.0]);
+        scene.meshes[1].visible = false;
+
+        let mut bytes = Vec::new();
+        scene.write_stl(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), 84 + 50);
+        assert!(bytes.starts_with(b"CSTEngine STL export"));
+        assert_eq!(u32::from_le_bytes(bytes[80..84].try_into().unwrap()), 1);
+        // Facet normal, then the second vertex
+        assert_eq!(read_f32(&bytes, 92), 1.0);
+        assert_eq!(read_f32(&bytes, 108), 1.0);
+    }
+
+    #[test]
+    fn test_stl_expands_instances() {
+        let mut scene = Scene::new();
+        let shift = DMat4::from_translation(DVec3::new(10.0, 0.0, 0.0));
+        let transforms = [DMat4::IDENTITY, shift]
+            .iter()
+            .map(|m| m.to_cols_array().map(|v| v as f32))
+            .collect();
+        scene.add_instanced_group("Column", triangle(), [0.7, 0.7, 0.7], transforms);
+
+        let mut bytes = Vec::new();
+        scene.write_stl(&mut bytes).unwrap();
+        assert_eq!(u32::from_le_bytes(bytes[80..84].try_into().unwrap()), 2);
+        // First vertex x of the second facet
+        assert_eq!(read_f32(&bytes, 84 + 50 + 12), 10.0);
+    }
+}
@@ -0,0 +1,511 @@
+//! Chunked binary mesh format (v4) for progressive loading.
+//!
+//! The v2/v3 `mesh.bin` layouts can only be parsed front to back once the
+//! whole file has arrived. v4 starts with a manifest of per-chunk byte ranges
+//! and bounds, and orders the chunks so the ones covering the most screen
+//! space from the initial viewpoint come first. A streaming reader can add
+//! each mesh to the scene as soon as its bytes are in; [`MESH_READER_JS`] is
+//! such a reader for the web viewer.
+//!
+//! Both the sequential (v5) and chunked layouts can carry per-vertex normals,
+//! either as three floats or octahedral-encoded in two `i16`s.
+
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::path::Path;
+
+use cst_math::{Aabb3, Point3, Vector3};
+use cst_mesh::TriangleMesh;
+
+use crate::material::Material;
+use crate::scene::Scene;
+
+/// Format version written in the first byte
+pub const CHUNKED_VERSION: u8 = 4;
+
+/// Bytes before the manifest: version, normal encoding, padding and three
+/// counts
+pub const CHUNKED_HEADER_SIZE: usize = 16;
+
+/// Bytes per manifest entry
+pub const CHUNKED_MANIFEST_ENTRY_SIZE: usize = 40;
+
+/// JavaScript module that streams a v4 file and hands out chunks as they
+/// arrive. Written next to `mesh.bin` by the web export.
+pub const MESH_READER_JS: &str = include_str!("mesh_reader.js");
+
+/// Viewer page that streams `mesh.bin` through [`MESH_READER_JS`]. Written
+/// as `index.html` by the web export.
+pub const WEB_VIEWER_HTML: &str = include_str!("web_viewer.html");
+
+/// Options for [`Scene::export_binary_mesh_with_options`]
+#[derive(Debug, Clone, Default)]
+pub struct BinaryMeshOptions {
+    /// Write the v4 chunked layout instead of v2/v3
+    pub chunked: bool,
+    /// Include per-vertex normals. Without chunking this selects format v5.
+    pub normals: Option<NormalEncoding>,
+    /// Viewpoint used to order chunks. Defaults to the initial camera
+    /// position of the viewer (isometric, framed on the scene bounds).
+    pub eye: Option<Point3>,
+}
+
+/// How vertex normals are stored in the binary formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum NormalEncoding {
+    /// `[f32 x][f32 y][f32 z]`, 12 bytes per vertex
+    Float32 = 1,
+    /// Octahedral mapping quantized to `[i16 x][i16 y]` (snorm), 4 bytes per
+    /// vertex with under 0.01° of error
+    Octahedral = 2,
+}
+
+/// Map a unit vector onto the octahedron unfolded into [-1, 1]², quantized
+/// to signed 16-bit
+pub fn octahedral_encode(normal: Vector3) -> [i16; 2] {
+    let l1 = normal.x.abs() + normal.y.abs() + normal.z.abs();
+    if l1 == 0.0 {
+        return [0, 0];
+    }
+    let (mut x, mut y) = (normal.x / l1, normal.y / l1);
+    if normal.z < 0.0 {
+        // Fold the lower hemisphere over the diagonals
+        (x, y) = ((1.0 - y.abs()) * x.signum(), (1.0 - x.abs()) * y.signum());
+    }
+    let quantize = |v: f64| (v.clamp(-1.0, 1.0) * 32767.0).round() as i16;
+    [quantize(x), quantize(y)]
+}
+
+/// Inverse of [`octahedral_encode`], returning a unit vector
+pub fn octahedral_decode(encoded: [i16; 2]) -> Vector3 {
+    let x = (encoded[0] as f64 / 32767.0).max(-1.0);
+    let y = (encoded[1] as f64 / 32767.0).max(-1.0);
+    let z = 1.0 - x.abs() - y.abs();
+    let t = (-z).max(0.0);
+    let x = x - t * x.signum();
+    let y = y - t * y.signum();
+    Vector3::new(x, y, z).normalize_or_zero()
+}
+
+/// Append one normal per vertex of `mesh`. Meshes without matching normals
+/// get smooth normals computed from their triangles.
+pub(crate) fn write_normals(buf: &mut Vec<u8>, mesh: &TriangleMesh, encoding: NormalEncoding) {
+    let normals = if mesh.normals.len() == mesh.positions.len() {
+        Cow::Borrowed(&mesh.normals)
+    } else {
+        let mut computed = mesh.clone();
+        computed.compute_normals();
+        Cow::Owned(computed.normals)
+    };
+    for n in normals.iter() {
+        match encoding {
+            NormalEncoding::Float32 => {
+                push_f32(buf, n.x as f32);
+                push_f32(buf, n.y as f32);
+                push_f32(buf, n.z as f32);
+            }
+            NormalEncoding::Octahedral => {
+                for v in octahedral_encode(*n) {
+                    buf.extend_from_slice(&v.to_le_bytes());
+                }
+            }
+        }
+    }
+}
+
+/// What a chunk holds, stored as the first manifest field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum ChunkKind {
+    Mesh = 0,
+    InstancedGroup = 1,
+}
+
+struct ChunkSource<'a> {
+    kind: ChunkKind,
+    index: usize,
+    name: &'a str,
+    mesh: &'a TriangleMesh,
+    material: &'a Material,
+    transforms: &'a [[f32; 16]],
+    bounds: Aabb3,
+}
+
+impl Scene {
+    /// Export scene mesh data for web streaming.
+    ///
+    /// Without `chunked` this is [`Scene::export_binary_mesh`], or format v5
+    /// when `normals` is set. The chunked layout (all values little endian,
+    /// every chunk 4-byte aligned):
+    ///
+    /// ```text
+    /// [u8 version=4][u8 normal_encoding: 0 = none, else NormalEncoding][2 bytes padding]
+    /// [u32 chunk_count][u32 mesh_count][u32 instanced_group_count]
+    /// Manifest, one entry per chunk in file order:
+    ///   [u32 kind: 0 = mesh, 1 = instanced group][u32 index in the scene]
+    ///   [u32 byte_offset][u32 byte_length][f32 min xyz][f32 max xyz]
+    /// Then per chunk:
+    ///   [u32 vertex_count][u32 index_count][u32 instance_count]
+    ///   [vertex_count * 3 * f32 positions]
+    ///   [vertex_count normals, if any]
+    ///   [index_count * u32 indices]
+    ///   [instance_count * 16 * f32 transform_matrices]
+    ///   [f32 r][f32 g][f32 b][f32 alpha][f32 metallic][f32 roughness]
+    ///   [u32 flags: bit 0 = double-sided]
+    ///   [u32 name_len][name_utf8][u32 texture_uri_len][texture_uri_utf8]
+    ///   [zero padding to a multiple of 4]
+    /// ```
+    ///
+    /// Chunks are sorted by apparent size from the viewpoint (bounding
+    /// radius over distance), so large and nearby elements render first.
+    pub fn export_binary_mesh_with_options(
+        &self,
+        path: &Path,
+        options: &BinaryMeshOptions,
+    ) -> std::io::Result<()> {
+        let buf = if options.chunked {
+            self.to_chunked_binary_mesh(options)
+        } else {
+            self.sequential_binary_mesh(options.normals)
+        };
+        std::fs::write(path, buf)
+    }
+
+    /// The v4 chunked layout as bytes; see
+    /// [`Scene::export_binary_mesh_with_options`]
+    pub fn to_chunked_binary_mesh(&self, options: &BinaryMeshOptions) -> Vec<u8> {
+        let mut chunks = self.chunk_sources();
+        let eye = options.eye.unwrap_or_else(|| self.default_eye());
+        let priority = |chunk: &ChunkSource| {
+            let radius = chunk.bounds.extents().length() * 0.5;
+            let distance = (chunk.bounds.center() - eye).length().max(radius);
+            if distance > 0.0 {
+                radius / distance
+            } else {
+                0.0
+            }
+        };
+        // Stable sort keeps scene order among equals
+        chunks.sort_by(|a, b| {
+            priority(b)
+                .partial_cmp(&priority(a))
+                .unwrap_or(Ordering::Equal)
+        });
+
+        let payloads: Vec<Vec<u8>> = chunks
+            .iter()
+            .map(|chunk| encode_chunk(chunk, options.normals))
+            .collect();
+
+        let mut buf = Vec::new();
+        buf.push(CHUNKED_VERSION);
+        buf.push(options.normals.map_or(0, |encoding| encoding as u8));
+        buf.extend_from_slice(&[0; 2]);
+        push_u32(&mut buf, chunks.len());
+        push_u32(&mut buf, self.meshes.len());
+        push_u32(&mut buf, self.instanced_groups.len());
+
+        let mut offset = CHUNKED_HEADER_SIZE + chunks.len() * CHUNKED_MANIFEST_ENTRY_SIZE;
+        for (chunk, payload) in chunks.iter().zip(&payloads) {
+            push_u32(&mut buf, chunk.kind as usize);
+            push_u32(&mut buf, chunk.index);
+            push_u32(&mut buf, offset);
+            push_u32(&mut buf, payload.len());
+            for v in [chunk.bounds.min, chunk.bounds.max] {
+                push_f32(&mut buf, v.x as f32);
+                push_f32(&mut buf, v.y as f32);
+                push_f32(&mut buf, v.z as f32);
+            }
+            offset += payload.len();
+        }
+        for payload in &payloads {
+            buf.extend_from_slice(payload);
+        }
+        buf
+    }
+
+    fn chunk_sources(&self) -> Vec<ChunkSource<'_>> {
+        let empty = || Aabb3::new(Point3::ZERO, Point3::ZERO);
+        let meshes = self.meshes.iter().enumerate().map(|(i, sm)| ChunkSource {
+            kind: ChunkKind::Mesh,
+            index: i,
+            name: &sm.name,
+            mesh: &sm.mesh,
+            material: &sm.material,
+            transforms: &[],
+            bounds: sm.bounds().unwrap_or_else(empty),
+        });
+        let groups = self
+            .instanced_groups
+            .iter()
+            .enumerate()
+            .map(|(g, ig)| ChunkSource {
+                kind: ChunkKind::InstancedGroup,
+                index: g,
+                name: &ig.name,
+                mesh: &ig.mesh,
+                material: &ig.material,
+                transforms: &ig.transforms,
+                bounds: (0..ig.transforms.len())
+                    .filter_map(|i| ig.instance_bounds(i))
+                    .reduce(|a, b| a.merge(&b))
+                    .unwrap_or_else(empty),
+            });
+        meshes.chain(groups).collect()
+    }
+
+    /// Initial camera position of the HTML and web viewers
+    fn default_eye(&self) -> Point3 {
+        match self.bounds() {
+            Some(bounds) => {
+                let distance = bounds.extents().length() * 1.5;
+                bounds.center() + Point3::splat(distance * 0.7)
+            }
+            None => Point3::splat(1.0),
+        }
+    }
+}
+
+fn encode_chunk(chunk: &ChunkSource, normals: Option<NormalEncoding>) -> Vec<u8> {
+    let mut buf = Vec::new();
+    push_u32(&mut buf, chunk.mesh.positions.len());
+    push_u32(&mut buf, chunk.mesh.indices.len());
+    push_u32(&mut buf, chunk.transforms.len());
+    for p in &chunk.mesh.positions {
+        push_f32(&mut buf, p.x as f32);
+        push_f32(&mut buf, p.y as f32);
+        push_f32(&mut buf, p.z as f32);
+    }
+    if let Some(encoding) = normals {
+        write_normals(&mut buf, chunk.mesh, encoding);
+    }
+    for &i in &chunk.mesh.indices {
+        buf.extend_from_slice(&i.to_le_bytes());
+    }
+    for transform in chunk.transforms {
+        for &value in transform {
+            push_f32(&mut buf, value);
+        }
+    }
+
+    let material = chunk.material;
+    for value in material.base_color_rgba() {
+        push_f32(&mut buf, value);
+    }
+    push_f32(&mut buf, material.metallic);
+    push_f32(&mut buf, material.roughness);
+    push_u32(&mut buf, material.double_sided as usize);
+    for text in [chunk.name, material.texture.as_deref().unwrap_or_default()] {
+        push_u32(&mut buf, text.len());
+        buf.extend_from_slice(text.as_bytes());
+    }
+    buf.resize(buf.len().next_multiple_of(4), 0);
+    buf
+}
+
+fn push_u32(buf: &mut Vec<u8>, value: usize) {
+    buf.extend_from_slice(&(value as u32).to_le_bytes());
+}
+
+fn push_f32(buf: &mut Vec<u8>, value: f32) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cst_math::{DMat4, DVec3};
+
+    fn box_mesh(min: DVec3, max: DVec3) -> TriangleMesh {
+        TriangleMesh {
+            positions: vec![min, DVec3::new(max.x, min.y, min.z), max],
+            normals: vec![],
+            indices: vec![0, 1, 2],
+            uvs: vec![],
+        }
+    }
+
+    fn u32_at(bytes: &[u8], offset: usize) -> usize {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap()) as usize
+    }
+
+    /// (kind, index, offset, length) of each manifest entry
+    fn manifest(bytes: &[u8]) -> Vec<(usize, usize, usize, usize)> {
+        (0..u32_at(bytes, 4))
+            .map(|c| {
+                let entry = CHUNKED_HEADER_SIZE + c * CHUNKED_MANIFEST_ENTRY_SIZE;
+                (
+                    u32_at(bytes, entry),
+                    u32_at(bytes, entry + 4),
+                    u32_at(bytes, entry + 8),
+                    u32_at(bytes, entry + 12),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_chunked_layout() {
+        let mut scene = Scene::new();
+        scene.add_mesh(
+            "Odd name",
+            box_mesh(DVec3::ZERO, DVec3::ONE),
+            Material::from_color([1.0, 0.5, 0.25]).with_alpha(0.5),
+        );
+        let shift = DMat4::from_translation(DVec3::new(5.0, 0.0, 0.0));
+        scene.add_instanced_group(
+            "Column",
+            box_mesh(DVec3::ZERO, DVec3::ONE),
+            [0.7, 0.7, 0.7],
+            [DMat4::IDENTITY, shift]
+                .iter()
+                .map(|m| m.to_cols_array().map(|v| v as f32))
+                .collect(),
+        );
+
+        let bytes = scene.to_chunked_binary_mesh(&BinaryMeshOptions::default());
+        assert_eq!(bytes[0], CHUNKED_VERSION);
+        assert_eq!(u32_at(&bytes, 8), 1);
+        assert_eq!(u32_at(&bytes, 12), 1);
+
+        let entries = manifest(&bytes);
+        assert_eq!(entries.len(), 2);
+        let mut end = CHUNKED_HEADER_SIZE + 2 * CHUNKED_MANIFEST_ENTRY_SIZE;
+        for &(_, _, offset, length) in &entries {
+            assert_eq!(offset, end);
+            assert_eq!(offset % 4, 0);
+            assert_eq!(length % 4, 0);
+            end += length;
+        }
+        assert_eq!(end, bytes.len());
+
+        // Mesh chunk: counts, geometry, material, then the name
+        let (kind, _, offset, length) = *entries.iter().find(|e| e.0 == 0).unwrap();
+        assert_eq!(kind, ChunkKind::Mesh as usize);
+        let chunk = &bytes[offset..offset + length];
+        assert_eq!(
+            (u32_at(chunk, 0), u32_at(chunk, 4), u32_at(chunk, 8)),
+            (3, 3, 0)
+        );
+        let material = 12 + 3 * 12 + 3 * 4;
+        assert_eq!(&chunk[material + 12..material + 16], &0.5f32.to_le_bytes());
+        assert_eq!(u32_at(chunk, material + 24), 1);
+        assert_eq!(u32_at(chunk, material + 28), 8);
+        assert_eq!(&chunk[material + 32..material + 40], b"Odd name");
+
+        // Instanced group chunk carries its transforms
+        let (_, index, offset, _) = *entries.iter().find(|e| e.0 == 1).unwrap();
+        assert_eq!(index, 0);
+        assert_eq!(u32_at(&bytes, offset + 8), 2);
+    }
+
+    #[test]
+    fn test_chunks_ordered_by_apparent_size() {
+        let mut scene = Scene::new();
+        let small = box_mesh(DVec3::ZERO, DVec3::splat(0.1));
+        let large = box_mesh(DVec3::ZERO, DVec3::splat(10.0));
+        scene.add_mesh("Far small", small.clone(), [0.5, 0.5, 0.5]);
+        scene.add_mesh("Large", large, [0.5, 0.5, 0.5]);
+        let near = box_mesh(DVec3::splat(19.0), DVec3::splat(20.0));
+        scene.add_mesh("Near small", near, [0.5, 0.5, 0.5]);
+
+        let eye = Point3::splat(20.5);
+        let options = BinaryMeshOptions {
+            eye: Some(eye),
+            ..Default::default()
+        };
+        let order: Vec<usize> = manifest(&scene.to_chunked_binary_mesh(&options))
+            .iter()
+            .map(|e| e.1)
+            .collect();
+        assert_eq!(order, vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn test_export_without_chunking_is_v2() {
+        let mut scene = Scene::new();
+        scene.add_mesh("A", box_mesh(DVec3::ZERO, DVec3::ONE), [0.5, 0.5, 0.5]);
+
+        let path = std::env::temp_dir().join("test_scene_streaming.bin");
+        scene
+            .export_binary_mesh_with_options(&path, &BinaryMeshOptions::default())
+            .unwrap();
+        assert_eq!(std::fs::read(&path).unwrap()[0], 2);
+
+        let options = BinaryMeshOptions {
+            chunked: true,
+            ..Default::default()
+        };
+        scene
+            .export_binary_mesh_with_options(&path, &options)
+            .unwrap();
+        assert_eq!(std::fs::read(&path).unwrap()[0], CHUNKED_VERSION);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_octahedral_round_trip() {
+        let normals = [
+            DVec3::Z,
+            -DVec3::Z,
+            DVec3::X,
+            -DVec3::Y,
+            DVec3::new(1.0, -2.0, -3.0).normalize(),
+            DVec3::new(-0.3, 0.2, 0.9).normalize(),
+        ];
+        for n in normals {
+            let decoded = octahedral_decode(octahedral_encode(n));
+            assert!(decoded.angle_between(n) < 0.01f64.to_radians(), "{:?}", n);
+        }
+        assert_eq!(octahedral_encode(DVec3::ZERO), [0, 0]);
+    }
+
+    #[test]
+    fn test_v5_normals() {
+        let mut scene = Scene::new();
+        // No stored normals: computed from the triangle (+Z)
+        scene.add_mesh("A", box_mesh(DVec3::ZERO, DVec3::ONE), [0.5, 0.5, 0.5]);
+
+        let float = scene.sequential_binary_mesh(Some(NormalEncoding::Float32));
+        assert_eq!(&float[..2], &[5, NormalEncoding::Float32 as u8]);
+        assert_eq!(u32_at(&float, 2), 1);
+        assert_eq!(u32_at(&float, 6), 0);
+
+        // Header, name, color, counts, positions, then the normals
+        let normals = 2 + 8 + (4 + 1) + 12 + 8 + 3 * 12;
+        let n = |k: usize| f32::from_le_bytes(float[normals + k * 4..][..4].try_into().unwrap());
+        let expected = DVec3::new(0.0, -1.0, 1.0).normalize();
+        assert!((n(1) as f64 - expected.y).abs() < 1e-6);
+        assert!((n(2) as f64 - expected.z).abs() < 1e-6);
+
+        let octahedral = scene.sequential_binary_mesh(Some(NormalEncoding::Octahedral));
+        assert_eq!(octahedral[1], NormalEncoding::Octahedral as u8);
+        assert_eq!(float.len() - octahedral.len(), 3 * (12 - 4));
+    }
+
+    #[test]
+    fn test_chunked_normals() {
+        let mut scene = Scene::new();
+        scene.add_mesh("A", box_mesh(DVec3::ZERO, DVec3::ONE), [0.5, 0.5, 0.5]);
+        let options = BinaryMeshOptions {
+            chunked: true,
+            normals: Some(NormalEncoding::Octahedral),
+            ..Default::default()
+        };
+        let bytes = scene.to_chunked_binary_mesh(&options);
+        assert_eq!(bytes[1], NormalEncoding::Octahedral as u8);
+
+        let (_, _, offset, length) = manifest(&bytes)[0];
+        let plain = scene.to_chunked_binary_mesh(&BinaryMeshOptions::default());
+        assert_eq!(plain[1], 0);
+        assert_eq!(length - manifest(&plain)[0].3, 3 * 4);
+
+        let normal = offset + 12 + 3 * 12;
+        let encoded =
+            [0, 1].map(|k| i16::from_le_bytes(bytes[normal + k * 2..][..2].try_into().unwrap()));
+        let decoded = octahedral_decode(encoded);
+        assert!(decoded.angle_between(DVec3::new(0.0, -1.0, 1.0)) < 1e-3);
+    }
+}
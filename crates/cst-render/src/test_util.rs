@@ -0,0 +1,22 @@
+//! Shared scene fixtures for unit tests.
+
+use cst_math::DVec3;
+use cst_mesh::TriangleMesh;
+
+/// A single triangle in the XY plane facing +Z.
+pub(crate) fn create_test_triangle() -> TriangleMesh {
+    TriangleMesh {
+        positions: vec![
+            DVec3::new(0.0, 0.0, 0.0),
+            DVec3::new(1.0, 0.0, 0.0),
+            DVec3::new(0.0, 1.0, 0.0),
+        ],
+        normals: vec![
+            DVec3::new(0.0, 0.0, 1.0),
+            DVec3::new(0.0, 0.0, 1.0),
+            DVec3::new(0.0, 0.0, 1.0),
+        ],
+        indices: vec![0, 1, 2],
+        uvs: vec![],
+    }
+}
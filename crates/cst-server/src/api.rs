@@ -0,0 +1,526 @@
+//! REST API for using the crate as a conversion backend.
+//!
+//! | Method | Path | Result |
+//! |---|---|---|
+//! | `POST` | `/api/models?name=NAME` | Convert the IFC request body; 201 with the manifest |
+//! | `GET` | `/api/models` | All loaded models |
+//! | `GET` | `/api/models/{id}` | Scene manifest: bounds, one entry per chunk and conversion warnings |
+//! | `DELETE` | `/api/models/{id}` | Unload a model |
+//! | `GET` | `/api/models/{id}/mesh.bin` | Whole chunked mesh file, byte ranges allowed |
+//! | `GET` | `/api/models/{id}/chunks/{n}` | Chunk `n` of the mesh file |
+//! | `GET` | `/api/models/{id}/elements/{guid}` | Type, name, storey and properties |
+//!
+//! Errors are `{"error": "..."}` with a 4xx or 5xx status. Every response
+//! allows cross-origin requests so frontends can live on another host.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+use cst_core::{Diagnostics, ModelUnits, Result};
+use cst_ifc::ifc_options::IfcPipelineOptions;
+use cst_ifc::ifc_query::IfcQuery;
+use cst_ifc::ifc_to_mesh::faces_to_trimesh_with_diagnostics;
+use cst_mesh::TriangleMesh;
+use cst_render::streaming::{ChunkKind, CHUNKED_HEADER_SIZE, CHUNKED_MANIFEST_ENTRY_SIZE};
+use cst_render::{BinaryMeshOptions, ElementMetadata, NormalEncoding, Scene};
+use serde::Serialize;
+
+use crate::http::{Request, Response};
+
+/// Element data returned by `/api/models/{id}/elements/{guid}`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ElementInfo {
+    pub global_id: String,
+    /// Upper-case IFC type, e.g. `IFCWALL`
+    pub ifc_type: String,
+    pub name: Option<String>,
+    pub storey: Option<String>,
+    /// Single-value properties by name
+    pub properties: BTreeMap<String, String>,
+}
+
+/// One chunk of the mesh file, in file order
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChunkInfo {
+    pub index: usize,
+    /// `mesh` or `instanced`
+    pub kind: &'static str,
+    pub name: String,
+    pub global_id: Option<String>,
+    /// Byte range of the chunk in `mesh.bin`
+    pub offset: usize,
+    pub length: usize,
+    pub triangles: usize,
+    /// `[min, max]` corners
+    pub bounds: [[f32; 3]; 2],
+}
+
+/// Scene manifest returned on upload and by `/api/models/{id}`
+#[derive(Debug, Clone, Serialize)]
+pub struct SceneManifest {
+    pub id: u64,
+    pub name: String,
+    pub element_count: usize,
+    pub triangle_count: usize,
+    pub mesh_bin_size: usize,
+    pub chunks: Vec<ChunkInfo>,
+    /// Units of the mesh coordinates, after the unit override
+    pub units: ModelUnits,
+    /// What had to be skipped during conversion, one line per kind, e.g.
+    /// `3 faces skipped as degenerate`
+    pub warnings: Vec<String>,
+}
+
+/// A converted model held in memory.
+#[derive(Debug)]
+pub struct Model {
+    pub manifest: SceneManifest,
+    /// Chunked (v4) mesh file
+    mesh_bin: Arc<Vec<u8>>,
+    elements: HashMap<String, ElementInfo>,
+}
+
+impl Model {
+    /// Convert the IFC file at `path`. Type and storey filters, the unit
+    /// scale and the tessellation tolerance of `options` apply.
+    pub fn from_ifc(
+        id: u64,
+        name: &str,
+        path: &Path,
+        options: &IfcPipelineOptions,
+    ) -> Result<Self> {
+        let query = IfcQuery::open(path)?;
+        let mut ids = match &options.storey {
+            Some(storey) => query.elements_in_storey(storey),
+            None => query.elements(),
+        };
+        ids.retain(|&id| query.type_of(id).is_some_and(|t| options.accepts_type(t)));
+
+        let mut scene = Scene::new();
+        let mut elements = HashMap::new();
+        let mut diagnostics = Diagnostics::new();
+        for id in ids {
+            let global_id = query.global_id(id);
+            let element = ElementInfo {
+                global_id: global_id.clone().unwrap_or_default(),
+                ifc_type: query.type_of(id).unwrap_or_default().to_string(),
+                name: query.name(id),
+                storey: query.storey_of(id).map(str::to_string),
+                properties: query.properties(id).iter().cloned().collect(),
+            };
+            let metadata = ElementMetadata {
+                ifc_type: Some(element.ifc_type.clone()),
+                storey: element.storey.clone(),
+                global_id,
+                properties: query.properties(id).to_vec(),
+            };
+            for data in query.meshes_with_diagnostics(&[id], &mut diagnostics) {
+                let tri = faces_to_trimesh_with_diagnostics(
+                    &data.name,
+                    &data.faces,
+                    options.tessellation_tolerance,
+                    &mut diagnostics,
+                );
+                if tri.indices.is_empty() {
+                    continue;
+                }
+                let mut mesh = TriangleMesh {
+                    positions: tri.positions,
+                    normals: tri.normals,
+                    indices: tri.indices,
+                    uvs: vec![],
+                };
+                for p in &mut mesh.positions {
+                    *p *= options.scale();
+                }
+                let color = options.color_or_default(data.color);
+                scene.add_element(&data.name, mesh, color, metadata.clone());
+            }
+            if !element.global_id.is_empty() {
+                elements.insert(element.global_id.clone(), element);
+            }
+        }
+
+        let mesh_bin = scene.to_chunked_binary_mesh(&BinaryMeshOptions {
+            chunked: true,
+            normals: Some(NormalEncoding::Octahedral),
+            eye: None,
+        });
+        let manifest = SceneManifest {
+            id,
+            name: name.to_string(),
+            element_count: elements.len(),
+            triangle_count: scene.meshes.iter().map(|m| m.mesh.triangle_count()).sum(),
+            mesh_bin_size: mesh_bin.len(),
+            chunks: chunk_manifest(&mesh_bin, &scene),
+            units: query.units().with_length_scale(options.scale()),
+            warnings: diagnostics
+                .summary()
+                .into_iter()
+                .map(|(_, line)| line)
+                .collect(),
+        };
+        Ok(Self {
+            manifest,
+            mesh_bin: Arc::new(mesh_bin),
+            elements,
+        })
+    }
+
+    pub fn element(&self, guid: &str) -> Option<&ElementInfo> {
+        self.elements.get(guid)
+    }
+
+    /// The chunked mesh file as served by `/mesh.bin`.
+    pub fn mesh_bin(&self) -> &[u8] {
+        &self.mesh_bin
+    }
+}
+
+/// Read the manifest back out of a chunked mesh file and name its chunks.
+fn chunk_manifest(bytes: &[u8], scene: &Scene) -> Vec<ChunkInfo> {
+    let u32_at = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap()) as usize;
+    let f32_at = |at: usize| f32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+    (0..u32_at(4))
+        .map(|i| {
+            let at = CHUNKED_HEADER_SIZE + i * CHUNKED_MANIFEST_ENTRY_SIZE;
+            let scene_index = u32_at(at + 4);
+            let (kind, name, global_id, triangles) = if u32_at(at) == ChunkKind::Mesh as usize {
+                let sm = &scene.meshes[scene_index];
+                let triangles = sm.mesh.triangle_count();
+                ("mesh", &sm.name, sm.metadata.global_id.clone(), triangles)
+            } else {
+                let ig = &scene.instanced_groups[scene_index];
+                let triangles = ig.mesh.triangle_count() * ig.transforms.len();
+                ("instanced", &ig.name, None, triangles)
+            };
+            let corner = |at: usize| [f32_at(at), f32_at(at + 4), f32_at(at + 8)];
+            ChunkInfo {
+                index: i,
+                kind,
+                name: name.clone(),
+                global_id,
+                offset: u32_at(at + 8),
+                length: u32_at(at + 12),
+                triangles,
+                bounds: [corner(at + 16), corner(at + 28)],
+            }
+        })
+        .collect()
+}
+
+/// The API: converted models keyed by id, and the options uploads are
+/// converted with.
+#[derive(Debug, Default)]
+pub struct ApiServer {
+    options: IfcPipelineOptions,
+    models: RwLock<BTreeMap<u64, Arc<Model>>>,
+    next_id: AtomicU64,
+}
+
+impl ApiServer {
+    pub fn new(options: IfcPipelineOptions) -> Self {
+        Self {
+            options,
+            models: RwLock::default(),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Convert the IFC file at `path` and make it available under a new id.
+    pub fn add_model(&self, name: &str, path: &Path) -> Result<Arc<Model>> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed).max(1);
+        let model = Arc::new(Model::from_ifc(id, name, path, &self.options)?);
+        self.models.write().unwrap().insert(id, Arc::clone(&model));
+        Ok(model)
+    }
+
+    pub fn model(&self, id: u64) -> Option<Arc<Model>> {
+        self.models.read().unwrap().get(&id).cloned()
+    }
+
+    /// Answer one API request.
+    pub fn handle(&self, request: &Request) -> Response {
+        self.route(request)
+            .with_header("Access-Control-Allow-Origin", "*")
+    }
+
+    fn route(&self, request: &Request) -> Response {
+        let Some(segments) = request.segments() else {
+            return Response::error(400, "invalid path");
+        };
+        let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+        let method = request.method.as_str();
+        if method == "OPTIONS" {
+            // CORS preflight
+            return Response::new(204)
+                .with_header(
+                    "Access-Control-Allow-Methods",
+                    "GET, HEAD, POST, DELETE, OPTIONS",
+                )
+                .with_header("Access-Control-Allow-Headers", "Content-Type, Range");
+        }
+        let model = |id: &str| {
+            id.parse()
+                .ok()
+                .and_then(|id| self.model(id))
+                .ok_or_else(|| Response::error(404, "no such model"))
+        };
+        let result = match (method, segments.as_slice()) {
+            ("GET", ["api", "models"]) => {
+                let models = self.models.read().unwrap();
+                let list: Vec<_> = models
+                    .values()
+                    .map(|m| {
+                        serde_json::json!({
+                            "id": m.manifest.id,
+                            "name": m.manifest.name,
+                            "element_count": m.manifest.element_count,
+                            "triangle_count": m.manifest.triangle_count,
+                        })
+                    })
+                    .collect();
+                Ok(Response::json(200, &list))
+            }
+            ("POST", ["api", "models"]) => self.upload(request),
+            ("GET", ["api", "models", id]) => model(id).map(|m| Response::json(200, &m.manifest)),
+            ("DELETE", ["api", "models", id]) => {
+                let removed = id
+                    .parse()
+                    .ok()
+                    .and_then(|id| self.models.write().unwrap().remove(&id));
+                match removed {
+                    Some(_) => Ok(Response::new(204)),
+                    None => Err(Response::error(404, "no such model")),
+                }
+            }
+            ("GET" | "HEAD", ["api", "models", id, "mesh.bin"]) => model(id).map(|m| {
+                Response::bytes(200, "application/octet-stream", Arc::clone(&m.mesh_bin))
+                    .with_range(request.header("range"))
+            }),
+            ("GET", ["api", "models", id, "chunks", index]) => model(id).and_then(|m| {
+                let chunk = index
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|i| m.manifest.chunks.get(i))
+                    .ok_or_else(|| Response::error(404, "no such chunk"))?;
+                let range = format!("bytes={}-{}", chunk.offset, chunk.offset + chunk.length - 1);
+                let mut response =
+                    Response::bytes(200, "application/octet-stream", Arc::clone(&m.mesh_bin))
+                        .with_range(Some(&range));
+                // A whole resource from the client's point of view
+                response.status = 200;
+                response.headers.retain(|(name, _)| name != "Content-Range");
+                Ok(response)
+            }),
+            ("GET", ["api", "models", id, "elements", guid]) => model(id).and_then(|m| {
+                m.element(guid)
+                    .map(|element| Response::json(200, element))
+                    .ok_or_else(|| Response::error(404, "no element with that GlobalId"))
+            }),
+            (_, ["api", "models", ..]) => Err(Response::error(405, "method not allowed")),
+            _ => Err(Response::error(404, "not found")),
+        };
+        result.unwrap_or_else(|error| error)
+    }
+
+    fn upload(&self, request: &Request) -> std::result::Result<Response, Response> {
+        if request.body.is_empty() {
+            return Err(Response::error(
+                400,
+                "expected an IFC file as the request body",
+            ));
+        }
+        let text_start = request
+            .body
+            .iter()
+            .position(|b| !b.is_ascii_whitespace())
+            .unwrap_or(0);
+        if !request.body[text_start..].starts_with(b"ISO-10303-21") {
+            return Err(Response::error(
+                422,
+                "request body is not an IFC (STEP) file",
+            ));
+        }
+        // The parser streams from disk, so stage the upload in a file
+        let id = self.next_id.load(Ordering::Relaxed);
+        let path =
+            std::env::temp_dir().join(format!("cst-server-{}-{}.ifc", std::process::id(), id));
+        std::fs::write(&path, &request.body)
+            .map_err(|e| Response::error(500, &format!("failed to stage upload: {}", e)))?;
+        let name = request
+            .query_param("name")
+            .unwrap_or_else(|| format!("model-{}", id));
+        let result = self.add_model(&name, &path);
+        let _ = std::fs::remove_file(&path);
+        let model = result.map_err(|e| Response::error(422, &e.to_string()))?;
+        Ok(Response::json(201, &model.manifest)
+            .with_header("Location", &format!("/api/models/{}", model.manifest.id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODEL: &str = "ISO-10303-21;
+HEADER;
+FILE_SCHEMA(('IFC2X3'));
+ENDSEC;
+DATA;
+#1= IFCCARTESIANPOINT((0.,0.,0.));
+#2= IFCCARTESIANPOINT((1.,0.,0.));
+#3= IFCCARTESIANPOINT((1.,1.,0.));
+#4= IFCCARTESIANPOINT((0.,1.,0.));
+#5= IFCPOLYLOOP((#1,#2,#3,#4));
+#6= IFCFACEOUTERBOUND(#5,.T.);
+#7= IFCFACE((#6));
+#8= IFCCLOSEDSHELL((#7));
+#9= IFCFACETEDBREP(#8);
+#10= IFCSHAPEREPRESENTATION($,'Body','Brep',(#9));
+#11= IFCPRODUCTDEFINITIONSHAPE($,$,(#10));
+#20= IFCWALL('2O2Fr$t4X7Zf8NOew3FLOH',$,'Wall A',$,$,$,#11,$);
+#21= IFCSLAB('1hOSvn6df7F8_7GcBWlRGQ',$,'Slab',$,$,$,#11,$);
+#30= IFCBUILDINGSTOREY('st1',$,'Level 1',$,$,$,$,$,.ELEMENT.,0.);
+#31= IFCRELCONTAINEDINSPATIALSTRUCTURE('r1',$,$,$,(#20,#21),#30);
+#40= IFCPROPERTYSINGLEVALUE('FireRating',$,IFCLABEL('REI120'),$);
+#41= IFCPROPERTYSET('p1',$,'Pset_WallCommon',$,(#40));
+#42= IFCRELDEFINESBYPROPERTIES('r2',$,$,$,(#20),#41);
+ENDSEC;
+END-ISO-10303-21;
+";
+
+    fn upload(server: &ApiServer) -> Response {
+        let mut request = Request::new("POST", "/api/models?name=Test");
+        request.body = MODEL.as_bytes().to_vec();
+        server.handle(&request)
+    }
+
+    fn json(response: &Response) -> serde_json::Value {
+        match &response.body {
+            crate::http::Body::Bytes { data, start, len } => {
+                serde_json::from_slice(&data[*start..start + len]).unwrap()
+            }
+            other => panic!("unexpected body {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_upload_and_manifest() {
+        let server = ApiServer::default();
+        let response = upload(&server);
+        assert_eq!(response.status, 201);
+        let manifest = json(&response);
+        assert_eq!(manifest["id"], 1);
+        assert_eq!(manifest["name"], "Test");
+        assert_eq!(manifest["element_count"], 2);
+        assert_eq!(manifest["triangle_count"], 4);
+        assert_eq!(manifest["chunks"].as_array().unwrap().len(), 2);
+        assert_eq!(manifest["units"]["length"], "metre");
+        assert_eq!(manifest["warnings"], serde_json::json!([]));
+
+        let response = server.handle(&Request::new("GET", "/api/models/1"));
+        assert_eq!(response.status, 200);
+        assert_eq!(json(&response)["chunks"], manifest["chunks"]);
+        assert!(response
+            .headers
+            .contains(&("Access-Control-Allow-Origin".into(), "*".into())));
+
+        let list = json(&server.handle(&Request::new("GET", "/api/models")));
+        assert_eq!(list[0]["name"], "Test");
+    }
+
+    #[test]
+    fn test_chunks_and_mesh_bin() {
+        let server = ApiServer::default();
+        upload(&server);
+        let model = server.model(1).unwrap();
+        let chunk = &model.manifest.chunks[1];
+
+        let response = server.handle(&Request::new("GET", "/api/models/1/chunks/1"));
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body.len(), chunk.length as u64);
+
+        let mut ranged = Request::new("GET", "/api/models/1/mesh.bin");
+        ranged.headers.push(("Range".into(), "bytes=0-15".into()));
+        let response = server.handle(&ranged);
+        assert_eq!(response.status, 206);
+        assert_eq!(response.body.len(), 16);
+        assert_eq!(model.mesh_bin()[0], 4);
+
+        assert_eq!(
+            server
+                .handle(&Request::new("GET", "/api/models/1/chunks/9"))
+                .status,
+            404
+        );
+    }
+
+    #[test]
+    fn test_element_by_guid() {
+        let server = ApiServer::default();
+        upload(&server);
+        let response = server.handle(&Request::new(
+            "GET",
+            "/api/models/1/elements/2O2Fr$t4X7Zf8NOew3FLOH",
+        ));
+        assert_eq!(response.status, 200);
+        let element = json(&response);
+        assert_eq!(element["ifc_type"], "IFCWALL");
+        assert_eq!(element["name"], "Wall A");
+        assert_eq!(element["storey"], "Level 1");
+        assert_eq!(element["properties"]["FireRating"], "REI120");
+
+        let missing = server.handle(&Request::new("GET", "/api/models/1/elements/nope"));
+        assert_eq!(missing.status, 404);
+        assert_eq!(json(&missing)["error"], "no element with that GlobalId");
+    }
+
+    #[test]
+    fn test_errors_and_delete() {
+        let server = ApiServer::default();
+        assert_eq!(
+            server.handle(&Request::new("GET", "/api/models/7")).status,
+            404
+        );
+        assert_eq!(
+            server.handle(&Request::new("POST", "/api/models")).status,
+            400
+        );
+        let mut garbage = Request::new("POST", "/api/models");
+        garbage.body = b"not an ifc".to_vec();
+        assert_eq!(server.handle(&garbage).status, 422);
+        assert_eq!(
+            server.handle(&Request::new("PUT", "/api/models/1")).status,
+            405
+        );
+        assert_eq!(
+            server
+                .handle(&Request::new("OPTIONS", "/api/models"))
+                .status,
+            204
+        );
+
+        upload(&server);
+        assert_eq!(
+            server
+                .handle(&Request::new("DELETE", "/api/models/1"))
+                .status,
+            204
+        );
+        assert!(server.model(1).is_none());
+    }
+
+    #[test]
+    fn test_upload_applies_type_filter() {
+        let server = ApiServer::new(IfcPipelineOptions {
+            type_filter: Some(vec!["IfcSlab".into()]),
+            ..Default::default()
+        });
+        let manifest = json(&upload(&server));
+        assert_eq!(manifest["element_count"], 1);
+        assert_eq!(manifest["chunks"][0]["global_id"], "1hOSvn6df7F8_7GcBWlRGQ");
+    }
+}
@@ -0,0 +1,695 @@
+//! Minimal HTTP/1.1 on std: enough to serve files and a JSON API with
+//! keep-alive and byte ranges, one thread per connection up to a bounded
+//! number of connections.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+use log::{debug, warn};
+use serde::Serialize;
+
+/// Longest request or header line accepted, in bytes.
+pub const MAX_LINE_LENGTH: usize = 8 * 1024;
+
+/// Most header lines accepted in one request.
+pub const MAX_HEADERS: usize = 100;
+
+/// How long a connection may stay silent, between or within requests,
+/// before it is dropped.
+pub const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Most connections served at once; further clients wait to be accepted.
+pub const MAX_CONNECTIONS: usize = 64;
+
+/// A parsed request with its body read into memory.
+#[derive(Debug, Clone, Default)]
+pub struct Request {
+    pub method: String,
+    /// Path without the query string, still percent-encoded
+    pub path: String,
+    pub query: Option<String>,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn new(method: &str, target: &str) -> Self {
+        let (path, query) = match target.split_once('?') {
+            Some((path, query)) => (path, Some(query.to_string())),
+            None => (target, None),
+        };
+        Self {
+            method: method.to_string(),
+            path: path.to_string(),
+            query,
+            ..Default::default()
+        }
+    }
+
+    /// Value of the first header called `name`, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Decoded value of query parameter `name`.
+    pub fn query_param(&self, name: &str) -> Option<String> {
+        self.query
+            .as_deref()?
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(n, _)| *n == name)
+            .and_then(|(_, v)| percent_decode(&v.replace('+', " ")))
+    }
+
+    /// Decoded, non-empty path segments. `None` when a segment is not valid
+    /// UTF-8 or would step outside the root (`.`, `..`, embedded slashes).
+    pub fn segments(&self) -> Option<Vec<String>> {
+        self.path
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(|s| {
+                let segment = percent_decode(s)?;
+                let unsafe_segment =
+                    segment == "." || segment == ".." || segment.contains(['/', '\\']);
+                (!unsafe_segment).then_some(segment)
+            })
+            .collect()
+    }
+
+    fn wants_close(&self) -> bool {
+        self.header("connection")
+            .is_some_and(|v| v.eq_ignore_ascii_case("close"))
+    }
+}
+
+/// Response body. Byte and file bodies carry the window that is sent, so
+/// ranges do not copy data.
+#[derive(Debug)]
+pub enum Body {
+    Empty,
+    Bytes {
+        data: Arc<Vec<u8>>,
+        start: usize,
+        len: usize,
+    },
+    File {
+        file: File,
+        start: u64,
+        len: u64,
+    },
+}
+
+impl Body {
+    pub fn len(&self) -> u64 {
+        match self {
+            Body::Empty => 0,
+            Body::Bytes { len, .. } => *len as u64,
+            Body::File { len, .. } => *len,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Body,
+}
+
+impl Response {
+    pub fn new(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Body::Empty,
+        }
+    }
+
+    pub fn bytes(status: u16, content_type: &str, data: impl Into<Arc<Vec<u8>>>) -> Self {
+        let data = data.into();
+        let len = data.len();
+        Self::new(status)
+            .with_header("Content-Type", content_type)
+            .with_body(Body::Bytes {
+                data,
+                start: 0,
+                len,
+            })
+    }
+
+    /// `value` serialized as JSON.
+    pub fn json(status: u16, value: &impl Serialize) -> Self {
+        let body = serde_json::to_vec(value).unwrap_or_default();
+        Self::bytes(status, "application/json", body)
+    }
+
+    /// `{"error": message}`
+    pub fn error(status: u16, message: &str) -> Self {
+        Self::json(status, &serde_json::json!({ "error": message }))
+    }
+
+    /// The whole of `file`.
+    pub fn file(file: File, content_type: &str) -> io::Result<Self> {
+        let len = file.metadata()?.len();
+        Ok(Self::new(200)
+            .with_header("Content-Type", content_type)
+            .with_body(Body::File {
+                file,
+                start: 0,
+                len,
+            }))
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn with_body(mut self, body: Body) -> Self {
+        self.body = body;
+        self
+    }
+
+    /// Narrow a 200 response to the `Range` header `spec`: 206 with the
+    /// requested bytes, 416 when the range lies outside the body, and the
+    /// full body for ranges that are ignored (multiple ranges, other units).
+    pub fn with_range(mut self, spec: Option<&str>) -> Self {
+        self = self.with_header("Accept-Ranges", "bytes");
+        if self.status != 200 {
+            return self;
+        }
+        let size = self.body.len();
+        match spec.and_then(|spec| parse_range(spec, size)) {
+            None => self,
+            Some(Err(())) => {
+                Response::new(416).with_header("Content-Range", &format!("bytes */{}", size))
+            }
+            Some(Ok((first, last))) => {
+                let len = last - first + 1;
+                self.body = match self.body {
+                    Body::Bytes { data, start, .. } => Body::Bytes {
+                        data,
+                        start: start + first as usize,
+                        len: len as usize,
+                    },
+                    Body::File { file, start, .. } => Body::File {
+                        file,
+                        start: start + first,
+                        len,
+                    },
+                    Body::Empty => Body::Empty,
+                };
+                self.status = 206;
+                let range = format!("bytes {}-{}/{}", first, last, size);
+                self.with_header("Content-Range", &range)
+            }
+        }
+    }
+}
+
+/// Standard reason phrase for the status codes used here.
+pub fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        206 => "Partial Content",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        411 => "Length Required",
+        413 => "Payload Too Large",
+        414 => "URI Too Long",
+        416 => "Range Not Satisfiable",
+        422 => "Unprocessable Entity",
+        431 => "Request Header Fields Too Large",
+        500 => "Internal Server Error",
+        501 => "Not Implemented",
+        _ => "",
+    }
+}
+
+/// Read one request, with a body of at most `max_body` bytes. `Ok(None)`
+/// when the client closed the connection; `Err` holds the error response
+/// for a request that cannot be handled. Lines longer than
+/// [`MAX_LINE_LENGTH`] and more than [`MAX_HEADERS`] headers are refused.
+pub fn read_request<R: BufRead>(
+    reader: &mut R,
+    max_body: u64,
+) -> Result<Option<Request>, Response> {
+    let Some(request_line) = read_line(reader, 414)? else {
+        return Ok(None);
+    };
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(Response::error(400, "malformed request line"));
+    };
+    let mut request = Request::new(method, target);
+
+    while let Some(line) = read_line(reader, 431)? {
+        if line.trim().is_empty() {
+            break;
+        }
+        if request.headers.len() == MAX_HEADERS {
+            return Err(Response::error(431, "too many headers"));
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            request
+                .headers
+                .push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    if request.header("transfer-encoding").is_some() {
+        return Err(Response::error(411, "send a Content-Length body"));
+    }
+    let length = match request.header("content-length") {
+        Some(value) => value
+            .parse::<u64>()
+            .map_err(|_| Response::error(400, "invalid Content-Length"))?,
+        None => 0,
+    };
+    if length > max_body {
+        return Err(Response::error(413, "request body too large"));
+    }
+    request.body = vec![0; length as usize];
+    reader.read_exact(&mut request.body).map_err(read_error)?;
+    Ok(Some(request))
+}
+
+/// One line of at most [`MAX_LINE_LENGTH`] bytes, `None` at the end of the
+/// stream. A longer line is refused with `too_long_status`.
+fn read_line<R: BufRead>(reader: &mut R, too_long_status: u16) -> Result<Option<String>, Response> {
+    let mut line = String::new();
+    let limit = MAX_LINE_LENGTH as u64 + 1;
+    let read = reader
+        .by_ref()
+        .take(limit)
+        .read_line(&mut line)
+        .map_err(read_error)?;
+    if read == 0 {
+        return Ok(None);
+    }
+    if line.len() > MAX_LINE_LENGTH {
+        return Err(Response::error(too_long_status, "line too long"));
+    }
+    Ok(Some(line))
+}
+
+/// Error response for a failed read: 408 when the client went quiet.
+fn read_error(error: io::Error) -> Response {
+    match error.kind() {
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => {
+            Response::error(408, "request timed out")
+        }
+        _ => Response::error(400, "malformed request"),
+    }
+}
+
+/// Write `response`; HEAD requests get the headers only.
+pub fn write_response<W: Write>(
+    out: &mut W,
+    response: Response,
+    head_only: bool,
+) -> io::Result<()> {
+    write!(
+        out,
+        "HTTP/1.1 {} {}\r\n",
+        response.status,
+        reason(response.status)
+    )?;
+    for (name, value) in &response.headers {
+        write!(out, "{}: {}\r\n", name, value)?;
+    }
+    write!(out, "Content-Length: {}\r\n\r\n", response.body.len())?;
+    if head_only {
+        return Ok(());
+    }
+    match response.body {
+        Body::Empty => {}
+        Body::Bytes { data, start, len } => out.write_all(&data[start..start + len])?,
+        Body::File {
+            mut file,
+            start,
+            len,
+        } => {
+            file.seek(SeekFrom::Start(start))?;
+            io::copy(&mut file.take(len), out)?;
+        }
+    }
+    Ok(())
+}
+
+/// Accept connections on `listener` forever, answering each request with
+/// `handler`. Request bodies over `max_body` bytes are refused with 413. At
+/// most [`MAX_CONNECTIONS`] connections are served at once.
+pub fn serve<H>(listener: TcpListener, max_body: u64, handler: H) -> io::Result<()>
+where
+    H: Fn(&Request) -> Response + Send + Sync + 'static,
+{
+    let handler = Arc::new(handler);
+    let slots = Arc::new(Slots::new(MAX_CONNECTIONS));
+    loop {
+        // Hold back accepting until a connection slot is free
+        slots.acquire();
+        let stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                slots.release();
+                warn!("Failed to accept connection: {}", e);
+                continue;
+            }
+        };
+        let handler = Arc::clone(&handler);
+        let slots = Arc::clone(&slots);
+        std::thread::spawn(move || {
+            if let Err(e) = serve_connection(stream, max_body, &*handler) {
+                debug!("Connection closed: {}", e);
+            }
+            slots.release();
+        });
+    }
+}
+
+/// Counting semaphore over the connections being served.
+struct Slots {
+    free: Mutex<usize>,
+    released: Condvar,
+}
+
+impl Slots {
+    fn new(count: usize) -> Self {
+        Self {
+            free: Mutex::new(count),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Take a slot, waiting for one to be released if none is free.
+    fn acquire(&self) {
+        let mut free = self.free.lock().unwrap_or_else(|e| e.into_inner());
+        while *free == 0 {
+            free = self.released.wait(free).unwrap_or_else(|e| e.into_inner());
+        }
+        *free -= 1;
+    }
+
+    fn release(&self) {
+        *self.free.lock().unwrap_or_else(|e| e.into_inner()) += 1;
+        self.released.notify_one();
+    }
+}
+
+/// Answer requests on one keep-alive connection until the client leaves or
+/// stays silent for [`READ_TIMEOUT`].
+fn serve_connection<H>(stream: TcpStream, max_body: u64, handler: &H) -> io::Result<()>
+where
+    H: Fn(&Request) -> Response,
+{
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut out = BufWriter::new(stream);
+    loop {
+        let request = match read_request(&mut reader, max_body) {
+            Ok(Some(request)) => request,
+            Ok(None) => return Ok(()),
+            Err(response) => {
+                // The rest of the stream cannot be trusted
+                write_response(&mut out, response, false)?;
+                return out.flush();
+            }
+        };
+        let response = handler(&request);
+        debug!("{} {} {}", request.method, request.path, response.status);
+        write_response(&mut out, response, request.method == "HEAD")?;
+        out.flush()?;
+        if request.wants_close() {
+            return Ok(());
+        }
+    }
+}
+
+/// Serve files below `root` for GET and HEAD, with `index.html` for
+/// directories and byte ranges.
+pub fn static_file(root: &Path, request: &Request) -> Response {
+    if request.method != "GET" && request.method != "HEAD" {
+        return Response::error(405, "only GET and HEAD are supported");
+    }
+    let Some(segments) = request.segments() else {
+        return Response::error(404, "not found");
+    };
+    let mut path: PathBuf = root.to_path_buf();
+    path.extend(segments);
+    if path.is_dir() {
+        path.push("index.html");
+    }
+    match File::open(&path).and_then(|file| Response::file(file, content_type(&path))) {
+        Ok(response) => response
+            .with_header("Cache-Control", "no-cache")
+            .with_range(request.header("range")),
+        Err(_) => Response::error(404, "not found"),
+    }
+}
+
+/// Inclusive byte range of a single-range `Range` header over a body of
+/// `size` bytes. `None` when the header should be ignored, `Err` when it
+/// cannot be satisfied.
+pub fn parse_range(spec: &str, size: u64) -> Option<Result<(u64, u64), ()>> {
+    let spec = spec.trim().strip_prefix("bytes=")?;
+    if spec.contains(',') {
+        return None;
+    }
+    let (first, last) = spec.split_once('-')?;
+    let last_byte = size.checked_sub(1);
+    let (first, last) = match (first.trim(), last.trim()) {
+        // Suffix range: the final N bytes
+        ("", suffix) => {
+            let suffix: u64 = suffix.parse().ok()?;
+            if suffix == 0 {
+                return Some(Err(()));
+            }
+            (size.saturating_sub(suffix), last_byte)
+        }
+        (first, "") => (first.parse().ok()?, last_byte),
+        (first, last) => {
+            let last: u64 = last.parse().ok()?;
+            (first.parse().ok()?, last_byte.map(|end| last.min(end)))
+        }
+    };
+    Some(match last {
+        Some(last) if first <= last => Ok((first, last)),
+        _ => Err(()),
+    })
+}
+
+/// MIME type for the files a web export contains.
+pub fn content_type(path: &Path) -> &'static str {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default();
+    match ext.to_ascii_lowercase().as_str() {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "js" | "mjs" => "text/javascript; charset=utf-8",
+        "json" => "application/json",
+        "css" => "text/css",
+        "png" => "image/png",
+        "wasm" => "application/wasm",
+        "glb" => "model/gltf-binary",
+        _ => "application/octet-stream",
+    }
+}
+
+fn percent_decode(text: &str) -> Option<String> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn written(response: Response, head_only: bool) -> String {
+        let mut out = Vec::new();
+        write_response(&mut out, response, head_only).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-15", 100), Some(Ok((0, 15))));
+        assert_eq!(parse_range("bytes=90-", 100), Some(Ok((90, 99))));
+        assert_eq!(parse_range("bytes=-10", 100), Some(Ok((90, 99))));
+        assert_eq!(parse_range("bytes=-500", 100), Some(Ok((0, 99))));
+        // End clamped to the body
+        assert_eq!(parse_range("bytes=50-500", 100), Some(Ok((50, 99))));
+        assert_eq!(parse_range("bytes=100-", 100), Some(Err(())));
+        assert_eq!(parse_range("bytes=20-10", 100), Some(Err(())));
+        assert_eq!(parse_range("bytes=0-", 0), Some(Err(())));
+        assert_eq!(parse_range("bytes=0-1,5-6", 100), None);
+        assert_eq!(parse_range("items=0-1", 100), None);
+    }
+
+    #[test]
+    fn test_read_request() {
+        let raw = b"POST /api/models?name=Tower%20A HTTP/1.1\r\nHost: x\r\nContent-Length: 5\r\n\r\nhelloGET / HTTP/1.1\r\n\r\n";
+        let mut reader = &raw[..];
+        let request = read_request(&mut reader, 1024).unwrap().unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/api/models");
+        assert_eq!(request.query_param("name").as_deref(), Some("Tower A"));
+        assert_eq!(request.header("content-length"), Some("5"));
+        assert_eq!(request.body, b"hello");
+
+        // Keep-alive: the next request follows on the same stream
+        let next = read_request(&mut reader, 1024).unwrap().unwrap();
+        assert_eq!(next.method, "GET");
+        assert!(read_request(&mut reader, 1024).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_read_request_limits() {
+        let mut reader = &b"POST / HTTP/1.1\r\nContent-Length: 100\r\n\r\n"[..];
+        assert_eq!(read_request(&mut reader, 10).unwrap_err().status, 413);
+
+        let mut reader = &b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n"[..];
+        assert_eq!(read_request(&mut reader, 10).unwrap_err().status, 411);
+
+        let long_target = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(MAX_LINE_LENGTH));
+        let mut reader = long_target.as_bytes();
+        assert_eq!(read_request(&mut reader, 10).unwrap_err().status, 414);
+
+        let long_header = format!(
+            "GET / HTTP/1.1\r\nX: {}\r\n\r\n",
+            "a".repeat(MAX_LINE_LENGTH)
+        );
+        let mut reader = long_header.as_bytes();
+        assert_eq!(read_request(&mut reader, 10).unwrap_err().status, 431);
+
+        let many_headers = format!(
+            "GET / HTTP/1.1\r\n{}\r\n",
+            "X: y\r\n".repeat(MAX_HEADERS + 1)
+        );
+        let mut reader = many_headers.as_bytes();
+        assert_eq!(read_request(&mut reader, 10).unwrap_err().status, 431);
+        let enough_headers = format!("GET / HTTP/1.1\r\n{}\r\n", "X: y\r\n".repeat(MAX_HEADERS));
+        let mut reader = enough_headers.as_bytes();
+        assert!(read_request(&mut reader, 10).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_read_request_timeout() {
+        struct Silent;
+        impl Read for Silent {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::ErrorKind::WouldBlock.into())
+            }
+        }
+        let mut reader = BufReader::new(Silent);
+        assert_eq!(read_request(&mut reader, 10).unwrap_err().status, 408);
+    }
+
+    #[test]
+    fn test_slots_bound_connections() {
+        let slots = Arc::new(Slots::new(2));
+        slots.acquire();
+        slots.acquire();
+        let waiter = {
+            let slots = Arc::clone(&slots);
+            std::thread::spawn(move || slots.acquire())
+        };
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(!waiter.is_finished());
+        slots.release();
+        waiter.join().unwrap();
+        assert_eq!(*slots.free.lock().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_segments_reject_traversal() {
+        let request = Request::new("GET", "/a/b%20c/index.html");
+        assert_eq!(request.segments().unwrap(), ["a", "b c", "index.html"]);
+        assert!(Request::new("GET", "/../secret").segments().is_none());
+        assert!(Request::new("GET", "/%2e%2e/secret").segments().is_none());
+        assert!(Request::new("GET", "/a%2fb").segments().is_none());
+    }
+
+    #[test]
+    fn test_ranged_bytes_response() {
+        let data = (0u8..10).collect::<Vec<_>>();
+        let response = Response::bytes(200, "application/octet-stream", data.clone())
+            .with_range(Some("bytes=2-4"));
+        assert_eq!(response.status, 206);
+        let mut out = Vec::new();
+        write_response(&mut out, response, false).unwrap();
+        assert!(out.ends_with(&[2, 3, 4]));
+        let text = String::from_utf8_lossy(&out);
+        assert!(text.contains("Content-Range: bytes 2-4/10\r\n"));
+        assert!(text.contains("Content-Length: 3\r\n"));
+
+        let response = Response::bytes(200, "text/plain", data).with_range(Some("bytes=10-"));
+        let text = written(response, false);
+        assert!(text.starts_with("HTTP/1.1 416 Range Not Satisfiable\r\n"));
+        assert!(text.contains("Content-Range: bytes */10\r\n"));
+    }
+
+    #[test]
+    fn test_static_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("index.html"), "<html></html>").unwrap();
+        std::fs::write(dir.path().join("mesh.bin"), [1u8, 2, 3, 4]).unwrap();
+
+        let text = written(static_file(dir.path(), &Request::new("GET", "/")), false);
+        assert!(text.contains("Content-Type: text/html; charset=utf-8\r\n"));
+        assert!(text.ends_with("<html></html>"));
+
+        let mut request = Request::new("GET", "/mesh.bin");
+        request.headers.push(("Range".into(), "bytes=-2".into()));
+        let response = static_file(dir.path(), &request);
+        assert_eq!(response.status, 206);
+        assert_eq!(response.body.len(), 2);
+
+        let head = written(
+            static_file(dir.path(), &Request::new("HEAD", "/mesh.bin")),
+            true,
+        );
+        assert!(head.ends_with("Content-Length: 4\r\n\r\n"));
+
+        assert_eq!(
+            static_file(dir.path(), &Request::new("GET", "/missing")).status,
+            404
+        );
+        assert_eq!(
+            static_file(dir.path(), &Request::new("GET", "/../x")).status,
+            404
+        );
+        assert_eq!(
+            static_file(dir.path(), &Request::new("PUT", "/")).status,
+            405
+        );
+    }
+}
@@ -0,0 +1,8 @@
+//! HTTP front end for CSTEngine: hosts web viewer exports and exposes IFC
+//! conversion as a REST API for web frontends.
+
+pub mod api;
+pub mod http;
+
+pub use api::{ApiServer, ChunkInfo, ElementInfo, Model, SceneManifest};
+pub use http::{serve, static_file, Request, Response};
@@ -0,0 +1,279 @@
+//! Binary space partitioning of convex polygons, the workhorse behind the
+//! boolean operators. The approach follows the classic csg.js algorithm:
+//! each solid is turned into a BSP tree and polygons of one solid are
+//! clipped against the tree of the other.
+
+use cst_math::plane::newell_normal;
+use cst_math::{Point3, Vector3};
+
+/// A planar convex polygon with a cached supporting plane.
+#[derive(Debug, Clone)]
+pub(crate) struct Polygon {
+    pub vertices: Vec<Point3>,
+    pub plane: SplitPlane,
+}
+
+impl Polygon {
+    /// Build a polygon from its vertices; `None` when they are degenerate.
+    pub fn new(vertices: Vec<Point3>) -> Option<Self> {
+        let plane = SplitPlane::from_polygon(&vertices)?;
+        Some(Self { vertices, plane })
+    }
+
+    fn flip(&mut self) {
+        self.vertices.reverse();
+        self.plane.flip();
+    }
+}
+
+/// Plane in Hessian normal form: `normal · p = w`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SplitPlane {
+    pub normal: Vector3,
+    pub w: f64,
+}
+
+const COPLANAR: u8 = 0;
+const FRONT: u8 = 1;
+const BACK: u8 = 2;
+const SPANNING: u8 = 3;
+
+impl SplitPlane {
+    /// Newell-normal plane through a polygon.
+    fn from_polygon(vertices: &[Point3]) -> Option<Self> {
+        if vertices.len() < 3 {
+            return None;
+        }
+        let normal = newell_normal(vertices);
+        let len = normal.length();
+        if len < 1e-14 {
+            return None;
+        }
+        let normal = normal / len;
+        let centroid = vertices.iter().copied().sum::<Point3>() / vertices.len() as f64;
+        Some(Self {
+            normal,
+            w: normal.dot(centroid),
+        })
+    }
+
+    fn flip(&mut self) {
+        self.normal = -self.normal;
+        self.w = -self.w;
+    }
+
+    /// Split `polygon` by this plane, sorting the pieces into the four
+    /// output lists.
+    fn split_polygon(
+        &self,
+        polygon: &Polygon,
+        epsilon: f64,
+        coplanar_front: &mut Vec<Polygon>,
+        coplanar_back: &mut Vec<Polygon>,
+        front: &mut Vec<Polygon>,
+        back: &mut Vec<Polygon>,
+    ) {
+        let mut polygon_type = COPLANAR;
+        let types: Vec<u8> = polygon
+            .vertices
+            .iter()
+            .map(|v| {
+                let t = self.normal.dot(*v) - self.w;
+                let ty = if t < -epsilon {
+                    BACK
+                } else if t > epsilon {
+                    FRONT
+                } else {
+                    COPLANAR
+                };
+                polygon_type |= ty;
+                ty
+            })
+            .collect();
+
+        match polygon_type {
+            COPLANAR => {
+                if self.normal.dot(polygon.plane.normal) > 0.0 {
+                    coplanar_front.push(polygon.clone());
+                } else {
+                    coplanar_back.push(polygon.clone());
+                }
+            }
+            FRONT => front.push(polygon.clone()),
+            BACK => back.push(polygon.clone()),
+            _ => {
+                let n = polygon.vertices.len();
+                let mut f = Vec::with_capacity(n + 1);
+                let mut b = Vec::with_capacity(n + 1);
+                for i in 0..n {
+                    let j = (i + 1) % n;
+                    let (ti, tj) = (types[i], types[j]);
+                    let (vi, vj) = (polygon.vertices[i], polygon.vertices[j]);
+                    if ti != BACK {
+                        f.push(vi);
+                    }
+                    if ti != FRONT {
+                        b.push(vi);
+                    }
+                    if (ti | tj) == SPANNING {
+                        let t = (self.w - self.normal.dot(vi)) / self.normal.dot(vj - vi);
+                        let v = vi.lerp(vj, t);
+                        f.push(v);
+                        b.push(v);
+                    }
+                }
+                if f.len() >= 3 {
+                    front.push(Polygon {
+                        vertices: f,
+                        plane: polygon.plane,
+                    });
+                }
+                if b.len() >= 3 {
+                    back.push(Polygon {
+                        vertices: b,
+                        plane: polygon.plane,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// A node of the BSP tree. Polygons coplanar with the node plane are stored
+/// on the node itself.
+#[derive(Debug, Default)]
+pub(crate) struct BspNode {
+    plane: Option<SplitPlane>,
+    front: Option<Box<BspNode>>,
+    back: Option<Box<BspNode>>,
+    polygons: Vec<Polygon>,
+    epsilon: f64,
+}
+
+impl BspNode {
+    pub fn new(polygons: Vec<Polygon>, epsilon: f64) -> Self {
+        let mut node = Self {
+            epsilon,
+            ..Default::default()
+        };
+        node.build(polygons);
+        node
+    }
+
+    /// Convert solid space to empty space and vice versa.
+    pub fn invert(&mut self) {
+        for p in &mut self.polygons {
+            p.flip();
+        }
+        if let Some(plane) = &mut self.plane {
+            plane.flip();
+        }
+        if let Some(front) = &mut self.front {
+            front.invert();
+        }
+        if let Some(back) = &mut self.back {
+            back.invert();
+        }
+        std::mem::swap(&mut self.front, &mut self.back);
+    }
+
+    /// Remove the parts of `polygons` that lie inside this tree.
+    fn clip_polygons(&self, polygons: Vec<Polygon>) -> Vec<Polygon> {
+        let Some(plane) = self.plane else {
+            return polygons;
+        };
+        let mut front = Vec::new();
+        let mut back = Vec::new();
+        for p in &polygons {
+            let mut coplanar_front = Vec::new();
+            let mut coplanar_back = Vec::new();
+            plane.split_polygon(
+                p,
+                self.epsilon,
+                &mut coplanar_front,
+                &mut coplanar_back,
+                &mut front,
+                &mut back,
+            );
+            front.append(&mut coplanar_front);
+            back.append(&mut coplanar_back);
+        }
+        let mut front = match &self.front {
+            Some(node) => node.clip_polygons(front),
+            None => front,
+        };
+        let back = match &self.back {
+            Some(node) => node.clip_polygons(back),
+            None => Vec::new(),
+        };
+        front.extend(back);
+        front
+    }
+
+    /// Remove the parts of this tree's polygons that lie inside `other`.
+    pub fn clip_to(&mut self, other: &BspNode) {
+        self.polygons = other.clip_polygons(std::mem::take(&mut self.polygons));
+        if let Some(front) = &mut self.front {
+            front.clip_to(other);
+        }
+        if let Some(back) = &mut self.back {
+            back.clip_to(other);
+        }
+    }
+
+    pub fn all_polygons(&self) -> Vec<Polygon> {
+        let mut out = self.polygons.clone();
+        if let Some(front) = &self.front {
+            out.extend(front.all_polygons());
+        }
+        if let Some(back) = &self.back {
+            out.extend(back.all_polygons());
+        }
+        out
+    }
+
+    /// Insert polygons into the tree, splitting them where necessary.
+    pub fn build(&mut self, polygons: Vec<Polygon>) {
+        if polygons.is_empty() {
+            return;
+        }
+        let plane = *self.plane.get_or_insert(polygons[0].plane);
+        let mut front = Vec::new();
+        let mut back = Vec::new();
+        for p in &polygons {
+            let mut coplanar_front = Vec::new();
+            let mut coplanar_back = Vec::new();
+            plane.split_polygon(
+                p,
+                self.epsilon,
+                &mut coplanar_front,
+                &mut coplanar_back,
+                &mut front,
+                &mut back,
+            );
+            self.polygons.append(&mut coplanar_front);
+            self.polygons.append(&mut coplanar_back);
+        }
+        let epsilon = self.epsilon;
+        if !front.is_empty() {
+            self.front
+                .get_or_insert_with(|| {
+                    Box::new(BspNode {
+                        epsilon,
+                        ..Default::default()
+                    })
+                })
+                .build(front);
+        }
+        if !back.is_empty() {
+            self.back
+                .get_or_insert_with(|| {
+                    Box::new(BspNode {
+                        epsilon,
+                        ..Default::default()
+                    })
+                })
+                .build(back);
+        }
+    }
+}
@@ -0,0 +1,339 @@
+//! Mesh CSG (union, difference, intersection) of closed half-edge solids
+//! with planar faces.
+//!
+//! This is polygon-level CSG in the style of csg.js, not an exact B-rep
+//! boolean: faces are cut into convex polygons and clipped against a BSP
+//! tree of the other solid, without computing face/face intersection
+//! curves. The surviving fragments are welded back into a half-edge
+//! [`Mesh`] (T-junctions introduced by the splitting are resolved so the
+//! result stays closed), and coplanar fragments of the same face are merged
+//! again. Faces are treated as flat polygons; surface and curve references
+//! are not carried over.
+
+mod bsp;
+
+use std::collections::{HashMap, HashSet};
+
+use cst_core::error::{CstError, Result};
+use cst_core::{Tolerance, ToleranceContext};
+use cst_math::plane::newell_normal;
+use cst_math::spatial::PointIndex;
+use cst_math::{Point3, Vector3};
+
+use crate::halfedge::{EdgeId, Mesh, VertexId};
+use bsp::{BspNode, Polygon};
+
+/// The boolean operation to apply in [`bsp_boolean`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BooleanOp {
+    /// Everything inside either solid.
+    Union,
+    /// Everything inside the first solid but outside the second.
+    Difference,
+    /// Everything inside both solids.
+    Intersection,
+}
+
+/// Combine two closed, outward-oriented solids.
+///
+/// `tolerance.linear` is used both for classifying points against splitting
+/// planes and for welding the resulting vertices, `tolerance.angular` for
+/// telling whether fragments are coplanar; pass a [`Tolerance`] or a
+/// [`ToleranceContext`]. Faces with inner loops are triangulated first, so
+/// the holes come out as fragments around an opening rather than as inner
+/// loops.
+pub fn bsp_boolean(
+    a: &Mesh,
+    b: &Mesh,
+    op: BooleanOp,
+    tolerance: impl Into<ToleranceContext>,
+) -> Result<Mesh> {
+    let tolerance = tolerance.into();
+    let eps = tolerance.linear;
+    let mut na = BspNode::new(mesh_to_polygons(a)?, eps);
+    let mut nb = BspNode::new(mesh_to_polygons(b)?, eps);
+
+    match op {
+        BooleanOp::Union => {
+            na.clip_to(&nb);
+            nb.clip_to(&na);
+            nb.invert();
+            nb.clip_to(&na);
+            nb.invert();
+            na.build(nb.all_polygons());
+        }
+        BooleanOp::Difference => {
+            na.invert();
+            na.clip_to(&nb);
+            nb.clip_to(&na);
+            nb.invert();
+            nb.clip_to(&na);
+            nb.invert();
+            na.build(nb.all_polygons());
+            na.invert();
+        }
+        BooleanOp::Intersection => {
+            na.invert();
+            nb.clip_to(&na);
+            nb.invert();
+            na.clip_to(&nb);
+            nb.clip_to(&na);
+            na.build(nb.all_polygons());
+            na.invert();
+        }
+    }
+
+    polygons_to_mesh(na.all_polygons(), eps, tolerance.angular)
+}
+
+impl Mesh {
+    /// Boolean union with another solid at default tolerance.
+    pub fn union(&self, other: &Mesh) -> Result<Mesh> {
+        bsp_boolean(self, other, BooleanOp::Union, Tolerance::default())
+    }
+
+    /// Boolean difference `self - other` at default tolerance.
+    pub fn difference(&self, other: &Mesh) -> Result<Mesh> {
+        bsp_boolean(self, other, BooleanOp::Difference, Tolerance::default())
+    }
+
+    /// Boolean intersection with another solid at default tolerance.
+    pub fn intersection(&self, other: &Mesh) -> Result<Mesh> {
+        bsp_boolean(self, other, BooleanOp::Intersection, Tolerance::default())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Mesh -> polygons
+// ---------------------------------------------------------------------------
+
+fn mesh_to_polygons(mesh: &Mesh) -> Result<Vec<Polygon>> {
+    let mut polygons = Vec::with_capacity(mesh.faces.len());
+    for (face_id, face) in &mesh.faces {
+        let position = |v: VertexId| mesh.vertices[v].position;
+        let outer: Vec<Point3> = mesh
+            .face_vertices(face_id)
+            .into_iter()
+            .flatten()
+            .map(position)
+            .collect();
+        let pieces = if face.inner_loops.is_empty() {
+            convex_pieces(outer)
+        } else {
+            let holes: Vec<Vec<Point3>> = face
+                .inner_loops
+                .iter()
+                .map(|&l| mesh.loop_vertices(l).into_iter().map(position).collect())
+                .collect();
+            triangulate_with_holes(outer, &holes)?
+        };
+        for piece in pieces {
+            polygons.extend(Polygon::new(piece));
+        }
+    }
+    Ok(polygons)
+}
+
+/// Triangulate a planar face with holes, winding every triangle like
+/// `outer`.
+fn triangulate_with_holes(outer: Vec<Point3>, holes: &[Vec<Point3>]) -> Result<Vec<Vec<Point3>>> {
+    let normal = newell_normal(&outer).normalize_or_zero();
+    let u = normal.any_orthonormal_vector();
+    let v = normal.cross(u);
+
+    let mut points = outer;
+    let mut hole_starts = Vec::with_capacity(holes.len());
+    for hole in holes {
+        hole_starts.push(points.len());
+        points.extend_from_slice(hole);
+    }
+    let coords: Vec<f64> = points.iter().flat_map(|p| [p.dot(u), p.dot(v)]).collect();
+    let indices = match earcutr::earcut(&coords, &hole_starts, 2) {
+        Ok(indices) if !indices.is_empty() => indices,
+        _ => {
+            return Err(CstError::InvalidOperation(format!(
+                "cannot triangulate a face with {} holes",
+                holes.len()
+            )))
+        }
+    };
+    Ok(indices
+        .chunks_exact(3)
+        .map(|tri| {
+            let (a, b, c) = (points[tri[0]], points[tri[1]], points[tri[2]]);
+            if (b - a).cross(c - a).dot(normal) < 0.0 {
+                vec![a, c, b]
+            } else {
+                vec![a, b, c]
+            }
+        })
+        .collect())
+}
+
+/// Split a simple planar polygon into convex pieces (ear clipping when the
+/// polygon is not already convex). BSP splitting requires convex input.
+fn convex_pieces(points: Vec<Point3>) -> Vec<Vec<Point3>> {
+    let n = points.len();
+    if n <= 3 {
+        return vec![points];
+    }
+    let normal = newell_normal(&points).normalize_or_zero();
+    let turn = |a: Point3, b: Point3, c: Point3| (b - a).cross(c - b).dot(normal);
+    let convex = (0..n).all(|i| turn(points[i], points[(i + 1) % n], points[(i + 2) % n]) >= 0.0);
+    if convex {
+        return vec![points];
+    }
+
+    let mut remaining: Vec<usize> = (0..n).collect();
+    let mut triangles = Vec::with_capacity(n - 2);
+    let mut guard = 0;
+    while remaining.len() > 3 && guard < n * n {
+        guard += 1;
+        let m = remaining.len();
+        let ear = (0..m).find(|&i| {
+            let (a, b, c) = (
+                points[remaining[(i + m - 1) % m]],
+                points[remaining[i]],
+                points[remaining[(i + 1) % m]],
+            );
+            if turn(a, b, c) <= 0.0 {
+                return false;
+            }
+            // No other vertex may lie inside the candidate ear.
+            remaining.iter().all(|&k| {
+                let p = points[k];
+                if p == a || p == b || p == c {
+                    return true;
+                }
+                !(turn(a, b, p) >= 0.0 && turn(b, c, p) >= 0.0 && turn(c, a, p) >= 0.0)
+            })
+        });
+        let Some(i) = ear else { break };
+        triangles.push(vec![
+            points[remaining[(i + m - 1) % m]],
+            points[remaining[i]],
+            points[remaining[(i + 1) % m]],
+        ]);
+        remaining.remove(i);
+    }
+    triangles.push(remaining.iter().map(|&k| points[k]).collect());
+    triangles
+}
+
+// ---------------------------------------------------------------------------
+// Polygons -> mesh
+// ---------------------------------------------------------------------------
+
+fn polygons_to_mesh(polygons: Vec<Polygon>, eps: f64, angular: f64) -> Result<Mesh> {
+    let mut mesh = Mesh::new();
+    let mut welded: Vec<VertexId> = Vec::new();
+    let mut index = PointIndex::new(eps.max(1e-12) * 4.0);
+
+    // Weld polygon corners into shared vertices.
+    let mut outlines: Vec<(Vec<usize>, Vector3)> = Vec::with_capacity(polygons.len());
+    for polygon in &polygons {
+        let mut outline: Vec<usize> = polygon
+            .vertices
+            .iter()
+            .map(|&p| match index.find_within(p, eps, |_| true) {
+                Some(k) => k,
+                None => {
+                    welded.push(mesh.add_vertex(p));
+                    index.insert(p)
+                }
+            })
+            .collect();
+        outline.dedup();
+        while outline.len() > 1 && outline.first() == outline.last() {
+            outline.pop();
+        }
+        if outline.len() >= 3 {
+            outlines.push((outline, polygon.plane.normal));
+        }
+    }
+
+    // Resolve T-junctions: insert vertices that lie on the interior of a
+    // polygon edge, so adjacent fragments share their edges exactly.
+    let positions: Vec<Point3> = welded.iter().map(|&v| mesh.vertices[v].position).collect();
+    for (outline, _) in &mut outlines {
+        let mut refined = Vec::with_capacity(outline.len());
+        for i in 0..outline.len() {
+            let (a, b) = (outline[i], outline[(i + 1) % outline.len()]);
+            refined.push(a);
+            let (pa, pb) = (positions[a], positions[b]);
+            let dir = pb - pa;
+            let len_sq = dir.length_squared();
+            if len_sq < eps * eps {
+                continue;
+            }
+            let mut on_edge: Vec<(f64, usize)> = positions
+                .iter()
+                .enumerate()
+                .filter(|&(k, _)| k != a && k != b)
+                .filter_map(|(k, &p)| {
+                    let t = (p - pa).dot(dir) / len_sq;
+                    if t <= 0.0 || t >= 1.0 {
+                        return None;
+                    }
+                    let dist_sq = (pa + dir * t).distance_squared(p);
+                    (dist_sq < eps * eps).then_some((t, k))
+                })
+                .collect();
+            on_edge.sort_by(|x, y| x.0.total_cmp(&y.0));
+            refined.extend(on_edge.into_iter().map(|(_, k)| k));
+        }
+        *outline = refined;
+    }
+
+    let mut normals = HashMap::new();
+    for (outline, normal) in &outlines {
+        let verts: Vec<VertexId> = outline.iter().map(|&k| welded[k]).collect();
+        let face = mesh
+            .make_face(&verts)
+            .map_err(|e| CstError::Topology(format!("Boolean result is not manifold: {e}")))?;
+        normals.insert(face, *normal);
+    }
+
+    merge_coplanar_faces(&mut mesh, &normals, angular)?;
+
+    // Corners of fragments that collapsed during welding stay unused.
+    mesh.vertices.retain(|_, v| v.halfedge.is_some());
+    Ok(mesh)
+}
+
+/// Dissolve edges between fragments that came from coplanar faces, i.e.
+/// whose normals are at most `angular` radians apart. Fragments touching
+/// anywhere but along the edge are kept apart, as merging them would pinch
+/// the face.
+fn merge_coplanar_faces(
+    mesh: &mut Mesh,
+    normals: &HashMap<crate::halfedge::FaceId, Vector3>,
+    angular: f64,
+) -> Result<()> {
+    let edges: Vec<EdgeId> = mesh.edges.keys().collect();
+    for edge_id in edges {
+        if !mesh.edges.contains_key(edge_id) {
+            continue;
+        }
+        let (Some(fa), Some(fb)) = mesh.edge_faces(edge_id) else {
+            continue;
+        };
+        let (Some(na), Some(nb)) = (normals.get(&fa), normals.get(&fb)) else {
+            continue;
+        };
+        if fa == fb || na.dot(*nb) <= 0.0 || na.cross(*nb).length() > angular.sin() {
+            continue;
+        }
+        let corners: HashSet<VertexId> = mesh.face_vertices(fa).into_iter().flatten().collect();
+        let shared = mesh
+            .face_vertices(fb)
+            .into_iter()
+            .flatten()
+            .filter(|v| corners.contains(v))
+            .count();
+        if shared == 2 {
+            mesh.dissolve_edge(edge_id)?;
+        }
+    }
+    Ok(())
+}
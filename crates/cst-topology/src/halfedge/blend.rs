@@ -0,0 +1,293 @@
+use cst_core::error::{CstError, Result};
+use cst_math::plane::newell_normal;
+use cst_math::{Point3, Vector3};
+
+use super::mesh::Mesh;
+use super::types::*;
+
+// --- Edge blends ---
+//
+// Chamfers and fillets replace a convex edge between two planar faces by a
+// strip of new faces. Both end vertices must have exactly three faces around
+// them (as on boxes and extrusions): the end faces are then cut along the
+// blend profile, and the neighbouring edges are shortened so that every face
+// stays planar.
+
+impl Mesh {
+    /// Cut off a convex edge with a flat chamfer face, returning that face.
+    ///
+    /// `distance` is measured perpendicular to the edge inside each of the
+    /// two adjacent faces.
+    pub fn chamfer_edge(&mut self, edge_id: EdgeId, distance: f64) -> Result<FaceId> {
+        if !(distance > 0.0 && distance.is_finite()) {
+            return Err(CstError::InvalidOperation(
+                "Chamfer distance must be positive".into(),
+            ));
+        }
+        let faces = self.blend_edge(edge_id, |w1, w2| Some(vec![w1 * distance, w2 * distance]))?;
+        Ok(faces[0])
+    }
+
+    /// Round a convex edge with a constant-radius fillet approximated by
+    /// `segments` flat strips, returning the strip faces in order from the
+    /// `halfedge_a` face to the `halfedge_b` face.
+    pub fn fillet_edge(
+        &mut self,
+        edge_id: EdgeId,
+        radius: f64,
+        segments: usize,
+    ) -> Result<Vec<FaceId>> {
+        if !(radius > 0.0 && radius.is_finite()) || segments == 0 {
+            return Err(CstError::InvalidOperation(
+                "Fillet needs a positive radius and at least one segment".into(),
+            ));
+        }
+        self.blend_edge(edge_id, |w1, w2| {
+            // Interior angle between the faces; the arc is tangent to both.
+            let alpha = w1.angle_between(w2);
+            let setback = radius / (alpha / 2.0).tan();
+            let center = (w1 + w2).normalize_or_zero() * (radius / (alpha / 2.0).sin());
+            let start = w1 * setback - center;
+            let end = w2 * setback - center;
+            let sweep = start.angle_between(end);
+            let u = start.normalize_or_zero();
+            let v = (end - u * end.dot(u)).normalize_or_zero();
+            Some(
+                (0..=segments)
+                    .map(|k| {
+                        let phi = sweep * k as f64 / segments as f64;
+                        center + (u * phi.cos() + v * phi.sin()) * radius
+                    })
+                    .collect(),
+            )
+        })
+    }
+
+    /// Replace an edge by a strip of faces following a profile.
+    ///
+    /// `profile(w1, w2)` returns offsets from the edge end, going from the
+    /// `halfedge_a` face to the `halfedge_b` face, given the unit in-face
+    /// directions perpendicular to the edge (into those two faces). The
+    /// offsets are moved along the edge onto the end faces.
+    fn blend_edge<F>(&mut self, edge_id: EdgeId, profile: F) -> Result<Vec<FaceId>>
+    where
+        F: Fn(Vector3, Vector3) -> Option<Vec<Vector3>>,
+    {
+        let edge = *self
+            .edges
+            .get(edge_id)
+            .ok_or_else(|| CstError::NotFound("Edge not found".into()))?;
+        let he_ab = edge.halfedge_a;
+        let he_ba = edge.halfedge_b;
+        let (Some(face_1), Some(face_2)) = self.edge_faces(edge_id) else {
+            return Err(CstError::Topology("Cannot blend a boundary edge".into()));
+        };
+
+        // Corners around the edge: F1 runs x -> a -> b -> y, F2 runs
+        // w -> b -> a -> z; the end faces F3 (at a) and F4 (at b) hold the
+        // reversed neighbouring edges.
+        let a = self.halfedges[he_ab].origin;
+        let b = self.halfedges[he_ba].origin;
+        let neighbour = |he: Option<HalfEdgeId>, incoming: bool| -> Option<(VertexId, FaceId)> {
+            let he = self.halfedges.get(he?)?;
+            let twin = self.halfedges.get(he.twin?)?;
+            let other = if incoming { he.origin } else { twin.origin };
+            Some((other, twin.face?))
+        };
+        let h = &self.halfedges;
+        let corners = (
+            neighbour(h[he_ab].prev, true),
+            neighbour(h[he_ab].next, false),
+            neighbour(h[he_ba].prev, true),
+            neighbour(h[he_ba].next, false),
+        );
+        let (Some((x, face_3)), Some((y, face_4)), Some((w, face_4b)), Some((z, face_3b))) =
+            corners
+        else {
+            return Err(CstError::Topology(
+                "Blended edge must be surrounded by faces".into(),
+            ));
+        };
+        let involved = [face_1, face_2, face_3, face_4];
+        let distinct = (0..4).all(|i| (i + 1..4).all(|j| involved[i] != involved[j]));
+        if face_3 != face_3b
+            || face_4 != face_4b
+            || !distinct
+            || self.vertex_valence(a) != 3
+            || self.vertex_valence(b) != 3
+        {
+            return Err(CstError::InvalidOperation(
+                "Blends require both edge ends to have exactly three faces".into(),
+            ));
+        }
+        if involved
+            .iter()
+            .any(|&f| !self.faces[f].inner_loops.is_empty())
+        {
+            return Err(CstError::InvalidOperation(
+                "Cannot blend next to faces with inner loops".into(),
+            ));
+        }
+
+        let pos = |v: VertexId| self.vertices[v].position;
+        let tangent = (pos(b) - pos(a)).normalize_or_zero();
+        let across = |p: Point3| {
+            let d = p - pos(a);
+            (d - tangent * d.dot(tangent)).normalize_or_zero()
+        };
+        let (w1, w2) = (across(pos(x)), across(pos(z)));
+        let normal_1 = self.face_normal(face_1);
+        if normal_1.dot(w2) >= -1e-12 || w1 == Vector3::ZERO || w2 == Vector3::ZERO {
+            return Err(CstError::InvalidOperation(
+                "Only convex edges can be blended".into(),
+            ));
+        }
+        let offsets = profile(w1, w2)
+            .filter(|o| o.len() >= 2)
+            .ok_or_else(|| CstError::Geometry("Degenerate blend profile".into()))?;
+
+        // Cross-sections at both ends, slid along the edge onto the end faces.
+        let end_profile = |origin: Point3, end_face: FaceId| -> Option<Vec<Point3>> {
+            let n = self.face_normal(end_face);
+            let denom = tangent.dot(n);
+            if denom.abs() < 1e-12 {
+                return None;
+            }
+            Some(
+                offsets
+                    .iter()
+                    .map(|&o| {
+                        let p = origin + o;
+                        p + tangent * ((origin - p).dot(n) / denom)
+                    })
+                    .collect(),
+            )
+        };
+        let (Some(at_a), Some(at_b)) = (end_profile(pos(a), face_3), end_profile(pos(b), face_4))
+        else {
+            return Err(CstError::Geometry("Edge is parallel to an end face".into()));
+        };
+
+        // The profile ends must stay inside the neighbouring edges.
+        let last = offsets.len() - 1;
+        let fits = |p: Point3, from: VertexId, to: VertexId| {
+            let along = pos(to) - pos(from);
+            let t = (p - pos(from)).dot(along) / along.length_squared();
+            t > 1e-9 && t < 1.0 - 1e-9
+        };
+        if !(fits(at_a[0], a, x)
+            && fits(at_a[last], a, z)
+            && fits(at_b[0], b, y)
+            && fits(at_b[last], b, w))
+        {
+            return Err(CstError::InvalidOperation(
+                "Blend is too large for the neighbouring edges".into(),
+            ));
+        }
+
+        // Outlines starting at the corners being replaced.
+        let f1 = self.loop_vertices_from(Some(he_ab)); // [a, b, y, .., x]
+        let f2 = self.loop_vertices_from(Some(he_ba)); // [b, a, z, .., w]
+        let f3 = self.outline_from(face_3, a); // [a, x, .., z]
+        let f4 = self.outline_from(face_4, b); // [b, w, .., y]
+
+        // Apply: unlink the four faces and drop the edges around a and b.
+        let mut touched_edges = Vec::new();
+        for face_id in involved {
+            let loop_id = self.faces[face_id].outer_loop;
+            touched_edges.extend(
+                self.unlink_loop(loop_id)
+                    .into_iter()
+                    .filter_map(|he| self.halfedges[he].edge),
+            );
+        }
+        self.prune_free_edges(&touched_edges);
+        self.vertices.remove(a);
+        self.vertices.remove(b);
+
+        let ring_a: Vec<VertexId> = at_a.iter().map(|&p| self.add_vertex(p)).collect();
+        let ring_b: Vec<VertexId> = at_b.iter().map(|&p| self.add_vertex(p)).collect();
+
+        let mut outline_1 = vec![ring_a[0], ring_b[0]];
+        outline_1.extend_from_slice(&f1[2..]);
+        let mut outline_2 = vec![ring_b[last], ring_a[last]];
+        outline_2.extend_from_slice(&f2[2..]);
+        let mut outline_3: Vec<VertexId> = ring_a.iter().rev().copied().collect();
+        outline_3.extend_from_slice(&f3[1..]);
+        let mut outline_4 = ring_b.clone();
+        outline_4.extend_from_slice(&f4[1..]);
+
+        for (face_id, outline) in involved
+            .into_iter()
+            .zip([outline_1, outline_2, outline_3, outline_4])
+        {
+            let halfedges = self.collect_loop_halfedges(&outline)?;
+            let loop_id = self.faces[face_id].outer_loop;
+            self.link_loop(&halfedges, face_id, loop_id);
+        }
+
+        let mut strip = Vec::with_capacity(last);
+        for i in 0..last {
+            let face = self.make_face(&[ring_b[i], ring_a[i], ring_a[i + 1], ring_b[i + 1]])?;
+            for shell in self.shells.values_mut() {
+                if shell.faces.contains(&face_1) {
+                    shell.faces.push(face);
+                }
+            }
+            strip.push(face);
+        }
+        Ok(strip)
+    }
+
+    // --- Internal helpers ---
+
+    /// Number of edges around a vertex, walking its one-ring forwards via
+    /// `twin.next` and, from a boundary, backwards via `prev.twin`.
+    fn vertex_valence(&self, v: VertexId) -> usize {
+        let Some(start) = self.vertices.get(v).and_then(|vx| vx.halfedge) else {
+            return 0;
+        };
+        let twin_next =
+            |he: HalfEdgeId| self.halfedges[he].twin.and_then(|t| self.halfedges[t].next);
+        let prev_twin =
+            |he: HalfEdgeId| self.halfedges[he].prev.and_then(|p| self.halfedges[p].twin);
+
+        let mut count = 1;
+        let mut current = start;
+        loop {
+            match twin_next(current) {
+                Some(next) if next == start => return count,
+                Some(next) if count <= self.halfedges.len() => {
+                    count += 1;
+                    current = next;
+                }
+                _ => break,
+            }
+        }
+        let mut current = start;
+        while let Some(previous) = prev_twin(current).filter(|_| count <= self.halfedges.len()) {
+            count += 1;
+            current = previous;
+        }
+        count
+    }
+
+    /// Outer loop vertices of a face, rotated to start at `start`.
+    fn outline_from(&self, face_id: FaceId, start: VertexId) -> Vec<VertexId> {
+        let mut outline = self.loop_vertices(self.faces[face_id].outer_loop);
+        if let Some(i) = outline.iter().position(|&v| v == start) {
+            outline.rotate_left(i);
+        }
+        outline
+    }
+
+    /// Unit normal of a face's outer loop (Newell's method).
+    fn face_normal(&self, face_id: FaceId) -> Vector3 {
+        let points: Vec<Point3> = self
+            .loop_vertices(self.faces[face_id].outer_loop)
+            .into_iter()
+            .map(|v| self.vertices[v].position)
+            .collect();
+        newell_normal(&points).normalize_or_zero()
+    }
+}
@@ -0,0 +1,561 @@
+use std::collections::HashSet;
+use std::f64::consts::PI;
+
+use cst_core::error::{CstError, Result};
+use cst_math::Point3;
+
+use super::mesh::Mesh;
+use super::types::*;
+
+// --- Local editing operators ---
+//
+// Faces touched by an operator keep their `FaceId`: their loops are unlinked,
+// re-collected from the updated vertex list and linked again. Edges and
+// half-edges that end up without any face are freed.
+
+impl Mesh {
+    /// Delete a face together with its loops.
+    ///
+    /// Edges that no longer border any face are removed, and vertices left
+    /// without edges are removed as well.
+    pub fn delete_face(&mut self, face_id: FaceId) -> Result<()> {
+        let face = self
+            .faces
+            .get(face_id)
+            .cloned()
+            .ok_or_else(|| CstError::NotFound("Face not found".into()))?;
+
+        let mut touched_edges = Vec::new();
+        let mut touched_vertices = Vec::new();
+        for loop_id in std::iter::once(face.outer_loop).chain(face.inner_loops.iter().copied()) {
+            for he_id in self.unlink_loop(loop_id) {
+                let he = self.halfedges[he_id];
+                touched_vertices.push(he.origin);
+                touched_edges.extend(he.edge);
+            }
+            self.loops.remove(loop_id);
+        }
+        self.faces.remove(face_id);
+        self.forget_face(face_id);
+
+        self.prune_free_edges(&touched_edges);
+        for v in touched_vertices {
+            if self.vertices.get(v).is_some_and(|vx| vx.halfedge.is_none()) {
+                self.vertices.remove(v);
+            }
+        }
+        Ok(())
+    }
+
+    /// Collapse an edge, merging its two end vertices at the edge midpoint.
+    ///
+    /// Triangles adjacent to the edge degenerate and are removed; larger
+    /// polygons lose one corner. Returns the surviving vertex (the origin of
+    /// the edge's `halfedge_a`).
+    ///
+    /// Fails without modifying the mesh when the collapse would pinch a face
+    /// or create a non-manifold edge.
+    pub fn collapse_edge(&mut self, edge_id: EdgeId) -> Result<VertexId> {
+        let edge = *self
+            .edges
+            .get(edge_id)
+            .ok_or_else(|| CstError::NotFound("Edge not found".into()))?;
+        let keep = self.halfedges[edge.halfedge_a].origin;
+        let remove = self.halfedges[edge.halfedge_b].origin;
+
+        // Faces around the removed vertex, with their post-collapse outlines.
+        let mut affected: Vec<FaceId> = self
+            .halfedges
+            .values()
+            .filter(|he| he.origin == remove)
+            .filter_map(|he| he.face)
+            .collect();
+        affected.sort_unstable();
+        affected.dedup();
+
+        let mut rebuilt = Vec::with_capacity(affected.len());
+        for &face_id in &affected {
+            let face = &self.faces[face_id];
+            if !face.inner_loops.is_empty() {
+                return Err(CstError::InvalidOperation(
+                    "Cannot collapse an edge of a face with inner loops".into(),
+                ));
+            }
+            let mut outline: Vec<VertexId> = self
+                .loop_vertices(face.outer_loop)
+                .into_iter()
+                .map(|v| if v == remove { keep } else { v })
+                .collect();
+            outline.dedup();
+            while outline.len() > 1 && outline.first() == outline.last() {
+                outline.pop();
+            }
+            let unique: HashSet<_> = outline.iter().collect();
+            if unique.len() != outline.len() {
+                return Err(CstError::Topology(
+                    "Edge collapse would pinch a face".into(),
+                ));
+            }
+            rebuilt.push((face_id, outline));
+        }
+
+        // Every directed edge may be used by at most one face afterwards.
+        let mut directed: HashSet<(VertexId, VertexId)> = self
+            .halfedges
+            .iter()
+            .filter(|(_, he)| he.face.is_some_and(|f| !affected.contains(&f)))
+            .filter_map(|(he_id, he)| Some((he.origin, self.halfedge_target(he_id)?)))
+            .collect();
+        for (_, outline) in rebuilt.iter().filter(|(_, o)| o.len() >= 3) {
+            for i in 0..outline.len() {
+                let key = (outline[i], outline[(i + 1) % outline.len()]);
+                if !directed.insert(key) {
+                    return Err(CstError::Topology(
+                        "Edge collapse would create a non-manifold edge".into(),
+                    ));
+                }
+            }
+        }
+
+        // Apply: unlink, prune, merge the vertices, relink. Nothing below
+        // fails.
+        let mut touched_edges = Vec::new();
+        for &face_id in &affected {
+            let loop_id = self.faces[face_id].outer_loop;
+            touched_edges.extend(
+                self.unlink_loop(loop_id)
+                    .into_iter()
+                    .filter_map(|he| self.halfedges[he].edge),
+            );
+        }
+        self.prune_free_edges(&touched_edges);
+
+        // Wire edges (without faces) at the removed vertex survive the prune.
+        // The collapsed edge and edges that would double one at `keep` go,
+        // the others move over to `keep`.
+        let wires: Vec<EdgeId> = self
+            .edges
+            .iter()
+            .filter(|(_, e)| {
+                self.halfedges[e.halfedge_a].origin == remove
+                    || self.halfedges[e.halfedge_b].origin == remove
+            })
+            .map(|(id, _)| id)
+            .collect();
+        for wire in wires {
+            let e = self.edges[wire];
+            let (at_remove, other) = if self.halfedges[e.halfedge_a].origin == remove {
+                (e.halfedge_a, self.halfedges[e.halfedge_b].origin)
+            } else {
+                (e.halfedge_b, self.halfedges[e.halfedge_a].origin)
+            };
+            if other == keep || self.find_halfedge(keep, other).is_some() {
+                self.prune_free_edges(&[wire]);
+            } else {
+                self.halfedges[at_remove].origin = keep;
+            }
+        }
+        let midpoint = (self.vertices[keep].position + self.vertices[remove].position) * 0.5;
+        self.vertices[keep].position = midpoint;
+        self.vertices.remove(remove);
+
+        for (face_id, outline) in rebuilt {
+            let loop_id = self.faces[face_id].outer_loop;
+            if outline.len() < 3 {
+                self.loops.remove(loop_id);
+                self.faces.remove(face_id);
+                self.forget_face(face_id);
+                continue;
+            }
+            let halfedges = self
+                .collect_loop_halfedges(&outline)
+                .expect("outline validated before the collapse");
+            self.link_loop(&halfedges, face_id, loop_id);
+        }
+
+        self.refresh_vertex_halfedge(keep);
+        Ok(keep)
+    }
+
+    /// Remove an edge between two different faces, merging them into one.
+    ///
+    /// The face on the `halfedge_a` side survives and is returned; the other
+    /// face is deleted and its inner loops are moved to the survivor.
+    ///
+    /// Fails without modifying the mesh when the edge does not separate two
+    /// faces, lies on an inner loop or the merged face would pinch.
+    pub fn dissolve_edge(&mut self, edge_id: EdgeId) -> Result<FaceId> {
+        let edge = *self
+            .edges
+            .get(edge_id)
+            .ok_or_else(|| CstError::NotFound("Edge not found".into()))?;
+        let (keep_face, drop_face) = match self.edge_faces(edge_id) {
+            (Some(a), Some(b)) if a != b => (a, b),
+            (Some(_), Some(_)) => {
+                return Err(CstError::Topology(
+                    "Cannot dissolve an edge with the same face on both sides".into(),
+                ))
+            }
+            _ => return Err(CstError::Topology("Cannot dissolve a boundary edge".into())),
+        };
+
+        if self.halfedges[edge.halfedge_a].loop_id != Some(self.faces[keep_face].outer_loop)
+            || self.halfedges[edge.halfedge_b].loop_id != Some(self.faces[drop_face].outer_loop)
+        {
+            return Err(CstError::InvalidOperation(
+                "Cannot dissolve an edge that lies on an inner loop".into(),
+            ));
+        }
+
+        // The merged loop walks the surviving loop from the end of the shared
+        // edge back to its start, then continues through the other face.
+        let mut merged = self.loop_halfedges_from(edge.halfedge_a);
+        merged.remove(0);
+        let mut dropped_ring = self.loop_halfedges_from(edge.halfedge_b);
+        dropped_ring.remove(0);
+        merged.extend(dropped_ring);
+        let unique: HashSet<_> = merged.iter().map(|&he| self.halfedges[he].origin).collect();
+        if unique.len() != merged.len() {
+            return Err(CstError::Topology(
+                "Faces share more than one edge; dissolving would pinch the face".into(),
+            ));
+        }
+
+        let keep_loop = self.faces[keep_face].outer_loop;
+        let dropped = self.faces.remove(drop_face).expect("face checked above");
+        self.forget_face(drop_face);
+        self.unlink_loop(keep_loop);
+        self.unlink_loop(dropped.outer_loop);
+        self.loops.remove(dropped.outer_loop);
+
+        for &inner in &dropped.inner_loops {
+            self.loops[inner].face = Some(keep_face);
+            let start = self.loops[inner].halfedge;
+            let mut current = Some(start);
+            while let Some(he) = current {
+                self.halfedges[he].face = Some(keep_face);
+                current = self.halfedges[he].next.filter(|&n| n != start);
+            }
+        }
+        self.faces[keep_face]
+            .inner_loops
+            .extend(dropped.inner_loops);
+
+        // Only the shared edge is left without faces.
+        self.link_loop(&merged, keep_face, keep_loop);
+        self.prune_free_edges(&[edge_id]);
+        Ok(keep_face)
+    }
+
+    /// Split an edge at parameter `t` (measured from the origin of its
+    /// `halfedge_a`), returning the new vertex.
+    ///
+    /// Adjacent triangles are divided in two by connecting the new vertex to
+    /// the opposite corner; the new triangle copies the surface and shells of
+    /// the original. Larger polygons just gain a corner.
+    pub fn split_edge(&mut self, edge_id: EdgeId, t: f64) -> Result<VertexId> {
+        let edge = *self
+            .edges
+            .get(edge_id)
+            .ok_or_else(|| CstError::NotFound("Edge not found".into()))?;
+        if !(t > 0.0 && t < 1.0) {
+            return Err(CstError::InvalidOperation(
+                "Split parameter must lie strictly between 0 and 1".into(),
+            ));
+        }
+        let a = self.halfedges[edge.halfedge_a].origin;
+        let b = self.halfedges[edge.halfedge_b].origin;
+
+        // Loops running through the edge, as [target, .., origin].
+        let mut sides = Vec::new();
+        for he_id in [edge.halfedge_a, edge.halfedge_b] {
+            let he = self.halfedges[he_id];
+            if let (Some(face), Some(loop_id)) = (he.face, he.loop_id) {
+                sides.push((face, loop_id, self.loop_vertices_from(he.next)));
+            }
+        }
+        for &(_, loop_id, _) in &sides {
+            self.unlink_loop(loop_id);
+        }
+        self.prune_free_edges(&[edge_id]);
+
+        let position = self.vertices[a].position.lerp(self.vertices[b].position, t);
+        let mid = self.add_vertex(position);
+        if sides.is_empty() {
+            self.make_edge(a, mid)?;
+            self.make_edge(mid, b)?;
+        }
+        for (face_id, loop_id, mut outline) in sides {
+            outline.push(mid);
+            let face = &self.faces[face_id];
+            let is_triangle =
+                outline.len() == 4 && face.inner_loops.is_empty() && face.outer_loop == loop_id;
+            if !is_triangle {
+                let halfedges = self.collect_loop_halfedges(&outline)?;
+                self.link_loop(&halfedges, face_id, loop_id);
+                continue;
+            }
+            // outline = [q, apex, p, mid] for the split half-edge p -> q.
+            let (q, apex, p) = (outline[0], outline[1], outline[2]);
+            let halfedges = self.collect_loop_halfedges(&[mid, q, apex])?;
+            self.link_loop(&halfedges, face_id, loop_id);
+            let new_face = self.make_face(&[p, mid, apex])?;
+            self.copy_face_attributes(face_id, new_face);
+        }
+
+        if edge.curve.is_some() {
+            for (u, v) in [(a, mid), (mid, b)] {
+                if let Some(e) = self
+                    .find_halfedge(u, v)
+                    .and_then(|he| self.halfedges[he].edge)
+                {
+                    self.edges[e].curve = edge.curve;
+                }
+            }
+        }
+        Ok(mid)
+    }
+
+    /// Replace the diagonal shared by two triangles with the other diagonal
+    /// of their quad, returning the new edge.
+    ///
+    /// Both faces keep their ids. Fails if the edge does not separate two
+    /// triangles or the other diagonal already exists.
+    pub fn flip_edge(&mut self, edge_id: EdgeId) -> Result<EdgeId> {
+        let edge = *self
+            .edges
+            .get(edge_id)
+            .ok_or_else(|| CstError::NotFound("Edge not found".into()))?;
+        let (face_a, face_b) = match self.edge_faces(edge_id) {
+            (Some(fa), Some(fb)) if fa != fb => (fa, fb),
+            _ => {
+                return Err(CstError::Topology(
+                    "Only edges between two different faces can be flipped".into(),
+                ))
+            }
+        };
+        let (Some([a, b, c]), Some([_, _, d])) = (
+            self.triangle_corners(edge.halfedge_a),
+            self.triangle_corners(edge.halfedge_b),
+        ) else {
+            return Err(CstError::InvalidOperation(
+                "Edge flips require two adjacent triangles".into(),
+            ));
+        };
+        if c == d || self.find_halfedge(c, d).is_some() {
+            return Err(CstError::Topology(
+                "Flipping would duplicate an existing edge".into(),
+            ));
+        }
+
+        let loop_a = self.faces[face_a].outer_loop;
+        let loop_b = self.faces[face_b].outer_loop;
+        self.unlink_loop(loop_a);
+        self.unlink_loop(loop_b);
+        self.prune_free_edges(&[edge_id]);
+
+        // Quad boundary is a -> d -> b -> c.
+        let halfedges = self.collect_loop_halfedges(&[c, a, d])?;
+        self.link_loop(&halfedges, face_a, loop_a);
+        let halfedges = self.collect_loop_halfedges(&[d, b, c])?;
+        self.link_loop(&halfedges, face_b, loop_b);
+
+        self.find_halfedge(c, d)
+            .and_then(|he| self.halfedges[he].edge)
+            .ok_or_else(|| CstError::Topology("Flipped edge was not created".into()))
+    }
+
+    /// Flip edges between triangles until every such edge is locally
+    /// Delaunay (the angles opposite it sum to at most π). Returns the number
+    /// of flips.
+    ///
+    /// Edges whose dihedral angle exceeds `feature_angle` (radians) are kept
+    /// so the shape is preserved, as are flips that would fold a triangle
+    /// over. Non-triangular faces are left alone.
+    pub fn make_delaunay(&mut self, feature_angle: f64) -> usize {
+        let cos_feature = feature_angle.cos();
+        let mut flips = 0;
+        // Delaunay flipping terminates on planar patches; the pass limit
+        // guards against cycling on curved ones.
+        for _ in 0..self.edges.len().max(1) {
+            let mut flipped = false;
+            let edge_ids: Vec<EdgeId> = self.edges.keys().collect();
+            for edge_id in edge_ids {
+                if self.should_flip(edge_id, cos_feature) && self.flip_edge(edge_id).is_ok() {
+                    flips += 1;
+                    flipped = true;
+                }
+            }
+            if !flipped {
+                break;
+            }
+        }
+        flips
+    }
+
+    // --- Internal helpers ---
+
+    /// Corners `[p, q, r]` of a triangle without holes, starting at the
+    /// half-edge `p -> q`.
+    fn triangle_corners(&self, he_id: HalfEdgeId) -> Option<[VertexId; 3]> {
+        let face = self.faces.get(self.halfedges.get(he_id)?.face?)?;
+        if !face.inner_loops.is_empty() {
+            return None;
+        }
+        match self.loop_vertices_from(Some(he_id))[..] {
+            [p, q, r] => Some([p, q, r]),
+            _ => None,
+        }
+    }
+
+    /// Whether an edge between two triangles violates the Delaunay condition
+    /// and can be flipped without folding or crossing a feature edge.
+    fn should_flip(&self, edge_id: EdgeId, cos_feature: f64) -> bool {
+        let Some(edge) = self.edges.get(edge_id) else {
+            return false;
+        };
+        let (Some([a, b, c]), Some([_, _, d])) = (
+            self.triangle_corners(edge.halfedge_a),
+            self.triangle_corners(edge.halfedge_b),
+        ) else {
+            return false;
+        };
+        let [pa, pb, pc, pd] = [a, b, c, d].map(|v| self.vertices[v].position);
+        let normal = |p: Point3, q: Point3, r: Point3| (q - p).cross(r - p).normalize_or_zero();
+        let angle = |apex: Point3, p: Point3, q: Point3| (p - apex).angle_between(q - apex);
+
+        let n_abc = normal(pa, pb, pc);
+        let n_bad = normal(pb, pa, pd);
+        if n_abc.dot(n_bad) < cos_feature {
+            return false;
+        }
+        if angle(pc, pa, pb) + angle(pd, pb, pa) <= PI + 1e-12 {
+            return false;
+        }
+        // Both new triangles must keep facing the same way.
+        normal(pc, pa, pd).dot(n_abc) > 0.0 && normal(pd, pb, pc).dot(n_abc) > 0.0
+    }
+
+    /// Give a face created by splitting `source` the same surface and shell
+    /// membership.
+    fn copy_face_attributes(&mut self, source: FaceId, target: FaceId) {
+        let (surface, reversed) = {
+            let face = &self.faces[source];
+            (face.surface, face.surface_reversed)
+        };
+        let face = &mut self.faces[target];
+        face.surface = surface;
+        face.surface_reversed = reversed;
+        for shell in self.shells.values_mut() {
+            if shell.faces.contains(&source) {
+                shell.faces.push(target);
+            }
+        }
+    }
+
+    /// Vertices of a loop in order, starting at the loop's first half-edge.
+    pub(crate) fn loop_vertices(&self, loop_id: LoopId) -> Vec<VertexId> {
+        self.loop_vertices_from(self.loops.get(loop_id).map(|lp| lp.halfedge))
+    }
+
+    /// Vertices of the loop containing `start`, beginning at its origin.
+    pub(crate) fn loop_vertices_from(&self, start: Option<HalfEdgeId>) -> Vec<VertexId> {
+        let Some(start) = start else {
+            return Vec::new();
+        };
+        let mut out = Vec::new();
+        let mut current = start;
+        loop {
+            let he = &self.halfedges[current];
+            out.push(he.origin);
+            match he.next {
+                Some(next) if next != start && out.len() <= self.halfedges.len() => current = next,
+                _ => break,
+            }
+        }
+        out
+    }
+
+    /// Half-edges of the loop containing `start`, in order from `start`.
+    fn loop_halfedges_from(&self, start: HalfEdgeId) -> Vec<HalfEdgeId> {
+        let mut out = vec![start];
+        let mut current = self.halfedges[start].next;
+        while let Some(he_id) = current.filter(|&n| n != start) {
+            if out.len() > self.halfedges.len() {
+                break;
+            }
+            out.push(he_id);
+            current = self.halfedges[he_id].next;
+        }
+        out
+    }
+
+    /// Detach all half-edges of a loop from it, returning them in order.
+    /// The loop entry itself is left in place for reuse or removal.
+    pub(crate) fn unlink_loop(&mut self, loop_id: LoopId) -> Vec<HalfEdgeId> {
+        let Some(start) = self.loops.get(loop_id).map(|lp| lp.halfedge) else {
+            return Vec::new();
+        };
+        let mut ring = Vec::new();
+        let mut current = Some(start);
+        while let Some(he_id) = current {
+            ring.push(he_id);
+            let next = self.halfedges[he_id].next;
+            current = next.filter(|&n| n != start && ring.len() <= self.halfedges.len());
+        }
+        for &he_id in &ring {
+            let he = &mut self.halfedges[he_id];
+            he.next = None;
+            he.prev = None;
+            he.face = None;
+            he.loop_id = None;
+        }
+        ring
+    }
+
+    /// Remove the given edges if neither of their half-edges borders a face.
+    pub(crate) fn prune_free_edges(&mut self, edges: &[EdgeId]) {
+        let mut endpoints = Vec::new();
+        for &edge_id in edges {
+            let Some(edge) = self.edges.get(edge_id).copied() else {
+                continue;
+            };
+            let free = |he: HalfEdgeId| self.halfedges.get(he).map_or(true, |h| h.face.is_none());
+            if !(free(edge.halfedge_a) && free(edge.halfedge_b)) {
+                continue;
+            }
+            for he in [edge.halfedge_a, edge.halfedge_b] {
+                if let Some(removed) = self.halfedges.remove(he) {
+                    endpoints.push(removed.origin);
+                }
+            }
+            self.edges.remove(edge_id);
+        }
+        for v in endpoints {
+            self.refresh_vertex_halfedge(v);
+        }
+    }
+
+    /// Drop a removed face from every shell that lists it.
+    fn forget_face(&mut self, face_id: FaceId) {
+        for shell in self.shells.values_mut() {
+            shell.faces.retain(|&f| f != face_id);
+        }
+    }
+
+    /// Make sure a vertex points at one of its outgoing half-edges (or none).
+    pub(crate) fn refresh_vertex_halfedge(&mut self, v: VertexId) {
+        let Some(current) = self.vertices.get(v).map(|vx| vx.halfedge) else {
+            return;
+        };
+        let valid = current.is_some_and(|he| self.halfedges.get(he).is_some_and(|h| h.origin == v));
+        if !valid {
+            let replacement = self
+                .halfedges
+                .iter()
+                .find(|(_, he)| he.origin == v)
+                .map(|(id, _)| id);
+            self.vertices[v].halfedge = replacement;
+        }
+    }
+}
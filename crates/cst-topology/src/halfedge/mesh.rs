@@ -3,15 +3,23 @@ use cst_math::Point3;
 use serde::{Deserialize, Serialize};
 use slotmap::SlotMap;
 
+use super::portable::PortableMesh;
 use super::types::*;
 
+/// Half-edge boundary representation.
+///
+/// Serializes through [`PortableMesh`] so stored data does not depend on
+/// arena layout.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(into = "PortableMesh", try_from = "PortableMesh")]
 pub struct Mesh {
     pub vertices: SlotMap<VertexId, Vertex>,
     pub halfedges: SlotMap<HalfEdgeId, HalfEdge>,
     pub edges: SlotMap<EdgeId, Edge>,
     pub loops: SlotMap<LoopId, Loop>,
     pub faces: SlotMap<FaceId, Face>,
+    pub shells: SlotMap<ShellId, Shell>,
+    pub solids: SlotMap<SolidId, Solid>,
 }
 
 impl Mesh {
@@ -22,6 +30,8 @@ impl Mesh {
             edges: SlotMap::with_key(),
             loops: SlotMap::with_key(),
             faces: SlotMap::with_key(),
+            shells: SlotMap::with_key(),
+            solids: SlotMap::with_key(),
         }
     }
 
@@ -64,6 +74,7 @@ impl Mesh {
         let edge_id = self.edges.insert(Edge {
             halfedge_a: he_a,
             halfedge_b: he_b,
+            curve: None,
         });
 
         self.halfedges[he_a].edge = Some(edge_id);
@@ -83,6 +94,50 @@ impl Mesh {
     /// Create a face from an ordered list of vertices (CCW winding).
     /// Reuses existing edges/half-edges where possible.
     pub fn make_face(&mut self, vertices: &[VertexId]) -> Result<FaceId> {
+        let face_halfedges = self.collect_loop_halfedges(vertices)?;
+
+        // Create the face and loop
+        let loop_id = self.loops.insert(Loop {
+            halfedge: face_halfedges[0],
+            face: None,
+        });
+
+        let face_id = self.faces.insert(Face {
+            outer_loop: loop_id,
+            inner_loops: Vec::new(),
+            surface_reversed: false,
+            surface: None,
+        });
+
+        self.loops[loop_id].face = Some(face_id);
+        self.link_loop(&face_halfedges, face_id, loop_id);
+
+        Ok(face_id)
+    }
+
+    /// Add a hole to an existing face.
+    ///
+    /// `vertices` must run opposite to the outer loop (clockwise when the
+    /// face is seen from its front side).
+    pub fn add_inner_loop(&mut self, face_id: FaceId, vertices: &[VertexId]) -> Result<LoopId> {
+        if !self.faces.contains_key(face_id) {
+            return Err(CstError::NotFound("Face not found".into()));
+        }
+        let halfedges = self.collect_loop_halfedges(vertices)?;
+        let loop_id = self.loops.insert(Loop {
+            halfedge: halfedges[0],
+            face: Some(face_id),
+        });
+        self.link_loop(&halfedges, face_id, loop_id);
+        self.faces[face_id].inner_loops.push(loop_id);
+        Ok(loop_id)
+    }
+
+    /// Find or create the free half-edges that run around `vertices` in order.
+    pub(crate) fn collect_loop_halfedges(
+        &mut self,
+        vertices: &[VertexId],
+    ) -> Result<Vec<HalfEdgeId>> {
         let n = vertices.len();
         if n < 3 {
             return Err(CstError::Topology(
@@ -144,21 +199,17 @@ impl Mesh {
             }
         }
 
-        // Create the face and loop
-        let loop_id = self.loops.insert(Loop {
-            halfedge: face_halfedges[0],
-            face: None,
-        });
-
-        let face_id = self.faces.insert(Face {
-            outer_loop: loop_id,
-            inner_loops: Vec::new(),
-            surface_reversed: false,
-        });
-
-        self.loops[loop_id].face = Some(face_id);
+        Ok(face_halfedges)
+    }
 
-        // Link half-edges: next/prev chain + face/loop assignment
+    /// Link half-edges into a closed loop: next/prev chain + face/loop assignment.
+    pub(crate) fn link_loop(
+        &mut self,
+        face_halfedges: &[HalfEdgeId],
+        face_id: FaceId,
+        loop_id: LoopId,
+    ) {
+        let n = face_halfedges.len();
         for i in 0..n {
             let he = face_halfedges[i];
             let next_he = face_halfedges[(i + 1) % n];
@@ -169,8 +220,7 @@ impl Mesh {
             self.halfedges[he].face = Some(face_id);
             self.halfedges[he].loop_id = Some(loop_id);
         }
-
-        Ok(face_id)
+        self.loops[loop_id].halfedge = face_halfedges[0];
     }
 
     /// Convenience: create a triangular face.
@@ -179,7 +229,7 @@ impl Mesh {
     }
 
     /// Find a half-edge going from `origin` to `target`.
-    fn find_halfedge(&self, origin: VertexId, target: VertexId) -> Option<HalfEdgeId> {
+    pub(crate) fn find_halfedge(&self, origin: VertexId, target: VertexId) -> Option<HalfEdgeId> {
         for (he_id, he) in &self.halfedges {
             if he.origin == origin {
                 if let Some(twin_id) = he.twin {
@@ -200,6 +250,26 @@ impl Mesh {
         Some(twin.origin)
     }
 
+    /// Attach (or clear) the surface underlying a face.
+    pub fn set_face_surface(&mut self, face_id: FaceId, surface: Option<SurfaceRef>) -> Result<()> {
+        let face = self
+            .faces
+            .get_mut(face_id)
+            .ok_or_else(|| CstError::NotFound("Face not found".into()))?;
+        face.surface = surface;
+        Ok(())
+    }
+
+    /// Attach (or clear) the curve underlying an edge.
+    pub fn set_edge_curve(&mut self, edge_id: EdgeId, curve: Option<CurveRef>) -> Result<()> {
+        let edge = self
+            .edges
+            .get_mut(edge_id)
+            .ok_or_else(|| CstError::NotFound("Edge not found".into()))?;
+        edge.curve = curve;
+        Ok(())
+    }
+
     /// Get both faces adjacent to an edge.
     pub fn edge_faces(&self, edge_id: EdgeId) -> (Option<FaceId>, Option<FaceId>) {
         let edge = match self.edges.get(edge_id) {
@@ -1,9 +1,18 @@
+mod blend;
 mod bounding;
+mod edit;
 mod iter;
 pub mod mesh;
+mod portable;
+mod query;
+mod sew;
+mod solid;
 pub mod types;
 mod validate;
 
 pub use iter::{FaceHalfEdgeIter, FaceVertexIter, VertexOutgoingIter};
 pub use mesh::Mesh;
+pub use portable::{
+    PortableEdge, PortableFace, PortableMesh, PortableSolid, PORTABLE_MESH_VERSION,
+};
 pub use types::*;
@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+
+use cst_core::error::{CstError, Result};
+use cst_math::Point3;
+use serde::{Deserialize, Serialize};
+
+use super::mesh::Mesh;
+use super::types::*;
+
+// --- Stable interchange format ---
+//
+// SlotMap keys encode slot indices and generations, so serializing the arenas
+// directly ties stored data to the exact allocation history and to the
+// SlotMap serde layout. `PortableMesh` instead stores plain indexed arrays
+// and is what `Mesh` serializes through. Bump `PORTABLE_MESH_VERSION` when
+// the layout changes and keep reading older versions.
+
+/// Current version written by [`Mesh::to_portable`].
+pub const PORTABLE_MESH_VERSION: u32 = 1;
+
+/// Index-based, versioned representation of a [`Mesh`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PortableMesh {
+    pub version: u32,
+    pub vertices: Vec<[f64; 3]>,
+    pub faces: Vec<PortableFace>,
+    /// Every edge, oriented as its `halfedge_a`. Edges not used by any face
+    /// (wire edges) are recreated from this list.
+    pub edges: Vec<PortableEdge>,
+    /// Shells as lists of face indices.
+    #[serde(default)]
+    pub shells: Vec<Vec<u32>>,
+    #[serde(default)]
+    pub solids: Vec<PortableSolid>,
+}
+
+/// A face as vertex-index loops.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PortableFace {
+    pub outer: Vec<u32>,
+    #[serde(default)]
+    pub holes: Vec<Vec<u32>>,
+    #[serde(default)]
+    pub surface_reversed: bool,
+    #[serde(default)]
+    pub surface: Option<usize>,
+}
+
+/// An edge from `vertices[0]` to `vertices[1]`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PortableEdge {
+    pub vertices: [u32; 2],
+    #[serde(default)]
+    pub curve: Option<usize>,
+}
+
+/// A solid as shell indices.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PortableSolid {
+    pub outer_shell: u32,
+    #[serde(default)]
+    pub inner_shells: Vec<u32>,
+}
+
+impl Mesh {
+    /// Convert to the stable interchange representation.
+    ///
+    /// Entities are numbered in arena iteration order.
+    pub fn to_portable(&self) -> PortableMesh {
+        let vertex_index: HashMap<VertexId, u32> = self
+            .vertices
+            .keys()
+            .enumerate()
+            .map(|(i, v)| (v, i as u32))
+            .collect();
+        let face_index: HashMap<FaceId, u32> = self
+            .faces
+            .keys()
+            .enumerate()
+            .map(|(i, f)| (f, i as u32))
+            .collect();
+        let shell_index: HashMap<ShellId, u32> = self
+            .shells
+            .keys()
+            .enumerate()
+            .map(|(i, s)| (s, i as u32))
+            .collect();
+        let indices = |loop_id: LoopId| -> Vec<u32> {
+            self.loop_vertices(loop_id)
+                .into_iter()
+                .map(|v| vertex_index[&v])
+                .collect()
+        };
+
+        PortableMesh {
+            version: PORTABLE_MESH_VERSION,
+            vertices: self
+                .vertices
+                .values()
+                .map(|v| v.position.to_array())
+                .collect(),
+            faces: self
+                .faces
+                .values()
+                .map(|f| PortableFace {
+                    outer: indices(f.outer_loop),
+                    holes: f.inner_loops.iter().map(|&l| indices(l)).collect(),
+                    surface_reversed: f.surface_reversed,
+                    surface: f.surface.map(|s| s.0),
+                })
+                .collect(),
+            edges: self
+                .edges
+                .values()
+                .filter_map(|e| {
+                    let a = self.halfedges.get(e.halfedge_a)?.origin;
+                    let b = self.halfedges.get(e.halfedge_b)?.origin;
+                    Some(PortableEdge {
+                        vertices: [vertex_index[&a], vertex_index[&b]],
+                        curve: e.curve.map(|c| c.0),
+                    })
+                })
+                .collect(),
+            shells: self
+                .shells
+                .values()
+                .map(|s| s.faces.iter().map(|f| face_index[f]).collect())
+                .collect(),
+            solids: self
+                .solids
+                .values()
+                .map(|s| PortableSolid {
+                    outer_shell: shell_index[&s.outer_shell],
+                    inner_shells: s.inner_shells.iter().map(|sh| shell_index[sh]).collect(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Rebuild a mesh from its interchange representation.
+    ///
+    /// Fails on unknown (newer) versions, out-of-range indices and face
+    /// loops that do not form a valid half-edge structure. Shells and solids
+    /// are restored as stored, without re-checking closedness.
+    pub fn from_portable(portable: &PortableMesh) -> Result<Mesh> {
+        if portable.version == 0 || portable.version > PORTABLE_MESH_VERSION {
+            return Err(CstError::Parse(format!(
+                "Unsupported portable mesh version {} (expected at most {})",
+                portable.version, PORTABLE_MESH_VERSION
+            )));
+        }
+
+        let mut mesh = Mesh::new();
+        let vertices: Vec<VertexId> = portable
+            .vertices
+            .iter()
+            .map(|&p| mesh.add_vertex(Point3::from_array(p)))
+            .collect();
+        let vertex_at = |i: u32| -> Result<VertexId> {
+            vertices
+                .get(i as usize)
+                .copied()
+                .ok_or_else(|| CstError::Parse(format!("Vertex index {} out of range", i)))
+        };
+        let lookup = |indices: &[u32]| -> Result<Vec<VertexId>> {
+            indices.iter().map(|&i| vertex_at(i)).collect()
+        };
+
+        let mut faces = Vec::with_capacity(portable.faces.len());
+        for pf in &portable.faces {
+            let face_id = mesh.make_face(&lookup(&pf.outer)?)?;
+            for hole in &pf.holes {
+                mesh.add_inner_loop(face_id, &lookup(hole)?)?;
+            }
+            let face = &mut mesh.faces[face_id];
+            face.surface_reversed = pf.surface_reversed;
+            face.surface = pf.surface.map(SurfaceRef);
+            faces.push(face_id);
+        }
+
+        let mut existing: HashMap<(VertexId, VertexId), EdgeId> = HashMap::new();
+        for (edge_id, edge) in &mesh.edges {
+            let a = mesh.halfedges[edge.halfedge_a].origin;
+            let b = mesh.halfedges[edge.halfedge_b].origin;
+            existing.insert((a, b), edge_id);
+        }
+        for pe in &portable.edges {
+            let (a, b) = (vertex_at(pe.vertices[0])?, vertex_at(pe.vertices[1])?);
+            let edge_id = if let Some(&e) = existing.get(&(a, b)) {
+                e
+            } else if let Some(&e) = existing.get(&(b, a)) {
+                // Keep the stored orientation of `halfedge_a`.
+                let edge = &mut mesh.edges[e];
+                std::mem::swap(&mut edge.halfedge_a, &mut edge.halfedge_b);
+                existing.remove(&(b, a));
+                existing.insert((a, b), e);
+                e
+            } else {
+                let e = mesh.make_edge(a, b)?;
+                existing.insert((a, b), e);
+                e
+            };
+            mesh.edges[edge_id].curve = pe.curve.map(CurveRef);
+        }
+
+        let face_at = |i: u32| -> Result<FaceId> {
+            faces
+                .get(i as usize)
+                .copied()
+                .ok_or_else(|| CstError::Parse(format!("Face index {} out of range", i)))
+        };
+        let mut shells = Vec::with_capacity(portable.shells.len());
+        for ps in &portable.shells {
+            let shell_faces = ps.iter().map(|&i| face_at(i)).collect::<Result<Vec<_>>>()?;
+            shells.push(mesh.shells.insert(Shell { faces: shell_faces }));
+        }
+        let shell_at = |i: u32| -> Result<ShellId> {
+            shells
+                .get(i as usize)
+                .copied()
+                .ok_or_else(|| CstError::Parse(format!("Shell index {} out of range", i)))
+        };
+        for ps in &portable.solids {
+            let solid = Solid {
+                outer_shell: shell_at(ps.outer_shell)?,
+                inner_shells: ps
+                    .inner_shells
+                    .iter()
+                    .map(|&i| shell_at(i))
+                    .collect::<Result<Vec<_>>>()?,
+            };
+            mesh.solids.insert(solid);
+        }
+        Ok(mesh)
+    }
+}
+
+impl From<Mesh> for PortableMesh {
+    fn from(mesh: Mesh) -> Self {
+        mesh.to_portable()
+    }
+}
+
+impl TryFrom<PortableMesh> for Mesh {
+    type Error = CstError;
+
+    fn try_from(portable: PortableMesh) -> Result<Self> {
+        Mesh::from_portable(&portable)
+    }
+}
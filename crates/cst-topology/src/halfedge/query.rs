@@ -0,0 +1,156 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::mesh::Mesh;
+use super::types::*;
+
+// --- Global topology queries ---
+//
+// Used by import QA to report open shells, disconnected parts and handle
+// counts. Edges without a face on either side (wire edges) are ignored by the
+// boundary and closedness queries but still count towards the Euler
+// characteristic.
+
+impl Mesh {
+    /// Closed boundary loops as vertex cycles.
+    ///
+    /// A boundary half-edge has no face while its twin does; each loop
+    /// follows those half-edges, so it runs opposite to the adjacent faces.
+    pub fn boundary_loops(&self) -> Vec<Vec<VertexId>> {
+        let mut by_origin: HashMap<VertexId, Vec<HalfEdgeId>> = HashMap::new();
+        let mut boundary: Vec<HalfEdgeId> = Vec::new();
+        for (he_id, he) in &self.halfedges {
+            if self.is_boundary_halfedge(he_id) {
+                by_origin.entry(he.origin).or_default().push(he_id);
+                boundary.push(he_id);
+            }
+        }
+
+        let mut visited: HashSet<HalfEdgeId> = HashSet::new();
+        let mut loops = Vec::new();
+        for start in boundary {
+            if visited.contains(&start) {
+                continue;
+            }
+            let mut cycle = Vec::new();
+            let mut current = start;
+            while visited.insert(current) {
+                cycle.push(self.halfedges[current].origin);
+                // At a vertex where several boundaries touch, take any
+                // unvisited continuation; the cycle still closes.
+                let Some(target) = self.halfedge_target(current) else {
+                    break;
+                };
+                let next = by_origin.get(&target).and_then(|candidates| {
+                    candidates
+                        .iter()
+                        .copied()
+                        .find(|he| !visited.contains(he))
+                        .or_else(|| candidates.contains(&start).then_some(start))
+                });
+                match next {
+                    Some(next) => current = next,
+                    None => break,
+                }
+            }
+            loops.push(cycle);
+        }
+        loops
+    }
+
+    /// Faces grouped into edge-connected components.
+    pub fn connected_components(&self) -> Vec<Vec<FaceId>> {
+        let mut seen: HashSet<FaceId> = HashSet::new();
+        let mut components = Vec::new();
+        for start in self.faces.keys() {
+            if !seen.insert(start) {
+                continue;
+            }
+            let mut component = vec![start];
+            let mut queue = VecDeque::from([start]);
+            while let Some(face_id) = queue.pop_front() {
+                for loop_id in self.face_loop_ids(face_id) {
+                    for he_id in self.loop_halfedges(loop_id) {
+                        let neighbour = self.halfedges[he_id]
+                            .twin
+                            .and_then(|t| self.halfedges.get(t))
+                            .and_then(|t| t.face);
+                        if let Some(n) = neighbour {
+                            if seen.insert(n) {
+                                component.push(n);
+                                queue.push_back(n);
+                            }
+                        }
+                    }
+                }
+            }
+            components.push(component);
+        }
+        components
+    }
+
+    /// Euler characteristic `V - E + F`, where a face with `k` holes counts
+    /// as `1 - k`.
+    pub fn euler_characteristic(&self) -> i64 {
+        let faces: i64 = self
+            .faces
+            .values()
+            .map(|f| 1 - f.inner_loops.len() as i64)
+            .sum();
+        self.vertices.len() as i64 - self.edges.len() as i64 + faces
+    }
+
+    /// Number of handles (through-holes) of the face set.
+    ///
+    /// Derived from `χ = 2C - 2g - B` with `C` components and `B` boundary
+    /// loops. Only meaningful for orientable manifold meshes without
+    /// isolated vertices or wire edges.
+    pub fn genus(&self) -> i64 {
+        let components = self.connected_components().len() as i64;
+        let boundaries = self.boundary_loops().len() as i64;
+        (2 * components - boundaries - self.euler_characteristic()) / 2
+    }
+
+    /// Whether the mesh has faces and every edge has a face on both sides.
+    pub fn is_closed(&self) -> bool {
+        !self.faces.is_empty()
+            && self.edges.values().all(|e| {
+                [e.halfedge_a, e.halfedge_b]
+                    .iter()
+                    .all(|&he| self.halfedges.get(he).is_some_and(|h| h.face.is_some()))
+            })
+    }
+
+    // --- Internal helpers ---
+
+    fn is_boundary_halfedge(&self, he_id: HalfEdgeId) -> bool {
+        let he = &self.halfedges[he_id];
+        he.face.is_none()
+            && he
+                .twin
+                .and_then(|t| self.halfedges.get(t))
+                .is_some_and(|t| t.face.is_some())
+    }
+
+    fn face_loop_ids(&self, face_id: FaceId) -> Vec<LoopId> {
+        let face = &self.faces[face_id];
+        std::iter::once(face.outer_loop)
+            .chain(face.inner_loops.iter().copied())
+            .collect()
+    }
+
+    /// Half-edges of a loop in order.
+    fn loop_halfedges(&self, loop_id: LoopId) -> Vec<HalfEdgeId> {
+        let Some(start) = self.loops.get(loop_id).map(|lp| lp.halfedge) else {
+            return Vec::new();
+        };
+        let mut ring = Vec::new();
+        let mut current = Some(start);
+        while let Some(he_id) = current {
+            ring.push(he_id);
+            current = self.halfedges[he_id]
+                .next
+                .filter(|&n| n != start && ring.len() <= self.halfedges.len());
+        }
+        ring
+    }
+}
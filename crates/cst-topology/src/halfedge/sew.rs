@@ -0,0 +1,169 @@
+use std::collections::{HashMap, HashSet};
+
+use cst_core::error::{CstError, Result};
+use cst_math::spatial::PointIndex;
+
+use super::mesh::Mesh;
+use super::types::*;
+
+// --- Tolerant sewing ---
+//
+// Faces built independently (e.g. from exported IFC breps with duplicated
+// points) only touch geometrically. Sewing first merges boundary vertices
+// that lie within the tolerance, then joins pairs of opposite boundary
+// half-edges between the same two vertices into one shared edge.
+
+impl Mesh {
+    /// Merge nearly-coincident boundary vertices and edges.
+    ///
+    /// Vertices of the same face are never merged with each other, and only
+    /// consistently oriented neighbours are joined (a boundary half-edge
+    /// `a -> b` pairs with one running `b -> a`). T-junctions, where a vertex
+    /// lies inside another face's edge, are left open.
+    ///
+    /// Returns the number of edges that were sewn.
+    pub fn sew(&mut self, tolerance: f64) -> Result<usize> {
+        if !(tolerance > 0.0 && tolerance.is_finite()) {
+            return Err(CstError::Tolerance(format!(
+                "Sewing tolerance must be positive, got {}",
+                tolerance
+            )));
+        }
+        self.merge_boundary_vertices(tolerance);
+        Ok(self.join_boundary_edges())
+    }
+
+    /// Merge boundary vertices closer than `tolerance`, keeping the position
+    /// of the first vertex of each cluster.
+    fn merge_boundary_vertices(&mut self, tolerance: f64) {
+        // Boundary vertices with the faces around them, in a stable order.
+        let mut vertex_faces: HashMap<VertexId, HashSet<FaceId>> = HashMap::new();
+        let mut boundary: Vec<VertexId> = Vec::new();
+        for (he_id, he) in &self.halfedges {
+            if let Some(face) = he.face {
+                vertex_faces.entry(he.origin).or_default().insert(face);
+            }
+            if self.is_open_halfedge(he_id) {
+                boundary.push(he.origin);
+                boundary.extend(self.halfedge_target(he_id));
+            }
+        }
+        boundary.sort_unstable();
+        boundary.dedup();
+
+        // Representatives of the clusters so far; merged vertices map to them.
+        let mut index = PointIndex::new(tolerance * 2.0);
+        let mut representatives: Vec<VertexId> = Vec::new();
+        let mut merge_into: HashMap<VertexId, VertexId> = HashMap::new();
+        for &v in &boundary {
+            let p = self.vertices[v].position;
+            let faces = vertex_faces.get(&v).cloned().unwrap_or_default();
+            let target = index
+                .find_within(p, tolerance, |k| {
+                    vertex_faces
+                        .get(&representatives[k])
+                        .map_or(true, |rf| rf.is_disjoint(&faces))
+                })
+                .map(|k| representatives[k]);
+            match target {
+                Some(r) => {
+                    vertex_faces.remove(&v);
+                    vertex_faces.entry(r).or_default().extend(faces);
+                    merge_into.insert(v, r);
+                }
+                None => {
+                    index.insert(p);
+                    representatives.push(v);
+                }
+            }
+        }
+
+        if merge_into.is_empty() {
+            return;
+        }
+        for he in self.halfedges.values_mut() {
+            if let Some(&r) = merge_into.get(&he.origin) {
+                he.origin = r;
+            }
+        }
+        for (&v, &r) in &merge_into {
+            self.vertices.remove(v);
+            self.refresh_vertex_halfedge(r);
+        }
+    }
+
+    /// Join pairs of boundary half-edges `a -> b` and `b -> a` that belong to
+    /// different edges.
+    fn join_boundary_edges(&mut self) -> usize {
+        // Directed boundary half-edges (with a face) keyed by endpoints.
+        let mut open: HashMap<(VertexId, VertexId), Vec<HalfEdgeId>> = HashMap::new();
+        let mut ordered: Vec<HalfEdgeId> = Vec::new();
+        for (he_id, he) in &self.halfedges {
+            if he.face.is_some() && self.is_open_halfedge(he_id) {
+                if let Some(target) = self.halfedge_target(he_id) {
+                    open.entry((he.origin, target)).or_default().push(he_id);
+                    ordered.push(he_id);
+                }
+            }
+        }
+
+        let mut used: HashSet<HalfEdgeId> = HashSet::new();
+        let mut sewn = 0;
+        for he_a in ordered {
+            if used.contains(&he_a) {
+                continue;
+            }
+            let origin = self.halfedges[he_a].origin;
+            let Some(target) = self.halfedge_target(he_a) else {
+                continue;
+            };
+            let Some(he_b) = open
+                .get(&(target, origin))
+                .and_then(|c| c.iter().copied().find(|he| !used.contains(he)))
+            else {
+                continue;
+            };
+            used.insert(he_a);
+            used.insert(he_b);
+            self.join_halfedges(he_a, he_b);
+            sewn += 1;
+        }
+        sewn
+    }
+
+    /// Make two face half-edges twins of one edge, dropping their former free
+    /// twins and the second edge.
+    fn join_halfedges(&mut self, he_a: HalfEdgeId, he_b: HalfEdgeId) {
+        let (Some(edge_a), Some(edge_b)) = (self.halfedges[he_a].edge, self.halfedges[he_b].edge)
+        else {
+            return;
+        };
+        let free_a = self.halfedges[he_a].twin;
+        let free_b = self.halfedges[he_b].twin;
+        let ends = [self.halfedges[he_a].origin, self.halfedges[he_b].origin];
+
+        self.halfedges[he_a].twin = Some(he_b);
+        self.halfedges[he_b].twin = Some(he_a);
+        self.halfedges[he_b].edge = Some(edge_a);
+        let edge = &mut self.edges[edge_a];
+        edge.halfedge_a = he_a;
+        edge.halfedge_b = he_b;
+        self.edges.remove(edge_b);
+        for free in [free_a, free_b].into_iter().flatten() {
+            self.halfedges.remove(free);
+        }
+        for v in ends {
+            self.refresh_vertex_halfedge(v);
+        }
+    }
+
+    /// Whether a half-edge borders a face on exactly one side.
+    fn is_open_halfedge(&self, he_id: HalfEdgeId) -> bool {
+        let he = &self.halfedges[he_id];
+        let twin_face = he
+            .twin
+            .and_then(|t| self.halfedges.get(t))
+            .and_then(|t| t.face);
+        he.face.is_some() != twin_face.is_some()
+    }
+}
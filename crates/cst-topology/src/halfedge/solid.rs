@@ -0,0 +1,256 @@
+use std::collections::{HashSet, VecDeque};
+use std::f64::consts::PI;
+
+use cst_core::error::{CstError, Result};
+use cst_math::Point3;
+
+use super::mesh::Mesh;
+use super::types::*;
+
+// --- Shells and solids ---
+//
+// A shell groups connected faces; a solid is an outer shell plus optional
+// void shells, mirroring IFC's IfcClosedShell / IfcFacetedBrepWithVoids.
+// Outer shells face outward (positive signed volume), void shells face into
+// the void (negative signed volume).
+
+impl Mesh {
+    /// Group a set of connected faces into a shell.
+    pub fn add_shell(&mut self, faces: Vec<FaceId>) -> Result<ShellId> {
+        if faces.is_empty() {
+            return Err(CstError::Topology(
+                "A shell requires at least one face".into(),
+            ));
+        }
+        if let Some(missing) = faces.iter().find(|&&f| !self.faces.contains_key(f)) {
+            return Err(CstError::NotFound(format!("Face {:?} not found", missing)));
+        }
+        if !self.faces_connected(&faces) {
+            return Err(CstError::Topology("Shell faces are not connected".into()));
+        }
+        Ok(self.shells.insert(Shell { faces }))
+    }
+
+    /// Whether every edge of the shell is shared by two faces of the shell.
+    pub fn shell_is_closed(&self, shell_id: ShellId) -> Result<bool> {
+        let shell = self.shell(shell_id)?;
+        let members: HashSet<FaceId> = shell.faces.iter().copied().collect();
+        for &face_id in &shell.faces {
+            for he_id in self.face_halfedges(face_id).into_iter().flatten() {
+                let twin_face = self.halfedges[he_id]
+                    .twin
+                    .and_then(|t| self.halfedges.get(t))
+                    .and_then(|t| t.face);
+                if !twin_face.is_some_and(|f| members.contains(&f)) {
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Signed volume enclosed by a shell (divergence theorem over its faces).
+    ///
+    /// Positive when the faces point outward. Only meaningful for closed
+    /// shells.
+    pub fn shell_volume(&self, shell_id: ShellId) -> Result<f64> {
+        let shell = self.shell(shell_id)?;
+        Ok(self
+            .shell_triangles(shell)
+            .map(|[a, b, c]| a.dot(b.cross(c)) / 6.0)
+            .sum())
+    }
+
+    /// Whether a shell is closed and its faces point away from the enclosed
+    /// volume.
+    pub fn shell_is_outward(&self, shell_id: ShellId) -> Result<bool> {
+        Ok(self.shell_is_closed(shell_id)? && self.shell_volume(shell_id)? > 0.0)
+    }
+
+    /// Reverse the orientation of every face in a shell.
+    ///
+    /// Faces keep their ids; `surface_reversed` is toggled so faces stay
+    /// consistent with their underlying surfaces.
+    pub fn flip_shell(&mut self, shell_id: ShellId) -> Result<()> {
+        let faces = self.shell(shell_id)?.faces.clone();
+        let members: HashSet<FaceId> = faces.iter().copied().collect();
+        let shares_edge_with_other_face = faces
+            .iter()
+            .flat_map(|&f| self.face_halfedges(f).into_iter().flatten())
+            .filter_map(|he| self.halfedges[he].twin)
+            .filter_map(|twin| self.halfedges.get(twin).and_then(|t| t.face))
+            .any(|f| !members.contains(&f));
+        if shares_edge_with_other_face {
+            return Err(CstError::InvalidOperation(
+                "Cannot flip a shell that shares edges with faces outside it".into(),
+            ));
+        }
+
+        let mut outlines = Vec::with_capacity(faces.len());
+        for &face_id in &faces {
+            if !self.faces[face_id].inner_loops.is_empty() {
+                return Err(CstError::InvalidOperation(
+                    "Cannot flip a shell containing faces with inner loops".into(),
+                ));
+            }
+            let mut outline = self.loop_vertices(self.faces[face_id].outer_loop);
+            outline.reverse();
+            outlines.push(outline);
+        }
+        for &face_id in &faces {
+            self.unlink_loop(self.faces[face_id].outer_loop);
+        }
+        for (&face_id, outline) in faces.iter().zip(&outlines) {
+            let halfedges = self.collect_loop_halfedges(outline)?;
+            let loop_id = self.faces[face_id].outer_loop;
+            self.link_loop(&halfedges, face_id, loop_id);
+            let face = &mut self.faces[face_id];
+            face.surface_reversed = !face.surface_reversed;
+        }
+        Ok(())
+    }
+
+    /// Create a solid from a closed outward shell and optional void shells.
+    ///
+    /// Void shells must be closed, face into the void (negative signed
+    /// volume) and lie inside the outer shell.
+    pub fn add_solid(
+        &mut self,
+        outer_shell: ShellId,
+        inner_shells: Vec<ShellId>,
+    ) -> Result<SolidId> {
+        if !self.shell_is_closed(outer_shell)? {
+            return Err(CstError::Topology(
+                "Outer shell of a solid must be closed".into(),
+            ));
+        }
+        if self.shell_volume(outer_shell)? <= 0.0 {
+            return Err(CstError::Topology(
+                "Outer shell of a solid must face outward".into(),
+            ));
+        }
+        for &void in &inner_shells {
+            if void == outer_shell {
+                return Err(CstError::Topology(
+                    "A void cannot be the outer shell".into(),
+                ));
+            }
+            if !self.shell_is_closed(void)? {
+                return Err(CstError::Topology("Void shells must be closed".into()));
+            }
+            if self.shell_volume(void)? >= 0.0 {
+                return Err(CstError::Topology(
+                    "Void shells must face into the void".into(),
+                ));
+            }
+            let inside = self
+                .shell(void)?
+                .faces
+                .iter()
+                .flat_map(|&f| self.face_vertices(f).into_iter().flatten())
+                .all(|v| self.shell_winding_number(outer_shell, self.vertices[v].position) > 0.5);
+            if !inside {
+                return Err(CstError::Topology(
+                    "Void shell lies outside the outer shell".into(),
+                ));
+            }
+        }
+        Ok(self.solids.insert(Solid {
+            outer_shell,
+            inner_shells,
+        }))
+    }
+
+    /// Net volume of a solid: outer volume minus its voids.
+    pub fn solid_volume(&self, solid_id: SolidId) -> Result<f64> {
+        let solid = self.solid(solid_id)?;
+        let mut volume = self.shell_volume(solid.outer_shell)?;
+        for &void in &solid.inner_shells {
+            // Voids face inward, so their signed volume is already negative.
+            volume += self.shell_volume(void)?;
+        }
+        Ok(volume)
+    }
+
+    /// Whether a point lies in the material of a solid (inside the outer
+    /// shell and outside every void).
+    ///
+    /// Uses the generalized winding number, which is robust for points close
+    /// to edges and vertices.
+    pub fn solid_contains_point(&self, solid_id: SolidId, point: Point3) -> Result<bool> {
+        let solid = self.solid(solid_id)?;
+        let mut winding = self.shell_winding_number(solid.outer_shell, point);
+        for &void in &solid.inner_shells {
+            winding += self.shell_winding_number(void, point);
+        }
+        Ok(winding > 0.5)
+    }
+
+    // --- Internal helpers ---
+
+    fn shell(&self, shell_id: ShellId) -> Result<&Shell> {
+        self.shells
+            .get(shell_id)
+            .ok_or_else(|| CstError::NotFound("Shell not found".into()))
+    }
+
+    fn solid(&self, solid_id: SolidId) -> Result<&Solid> {
+        self.solids
+            .get(solid_id)
+            .ok_or_else(|| CstError::NotFound("Solid not found".into()))
+    }
+
+    /// Fan triangles of every face in a shell.
+    fn shell_triangles<'a>(&'a self, shell: &'a Shell) -> impl Iterator<Item = [Point3; 3]> + 'a {
+        shell.faces.iter().flat_map(move |&face_id| {
+            let points: Vec<Point3> = self
+                .face_vertices(face_id)
+                .into_iter()
+                .flatten()
+                .map(|v| self.vertices[v].position)
+                .collect();
+            (1..points.len().saturating_sub(1)).map(move |i| [points[0], points[i], points[i + 1]])
+        })
+    }
+
+    /// Generalized winding number of a shell around `point` (1 inside an
+    /// outward shell, 0 outside, -1 inside an inward one).
+    fn shell_winding_number(&self, shell_id: ShellId, point: Point3) -> f64 {
+        let Some(shell) = self.shells.get(shell_id) else {
+            return 0.0;
+        };
+        let solid_angle: f64 = self
+            .shell_triangles(shell)
+            .map(|[a, b, c]| {
+                // Van Oosterom & Strackee signed solid angle.
+                let (a, b, c) = (a - point, b - point, c - point);
+                let (la, lb, lc) = (a.length(), b.length(), c.length());
+                let numerator = a.dot(b.cross(c));
+                let denominator = la * lb * lc + a.dot(b) * lc + b.dot(c) * la + c.dot(a) * lb;
+                2.0 * numerator.atan2(denominator)
+            })
+            .sum();
+        solid_angle / (4.0 * PI)
+    }
+
+    /// Whether the given faces form one edge-connected component.
+    fn faces_connected(&self, faces: &[FaceId]) -> bool {
+        let members: HashSet<FaceId> = faces.iter().copied().collect();
+        let mut seen = HashSet::from([faces[0]]);
+        let mut queue = VecDeque::from([faces[0]]);
+        while let Some(face_id) = queue.pop_front() {
+            for he_id in self.face_halfedges(face_id).into_iter().flatten() {
+                let neighbour = self.halfedges[he_id]
+                    .twin
+                    .and_then(|t| self.halfedges.get(t))
+                    .and_then(|t| t.face);
+                if let Some(n) = neighbour {
+                    if members.contains(&n) && seen.insert(n) {
+                        queue.push_back(n);
+                    }
+                }
+            }
+        }
+        seen.len() == members.len()
+    }
+}
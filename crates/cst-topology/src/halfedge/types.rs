@@ -14,6 +14,17 @@ new_key_type! {
     pub struct SolidId;
 }
 
+// --- Geometry references ---
+
+/// Index of the surface carrying a face, in an external geometry pool
+/// (e.g. `cst_geometry::GeometryPool`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SurfaceRef(pub usize);
+
+/// Index of the curve carrying an edge, in an external geometry pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CurveRef(pub usize);
+
 // --- Entity structs ---
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,6 +48,9 @@ pub struct HalfEdge {
 pub struct Edge {
     pub halfedge_a: HalfEdgeId,
     pub halfedge_b: HalfEdgeId,
+    /// Underlying curve; `None` means a straight segment between the vertices.
+    #[serde(default)]
+    pub curve: Option<CurveRef>,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -50,13 +64,18 @@ pub struct Face {
     pub outer_loop: LoopId,
     pub inner_loops: Vec<LoopId>,
     pub surface_reversed: bool,
+    /// Underlying surface; `None` means the face is planar through its vertices.
+    #[serde(default)]
+    pub surface: Option<SurfaceRef>,
 }
 
+/// A connected set of faces, e.g. an IFC closed shell.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Shell {
     pub faces: Vec<FaceId>,
 }
 
+/// A closed outer shell with optional void shells.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Solid {
     pub outer_shell: ShellId,
@@ -1,3 +1,7 @@
+pub mod boolean;
 pub mod halfedge;
+pub mod obj;
 
+pub use boolean::{bsp_boolean, BooleanOp};
 pub use halfedge::*;
+pub use obj::{load_obj, read_obj, save_obj, write_obj};
@@ -0,0 +1,116 @@
+//! Wavefront OBJ import/export for half-edge meshes.
+//!
+//! Only positions and polygonal faces are exchanged: faces keep their vertex
+//! count, and faces sharing OBJ vertex indices share edges after loading.
+//! Texture coordinates, normals, groups and materials are ignored on import.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use cst_core::error::{CstError, Result};
+use cst_math::Point3;
+
+use crate::halfedge::{Mesh, VertexId};
+
+/// Load a mesh from an OBJ file.
+pub fn load_obj(path: &Path) -> Result<Mesh> {
+    read_obj(BufReader::new(File::open(path)?))
+}
+
+/// Save a mesh to an OBJ file.
+pub fn save_obj(mesh: &Mesh, path: &Path) -> Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_obj(mesh, &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Parse OBJ text into a mesh.
+///
+/// Face corners may use the `v`, `v/vt`, `v//vn` or `v/vt/vn` forms and
+/// negative (relative) indices. Faces that cannot be added, e.g. because
+/// they would make an edge non-manifold, fail with a parse error naming the
+/// line.
+pub fn read_obj<R: BufRead>(reader: R) -> Result<Mesh> {
+    let mut mesh = Mesh::new();
+    let mut vertices: Vec<VertexId> = Vec::new();
+
+    for (line_no, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line_no = line_no + 1;
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("v") => {
+                let coords: Vec<f64> = tokens
+                    .take(3)
+                    .map(|t| t.parse::<f64>())
+                    .collect::<std::result::Result<_, _>>()
+                    .map_err(|e| parse_error(line_no, &e.to_string()))?;
+                if coords.len() < 3 {
+                    return Err(parse_error(line_no, "vertex needs three coordinates"));
+                }
+                vertices.push(mesh.add_vertex(Point3::new(coords[0], coords[1], coords[2])));
+            }
+            Some("f") => {
+                let corners = tokens
+                    .map(|t| resolve_index(t, vertices.len()).map(|i| vertices[i]))
+                    .collect::<Option<Vec<VertexId>>>()
+                    .ok_or_else(|| parse_error(line_no, "invalid face vertex index"))?;
+                mesh.make_face(&corners)
+                    .map_err(|e| parse_error(line_no, &e.to_string()))?;
+            }
+            _ => {}
+        }
+    }
+    Ok(mesh)
+}
+
+/// Write a mesh as OBJ text.
+///
+/// Every vertex is written (including isolated ones) followed by the outer
+/// loop of every face. OBJ has no notion of holes, so faces with inner
+/// loops are rejected.
+pub fn write_obj<W: Write>(mesh: &Mesh, writer: &mut W) -> Result<()> {
+    if mesh.faces.values().any(|f| !f.inner_loops.is_empty()) {
+        return Err(CstError::InvalidOperation(
+            "OBJ cannot represent faces with inner loops".into(),
+        ));
+    }
+
+    let mut index: HashMap<VertexId, usize> = HashMap::with_capacity(mesh.vertices.len());
+    for (i, (v_id, v)) in mesh.vertices.iter().enumerate() {
+        index.insert(v_id, i + 1);
+        writeln!(
+            writer,
+            "v {} {} {}",
+            v.position.x, v.position.y, v.position.z
+        )?;
+    }
+    for face_id in mesh.faces.keys() {
+        let Some(corners) = mesh.face_vertices(face_id) else {
+            continue;
+        };
+        let corners: Vec<String> = corners.map(|v| index[&v].to_string()).collect();
+        writeln!(writer, "f {}", corners.join(" "))?;
+    }
+    Ok(())
+}
+
+/// Resolve the vertex part of a face corner to a 0-based index.
+fn resolve_index(token: &str, vertex_count: usize) -> Option<usize> {
+    let index: i64 = token.split('/').next()?.parse().ok()?;
+    let resolved = if index < 0 {
+        vertex_count as i64 + index
+    } else {
+        index - 1
+    };
+    (0..vertex_count as i64)
+        .contains(&resolved)
+        .then_some(resolved as usize)
+}
+
+fn parse_error(line_no: usize, message: &str) -> CstError {
+    CstError::Parse(format!("OBJ line {}: {}", line_no, message))
+}
@@ -0,0 +1,132 @@
+use cst_core::traits::Validate;
+use cst_math::DVec3;
+use cst_topology::{EdgeId, Mesh};
+
+mod common;
+use common::add_box;
+
+/// Unit box as one shell, plus its top edge along +X at y = 1.
+fn unit_box() -> (Mesh, EdgeId) {
+    let mut mesh = Mesh::new();
+    let faces = add_box(&mut mesh, DVec3::ZERO, DVec3::ONE);
+    mesh.add_shell(faces).unwrap();
+    let edge = mesh
+        .edges
+        .iter()
+        .find(|(_, e)| {
+            let p = mesh.vertices[mesh.halfedges[e.halfedge_a].origin].position;
+            let q = mesh.vertices[mesh.halfedges[e.halfedge_b].origin].position;
+            p.y == 1.0 && q.y == 1.0 && p.z == 1.0 && q.z == 1.0
+        })
+        .map(|(id, _)| id)
+        .unwrap();
+    (mesh, edge)
+}
+
+fn volume(mesh: &Mesh) -> f64 {
+    let shell = mesh.shells.keys().next().unwrap();
+    mesh.shell_volume(shell).unwrap()
+}
+
+#[test]
+fn test_chamfer_box_edge() {
+    let (mut mesh, edge) = unit_box();
+    let face = mesh.chamfer_edge(edge, 0.25).unwrap();
+
+    assert_eq!(mesh.vertices.len(), 10);
+    assert_eq!(mesh.edges.len(), 15);
+    assert_eq!(mesh.faces.len(), 7);
+    assert_eq!(mesh.face_vertices(face).unwrap().count(), 4);
+    assert!(mesh.is_closed());
+    mesh.validate().unwrap();
+
+    // The shell picked up the chamfer face and lost a prism of 0.25^2 / 2.
+    assert_eq!(mesh.shells.values().next().unwrap().faces.len(), 7);
+    assert!((volume(&mesh) - (1.0 - 0.03125)).abs() < 1e-12);
+}
+
+#[test]
+fn test_fillet_box_edge() {
+    let (mut mesh, edge) = unit_box();
+    let radius = 0.4;
+    let strip = mesh.fillet_edge(edge, radius, 6).unwrap();
+
+    assert_eq!(strip.len(), 6);
+    assert_eq!(mesh.faces.len(), 12);
+    assert!(mesh.is_closed());
+    assert_eq!(mesh.euler_characteristic(), 2);
+    mesh.validate().unwrap();
+
+    // Profile points lie on the fillet cylinder around (y, z) = (0.6, 0.6).
+    for v in mesh.vertices.values() {
+        let p = v.position;
+        if p.y > 0.6 + 1e-9 && p.z > 0.6 + 1e-9 {
+            let r = ((p.y - 0.6).powi(2) + (p.z - 0.6).powi(2)).sqrt();
+            assert!((r - radius).abs() < 1e-12, "r = {r}");
+        }
+    }
+    // Inscribed strips remove more than the exact fillet, less than a chamfer.
+    let v = volume(&mesh);
+    assert!(v < 1.0 - (1.0 - std::f64::consts::FRAC_PI_4) * radius * radius);
+    assert!(v > 1.0 - radius * radius / 2.0);
+}
+
+#[test]
+fn test_blend_too_large_fails_without_changes() {
+    let (mut mesh, edge) = unit_box();
+    assert!(mesh.chamfer_edge(edge, 1.5).is_err());
+    assert!(mesh.fillet_edge(edge, 0.2, 0).is_err());
+    assert_eq!(mesh.faces.len(), 6);
+    assert_eq!(mesh.vertices.len(), 8);
+    mesh.validate().unwrap();
+}
+
+#[test]
+fn test_blend_open_edge_fails() {
+    let mut mesh = Mesh::new();
+    let v0 = mesh.add_vertex(DVec3::new(0.0, 0.0, 0.0));
+    let v1 = mesh.add_vertex(DVec3::new(1.0, 0.0, 0.0));
+    let v2 = mesh.add_vertex(DVec3::new(0.0, 1.0, 0.0));
+    mesh.make_triangle(v0, v1, v2).unwrap();
+    let edge = mesh.edges.keys().next().unwrap();
+    assert!(mesh.chamfer_edge(edge, 0.1).is_err());
+}
+
+#[test]
+fn test_blend_needs_three_edges_at_each_end() {
+    // The unit box with its +X side split into two triangles, which gives
+    // the corner (1, 1, 1) a fourth edge.
+    let mut mesh = Mesh::new();
+    let v: Vec<_> = (0..8)
+        .map(|i| {
+            let bit = |b: usize| if i & b == 0 { 0.0 } else { 1.0 };
+            mesh.add_vertex(DVec3::new(bit(1), bit(2), bit(4)))
+        })
+        .collect();
+    let quads = [
+        [0, 2, 3, 1],
+        [4, 5, 7, 6],
+        [0, 1, 5, 4],
+        [2, 6, 7, 3],
+        [0, 4, 6, 2],
+    ];
+    for q in quads {
+        mesh.make_face(&q.map(|i| v[i])).unwrap();
+    }
+    mesh.make_triangle(v[1], v[3], v[7]).unwrap();
+    mesh.make_triangle(v[1], v[7], v[5]).unwrap();
+    assert!(mesh.is_closed());
+
+    let edge = mesh
+        .edges
+        .iter()
+        .find(|(_, e)| {
+            let ends = [e.halfedge_a, e.halfedge_b].map(|he| mesh.halfedges[he].origin);
+            ends.contains(&v[6]) && ends.contains(&v[7])
+        })
+        .map(|(id, _)| id)
+        .unwrap();
+    assert!(mesh.chamfer_edge(edge, 0.25).is_err());
+    assert_eq!(mesh.faces.len(), 7);
+    mesh.validate().unwrap();
+}
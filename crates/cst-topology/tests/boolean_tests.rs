@@ -0,0 +1,233 @@
+use cst_core::traits::Validate;
+use cst_core::{Tolerance, ToleranceContext};
+use cst_math::DVec3;
+use cst_topology::{bsp_boolean, BooleanOp, Mesh};
+
+mod common;
+use common::box_solid;
+
+/// Signed volume via fan triangulation of every face.
+fn volume(mesh: &Mesh) -> f64 {
+    let mut vol = 0.0;
+    for face in mesh.faces.keys() {
+        let pts: Vec<DVec3> = mesh
+            .face_vertices(face)
+            .unwrap()
+            .map(|v| mesh.vertices[v].position)
+            .collect();
+        for i in 1..pts.len() - 1 {
+            vol += pts[0].dot(pts[i].cross(pts[i + 1])) / 6.0;
+        }
+    }
+    vol
+}
+
+fn assert_closed(mesh: &Mesh) {
+    mesh.validate().unwrap();
+    for (id, he) in &mesh.halfedges {
+        assert!(
+            he.face.is_some(),
+            "half-edge {:?} is on an open boundary",
+            id
+        );
+    }
+}
+
+#[test]
+fn test_box_solid_fixture() {
+    let b = box_solid(DVec3::ZERO, DVec3::ONE);
+    assert_closed(&b);
+    assert!((volume(&b) - 1.0).abs() < 1e-12);
+}
+
+#[test]
+fn test_union_of_overlapping_boxes() {
+    let a = box_solid(DVec3::ZERO, DVec3::splat(2.0));
+    let b = box_solid(DVec3::splat(1.0), DVec3::splat(3.0));
+    let result = a.union(&b).unwrap();
+    assert_closed(&result);
+    assert!(
+        (volume(&result) - 15.0).abs() < 1e-9,
+        "volume = {}",
+        volume(&result)
+    );
+}
+
+#[test]
+fn test_difference_of_overlapping_boxes() {
+    let a = box_solid(DVec3::ZERO, DVec3::splat(2.0));
+    let b = box_solid(DVec3::splat(1.0), DVec3::splat(3.0));
+    let result = a.difference(&b).unwrap();
+    assert_closed(&result);
+    assert!(
+        (volume(&result) - 7.0).abs() < 1e-9,
+        "volume = {}",
+        volume(&result)
+    );
+}
+
+#[test]
+fn test_intersection_of_overlapping_boxes() {
+    let a = box_solid(DVec3::ZERO, DVec3::splat(2.0));
+    let b = box_solid(DVec3::splat(1.0), DVec3::splat(3.0));
+    let result = a.intersection(&b).unwrap();
+    assert_closed(&result);
+    assert!(
+        (volume(&result) - 1.0).abs() < 1e-9,
+        "volume = {}",
+        volume(&result)
+    );
+    // The intersection is a unit box: coplanar fragments are merged back.
+    assert_eq!(result.faces.len(), 6);
+}
+
+#[test]
+fn test_difference_drills_through_hole() {
+    // A bar pushed all the way through a slab leaves a hole; every face of
+    // the result is still closed off by half-edges.
+    let slab = box_solid(DVec3::ZERO, DVec3::new(4.0, 4.0, 1.0));
+    let bar = box_solid(DVec3::new(1.0, 1.0, -1.0), DVec3::new(2.0, 2.0, 2.0));
+    let result = bsp_boolean(&slab, &bar, BooleanOp::Difference, Tolerance::default()).unwrap();
+    assert_closed(&result);
+    assert!(
+        (volume(&result) - 15.0).abs() < 1e-9,
+        "volume = {}",
+        volume(&result)
+    );
+}
+
+#[test]
+fn test_difference_with_extent_tolerance() {
+    // The same overlap modelled in millimetres, with tolerances scaled to
+    // the model size.
+    let a = box_solid(DVec3::ZERO, DVec3::splat(2000.0));
+    let b = box_solid(DVec3::splat(1000.0), DVec3::splat(3000.0));
+    let context = ToleranceContext::from_extent(DVec3::splat(3000.0).length());
+    let result = bsp_boolean(&a, &b, BooleanOp::Difference, context).unwrap();
+    assert_closed(&result);
+    assert!(
+        (volume(&result) - 7.0e9).abs() < 1e-3,
+        "volume = {}",
+        volume(&result)
+    );
+}
+
+#[test]
+fn test_union_of_disjoint_boxes() {
+    let a = box_solid(DVec3::ZERO, DVec3::ONE);
+    let b = box_solid(DVec3::splat(5.0), DVec3::splat(6.0));
+    let result = a.union(&b).unwrap();
+    assert_closed(&result);
+    assert!((volume(&result) - 2.0).abs() < 1e-12);
+    assert_eq!(result.faces.len(), 12);
+}
+
+#[test]
+fn test_difference_of_disjoint_boxes_is_unchanged() {
+    let a = box_solid(DVec3::ZERO, DVec3::ONE);
+    let b = box_solid(DVec3::splat(5.0), DVec3::splat(6.0));
+    let result = a.difference(&b).unwrap();
+    assert!((volume(&result) - 1.0).abs() < 1e-12);
+    let empty = a.intersection(&b).unwrap();
+    assert!(empty.faces.is_empty());
+}
+
+#[test]
+fn test_coarse_linear_tolerance_keeps_slightly_tilted_faces() {
+    // A prism cuts a 2 degree slope into the top of a box. With a linear
+    // tolerance of 0.01 the flat and sloped parts of the top must still
+    // stay separate faces: coplanarity is an angular test.
+    let slope = 2f64.to_radians().tan();
+    let section = [(1.0, 2.0), (3.0, 2.0 - 2.0 * slope), (3.0, 3.0)];
+    let mut prism = Mesh::new();
+    let back: Vec<_> = section
+        .iter()
+        .map(|&(x, z)| prism.add_vertex(DVec3::new(x, -1.0, z)))
+        .collect();
+    let front: Vec<_> = section
+        .iter()
+        .map(|&(x, z)| prism.add_vertex(DVec3::new(x, 3.0, z)))
+        .collect();
+    prism.make_face(&back).unwrap();
+    prism.make_face(&[front[0], front[2], front[1]]).unwrap();
+    for i in 0..3 {
+        let j = (i + 1) % 3;
+        prism
+            .make_face(&[back[j], back[i], front[i], front[j]])
+            .unwrap();
+    }
+    assert!(volume(&prism) > 0.0);
+
+    let block = box_solid(DVec3::ZERO, DVec3::splat(2.0));
+    let tolerance = ToleranceContext::new(0.01, Tolerance::DEFAULT_ANGULAR, 1e-9);
+    let result = bsp_boolean(&block, &prism, BooleanOp::Difference, tolerance).unwrap();
+    assert_closed(&result);
+    // The cut is a triangle of area slope / 2 over a depth of 2
+    assert!((volume(&result) - (8.0 - slope)).abs() < 1e-9);
+
+    let top_faces = result
+        .faces
+        .keys()
+        .filter(|&face| {
+            let pts: Vec<DVec3> = result
+                .face_vertices(face)
+                .unwrap()
+                .map(|v| result.vertices[v].position)
+                .collect();
+            let normal = (pts[1] - pts[0]).cross(pts[2] - pts[0]).normalize();
+            normal.z > 0.9
+        })
+        .count();
+    assert_eq!(top_faces, 2);
+}
+
+/// A 4 x 4 x 1 slab with a square opening from (1.5, 1.5) to (2.5, 2.5),
+/// its top and bottom faces carrying the opening as inner loops.
+fn slab_with_opening() -> Mesh {
+    let mut mesh = Mesh::new();
+    let mut ring = |corners: [(f64, f64); 4], z: f64| -> Vec<_> {
+        corners.iter()
+            .map(|&(x, y)| mesh.add_vertex(DVec3::new(x, y, z)))
+            .collect()
+    };
+    let square = |lo: f64, hi: f64| [(lo, lo), (hi, lo), (hi, hi), (lo, hi)];
+    let outer_bottom = ring(square(0.0, 4.0), 0.0);
+    let outer_top = ring(square(0.0, 4.0), 1.0);
+    let inner_bottom = ring(square(1.5, 2.5), 0.0);
+    let inner_top = ring(square(1.5, 2.5), 1.0);
+
+    let rev = |outline: &[_]| outline.iter().rev().copied().collect::<Vec<_>>();
+    let top = mesh.make_face(&outer_top).unwrap();
+    mesh.add_inner_loop(top, &rev(&inner_top)).unwrap();
+    let bottom = mesh.make_face(&rev(&outer_bottom)).unwrap();
+    mesh.add_inner_loop(bottom, &inner_bottom).unwrap();
+    for i in 0..4 {
+        let j = (i + 1) % 4;
+        mesh.make_face(&[outer_bottom[i], outer_bottom[j], outer_top[j], outer_top[i]])
+            .unwrap();
+        mesh.make_face(&[inner_bottom[j], inner_bottom[i], inner_top[i], inner_top[j]])
+            .unwrap();
+    }
+    mesh
+}
+
+#[test]
+fn test_difference_cuts_faces_with_inner_loops() {
+    let slab = slab_with_opening();
+    slab.validate().unwrap();
+    // Keep the half of the slab at x >= 2, which runs through the opening
+    let cutter = box_solid(DVec3::new(-1.0, -1.0, -1.0), DVec3::new(2.0, 5.0, 2.0));
+    let result = slab.difference(&cutter).unwrap();
+    assert_closed(&result);
+    assert!(
+        (volume(&result) - 7.5).abs() < 1e-9,
+        "volume = {}",
+        volume(&result)
+    );
+
+    // Disjoint operands pass through, the holed faces as fragments
+    let apart = box_solid(DVec3::splat(10.0), DVec3::splat(11.0));
+    let result = slab.union(&apart).unwrap();
+    assert_closed(&result);
+    assert!((volume(&result) - 16.0).abs() < 1e-9, "volume = {}", volume(&result));
+}
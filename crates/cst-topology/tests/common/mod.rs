@@ -0,0 +1,40 @@
+//! Fixtures shared by the integration tests.
+#![allow(dead_code)]
+
+use cst_math::DVec3;
+use cst_topology::{FaceId, Mesh};
+
+/// Add a closed axis-aligned box with outward-facing quads to `mesh`.
+pub fn add_box(mesh: &mut Mesh, min: DVec3, max: DVec3) -> Vec<FaceId> {
+    let mut v = Vec::new();
+    for i in 0..8 {
+        let p = DVec3::new(
+            if i & 1 == 0 { min.x } else { max.x },
+            if i & 2 == 0 { min.y } else { max.y },
+            if i & 4 == 0 { min.z } else { max.z },
+        );
+        v.push(mesh.add_vertex(p));
+    }
+    let quads = [
+        [0, 2, 3, 1], // -Z
+        [4, 5, 7, 6], // +Z
+        [0, 1, 5, 4], // -Y
+        [2, 6, 7, 3], // +Y
+        [0, 4, 6, 2], // -X
+        [1, 3, 7, 5], // +X
+    ];
+    quads
+        .iter()
+        .map(|q| {
+            mesh.make_face(&[v[q[0]], v[q[1]], v[q[2]], v[q[3]]])
+                .unwrap()
+        })
+        .collect()
+}
+
+/// A mesh holding a single closed box.
+pub fn box_solid(min: DVec3, max: DVec3) -> Mesh {
+    let mut mesh = Mesh::new();
+    add_box(&mut mesh, min, max);
+    mesh
+}
@@ -0,0 +1,428 @@
+use cst_core::traits::Validate;
+use cst_math::DVec3;
+use cst_topology::{EdgeId, Mesh, VertexId};
+
+/// 3x3 vertex grid on the XY plane split into 8 CCW triangles.
+///
+/// ```text
+/// 6 - 7 - 8
+/// | / | / |
+/// 3 - 4 - 5
+/// | / | / |
+/// 0 - 1 - 2
+/// ```
+fn grid_mesh() -> (Mesh, Vec<VertexId>) {
+    let mut mesh = Mesh::new();
+    let mut verts = Vec::new();
+    for j in 0..3 {
+        for i in 0..3 {
+            verts.push(mesh.add_vertex(DVec3::new(i as f64, j as f64, 0.0)));
+        }
+    }
+    for j in 0..2 {
+        for i in 0..2 {
+            let a = verts[j * 3 + i];
+            let b = verts[j * 3 + i + 1];
+            let c = verts[(j + 1) * 3 + i + 1];
+            let d = verts[(j + 1) * 3 + i];
+            mesh.make_triangle(a, b, c).unwrap();
+            mesh.make_triangle(a, c, d).unwrap();
+        }
+    }
+    (mesh, verts)
+}
+
+/// Everything the mesh stores, to check that a failed edit left it as it
+/// was.
+fn snapshot(mesh: &Mesh) -> String {
+    format!("{mesh:?}")
+}
+
+fn edge_between(mesh: &Mesh, a: VertexId, b: VertexId) -> EdgeId {
+    mesh.edges
+        .iter()
+        .find(|(_, e)| {
+            let oa = mesh.halfedges[e.halfedge_a].origin;
+            let ob = mesh.halfedges[e.halfedge_b].origin;
+            (oa == a && ob == b) || (oa == b && ob == a)
+        })
+        .map(|(id, _)| id)
+        .expect("edge exists")
+}
+
+#[test]
+fn test_delete_face_frees_dangling_entities() {
+    let mut mesh = Mesh::new();
+    let v0 = mesh.add_vertex(DVec3::new(0.0, 0.0, 0.0));
+    let v1 = mesh.add_vertex(DVec3::new(1.0, 0.0, 0.0));
+    let v2 = mesh.add_vertex(DVec3::new(1.0, 1.0, 0.0));
+    let v3 = mesh.add_vertex(DVec3::new(0.0, 1.0, 0.0));
+    let f0 = mesh.make_triangle(v0, v1, v2).unwrap();
+    let f1 = mesh.make_triangle(v0, v2, v3).unwrap();
+
+    mesh.delete_face(f1).unwrap();
+    assert!(!mesh.faces.contains_key(f1));
+    assert!(mesh.faces.contains_key(f0));
+    // v3 lost all its edges; the diagonal survives because f0 still uses it.
+    assert_eq!(mesh.vertices.len(), 3);
+    assert_eq!(mesh.edges.len(), 3);
+    assert_eq!(mesh.halfedges.len(), 6);
+    assert_eq!(mesh.loops.len(), 1);
+    mesh.validate().unwrap();
+
+    mesh.delete_face(f0).unwrap();
+    assert!(mesh.faces.is_empty());
+    assert!(mesh.edges.is_empty());
+    assert!(mesh.halfedges.is_empty());
+    assert!(mesh.vertices.is_empty());
+}
+
+#[test]
+fn test_delete_missing_face_fails() {
+    let (mut mesh, _) = grid_mesh();
+    let face = mesh.faces.keys().next().unwrap();
+    mesh.delete_face(face).unwrap();
+    assert!(mesh.delete_face(face).is_err());
+}
+
+#[test]
+fn test_vertex_pointers_stay_valid_after_delete() {
+    let (mut mesh, _) = grid_mesh();
+    let faces: Vec<_> = mesh.faces.keys().take(3).collect();
+    for f in faces {
+        mesh.delete_face(f).unwrap();
+    }
+    for (v_id, v) in &mesh.vertices {
+        let he = v
+            .halfedge
+            .expect("remaining vertices keep an outgoing half-edge");
+        assert_eq!(mesh.halfedges[he].origin, v_id);
+    }
+    mesh.validate().unwrap();
+}
+
+#[test]
+fn test_collapse_interior_edge() {
+    let (mut mesh, verts) = grid_mesh();
+    let center = verts[4];
+    let right = verts[5];
+    let edge = edge_between(&mesh, center, right);
+    let faces_before: Vec<_> = mesh.faces.keys().collect();
+
+    let kept = mesh.collapse_edge(edge).unwrap();
+    assert!(kept == center || kept == right);
+    assert_eq!(mesh.vertices.len(), 8);
+    // The two triangles sharing the edge degenerate.
+    assert_eq!(mesh.faces.len(), 6);
+    // V - E + F stays 1 for a disk.
+    assert_eq!(
+        mesh.vertices.len() as i64 - mesh.edges.len() as i64 + mesh.faces.len() as i64,
+        1
+    );
+    assert_eq!(mesh.vertices[kept].position, DVec3::new(1.5, 1.0, 0.0));
+    // Surviving faces keep their ids.
+    for f in mesh.faces.keys() {
+        assert!(faces_before.contains(&f));
+    }
+    mesh.validate().unwrap();
+}
+
+#[test]
+fn test_collapse_shrinks_quad_to_triangle() {
+    let mut mesh = Mesh::new();
+    let v0 = mesh.add_vertex(DVec3::new(0.0, 0.0, 0.0));
+    let v1 = mesh.add_vertex(DVec3::new(1.0, 0.0, 0.0));
+    let v2 = mesh.add_vertex(DVec3::new(1.0, 1.0, 0.0));
+    let v3 = mesh.add_vertex(DVec3::new(0.0, 1.0, 0.0));
+    let face = mesh.make_face(&[v0, v1, v2, v3]).unwrap();
+
+    let edge = edge_between(&mesh, v0, v1);
+    mesh.collapse_edge(edge).unwrap();
+    assert_eq!(mesh.face_vertices(face).unwrap().count(), 3);
+    assert_eq!(mesh.edges.len(), 3);
+    mesh.validate().unwrap();
+}
+
+#[test]
+fn test_collapse_rejects_pinch() {
+    // Quad [a, x, b, y] plus a triangle on the a-b diagonal: collapsing a-b
+    // would make the quad visit the merged vertex twice.
+    let mut mesh = Mesh::new();
+    let a = mesh.add_vertex(DVec3::new(0.0, 0.0, 0.0));
+    let x = mesh.add_vertex(DVec3::new(1.0, -1.0, 0.0));
+    let b = mesh.add_vertex(DVec3::new(2.0, 0.0, 0.0));
+    let y = mesh.add_vertex(DVec3::new(1.0, 1.0, 0.0));
+    let z = mesh.add_vertex(DVec3::new(1.0, 0.0, 1.0));
+    mesh.make_face(&[a, x, b, y]).unwrap();
+    mesh.make_triangle(a, b, z).unwrap();
+
+    let edge = edge_between(&mesh, a, b);
+    let vertices_before = mesh.vertices.len();
+    let before = snapshot(&mesh);
+    assert!(mesh.collapse_edge(edge).is_err());
+    // Nothing changed.
+    assert_eq!(mesh.vertices.len(), vertices_before);
+    assert_eq!(snapshot(&mesh), before);
+    mesh.validate().unwrap();
+}
+
+#[test]
+fn test_collapse_rejects_non_manifold_edge_unchanged() {
+    // Triangles keep-x-p and remove-x-q joined by a wire edge: collapsing it
+    // would give both triangles the half-edge keep -> x.
+    let mut mesh = Mesh::new();
+    let keep = mesh.add_vertex(DVec3::new(0.0, 0.0, 0.0));
+    let remove = mesh.add_vertex(DVec3::new(1.0, 0.0, 0.0));
+    let x = mesh.add_vertex(DVec3::new(0.5, 1.0, 0.0));
+    let p = mesh.add_vertex(DVec3::new(-1.0, 1.0, 0.0));
+    let q = mesh.add_vertex(DVec3::new(2.0, 1.0, 0.0));
+    let edge = mesh.make_edge(keep, remove).unwrap();
+    mesh.make_triangle(keep, x, p).unwrap();
+    mesh.make_triangle(remove, x, q).unwrap();
+
+    let before = snapshot(&mesh);
+    assert!(mesh.collapse_edge(edge).is_err());
+    assert_eq!(snapshot(&mesh), before);
+    mesh.validate().unwrap();
+}
+
+#[test]
+fn test_collapse_with_inner_loop_fails_unchanged() {
+    let mut mesh = Mesh::new();
+    let corners = [(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)];
+    let outer: Vec<_> = corners
+        .iter()
+        .map(|&(x, y)| mesh.add_vertex(DVec3::new(x, y, 0.0)))
+        .collect();
+    let hole: Vec<_> = corners
+        .iter()
+        .rev()
+        .map(|&(x, y)| mesh.add_vertex(DVec3::new(1.0 + x / 2.0, 1.0 + y / 2.0, 0.0)))
+        .collect();
+    let face = mesh.make_face(&outer).unwrap();
+    mesh.add_inner_loop(face, &hole).unwrap();
+
+    let edge = edge_between(&mesh, outer[0], outer[1]);
+    let before = snapshot(&mesh);
+    assert!(mesh.collapse_edge(edge).is_err());
+    assert_eq!(snapshot(&mesh), before);
+}
+
+#[test]
+fn test_collapse_wire_edge_leaves_no_self_loop() {
+    // A triangle with a wire edge keep-remove and a wire edge remove-x
+    // doubling the triangle edge keep-x once collapsed.
+    let mut mesh = Mesh::new();
+    let keep = mesh.add_vertex(DVec3::new(0.0, 0.0, 0.0));
+    let x = mesh.add_vertex(DVec3::new(1.0, 0.0, 0.0));
+    let y = mesh.add_vertex(DVec3::new(0.0, 1.0, 0.0));
+    let remove = mesh.add_vertex(DVec3::new(-1.0, 0.0, 0.0));
+    let face = mesh.make_triangle(keep, x, y).unwrap();
+    let edge = mesh.make_edge(keep, remove).unwrap();
+    mesh.make_edge(remove, x).unwrap();
+
+    assert_eq!(mesh.collapse_edge(edge).unwrap(), keep);
+    assert_eq!(mesh.vertices.len(), 3);
+    assert_eq!(mesh.edges.len(), 3);
+    assert_eq!(mesh.halfedges.len(), 6);
+    for (he_id, he) in &mesh.halfedges {
+        assert_ne!(mesh.halfedge_target(he_id), Some(he.origin));
+    }
+    assert_eq!(mesh.face_vertices(face).unwrap().count(), 3);
+    mesh.validate().unwrap();
+}
+
+#[test]
+fn test_dissolve_edge_merges_triangles() {
+    let mut mesh = Mesh::new();
+    let v0 = mesh.add_vertex(DVec3::new(0.0, 0.0, 0.0));
+    let v1 = mesh.add_vertex(DVec3::new(1.0, 0.0, 0.0));
+    let v2 = mesh.add_vertex(DVec3::new(1.0, 1.0, 0.0));
+    let v3 = mesh.add_vertex(DVec3::new(0.0, 1.0, 0.0));
+    mesh.make_triangle(v0, v1, v2).unwrap();
+    mesh.make_triangle(v0, v2, v3).unwrap();
+
+    let diagonal = edge_between(&mesh, v0, v2);
+    let face = mesh.dissolve_edge(diagonal).unwrap();
+
+    assert_eq!(mesh.faces.len(), 1);
+    assert_eq!(mesh.edges.len(), 4);
+    assert_eq!(mesh.halfedges.len(), 8);
+    assert_eq!(mesh.loops.len(), 1);
+    assert!(!mesh.edges.contains_key(diagonal));
+
+    let verts: Vec<_> = mesh.face_vertices(face).unwrap().collect();
+    assert_eq!(verts.len(), 4);
+    for v in [v0, v1, v2, v3] {
+        assert!(verts.contains(&v));
+    }
+    mesh.validate().unwrap();
+}
+
+#[test]
+fn test_dissolve_boundary_edge_fails() {
+    let (mut mesh, verts) = grid_mesh();
+    let edge = edge_between(&mesh, verts[0], verts[1]);
+    let before = snapshot(&mesh);
+    assert!(mesh.dissolve_edge(edge).is_err());
+    assert_eq!(snapshot(&mesh), before);
+}
+
+#[test]
+fn test_dissolve_rejects_pinch_unchanged() {
+    // A quad and a triangle sharing the edges a-b and b-c: merging them
+    // would visit b twice.
+    let mut mesh = Mesh::new();
+    let a = mesh.add_vertex(DVec3::new(0.0, 0.0, 0.0));
+    let b = mesh.add_vertex(DVec3::new(1.0, 0.0, 0.0));
+    let c = mesh.add_vertex(DVec3::new(1.0, 1.0, 0.0));
+    let d = mesh.add_vertex(DVec3::new(0.0, 1.0, 0.0));
+    mesh.make_face(&[a, b, c, d]).unwrap();
+    mesh.make_triangle(b, a, c).unwrap();
+
+    let edge = edge_between(&mesh, a, b);
+    let before = snapshot(&mesh);
+    assert!(mesh.dissolve_edge(edge).is_err());
+    assert_eq!(snapshot(&mesh), before);
+    mesh.validate().unwrap();
+}
+
+#[test]
+fn test_dissolve_keeps_inner_loops_of_both_faces() {
+    // Two squares side by side, the right one with a hole.
+    let mut mesh = Mesh::new();
+    let corners = [
+        (0.0, 0.0),
+        (4.0, 0.0),
+        (8.0, 0.0),
+        (8.0, 4.0),
+        (4.0, 4.0),
+        (0.0, 4.0),
+    ];
+    let v: Vec<_> = corners
+        .iter()
+        .map(|&(x, y)| mesh.add_vertex(DVec3::new(x, y, 0.0)))
+        .collect();
+    let hole: Vec<_> = [(5.0, 1.0), (5.0, 3.0), (7.0, 3.0), (7.0, 1.0)]
+        .iter()
+        .map(|&(x, y)| mesh.add_vertex(DVec3::new(x, y, 0.0)))
+        .collect();
+    let left = mesh.make_face(&[v[0], v[1], v[4], v[5]]).unwrap();
+    let right = mesh.make_face(&[v[1], v[2], v[3], v[4]]).unwrap();
+    mesh.add_inner_loop(right, &hole).unwrap();
+
+    let face = mesh.dissolve_edge(edge_between(&mesh, v[1], v[4])).unwrap();
+    let other = if face == left { right } else { left };
+    assert!(!mesh.faces.contains_key(other));
+    assert_eq!(mesh.faces[face].inner_loops.len(), 1);
+    assert_eq!(mesh.face_vertices(face).unwrap().count(), 6);
+    mesh.validate().unwrap();
+}
+
+#[test]
+fn test_split_interior_edge_splits_both_triangles() {
+    let (mut mesh, verts) = grid_mesh();
+    let edge = edge_between(&mesh, verts[4], verts[5]);
+
+    let mid = mesh.split_edge(edge, 0.5).unwrap();
+    assert_eq!(mesh.vertices[mid].position, DVec3::new(1.5, 1.0, 0.0));
+    assert_eq!(mesh.vertices.len(), 10);
+    assert_eq!(mesh.faces.len(), 10);
+    for f in mesh.faces.keys() {
+        assert_eq!(mesh.face_vertices(f).unwrap().count(), 3);
+    }
+    // The mid vertex connects to both edge ends and both apexes.
+    assert_eq!(mesh.vertex_outgoing(mid).unwrap().count(), 4);
+    mesh.validate().unwrap();
+}
+
+#[test]
+fn test_split_boundary_edge_adds_one_triangle() {
+    let (mut mesh, verts) = grid_mesh();
+    let edge = edge_between(&mesh, verts[0], verts[1]);
+    mesh.split_edge(edge, 0.25).unwrap();
+    assert_eq!(mesh.faces.len(), 9);
+    assert_eq!(
+        mesh.vertices.len() as i64 - mesh.edges.len() as i64 + mesh.faces.len() as i64,
+        1
+    );
+    mesh.validate().unwrap();
+}
+
+#[test]
+fn test_split_quad_edge_adds_corner() {
+    let mut mesh = Mesh::new();
+    let v0 = mesh.add_vertex(DVec3::new(0.0, 0.0, 0.0));
+    let v1 = mesh.add_vertex(DVec3::new(1.0, 0.0, 0.0));
+    let v2 = mesh.add_vertex(DVec3::new(1.0, 1.0, 0.0));
+    let v3 = mesh.add_vertex(DVec3::new(0.0, 1.0, 0.0));
+    let face = mesh.make_face(&[v0, v1, v2, v3]).unwrap();
+
+    let edge = edge_between(&mesh, v0, v1);
+    assert!(mesh.split_edge(edge, 1.0).is_err());
+    mesh.split_edge(edge, 0.5).unwrap();
+    assert_eq!(mesh.faces.len(), 1);
+    assert_eq!(mesh.face_vertices(face).unwrap().count(), 5);
+    mesh.validate().unwrap();
+}
+
+#[test]
+fn test_flip_edge_swaps_diagonal() {
+    let mut mesh = Mesh::new();
+    let v0 = mesh.add_vertex(DVec3::new(0.0, 0.0, 0.0));
+    let v1 = mesh.add_vertex(DVec3::new(1.0, 0.0, 0.0));
+    let v2 = mesh.add_vertex(DVec3::new(1.0, 1.0, 0.0));
+    let v3 = mesh.add_vertex(DVec3::new(0.0, 1.0, 0.0));
+    let f0 = mesh.make_triangle(v0, v1, v2).unwrap();
+    let f1 = mesh.make_triangle(v0, v2, v3).unwrap();
+
+    let diagonal = edge_between(&mesh, v0, v2);
+    let flipped = mesh.flip_edge(diagonal).unwrap();
+    assert_eq!(edge_between(&mesh, v1, v3), flipped);
+    assert!(!mesh.edges.contains_key(diagonal));
+    assert_eq!(mesh.edges.len(), 5);
+    // Faces keep their ids and stay counter-clockwise.
+    for f in [f0, f1] {
+        let p: Vec<_> = mesh
+            .face_vertices(f)
+            .unwrap()
+            .map(|v| mesh.vertices[v].position)
+            .collect();
+        assert!((p[1] - p[0]).cross(p[2] - p[0]).z > 0.0);
+    }
+    mesh.validate().unwrap();
+
+    assert!(mesh.flip_edge(edge_between(&mesh, v0, v1)).is_err());
+}
+
+#[test]
+fn test_make_delaunay_fixes_skinny_pair() {
+    // Long diagonal a-b with the apexes c, d close to it.
+    let mut mesh = Mesh::new();
+    let a = mesh.add_vertex(DVec3::new(0.0, 0.0, 0.0));
+    let b = mesh.add_vertex(DVec3::new(4.0, 0.0, 0.0));
+    let c = mesh.add_vertex(DVec3::new(2.0, 0.5, 0.0));
+    let d = mesh.add_vertex(DVec3::new(2.0, -0.5, 0.0));
+    mesh.make_triangle(a, b, c).unwrap();
+    mesh.make_triangle(b, a, d).unwrap();
+
+    assert_eq!(mesh.make_delaunay(30f64.to_radians()), 1);
+    edge_between(&mesh, c, d);
+    assert_eq!(mesh.make_delaunay(30f64.to_radians()), 0);
+    mesh.validate().unwrap();
+}
+
+#[test]
+fn test_make_delaunay_keeps_feature_edges() {
+    // Same skinny pair, but folded by 90 degrees along a-b.
+    let mut mesh = Mesh::new();
+    let a = mesh.add_vertex(DVec3::new(0.0, 0.0, 0.0));
+    let b = mesh.add_vertex(DVec3::new(4.0, 0.0, 0.0));
+    let c = mesh.add_vertex(DVec3::new(2.0, 0.5, 0.0));
+    let d = mesh.add_vertex(DVec3::new(2.0, 0.0, -0.5));
+    mesh.make_triangle(a, b, c).unwrap();
+    mesh.make_triangle(b, a, d).unwrap();
+
+    assert_eq!(mesh.make_delaunay(30f64.to_radians()), 0);
+    edge_between(&mesh, a, b);
+}
@@ -16,6 +16,7 @@ fn make_triangle_mesh() -> (Mesh, VertexId, VertexId, VertexId) {
 }
 
 #[test]
+#[allow(unused_variables)]
 fn test_single_triangle_creation() {
     let (mut mesh, v0, v1, v2) = make_triangle_mesh();
     let face_id = mesh.make_triangle(v0, v1, v2).unwrap();
@@ -80,6 +81,7 @@ fn test_quad_face_creation() {
 }
 
 #[test]
+#[allow(clippy::useless_vec)]
 fn test_two_adjacent_triangles_shared_edge() {
     let mut mesh = Mesh::new();
     let v0 = mesh.add_vertex(dvec3(0.0, 0.0, 0.0));
@@ -119,6 +121,7 @@ fn test_two_adjacent_triangles_shared_edge() {
 }
 
 #[test]
+#[allow(clippy::len_zero)]
 fn test_vertex_outgoing_iteration() {
     let (mut mesh, v0, v1, v2) = make_triangle_mesh();
     let _face_id = mesh.make_triangle(v0, v1, v2).unwrap();
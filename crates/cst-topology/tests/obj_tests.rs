@@ -0,0 +1,92 @@
+use cst_core::traits::Validate;
+use cst_math::DVec3;
+use cst_topology::{read_obj, write_obj, Mesh};
+
+mod common;
+use common::box_solid;
+
+fn round_trip(mesh: &Mesh) -> Mesh {
+    let mut buffer = Vec::new();
+    write_obj(mesh, &mut buffer).unwrap();
+    read_obj(buffer.as_slice()).unwrap()
+}
+
+#[test]
+fn test_box_round_trip_keeps_quads_and_sharing() {
+    let mesh = box_solid(DVec3::ZERO, DVec3::new(1.5, 2.0, 0.1));
+    let loaded = round_trip(&mesh);
+
+    assert_eq!(loaded.vertices.len(), 8);
+    assert_eq!(loaded.edges.len(), 12);
+    assert_eq!(loaded.faces.len(), 6);
+    for f in loaded.faces.keys() {
+        assert_eq!(loaded.face_vertices(f).unwrap().count(), 4);
+    }
+    assert!(loaded.is_closed());
+    loaded.validate().unwrap();
+
+    // Positions survive exactly.
+    let mut before: Vec<_> = mesh
+        .vertices
+        .values()
+        .map(|v| v.position.to_array())
+        .collect();
+    let mut after: Vec<_> = loaded
+        .vertices
+        .values()
+        .map(|v| v.position.to_array())
+        .collect();
+    before.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    after.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(before, after);
+}
+
+#[test]
+fn test_read_obj_index_forms() {
+    let text = "\
+# square made of two triangles
+o square
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vn 0 0 1
+f 1/1/1 2/1/1 3/1/1
+f -4//1 -2//1 -1//1
+";
+    let mesh = read_obj(text.as_bytes()).unwrap();
+    assert_eq!(mesh.faces.len(), 2);
+    assert_eq!(mesh.edges.len(), 5);
+    mesh.validate().unwrap();
+}
+
+#[test]
+fn test_read_obj_reports_bad_lines() {
+    let err = read_obj("v 0 0 0\nv 1 0 0\nf 1 2 3\n".as_bytes()).unwrap_err();
+    assert!(err.to_string().contains("line 3"), "{err}");
+
+    let err = read_obj("v 0 zero 0\n".as_bytes()).unwrap_err();
+    assert!(err.to_string().contains("line 1"), "{err}");
+
+    // The same directed edge used twice is non-manifold.
+    let text = "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 0 0 1\nf 1 2 3\nf 1 2 4\n";
+    assert!(read_obj(text.as_bytes()).is_err());
+}
+
+#[test]
+fn test_write_obj_rejects_holes() {
+    let mut mesh = Mesh::new();
+    let outer: Vec<_> = [(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)]
+        .iter()
+        .map(|&(x, y)| mesh.add_vertex(DVec3::new(x, y, 0.0)))
+        .collect();
+    let inner: Vec<_> = [(1.0, 1.0), (1.0, 3.0), (3.0, 3.0), (3.0, 1.0)]
+        .iter()
+        .map(|&(x, y)| mesh.add_vertex(DVec3::new(x, y, 0.0)))
+        .collect();
+    let face = mesh.make_face(&outer).unwrap();
+    mesh.add_inner_loop(face, &inner).unwrap();
+
+    assert!(write_obj(&mesh, &mut Vec::new()).is_err());
+}
@@ -0,0 +1,116 @@
+use cst_core::traits::Validate;
+use cst_math::DVec3;
+use cst_topology::{CurveRef, Mesh, PortableMesh, SurfaceRef, PORTABLE_MESH_VERSION};
+
+mod common;
+use common::add_box;
+
+/// Box solid with a void, a surface reference, an edge curve and a wire edge.
+fn sample_mesh() -> Mesh {
+    let mut mesh = Mesh::new();
+    let outer = add_box(&mut mesh, DVec3::ZERO, DVec3::splat(4.0));
+    mesh.set_face_surface(outer[0], Some(SurfaceRef(3)))
+        .unwrap();
+    let outer_shell = mesh.add_shell(outer).unwrap();
+
+    let inner = add_box(&mut mesh, DVec3::ONE, DVec3::splat(2.0));
+    let void = mesh.add_shell(inner).unwrap();
+    mesh.flip_shell(void).unwrap();
+    mesh.add_solid(outer_shell, vec![void]).unwrap();
+
+    let edge = mesh.edges.keys().next().unwrap();
+    mesh.set_edge_curve(edge, Some(CurveRef(7))).unwrap();
+
+    let p = mesh.add_vertex(DVec3::new(10.0, 0.0, 0.0));
+    let q = mesh.add_vertex(DVec3::new(11.0, 0.0, 0.0));
+    mesh.make_edge(p, q).unwrap();
+    mesh
+}
+
+/// Portable form with edges sorted, since rebuilt meshes may allocate edges
+/// in a different order.
+fn canonical(mesh: &Mesh) -> PortableMesh {
+    let mut portable = mesh.to_portable();
+    portable.edges.sort_by_key(|e| e.vertices);
+    portable
+}
+
+#[test]
+fn test_portable_round_trip() {
+    let mesh = sample_mesh();
+    let portable = mesh.to_portable();
+    assert_eq!(portable.version, PORTABLE_MESH_VERSION);
+    assert_eq!(portable.vertices.len(), 18);
+    assert_eq!(portable.faces.len(), 12);
+    assert_eq!(portable.edges.len(), 25);
+
+    let restored = Mesh::from_portable(&portable).unwrap();
+    restored.validate().unwrap();
+    assert_eq!(restored.vertices.len(), mesh.vertices.len());
+    assert_eq!(restored.edges.len(), mesh.edges.len());
+    assert_eq!(restored.faces.len(), mesh.faces.len());
+    assert_eq!(restored.shells.len(), 2);
+    assert_eq!(
+        restored
+            .faces
+            .values()
+            .filter(|f| f.surface.is_some())
+            .count(),
+        1
+    );
+    assert_eq!(
+        restored
+            .faces
+            .values()
+            .filter(|f| f.surface_reversed)
+            .count(),
+        6
+    );
+    assert_eq!(
+        restored
+            .edges
+            .values()
+            .filter(|e| e.curve.is_some())
+            .count(),
+        1
+    );
+
+    let solid = restored.solids.keys().next().unwrap();
+    assert!((restored.solid_volume(solid).unwrap() - 63.0).abs() < 1e-9);
+
+    // Converting again yields the same data, including edge orientation.
+    assert_eq!(canonical(&restored), canonical(&mesh));
+}
+
+#[test]
+fn test_serde_goes_through_portable_form() {
+    let mesh = sample_mesh();
+    let json = serde_json::to_string(&mesh).unwrap();
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["version"], PORTABLE_MESH_VERSION);
+    assert!(value["vertices"][0].is_array());
+
+    let restored: Mesh = serde_json::from_str(&json).unwrap();
+    assert_eq!(canonical(&restored), canonical(&mesh));
+}
+
+#[test]
+fn test_from_portable_rejects_bad_input() {
+    let mut portable = sample_mesh().to_portable();
+    portable.version = PORTABLE_MESH_VERSION + 1;
+    assert!(Mesh::from_portable(&portable).is_err());
+
+    let mut portable = sample_mesh().to_portable();
+    portable.faces[0].outer[0] = 999;
+    assert!(Mesh::from_portable(&portable).is_err());
+
+    let mut portable = sample_mesh().to_portable();
+    portable.solids[0].outer_shell = 5;
+    assert!(Mesh::try_from(portable).is_err());
+
+    let json = r#"{"version":1,"vertices":[[0,0,0],[1,0,0],[0,1,0]],"faces":[{"outer":[0,1,2]}],"edges":[]}"#;
+    let portable: PortableMesh = serde_json::from_str(json).unwrap();
+    let mesh = Mesh::from_portable(&portable).unwrap();
+    assert_eq!(mesh.faces.len(), 1);
+    assert!(mesh.shells.is_empty());
+}
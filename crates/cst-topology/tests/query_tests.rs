@@ -0,0 +1,107 @@
+use cst_math::DVec3;
+use cst_topology::Mesh;
+
+mod common;
+use common::{add_box, box_solid};
+
+/// Quad torus with `n` x `m` faces, wrapping in both directions.
+fn torus(n: usize, m: usize) -> Mesh {
+    let mut mesh = Mesh::new();
+    let mut verts = Vec::new();
+    for i in 0..n {
+        let u = i as f64 / n as f64 * std::f64::consts::TAU;
+        for j in 0..m {
+            let v = j as f64 / m as f64 * std::f64::consts::TAU;
+            let r = 2.0 + v.cos();
+            verts.push(mesh.add_vertex(DVec3::new(r * u.cos(), r * u.sin(), v.sin())));
+        }
+    }
+    let at = |i: usize, j: usize| verts[(i % n) * m + (j % m)];
+    for i in 0..n {
+        for j in 0..m {
+            mesh.make_face(&[at(i, j), at(i + 1, j), at(i + 1, j + 1), at(i, j + 1)])
+                .unwrap();
+        }
+    }
+    mesh
+}
+
+#[test]
+fn test_closed_box_queries() {
+    let mesh = box_solid(DVec3::ZERO, DVec3::ONE);
+    assert!(mesh.is_closed());
+    assert!(mesh.boundary_loops().is_empty());
+    assert_eq!(mesh.connected_components().len(), 1);
+    assert_eq!(mesh.euler_characteristic(), 2);
+    assert_eq!(mesh.genus(), 0);
+}
+
+#[test]
+fn test_open_box_has_one_boundary_loop() {
+    let mut mesh = Mesh::new();
+    let faces = add_box(&mut mesh, DVec3::ZERO, DVec3::ONE);
+    mesh.delete_face(faces[1]).unwrap();
+
+    assert!(!mesh.is_closed());
+    let loops = mesh.boundary_loops();
+    assert_eq!(loops.len(), 1);
+    assert_eq!(loops[0].len(), 4);
+    for v in &loops[0] {
+        assert_eq!(mesh.vertices[*v].position.z, 1.0);
+    }
+    assert_eq!(mesh.euler_characteristic(), 1);
+    assert_eq!(mesh.genus(), 0);
+}
+
+#[test]
+fn test_separate_boxes_are_two_components() {
+    let mut mesh = Mesh::new();
+    let a = add_box(&mut mesh, DVec3::ZERO, DVec3::ONE);
+    let b = add_box(&mut mesh, DVec3::splat(3.0), DVec3::splat(4.0));
+
+    let components = mesh.connected_components();
+    assert_eq!(components.len(), 2);
+    for component in &components {
+        assert_eq!(component.len(), 6);
+        assert!(component.iter().all(|f| a.contains(f)) || component.iter().all(|f| b.contains(f)));
+    }
+    assert_eq!(mesh.euler_characteristic(), 4);
+    assert_eq!(mesh.genus(), 0);
+}
+
+#[test]
+fn test_torus_has_genus_one() {
+    let mesh = torus(6, 4);
+    assert!(mesh.is_closed());
+    assert_eq!(mesh.euler_characteristic(), 0);
+    assert_eq!(mesh.genus(), 1);
+}
+
+#[test]
+fn test_face_hole_counts_in_euler_characteristic() {
+    // A square face with a square hole is an annulus: χ = 0, two boundaries.
+    let mut mesh = Mesh::new();
+    let outer: Vec<_> = [(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)]
+        .iter()
+        .map(|&(x, y)| mesh.add_vertex(DVec3::new(x, y, 0.0)))
+        .collect();
+    let inner: Vec<_> = [(1.0, 1.0), (1.0, 3.0), (3.0, 3.0), (3.0, 1.0)]
+        .iter()
+        .map(|&(x, y)| mesh.add_vertex(DVec3::new(x, y, 0.0)))
+        .collect();
+    let face = mesh.make_face(&outer).unwrap();
+    mesh.add_inner_loop(face, &inner).unwrap();
+
+    assert_eq!(mesh.euler_characteristic(), 0);
+    assert_eq!(mesh.boundary_loops().len(), 2);
+    assert_eq!(mesh.connected_components().len(), 1);
+    assert_eq!(mesh.genus(), 0);
+}
+
+#[test]
+fn test_empty_mesh_is_not_closed() {
+    let mesh = Mesh::new();
+    assert!(!mesh.is_closed());
+    assert!(mesh.connected_components().is_empty());
+    assert_eq!(mesh.euler_characteristic(), 0);
+}
@@ -0,0 +1,105 @@
+use cst_core::traits::Validate;
+use cst_math::DVec3;
+use cst_topology::{FaceId, Mesh};
+
+/// Add a polygon with its own, unshared vertices.
+fn add_loose_face(mesh: &mut Mesh, points: &[DVec3]) -> FaceId {
+    let verts: Vec<_> = points.iter().map(|&p| mesh.add_vertex(p)).collect();
+    mesh.make_face(&verts).unwrap()
+}
+
+/// Unit box as six disconnected outward quads, with every corner copy
+/// jittered by up to `noise`.
+fn loose_box(noise: f64) -> Mesh {
+    let corner =
+        |i: usize| DVec3::new((i & 1) as f64, ((i >> 1) & 1) as f64, ((i >> 2) & 1) as f64);
+    let quads = [
+        [0, 2, 3, 1],
+        [4, 5, 7, 6],
+        [0, 1, 5, 4],
+        [2, 6, 7, 3],
+        [0, 4, 6, 2],
+        [1, 3, 7, 5],
+    ];
+    let mut mesh = Mesh::new();
+    for (f, quad) in quads.iter().enumerate() {
+        let points: Vec<_> = quad
+            .iter()
+            .enumerate()
+            .map(|(k, &i)| corner(i) + DVec3::splat(noise * ((f + k) % 3) as f64 / 2.0))
+            .collect();
+        add_loose_face(&mut mesh, &points);
+    }
+    mesh
+}
+
+#[test]
+fn test_sew_loose_box_into_closed_mesh() {
+    let mut mesh = loose_box(1e-9);
+    assert_eq!(mesh.vertices.len(), 24);
+    assert_eq!(mesh.edges.len(), 24);
+
+    let sewn = mesh.sew(1e-6).unwrap();
+    assert_eq!(sewn, 12);
+    assert_eq!(mesh.vertices.len(), 8);
+    assert_eq!(mesh.edges.len(), 12);
+    assert_eq!(mesh.halfedges.len(), 24);
+    assert!(mesh.is_closed());
+    assert_eq!(mesh.euler_characteristic(), 2);
+    mesh.validate().unwrap();
+}
+
+#[test]
+fn test_sew_is_idempotent() {
+    let mut mesh = loose_box(0.0);
+    assert_eq!(mesh.sew(1e-6).unwrap(), 12);
+    assert_eq!(mesh.sew(1e-6).unwrap(), 0);
+    assert_eq!(mesh.vertices.len(), 8);
+}
+
+#[test]
+fn test_gap_wider_than_tolerance_stays_open() {
+    let mut mesh = loose_box(1e-3);
+    assert_eq!(mesh.sew(1e-6).unwrap(), 0);
+    assert!(!mesh.is_closed());
+    mesh.validate().unwrap();
+}
+
+#[test]
+fn test_short_edges_within_a_face_are_kept() {
+    // Two vertices of the same triangle closer than the tolerance must not
+    // be merged, or the face would degenerate.
+    let mut mesh = Mesh::new();
+    add_loose_face(
+        &mut mesh,
+        &[
+            DVec3::new(0.0, 0.0, 0.0),
+            DVec3::new(1e-8, 0.0, 0.0),
+            DVec3::new(0.0, 1.0, 0.0),
+        ],
+    );
+    assert_eq!(mesh.sew(1e-6).unwrap(), 0);
+    assert_eq!(mesh.vertices.len(), 3);
+    mesh.validate().unwrap();
+}
+
+#[test]
+fn test_inconsistent_orientation_is_not_sewn() {
+    let mut mesh = Mesh::new();
+    let a = DVec3::new(0.0, 0.0, 0.0);
+    let b = DVec3::new(1.0, 0.0, 0.0);
+    add_loose_face(&mut mesh, &[a, b, DVec3::new(0.5, 1.0, 0.0)]);
+    // Same winding along a -> b on the other side: the faces disagree.
+    add_loose_face(&mut mesh, &[a, b, DVec3::new(0.5, -1.0, 0.0)]);
+
+    assert_eq!(mesh.sew(1e-6).unwrap(), 0);
+    mesh.validate().unwrap();
+}
+
+#[test]
+fn test_non_positive_tolerance_fails() {
+    let mut mesh = loose_box(0.0);
+    assert!(mesh.sew(0.0).is_err());
+    assert!(mesh.sew(-1.0).is_err());
+    assert!(mesh.sew(f64::NAN).is_err());
+}
@@ -0,0 +1,96 @@
+use cst_core::traits::Validate;
+use cst_math::DVec3;
+use cst_topology::Mesh;
+
+mod common;
+use common::add_box;
+
+#[test]
+fn test_closed_box_shell() {
+    let mut mesh = Mesh::new();
+    let faces = add_box(&mut mesh, DVec3::ZERO, DVec3::new(2.0, 3.0, 4.0));
+    let shell = mesh.add_shell(faces).unwrap();
+
+    assert!(mesh.shell_is_closed(shell).unwrap());
+    assert!(mesh.shell_is_outward(shell).unwrap());
+    assert!((mesh.shell_volume(shell).unwrap() - 24.0).abs() < 1e-12);
+}
+
+#[test]
+fn test_open_shell_is_not_closed() {
+    let mut mesh = Mesh::new();
+    let mut faces = add_box(&mut mesh, DVec3::ZERO, DVec3::ONE);
+    let lid = faces.pop().unwrap();
+    mesh.delete_face(lid).unwrap();
+
+    let shell = mesh.add_shell(faces).unwrap();
+    assert!(!mesh.shell_is_closed(shell).unwrap());
+    assert!(!mesh.shell_is_outward(shell).unwrap());
+    assert!(mesh.add_solid(shell, vec![]).is_err());
+}
+
+#[test]
+fn test_disconnected_faces_rejected() {
+    let mut mesh = Mesh::new();
+    let mut a = add_box(&mut mesh, DVec3::ZERO, DVec3::ONE);
+    let b = add_box(&mut mesh, DVec3::splat(5.0), DVec3::splat(6.0));
+    a.extend(b);
+    assert!(mesh.add_shell(a).is_err());
+}
+
+#[test]
+fn test_flip_shell_reverses_orientation() {
+    let mut mesh = Mesh::new();
+    let faces = add_box(&mut mesh, DVec3::ZERO, DVec3::ONE);
+    let shell = mesh.add_shell(faces.clone()).unwrap();
+
+    mesh.flip_shell(shell).unwrap();
+    mesh.validate().unwrap();
+    assert!((mesh.shell_volume(shell).unwrap() + 1.0).abs() < 1e-12);
+    assert!(mesh.shell_is_closed(shell).unwrap());
+    for f in faces {
+        assert!(mesh.faces[f].surface_reversed);
+    }
+    // An inward shell cannot be the outer shell of a solid.
+    assert!(mesh.add_solid(shell, vec![]).is_err());
+}
+
+#[test]
+fn test_solid_with_void() {
+    let mut mesh = Mesh::new();
+    let outer_faces = add_box(&mut mesh, DVec3::ZERO, DVec3::splat(4.0));
+    let void_faces = add_box(&mut mesh, DVec3::ONE, DVec3::splat(2.0));
+    let outer = mesh.add_shell(outer_faces).unwrap();
+    let void = mesh.add_shell(void_faces).unwrap();
+
+    // Voids must face into the void first.
+    assert!(mesh.add_solid(outer, vec![void]).is_err());
+    mesh.flip_shell(void).unwrap();
+    let solid = mesh.add_solid(outer, vec![void]).unwrap();
+
+    assert!((mesh.solid_volume(solid).unwrap() - 63.0).abs() < 1e-9);
+    assert!(mesh.solid_contains_point(solid, DVec3::splat(3.0)).unwrap());
+    assert!(!mesh.solid_contains_point(solid, DVec3::splat(1.5)).unwrap());
+    assert!(!mesh.solid_contains_point(solid, DVec3::splat(5.0)).unwrap());
+}
+
+#[test]
+fn test_void_outside_outer_shell_rejected() {
+    let mut mesh = Mesh::new();
+    let outer_faces = add_box(&mut mesh, DVec3::ZERO, DVec3::ONE);
+    let void_faces = add_box(&mut mesh, DVec3::splat(3.0), DVec3::splat(4.0));
+    let outer = mesh.add_shell(outer_faces).unwrap();
+    let void = mesh.add_shell(void_faces).unwrap();
+    mesh.flip_shell(void).unwrap();
+    assert!(mesh.add_solid(outer, vec![void]).is_err());
+}
+
+#[test]
+fn test_deleted_faces_leave_shells() {
+    let mut mesh = Mesh::new();
+    let faces = add_box(&mut mesh, DVec3::ZERO, DVec3::ONE);
+    let shell = mesh.add_shell(faces.clone()).unwrap();
+    mesh.delete_face(faces[0]).unwrap();
+    assert_eq!(mesh.shells[shell].faces.len(), 5);
+    assert!(!mesh.shells[shell].faces.contains(&faces[0]));
+}
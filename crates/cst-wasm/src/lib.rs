@@ -0,0 +1,225 @@
+//! WebAssembly bindings for parsing small IFC files in the browser.
+//!
+//! Build with `wasm-pack build crates/cst-wasm --target web`, then:
+//!
+//! ```js
+//! import init, { IfcModel } from './pkg/cst_wasm.js';
+//!
+//! await init();
+//! const bytes = new Uint8Array(await file.arrayBuffer());
+//! const model = new IfcModel(bytes, 0.001); // millimetres to metres
+//! for (let i = 0; i < model.meshCount; i++) {
+//!     const positions = model.positions(i); // Float32Array, xyz per vertex
+//!     const indices = model.indices(i);     // Uint32Array
+//!     const props = JSON.parse(model.properties(model.globalId(i)) ?? '{}');
+//! }
+//! model.free();
+//! ```
+//!
+//! The whole file is parsed in memory on the calling thread, so this suits
+//! models of a few tens of megabytes; larger ones belong on a server.
+
+use std::collections::{BTreeMap, HashMap};
+
+use cst_core::ProductId;
+use cst_ifc::ifc_cache::{CachedMesh, CachedModel, CachedProduct};
+use cst_ifc::ifc_options::IfcPipelineOptions;
+use cst_ifc::ifc_progress::NoProgress;
+use wasm_bindgen::prelude::*;
+
+/// A parsed and tessellated IFC model.
+#[wasm_bindgen]
+pub struct IfcModel {
+    model: CachedModel,
+    /// GlobalId -> product id
+    by_guid: HashMap<String, ProductId>,
+}
+
+#[wasm_bindgen]
+impl IfcModel {
+    /// Parse IFC file contents. `unit_scale` multiplies every coordinate;
+    /// faces smaller than a `tolerance`-sized square are dropped.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        bytes: &[u8],
+        unit_scale: Option<f64>,
+        tolerance: Option<f64>,
+    ) -> Result<IfcModel, String> {
+        let options = IfcPipelineOptions {
+            unit_scale,
+            tessellation_tolerance: tolerance.unwrap_or(0.0),
+            ..Default::default()
+        };
+        let model = CachedModel::from_reader(bytes, &options, &NoProgress)
+            .map_err(|e| format!("failed to parse IFC: {}", e))?;
+        let by_guid = model
+            .products
+            .iter()
+            .filter_map(|(&id, product)| Some((product.global_id.clone()?, id)))
+            .collect();
+        Ok(Self { model, by_guid })
+    }
+
+    #[wasm_bindgen(getter, js_name = meshCount)]
+    pub fn mesh_count(&self) -> usize {
+        self.model.meshes.len()
+    }
+
+    #[wasm_bindgen(js_name = meshName)]
+    pub fn mesh_name(&self, index: usize) -> Option<String> {
+        Some(self.mesh(index)?.mesh.name.clone())
+    }
+
+    /// Vertex positions of mesh `index`, xyz per vertex; empty when out of
+    /// range.
+    pub fn positions(&self, index: usize) -> Vec<f32> {
+        self.mesh(index)
+            .map_or_else(Vec::new, |m| flatten(&m.mesh.positions))
+    }
+
+    /// Vertex normals of mesh `index`, xyz per vertex.
+    pub fn normals(&self, index: usize) -> Vec<f32> {
+        self.mesh(index)
+            .map_or_else(Vec::new, |m| flatten(&m.mesh.normals))
+    }
+
+    /// Triangle indices of mesh `index`, three per triangle.
+    pub fn indices(&self, index: usize) -> Vec<u32> {
+        self.mesh(index)
+            .map_or_else(Vec::new, |m| m.mesh.indices.clone())
+    }
+
+    /// Linear RGB of mesh `index` from the IFC style, if it has one.
+    pub fn color(&self, index: usize) -> Option<Vec<f32>> {
+        self.mesh(index)?.color.map(|c| c.to_vec())
+    }
+
+    /// IFC GlobalId of the product mesh `index` belongs to.
+    #[wasm_bindgen(js_name = globalId)]
+    pub fn global_id(&self, index: usize) -> Option<String> {
+        self.product_of(index)?.global_id.clone()
+    }
+
+    /// Upper-case IFC type of the product mesh `index` belongs to.
+    #[wasm_bindgen(js_name = ifcType)]
+    pub fn ifc_type(&self, index: usize) -> Option<String> {
+        Some(self.product_of(index)?.ifc_type.clone())
+    }
+
+    /// Name, type, storey and properties of the product with `global_id`
+    /// as a JSON object.
+    pub fn properties(&self, global_id: &str) -> Option<String> {
+        let product = self.model.products.get(self.by_guid.get(global_id)?)?;
+        let properties: BTreeMap<&str, &str> = product
+            .properties
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        let json = serde_json::json!({
+            "global_id": global_id,
+            "ifc_type": product.ifc_type,
+            "name": product.name,
+            "storey": product.storey,
+            "properties": properties,
+        });
+        Some(json.to_string())
+    }
+
+    /// Storey names mapped to the GlobalIds they contain, as JSON.
+    pub fn storeys(&self) -> String {
+        let storeys: BTreeMap<&str, Vec<&str>> = self
+            .model
+            .storeys
+            .iter()
+            .map(|(name, ids)| {
+                let guids = ids
+                    .iter()
+                    .filter_map(|id| self.model.products.get(id)?.global_id.as_deref())
+                    .collect();
+                (name.as_str(), guids)
+            })
+            .collect();
+        serde_json::to_string(&storeys).unwrap_or_default()
+    }
+}
+
+impl IfcModel {
+    fn mesh(&self, index: usize) -> Option<&CachedMesh> {
+        self.model.meshes.get(index)
+    }
+
+    fn product_of(&self, index: usize) -> Option<&CachedProduct> {
+        self.model.products.get(&self.mesh(index)?.product?)
+    }
+}
+
+fn flatten(points: &[cst_math::DVec3]) -> Vec<f32> {
+    points
+        .iter()
+        .flat_map(|p| [p.x as f32, p.y as f32, p.z as f32])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODEL: &str = "ISO-10303-21;
+HEADER;
+FILE_SCHEMA(('IFC2X3'));
+ENDSEC;
+DATA;
+#1= IFCCARTESIANPOINT((0.,0.,0.));
+#2= IFCCARTESIANPOINT((1000.,0.,0.));
+#3= IFCCARTESIANPOINT((1000.,1000.,0.));
+#4= IFCCARTESIANPOINT((0.,1000.,0.));
+#5= IFCPOLYLOOP((#1,#2,#3,#4));
+#6= IFCFACEOUTERBOUND(#5,.T.);
+#7= IFCFACE((#6));
+#8= IFCCLOSEDSHELL((#7));
+#9= IFCFACETEDBREP(#8);
+#10= IFCSHAPEREPRESENTATION($,'Body','Brep',(#9));
+#11= IFCPRODUCTDEFINITIONSHAPE($,$,(#10));
+#20= IFCWALL('2O2Fr$t4X7Zf8NOew3FLOH',$,'Wall A',$,$,$,#11,$);
+#30= IFCBUILDINGSTOREY('st1',$,'Level 1',$,$,$,$,$,.ELEMENT.,0.);
+#31= IFCRELCONTAINEDINSPATIALSTRUCTURE('r1',$,$,$,(#20),#30);
+#40= IFCPROPERTYSINGLEVALUE('FireRating',$,IFCLABEL('REI120'),$);
+#41= IFCPROPERTYSET('p1',$,'Pset_WallCommon',$,(#40));
+#42= IFCRELDEFINESBYPROPERTIES('r2',$,$,$,(#20),#41);
+ENDSEC;
+END-ISO-10303-21;
+";
+
+    #[test]
+    fn test_mesh_buffers() {
+        let model = IfcModel::new(MODEL.as_bytes(), Some(0.001), None).unwrap();
+        assert_eq!(model.mesh_count(), 1);
+        let positions = model.positions(0);
+        assert_eq!(positions.len(), 12);
+        assert!(positions
+            .iter()
+            .all(|&c| c == 0.0 || (c - 1.0).abs() < 1e-6));
+        assert_eq!(model.normals(0).len(), 12);
+        assert_eq!(model.indices(0).len(), 6);
+        assert_eq!(model.ifc_type(0).as_deref(), Some("IFCWALL"));
+        assert!(model.positions(1).is_empty());
+        assert_eq!(model.mesh_name(1), None);
+    }
+
+    #[test]
+    fn test_properties_and_storeys() {
+        let model = IfcModel::new(MODEL.as_bytes(), None, None).unwrap();
+        let guid = model.global_id(0).unwrap();
+        assert_eq!(guid, "2O2Fr$t4X7Zf8NOew3FLOH");
+
+        let json: serde_json::Value =
+            serde_json::from_str(&model.properties(&guid).unwrap()).unwrap();
+        assert_eq!(json["name"], "Wall A");
+        assert_eq!(json["storey"], "Level 1");
+        assert_eq!(json["properties"]["FireRating"], "REI120");
+        assert_eq!(model.properties("unknown"), None);
+
+        let storeys: serde_json::Value = serde_json::from_str(&model.storeys()).unwrap();
+        assert_eq!(storeys["Level 1"][0], "2O2Fr$t4X7Zf8NOew3FLOH");
+    }
+}